@@ -0,0 +1,1232 @@
+use super::grace::Date;
+use super::member_name;
+use super::preset::Preset;
+use super::schema::SCHEMA_VERSION;
+use super::scout::ScoutConfig;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Range;
+use toml_edit::{ImDocument, Item, TableLike};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a `.scout.toml` file.
+///
+/// Lines and columns start at 1.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, severity, self.message
+        )
+    }
+}
+
+/// Validates the contents of a `.scout.toml` file,
+/// and reports every problem instead of stopping at the first one.
+///
+/// `members` are the workspace members, overrides of any other member are reported.
+#[must_use]
+pub fn check(source: &str, members: &[String]) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        source,
+        diagnostics: Vec::new(),
+    };
+    match ImDocument::parse(source) {
+        Ok(document) => checker.check_document(document.as_table(), members),
+        Err(error) => checker.error(error.span(), error.message().to_string()),
+    }
+    if !checker.has_errors() {
+        // Anything the checks above missed is still caught here
+        if let Err(error) = toml::from_str::<ScoutConfig>(source) {
+            checker.error(error.span(), error.message().to_string());
+        }
+    }
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+
+    fn push(&mut self, severity: Severity, span: Option<Range<usize>>, message: String) {
+        let offset = span.map_or(0, |span| span.start).min(self.source.len());
+        let before = self.source.get(..offset).unwrap_or_default();
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        self.diagnostics.push(Diagnostic {
+            severity,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message,
+        });
+    }
+
+    fn error(&mut self, span: Option<Range<usize>>, message: String) {
+        self.push(Severity::Error, span, message);
+    }
+
+    fn warning(&mut self, span: Option<Range<usize>>, message: String) {
+        self.push(Severity::Warning, span, message);
+    }
+
+    fn check_document(&mut self, document: &dyn TableLike, members: &[String]) {
+        for (key, item) in document.iter() {
+            match key {
+                "members" => self.check_members(item, members),
+                "generated" => self.check_generated(item),
+                "build-outputs" => self.check_build_outputs(item),
+                "padding" => self.check_padding(item),
+                "grace" => self.check_grace(item),
+                "ranking" => self.check_ranking(item),
+                "path-map" => self.check_path_map(item),
+                "dirty" => self.check_dirty(item),
+                "relevance" => self.check_relevance(item),
+                "invalidators" => self.check_invalidators(item),
+                "feature-matrix" => self.check_feature_matrix(item),
+                "dependents" => self.check_dependents(item),
+                "test-code" => self.check_test_code(item),
+                "targets" => self.check_targets(item),
+                "workspaces" => self.check_workspaces(item),
+                "markdown" => self.check_markdown(item),
+                "webhook" => self.check_webhook(item),
+                "issues" => self.check_issues(item),
+                "presets" => self.check_presets(item),
+                "owners" => self.check_owners(item, document),
+                "include" => self.check_include(item),
+                "schema-version" => self.check_schema_version(item),
+                _ => self.unknown_key(document, key, ""),
+            }
+        }
+    }
+
+    fn check_members(&mut self, item: &Item, members: &[String]) {
+        let Some(overrides) = self.table(item, "members") else {
+            return;
+        };
+        for (name, item) in overrides.iter() {
+            let path = format!("members.{}", name);
+            if !members.iter().any(|m| member_name(m) == Some(name)) {
+                let span = overrides.key(name).and_then(toml_edit::Key::span);
+                self.warning(span, format!("`{}` is not a workspace member", name));
+            }
+            if let Some(member) = self.table(item, &path) {
+                self.check_member(member, &path);
+            }
+        }
+    }
+
+    fn check_generated(&mut self, item: &Item) {
+        let Some(generated) = self.table(item, "generated") else {
+            return;
+        };
+        for (key, item) in generated.iter() {
+            let valid = match key {
+                "enabled" => item.as_bool().is_some(),
+                "header-lines" => item.as_integer().is_some_and(|lines| lines >= 0),
+                "markers" => item
+                    .as_array()
+                    .is_some_and(|markers| markers.iter().all(toml_edit::Value::is_str)),
+                _ => {
+                    self.unknown_key(generated, key, "generated");
+                    continue;
+                }
+            };
+            if !valid {
+                let expected = match key {
+                    "enabled" => "a boolean",
+                    "header-lines" => "a positive integer",
+                    _ => "an array of strings",
+                };
+                self.error(
+                    item.span(),
+                    format!("`generated.{}` must be {}", key, expected),
+                );
+            }
+        }
+    }
+
+    fn check_dirty(&mut self, item: &Item) {
+        let Some(dirty) = self.table(item, "dirty") else {
+            return;
+        };
+        for (key, item) in dirty.iter() {
+            match key {
+                "files" => {
+                    if !item
+                        .as_array()
+                        .is_some_and(|files| files.iter().all(toml_edit::Value::is_str))
+                    {
+                        self.error(
+                            item.span(),
+                            "`dirty.files` must be an array of strings".to_string(),
+                        );
+                    }
+                }
+                "report-all" => {
+                    if item.as_bool().is_none() {
+                        self.error(
+                            item.span(),
+                            "`dirty.report-all` must be a boolean".to_string(),
+                        );
+                    }
+                }
+                _ => self.unknown_key(dirty, key, "dirty"),
+            }
+        }
+    }
+
+    fn check_dependents(&mut self, item: &Item) {
+        let Some(dependents) = self.table(item, "dependents") else {
+            return;
+        };
+        for (key, item) in dependents.iter() {
+            if key != "lints" {
+                self.unknown_key(dependents, key, "dependents");
+            } else if !item
+                .as_array()
+                .is_some_and(|lints| lints.iter().all(toml_edit::Value::is_str))
+            {
+                self.error(
+                    item.span(),
+                    "`dependents.lints` must be an array of strings".to_string(),
+                );
+            }
+        }
+    }
+
+    fn check_test_code(&mut self, item: &Item) {
+        let Some(test_code) = self.table(item, "test-code") else {
+            return;
+        };
+        for (key, item) in test_code.iter() {
+            match key {
+                "skip" => {
+                    if item.as_bool().is_none() {
+                        self.error(
+                            item.span(),
+                            "`test-code.skip` must be a boolean".to_string(),
+                        );
+                    }
+                }
+                "lints" => {
+                    if !item
+                        .as_array()
+                        .is_some_and(|lints| lints.iter().all(toml_edit::Value::is_str))
+                    {
+                        self.error(
+                            item.span(),
+                            "`test-code.lints` must be an array of strings".to_string(),
+                        );
+                    }
+                }
+                _ => self.unknown_key(test_code, key, "test-code"),
+            }
+        }
+    }
+
+    fn check_targets(&mut self, item: &Item) {
+        let Some(targets) = self.table(item, "targets") else {
+            return;
+        };
+        for (key, item) in targets.iter() {
+            if !matches!(key, "lib" | "bin" | "test" | "example" | "bench") {
+                self.unknown_key(targets, key, "targets");
+            } else if !matches!(item.as_str(), Some("error" | "info")) {
+                self.error(
+                    item.span(),
+                    format!("`targets.{}` must be one of error or info", key),
+                );
+            }
+        }
+    }
+
+    fn check_relevance(&mut self, item: &Item) {
+        let Some(relevance) = self.table(item, "relevance") else {
+            return;
+        };
+        for (key, item) in relevance.iter() {
+            if key != "include" && key != "exclude" {
+                self.unknown_key(relevance, key, "relevance");
+            } else if !item
+                .as_array()
+                .is_some_and(|patterns| patterns.iter().all(toml_edit::Value::is_str))
+            {
+                self.error(
+                    item.span(),
+                    format!("`relevance.{}` must be an array of strings", key),
+                );
+            }
+        }
+    }
+
+    fn check_invalidators(&mut self, item: &Item) {
+        let Some(invalidators) = self.table(item, "invalidators") else {
+            return;
+        };
+        for (key, item) in invalidators.iter() {
+            if key != "files" {
+                self.unknown_key(invalidators, key, "invalidators");
+            } else if !item
+                .as_array()
+                .is_some_and(|patterns| patterns.iter().all(toml_edit::Value::is_str))
+            {
+                self.error(
+                    item.span(),
+                    "`invalidators.files` must be an array of strings".to_string(),
+                );
+            }
+        }
+    }
+
+    fn check_feature_matrix(&mut self, item: &Item) {
+        let Some(matrix) = self.table(item, "feature-matrix") else {
+            return;
+        };
+        for (key, item) in matrix.iter() {
+            match key {
+                "sets" => {
+                    if !item.as_array().is_some_and(|sets| {
+                        sets.iter().all(|set| {
+                            set.as_array().is_some_and(|features| {
+                                features.iter().all(toml_edit::Value::is_str)
+                            })
+                        })
+                    }) {
+                        self.error(
+                            item.span(),
+                            "`feature-matrix.sets` must be an array of arrays of strings"
+                                .to_string(),
+                        );
+                    }
+                }
+                "no-default-features" => {
+                    if item.as_bool().is_none() {
+                        self.error(
+                            item.span(),
+                            "`feature-matrix.no-default-features` must be a boolean".to_string(),
+                        );
+                    }
+                }
+                _ => self.unknown_key(matrix, key, "feature-matrix"),
+            }
+        }
+    }
+
+    fn check_workspaces(&mut self, item: &Item) {
+        let Some(workspaces) = self.table(item, "workspaces") else {
+            return;
+        };
+        for (key, item) in workspaces.iter() {
+            if key != "include" && key != "exclude" {
+                self.unknown_key(workspaces, key, "workspaces");
+            } else if !item
+                .as_array()
+                .is_some_and(|patterns| patterns.iter().all(toml_edit::Value::is_str))
+            {
+                self.error(
+                    item.span(),
+                    format!("`workspaces.{}` must be an array of strings", key),
+                );
+            }
+        }
+    }
+
+    fn check_markdown(&mut self, item: &Item) {
+        let Some(markdown) = self.table(item, "markdown") else {
+            return;
+        };
+        for (key, item) in markdown.iter() {
+            match key {
+                "code-host" => {
+                    if !matches!(item.as_str(), Some("github" | "gitlab" | "bitbucket")) {
+                        self.error(
+                            item.span(),
+                            "`markdown.code-host` must be one of github, gitlab or bitbucket"
+                                .to_string(),
+                        );
+                    }
+                }
+                "link-prefix" => {
+                    if item.as_str().is_none() {
+                        self.error(
+                            item.span(),
+                            "`markdown.link-prefix` must be a string".to_string(),
+                        );
+                    }
+                }
+                _ => self.unknown_key(markdown, key, "markdown"),
+            }
+        }
+    }
+
+    fn check_webhook(&mut self, item: &Item) {
+        let Some(webhook) = self.table(item, "webhook") else {
+            return;
+        };
+        for (key, item) in webhook.iter() {
+            let message = match key {
+                "url" | "template" if item.as_str().is_none() => "a string",
+                "threshold" if item.as_integer().is_none_or(|t| t < 0) => "a non-negative integer",
+                "include-json" if item.as_bool().is_none() => "a boolean",
+                "url" | "template" | "threshold" | "include-json" => continue,
+                _ => {
+                    self.unknown_key(webhook, key, "webhook");
+                    continue;
+                }
+            };
+            self.error(
+                item.span(),
+                format!("`webhook.{}` must be {}", key, message),
+            );
+        }
+        if let Some(template) = webhook.get("template").and_then(Item::as_str) {
+            if serde_json::from_str::<serde_json::Value>(template).is_err() {
+                self.error(
+                    webhook.get("template").and_then(Item::span),
+                    "`webhook.template` must be valid json".to_string(),
+                );
+            }
+        }
+    }
+
+    fn check_issues(&mut self, item: &Item) {
+        let Some(issues) = self.table(item, "issues") else {
+            return;
+        };
+        for (key, item) in issues.iter() {
+            let message = match key {
+                "url" | "template" | "url-field" | "baseline" if item.as_str().is_none() => {
+                    "a string"
+                }
+                "headers"
+                    if !item
+                        .as_array()
+                        .is_some_and(|headers| headers.iter().all(toml_edit::Value::is_str)) =>
+                {
+                    "an array of strings"
+                }
+                "url-field" if !item.as_str().is_some_and(|field| field.starts_with('/')) => {
+                    "a json pointer, like \"/html_url\""
+                }
+                "url" | "template" | "url-field" | "baseline" | "headers" => continue,
+                _ => {
+                    self.unknown_key(issues, key, "issues");
+                    continue;
+                }
+            };
+            self.error(item.span(), format!("`issues.{}` must be {}", key, message));
+        }
+        if let Some(template) = issues.get("template").and_then(Item::as_str) {
+            // The placeholders of numbers aren't quoted
+            let template = template.replace("{{line}}", "0");
+            if serde_json::from_str::<serde_json::Value>(&template).is_err() {
+                self.error(
+                    issues.get("template").and_then(Item::span),
+                    "`issues.template` must be valid json".to_string(),
+                );
+            }
+        }
+    }
+
+    fn check_include(&mut self, item: &Item) {
+        if !item
+            .as_array()
+            .is_some_and(|includes| includes.iter().all(toml_edit::Value::is_str))
+        {
+            self.error(
+                item.span(),
+                "`include` must be an array of strings".to_string(),
+            );
+        }
+    }
+
+    fn check_schema_version(&mut self, item: &Item) {
+        match item.as_integer() {
+            Some(version) if version > i64::from(SCHEMA_VERSION) => self.error(
+                item.span(),
+                format!(
+                    "schema version {} is newer than the version {} this cargo-scout reads, \
+                     upgrade cargo-scout",
+                    version, SCHEMA_VERSION
+                ),
+            ),
+            Some(version) if version >= 1 => {}
+            _ => self.error(
+                item.span(),
+                "`schema-version` must be a schema version, \
+                 run `cargo scout config migrate` to set it"
+                    .to_string(),
+            ),
+        }
+    }
+
+    fn check_presets(&mut self, item: &Item) {
+        let Some(presets) = self.table(item, "presets") else {
+            return;
+        };
+        for (name, item) in presets.iter() {
+            let path = format!("presets.{}", name);
+            let Some(preset) = self.table(item, &path) else {
+                continue;
+            };
+            for (key, item) in preset.iter() {
+                if key != "warn" && key != "allow" {
+                    self.unknown_key(preset, key, &path);
+                } else if !item
+                    .as_array()
+                    .is_some_and(|lints| lints.iter().all(toml_edit::Value::is_str))
+                {
+                    self.error(
+                        item.span(),
+                        format!("`{}.{}` must be an array of strings", path, key),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_owners(&mut self, item: &Item, document: &dyn TableLike) {
+        let Some(owners) = self.table(item, "owners") else {
+            return;
+        };
+        for (key, item) in owners.iter() {
+            match key {
+                "file" => {
+                    if item.as_str().is_none() {
+                        self.error(item.span(), "`owners.file` must be a string".to_string());
+                    }
+                }
+                "profiles" => {
+                    let Some(profiles) = self.table(item, "owners.profiles") else {
+                        continue;
+                    };
+                    for (team, item) in profiles.iter() {
+                        let Some(name) = item.as_str() else {
+                            self.error(
+                                item.span(),
+                                format!("`owners.profiles.{}` must be a string", team),
+                            );
+                            continue;
+                        };
+                        let defined = document
+                            .get("presets")
+                            .and_then(Item::as_table_like)
+                            .is_some_and(|presets| presets.contains_key(name));
+                        if !defined && Preset::builtin(name).is_none() {
+                            self.error(
+                                item.span(),
+                                format!("`{}` of `{}` is not a preset", name, team),
+                            );
+                        }
+                    }
+                }
+                _ => self.unknown_key(owners, key, "owners"),
+            }
+        }
+    }
+
+    fn check_build_outputs(&mut self, item: &Item) {
+        let Some(build_outputs) = item.as_array_of_tables() else {
+            self.error(
+                item.span(),
+                "`build-outputs` must be an array of tables".to_string(),
+            );
+            return;
+        };
+        for build_output in build_outputs {
+            for (key, item) in build_output.iter() {
+                if key != "inputs" && key != "outputs" {
+                    self.unknown_key(build_output, key, "build-outputs");
+                } else if !item
+                    .as_array()
+                    .is_some_and(|patterns| patterns.iter().all(toml_edit::Value::is_str))
+                {
+                    self.error(
+                        item.span(),
+                        format!("`build-outputs.{}` must be an array of strings", key),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_padding(&mut self, item: &Item) {
+        let Some(rules) = item.as_array_of_tables() else {
+            self.error(
+                item.span(),
+                "`padding` must be an array of tables".to_string(),
+            );
+            return;
+        };
+        for rule in rules {
+            for (key, item) in rule.iter() {
+                let valid = match key {
+                    "lints" => item
+                        .as_array()
+                        .is_some_and(|lints| lints.iter().all(toml_edit::Value::is_str)),
+                    "before" | "after" => item
+                        .as_integer()
+                        .is_some_and(|lines| u32::try_from(lines).is_ok()),
+                    "enclosing-item" => item.as_bool().is_some(),
+                    _ => {
+                        self.unknown_key(rule, key, "padding");
+                        continue;
+                    }
+                };
+                if !valid {
+                    let expected = match key {
+                        "lints" => "an array of strings",
+                        "enclosing-item" => "a boolean",
+                        _ => "a positive integer",
+                    };
+                    self.error(
+                        item.span(),
+                        format!("`padding.{}` must be {}", key, expected),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_grace(&mut self, item: &Item) {
+        let Some(rules) = item.as_array_of_tables() else {
+            self.error(
+                item.span(),
+                "`grace` must be an array of tables".to_string(),
+            );
+            return;
+        };
+        for rule in rules {
+            for (key, item) in rule.iter() {
+                let valid = match key {
+                    "lints" => item
+                        .as_array()
+                        .is_some_and(|lints| lints.iter().all(toml_edit::Value::is_str)),
+                    "until" | "added" => item.as_str().and_then(Date::parse).is_some(),
+                    "days" => item
+                        .as_integer()
+                        .is_some_and(|days| u32::try_from(days).is_ok()),
+                    _ => {
+                        self.unknown_key(rule, key, "grace");
+                        continue;
+                    }
+                };
+                if !valid {
+                    let expected = match key {
+                        "lints" => "an array of strings",
+                        "days" => "a positive integer",
+                        _ => "a date string, like \"2026-12-01\"",
+                    };
+                    self.error(item.span(), format!("`grace.{}` must be {}", key, expected));
+                }
+            }
+            let ends = rule.contains_key("until")
+                || (rule.contains_key("added") && rule.contains_key("days"));
+            if !ends {
+                self.error(
+                    rule.span(),
+                    "`grace` needs an `until` date, or an `added` date and `days`".to_string(),
+                );
+            }
+        }
+    }
+
+    fn check_ranking(&mut self, item: &Item) {
+        let Some(ranking) = self.table(item, "ranking") else {
+            return;
+        };
+        for (key, item) in ranking.iter() {
+            match key {
+                "error" | "deny-listed" | "new-file" | "modified-line" | "boundary" => {
+                    let valid = item
+                        .as_integer()
+                        .is_some_and(|weight| u32::try_from(weight).is_ok());
+                    if !valid {
+                        self.error(
+                            item.span(),
+                            format!("`ranking.{}` must be a positive integer", key),
+                        );
+                    }
+                }
+                "deny" => {
+                    if !item
+                        .as_array()
+                        .is_some_and(|lints| lints.iter().all(toml_edit::Value::is_str))
+                    {
+                        self.error(
+                            item.span(),
+                            "`ranking.deny` must be an array of strings".to_string(),
+                        );
+                    }
+                }
+                _ => self.unknown_key(ranking, key, "ranking"),
+            }
+        }
+    }
+
+    fn check_path_map(&mut self, item: &Item) {
+        // Either `path-map = [{ from = .., to = .. }]`, or `[[path-map]]` tables
+        let mappings: Vec<&dyn TableLike> = match (item.as_array(), item.as_array_of_tables()) {
+            (Some(mappings), _) if mappings.iter().all(toml_edit::Value::is_inline_table) => {
+                mappings
+                    .iter()
+                    .filter_map(toml_edit::Value::as_inline_table)
+                    .map(|mapping| mapping as &dyn TableLike)
+                    .collect()
+            }
+            (_, Some(mappings)) => mappings
+                .iter()
+                .map(|mapping| mapping as &dyn TableLike)
+                .collect(),
+            _ => {
+                self.error(
+                    item.span(),
+                    "`path-map` must be an array of tables".to_string(),
+                );
+                return;
+            }
+        };
+        for mapping in mappings {
+            for key in ["from", "to"] {
+                if !mapping.contains_key(key) {
+                    self.error(
+                        item.span(),
+                        format!("`path-map` needs a `{}` path in each mapping", key),
+                    );
+                }
+            }
+            for (key, item) in mapping.iter() {
+                if key != "from" && key != "to" {
+                    self.unknown_key(mapping, key, "path-map");
+                } else if !item.is_str() {
+                    self.error(item.span(), format!("`path-map.{}` must be a string", key));
+                }
+            }
+        }
+    }
+
+    fn check_member(&mut self, member: &dyn TableLike, path: &str) {
+        for (key, item) in member.iter() {
+            match key {
+                "manifest-path" | "cwd" => {
+                    if !item.is_str() {
+                        self.error(item.span(), format!("`{}.{}` must be a string", path, key));
+                    }
+                }
+                "extra-args" | "lint-args" => self.check_args(member, key, item, path),
+                _ => self.unknown_key(member, key, path),
+            }
+        }
+    }
+
+    fn check_args(&mut self, member: &dyn TableLike, key: &str, item: &Item, path: &str) {
+        let Some(args) = item.as_array() else {
+            self.error(
+                item.span(),
+                format!("`{}.{}` must be an array of strings", path, key),
+            );
+            return;
+        };
+        for arg in args.iter() {
+            match arg.as_str() {
+                None => self.error(
+                    arg.span(),
+                    format!("`{}.{}` must be an array of strings", path, key),
+                ),
+                Some(flag @ "--manifest-path")
+                    if key == "extra-args" && member.contains_key("manifest-path") =>
+                {
+                    self.error(
+                        arg.span(),
+                        format!("`{}` conflicts with `{}.manifest-path`", flag, path),
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn table<'i>(&mut self, item: &'i Item, path: &str) -> Option<&'i dyn TableLike> {
+        let table = item.as_table_like();
+        if table.is_none() {
+            self.error(item.span(), format!("`{}` must be a table", path));
+        }
+        table
+    }
+
+    fn unknown_key(&mut self, table: &dyn TableLike, key: &str, path: &str) {
+        let span = table.key(key).and_then(toml_edit::Key::span);
+        let key = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        self.error(span, format!("unknown key `{}`", key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, Diagnostic, Severity};
+
+    fn members() -> Vec<String> {
+        vec!["crates/foo".to_string(), "bar".to_string()]
+    }
+
+    fn error(line: usize, column: usize, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let source = "[members.foo]\nmanifest-path = \"Cargo.toml\"\nextra-args = [\"--locked\"]\n";
+        assert!(check(source, &members()).is_empty());
+        assert!(check("", &members()).is_empty());
+    }
+
+    #[test]
+    fn test_every_unknown_key_is_reported() {
+        let source =
+            "ignore = true\n[members.foo]\nmanifest = \"Cargo.toml\"\n  cwd = \".\"\n  args = []\n";
+        assert_eq!(
+            vec![
+                error(1, 1, "unknown key `ignore`"),
+                error(3, 1, "unknown key `members.foo.manifest`"),
+                error(5, 3, "unknown key `members.foo.args`"),
+            ],
+            check(source, &members())
+        );
+    }
+
+    #[test]
+    fn test_wrong_types() {
+        let source = "[members.foo]\ncwd = 1\nextra-args = [\"--locked\", 2]\n[members.bar]\nextra-args = \"--locked\"\nlint-args = \"-A\"\n";
+        assert_eq!(
+            vec![
+                error(2, 7, "`members.foo.cwd` must be a string"),
+                error(
+                    3,
+                    27,
+                    "`members.foo.extra-args` must be an array of strings"
+                ),
+                error(
+                    5,
+                    14,
+                    "`members.bar.extra-args` must be an array of strings"
+                ),
+                error(6, 13, "`members.bar.lint-args` must be an array of strings"),
+            ],
+            check(source, &members())
+        );
+    }
+
+    #[test]
+    fn test_generated() {
+        let source = "[generated]\nmarkers = [\"@generated\"]\nheader-lines = -1\npatterns = []\n";
+        assert_eq!(
+            vec![
+                error(3, 16, "`generated.header-lines` must be a positive integer"),
+                error(4, 1, "unknown key `generated.patterns`"),
+            ],
+            check(source, &members())
+        );
+    }
+
+    #[test]
+    fn test_build_outputs() {
+        let source = "[[build-outputs]]\ninputs = [\"a.proto\"]\noutputs = \"a.rs\"\n[[build-outputs]]\ninput = []\n";
+        assert_eq!(
+            vec![
+                error(3, 11, "`build-outputs.outputs` must be an array of strings"),
+                error(5, 1, "unknown key `build-outputs.input`"),
+            ],
+            check(source, &members())
+        );
+        assert_eq!(
+            vec![error(1, 17, "`build-outputs` must be an array of tables")],
+            check("build-outputs = 1", &members())
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        assert!(check(
+            "[[padding]]\nlints = [\"missing_docs\"]\nenclosing-item = true\nafter = 2\n",
+            &members()
+        )
+        .is_empty());
+        let source =
+            "[[padding]]\nlints = [\"missing_docs\"]\nbefore = -1\n[[padding]]\nitem = true\n";
+        assert_eq!(
+            vec![
+                error(3, 10, "`padding.before` must be a positive integer"),
+                error(5, 1, "unknown key `padding.item`"),
+            ],
+            check(source, &members())
+        );
+    }
+
+    #[test]
+    fn test_grace() {
+        assert!(check(
+            "[[grace]]\nlints = [\"clippy::unwrap_used\"]\nadded = \"2026-10-01\"\ndays = 30\n\
+             [[grace]]\nlints = [\"clippy::todo\"]\nuntil = \"2027-01-01\"\n",
+            &members()
+        )
+        .is_empty());
+        let source = "[[grace]]\nlints = [\"clippy::todo\"]\nuntil = 2027-01-01\n\
+                      [[grace]]\nlints = []\nadded = \"2026-10-01\"\n";
+        assert_eq!(
+            vec![
+                error(
+                    3,
+                    9,
+                    "`grace.until` must be a date string, like \"2026-12-01\""
+                ),
+                error(
+                    4,
+                    1,
+                    "`grace` needs an `until` date, or an `added` date and `days`"
+                ),
+            ],
+            check(source, &members())
+        );
+    }
+
+    #[test]
+    fn test_path_map() {
+        assert!(check(
+            "path-map = [{ from = \"/workspace\", to = \".\" }]\n",
+            &members()
+        )
+        .is_empty());
+        assert!(check("[[path-map]]\nfrom = \"/build\"\nto = \".\"\n", &members()).is_empty());
+        assert_eq!(
+            vec![
+                error(1, 12, "`path-map` needs a `to` path in each mapping"),
+                error(1, 22, "`path-map.from` must be a string"),
+                error(1, 25, "unknown key `path-map.into`"),
+            ],
+            check("path-map = [{ from = 1, into = \".\" }]\n", &members())
+        );
+        assert_eq!(
+            vec![error(1, 12, "`path-map` must be an array of tables")],
+            check("path-map = [\"/workspace\"]\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_dirty() {
+        assert!(check(
+            "[dirty]\nfiles = [\"build.rs\"]\nreport-all = true\n",
+            &members()
+        )
+        .is_empty());
+        assert_eq!(
+            vec![error(2, 14, "`dirty.report-all` must be a boolean")],
+            check("[dirty]\nreport-all = \"yes\"\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_dependents() {
+        assert!(check("[dependents]\nlints = [\"deprecated\"]\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(
+                2,
+                9,
+                "`dependents.lints` must be an array of strings"
+            )],
+            check("[dependents]\nlints = \"deprecated\"\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_test_code() {
+        assert!(check(
+            "[test-code]\nskip = true\nlints = [\"clippy::*\"]\n",
+            &members()
+        )
+        .is_empty());
+        assert_eq!(
+            vec![
+                error(2, 8, "`test-code.skip` must be a boolean"),
+                error(3, 9, "`test-code.lints` must be an array of strings")
+            ],
+            check("[test-code]\nskip = 1\nlints = \"clippy::*\"\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_targets() {
+        assert!(check("[targets]\nexample = \"info\"\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(2, 9, "`targets.bench` must be one of error or info")],
+            check("[targets]\nbench = \"warn\"\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_workspaces() {
+        assert!(check("[workspaces]\nexclude = [\"vendor/**\"]\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(
+                2,
+                11,
+                "`workspaces.include` must be an array of strings"
+            )],
+            check("[workspaces]\ninclude = \"services\"\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_relevance() {
+        assert!(check("[relevance]\nexclude = [\"testdata/**\"]\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(2, 1, "unknown key `relevance.compiled`")],
+            check("[relevance]\ncompiled = []\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_invalidators() {
+        assert!(check("[invalidators]\nfiles = [\"lints.toml\"]\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(
+                2,
+                9,
+                "`invalidators.files` must be an array of strings"
+            )],
+            check("[invalidators]\nfiles = \"lints.toml\"\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_feature_matrix() {
+        assert!(check("[feature-matrix]\nsets = [[], [\"serde\"]]\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(
+                2,
+                8,
+                "`feature-matrix.sets` must be an array of arrays of strings"
+            )],
+            check("[feature-matrix]\nsets = [\"serde\"]\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert!(check("[markdown]\ncode-host = \"gitlab\"\n", &members()).is_empty());
+        assert_eq!(
+            vec![
+                error(
+                    2,
+                    13,
+                    "`markdown.code-host` must be one of github, gitlab or bitbucket"
+                ),
+                error(3, 15, "`markdown.link-prefix` must be a string")
+            ],
+            check(
+                "[markdown]\ncode-host = \"gitea\"\nlink-prefix = 1\n",
+                &members()
+            )
+        );
+    }
+
+    #[test]
+    fn test_webhook() {
+        assert!(check(
+            "[webhook]\nthreshold = 3\ntemplate = '{\"text\": \"{{summary}}\"}'\n",
+            &members()
+        )
+        .is_empty());
+        assert_eq!(
+            vec![
+                error(2, 13, "`webhook.threshold` must be a non-negative integer"),
+                error(3, 12, "`webhook.template` must be valid json")
+            ],
+            check("[webhook]\nthreshold = -1\ntemplate = '{'\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_include() {
+        assert!(check("include = [\"presets/strict.toml\"]\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(1, 11, "`include` must be an array of strings")],
+            check("include = \"presets/strict.toml\"\n", &members())
+        );
+    }
+
+    #[test]
+    fn test_schema_version() {
+        assert!(check("schema-version = 1\n", &members()).is_empty());
+        assert_eq!(
+            vec![error(
+                1,
+                18,
+                "schema version 2 is newer than the version 1 this cargo-scout reads, \
+                 upgrade cargo-scout"
+            )],
+            check("schema-version = 2\n", &members())
+        );
+        assert_eq!(1, check("schema-version = \"1\"\n", &members()).len());
+    }
+
+    #[test]
+    fn test_presets() {
+        assert!(check(
+            "[presets.strict]\nwarn = [\"clippy::unwrap_used\"]\nallow = []\n",
+            &members()
+        )
+        .is_empty());
+        assert_eq!(
+            vec![
+                error(2, 8, "`presets.strict.warn` must be an array of strings"),
+                error(3, 1, "unknown key `presets.strict.deny`")
+            ],
+            check(
+                "[presets.strict]\nwarn = \"clippy::unwrap_used\"\ndeny = []\n",
+                &members()
+            )
+        );
+    }
+
+    #[test]
+    fn test_issues() {
+        assert!(check(
+            "[issues]\nurl = \"https://example.atlassian.net/rest/api/2/issue\"\n\
+             template = '{\"fields\": {\"summary\": \"{{title}}\", \"line\": {{line}}}}'\n\
+             headers = [\"Authorization: Basic {{token}}\"]\nurl-field = \"/key\"\n",
+            &members()
+        )
+        .is_empty());
+        assert_eq!(
+            vec![
+                error(
+                    2,
+                    13,
+                    "`issues.url-field` must be a json pointer, like \"/html_url\""
+                ),
+                error(3, 11, "`issues.headers` must be an array of strings"),
+                error(4, 12, "`issues.template` must be valid json"),
+            ],
+            check(
+                "[issues]\nurl-field = \"html_url\"\nheaders = \"Accept: */*\"\ntemplate = \"{\"\n",
+                &members()
+            )
+        );
+    }
+
+    #[test]
+    fn test_ranking() {
+        assert!(check(
+            "[ranking]\nerror = 100\nboundary = 0\ndeny = [\"clippy::unwrap_*\"]\n",
+            &members()
+        )
+        .is_empty());
+        assert_eq!(
+            vec![
+                error(2, 12, "`ranking.new-file` must be a positive integer"),
+                error(3, 8, "`ranking.deny` must be an array of strings"),
+                error(4, 1, "unknown key `ranking.warning`"),
+            ],
+            check(
+                "[ranking]\nnew-file = -1\ndeny = \"dead_code\"\nwarning = 1\n",
+                &members()
+            )
+        );
+    }
+
+    #[test]
+    fn test_owners() {
+        assert!(check(
+            "[presets.lenient]\nwarn = [\"clippy::todo\"]\n\
+             [owners]\nfile = \".github/CODEOWNERS\"\n\
+             [owners.profiles]\n\"@org/a\" = \"security\"\n\"@org/legacy\" = \"lenient\"\n",
+            &members()
+        )
+        .is_empty());
+        assert_eq!(
+            vec![
+                error(2, 12, "`strict` of `@org/a` is not a preset"),
+                error(3, 12, "`owners.profiles.@org/b` must be a string"),
+                error(4, 9, "unknown key `owners.teams`"),
+            ],
+            check(
+                "[owners.profiles]\n\"@org/a\" = \"strict\"\n\"@org/b\" = 1\n[owners.teams]\n",
+                &members()
+            )
+        );
+    }
+
+    #[test]
+    fn test_conflicting_rules() {
+        let source =
+            "[members.foo]\nmanifest-path = \"Cargo.toml\"\nextra-args = [\"--manifest-path\"]\n";
+        assert_eq!(
+            vec![error(
+                3,
+                15,
+                "`--manifest-path` conflicts with `members.foo.manifest-path`"
+            )],
+            check(source, &members())
+        );
+    }
+
+    #[test]
+    fn test_unknown_member_is_a_warning() {
+        let diagnostics = check("[members.qux]\ncwd = \".\"\n", &members());
+        assert_eq!(
+            vec![Diagnostic {
+                severity: Severity::Warning,
+                line: 1,
+                column: 10,
+                message: "`qux` is not a workspace member".to_string(),
+            }],
+            diagnostics
+        );
+        assert_eq!(
+            "1:10: warning: `qux` is not a workspace member",
+            diagnostics[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let diagnostics = check("[members.foo]\ncwd = \n", &members());
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(Severity::Error, diagnostics[0].severity);
+        assert_eq!(2, diagnostics[0].line);
+    }
+}
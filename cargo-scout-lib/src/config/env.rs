@@ -0,0 +1,176 @@
+use crate::error::Error;
+
+/// The prefix of the environment variables overriding the configuration.
+pub const PREFIX: &str = "SCOUT_";
+
+/// Replaces the `${VAR}` of `value` by the values `var` gives, `$$` is a literal `$`.
+#[allow(clippy::missing_errors_doc)]
+pub fn interpolate(value: &str, var: impl Fn(&str) -> Option<String>) -> Result<String, Error> {
+    let mut interpolated = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        interpolated.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$$") {
+            interpolated.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| Error::Env(format!("unclosed `${{` in `{}`", value)))?;
+            let name = &after[..end];
+            interpolated.push_str(&var(name).ok_or_else(|| {
+                Error::Env(format!("`{}` uses the undefined variable {}", value, name))
+            })?);
+            rest = &after[end + 1..];
+        } else {
+            interpolated.push('$');
+            rest = &rest[1..];
+        }
+    }
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
+
+/// Interpolates every string of `table`, see `interpolate`.
+#[allow(clippy::missing_errors_doc)]
+pub fn interpolate_table(
+    table: &mut toml::Table,
+    var: &impl Fn(&str) -> Option<String>,
+) -> Result<(), Error> {
+    for (_, value) in table.iter_mut() {
+        interpolate_value(value, var)?;
+    }
+    Ok(())
+}
+
+fn interpolate_value(
+    value: &mut toml::Value,
+    var: &impl Fn(&str) -> Option<String>,
+) -> Result<(), Error> {
+    match value {
+        toml::Value::String(string) => *string = interpolate(string, var)?,
+        toml::Value::Array(values) => {
+            for value in values {
+                interpolate_value(value, var)?;
+            }
+        }
+        toml::Value::Table(table) => interpolate_table(table, var)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The configuration set by `SCOUT_<TABLE>__<KEY>` variables, like `SCOUT_WEBHOOK__THRESHOLD`.
+///
+/// `__` separates the tables and keys, whose `_` are `-`.
+/// Values are parsed as TOML, or else taken as strings.
+/// Variables without `__`, like `SCOUT_WEBHOOK_URL`, are left alone.
+#[must_use]
+pub fn overrides(vars: impl IntoIterator<Item = (String, String)>) -> toml::Table {
+    let mut overrides = toml::Table::new();
+    'vars: for (name, value) in vars {
+        let Some(path) = name.strip_prefix(PREFIX).filter(|path| path.contains("__")) else {
+            continue;
+        };
+        let keys: Vec<String> = path
+            .split("__")
+            .map(|key| key.to_lowercase().replace('_', "-"))
+            .collect();
+        if keys.iter().any(String::is_empty) {
+            continue;
+        }
+        let mut table = &mut overrides;
+        for key in &keys[..keys.len() - 1] {
+            // A variable setting a value where another one sets a table is ignored
+            let Some(nested) = table
+                .entry(key.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+            else {
+                continue 'vars;
+            };
+            table = nested;
+        }
+        table.insert(keys[keys.len() - 1].clone(), parse_value(&value));
+    }
+    overrides
+}
+
+/// Whether the value of a boolean environment variable is true, like `1` or `true`.
+#[must_use]
+pub fn is_true(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+fn parse_value(value: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", value))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpolate, interpolate_table, is_true, overrides};
+
+    fn var(name: &str) -> Option<String> {
+        match name {
+            "ORG" => Some("acme".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(
+            "https://acme.example.com/$5",
+            interpolate("https://${ORG}.example.com/$$5", var).unwrap()
+        );
+        assert_eq!("a $b", interpolate("a $b", var).unwrap());
+        assert!(interpolate("${MISSING}", var).is_err());
+        assert!(interpolate("${ORG", var).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_table() {
+        let mut table: toml::Table =
+            toml::from_str("include = [\"${ORG}.toml\"]\n[webhook]\nthreshold = 3\n").unwrap();
+        interpolate_table(&mut table, &var).unwrap();
+        assert_eq!("acme.toml", table["include"][0].as_str().unwrap());
+        assert_eq!(3, table["webhook"]["threshold"].as_integer().unwrap());
+    }
+
+    #[test]
+    fn test_is_true() {
+        assert!(is_true("1"));
+        assert!(is_true(" True"));
+        assert!(!is_true("0"));
+        assert!(!is_true(""));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let vars = [
+            ("SCOUT_WEBHOOK__THRESHOLD", "5"),
+            ("SCOUT_WEBHOOK__TEMPLATE", "{\"text\": \"hi\"}"),
+            ("SCOUT_GENERATED__HEADER_LINES", "10"),
+            ("SCOUT_DEPENDENTS__LINTS", "[\"clippy::*\"]"),
+            ("SCOUT_WEBHOOK_URL", "https://example.com"),
+            ("PATH", "/bin"),
+        ];
+        let overrides = overrides(
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        let expected: toml::Table = toml::from_str(
+            "[webhook]\nthreshold = 5\ntemplate = '{\"text\": \"hi\"}'\n\
+             [generated]\nheader-lines = 10\n[dependents]\nlints = [\"clippy::*\"]\n",
+        )
+        .unwrap();
+        assert_eq!(expected, overrides);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::linter::Lint;
+use crate::scout::build_output::glob_match;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::SystemTime;
+
+/// Lints which are reported without failing the run for a while, to roll stricter lints out
+/// to a large team: warn-only until `until`, or for `days` days after they were `added`.
+///
+/// # Example
+/// ```toml
+/// [[grace]]
+/// lints = ["clippy::unwrap_used", "clippy::indexing_slicing"]
+/// added = "2026-10-01"
+/// days = 30
+///
+/// [[grace]]
+/// lints = ["clippy::todo"]
+/// until = "2027-01-01"
+/// ```
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct GraceRule {
+    /// Glob patterns of the lint codes
+    pub lints: Vec<String>,
+    /// The day the lints start failing the run, like `2027-01-01`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// The day the lints were added to the configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<String>,
+    /// How many days after `added` the lints start failing the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+}
+
+impl GraceRule {
+    /// The day the lints start failing the run, `None` when the dates are missing or invalid.
+    #[must_use]
+    pub fn ends(&self) -> Option<Date> {
+        match (&self.until, &self.added, self.days) {
+            (Some(until), _, _) => Date::parse(until),
+            (None, Some(added), Some(days)) => Some(Date::parse(added)?.add_days(days)),
+            _ => None,
+        }
+    }
+
+    /// Whether the grace period of the rule applies to `lint` on `today`.
+    #[must_use]
+    pub fn excuses(&self, lint: &Lint, today: Date) -> bool {
+        self.ends().is_some_and(|ends| today < ends)
+            && lint
+                .code
+                .as_ref()
+                .is_some_and(|code| self.lints.iter().any(|pattern| glob_match(pattern, code)))
+    }
+}
+
+/// The day the grace period of `lint` ends if it is warn-only on `today`, the latest of its rules.
+#[must_use]
+pub fn ends(rules: &[GraceRule], lint: &Lint, today: Date) -> Option<Date> {
+    rules
+        .iter()
+        .filter(|rule| rule.excuses(lint, today))
+        .filter_map(GraceRule::ends)
+        .max()
+}
+
+/// A day of the proleptic Gregorian calendar, in UTC.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Date {
+    /// Days since 1970-01-01
+    days: i64,
+}
+
+impl Date {
+    /// Parses a `YYYY-MM-DD` date.
+    #[must_use]
+    pub fn parse(date: &str) -> Option<Self> {
+        let mut parts = date.trim().splitn(3, '-');
+        let year: i64 = parts.next()?.parse().ok()?;
+        let month: u32 = parts.next()?.parse().ok()?;
+        let day: u32 = parts.next()?.parse().ok()?;
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self {
+            days: days_from_civil(year, month, day),
+        })
+    }
+
+    /// The current day, in UTC.
+    #[must_use]
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            days: i64::try_from(seconds / 86_400).unwrap_or(i64::MAX),
+        }
+    }
+
+    #[must_use]
+    pub fn add_days(self, days: u32) -> Self {
+        Self {
+            days: self.days + i64::from(days),
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.days);
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The algorithms of http://howardhinnant.github.io/date_algorithms.html,
+// with years starting in March so that the leap day is the last one
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    // The day and month are in 1..=31 and 1..=12
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    (year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ends, Date, GraceRule};
+    use crate::linter::{Lint, Location};
+
+    fn lint(code: &str) -> Lint {
+        Lint {
+            message: String::new(),
+            code: Some(code.to_string()),
+            location: Location::default(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn date(date: &str) -> Date {
+        Date::parse(date).unwrap()
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!("1970-01-01", date("1970-01-01").to_string());
+        assert_eq!("2024-02-29", date("2024-02-29").to_string());
+        assert_eq!("2024-03-01", date("2024-02-29").add_days(1).to_string());
+        assert_eq!("2027-01-30", date("2026-12-31").add_days(30).to_string());
+        assert_eq!("1969-12-31", date("1969-12-31").to_string());
+        assert!(date("2026-09-30") < date("2026-10-01"));
+        assert_eq!(None, Date::parse("2026-02-29"));
+        assert_eq!(None, Date::parse("2026-13-01"));
+        assert_eq!(None, Date::parse("2026/10/01"));
+        assert_eq!(None, Date::parse("tomorrow"));
+        assert!(Date::today() > date("2020-01-01"));
+    }
+
+    #[test]
+    fn test_grace() {
+        let config: crate::config::scout::ScoutConfig = toml::from_str(
+            "[[grace]]\nlints = [\"clippy::unwrap_used\"]\nadded = \"2026-10-01\"\ndays = 30\n\
+             [[grace]]\nlints = [\"clippy::*\"]\nuntil = \"2026-10-15\"\n",
+        )
+        .unwrap();
+        let rules = &config.grace;
+        assert_eq!(Some(date("2026-10-31")), rules[0].ends());
+
+        let unwrap = lint("clippy::unwrap_used");
+        let todo = lint("clippy::todo");
+        assert_eq!(
+            Some(date("2026-10-31")),
+            ends(rules, &unwrap, date("2026-10-10"))
+        );
+        assert_eq!(
+            Some(date("2026-10-15")),
+            ends(rules, &todo, date("2026-10-10"))
+        );
+        assert_eq!(None, ends(rules, &todo, date("2026-10-15")));
+        assert_eq!(None, ends(rules, &unwrap, date("2026-10-31")));
+        assert_eq!(None, ends(rules, &lint("dead_code"), date("2026-10-10")));
+
+        // Rules without a valid end never apply
+        let rule = GraceRule {
+            lints: vec!["*".to_string()],
+            added: Some("2026-10-01".to_string()),
+            ..GraceRule::default()
+        };
+        assert_eq!(None, rule.ends());
+        assert!(!rule.excuses(&unwrap, date("2026-10-10")));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::error::Error;
+use crate::retry::RetryPolicy;
+use crate::utils::{decode_output, normalize_path};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    manifest_path: PathBuf,
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    path: Option<PathBuf>,
+}
+
+/// The dependencies between workspace members.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct DependencyGraph {
+    /// For each member, the members it depends on
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+    /// How many times `cargo metadata` was retried to load the graph
+    retries: u32,
+}
+
+impl DependencyGraph {
+    /// Builds the graph of `members`, relative to `root`,
+    /// from the output of `cargo metadata --format-version 1 --no-deps`.
+    ///
+    /// Only path dependencies between members are kept.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_metadata(metadata: &str, root: &Path, members: &[String]) -> Result<Self, Error> {
+        let metadata: Metadata = serde_json::from_str(metadata)?;
+        let member_dirs: BTreeMap<String, &String> = members
+            .iter()
+            .map(|m| (member_dir(&root.join(m)), m))
+            .collect();
+        let mut dependencies = BTreeMap::new();
+        for package in metadata.packages {
+            let Some(member) = package
+                .manifest_path
+                .parent()
+                .and_then(|dir| member_dirs.get(&member_dir(dir)))
+            else {
+                continue;
+            };
+            let member_dependencies: BTreeSet<String> = package
+                .dependencies
+                .iter()
+                .filter_map(|d| d.path.as_ref())
+                .filter_map(|path| member_dirs.get(&member_dir(path)))
+                .map(|m| (*m).clone())
+                .collect();
+            dependencies.insert((*member).clone(), member_dependencies);
+        }
+        Ok(Self {
+            dependencies,
+            retries: 0,
+        })
+    }
+
+    /// Runs `cargo metadata` on the manifest at `manifest_path` to build the graph.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn load(
+        manifest_path: impl AsRef<Path>,
+        root: &Path,
+        members: &[String],
+        retry: &RetryPolicy,
+    ) -> Result<Self, Error> {
+        let (metadata, retries) = metadata(manifest_path.as_ref(), retry)?;
+        Ok(Self {
+            retries,
+            ..Self::from_metadata(&metadata, root, members)?
+        })
+    }
+
+    /// How many times `cargo metadata` was retried to load the graph.
+    #[must_use]
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Returns the members depending on `changed`, directly or not,
+    /// excluding `changed` themselves.
+    #[must_use]
+    pub fn dependents(&self, changed: &[String]) -> Vec<String> {
+        let mut reached: BTreeSet<&str> = changed.iter().map(String::as_str).collect();
+        loop {
+            let next: Vec<&str> = self
+                .dependencies
+                .iter()
+                .filter(|(member, _)| !reached.contains(member.as_str()))
+                .filter(|(_, dependencies)| {
+                    dependencies.iter().any(|d| reached.contains(d.as_str()))
+                })
+                .map(|(member, _)| member.as_str())
+                .collect();
+            if next.is_empty() {
+                break;
+            }
+            reached.extend(next);
+        }
+        reached
+            .into_iter()
+            .filter(|m| !changed.iter().any(|c| c == m))
+            .map(ToString::to_string)
+            .collect()
+    }
+}
+
+/// The output of `cargo metadata --format-version 1 --no-deps` for the manifest at `manifest_path`,
+/// and how many times it was retried.
+#[cfg(not(tarpaulin_include))]
+pub(crate) fn metadata(manifest_path: &Path, retry: &RetryPolicy) -> Result<(String, u32), Error> {
+    let (output, retries) = retry.run(|| {
+        std::process::Command::new("cargo")
+            .args([
+                "metadata",
+                "--format-version",
+                "1",
+                "--no-deps",
+                "--manifest-path",
+            ])
+            .arg(manifest_path)
+            .output()
+    });
+    let output = output?;
+    if !output.status.success() {
+        return Err(Error::Command(decode_output(
+            output.stderr,
+            "cargo metadata",
+        )));
+    }
+    Ok((decode_output(output.stdout, "cargo metadata"), retries))
+}
+
+/// The normalized directory of a member, `.` components are dropped.
+pub(crate) fn member_dir(dir: &Path) -> String {
+    let dir: PathBuf = dir.components().collect();
+    normalize_path(&dir.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DependencyGraph;
+    use std::path::Path;
+
+    fn metadata() -> &'static str {
+        r#"{
+            "packages": [
+                {
+                    "name": "app",
+                    "manifest_path": "/repo/app/Cargo.toml",
+                    "dependencies": [
+                        {"name": "core", "path": "/repo/crates/core"},
+                        {"name": "serde", "path": null}
+                    ]
+                },
+                {
+                    "name": "cli",
+                    "manifest_path": "/repo/cli/Cargo.toml",
+                    "dependencies": [{"name": "app", "path": "/repo/app"}]
+                },
+                {
+                    "name": "core",
+                    "manifest_path": "/repo/crates/core/Cargo.toml",
+                    "dependencies": []
+                },
+                {
+                    "name": "other",
+                    "manifest_path": "/repo/other/Cargo.toml",
+                    "dependencies": [{"name": "vendored", "path": "/elsewhere/vendored"}]
+                }
+            ],
+            "workspace_members": []
+        }"#
+    }
+
+    fn members() -> Vec<String> {
+        vec![
+            "app".to_string(),
+            "cli".to_string(),
+            "crates/core".to_string(),
+            "other".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_dependents() -> Result<(), crate::error::Error> {
+        let graph = DependencyGraph::from_metadata(metadata(), Path::new("/repo"), &members())?;
+        assert_eq!(
+            vec!["app".to_string(), "cli".to_string()],
+            graph.dependents(&["crates/core".to_string()])
+        );
+        assert_eq!(
+            vec!["cli".to_string()],
+            graph.dependents(&["app".to_string(), "crates/core".to_string()])
+        );
+        assert!(graph.dependents(&["other".to_string()]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_metadata() {
+        assert!(DependencyGraph::from_metadata("{}", Path::new("/repo"), &members()).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::error::Error;
+use std::path::{Path, PathBuf};
+
+/// How deep includes can be nested, which also stops include cycles.
+const MAX_DEPTH: usize = 8;
+
+/// Reads the `.scout.toml` at `path`, merged over the files of its `include` array.
+///
+/// Includes are paths relative to the including file, or urls fetched with the `http` feature.
+/// The `${VAR}` of the strings of each file are replaced by environment variables first.
+/// Later includes override earlier ones, and the including file overrides them all.
+#[allow(clippy::missing_errors_doc)]
+pub fn load(path: &Path) -> Result<toml::Table, Error> {
+    load_source(&Source::File(path.to_path_buf()), 0)
+}
+
+/// Merges `overrides` into `base`: tables are merged key by key, other values are replaced.
+pub fn merge(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => merge(base, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Where a configuration file is read from.
+#[derive(Debug, PartialEq, Clone)]
+enum Source {
+    File(PathBuf),
+    Url(String),
+}
+
+impl Source {
+    /// The source of the `include` entry of a file read from `self`.
+    fn join(&self, include: &str) -> Self {
+        if is_url(include) {
+            return Self::Url(include.to_string());
+        }
+        match self {
+            Self::File(path) => Self::File(
+                path.parent()
+                    .map_or_else(|| PathBuf::from(include), |dir| dir.join(include)),
+            ),
+            Self::Url(url) => Self::Url(format!(
+                "{}/{}",
+                url.rsplit_once('/').map_or(url.as_str(), |(dir, _)| dir),
+                include.trim_start_matches("./")
+            )),
+        }
+    }
+
+    fn read(&self) -> Result<String, Error> {
+        match self {
+            Self::File(path) => Ok(std::fs::read_to_string(path)?),
+            Self::Url(url) => fetch(url),
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+fn load_source(source: &Source, depth: usize) -> Result<toml::Table, Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::Include(format!(
+            "{} is included more than {} levels deep, is there a cycle?",
+            source, MAX_DEPTH
+        )));
+    }
+    let mut table: toml::Table = toml::from_str(&source.read()?)?;
+    super::schema::check(&table, &source.to_string())?;
+    super::env::interpolate_table(&mut table, &|name| std::env::var(name).ok())?;
+    let Some(includes) = table.get("include") else {
+        return Ok(table);
+    };
+    let includes = includes
+        .as_array()
+        .and_then(|includes| {
+            includes
+                .iter()
+                .map(toml::Value::as_str)
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| {
+            Error::Include(format!(
+                "`include` of {} must be an array of strings",
+                source
+            ))
+        })?;
+    let mut merged = toml::Table::new();
+    for include in includes {
+        merge(&mut merged, load_source(&source.join(include), depth + 1)?);
+    }
+    // The includes of the file override those of the files it includes
+    merge(&mut merged, table.clone());
+    Ok(merged)
+}
+
+fn is_url(include: &str) -> bool {
+    include.starts_with("https://") || include.starts_with("http://")
+}
+
+/// Downloads the file at `url`, with `curl`.
+#[cfg(all(feature = "http", not(tarpaulin_include)))]
+fn fetch(url: &str) -> Result<String, Error> {
+    let output = std::process::Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", url])
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::Include(format!(
+            "could not fetch {}: {}",
+            url,
+            crate::utils::decode_output(output.stderr, "curl").trim_end()
+        )));
+    }
+    Ok(crate::utils::decode_output(output.stdout, "curl"))
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch(url: &str) -> Result<String, Error> {
+    Err(Error::Include(format!(
+        "{} can only be fetched by a cargo-scout built with the `http` feature",
+        url
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, merge, Source};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_merge() {
+        let mut base: toml::Table = toml::from_str(
+            "[presets.strict]\nwarn = [\"a\"]\nallow = [\"b\"]\n[dirty]\nreport-all = true\n",
+        )
+        .unwrap();
+        merge(
+            &mut base,
+            toml::from_str("[presets.strict]\nwarn = [\"c\"]\n[presets.local]\nwarn = []\n")
+                .unwrap(),
+        );
+        let expected: toml::Table = toml::from_str(
+            "[presets.strict]\nwarn = [\"c\"]\nallow = [\"b\"]\n\
+             [presets.local]\nwarn = []\n[dirty]\nreport-all = true\n",
+        )
+        .unwrap();
+        assert_eq!(expected, base);
+    }
+
+    #[test]
+    fn test_join() {
+        let file = Source::File(PathBuf::from("/repo/.scout.toml"));
+        assert_eq!(
+            Source::File(PathBuf::from("/repo/presets/strict.toml")),
+            file.join("presets/strict.toml")
+        );
+        let url = Source::Url("https://example.com/org/scout.toml".to_string());
+        assert_eq!(url, file.join("https://example.com/org/scout.toml"));
+        assert_eq!(
+            Source::Url("https://example.com/org/base.toml".to_string()),
+            url.join("./base.toml")
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("presets")).unwrap();
+        fs::write(
+            dir.path().join("presets/org.toml"),
+            "include = [\"base.toml\"]\n[presets.strict]\nwarn = [\"org\"]\nallow = [\"org\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("presets/base.toml"),
+            "[presets.strict]\nwarn = [\"base\"]\n[dirty]\nreport-all = true\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".scout.toml"),
+            "include = [\"presets/org.toml\"]\n[presets.strict]\nwarn = [\"local\"]\n",
+        )
+        .unwrap();
+        let expected: toml::Table = toml::from_str(
+            "include = [\"presets/org.toml\"]\n[presets.strict]\nwarn = [\"local\"]\nallow = [\"org\"]\n[dirty]\nreport-all = true\n",
+        )
+        .unwrap();
+        assert_eq!(expected, load(&dir.path().join(".scout.toml")).unwrap());
+
+        // A file including itself
+        fs::write(
+            dir.path().join(".scout.toml"),
+            "include = [\".scout.toml\"]\n",
+        )
+        .unwrap();
+        assert!(load(&dir.path().join(".scout.toml")).is_err());
+
+        // An included file needing a newer scout
+        fs::write(
+            dir.path().join("presets/base.toml"),
+            "schema-version = 99\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".scout.toml"),
+            "include = [\"presets/base.toml\"]\n",
+        )
+        .unwrap();
+        let error = load(&dir.path().join(".scout.toml")).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("base.toml has schema version 99"));
+    }
+
+    #[cfg(not(feature = "http"))]
+    #[test]
+    fn test_url_without_http() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".scout.toml"),
+            "include = [\"https://example.com/scout.toml\"]\n",
+        )
+        .unwrap();
+        let error = load(&dir.path().join(".scout.toml")).unwrap_err();
+        assert!(error.to_string().contains("`http` feature"));
+    }
+}
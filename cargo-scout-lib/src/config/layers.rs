@@ -0,0 +1,300 @@
+use super::scout::SCOUT_TOML;
+use super::{env, include};
+use crate::error::Error;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The configuration file of the machine.
+pub const SYSTEM_CONFIG: &str = "/etc/cargo-scout/config.toml";
+
+/// Where a configuration value comes from, from the lowest precedence to the highest.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum Origin {
+    /// The value scout uses when no file sets it
+    Default,
+    System(PathBuf),
+    /// `$XDG_CONFIG_HOME/cargo-scout/config.toml`
+    User(PathBuf),
+    /// The `.scout.toml` at the root of the repository
+    Repository(PathBuf),
+    /// The `.scout.toml` next to the workspace `Cargo.toml`
+    Workspace(PathBuf),
+    /// The `.scout.toml` of the current directory
+    CurrentDir(PathBuf),
+    /// A `SCOUT_<TABLE>__<KEY>` variable
+    Environment,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::System(path) => write!(f, "system ({})", path.display()),
+            Self::User(path) => write!(f, "user ({})", path.display()),
+            Self::Repository(path) => write!(f, "repository ({})", path.display()),
+            Self::Workspace(path) => write!(f, "workspace ({})", path.display()),
+            Self::CurrentDir(path) => write!(f, "current directory ({})", path.display()),
+            Self::Environment => write!(f, "environment"),
+        }
+    }
+}
+
+impl Origin {
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Self::System(path)
+            | Self::User(path)
+            | Self::Repository(path)
+            | Self::Workspace(path)
+            | Self::CurrentDir(path) => Some(path),
+            Self::Default | Self::Environment => None,
+        }
+    }
+}
+
+/// The directories the configuration files are looked up in.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Locations {
+    /// `$XDG_CONFIG_HOME`, or `$HOME/.config`
+    pub config_home: Option<PathBuf>,
+    pub repository: Option<PathBuf>,
+    pub workspace: PathBuf,
+    pub current_dir: Option<PathBuf>,
+}
+
+impl Locations {
+    /// The locations of the workspace at `workspace`, from the environment of the process.
+    #[must_use]
+    pub fn discover(workspace: &Path) -> Self {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|home| !home.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+        let repository = crate::vcs::git::open(workspace)
+            .ok()
+            .and_then(|repo| repo.workdir().map(Path::to_path_buf));
+        Self {
+            config_home,
+            repository,
+            workspace: workspace.to_path_buf(),
+            current_dir: std::env::current_dir().ok(),
+        }
+    }
+
+    /// The files that may configure scout, from the lowest precedence to the highest.
+    ///
+    /// A file is only listed once, with its highest precedence.
+    #[must_use]
+    pub fn files(&self) -> Vec<Origin> {
+        let mut files = vec![Origin::System(PathBuf::from(SYSTEM_CONFIG))];
+        if let Some(home) = &self.config_home {
+            files.push(Origin::User(home.join("cargo-scout").join("config.toml")));
+        }
+        if let Some(repository) = &self.repository {
+            files.push(Origin::Repository(repository.join(SCOUT_TOML)));
+        }
+        files.push(Origin::Workspace(self.workspace.join(SCOUT_TOML)));
+        if let Some(current_dir) = &self.current_dir {
+            files.push(Origin::CurrentDir(current_dir.join(SCOUT_TOML)));
+        }
+        let mut listed = Vec::new();
+        for origin in files.into_iter().rev() {
+            let path = origin.path().map(normalize);
+            if !listed
+                .iter()
+                .any(|listed: &Origin| listed.path().map(normalize) == path)
+            {
+                listed.push(origin);
+            }
+        }
+        listed.reverse();
+        listed
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The configuration merged from every file and the environment,
+/// with the origin of each of its values.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Layered {
+    pub table: toml::Table,
+    /// The origins of the values, by dotted key like `webhook.threshold`
+    pub origins: BTreeMap<String, Origin>,
+}
+
+impl Layered {
+    /// Merges the files of `locations` that exist, then the `SCOUT_` variables of `vars`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn load(
+        locations: &Locations,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, Error> {
+        let mut layered = Self::default();
+        for origin in locations.files() {
+            if let Some(path) = origin.path().filter(|path| path.is_file()) {
+                let table = include::load(path)?;
+                layered.merge(table, &origin);
+            }
+        }
+        layered.merge(env::overrides(vars), &Origin::Environment);
+        Ok(layered)
+    }
+
+    /// Merges `table` over the configuration, the values it sets coming from `origin`.
+    pub fn merge(&mut self, table: toml::Table, origin: &Origin) {
+        record(&table, "", origin, &mut self.origins);
+        include::merge(&mut self.table, table);
+    }
+
+    /// The `key = value  # origin` lines of the values of `table`, the effective configuration.
+    #[must_use]
+    pub fn annotate(&self, table: &toml::Table) -> Vec<String> {
+        flatten(table)
+            .into_iter()
+            .map(|(key, value)| format!("{} = {}  # {}", key, value, self.origin(&key)))
+            .collect()
+    }
+
+    /// The origin of the value at `key`, values no layer sets are defaults.
+    #[must_use]
+    pub fn origin(&self, key: &str) -> &Origin {
+        self.origins.get(key).unwrap_or(&Origin::Default)
+    }
+}
+
+fn record(
+    table: &toml::Table,
+    prefix: &str,
+    origin: &Origin,
+    origins: &mut BTreeMap<String, Origin>,
+) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(table) => record(table, &key, origin, origins),
+            _ => {
+                origins.insert(key, origin.clone());
+            }
+        }
+    }
+}
+
+/// The `key = value` lines of `table`, by dotted key, in the order of the keys.
+#[must_use]
+pub fn flatten(table: &toml::Table) -> Vec<(String, String)> {
+    let mut lines = Vec::new();
+    flatten_into(table, "", &mut lines);
+    lines
+}
+
+fn flatten_into(table: &toml::Table, prefix: &str, lines: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(table) if !table.is_empty() => flatten_into(table, &key, lines),
+            value => lines.push((key, value.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flatten, Layered, Locations, Origin};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_files() {
+        let locations = Locations {
+            config_home: Some(PathBuf::from("/home/me/.config")),
+            repository: Some(PathBuf::from("/repo")),
+            workspace: PathBuf::from("/repo"),
+            current_dir: Some(PathBuf::from("/repo/crates/foo")),
+        };
+        assert_eq!(
+            vec![
+                Origin::System(PathBuf::from("/etc/cargo-scout/config.toml")),
+                Origin::User(PathBuf::from("/home/me/.config/cargo-scout/config.toml")),
+                Origin::Workspace(PathBuf::from("/repo/.scout.toml")),
+                Origin::CurrentDir(PathBuf::from("/repo/crates/foo/.scout.toml")),
+            ],
+            locations.files()
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("home/cargo-scout")).unwrap();
+        fs::create_dir_all(root.join("repo/ws")).unwrap();
+        fs::write(
+            root.join("home/cargo-scout/config.toml"),
+            "[webhook]\nthreshold = 1\nurl = \"https://example.com\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("repo/.scout.toml"), "[webhook]\nthreshold = 2\n").unwrap();
+        fs::write(
+            root.join("repo/ws/.scout.toml"),
+            "[dirty]\nreport-all = true\n",
+        )
+        .unwrap();
+        let locations = Locations {
+            config_home: Some(root.join("home")),
+            repository: Some(root.join("repo")),
+            workspace: root.join("repo/ws"),
+            current_dir: None,
+        };
+        let layered = Layered::load(
+            &locations,
+            vec![(
+                "SCOUT_DIRTY__FILES".to_string(),
+                "[\"Cargo.lock\"]".to_string(),
+            )],
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                ("dirty.files".to_string(), "[\"Cargo.lock\"]".to_string()),
+                ("dirty.report-all".to_string(), "true".to_string()),
+                ("webhook.threshold".to_string(), "2".to_string()),
+                (
+                    "webhook.url".to_string(),
+                    "\"https://example.com\"".to_string()
+                ),
+            ],
+            flatten(&layered.table)
+        );
+        assert_eq!(&Origin::Environment, layered.origin("dirty.files"));
+        assert_eq!(
+            &Origin::Workspace(root.join("repo/ws/.scout.toml")),
+            layered.origin("dirty.report-all")
+        );
+        assert_eq!(
+            &Origin::Repository(root.join("repo/.scout.toml")),
+            layered.origin("webhook.threshold")
+        );
+        assert_eq!(
+            &Origin::User(root.join("home/cargo-scout/config.toml")),
+            layered.origin("webhook.url")
+        );
+        assert_eq!(&Origin::Default, layered.origin("generated.enabled"));
+        let table: toml::Table = toml::from_str("[generated]\nenabled = true\n").unwrap();
+        assert_eq!(
+            vec!["generated.enabled = true  # default"],
+            layered.annotate(&table)
+        );
+    }
+}
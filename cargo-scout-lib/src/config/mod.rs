@@ -1,6 +1,29 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+pub mod check;
+pub mod env;
+pub mod grace;
+pub mod graph;
+pub mod include;
+pub mod layers;
+pub mod owners;
+pub mod preset;
 pub mod rust;
+pub mod schema;
+pub mod scout;
+
+/// Returns the name of a workspace member,
+/// which is the last segment of its path.
+///
+/// ```
+/// # use cargo_scout_lib::config::member_name;
+/// assert_eq!(Some("bar"), member_name("foo/bar"));
+/// assert_eq!(Some("foo"), member_name("foo"));
+/// ```
+#[must_use]
+pub fn member_name(member: &str) -> Option<&str> {
+    Path::new(member).file_name().and_then(|f| f.to_str())
+}
 
 /// This trait is responsible for providing a list of members,
 /// which are directories to be linted against.
@@ -31,6 +54,9 @@ pub trait Config {
     /// #    fn members(&self) -> Vec<String> {
     /// #        vec![".".to_string()]
     /// #    }
+    /// #    fn root(&self) -> &std::path::PathBuf {
+    /// #        unimplemented!()
+    /// #    }
     /// # }
     /// let config = CustomConfig::new();
     /// // Only the current directory must be linted
@@ -51,6 +77,9 @@ pub trait Config {
     /// #    fn members(&self) -> Vec<String> {
     /// #        vec!["foo".to_string(), "bar".to_string()]
     /// #    }
+    /// #    fn root(&self) -> &std::path::PathBuf {
+    /// #        unimplemented!()
+    /// #    }
     /// # }
     /// let config = CustomConfig::new();
     /// // Directories ./foo and ./bar must be linted
@@ -60,6 +89,7 @@ pub trait Config {
     /// # Implementing your own Config
     /// ```
     /// use cargo_scout_lib::config::Config;
+    /// use std::path::PathBuf;
     ///
     /// struct CustomConfig{}
     ///
@@ -74,10 +104,24 @@ pub trait Config {
     ///        // directories to iterate on goes here
     ///        # vec![".".to_string()]
     ///    }
+    ///
+    ///    fn root(&self) -> &PathBuf {
+    ///        // The workspace root the members are relative to
+    ///        # unimplemented!()
+    ///    }
     /// }
     /// ```
     fn members(&self) -> Vec<String>;
 
+    /// This function should return the members that are linted
+    /// when no diff is used to select them.
+    ///
+    /// It mirrors cargo's `workspace.default-members`,
+    /// and defaults to every member.
+    fn default_members(&self) -> Vec<String> {
+        self.members()
+    }
+
     /// returns the workspace root
     fn root(&self) -> &PathBuf;
 }
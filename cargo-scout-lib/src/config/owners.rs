@@ -0,0 +1,170 @@
+use crate::error::Error;
+use crate::scout::build_output::glob_match;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Where GitHub looks for the `CODEOWNERS` file, relative to the repository root, in order.
+pub const LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// The policy profiles of the teams owning the changed files, by their `CODEOWNERS` entry.
+///
+/// The lints of a file owned by a team with a profile are those the preset of the profile reports,
+/// the lints of the other files are reported as usual.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct OwnersConfig {
+    /// The `CODEOWNERS` file, relative to the workspace root, looked up like GitHub does by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// The preset of each team, like `"@org/team-a" = "strict"`
+    pub profiles: BTreeMap<String, String>,
+}
+
+/// The rules of a `CODEOWNERS` file, the last matching rule of a path giving its owners.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct CodeOwners {
+    /// The directory the patterns are relative to
+    root: PathBuf,
+    rules: Vec<(String, Vec<String>)>,
+}
+
+impl CodeOwners {
+    #[must_use]
+    pub fn parse(source: &str, root: impl Into<PathBuf>) -> Self {
+        let rules = source
+            .lines()
+            .filter_map(|line| {
+                let line = line.split('#').next().unwrap_or_default();
+                let mut words = line.split_whitespace();
+                let pattern = words.next()?;
+                Some((
+                    pattern.to_string(),
+                    words.map(ToString::to_string).collect(),
+                ))
+            })
+            .collect();
+        Self {
+            root: root.into(),
+            rules,
+        }
+    }
+
+    /// Reads `file`, relative to `workspace_root`, or the first file of `LOCATIONS`
+    /// in the workspace root or one of its parents.
+    ///
+    /// The patterns of a file of a `.github` or `docs` directory are relative to its parent.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn discover(file: Option<&Path>, workspace_root: &Path) -> Result<Option<Self>, Error> {
+        let path = match file {
+            Some(file) => Some(workspace_root.join(file)),
+            None => workspace_root.ancestors().find_map(|dir| {
+                LOCATIONS
+                    .iter()
+                    .map(|location| dir.join(location))
+                    .find(|path| path.is_file())
+            }),
+        };
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        let mut root = path.parent().unwrap_or(workspace_root);
+        if root.ends_with(".github") || root.ends_with("docs") {
+            root = root.parent().unwrap_or(root);
+        }
+        Ok(Some(Self::parse(&std::fs::read_to_string(&path)?, root)))
+    }
+
+    /// The owners of `path`, absolute or relative to the root of the file.
+    #[must_use]
+    pub fn owners(&self, path: &str) -> &[String] {
+        let path = crate::utils::relative_path(path, &self.root).replace('\\', "/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| matches(pattern, &path))
+            .map_or(&[], |(_, owners)| owners)
+    }
+}
+
+/// Whether the `CODEOWNERS` `pattern` matches `path`, with the rules of gitignore:
+/// patterns with a leading or middle slash are relative to the root, the others match at any depth,
+/// and a pattern naming a directory matches its files, but `docs/*` doesn't match `docs/a/b.md`.
+fn matches(pattern: &str, path: &str) -> bool {
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    let (pattern, directory) = match pattern.strip_suffix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let pattern = if anchored {
+        pattern.to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+    (!directory && glob_match(&pattern, path))
+        || (!pattern.ends_with('*') && glob_match(&format!("{}/**", pattern), path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matches, CodeOwners};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*", "src/lib.rs"));
+        assert!(matches("*.rs", "src/lib.rs"));
+        assert!(matches("/src/", "src/api/mod.rs"));
+        assert!(!matches("/src/", "crates/src/lib.rs"));
+        assert!(matches("legacy/", "crates/legacy/src/lib.rs"));
+        assert!(matches("crates/legacy", "crates/legacy/src/lib.rs"));
+        assert!(!matches("crates/legacy", "vendor/crates/legacy/lib.rs"));
+        assert!(matches("docs/*", "docs/a.md"));
+        assert!(!matches("docs/*", "docs/a/b.md"));
+        assert!(!matches("lib.rs", "src/lib.rs.orig"));
+    }
+
+    #[test]
+    fn test_owners() {
+        let owners = CodeOwners::parse(
+            "# Default owners\n\
+             *       @org/core\n\
+             \n\
+             /crates/legacy/ @org/team-legacy @alice # inherited\n\
+             *.md\n",
+            "/repo",
+        );
+        assert_eq!(["@org/core"], owners.owners("/repo/crates/api/src/lib.rs"));
+        assert_eq!(
+            ["@org/team-legacy", "@alice"],
+            owners.owners("crates/legacy/src/lib.rs")
+        );
+        // The last matching rule wins, even without owners
+        assert!(owners.owners("/repo/crates/legacy/README.md").is_empty());
+    }
+
+    #[test]
+    fn test_discover() -> Result<(), crate::error::Error> {
+        let repo = TempDir::new()?;
+        fs::create_dir_all(repo.path().join(".github"))?;
+        fs::create_dir_all(repo.path().join("crates/app"))?;
+        fs::write(repo.path().join(".github/CODEOWNERS"), "/crates/ @org/a\n")?;
+
+        let owners = CodeOwners::discover(None, &repo.path().join("crates/app"))?.unwrap();
+        assert_eq!(
+            ["@org/a"],
+            owners.owners(&repo.path().join("crates/app/src/main.rs").to_string_lossy())
+        );
+
+        fs::write(repo.path().join("crates/app/OWNERS"), "* @org/b\n")?;
+        let owners =
+            CodeOwners::discover(Some("OWNERS".as_ref()), &repo.path().join("crates/app"))?
+                .unwrap();
+        assert_eq!(["@org/b"], owners.owners("src/main.rs"));
+
+        assert_eq!(None, CodeOwners::discover(None, TempDir::new()?.path())?);
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use crate::linter::Lint;
+use crate::scout::build_output::glob_match;
+use serde::{Deserialize, Serialize};
+
+/// The names of the presets scout defines, which `.scout.toml` presets can override.
+pub const BUILTIN: &[&str] = &["security"];
+
+/// A named set of lints, replacing the `clippy::all` and `clippy::pedantic` lints scout enables.
+///
+/// Only the lints a preset warns about are reported.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct Preset {
+    /// The lints passed to clippy with `-W`
+    pub warn: Vec<String>,
+    /// The lints and lint groups passed to clippy with `-A`
+    pub allow: Vec<String>,
+}
+
+impl Preset {
+    /// The preset named `name` that scout defines, if any.
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "security" => Some(Self::security()),
+            _ => None,
+        }
+    }
+
+    /// Lints that may panic, overflow or break memory safety, without the style lints.
+    ///
+    /// Test code is only linted with `--tests` or `--all-targets`,
+    /// so the panicking lints are about non-test code.
+    /// `unsafe_code` catches every `mem::transmute`, which needs an `unsafe` block.
+    #[must_use]
+    pub fn security() -> Self {
+        let strings = |lints: &[&str]| lints.iter().map(ToString::to_string).collect();
+        Self {
+            warn: strings(&[
+                "clippy::unwrap_used",
+                "clippy::expect_used",
+                "clippy::panic",
+                "clippy::arithmetic_side_effects",
+                "unsafe_code",
+                "clippy::transmute_ptr_to_ptr",
+                "clippy::transmute_undefined_repr",
+            ]),
+            allow: strings(&[
+                "clippy::style",
+                "clippy::complexity",
+                "clippy::perf",
+                "clippy::pedantic",
+            ]),
+        }
+    }
+
+    /// The lint flags passed to clippy, after `--`.
+    #[must_use]
+    pub fn lint_flags(&self) -> Vec<&str> {
+        let mut flags = Vec::new();
+        for lint in &self.allow {
+            flags.extend(["-A", lint.as_str()]);
+        }
+        for lint in &self.warn {
+            flags.extend(["-W", lint.as_str()]);
+        }
+        flags
+    }
+
+    /// Whether `lint` is one of the lints the preset warns about.
+    #[must_use]
+    pub fn reports(&self, lint: &Lint) -> bool {
+        lint.code
+            .as_ref()
+            .is_some_and(|code| self.warn.iter().any(|pattern| glob_match(pattern, code)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preset;
+    use crate::linter::Lint;
+
+    #[test]
+    fn test_lint_flags() {
+        let preset = Preset {
+            warn: vec!["clippy::unwrap_used".to_string()],
+            allow: vec!["clippy::style".to_string()],
+        };
+        assert_eq!(
+            vec!["-A", "clippy::style", "-W", "clippy::unwrap_used"],
+            preset.lint_flags()
+        );
+    }
+
+    #[test]
+    fn test_reports() {
+        let lint = |code: Option<&str>| Lint {
+            code: code.map(str::to_string),
+            ..Lint::default()
+        };
+        let preset = Preset::builtin("security").unwrap();
+        assert!(preset.reports(&lint(Some("clippy::unwrap_used"))));
+        assert!(preset.reports(&lint(Some("unsafe_code"))));
+        assert!(!preset.reports(&lint(Some("clippy::needless_borrow"))));
+        assert!(!preset.reports(&lint(None)));
+        assert_eq!(None, Preset::builtin("strict"));
+    }
+}
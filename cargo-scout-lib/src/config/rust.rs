@@ -1,25 +1,25 @@
-use cargo_scout_macros::warn;
-
-use crate::config::Config;
-use colored::Colorize;
-use std::{
-    path::{Path, PathBuf},
-    str::FromStr,
-};
+use crate::config::scout::WorkspacesConfig;
+use crate::config::{member_name, Config};
+use crate::scout::build_output::glob_match;
+use crate::utils::relative_path;
+use std::path::{Path, PathBuf};
 
 /// This struct represents a Cargo project configuration.
 pub struct CargoConfig {
     root: PathBuf,
     members: Vec<String>,
+    default_members: Vec<String>,
 }
 
 impl Config for CargoConfig {
-    #[must_use]
     fn members(&self) -> Vec<String> {
         self.members.clone()
     }
 
-    #[must_use]
+    fn default_members(&self) -> Vec<String> {
+        self.default_members.clone()
+    }
+
     fn root(&self) -> &PathBuf {
         &self.root
     }
@@ -37,7 +37,7 @@ impl CargoConfig {
     /// ```
     /// # use cargo_scout_lib::config::Config;
     /// # use cargo_scout_lib::config::rust::CargoConfig;
-    /// let config = CargoConfig::from_manifest_path("Cargo.toml")?;
+    /// let config = CargoConfig::from_manifest_path("./Cargo.toml", &[])?;
     /// // There is only one directory to lint, which is the current one.
     /// assert_eq!(vec!["."], config.members());
     /// # Ok::<(), cargo_scout_lib::Error>(())
@@ -47,9 +47,16 @@ impl CargoConfig {
     /// ```
     /// # use cargo_scout_lib::config::Config;
     /// # use cargo_scout_lib::config::rust::CargoConfig;
-    /// let config = CargoConfig::from_manifest_path("../Cargo.toml")?;
-    /// // We will lint `./cargo-scout` and `./cargo-scout-lib`.
-    /// assert_eq!(vec!["cargo-scout".to_string(), "cargo-scout-lib".to_string()], config.members());
+    /// let config = CargoConfig::from_manifest_path("../Cargo.toml", &[])?;
+    /// // We will lint `./cargo-scout`, `./cargo-scout-lib` and `./cargo-scout-macros`.
+    /// assert_eq!(
+    ///     vec![
+    ///         "cargo-scout".to_string(),
+    ///         "cargo-scout-lib".to_string(),
+    ///         "cargo-scout-macros".to_string()
+    ///     ],
+    ///     config.members()
+    /// );
     /// # Ok::<(), cargo_scout_lib::Error>(())
     /// ```
     #[allow(clippy::missing_errors_doc)]
@@ -57,75 +64,398 @@ impl CargoConfig {
         p: impl AsRef<Path> + Clone,
         only_members: &[String],
     ) -> Result<Self, crate::error::Error> {
-        Ok(Self::from_manifest(
-            p.clone(),
-            cargo_toml::Manifest::from_path(p)?,
-            only_members,
-        ))
+        Self::from_manifest(p.clone(), cargo_toml::Manifest::from_path(p)?, only_members)
+    }
+
+    /// Finds the manifest cargo uses when run from `dir`:
+    /// the workspace manifest above the closest `Cargo.toml`, or that `Cargo.toml` itself.
+    ///
+    /// Lint paths are relative to that workspace root, wherever scout is run from.
+    #[must_use]
+    pub fn discover_manifest(dir: &Path) -> Option<PathBuf> {
+        let mut manifests = dir
+            .ancestors()
+            .map(|ancestor| ancestor.join("Cargo.toml"))
+            .filter(|manifest| manifest.is_file());
+        let closest = manifests.next()?;
+        if is_workspace_manifest(&closest) {
+            return Some(closest);
+        }
+        manifests
+            .find(|manifest| is_workspace_manifest(manifest))
+            .or(Some(closest))
+    }
+
+    /// Finds the workspaces of a repository holding several of them,
+    /// and returns the path of their manifests.
+    ///
+    /// Manifests with a `[workspace]` are workspace roots,
+    /// and so are the packages outside of any of them.
+    /// Hidden and `target` directories are skipped.
+    #[must_use]
+    pub fn discover_workspaces(repo_root: &Path, config: &WorkspacesConfig) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        find_workspaces(repo_root, false, &mut roots);
+        roots.sort();
+        roots
+            .into_iter()
+            .filter(|manifest| {
+                let dir = manifest.parent().unwrap_or(repo_root);
+                config.includes(&relative_path(&dir.to_string_lossy(), repo_root))
+            })
+            .collect()
     }
 
     fn from_manifest(
         p: impl AsRef<Path>,
         m: cargo_toml::Manifest,
         only_members: &[String],
-    ) -> Self {
+    ) -> Result<Self, crate::error::Error> {
         if let Some(w) = m.workspace {
-            Self {
-                root: std::fs::canonicalize(p.as_ref().parent().unwrap())
-                    .unwrap()
-                    .to_path_buf(),
-                members: w
-                    .members
-                    .into_iter()
-                    .filter(|m| {
-                        if only_members.is_empty() {
-                            return true;
-                        }
-                        // return the last path segment as the member name
-                        let Ok(pb) = PathBuf::from_str(m) else {
-                            warn!("failed to parse member name {} in predicate", m);
-                            return false;
-                        };
-                        let Some(final_path_seg) = pb.file_name().and_then(|f| f.to_str()) else {
-                            warn!("failed to convert member {} pathbuf to str", m);
-                            return false;
-                        };
-
-                        // filter by the last named segment of the workspace member--- the manifest folder
-                        only_members.contains(&final_path_seg.to_owned())
-                    })
-                    .collect(),
+            let root = std::fs::canonicalize(p.as_ref().parent().unwrap()).unwrap();
+            let mut members = workspace_members(&root, &w.members, &w.exclude);
+            if !only_members.is_empty() {
+                let mut selected = Vec::new();
+                for selection in only_members {
+                    selected.extend(select_members(&root, &members, selection)?);
+                }
+                members.retain(|m| selected.contains(m));
             }
+            // Explicitly selected members take precedence over the default ones,
+            // and cargo lints every member if there are no default ones.
+            let default_members = if only_members.is_empty() && !w.default_members.is_empty() {
+                w.default_members
+            } else {
+                members.clone()
+            };
+            Ok(Self {
+                root,
+                members,
+                default_members,
+            })
         } else {
-            Self {
+            Ok(Self {
                 root: std::fs::canonicalize(p.as_ref().parent().unwrap())
                     .unwrap()
                     .to_path_buf(),
                 // Project root only
                 members: vec![".".to_string()],
-            }
+                default_members: vec![".".to_string()],
+            })
+        }
+    }
+}
+
+/// Returns the members `selection` refers to, in order of precedence:
+/// the member at that relative path, the member with that package name,
+/// or the member whose directory has that name.
+///
+/// Several members sharing the package or directory name are an error.
+fn select_members(
+    root: &Path,
+    members: &[String],
+    selection: &str,
+) -> Result<Vec<String>, crate::error::Error> {
+    let path = selection.trim_start_matches("./").trim_end_matches('/');
+    if let Some(member) = members.iter().find(|m| m.as_str() == path) {
+        return Ok(vec![member.clone()]);
+    }
+    let by_package: Vec<&String> = members
+        .iter()
+        .filter(|m| package_name(&root.join(m)).as_deref() == Some(selection))
+        .collect();
+    let matched = if by_package.is_empty() {
+        members
+            .iter()
+            .filter(|m| member_name(m) == Some(selection))
+            .collect()
+    } else {
+        by_package
+    };
+    if matched.len() > 1 {
+        return Err(crate::error::Error::AmbiguousMember(
+            selection.to_string(),
+            matched
+                .iter()
+                .map(|m| m.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+    Ok(matched.into_iter().cloned().collect())
+}
+
+/// The name of the package in `dir`, from its manifest.
+fn package_name(dir: &Path) -> Option<String> {
+    let source = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    let table = source.parse::<toml::Table>().ok()?;
+    table
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(ToString::to_string)
+}
+
+/// Expands the glob patterns of `workspace.members`, the way cargo does.
+///
+/// Members under a `workspace.exclude` path are dropped,
+/// and so are nested workspaces, which are their own roots.
+/// A member matched by several patterns is only linted once.
+fn workspace_members(root: &Path, patterns: &[String], exclude: &[String]) -> Vec<String> {
+    let mut members = Vec::new();
+    for pattern in patterns {
+        if pattern.contains(['*', '?']) {
+            let segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+            let mut matched = Vec::new();
+            expand_member(root, String::new(), &segments, &mut matched);
+            matched.sort();
+            members.extend(matched);
+        } else {
+            members.push(pattern.trim_end_matches('/').to_string());
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    members
+        .into_iter()
+        .filter(|member| {
+            !exclude.iter().any(|excluded| {
+                let excluded = excluded.trim_end_matches('/');
+                member == excluded || member.starts_with(&format!("{}/", excluded))
+            })
+        })
+        .filter(|member| {
+            member == "." || !is_workspace_manifest(&root.join(member).join("Cargo.toml"))
+        })
+        .filter(|member| seen.insert(member.clone()))
+        .collect()
+}
+
+/// Collects the directories with a `Cargo.toml` matching the glob `segments` under `prefix`.
+fn expand_member(root: &Path, prefix: String, segments: &[&str], matched: &mut Vec<String>) {
+    let Some((segment, rest)) = segments.split_first() else {
+        if root.join(&prefix).join("Cargo.toml").is_file() {
+            matched.push(prefix);
+        }
+        return;
+    };
+    let join = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    };
+    if !segment.contains(['*', '?']) {
+        expand_member(root, join(segment), rest, matched);
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(root.join(&prefix)) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type().is_ok_and(|t| t.is_dir()) && glob_match(segment, &name) {
+            expand_member(root, join(&name), rest, matched);
+        }
+    }
+}
+
+/// Collects the workspace roots under `dir`, `in_workspace` when a parent directory is one.
+fn find_workspaces(dir: &Path, in_workspace: bool, roots: &mut Vec<PathBuf>) {
+    let manifest = dir.join("Cargo.toml");
+    let mut in_workspace = in_workspace;
+    if manifest.is_file() {
+        if is_workspace_manifest(&manifest) {
+            roots.push(manifest);
+            in_workspace = true;
+        } else if !in_workspace {
+            roots.push(manifest);
+        }
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            find_workspaces(&entry.path(), in_workspace, roots);
         }
     }
 }
 
+fn is_workspace_manifest(manifest: &Path) -> bool {
+    std::fs::read_to_string(manifest)
+        .ok()
+        .and_then(|source| source.parse::<toml::Table>().ok())
+        .is_some_and(|table| table.contains_key("workspace"))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::config::rust::CargoConfig;
+    use crate::config::scout::WorkspacesConfig;
     use crate::config::Config;
+    use crate::error::Error;
+    use std::fs;
 
     #[test]
     fn test_not_workspace_manifest() {
         let manifest = cargo_toml::Manifest::from_path("Cargo.toml").unwrap();
         // Make sure we actually parsed the manifest
         assert_eq!("cargo-scout-lib", manifest.clone().package.unwrap().name);
-        let config = CargoConfig::from_manifest(manifest, &[]);
+        let config = CargoConfig::from_manifest("./Cargo.toml", manifest, &[]).unwrap();
         assert_eq!(vec!["."], config.members());
     }
     #[test]
     fn test_not_workspace_path() {
-        let config = CargoConfig::from_manifest_path("Cargo.toml", &[]).unwrap();
+        let config = CargoConfig::from_manifest_path("./Cargo.toml", &[]).unwrap();
         assert_eq!(vec!["."], config.members());
     }
+    #[test]
+    fn test_discover_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("member/src/nested")).unwrap();
+        fs::create_dir_all(root.join("standalone/src")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("member/Cargo.toml"),
+            "[package]\nname = \"member\"\n",
+        )
+        .unwrap();
+
+        // Wherever scout is run from, the workspace manifest is used
+        for dir in &[
+            root.to_path_buf(),
+            root.join("member"),
+            root.join("member/src/nested"),
+        ] {
+            assert_eq!(
+                Some(root.join("Cargo.toml")),
+                CargoConfig::discover_manifest(dir)
+            );
+        }
+
+        let other = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(other.path().join("src")).unwrap();
+        fs::write(other.path().join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+        assert_eq!(
+            Some(other.path().join("Cargo.toml")),
+            CargoConfig::discover_manifest(&other.path().join("src"))
+        );
+    }
+
+    #[test]
+    fn test_discover_workspaces() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        for (path, manifest) in &[
+            ("services/api", "[workspace]\nmembers = [\"server\"]\n"),
+            ("services/api/server", "[package]\nname = \"server\"\n"),
+            ("services/legacy", "[workspace]\n"),
+            ("tools/lint", "[package]\nname = \"lint\"\n"),
+            ("target/debug/build", "[workspace]\n"),
+            (".cargo/registry", "[workspace]\n"),
+        ] {
+            fs::create_dir_all(root.join(path)).unwrap();
+            fs::write(root.join(path).join("Cargo.toml"), manifest).unwrap();
+        }
+
+        // Members of a workspace aren't roots, and neither are build directories
+        assert_eq!(
+            vec![
+                root.join("services/api/Cargo.toml"),
+                root.join("services/legacy/Cargo.toml"),
+                root.join("tools/lint/Cargo.toml"),
+            ],
+            CargoConfig::discover_workspaces(root, &WorkspacesConfig::default())
+        );
+        let config = WorkspacesConfig {
+            include: vec!["services/*".to_string()],
+            exclude: vec!["services/legacy".to_string()],
+        };
+        assert_eq!(
+            vec![root.join("services/api/Cargo.toml")],
+            CargoConfig::discover_workspaces(root, &config)
+        );
+    }
+
+    #[test]
+    fn test_member_globs_and_excludes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        for (path, manifest) in &[
+            ("crates/a", "[package]\nname = \"a\"\n"),
+            ("crates/b", "[package]\nname = \"b\"\n"),
+            ("crates/old", "[package]\nname = \"old\"\n"),
+            ("crates/nested", "[workspace]\nmembers = [\"inner\"]\n"),
+            ("tools/cli", "[package]\nname = \"cli\"\n"),
+        ] {
+            fs::create_dir_all(root.join(path)).unwrap();
+            fs::write(root.join(path).join("Cargo.toml"), manifest).unwrap();
+        }
+        fs::create_dir_all(root.join("crates/docs")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\", \"crates/a\", \"tools/cli\"]\nexclude = [\"crates/old\"]\n",
+        )
+        .unwrap();
+
+        // Directories without a manifest, excluded and nested workspaces aren't members,
+        // and `crates/a` is only linted once.
+        let config = CargoConfig::from_manifest_path(root.join("Cargo.toml"), &[]).unwrap();
+        assert_eq!(vec!["crates/a", "crates/b", "tools/cli"], config.members());
+    }
+
+    #[test]
+    fn test_select_members() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        for (path, name) in &[("a/core", "a-core"), ("b/core", "b-core"), ("tools", "cli")] {
+            fs::create_dir_all(root.join(path)).unwrap();
+            fs::write(
+                root.join(path).join("Cargo.toml"),
+                format!("[package]\nname = \"{}\"\n", name),
+            )
+            .unwrap();
+        }
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"a/core\", \"b/core\", \"tools\"]\n",
+        )
+        .unwrap();
+        let select = |members: &[&str]| {
+            let members: Vec<String> = members.iter().map(ToString::to_string).collect();
+            CargoConfig::from_manifest_path(root.join("Cargo.toml"), &members)
+                .map(|config| config.members())
+        };
+
+        // By relative path, package name, or directory name
+        assert_eq!(vec!["b/core"], select(&["./b/core/"]).unwrap());
+        assert_eq!(vec!["a/core"], select(&["a-core"]).unwrap());
+        assert_eq!(
+            vec!["a/core", "tools"],
+            select(&["tools", "a/core"]).unwrap()
+        );
+        assert_eq!(vec!["tools"], select(&["cli"]).unwrap());
+        assert!(select(&["missing"]).unwrap().is_empty());
+        match select(&["core"]) {
+            Err(Error::AmbiguousMember(selection, members)) => {
+                assert_eq!("core", selection);
+                assert_eq!("a/core, b/core", members);
+            }
+            other => panic!("expected an ambiguous member, got {:?}", other.err()),
+        }
+    }
+
     #[test]
     fn test_neqo_members_manifest() {
         let neqo_toml = r#"[workspace]
@@ -143,7 +473,7 @@ mod tests {
         ]"#;
 
         let manifest = cargo_toml::Manifest::from_slice(neqo_toml.as_bytes()).unwrap();
-        let config = CargoConfig::from_manifest(manifest);
+        let config = CargoConfig::from_manifest("./Cargo.toml", manifest, &[]).unwrap();
         assert_eq!(
             vec![
                 "neqo-client",
@@ -160,4 +490,32 @@ mod tests {
             config.members()
         );
     }
+
+    #[test]
+    fn test_default_members() {
+        let toml = r#"[workspace]
+        members = ["foo", "bar", "nested/baz"]
+        default-members = ["nested/baz"]"#;
+
+        let manifest = cargo_toml::Manifest::from_slice(toml.as_bytes()).unwrap();
+        let config = CargoConfig::from_manifest("./Cargo.toml", manifest.clone(), &[]).unwrap();
+        assert_eq!(vec!["foo", "bar", "nested/baz"], config.members());
+        assert_eq!(vec!["nested/baz"], config.default_members());
+
+        // Explicitly selected members override the default ones
+        let config =
+            CargoConfig::from_manifest("./Cargo.toml", manifest, &["foo".to_string()]).unwrap();
+        assert_eq!(vec!["foo"], config.members());
+        assert_eq!(vec!["foo"], config.default_members());
+    }
+
+    #[test]
+    fn test_no_default_members() {
+        let toml = r#"[workspace]
+        members = ["foo", "bar"]"#;
+
+        let manifest = cargo_toml::Manifest::from_slice(toml.as_bytes()).unwrap();
+        let config = CargoConfig::from_manifest("./Cargo.toml", manifest, &[]).unwrap();
+        assert_eq!(config.members(), config.default_members());
+    }
 }
@@ -0,0 +1,95 @@
+use crate::error::Error;
+use std::convert::TryFrom;
+use toml_edit::DocumentMut;
+
+/// The version of the `.scout.toml` layout this version of scout reads and writes.
+///
+/// Files without a `schema-version` predate it, and have the layout of version 1.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The key recording the schema version of a file.
+pub const KEY: &str = "schema-version";
+
+/// The schema version of a file, `None` if it doesn't record one.
+///
+/// Fails with a hint when the file read from `origin` needs another version of scout.
+#[allow(clippy::missing_errors_doc)]
+pub fn check(table: &toml::Table, origin: &str) -> Result<Option<u32>, Error> {
+    let Some(version) = table.get(KEY) else {
+        return Ok(None);
+    };
+    let version = version
+        .as_integer()
+        .ok_or_else(|| Error::Schema(format!("`{}` of {} must be an integer", KEY, origin)))?;
+    match u32::try_from(version) {
+        Ok(version) if (1..=SCHEMA_VERSION).contains(&version) => Ok(Some(version)),
+        Ok(version) if version > SCHEMA_VERSION => Err(Error::Schema(format!(
+            "{} has schema version {}, but cargo-scout {} reads up to version {}, upgrade cargo-scout",
+            origin,
+            version,
+            crate::VERSION,
+            SCHEMA_VERSION
+        ))),
+        _ => Err(Error::Schema(format!(
+            "{} has no schema version {}, run `cargo scout config migrate` to upgrade it",
+            origin, version
+        ))),
+    }
+}
+
+/// Upgrades the contents of a `.scout.toml` file to `SCHEMA_VERSION`, keeping its comments
+/// and formatting, `None` if it is up to date.
+#[allow(clippy::missing_errors_doc)]
+pub fn migrate(source: &str) -> Result<Option<String>, Error> {
+    let mut document: DocumentMut = source
+        .parse()
+        .map_err(|error: toml_edit::TomlError| Error::Schema(error.to_string()))?;
+    let version = document.get(KEY).and_then(toml_edit::Item::as_integer);
+    if version == Some(i64::from(SCHEMA_VERSION)) {
+        return Ok(None);
+    }
+    if version.is_some_and(|version| version > i64::from(SCHEMA_VERSION)) {
+        check(&toml::from_str(source)?, "the file")?;
+    }
+    // There is a single schema yet, the keys of older ones would be rewritten here
+    if document.contains_key(KEY) {
+        document[KEY] = toml_edit::value(i64::from(SCHEMA_VERSION));
+        return Ok(Some(document.to_string()));
+    }
+    Ok(Some(format!("{} = {}\n{}", KEY, SCHEMA_VERSION, source)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, migrate, SCHEMA_VERSION};
+    use crate::error::Error;
+
+    #[test]
+    fn test_check() {
+        let table = |source: &str| -> toml::Table { toml::from_str(source).unwrap() };
+        assert_eq!(None, check(&table(""), ".scout.toml").unwrap());
+        assert_eq!(
+            Some(SCHEMA_VERSION),
+            check(&table("schema-version = 1"), ".scout.toml").unwrap()
+        );
+        let newer = check(&table("schema-version = 99"), ".scout.toml").unwrap_err();
+        assert!(matches!(&newer, Error::Schema(hint) if hint.contains("upgrade cargo-scout")));
+        let invalid = check(&table("schema-version = 0"), ".scout.toml").unwrap_err();
+        assert!(invalid.to_string().contains("cargo scout config migrate"));
+        assert!(check(&table("schema-version = \"1\""), ".scout.toml").is_err());
+    }
+
+    #[test]
+    fn test_migrate() -> Result<(), Error> {
+        let source = "# Shared lints\n[presets.strict]\nwarn = [\"clippy::unwrap_used\"]\n";
+        let migrated = migrate(source)?.unwrap();
+        assert_eq!(format!("schema-version = 1\n{}", source), migrated);
+        assert_eq!(None, migrate(&migrated)?);
+        assert_eq!(
+            Some("schema-version = 1\n".to_string()),
+            migrate("schema-version = 0\n")?
+        );
+        assert!(migrate("schema-version = 99\n").is_err());
+        Ok(())
+    }
+}
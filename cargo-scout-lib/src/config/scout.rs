@@ -0,0 +1,1076 @@
+use super::grace::GraceRule;
+use super::layers::{Layered, Locations};
+use super::owners::OwnersConfig;
+use super::preset::Preset;
+use crate::error::Error;
+use crate::linter::fingerprint::Fnv1a;
+use crate::linter::Lint;
+use crate::scout::build_output::glob_match;
+use crate::scout::targets::TargetKind;
+use crate::utils::relative_path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The name of the file cargo-scout reads its own settings from.
+///
+/// It is looked up next to the `Cargo.toml` being scouted.
+pub const SCOUT_TOML: &str = ".scout.toml";
+
+/// This struct represents the contents of a `.scout.toml` file.
+///
+/// The files of the top-level `include` array are read first, see `include::load`.
+///
+/// # Example
+/// ```toml
+/// include = ["https://example.com/org-scout-preset.toml", "presets/strict.toml"]
+///
+/// [members.my-crate]
+/// manifest-path = "crates/my-crate/Cargo.toml"
+/// cwd = "crates"
+/// extra-args = ["--locked", "--no-default-features"]
+/// lint-args = ["-A", "clippy::module_name_repetitions"]
+///
+/// [generated]
+/// markers = ["@generated", "Code generated by"]
+///
+/// [dirty]
+/// report-all = true
+///
+/// [relevance]
+/// exclude = ["**/testdata/**", "tests/golden/**"]
+///
+/// [invalidators]
+/// files = ["rust-toolchain.toml", "**/clippy.toml", "lints.toml"]
+///
+/// [feature-matrix]
+/// sets = [[], ["serde"], ["serde", "async"]]
+///
+/// [dependents]
+/// lints = ["deprecated", "clippy::*"]
+///
+/// [test-code]
+/// skip = true
+/// lints = ["clippy::*"]
+///
+/// [targets]
+/// example = "info"
+/// bench = "info"
+///
+/// [ranking]
+/// deny = ["clippy::unwrap_used", "clippy::todo"]
+/// boundary = 0
+///
+/// [[grace]]
+/// lints = ["clippy::unwrap_used"]
+/// added = "2026-10-01"
+/// days = 30
+///
+/// [[build-outputs]]
+/// inputs = ["proto/**/*.proto"]
+/// outputs = ["*.rs"]
+///
+/// [[padding]]
+/// lints = ["missing_docs", "clippy::missing_*_doc"]
+/// enclosing-item = true
+///
+/// path-map = [{ from = "/workspace", to = "." }]
+///
+/// [workspaces]
+/// exclude = ["vendor/**"]
+///
+/// [markdown]
+/// code-host = "gitlab"
+/// link-prefix = "https://gitlab.com/me/project/-/blob/main"
+///
+/// [webhook]
+/// threshold = 10
+/// template = '{"text": "{{summary}}"}'
+///
+/// [presets.strict]
+/// warn = ["clippy::unwrap_used", "clippy::indexing_slicing"]
+/// allow = ["clippy::style"]
+///
+/// [owners.profiles]
+/// "@org/team-a" = "strict"
+/// ```
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ScoutConfig {
+    /// The version of the layout of the file, see `schema::SCHEMA_VERSION`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    /// The files this one is merged over, by `from_path`.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Per member overrides, keyed by member name
+    /// (the last path segment of the workspace member).
+    #[serde(default)]
+    pub members: BTreeMap<String, MemberOverride>,
+    /// How generated files are detected.
+    #[serde(default)]
+    pub generated: GeneratedConfig,
+    /// Which changed files make a whole member dirty.
+    #[serde(default)]
+    pub dirty: DirtyConfig,
+    /// Which changed files make a member worth linting.
+    #[serde(default)]
+    pub relevance: RelevanceConfig,
+    /// Which changed files make every member relevant.
+    #[serde(default)]
+    pub invalidators: InvalidatorsConfig,
+    /// The feature combinations `--feature-matrix` lints the members with.
+    #[serde(default)]
+    pub feature_matrix: FeatureMatrixConfig,
+    /// Which lints are reported in the dependents of changed members.
+    #[serde(default)]
+    pub dependents: DependentsConfig,
+    /// Which lints of test code are skipped.
+    #[serde(default)]
+    pub test_code: TestCodeConfig,
+    /// Whether the lints of each kind of target fail the run.
+    #[serde(default)]
+    pub targets: TargetsConfig,
+    /// How `--rank` orders the lints.
+    #[serde(default)]
+    pub ranking: RankingConfig,
+    /// The lints which don't fail the run yet, see `GraceRule`.
+    #[serde(default)]
+    pub grace: Vec<GraceRule>,
+    /// Maps the files build scripts generate to their inputs.
+    #[serde(default)]
+    pub build_outputs: Vec<BuildOutput>,
+    /// Widens the lines of some lints before they are matched with the diff.
+    #[serde(default)]
+    pub padding: Vec<PaddingRule>,
+    /// Rewrites the paths of the lints of builds made in another directory.
+    #[serde(default)]
+    pub path_map: Vec<PathMapping>,
+    /// Which workspaces of the repository are scouted with `--all-workspaces`.
+    #[serde(default)]
+    pub workspaces: WorkspacesConfig,
+    /// How the markdown output links to files.
+    #[serde(default)]
+    pub markdown: MarkdownConfig,
+    /// When and how the webhook reporter notifies, with the `http` feature.
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    /// Where `cargo scout defer` files issues, and records them.
+    #[serde(default)]
+    pub issues: IssuesConfig,
+    /// The lint presets `--preset` selects, by name.
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+    /// The presets governing the files of each `CODEOWNERS` team.
+    #[serde(default)]
+    pub owners: OwnersConfig,
+}
+
+/// The webhook reporter posts a summary when more than `threshold` lints are reported.
+///
+/// The url is read from `SCOUT_WEBHOOK_URL` first, so that it can be kept secret.
+/// In the `template`, `{{count}}`, `{{summary}}` and `{{lints}}` are replaced by json-escaped values,
+/// the default one is a Slack message.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct WebhookConfig {
+    pub url: Option<String>,
+    pub threshold: usize,
+    pub template: String,
+    /// Also send the json report, under the `report` key of the payload
+    pub include_json: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            threshold: 0,
+            template: r#"{"text": "{{summary}}\n{{lints}}"}"#.to_string(),
+            include_json: false,
+        }
+    }
+}
+
+/// `cargo scout defer` files an issue for each deferred lint, by posting the `template` to `url`,
+/// and records the url of the issue in the `baseline` file so the lint isn't reported anymore.
+///
+/// In the `template` and `headers`, `{{title}}`, `{{body}}`, `{{code}}`, `{{path}}`, `{{line}}`
+/// and `{{fingerprint}}` are replaced by json-escaped values, and `{{token}}` by `SCOUT_ISSUES_TOKEN`.
+/// `url-field` is the json pointer to the url of the issue in the response.
+/// The defaults create a GitHub issue, with the url of the issues of a repository.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct IssuesConfig {
+    pub url: Option<String>,
+    pub template: String,
+    pub headers: Vec<String>,
+    pub url_field: String,
+    /// The file recording the deferred lints, relative to the workspace root
+    pub baseline: PathBuf,
+}
+
+impl Default for IssuesConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            template: r#"{"title": "{{title}}", "body": "{{body}}"}"#.to_string(),
+            headers: vec![
+                "Authorization: Bearer {{token}}".to_string(),
+                "Accept: application/vnd.github+json".to_string(),
+            ],
+            url_field: "/html_url".to_string(),
+            baseline: PathBuf::from(".scout-baseline.json"),
+        }
+    }
+}
+
+/// The code host the markdown output links to,
+/// which tells how lines are anchored in file links.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeHost {
+    #[default]
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+/// Links of the markdown output are relative to the repository root by default,
+/// `link-prefix` makes them absolute, e.g. with the url of a commit.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct MarkdownConfig {
+    pub code_host: CodeHost,
+    pub link_prefix: String,
+}
+
+/// The workspaces scouted in a repository holding several of them,
+/// as glob patterns of their directories relative to the repository root.
+///
+/// Every workspace is included by default.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct WorkspacesConfig {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl WorkspacesConfig {
+    /// Whether the workspace in `dir`, relative to the repository root, is scouted.
+    #[must_use]
+    pub fn includes(&self, dir: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, dir)))
+            && !self.exclude.iter().any(|p| glob_match(p, dir))
+    }
+}
+
+/// Changes to files like `build.rs` or `Cargo.toml` can alter lints anywhere in a member,
+/// they make the whole member dirty.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct DirtyConfig {
+    /// Glob patterns of the files, relative to the member directory.
+    /// Files at the workspace root make every member dirty.
+    pub files: Vec<String>,
+    /// Report every lint of a dirty member, not only the ones in the diff
+    pub report_all: bool,
+}
+
+impl Default for DirtyConfig {
+    fn default() -> Self {
+        Self {
+            files: vec![
+                "build.rs".to_string(),
+                "Cargo.toml".to_string(),
+                "rust-toolchain".to_string(),
+                "rust-toolchain.toml".to_string(),
+            ],
+            report_all: false,
+        }
+    }
+}
+
+/// The changed files a member is linted for, as glob patterns relative to its directory:
+/// the files it compiles, rather than the fixtures or test data it holds.
+///
+/// Every Rust file is relevant by default, as a `fixtures` or `testdata` directory can hold
+/// modules too: the directories which only hold data are excluded by the configuration.
+///
+/// A file one of the targets of the member is known to compile is always relevant.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct RelevanceConfig {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Default for RelevanceConfig {
+    fn default() -> Self {
+        Self {
+            include: vec!["**/*.rs".to_string()],
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl RelevanceConfig {
+    /// Whether a change to the file at `relative`, relative to its member, may change its lints.
+    #[must_use]
+    pub fn is_compiled(&self, relative: &str) -> bool {
+        self.include.iter().any(|p| glob_match(p, relative))
+            && !self.exclude.iter().any(|p| glob_match(p, relative))
+    }
+}
+
+/// Changes to the files configuring the toolchain or the linters, like `clippy.toml`,
+/// can alter lints in every member: they make every member relevant,
+/// and the reports of earlier runs aren't reused.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct InvalidatorsConfig {
+    /// Glob patterns of the files, relative to the workspace root.
+    pub files: Vec<String>,
+}
+
+impl Default for InvalidatorsConfig {
+    fn default() -> Self {
+        Self {
+            files: vec![
+                "rust-toolchain".to_string(),
+                "rust-toolchain.toml".to_string(),
+                "**/rustfmt.toml".to_string(),
+                "**/.rustfmt.toml".to_string(),
+                "**/clippy.toml".to_string(),
+                "**/.clippy.toml".to_string(),
+            ],
+        }
+    }
+}
+
+impl InvalidatorsConfig {
+    /// The first of the changed `files` in the workspace at `root` that invalidates every member,
+    /// relative to `root`.
+    #[must_use]
+    pub fn trigger(&self, files: &[String], root: &Path) -> Option<String> {
+        // By components, so that a sibling like `/repo2` isn't taken for a directory of `/repo`
+        files
+            .iter()
+            .filter_map(|file| Path::new(file).strip_prefix(root).ok())
+            .map(|relative| relative.to_string_lossy().into_owned())
+            .find(|relative| self.files.iter().any(|p| glob_match(p, relative)))
+    }
+
+    /// A hash of the contents of the invalidators in `dir` and its ancestors,
+    /// where the toolchain and the linters look for them, even out of the repository,
+    /// and in `clippy_conf_dir`, the `CLIPPY_CONF_DIR` clippy reads its configuration from.
+    #[must_use]
+    pub fn fingerprint(&self, dir: &Path, clippy_conf_dir: Option<&Path>) -> String {
+        let mut hasher = Fnv1a::default();
+        for dir in dir.ancestors().chain(clippy_conf_dir) {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            let mut names: Vec<String> = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| self.files.iter().any(|p| glob_match(p, name)))
+                .collect();
+            names.sort();
+            for name in names {
+                let path = dir.join(&name);
+                if let Ok(contents) = std::fs::read(&path) {
+                    hasher.write(&path.to_string_lossy());
+                    hasher.write(&String::from_utf8_lossy(&contents));
+                }
+            }
+        }
+        format!("{:016x}", hasher.finish())
+    }
+}
+
+/// The feature combinations `--feature-matrix` lints each relevant member with.
+///
+/// A combination is left out for the members that don't have all of its features.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct FeatureMatrixConfig {
+    /// The features of each combination, an empty one lints with the default features
+    pub sets: Vec<Vec<String>>,
+    /// Whether the default features are disabled in every combination
+    pub no_default_features: bool,
+}
+
+impl FeatureMatrixConfig {
+    /// The name of a combination in the reports, like `serde,async`.
+    #[must_use]
+    pub fn label(&self, set: &[String]) -> String {
+        match (set.is_empty(), self.no_default_features) {
+            (true, false) => "default".to_string(),
+            (true, true) => "none".to_string(),
+            (false, _) => set.join(","),
+        }
+    }
+
+    /// The cargo arguments linting with a combination.
+    #[must_use]
+    pub fn args(&self, set: &[String]) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        if !set.is_empty() {
+            args.push("--features".to_string());
+            args.push(set.join(","));
+        }
+        args
+    }
+}
+
+/// The lints reported in members only linted because they depend on changed members.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct DependentsConfig {
+    /// Glob patterns of the lint codes
+    pub lints: Vec<String>,
+}
+
+impl Default for DependentsConfig {
+    fn default() -> Self {
+        Self {
+            lints: vec!["deprecated".to_string()],
+        }
+    }
+}
+
+impl DependentsConfig {
+    /// Whether `lint` is reported in a dependent.
+    #[must_use]
+    pub fn reports(&self, lint: &Lint) -> bool {
+        lint.code
+            .as_ref()
+            .is_some_and(|code| self.lints.iter().any(|pattern| glob_match(pattern, code)))
+    }
+}
+
+/// The lints skipped in test code, the `#[cfg(test)]` and `#[test]` items and the `tests` directories.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct TestCodeConfig {
+    /// Whether lints of test code are skipped at all, which `--skip-tests-code` also sets
+    pub skip: bool,
+    /// Glob patterns of the lint codes skipped
+    pub lints: Vec<String>,
+}
+
+impl Default for TestCodeConfig {
+    fn default() -> Self {
+        Self {
+            skip: false,
+            lints: vec!["*".to_string()],
+        }
+    }
+}
+
+impl TestCodeConfig {
+    /// Whether `lint` is skipped when it is in test code.
+    ///
+    /// Lints without a code, like compiler errors, are never skipped.
+    #[must_use]
+    pub fn skips(&self, lint: &Lint) -> bool {
+        self.skip
+            && lint
+                .code
+                .as_ref()
+                .is_some_and(|code| self.lints.iter().any(|pattern| glob_match(pattern, code)))
+    }
+}
+
+/// Whether a lint fails the run.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    #[default]
+    Error,
+    /// The lint is reported, but doesn't fail the run
+    Info,
+}
+
+/// The severity of the lints of each kind of target, every lint is an error by default.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct TargetsConfig {
+    pub lib: LintSeverity,
+    pub bin: LintSeverity,
+    pub test: LintSeverity,
+    pub example: LintSeverity,
+    pub bench: LintSeverity,
+}
+
+impl TargetsConfig {
+    /// The severity of `lint`, by the kind of target of its file in the repository at `root`.
+    #[must_use]
+    pub fn severity(&self, lint: &Lint, root: &Path) -> LintSeverity {
+        let path = relative_path(&lint.location.path, root).replace('\\', "/");
+        match TargetKind::of(&path) {
+            TargetKind::Lib => self.lib,
+            TargetKind::Bin => self.bin,
+            TargetKind::Test => self.test,
+            TargetKind::Example => self.example,
+            TargetKind::Bench => self.bench,
+        }
+    }
+
+    /// Whether `lint` fails the run, see `severity`.
+    #[must_use]
+    pub fn fails(&self, lint: &Lint, root: &Path) -> bool {
+        self.severity(lint, root) == LintSeverity::Error
+    }
+}
+
+/// The weights `--rank` scores the lints with, the lints of the highest scores being shown first.
+///
+/// The score of a lint is the sum of the weights of what it is:
+/// the defaults rank the errors first, then the `deny` lints, then the lints of new files,
+/// then those of the added lines, before those only matching the boundaries of the diff.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct RankingConfig {
+    /// Compiler errors
+    pub error: u32,
+    /// The lints of `deny`
+    pub deny_listed: u32,
+    /// The lints of the files the diff adds
+    pub new_file: u32,
+    /// The lints of the lines the diff adds
+    pub modified_line: u32,
+    /// The other lints, like those of the context lines or attributed to the diff
+    pub boundary: u32,
+    /// Glob patterns of the lint codes worth fixing first
+    pub deny: Vec<String>,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            error: 16,
+            deny_listed: 8,
+            new_file: 4,
+            modified_line: 2,
+            boundary: 1,
+            deny: Vec::new(),
+        }
+    }
+}
+
+/// Files a build script generates into its `OUT_DIR` from input files.
+///
+/// Lints reported on the outputs are kept when one of the inputs is changed.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BuildOutput {
+    /// Glob patterns of the inputs, relative to the workspace root
+    pub inputs: Vec<String>,
+    /// Glob patterns of the outputs, relative to `OUT_DIR`
+    pub outputs: Vec<String>,
+}
+
+/// The paths a build made in another directory, like in a container or on another mount,
+/// reports its lints in, and where they are in the workspace.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PathMapping {
+    /// The prefix of the paths of the lints, like `/workspace`
+    pub from: PathBuf,
+    /// What replaces it, relative to the workspace root
+    pub to: PathBuf,
+}
+
+impl PathMapping {
+    /// The prefix the lint paths start with, and its replacement in the workspace at `root`.
+    #[must_use]
+    pub fn prefix(&self, root: &Path) -> (PathBuf, PathBuf) {
+        // `to = "."` is the root itself, not `root/.`
+        (
+            self.from.clone(),
+            root.join(&self.to).components().collect(),
+        )
+    }
+}
+
+/// Some lints, like `missing_docs`, are reported on the header of an item
+/// while the changed lines are in its body.
+///
+/// The lines of the lints with one of the codes are widened before they are matched with the diff.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct PaddingRule {
+    /// Glob patterns of the lint codes
+    pub lints: Vec<String>,
+    /// Lines added before the first line of the lint
+    pub before: u32,
+    /// Lines added after the last line of the lint
+    pub after: u32,
+    /// Widen the lint to the innermost item holding its first line, like the function it documents
+    pub enclosing_item: bool,
+}
+
+impl PaddingRule {
+    /// Whether the rule pads `lint`.
+    #[must_use]
+    pub fn applies(&self, lint: &Lint) -> bool {
+        lint.code
+            .as_ref()
+            .is_some_and(|code| self.lints.iter().any(|pattern| glob_match(pattern, code)))
+    }
+}
+
+/// Files with one of the markers in their first lines are generated,
+/// and their lints are never reported.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct GeneratedConfig {
+    /// Set to false to report the lints of generated files
+    pub enabled: bool,
+    /// How many lines are searched for a marker
+    pub header_lines: usize,
+    pub markers: Vec<String>,
+}
+
+impl Default for GeneratedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            header_lines: 5,
+            markers: vec!["@generated".to_string(), "DO NOT EDIT".to_string()],
+        }
+    }
+}
+
+/// Settings that change how the linter is invoked for a given member.
+///
+/// Relative paths are resolved against the workspace root.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MemberOverride {
+    /// The `Cargo.toml` to pass to cargo with `--manifest-path`
+    pub manifest_path: Option<PathBuf>,
+    /// The directory the linter will be run from,
+    /// instead of the member directory
+    pub cwd: Option<PathBuf>,
+    /// Additional arguments passed to cargo
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// Additional arguments passed to clippy after `--`, following the global lint flags,
+    /// like `["-A", "clippy::pedantic"]`
+    #[serde(default)]
+    pub lint_args: Vec<String>,
+}
+
+impl ScoutConfig {
+    /// Reads a `.scout.toml` file, merged over the files it includes,
+    /// then overridden by the `SCOUT_<TABLE>__<KEY>` environment variables.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_path(p: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::with_env_overrides(super::include::load(p.as_ref())?)
+    }
+
+    fn with_env_overrides(mut table: toml::Table) -> Result<Self, Error> {
+        super::include::merge(&mut table, super::env::overrides(std::env::vars()));
+        Ok(table.try_into()?)
+    }
+
+    /// Reads the configuration of the workspace located in `dir`.
+    ///
+    /// The system, user, repository, workspace and current directory files are merged,
+    /// then overridden by the environment variables, see `layers::Locations::files`.
+    /// Missing files are not an error, the defaults are used instead.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn discover(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_layers(&Layered::load(
+            &Locations::discover(dir.as_ref()),
+            std::env::vars(),
+        )?)
+    }
+
+    /// The configuration of the merged `layered` files.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_layers(layered: &Layered) -> Result<Self, Error> {
+        Ok(layered.table.clone().try_into()?)
+    }
+
+    /// Every value of the configuration, defaults included.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn to_table(&self) -> Result<toml::Table, Error> {
+        Ok(toml::Table::try_from(self)?)
+    }
+
+    /// The preset named `name`, defined in the file or else by scout.
+    #[must_use]
+    pub fn preset(&self, name: &str) -> Option<Preset> {
+        self.presets
+            .get(name)
+            .cloned()
+            .or_else(|| Preset::builtin(name))
+    }
+
+    /// Returns the overrides for a workspace member, if any.
+    ///
+    /// `member` is the member as listed in the workspace,
+    /// only its last path segment is used for the lookup.
+    #[must_use]
+    pub fn member_override(&self, member: &str) -> Option<&MemberOverride> {
+        super::member_name(member).and_then(|name| self.members.get(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BuildOutput, CodeHost, FeatureMatrixConfig, GeneratedConfig, InvalidatorsConfig,
+        LintSeverity, MarkdownConfig, MemberOverride, PaddingRule, RelevanceConfig, ScoutConfig,
+        WebhookConfig, WorkspacesConfig,
+    };
+    use crate::config::preset::Preset;
+    use crate::linter::Lint;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_empty_config() {
+        let config: ScoutConfig = toml::from_str("").unwrap();
+        assert_eq!(ScoutConfig::default(), config);
+    }
+
+    #[test]
+    fn test_member_overrides() {
+        let config: ScoutConfig = toml::from_str(
+            r#"
+            [members.foo]
+            manifest-path = "special/Cargo.toml"
+            cwd = "special"
+            extra-args = ["--locked", "--offline"]
+            lint-args = ["-A", "clippy::pedantic"]
+
+            [members.bar]
+            cwd = "../sibling"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(&MemberOverride {
+                manifest_path: Some(PathBuf::from("special/Cargo.toml")),
+                cwd: Some(PathBuf::from("special")),
+                extra_args: vec!["--locked".to_string(), "--offline".to_string()],
+                lint_args: vec!["-A".to_string(), "clippy::pedantic".to_string()],
+            }),
+            config.member_override("crates/foo")
+        );
+        assert_eq!(
+            Some(&MemberOverride {
+                manifest_path: None,
+                cwd: Some(PathBuf::from("../sibling")),
+                extra_args: Vec::new(),
+                lint_args: Vec::new(),
+            }),
+            config.member_override("bar")
+        );
+        assert_eq!(None, config.member_override("baz"));
+    }
+
+    #[test]
+    fn test_generated_config() {
+        let config: ScoutConfig = toml::from_str("[generated]\nheader-lines = 10\n").unwrap();
+        assert_eq!(
+            GeneratedConfig {
+                header_lines: 10,
+                ..GeneratedConfig::default()
+            },
+            config.generated
+        );
+        assert!(GeneratedConfig::default().enabled);
+    }
+
+    #[test]
+    fn test_build_outputs() {
+        let config: ScoutConfig = toml::from_str(
+            "[[build-outputs]]\ninputs = [\"wrapper.h\"]\noutputs = [\"bindings.rs\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![BuildOutput {
+                inputs: vec!["wrapper.h".to_string()],
+                outputs: vec!["bindings.rs".to_string()],
+            }],
+            config.build_outputs
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        let config: ScoutConfig =
+            toml::from_str("[[padding]]\nlints = [\"clippy::missing_*_doc\"]\nafter = 3\n")
+                .unwrap();
+        assert_eq!(
+            vec![PaddingRule {
+                lints: vec!["clippy::missing_*_doc".to_string()],
+                after: 3,
+                ..PaddingRule::default()
+            }],
+            config.padding
+        );
+        let lint = |code: &str| Lint {
+            code: Some(code.to_string()),
+            ..Lint::default()
+        };
+        assert!(config.padding[0].applies(&lint("clippy::missing_errors_doc")));
+        assert!(!config.padding[0].applies(&lint("missing_docs")));
+    }
+
+    #[test]
+    fn test_path_map() {
+        let config: ScoutConfig = toml::from_str(
+            "path-map = [{ from = \"/workspace\", to = \".\" }, { from = \"/gen\", to = \"target/gen\" }]\n",
+        )
+        .unwrap();
+        let root = Path::new("/home/me/repo");
+        assert_eq!(
+            vec![
+                (PathBuf::from("/workspace"), PathBuf::from("/home/me/repo")),
+                (
+                    PathBuf::from("/gen"),
+                    PathBuf::from("/home/me/repo/target/gen")
+                ),
+            ],
+            config
+                .path_map
+                .iter()
+                .map(|mapping| mapping.prefix(root))
+                .collect::<Vec<_>>()
+        );
+        assert!(toml::from_str::<ScoutConfig>("path-map = [{ from = \"/workspace\" }]").is_err());
+    }
+
+    #[test]
+    fn test_test_code() {
+        let lint = |code: Option<&str>| Lint {
+            code: code.map(str::to_string),
+            ..Lint::default()
+        };
+        let config = ScoutConfig::default();
+        assert!(!config.test_code.skips(&lint(Some("clippy::unwrap_used"))));
+        let config: ScoutConfig =
+            toml::from_str("[test-code]\nskip = true\nlints = [\"clippy::*\"]\n").unwrap();
+        assert!(config.test_code.skips(&lint(Some("clippy::unwrap_used"))));
+        assert!(!config.test_code.skips(&lint(Some("dead_code"))));
+        assert!(!config.test_code.skips(&lint(None)));
+    }
+
+    #[test]
+    fn test_targets() {
+        let lint = |path: &str| Lint {
+            location: crate::linter::Location {
+                path: path.to_string(),
+                lines: [1, 1],
+                ..crate::linter::Location::default()
+            },
+            ..Lint::default()
+        };
+        let config: ScoutConfig =
+            toml::from_str("[targets]\nexample = \"info\"\nbench = \"info\"\n").unwrap();
+        let root = Path::new("/repo");
+        assert_eq!(
+            LintSeverity::Info,
+            config
+                .targets
+                .severity(&lint("/repo/member/examples/demo.rs"), root)
+        );
+        assert_eq!(
+            LintSeverity::Error,
+            config
+                .targets
+                .severity(&lint("/repo/member/src/lib.rs"), root)
+        );
+        assert!(!config.targets.fails(&lint("/repo/benches/parse.rs"), root));
+        assert!(toml::from_str::<ScoutConfig>("[targets]\nexample = \"warn\"\n").is_err());
+    }
+
+    #[test]
+    fn test_workspaces() {
+        let config: ScoutConfig = toml::from_str(
+            "[workspaces]\ninclude = [\"services/*\"]\nexclude = [\"services/legacy\"]\n",
+        )
+        .unwrap();
+        assert!(config.workspaces.includes("services/api"));
+        assert!(!config.workspaces.includes("services/legacy"));
+        assert!(!config.workspaces.includes("tools"));
+        assert!(WorkspacesConfig::default().includes("tools"));
+    }
+
+    #[test]
+    fn test_invalidators() -> Result<(), crate::error::Error> {
+        let invalidators = InvalidatorsConfig::default();
+        let root = Path::new("/repo");
+        let files = |names: &[&str]| -> Vec<String> {
+            names.iter().map(|name| format!("/repo/{}", name)).collect()
+        };
+        assert_eq!(
+            Some("member1/clippy.toml".to_string()),
+            invalidators.trigger(&files(&["src/lib.rs", "member1/clippy.toml"]), root)
+        );
+        assert_eq!(
+            Some("rust-toolchain.toml".to_string()),
+            invalidators.trigger(&files(&["rust-toolchain.toml"]), root)
+        );
+        assert_eq!(
+            None,
+            invalidators.trigger(&files(&["member1/rust-toolchain.toml", "src/lib.rs"]), root)
+        );
+        assert_eq!(
+            None,
+            invalidators.trigger(&["/elsewhere/clippy.toml".to_string()], root)
+        );
+        assert_eq!(
+            None,
+            invalidators.trigger(&["/repo2/clippy.toml".to_string()], root)
+        );
+
+        let dir = tempfile::TempDir::new()?;
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir(&workspace)?;
+        let fingerprint = invalidators.fingerprint(&workspace, None);
+        std::fs::write(workspace.join("src.rs"), "")?;
+        assert_eq!(fingerprint, invalidators.fingerprint(&workspace, None));
+        std::fs::write(dir.path().join("clippy.toml"), "msrv = \"1.40\"")?;
+        let with_parent = invalidators.fingerprint(&workspace, None);
+        assert_ne!(fingerprint, with_parent);
+        std::fs::write(dir.path().join("clippy.toml"), "msrv = \"1.50\"")?;
+        assert_ne!(with_parent, invalidators.fingerprint(&workspace, None));
+
+        // The configuration of CLIPPY_CONF_DIR, out of the ancestors
+        let conf_dir = tempfile::TempDir::new()?;
+        let without_conf = invalidators.fingerprint(&workspace, Some(conf_dir.path()));
+        std::fs::write(conf_dir.path().join("clippy.toml"), "msrv = \"1.60\"")?;
+        let with_conf = invalidators.fingerprint(&workspace, Some(conf_dir.path()));
+        assert_ne!(without_conf, with_conf);
+        std::fs::write(conf_dir.path().join("clippy.toml"), "msrv = \"1.70\"")?;
+        assert_ne!(
+            with_conf,
+            invalidators.fingerprint(&workspace, Some(conf_dir.path()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_relevance() {
+        let relevance = RelevanceConfig::default();
+        assert!(relevance.is_compiled("src/lib.rs"));
+        assert!(relevance.is_compiled("build.rs"));
+        assert!(relevance.is_compiled("tests/testdata/input.rs"));
+        assert!(relevance.is_compiled("fixtures/mod.rs"));
+        assert!(!relevance.is_compiled("README.md"));
+        let config: ScoutConfig = toml::from_str(
+            "[relevance]
+exclude = [\"tests/golden/**\"]
+",
+        )
+        .unwrap();
+        assert!(config.relevance.is_compiled("tests/fixtures/mod.rs"));
+        assert!(!config.relevance.is_compiled("tests/golden/out.rs"));
+    }
+
+    #[test]
+    fn test_feature_matrix() {
+        assert!(ScoutConfig::default().feature_matrix.sets.is_empty());
+        let config: ScoutConfig = toml::from_str(
+            "[feature-matrix]
+sets = [[], [\"serde\", \"async\"]]
+",
+        )
+        .unwrap();
+        let matrix = config.feature_matrix;
+        assert_eq!("default", matrix.label(&matrix.sets[0]));
+        assert!(matrix.args(&matrix.sets[0]).is_empty());
+        assert_eq!("serde,async", matrix.label(&matrix.sets[1]));
+        assert_eq!(
+            vec!["--features", "serde,async"],
+            matrix.args(&matrix.sets[1])
+        );
+        let matrix = FeatureMatrixConfig {
+            no_default_features: true,
+            ..matrix
+        };
+        assert_eq!("none", matrix.label(&[]));
+        assert_eq!(vec!["--no-default-features"], matrix.args(&[]));
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(MarkdownConfig::default(), ScoutConfig::default().markdown);
+        let config: ScoutConfig = toml::from_str(
+            "[markdown]\ncode-host = \"bitbucket\"\nlink-prefix = \"https://host\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            MarkdownConfig {
+                code_host: CodeHost::Bitbucket,
+                link_prefix: "https://host".to_string(),
+            },
+            config.markdown
+        );
+        assert!(toml::from_str::<ScoutConfig>("[markdown]\ncode-host = \"gitea\"\n").is_err());
+    }
+
+    #[test]
+    fn test_webhook() {
+        let config: ScoutConfig =
+            toml::from_str("[webhook]\nthreshold = 5\ninclude-json = true\n").unwrap();
+        assert_eq!(5, config.webhook.threshold);
+        assert!(config.webhook.include_json);
+        assert_eq!(None, config.webhook.url);
+        assert_eq!(WebhookConfig::default().template, config.webhook.template);
+    }
+
+    #[test]
+    fn test_presets() {
+        let config: ScoutConfig =
+            toml::from_str("[presets.security]\nwarn = [\"clippy::unwrap_used\"]\n").unwrap();
+        assert_eq!(
+            Some(Preset {
+                warn: vec!["clippy::unwrap_used".to_string()],
+                allow: Vec::new(),
+            }),
+            config.preset("security")
+        );
+        assert_eq!(
+            Preset::builtin("security"),
+            ScoutConfig::default().preset("security")
+        );
+        assert_eq!(None, config.preset("strict"));
+    }
+
+    #[test]
+    fn test_to_table() {
+        let table = ScoutConfig::default().to_table().unwrap();
+        assert_eq!(Some(5), table["generated"]["header-lines"].as_integer());
+        assert_eq!(Some("github"), table["markdown"]["code-host"].as_str());
+        assert_eq!(
+            ScoutConfig::default(),
+            table.try_into::<ScoutConfig>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        assert!(toml::from_str::<ScoutConfig>("[members.foo]\nmanifest = \"Cargo.toml\"").is_err());
+    }
+
+    #[test]
+    fn test_discover_without_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(
+            ScoutConfig::default(),
+            ScoutConfig::discover(dir.path()).unwrap()
+        );
+    }
+}
@@ -4,6 +4,10 @@ use thiserror::Error;
 pub enum Error {
     #[error("ScoutBuilder error")]
     ScoutBuilder,
+    #[error("ScoutToml error: {0}")]
+    ScoutToml(#[from] toml::de::Error),
+    #[error("ScoutTomlSerialize error: {0}")]
+    ScoutTomlSerialize(#[from] toml::ser::Error),
     #[error("CargoToml error: {0}")]
     CargoToml(#[from] cargo_toml::Error),
     #[error("Command error: {0}")]
@@ -14,8 +18,70 @@ pub enum Error {
     Json(#[from] serde_json::Error),
     #[error("NotClean error")]
     NotClean,
+    #[error("InvalidConfig error: {0} error(s) in the configuration")]
+    InvalidConfig(usize),
     #[error("Io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Git error: {0}")]
     Git(#[from] git2::Error),
+    #[error("AmbiguousMember error: {0} matches several members: {1}")]
+    AmbiguousMember(String, String),
+    #[error("Env error: {0}")]
+    Env(String),
+    #[error("Include error: {0}")]
+    Include(String),
+    #[error(
+        "NotARepository error: {0} is not in a git repository, \
+         drop --no-vcs-fallback to report every warning without one"
+    )]
+    NotARepository(String),
+    #[error(
+        "BrokenWorktree error: {0} points to {1}, which is not a git directory, \
+         run `git worktree repair` from the main working tree"
+    )]
+    BrokenWorktree(String, String),
+    #[error(
+        "TooManyLints error: {0} has more than {1} lints, the most frequent being {2}. \
+         Allow them with the `lint-args` of the member, mark the generated files, \
+         or raise --max-lints"
+    )]
+    TooManyLints(String, usize, String),
+    #[error("DiffTooLarge error: {0}, the diff is skipped")]
+    DiffTooLarge(String),
+    #[error("NoChanges error: {0}, nothing was scouted")]
+    NoChanges(String),
+    #[error("Template error: {0}")]
+    Template(String),
+    #[error("Lock error: {0}")]
+    Lock(String),
+    #[error("Owners error: {0}")]
+    Owners(String),
+    #[error("Allow error: {0}")]
+    Allow(String),
+    #[error("Store error: {0}")]
+    Store(String),
+    #[error("Issues error: {0}")]
+    Issues(String),
+    #[error("UnknownPreset error: {0}")]
+    UnknownPreset(String),
+    #[error("Schema error: {0}")]
+    Schema(String),
+    #[error("Doctor error: the {0} check failed")]
+    Doctor(String),
+    #[error("Merge error: {0}")]
+    Merge(String),
+    #[error("FeatureMatrix error: {0}")]
+    FeatureMatrix(String),
+    #[error("Pathspec error: {0}")]
+    Pathspec(String),
+    #[error("Annotate error: {0}")]
+    Annotate(String),
+    #[error("Ingest error: {0}")]
+    Ingest(String),
+    #[error("Interrupted error: the linter was stopped by signal {0}")]
+    Interrupted(i32),
+    #[error("LinterCrashed error: the linter crashed on {0}, its lints are missing:\n{1}")]
+    LinterCrashed(String, String),
+    #[error("DiffParse error in {0}: {1}")]
+    DiffParse(String, crate::vcs::patch::DiffParseError),
 }
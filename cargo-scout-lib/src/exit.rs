@@ -0,0 +1,136 @@
+use crate::error::Error;
+
+/// No lints in the diff.
+pub const CLEAN: i32 = 0;
+/// Lints in the diff.
+pub const LINTS_FOUND: i32 = 1;
+/// Invalid arguments or configuration.
+pub const USAGE: i32 = 2;
+/// The linter, git, or the filesystem failed, so the lints are missing or partial,
+/// or a check of `cargo scout doctor` did.
+pub const TOOL_FAILURE: i32 = 3;
+/// The diff was too large to scout, with --max-diff-lines or --max-diff-files.
+pub const DIFF_TOO_LARGE: i32 = 4;
+/// The diff changes nothing, with --fail-on-empty-diff.
+pub const NO_CHANGES: i32 = 5;
+
+/// The exit codes, as `--help` lists them.
+pub const HELP: &str = "EXIT CODES:
+    0    No lints in the diff
+    1    Lints in the diff
+    2    Invalid arguments or configuration
+    3    The linter, git or the filesystem failed, or a doctor check
+    4    The diff was too large, and was skipped
+    5    The diff changes nothing, with --fail-on-empty-diff
+    128+n  Interrupted by the signal n";
+
+/// The exit code of a command which ended with `result`.
+///
+/// The codes are stable across releases, so that CI scripts can tell the lints from the failures.
+#[must_use]
+pub fn code(result: &Result<(), Error>) -> i32 {
+    let Err(error) = result else {
+        return CLEAN;
+    };
+    match error {
+        Error::NotClean => LINTS_FOUND,
+        Error::ScoutBuilder
+        | Error::ScoutToml(_)
+        | Error::CargoToml(_)
+        | Error::InvalidConfig(_)
+        | Error::AmbiguousMember(..)
+        | Error::Env(_)
+        | Error::Include(_)
+        | Error::NotARepository(_)
+        | Error::Template(_)
+        | Error::UnknownPreset(_)
+        | Error::Owners(_)
+        | Error::Allow(_)
+        | Error::Issues(_)
+        | Error::Schema(_)
+        | Error::Merge(_)
+        | Error::FeatureMatrix(_)
+        | Error::Pathspec(_)
+        | Error::Annotate(_)
+        | Error::Ingest(_) => USAGE,
+        Error::ScoutTomlSerialize(_)
+        | Error::Command(_)
+        | Error::Utf8(_)
+        | Error::Json(_)
+        | Error::Io(_)
+        | Error::Git(_)
+        | Error::BrokenWorktree(..)
+        | Error::Lock(_)
+        | Error::Store(_)
+        | Error::LinterCrashed(..)
+        | Error::TooManyLints(..)
+        | Error::Doctor(_)
+        | Error::DiffParse(..) => TOOL_FAILURE,
+        Error::DiffTooLarge(_) => DIFF_TOO_LARGE,
+        Error::NoChanges(_) => NO_CHANGES,
+        Error::Interrupted(signal) => crate::signal::exit_code(*signal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{code, CLEAN, DIFF_TOO_LARGE, LINTS_FOUND, NO_CHANGES, TOOL_FAILURE, USAGE};
+    use crate::error::Error;
+
+    #[test]
+    fn test_code() {
+        assert_eq!(CLEAN, code(&Ok(())));
+        assert_eq!(LINTS_FOUND, code(&Err(Error::NotClean)));
+        assert_eq!(USAGE, code(&Err(Error::InvalidConfig(2))));
+        assert_eq!(USAGE, code(&Err(Error::NotARepository("/tmp".to_string()))));
+        assert_eq!(USAGE, code(&Err(Error::Pathspec("outside".to_string()))));
+        assert_eq!(
+            TOOL_FAILURE,
+            code(&Err(Error::Io(std::io::Error::other("disk full"))))
+        );
+        assert_eq!(
+            TOOL_FAILURE,
+            code(&Err(Error::LinterCrashed(
+                "foo".to_string(),
+                "ICE".to_string()
+            )))
+        );
+        // The lints of the member are missing, the run is partial
+        assert_eq!(
+            TOOL_FAILURE,
+            code(&Err(Error::TooManyLints(
+                "member".to_string(),
+                10000,
+                "clippy::pedantic".to_string()
+            )))
+        );
+        // The environment is broken, nothing was linted
+        assert_eq!(
+            TOOL_FAILURE,
+            code(&Err(Error::Doctor("clippy".to_string())))
+        );
+        assert_eq!(
+            DIFF_TOO_LARGE,
+            code(&Err(Error::DiffTooLarge("12 files".to_string())))
+        );
+        assert_eq!(
+            NO_CHANGES,
+            code(&Err(Error::NoChanges("no changes".to_string())))
+        );
+        assert_eq!(130, code(&Err(Error::Interrupted(2))));
+    }
+
+    #[test]
+    fn test_help_lists_every_code() {
+        for code in [
+            CLEAN,
+            LINTS_FOUND,
+            USAGE,
+            TOOL_FAILURE,
+            DIFF_TOO_LARGE,
+            NO_CHANGES,
+        ] {
+            assert!(super::HELP.contains(&format!("\n    {}    ", code)));
+        }
+    }
+}
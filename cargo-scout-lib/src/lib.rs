@@ -1,9 +1,19 @@
 pub mod config;
 pub mod error;
+pub mod exit;
+pub mod limits;
 pub mod linter;
+pub mod lock;
+pub mod output;
+pub mod report;
+pub mod retry;
 pub mod scout;
+pub mod signal;
 pub mod vcs;
 
 pub use error::Error;
 
+/// The version of this library, told by the schema errors and the debug summary.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 mod utils;
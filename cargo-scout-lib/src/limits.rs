@@ -0,0 +1,135 @@
+use std::process::Command;
+
+/// Resource limits applied to the linters scout spawns,
+/// and inherited by the processes they spawn in turn.
+///
+/// Limits are only applied on Unix, they are ignored elsewhere.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct ResourceLimits {
+    /// The address space each process may use, in bytes
+    pub max_memory: Option<u64>,
+    /// The niceness processes run with
+    pub nice: Option<i32>,
+}
+
+impl ResourceLimits {
+    /// Whether any limit is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.max_memory.is_none() && self.nice.is_none()
+    }
+
+    /// Whether the limits can be applied on this platform.
+    #[must_use]
+    pub fn is_supported() -> bool {
+        cfg!(unix)
+    }
+
+    /// Applies the limits to the processes spawned by `command`.
+    pub fn apply(&self, command: &mut Command) {
+        if self.is_empty() {
+            return;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            let limits = *self;
+            // Safety: the hook only calls async-signal-safe functions
+            unsafe {
+                command.pre_exec(move || limits.apply_to_current_process());
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    #[cfg(unix)]
+    fn apply_to_current_process(self) -> std::io::Result<()> {
+        if let Some(max_memory) = self.max_memory {
+            let limit = libc::rlimit {
+                rlim_cur: max_memory as libc::rlim_t,
+                rlim_max: max_memory as libc::rlim_t,
+            };
+            if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        if let Some(nice) = self.nice {
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a memory size like `512M` or `4GiB`, in bytes.
+///
+/// The `K`, `M` and `G` suffixes are powers of 1024, an optional `B` or `iB` may follow.
+#[allow(clippy::missing_errors_doc)]
+pub fn parse_memory(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let without_unit = trimmed
+        .strip_suffix("iB")
+        .or_else(|| trimmed.strip_suffix('B'))
+        .unwrap_or(trimmed);
+    let (digits, multiplier) = match without_unit.chars().last() {
+        Some('K' | 'k') => (&without_unit[..without_unit.len() - 1], 1 << 10),
+        Some('M' | 'm') => (&without_unit[..without_unit.len() - 1], 1 << 20),
+        Some('G' | 'g') => (&without_unit[..without_unit.len() - 1], 1 << 30),
+        _ => (without_unit, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|size| size.checked_mul(multiplier))
+        .filter(|size| *size > 0)
+        .ok_or_else(|| {
+            format!(
+                "invalid memory size {}, expected a number of bytes like 512M or 4G",
+                s
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_memory, ResourceLimits};
+
+    #[test]
+    fn test_parse_memory() {
+        assert_eq!(Ok(1024), parse_memory("1024"));
+        assert_eq!(Ok(512 << 20), parse_memory("512M"));
+        assert_eq!(Ok(4 << 30), parse_memory("4G"));
+        assert_eq!(Ok(4 << 30), parse_memory("4GiB"));
+        assert_eq!(Ok(64 << 10), parse_memory("64kB"));
+        assert!(parse_memory("").is_err());
+        assert!(parse_memory("0").is_err());
+        assert!(parse_memory("lots").is_err());
+        assert!(parse_memory("4T").is_err());
+    }
+
+    #[test]
+    fn test_is_empty() {
+        assert!(ResourceLimits::default().is_empty());
+        assert!(!ResourceLimits {
+            nice: Some(10),
+            ..ResourceLimits::default()
+        }
+        .is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply() {
+        let limits = ResourceLimits {
+            nice: Some(19),
+            ..ResourceLimits::default()
+        };
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "nice"]);
+        limits.apply(&mut command);
+        let output = command.output().expect("failed to run nice");
+        assert_eq!("19", String::from_utf8_lossy(&output.stdout).trim());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::linter::fingerprint::{self, lint_context, Fingerprint};
+use crate::linter::Lint;
+use crate::utils::relative_path;
+use std::path::Path;
+
+/// The number of lines captured above and below a lint.
+pub const CONTEXT_RADIUS: usize = 3;
+
+/// A lint along with the source it was reported on,
+/// captured when the lint was reported.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Snapshot {
+    pub lint: Lint,
+    /// The path of the lint, relative to the workspace root
+    pub path: String,
+    pub fingerprint: Fingerprint,
+    /// The lines above the lint, closest last
+    pub before: Vec<String>,
+    /// The lines the lint spans
+    pub lines: Vec<String>,
+    /// The lines below the lint, closest first
+    pub after: Vec<String>,
+}
+
+impl Snapshot {
+    /// Captures a lint and its surrounding lines from the file source.
+    #[must_use]
+    pub fn capture(lint: &Lint, root: impl AsRef<Path>, source: &[String]) -> Self {
+        let lines = lint_context(lint, source);
+        let [start, end] = lint.location.lines;
+        let start = (start as usize).saturating_sub(1).min(source.len());
+        let end = (end as usize).clamp(start, source.len());
+        Self {
+            lint: lint.clone(),
+            path: relative_path(&lint.location.path, root.as_ref()),
+            fingerprint: fingerprint::fingerprint(lint, root, &lines),
+            before: source[start.saturating_sub(CONTEXT_RADIUS)..start].to_vec(),
+            lines: lines.iter().map(ToString::to_string).collect(),
+            after: source[end..(end + CONTEXT_RADIUS).min(source.len())].to_vec(),
+        }
+    }
+
+    /// Captures several lints, reading each source file only once.
+    #[must_use]
+    pub fn capture_all(lints: &[Lint], root: impl AsRef<Path>) -> Vec<Self> {
+        let mut sources = std::collections::HashMap::new();
+        lints
+            .iter()
+            .map(|lint| {
+                let source = sources
+                    .entry(lint.location.path.as_str())
+                    .or_insert_with(|| {
+                        fingerprint::source_lines(&lint.location.path).unwrap_or_default()
+                    });
+                Self::capture(lint, root.as_ref(), source)
+            })
+            .collect()
+    }
+
+    fn start_line(&self) -> u32 {
+        self.lint.location.lines[0]
+    }
+
+    /// Scores how much the surroundings of two lints look alike.
+    /// Lint lines weigh twice as much as the surrounding ones.
+    fn similarity(&self, other: &Self) -> usize {
+        let same = |a: &String, b: &String| a.trim() == b.trim();
+        let before = self
+            .before
+            .iter()
+            .rev()
+            .zip(other.before.iter().rev())
+            .filter(|(a, b)| same(a, b))
+            .count();
+        let lines = self
+            .lines
+            .iter()
+            .zip(other.lines.iter())
+            .filter(|(a, b)| same(a, b))
+            .count();
+        let after = self
+            .after
+            .iter()
+            .zip(other.after.iter())
+            .filter(|(a, b)| same(a, b))
+            .count();
+        before + 2 * lines + after
+    }
+
+    fn max_similarity(&self) -> usize {
+        self.before.len() + 2 * self.lines.len() + self.after.len()
+    }
+}
+
+/// How a lint of the current run relates to the previous run.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Anchor {
+    /// The lint was already reported, at index `previous`
+    /// of the previous run lints.
+    Existing { previous: usize },
+    /// The lint wasn't reported in the previous run.
+    New,
+}
+
+/// Anchors each lint of `current` to a lint of `previous`, if possible.
+///
+/// When new commits are pushed, lints move around in their file,
+/// and comparing locations would report them as new.
+/// Lints are matched by fingerprint first, and then by the source
+/// lines surrounding them, so a lint whose own line was slightly
+/// edited is still recognized.
+///
+/// The result has one `Anchor` per current lint, in the same order.
+/// Each previous lint is anchored at most once.
+#[must_use]
+pub fn reanchor(previous: &[Snapshot], current: &[Snapshot]) -> Vec<Anchor> {
+    let mut anchors = vec![Anchor::New; current.len()];
+    let mut taken = vec![false; previous.len()];
+
+    // Identical fingerprints first, closest lines winning
+    for (anchor, lint) in anchors.iter_mut().zip(current) {
+        let candidate = best_candidate(previous, &taken, |p| {
+            (p.fingerprint == lint.fingerprint).then(|| (usize::MAX, line_distance(p, lint)))
+        });
+        if let Some(index) = candidate {
+            taken[index] = true;
+            *anchor = Anchor::Existing { previous: index };
+        }
+    }
+
+    // Then lints with the same code in the same file looking alike enough
+    for (anchor, lint) in anchors.iter_mut().zip(current) {
+        if *anchor != Anchor::New {
+            continue;
+        }
+        let candidate = best_candidate(previous, &taken, |p| {
+            if p.path != lint.path || p.lint.code != lint.lint.code {
+                return None;
+            }
+            let score = p.similarity(lint);
+            let max = p.max_similarity().max(lint.max_similarity());
+            (score > 0 && 2 * score >= max).then(|| (score, line_distance(p, lint)))
+        });
+        if let Some(index) = candidate {
+            taken[index] = true;
+            *anchor = Anchor::Existing { previous: index };
+        }
+    }
+    anchors
+}
+
+/// Returns the index of the untaken candidate with the highest score,
+/// and then the lowest line distance.
+fn best_candidate(
+    previous: &[Snapshot],
+    taken: &[bool],
+    score: impl Fn(&Snapshot) -> Option<(usize, u32)>,
+) -> Option<usize> {
+    previous
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !taken[*index])
+        .filter_map(|(index, p)| score(p).map(|(score, distance)| (index, score, distance)))
+        .max_by(|(_, s1, d1), (_, s2, d2)| s1.cmp(s2).then(d2.cmp(d1)))
+        .map(|(index, _, _)| index)
+}
+
+fn line_distance(a: &Snapshot, b: &Snapshot) -> u32 {
+    a.start_line().abs_diff(b.start_line())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reanchor, Anchor, Snapshot};
+    use crate::linter::{Lint, Location};
+
+    fn source(text: &str) -> Vec<String> {
+        text.lines().map(ToString::to_string).collect()
+    }
+
+    fn lint(line: u32) -> Lint {
+        Lint {
+            message: String::new(),
+            code: Some("clippy::needless_borrow".to_string()),
+            location: Location {
+                path: "/root/src/lib.rs".to_string(),
+                lines: [line, line],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    const OLD: &str = "fn main() {\n    let a = 1;\n    foo(&a);\n    bar();\n}\n";
+
+    #[test]
+    fn test_moved_lint_is_existing() {
+        let old = vec![Snapshot::capture(&lint(3), "/root", &source(OLD))];
+        let new_source = source(&format!("// a new comment\n\n{}", OLD));
+        let new = vec![Snapshot::capture(&lint(5), "/root", &new_source)];
+
+        assert_eq!(vec![Anchor::Existing { previous: 0 }], reanchor(&old, &new));
+    }
+
+    #[test]
+    fn test_edited_lint_line_is_existing() {
+        let old = vec![Snapshot::capture(&lint(3), "/root", &source(OLD))];
+        let new_source = source("fn main() {\n    let a = 1;\n    foo(&a, 2);\n    bar();\n}\n");
+        let new = vec![Snapshot::capture(&lint(3), "/root", &new_source)];
+
+        assert_eq!(vec![Anchor::Existing { previous: 0 }], reanchor(&old, &new));
+    }
+
+    #[test]
+    fn test_unrelated_lint_is_new() {
+        let old = vec![Snapshot::capture(&lint(3), "/root", &source(OLD))];
+        let new_source = source("use std::io;\n\nstruct Foo;\nimpl Foo {}\n");
+        let new = vec![Snapshot::capture(&lint(3), "/root", &new_source)];
+
+        assert_eq!(vec![Anchor::New], reanchor(&old, &new));
+
+        let mut other_code = lint(3);
+        other_code.code = Some("clippy::other".to_string());
+        let new = vec![Snapshot::capture(&other_code, "/root", &source(OLD))];
+        assert_eq!(vec![Anchor::New], reanchor(&old, &new));
+    }
+
+    #[test]
+    fn test_duplicates_are_anchored_once() {
+        let text = "foo(&a);\nfoo(&a);\nfoo(&a);\n";
+        let old = vec![Snapshot::capture(&lint(1), "/root", &source(text))];
+        let new = vec![
+            Snapshot::capture(&lint(1), "/root", &source(text)),
+            Snapshot::capture(&lint(3), "/root", &source(text)),
+        ];
+        assert_eq!(
+            vec![Anchor::Existing { previous: 0 }, Anchor::New],
+            reanchor(&old, &new)
+        );
+    }
+
+    #[test]
+    fn test_capture_out_of_bounds() {
+        let snapshot = Snapshot::capture(&lint(42), "/root", &source(OLD));
+        assert!(snapshot.lines.is_empty());
+        assert!(snapshot.after.is_empty());
+        assert_eq!("src/lib.rs", snapshot.path);
+    }
+}
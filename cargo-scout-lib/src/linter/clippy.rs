@@ -1,11 +1,20 @@
-use crate::linter;
-use crate::utils::get_absolute_file_path;
+use crate::config::preset::Preset;
+use crate::limits::ResourceLimits;
+use crate::linter::container::Container;
+use crate::linter::crash;
+use crate::linter::runner::CommandRunner;
+use crate::linter::{self, with_cargo_args, with_lint_args, Invocation, Linter, LinterCommand};
+use crate::retry::RetryPolicy;
+use crate::utils::decode_output;
 use cargo_scout_macros::{error, info};
 use colored::Colorize;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::io::{self, Write};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
 #[derive(Default)]
 #[allow(clippy::struct_excessive_bools)]
@@ -18,6 +27,18 @@ pub struct Clippy {
     all_targets: bool,
     target: Option<String>,
     test: bool,
+    jobs: Option<String>,
+    fix: bool,
+    short_messages: bool,
+    diagnostic_width: Option<String>,
+    max_lints: Option<usize>,
+    retry: RetryPolicy,
+    limits: ResourceLimits,
+    preset: Option<Preset>,
+    container: Option<Container>,
+    runner: Option<Arc<dyn CommandRunner>>,
+    /// How many times the clippy commands were retried
+    retries: AtomicU32,
 }
 
 #[derive(Deserialize, Clone)]
@@ -35,9 +56,29 @@ struct Message {
     /// Example:
     /// unused variable `count`
     rendered: String,
+    /// The lint code, absent for plain compiler messages
+    code: Option<Code>,
     /// The file names and lines the lint
     /// was reported on
     spans: Vec<Span>,
+    /// The notes and suggestions attached to the lint
+    #[serde(default)]
+    children: Vec<Child>,
+}
+
+#[derive(Deserialize, Clone)]
+/// A `Child` is a note or a help message of a lint,
+/// its spans hold the suggested replacements
+struct Child {
+    spans: Vec<Span>,
+}
+
+#[derive(Deserialize, Clone)]
+/// A `Code` holds the lint name
+/// Example:
+/// clippy::needless_borrow
+struct Code {
+    code: String,
 }
 
 #[derive(Deserialize, Clone)]
@@ -46,20 +87,118 @@ struct Span {
     file_name: String,
     line_start: u32,
     line_end: u32,
+    #[serde(default)]
+    column_start: u32,
+    #[serde(default)]
+    column_end: u32,
+    byte_start: Option<u32>,
+    byte_end: Option<u32>,
+    /// The source lines of the span
+    #[serde(default)]
+    text: Vec<Text>,
+    suggested_replacement: Option<String>,
+    suggestion_applicability: Option<String>,
 }
 
-impl linter::Linter for Clippy {
+#[derive(Deserialize, Clone)]
+/// A `Text` is a source line of a span, with the columns of the span on it
+struct Text {
+    text: String,
+    highlight_start: u32,
+    highlight_end: u32,
+}
+
+impl Span {
+    fn location(&self) -> linter::Location {
+        linter::Location {
+            path: self.file_name.clone(),
+            lines: [self.line_start, self.line_end],
+            columns: [self.column_start, self.column_end],
+            bytes: self
+                .byte_start
+                .zip(self.byte_end)
+                .map(|(start, end)| [start, end]),
+            snippet: self
+                .text
+                .iter()
+                .map(|text| linter::SnippetLine {
+                    text: text.text.clone(),
+                    highlight: [text.highlight_start, text.highlight_end],
+                })
+                .collect(),
+        }
+    }
+
+    /// The replacement of the span, if it can be applied without a human looking at it.
+    fn suggestion(&self) -> Option<linter::Suggestion> {
+        if self.suggestion_applicability.as_deref() != Some("MachineApplicable") {
+            return None;
+        }
+        Some(linter::Suggestion {
+            path: self.file_name.clone(),
+            lines: [self.line_start, self.line_end],
+            columns: [self.column_start, self.column_end],
+            replacement: self.suggested_replacement.clone()?,
+        })
+    }
+}
+
+impl Linter for Clippy {
     fn lints(
         &self,
         working_dir: impl Into<PathBuf>,
     ) -> Result<Vec<linter::Lint>, crate::error::Error> {
-        let working_dir = working_dir.into();
+        self.invoke(&Invocation::new(working_dir))
+    }
+
+    fn invoke(&self, invocation: &Invocation) -> Result<Vec<linter::Lint>, crate::error::Error> {
         info!(
             "[Clippy] - getting lints for directory {}",
-            &working_dir.to_str().unwrap_or("<no directory>")
+            &invocation.working_dir.to_str().unwrap_or("<no directory>")
         );
-        self.clippy(working_dir)
-            .map(|clippy_output| lints(clippy_output.as_ref()))
+        let mut lints = lints(self.clippy(invocation)?.as_ref(), self.max_lints).map_err(
+            |(count, codes)| {
+                crate::error::Error::TooManyLints(
+                    invocation.working_dir.display().to_string(),
+                    count,
+                    codes,
+                )
+            },
+        )?;
+        if let Some(container) = &self.container {
+            container.map_lints(&mut lints);
+        }
+        if let Some(preset) = &self.preset {
+            lints.retain(|lint| preset.reports(lint));
+        }
+        Ok(lints)
+    }
+
+    fn command(&self, invocation: &Invocation) -> Option<LinterCommand> {
+        let command = LinterCommand {
+            working_dir: invocation.working_dir.clone(),
+            envs: self
+                .envs()
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            program: "cargo".to_string(),
+            args: with_lint_args(
+                with_cargo_args(self.command_parameters(), invocation.cargo_args()),
+                &invocation.lint_args,
+            )
+            .into_iter()
+            .map(ToString::to_string)
+            .collect(),
+        };
+        Some(match &self.container {
+            Some(container) => container.wrap(&command),
+            None => command,
+        })
+    }
+
+    fn retries(&self) -> u32 {
+        self.retries.load(Ordering::Relaxed)
     }
 }
 
@@ -104,7 +243,74 @@ impl Clippy {
         self
     }
 
+    /// Sets the `-j` passed to cargo, bounding how many jobs it runs at once.
+    pub fn set_jobs(&mut self, jobs: Option<usize>) -> &mut Self {
+        self.jobs = jobs.map(|jobs| jobs.to_string());
+        self
+    }
+
+    /// Lets clippy apply its machine-applicable suggestions with `--fix`,
+    /// even to files with uncommitted changes.
+    pub fn set_fix(&mut self, fix: bool) -> &mut Self {
+        self.fix = fix;
+        self
+    }
+
+    /// Asks for one line diagnostics, like `src/lib.rs:3:9: warning: needless borrow`.
+    pub fn set_short_messages(&mut self, short_messages: bool) -> &mut Self {
+        self.short_messages = short_messages;
+        self
+    }
+
+    /// Sets the width the diagnostics are rendered for, like the width of the terminal.
+    pub fn set_diagnostic_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.diagnostic_width = width.map(|width| format!("--diagnostic-width={}", width));
+        self
+    }
+
+    /// Stops with an error when a member has more than `max_lints` lints,
+    /// which usually means that pedantic lints run on generated code.
+    pub fn set_max_lints(&mut self, max_lints: Option<usize>) -> &mut Self {
+        self.max_lints = max_lints;
+        self
+    }
+
+    /// Sets how clippy is retried when cargo fails for a transient reason.
+    pub fn set_retry(&mut self, retry: RetryPolicy) -> &mut Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the resource limits cargo runs within.
+    pub fn set_limits(&mut self, limits: ResourceLimits) -> &mut Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Replaces the lints scout enables by those of `preset`, and only reports them.
+    pub fn set_preset(&mut self, preset: Option<Preset>) -> &mut Self {
+        self.preset = preset;
+        self
+    }
+
+    /// Runs clippy in `container`, instead of on the host.
+    pub fn set_container(&mut self, container: Option<Container>) -> &mut Self {
+        self.container = container;
+        self
+    }
+
+    /// Runs clippy with `runner`, like on a build farm, instead of on this machine.
+    pub fn set_runner(&mut self, runner: Option<Arc<dyn CommandRunner>>) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
     fn command_parameters(&self) -> Vec<&str> {
+        let message_format = if self.short_messages {
+            "json-diagnostic-short"
+        } else {
+            "json"
+        };
         let mut params = if self.preview {
             vec![
                 "+nightly",
@@ -112,10 +318,10 @@ impl Clippy {
                 "-Z",
                 "unstable-options",
                 "--message-format",
-                "json",
+                message_format,
             ]
         } else {
-            vec!["clippy", "--message-format", "json"]
+            vec!["clippy", "--message-format", message_format]
         };
         if self.verbose {
             params.push("--verbose");
@@ -138,13 +344,20 @@ impl Clippy {
         if self.test {
             params.append(&mut vec!["--tests"]);
         }
-        params.append(&mut vec![
-            "--",
-            "-W",
-            "clippy::all",
-            "-W",
-            "clippy::pedantic",
-        ]);
+        if let Some(jobs) = &self.jobs {
+            params.append(&mut vec!["-j", jobs]);
+        }
+        if self.fix {
+            params.append(&mut vec!["--fix", "--allow-dirty", "--allow-staged"]);
+        }
+        params.push("--");
+        if let Some(width) = &self.diagnostic_width {
+            params.push(width);
+        }
+        match &self.preset {
+            Some(preset) => params.append(&mut preset.lint_flags()),
+            None => params.append(&mut vec!["-W", "clippy::all", "-W", "clippy::pedantic"]),
+        }
         params
     }
 
@@ -160,64 +373,78 @@ impl Clippy {
     // because an external command
     // cannot be easily unit tested
     #[cfg(not(tarpaulin_include))]
-    fn clippy(&self, path: impl AsRef<Path>) -> Result<String, crate::error::Error> {
+    fn clippy(&self, invocation: &Invocation) -> Result<String, crate::error::Error> {
+        let command = Linter::command(self, invocation).expect("clippy runs a command");
         println!(
-            "running in {:?}: \"cargo {}\"",
-            path.as_ref(),
-            self.command_parameters().join(" ")
+            "running in {:?}: \"{} {}\"",
+            invocation.working_dir,
+            command.program,
+            command.args.join(" ")
         );
 
-        let clippy_pedantic_output = Command::new("cargo")
-            .current_dir(path)
-            .args(self.command_parameters())
-            .envs(self.envs())
-            .output()
-            .expect("failed to run clippy pedantic");
+        // Failing to spawn cargo, like when the limits can't be applied, is a tool failure
+        let (output, retries) = command.run(self.runner.as_deref(), &self.retry, &self.limits);
+        self.retries.fetch_add(retries, Ordering::Relaxed);
+        let clippy_pedantic_output = output.map_err(crate::signal::command_error)?;
 
         if self.verbose {
             info!(
                 "{}",
-                String::from_utf8(clippy_pedantic_output.stdout.clone())?
+                decode_output(clippy_pedantic_output.stdout.clone(), "clippy")
             );
         }
         if clippy_pedantic_output.status.success() {
-            Ok(String::from_utf8(clippy_pedantic_output.stdout)?)
+            return Ok(decode_output(clippy_pedantic_output.stdout, "clippy"));
+        }
+        let stderr = decode_output(clippy_pedantic_output.stderr, "clippy");
+        // The json stream of a crashed linter is truncated, its lints can't be trusted
+        if let Some(ice) = crash::internal_error(&stderr) {
+            Err(crate::error::Error::LinterCrashed(
+                invocation.working_dir.display().to_string(),
+                ice,
+            ))
         } else if self.verbose {
             error!("Clippy run failed");
             info!("cleaning and building with full backtrace");
             let _ = Command::new("cargo")
-                .args(&["clean"])
+                .args(["clean"])
                 .envs(self.envs())
                 .output()
                 .expect("failed to start cargo clean");
             let build = Command::new("cargo")
-                .args(&["build"])
+                .args(["build"])
                 .envs(self.envs())
                 .output()
                 .expect("failed to start cargo build");
             if build.status.success() {
-                Err(crate::error::Error::Command(String::from_utf8(
+                Err(crate::error::Error::Command(decode_output(
                     build.stdout,
-                )?))
+                    "cargo build",
+                )))
             } else {
                 io::stdout().write_all(&build.stdout)?;
-                Err(crate::error::Error::Command(String::from_utf8(
+                Err(crate::error::Error::Command(decode_output(
                     build.stderr,
-                )?))
+                    "cargo build",
+                )))
             }
         } else {
-            Err(crate::error::Error::Command(String::from_utf8(
-                clippy_pedantic_output.stderr,
-            )?))
+            Err(crate::error::Error::Command(stderr))
         }
     }
 }
 
-#[must_use]
-fn lints(clippy_output: &str) -> Vec<linter::Lint> {
+/// Parses the lints of the clippy output, line by line.
+///
+/// Past `max_lints` lints the parse stops, and the count is returned with
+/// the most frequent codes, to suggest which lints to allow.
+pub(crate) fn lints(
+    clippy_output: &str,
+    max_lints: Option<usize>,
+) -> Result<Vec<linter::Lint>, (usize, String)> {
     let mut lints = Vec::new();
 
-    let clippy_messages: Vec<Message> = clippy_output
+    let clippy_messages = clippy_output
         .lines()
         .filter(|l| l.starts_with('{'))
         .filter_map(|line| {
@@ -227,40 +454,68 @@ fn lints(clippy_output: &str) -> Vec<linter::Lint> {
                 None
             }
         })
-        .filter(|message: &Message| !message.spans.is_empty())
-        .collect();
+        .filter(|message: &Message| !message.spans.is_empty());
 
     for c in clippy_messages {
+        let suggestions: Vec<linter::Suggestion> = c
+            .spans
+            .iter()
+            .chain(c.children.iter().flat_map(|child| child.spans.iter()))
+            .filter_map(Span::suggestion)
+            .collect();
         for s in c.spans {
-            if let Ok(path) = get_absolute_file_path(s.file_name) {
-                lints.push(linter::Lint {
-                    message: c.rendered.clone(),
-                    location: linter::Location {
-                        path,
-                        lines: [s.line_start, s.line_end],
-                    },
-                })
-            }
+            // Cargo runs rustc from the workspace root,
+            // the paths it reports are relative to it rather than to the current directory
+            lints.push(linter::Lint {
+                message: c.rendered.clone(),
+                code: c.code.as_ref().map(|code| code.code.clone()),
+                location: s.location(),
+                suggestions: suggestions.clone(),
+            });
+        }
+        if let Some(max_lints) = max_lints.filter(|max_lints| lints.len() > *max_lints) {
+            return Err((max_lints, most_frequent_codes(&lints, 3)));
         }
     }
-    lints
+    Ok(lints)
+}
+
+/// The `count` most frequent codes of `lints`, like `clippy::doc_markdown (1200)`.
+fn most_frequent_codes(lints: &[linter::Lint], count: usize) -> String {
+    let mut codes: BTreeMap<&str, usize> = BTreeMap::new();
+    for lint in lints {
+        *codes
+            .entry(lint.code.as_deref().unwrap_or("uncoded"))
+            .or_default() += 1;
+    }
+    let mut codes: Vec<(&str, usize)> = codes.into_iter().collect();
+    codes.sort_by(|(_, a), (_, b)| b.cmp(a));
+    codes
+        .iter()
+        .take(count)
+        .map(|(code, count)| format!("{} ({})", code, count))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{get_absolute_file_path, lints, Clippy};
-    use crate::error::Error;
+    use super::{lints, Clippy};
+    use crate::config::preset::Preset;
+    use crate::linter::container::Container;
+    use crate::linter::{Invocation, Linter};
+    use std::path::PathBuf;
 
     #[test]
     fn test_set_verbose() {
         let mut linter = Clippy::default();
-        assert_eq!(false, linter.verbose);
+        assert!(!linter.verbose);
 
         let l2 = linter.set_verbose(true);
-        assert_eq!(true, l2.verbose);
+        assert!(l2.verbose);
 
         let l3 = l2.set_verbose(false);
-        assert_eq!(false, l3.verbose);
+        assert!(!l3.verbose);
     }
 
     #[test]
@@ -281,8 +536,6 @@ mod tests {
             "clippy",
             "--message-format",
             "json",
-            "--tests",
-            "--all-targets",
             "--",
             "-W",
             "clippy::all",
@@ -316,6 +569,50 @@ mod tests {
         assert!(features_linter
             .command_parameters()
             .contains(&"foo bar baz"));
+
+        let jobs_linter = linter.set_features(None).set_jobs(Some(4));
+        let params = jobs_linter.command_parameters();
+        let separator = params.iter().position(|p| *p == "--").unwrap();
+        assert_eq!(["-j", "4"], params[separator - 2..separator]);
+
+        let fix_linter = linter.set_jobs(None).set_fix(true);
+        let params = fix_linter.command_parameters();
+        let separator = params.iter().position(|p| *p == "--").unwrap();
+        assert_eq!(
+            ["--fix", "--allow-dirty", "--allow-staged"],
+            params[separator - 3..separator]
+        );
+
+        let preset_linter = linter
+            .set_fix(false)
+            .set_preset(Preset::builtin("security"));
+        let params = preset_linter.command_parameters();
+        let separator = params.iter().position(|p| *p == "--").unwrap();
+        assert_eq!(
+            ["-A", "clippy::style"],
+            params[separator + 1..separator + 3]
+        );
+        assert!(params.contains(&"clippy::unwrap_used"));
+        assert!(!params.contains(&"clippy::all"));
+    }
+
+    #[test]
+    fn test_short_messages() {
+        let mut linter = Clippy::default();
+        linter
+            .set_short_messages(true)
+            .set_diagnostic_width(Some(120));
+        let params = linter.command_parameters();
+        assert_eq!(
+            ["clippy", "--message-format", "json-diagnostic-short"],
+            params[..3]
+        );
+        let separator = params.iter().position(|p| *p == "--").unwrap();
+        assert_eq!("--diagnostic-width=120", params[separator + 1]);
+        linter.set_preview(true);
+        assert!(linter
+            .command_parameters()
+            .contains(&"json-diagnostic-short"));
     }
 
     #[test]
@@ -356,19 +653,104 @@ mod tests {
     }
 
     #[test]
-    fn test_lints() -> Result<(), Error> {
+    fn test_command_in_container() {
+        let mut linter = Clippy::default();
+        linter.set_container(Some(Container::new(
+            "docker",
+            "rust:1.80",
+            PathBuf::from("/repo"),
+        )));
+        let mut invocation = Invocation::new("/repo/foo");
+        invocation.manifest_path = Some(PathBuf::from("/repo/foo/Cargo.toml"));
+        let command = Linter::command(&linter, &invocation).unwrap();
+        assert_eq!("docker", command.program);
+        assert_eq!(PathBuf::from("/repo/foo"), command.working_dir);
+        assert_eq!(
+            vec![
+                "run",
+                "--rm",
+                "--volume",
+                "/repo:/workspace",
+                "--workdir",
+                "/workspace/foo"
+            ],
+            command.args[..6].to_vec()
+        );
+        assert_eq!(["rust:1.80", "cargo", "clippy"], command.args[6..9]);
+        assert!(command
+            .args
+            .contains(&"/workspace/foo/Cargo.toml".to_string()));
+    }
+
+    #[test]
+    fn test_lints() {
         use crate::linter;
         let expected_lints = vec![linter::Lint {
             message: "this is a test lint".to_string(),
+            code: Some("clippy::test_lint".to_string()),
             location: linter::Location {
-                path: get_absolute_file_path("test/foo/baz.rs")?,
+                path: "test/foo/baz.rs".to_string(),
                 lines: [10, 12],
+                columns: [5, 17],
+                bytes: Some([301, 356]),
+                snippet: vec![
+                    linter::SnippetLine {
+                        text: "    let a = foo(".to_string(),
+                        highlight: [5, 17],
+                    },
+                    linter::SnippetLine {
+                        text: "    );".to_string(),
+                        highlight: [1, 6],
+                    },
+                ],
             },
+            suggestions: Vec::new(),
         }];
 
-        let clippy_output = r#"{"package_id": "cargo-scout","src_path": "test/foo/bar.rs","message": { "rendered": "this is a test lint","spans": [{"file_name": "test/foo/baz.rs","line_start": 10,"line_end": 12}]}}"#;
+        let clippy_output = r#"{"package_id": "cargo-scout","src_path": "test/foo/bar.rs","message": { "rendered": "this is a test lint","code": {"code": "clippy::test_lint", "explanation": null},"spans": [{"file_name": "test/foo/baz.rs","line_start": 10,"line_end": 12,"column_start": 5,"column_end": 17,"byte_start": 301,"byte_end": 356,"text": [{"text": "    let a = foo(","highlight_start": 5,"highlight_end": 17},{"text": "    );","highlight_start": 1,"highlight_end": 6}]}]}}"#;
 
-        assert_eq!(expected_lints, lints(clippy_output));
-        Ok(())
+        assert_eq!(expected_lints, lints(clippy_output, None).unwrap());
+    }
+
+    #[test]
+    fn test_max_lints() {
+        let line = |code: &str, line: u32| {
+            format!(
+                r#"{{"message": {{"rendered": "lint", "code": {{"code": "{}"}}, "spans": [{{"file_name": "src/lib.rs", "line_start": {}, "line_end": {}}}]}}}}"#,
+                code, line, line
+            )
+        };
+        let clippy_output = [
+            line("clippy::doc_markdown", 1),
+            line("clippy::must_use_candidate", 2),
+            line("clippy::doc_markdown", 3),
+            line("clippy::doc_markdown", 4),
+        ]
+        .join("\n");
+        assert_eq!(4, lints(&clippy_output, Some(4)).unwrap().len());
+        assert_eq!(
+            Err((
+                2,
+                "clippy::doc_markdown (2), clippy::must_use_candidate (1)".to_string()
+            )),
+            lints(&clippy_output, Some(2))
+        );
+    }
+
+    #[test]
+    fn test_lints_suggestions() {
+        let clippy_output = r#"{"message": {"rendered": "needless borrow", "code": {"code": "clippy::needless_borrow"}, "spans": [{"file_name": "src/lib.rs", "line_start": 3, "line_end": 3, "column_start": 9, "column_end": 13, "suggested_replacement": null, "suggestion_applicability": null}], "children": [{"message": "change this to", "rendered": null, "spans": [{"file_name": "src/lib.rs", "line_start": 3, "line_end": 3, "column_start": 9, "column_end": 13, "suggested_replacement": "a", "suggestion_applicability": "MachineApplicable"}]}, {"message": "or maybe", "rendered": null, "spans": [{"file_name": "src/lib.rs", "line_start": 3, "line_end": 3, "column_start": 9, "column_end": 13, "suggested_replacement": "b", "suggestion_applicability": "MaybeIncorrect"}]}]}}"#;
+
+        let lints = lints(clippy_output, None).unwrap();
+        assert_eq!(1, lints.len());
+        assert_eq!(
+            vec![crate::linter::Suggestion {
+                path: "src/lib.rs".to_string(),
+                lines: [3, 3],
+                columns: [9, 13],
+                replacement: "a".to_string(),
+            }],
+            lints[0].suggestions
+        );
     }
 }
@@ -0,0 +1,527 @@
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+
+/// The rules command templates are split by, which differ between Unix and Windows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Platform {
+    /// POSIX shell words: quotes and backslashes escape
+    Unix,
+    /// The rules of the Microsoft C runtime: backslashes only escape quotes
+    Windows,
+}
+
+impl Platform {
+    /// The platform scout runs on.
+    #[must_use]
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+}
+
+/// A shell running a command template with `shell = true`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Shell {
+    Sh,
+    Cmd,
+    PowerShell,
+}
+
+impl Shell {
+    /// The shell of `platform`, `sh` or `cmd`.
+    #[must_use]
+    pub fn of(platform: Platform) -> Self {
+        match platform {
+            Platform::Unix => Self::Sh,
+            Platform::Windows => Self::Cmd,
+        }
+    }
+
+    /// Quotes `arg` so that the shell reads it as a single word, unchanged.
+    #[must_use]
+    pub fn quote(self, arg: &str) -> String {
+        match self {
+            Self::Sh => {
+                if is_safe(arg, "-_./:=@%+,") {
+                    return arg.to_string();
+                }
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+            Self::PowerShell => {
+                if is_safe(arg, "-_./:\\") && !arg.starts_with('-') {
+                    return arg.to_string();
+                }
+                // PowerShell also takes the typographic single quotes as quotes
+                let mut quoted = String::from("'");
+                for c in arg.chars() {
+                    if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}') {
+                        quoted.push(c);
+                    }
+                    quoted.push(c);
+                }
+                quoted.push('\'');
+                quoted
+            }
+            Self::Cmd => {
+                // The program still splits the line cmd passes it, so the argument is quoted
+                // for the C runtime first, then every character cmd interprets is escaped
+                let mut escaped = String::new();
+                for c in quote_windows(arg).chars() {
+                    if matches!(c, '(' | ')' | '%' | '!' | '^' | '"' | '<' | '>' | '&' | '|') {
+                        escaped.push('^');
+                    }
+                    escaped.push(c);
+                }
+                escaped
+            }
+        }
+    }
+
+    /// The arguments running `command` with the shell.
+    ///
+    /// The last argument of `cmd` must be passed as is, with `CommandExt::raw_arg`,
+    /// as cmd does not split its command line like other programs.
+    #[must_use]
+    pub fn argv(self, command: &str) -> Vec<String> {
+        let argv: &[&str] = match self {
+            Self::Sh => &["sh", "-c"],
+            Self::Cmd => &["cmd.exe", "/d", "/c"],
+            Self::PowerShell => &[
+                "powershell.exe",
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+            ],
+        };
+        let mut argv: Vec<String> = argv.iter().map(ToString::to_string).collect();
+        argv.push(command.to_string());
+        argv
+    }
+}
+
+fn is_safe(arg: &str, punctuation: &str) -> bool {
+    !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || punctuation.contains(c))
+}
+
+/// Quotes `arg` for the Microsoft C runtime, which splits the command line on Windows.
+#[must_use]
+pub fn quote_windows(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Backslashes before a quote are escaped, and so is the quote
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            c => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    // Backslashes before the closing quote are escaped too
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Splits `command` into words, as a shell of `platform` would, without expanding anything.
+#[allow(clippy::missing_errors_doc)]
+pub fn split(command: &str, platform: Platform) -> Result<Vec<String>, Error> {
+    match platform {
+        Platform::Unix => split_unix(command),
+        Platform::Windows => Ok(split_windows(command)),
+    }
+}
+
+fn split_unix(command: &str) -> Result<Vec<String>, Error> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(unclosed(command, "'")),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(unclosed(command, "\"")),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(unclosed(command, "\"")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => {
+                    return Err(Error::Template(format!(
+                        "`{}` ends with an escaping backslash",
+                        command
+                    )))
+                }
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+fn unclosed(command: &str, quote: &str) -> Error {
+    Error::Template(format!("`{}` has an unclosed {}", command, quote))
+}
+
+/// Splits like `CommandLineToArgvW` and the C runtime since 2008, where quotes left open are closed.
+fn split_windows(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' if !quoted => words.extend(word.take()),
+            '\\' => {
+                let mut backslashes = 1;
+                while chars.next_if_eq(&'\\').is_some() {
+                    backslashes += 1;
+                }
+                let word = word.get_or_insert_with(String::new);
+                if chars.next_if_eq(&'"').is_some() {
+                    word.extend(std::iter::repeat_n('\\', backslashes / 2));
+                    if backslashes % 2 == 1 {
+                        word.push('"');
+                    } else {
+                        quoted = toggle(quoted, &mut chars, word);
+                    }
+                } else {
+                    word.extend(std::iter::repeat_n('\\', backslashes));
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                quoted = toggle(quoted, &mut chars, word);
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// Handles an unescaped quote, a doubled quote within quotes is a literal one.
+fn toggle(
+    quoted: bool,
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    word: &mut String,
+) -> bool {
+    if quoted && chars.next_if_eq(&'"').is_some() {
+        word.push('"');
+        return true;
+    }
+    !quoted
+}
+
+/// Replaces the `{name}` placeholders of `template` by the values `var` gives,
+/// quoted with `quote`, `{{` and `}}` are literal braces.
+fn substitute(
+    template: &str,
+    var: &impl Fn(&str) -> Option<String>,
+    quote: impl Fn(&str) -> String,
+) -> Result<String, Error> {
+    let mut substituted = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => substituted.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => substituted.push('}'),
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(unclosed(template, "{")),
+                    }
+                }
+                let value = var(&name).ok_or_else(|| {
+                    Error::Template(format!("`{}` uses the unknown {{{}}}", template, name))
+                })?;
+                substituted.push_str(&quote(&value));
+            }
+            '}' => {
+                return Err(Error::Template(format!(
+                    "`{}` has a `}}` without `{{`, write `}}}}` for a brace",
+                    template
+                )))
+            }
+            c => substituted.push(c),
+        }
+    }
+    Ok(substituted)
+}
+
+/// The command of a custom linter, like `my-lint --manifest-path {manifest}`.
+///
+/// The command is split into arguments and run without a shell, unless `shell` is set.
+/// The `{name}` placeholders are replaced after the split, so that a value is always one
+/// argument, or quoted for the shell with `shell = true`.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct CommandTemplate {
+    pub command: String,
+    /// Runs the command with `sh -c`, or `cmd /c` on Windows
+    pub shell: bool,
+}
+
+impl CommandTemplate {
+    /// The arguments of the command on `platform`, the first one being the program.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn argv(
+        &self,
+        platform: Platform,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Vec<String>, Error> {
+        if self.shell {
+            return self.shell_argv(Shell::of(platform), var);
+        }
+        let argv = split(&self.command, platform)?
+            .iter()
+            .map(|word| substitute(word, &var, str::to_string))
+            .collect::<Result<Vec<_>, _>>()?;
+        if argv.is_empty() {
+            return Err(Error::Template("the command is empty".to_string()));
+        }
+        Ok(argv)
+    }
+
+    /// The arguments running the command with `shell`, its placeholders quoted for it.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn shell_argv(
+        &self,
+        shell: Shell,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Vec<String>, Error> {
+        if self.command.trim().is_empty() {
+            return Err(Error::Template("the command is empty".to_string()));
+        }
+        let command = substitute(&self.command, &var, |value| shell.quote(value))?;
+        Ok(shell.argv(&command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{quote_windows, split, CommandTemplate, Platform, Shell};
+
+    fn var(name: &str) -> Option<String> {
+        match name {
+            "manifest" => Some("C:\\My Projects\\app\\Cargo.toml".to_string()),
+            "file" => Some("it's $HOME & \"more\"".to_string()),
+            _ => None,
+        }
+    }
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_split_unix() {
+        assert_eq!(
+            words(&["lint", "a b", "it's", "\"q\"", "$x", "c\\d", ""]),
+            split(r#"lint  'a b' "it's" "\"q\"" \$x "c\d" ''"#, Platform::Unix).unwrap()
+        );
+        assert_eq!(words(&["a-b"]), split("a'-'b", Platform::Unix).unwrap());
+        assert!(split("lint 'open", Platform::Unix).is_err());
+        assert!(split("lint \"open", Platform::Unix).is_err());
+        assert!(split("lint \\", Platform::Unix).is_err());
+    }
+
+    #[test]
+    fn test_split_windows() {
+        assert_eq!(
+            words(&[
+                "C:\\Program Files\\lint.exe",
+                "a\\\\b",
+                "a\\\"b",
+                "a\\\\b c",
+                "say \"hi\"",
+                ""
+            ]),
+            split(
+                r#""C:\Program Files\lint.exe" a\\b a\\\"b "a\\b c" "say ""hi""" """#,
+                Platform::Windows
+            )
+            .unwrap()
+        );
+        // Backslashes before a closing quote are halved
+        assert_eq!(
+            words(&["dir\\", "next"]),
+            split(r#""dir\\" next"#, Platform::Windows).unwrap()
+        );
+        assert_eq!(
+            words(&["open quote"]),
+            split("\"open quote", Platform::Windows).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_quote_windows() {
+        for arg in [
+            "plain",
+            "",
+            "a b",
+            "dir\\",
+            "dir with space\\",
+            "say \"hi\"",
+            "\\\"",
+            "a\\\\b",
+        ] {
+            let quoted = quote_windows(arg);
+            assert_eq!(
+                words(&[arg]),
+                split(&quoted, Platform::Windows).unwrap(),
+                "{}",
+                quoted
+            );
+        }
+        assert_eq!("plain", quote_windows("plain"));
+        assert_eq!("\"dir with space\\\\\"", quote_windows("dir with space\\"));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!("Cargo.toml", Shell::Sh.quote("Cargo.toml"));
+        assert_eq!("''", Shell::Sh.quote(""));
+        assert_eq!("'it'\\''s $HOME'", Shell::Sh.quote("it's $HOME"));
+        for arg in ["it's $HOME & \"more\"", "a\nb", "*", "\\"] {
+            assert_eq!(
+                words(&[arg]),
+                split(&Shell::Sh.quote(arg), Platform::Unix).unwrap()
+            );
+        }
+        assert_eq!("'it''s $HOME'", Shell::PowerShell.quote("it's $HOME"));
+        assert_eq!(
+            "'a\u{2019}\u{2019}s'",
+            Shell::PowerShell.quote("a\u{2019}s")
+        );
+        assert_eq!("'-Force'", Shell::PowerShell.quote("-Force"));
+        assert_eq!(
+            "C:\\app\\Cargo.toml",
+            Shell::PowerShell.quote("C:\\app\\Cargo.toml")
+        );
+        assert_eq!(
+            "^\"a b ^& ^%PATH^% \\^\"x\\^\"^\"",
+            Shell::Cmd.quote("a b & %PATH% \"x\"")
+        );
+        assert_eq!("plain", Shell::Cmd.quote("plain"));
+    }
+
+    #[test]
+    fn test_argv() {
+        let template = CommandTemplate {
+            command: "lint --manifest-path {manifest} --file=\"{file}\" {{literal}}".to_string(),
+            shell: false,
+        };
+        assert_eq!(
+            words(&[
+                "lint",
+                "--manifest-path",
+                "C:\\My Projects\\app\\Cargo.toml",
+                "--file=it's $HOME & \"more\"",
+                "{literal}"
+            ]),
+            template.argv(Platform::Unix, var).unwrap()
+        );
+        assert_eq!(
+            template.argv(Platform::Unix, var).unwrap(),
+            template.argv(Platform::Windows, var).unwrap()
+        );
+
+        let unknown = CommandTemplate {
+            command: "lint {missing}".to_string(),
+            shell: false,
+        };
+        assert!(unknown.argv(Platform::Unix, var).is_err());
+        assert!(CommandTemplate::default()
+            .argv(Platform::Unix, var)
+            .is_err());
+        let stray = CommandTemplate {
+            command: "lint }".to_string(),
+            shell: false,
+        };
+        assert!(stray.argv(Platform::Unix, var).is_err());
+    }
+
+    #[test]
+    fn test_shell_argv() {
+        let template = CommandTemplate {
+            command: "lint {file} | tee out.txt".to_string(),
+            shell: true,
+        };
+        assert_eq!(
+            words(&["sh", "-c", "lint 'it'\\''s $HOME & \"more\"' | tee out.txt"]),
+            template.argv(Platform::Unix, var).unwrap()
+        );
+        assert_eq!(
+            words(&[
+                "cmd.exe",
+                "/d",
+                "/c",
+                "lint ^\"it's $HOME ^& \\^\"more\\^\"^\" | tee out.txt"
+            ]),
+            template.argv(Platform::Windows, var).unwrap()
+        );
+        assert_eq!(
+            words(&[
+                "powershell.exe",
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "lint 'it''s $HOME & \"more\"' | tee out.txt"
+            ]),
+            template.shell_argv(Shell::PowerShell, var).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let template: CommandTemplate = toml::from_str("command = \"lint {file}\"\n").unwrap();
+        assert!(!template.shell);
+        assert!(toml::from_str::<CommandTemplate>("command = \"lint\"\nshel = true\n").is_err());
+    }
+}
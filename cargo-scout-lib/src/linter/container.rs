@@ -0,0 +1,193 @@
+use crate::linter::{Lint, LinterCommand};
+use std::path::{Path, PathBuf};
+
+/// Where the host directory is mounted in the container, unless set otherwise.
+pub const MOUNT_POINT: &str = "/workspace";
+
+/// A Docker or Podman image the linter commands run in, with a host directory mounted in it.
+///
+/// The paths of the commands are mapped into the container,
+/// and the paths of the lints back to the host, so that they match the diff.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Container {
+    /// The container engine, `docker` or `podman`
+    pub engine: String,
+    pub image: String,
+    /// The directory mounted in the container, usually the repository root
+    pub host_root: PathBuf,
+    pub mount_point: PathBuf,
+}
+
+impl Container {
+    #[must_use]
+    pub fn new(engine: impl Into<String>, image: impl Into<String>, host_root: PathBuf) -> Self {
+        Self {
+            engine: engine.into(),
+            image: image.into(),
+            host_root,
+            mount_point: PathBuf::from(MOUNT_POINT),
+        }
+    }
+
+    /// The command running `command` in the container, from its working directory.
+    #[must_use]
+    pub fn wrap(&self, command: &LinterCommand) -> LinterCommand {
+        let mut args = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "--volume".to_string(),
+            format!(
+                "{}:{}",
+                self.host_root.display(),
+                self.mount_point.display()
+            ),
+            "--workdir".to_string(),
+            self.to_container(&command.working_dir)
+                .display()
+                .to_string(),
+        ];
+        for (key, value) in &command.envs {
+            args.push("--env".to_string());
+            args.push(format!("{}={}", key, value));
+        }
+        args.push(self.image.clone());
+        args.push(command.program.clone());
+        args.extend(
+            command
+                .args
+                .iter()
+                .map(|arg| self.to_container(Path::new(arg)).display().to_string()),
+        );
+        LinterCommand {
+            // The engine itself runs on the host
+            working_dir: command.working_dir.clone(),
+            envs: Vec::new(),
+            program: self.engine.clone(),
+            args,
+        }
+    }
+
+    /// The path of the host `path` in the container, paths outside of the mount are unchanged.
+    #[must_use]
+    pub fn to_container(&self, path: &Path) -> PathBuf {
+        map_prefix(path, &self.host_root, &self.mount_point)
+    }
+
+    /// The path of the container `path` on the host, paths outside of the mount are unchanged.
+    #[must_use]
+    pub fn to_host(&self, path: &Path) -> PathBuf {
+        map_prefix(path, &self.mount_point, &self.host_root)
+    }
+
+    /// Maps the paths of `lints` and their suggestions back to the host.
+    pub fn map_lints(&self, lints: &mut [Lint]) {
+        let to_host = |path: &mut String| {
+            *path = self.to_host(Path::new(path)).display().to_string();
+        };
+        for lint in lints {
+            to_host(&mut lint.location.path);
+            for suggestion in &mut lint.suggestions {
+                to_host(&mut suggestion.path);
+            }
+        }
+    }
+}
+
+pub(crate) fn map_prefix(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(relative) if relative.as_os_str().is_empty() => to.to_path_buf(),
+        Ok(relative) => to.join(relative),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Container;
+    use crate::linter::{Lint, LinterCommand, Location, Suggestion};
+    use std::path::{Path, PathBuf};
+
+    fn container() -> Container {
+        Container::new("podman", "rust:1.80", PathBuf::from("/home/me/repo"))
+    }
+
+    #[test]
+    fn test_wrap() {
+        let command = LinterCommand {
+            working_dir: PathBuf::from("/home/me/repo/crates/foo"),
+            envs: vec![("RUST_BACKTRACE".to_string(), "full".to_string())],
+            program: "cargo".to_string(),
+            args: vec![
+                "clippy".to_string(),
+                "--manifest-path".to_string(),
+                "/home/me/repo/crates/foo/Cargo.toml".to_string(),
+            ],
+        };
+        assert_eq!(
+            LinterCommand {
+                working_dir: PathBuf::from("/home/me/repo/crates/foo"),
+                envs: Vec::new(),
+                program: "podman".to_string(),
+                args: [
+                    "run",
+                    "--rm",
+                    "--volume",
+                    "/home/me/repo:/workspace",
+                    "--workdir",
+                    "/workspace/crates/foo",
+                    "--env",
+                    "RUST_BACKTRACE=full",
+                    "rust:1.80",
+                    "cargo",
+                    "clippy",
+                    "--manifest-path",
+                    "/workspace/crates/foo/Cargo.toml",
+                ]
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            },
+            container().wrap(&command)
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let container = container();
+        assert_eq!(
+            PathBuf::from("/workspace"),
+            container.to_container(Path::new("/home/me/repo"))
+        );
+        assert_eq!(
+            PathBuf::from("/home/me/repository"),
+            container.to_container(Path::new("/home/me/repository"))
+        );
+        assert_eq!(
+            PathBuf::from("/home/me/repo/src/lib.rs"),
+            container.to_host(Path::new("/workspace/src/lib.rs"))
+        );
+        assert_eq!(
+            PathBuf::from("src/lib.rs"),
+            container.to_host(Path::new("src/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn test_map_lints() {
+        let mut lints = vec![Lint {
+            location: Location {
+                path: "/workspace/src/lib.rs".to_string(),
+                lines: [1, 2],
+                ..Location::default()
+            },
+            suggestions: vec![Suggestion {
+                path: "/workspace/src/lib.rs".to_string(),
+                ..Suggestion::default()
+            }],
+            ..Lint::default()
+        }];
+        container().map_lints(&mut lints);
+        assert_eq!("/home/me/repo/src/lib.rs", lints[0].location.path);
+        assert_eq!("/home/me/repo/src/lib.rs", lints[0].suggestions[0].path);
+    }
+}
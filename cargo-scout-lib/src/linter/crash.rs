@@ -0,0 +1,103 @@
+use crate::error::Error;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The lines of stderr telling that rustc or clippy crashed, rather than the code failed to build.
+const MARKERS: &[&str] = &[
+    "error: internal compiler error",
+    "thread 'rustc' panicked",
+    "error: the compiler unexpectedly panicked",
+    "clippy-driver' panicked",
+];
+
+/// A crash of the linter on a member, whose lints are missing from the run.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Clone, Debug)]
+pub struct Crash {
+    pub member: String,
+    /// The internal compiler error, from its first line to the end of stderr
+    pub message: String,
+}
+
+/// The internal compiler error of the `stderr` of a failed linter, if it crashed.
+///
+/// The backtrace and the notes following the panic are kept, for the bug report.
+#[must_use]
+pub fn internal_error(stderr: &str) -> Option<String> {
+    let start = stderr
+        .lines()
+        .position(|line| MARKERS.iter().any(|marker| line.contains(marker)))?;
+    Some(
+        stderr
+            .lines()
+            .skip(start)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim_end()
+            .to_string(),
+    )
+}
+
+/// The error of a run the linter crashed during, `None` if it didn't.
+#[must_use]
+pub fn error(crashes: &[Crash]) -> Option<Error> {
+    if crashes.is_empty() {
+        return None;
+    }
+    Some(Error::LinterCrashed(
+        crashes
+            .iter()
+            .map(|crash| crash.member.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        crashes
+            .iter()
+            .map(|crash| crash.message.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{error, internal_error, Crash};
+    use crate::error::Error;
+
+    #[test]
+    fn test_internal_error() {
+        let stderr = "    Checking foo v0.1.0 (/foo)
+error: internal compiler error: compiler/rustc_middle/src/ty/mod.rs:12:5: unexpected type
+
+thread 'rustc' panicked at compiler/rustc_middle/src/util/bug.rs:35:44:
+Box<dyn Any>
+note: we would appreciate a bug report: https://github.com/rust-lang/rust-clippy/issues/new
+error: could not compile `foo` (lib)
+";
+        let ice = internal_error(stderr).unwrap();
+        assert!(ice.starts_with("error: internal compiler error: compiler/rustc_middle"));
+        assert!(ice.ends_with("error: could not compile `foo` (lib)"));
+        assert!(!ice.contains("Checking"));
+
+        let panicked = "thread 'rustc' panicked at clippy_lints/src/foo.rs:1:1:\nboom";
+        assert_eq!(Some(panicked.to_string()), internal_error(panicked));
+        // The code failing to build isn't a crash
+        assert_eq!(
+            None,
+            internal_error("error[E0425]: cannot find value `a` in this scope\nerror: could not compile `foo` (lib)")
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        assert!(error(&[]).is_none());
+        let crash = |member: &str, message: &str| Crash {
+            member: member.to_string(),
+            message: message.to_string(),
+        };
+        let crashes = [crash("a", "first ice"), crash("b", "second ice")];
+        assert!(matches!(
+            error(&crashes),
+            Some(Error::LinterCrashed(members, message))
+                if members == "a, b" && message == "first ice\n\nsecond ice"
+        ));
+    }
+}
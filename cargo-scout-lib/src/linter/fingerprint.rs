@@ -0,0 +1,224 @@
+use crate::linter::Lint;
+use crate::utils::{relative_path, text_lines};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Bumped whenever the fingerprint computation changes,
+/// so fingerprints from different schemes never collide.
+const SALT: &str = "cargo-scout-fingerprint-v1";
+
+/// The maximum number of source lines hashed for a single lint.
+const MAX_CONTEXT_LINES: usize = 16;
+
+/// A `Fingerprint` identifies a lint across runs.
+///
+/// It is computed from the lint code, the path of the file relative
+/// to the workspace root and the source lines the lint was reported on.
+/// Line numbers are left out, so that a lint keeps its fingerprint
+/// when unrelated changes move it up or down in the file.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub struct Fingerprint(u64);
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(Self)
+    }
+}
+
+impl Lint {
+    /// Computes the lint fingerprint, reading the source lines
+    /// it was reported on from disk.
+    ///
+    /// If the file can't be read, only the code and the path are hashed.
+    #[must_use]
+    pub fn fingerprint(&self, root: impl AsRef<Path>) -> Fingerprint {
+        let context = source_lines(&self.location.path).unwrap_or_default();
+        fingerprint(self, root, &lint_context(self, &context))
+    }
+}
+
+/// Computes the fingerprint of a lint from the given source lines.
+///
+/// Leading and trailing whitespace is ignored,
+/// so reindenting code doesn't change the fingerprint.
+#[must_use]
+pub fn fingerprint(lint: &Lint, root: impl AsRef<Path>, context: &[&str]) -> Fingerprint {
+    let mut hasher = Fnv1a::default();
+    hasher.write(SALT);
+    hasher.write(lint.code.as_deref().unwrap_or_default());
+    hasher.write(&relative_path(&lint.location.path, root.as_ref()));
+    for line in context.iter().take(MAX_CONTEXT_LINES) {
+        hasher.write(line.trim());
+    }
+    Fingerprint(hasher.finish())
+}
+
+/// Computes the fingerprints of several lints,
+/// reading each source file only once.
+#[must_use]
+pub fn fingerprints(lints: &[Lint], root: impl AsRef<Path>) -> Vec<Fingerprint> {
+    let mut sources: HashMap<&str, Vec<String>> = HashMap::new();
+    lints
+        .iter()
+        .map(|lint| {
+            let source = sources
+                .entry(&lint.location.path)
+                .or_insert_with(|| source_lines(&lint.location.path).unwrap_or_default());
+            fingerprint(lint, root.as_ref(), &lint_context(lint, source))
+        })
+        .collect()
+}
+
+pub(crate) fn source_lines(path: impl AsRef<Path>) -> std::io::Result<Vec<String>> {
+    Ok(text_lines(&std::fs::read_to_string(path)?)
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Returns the source lines a lint spans, lint lines start at 1.
+pub(crate) fn lint_context<'a>(lint: &Lint, source: &'a [String]) -> Vec<&'a str> {
+    let [start, end] = lint.location.lines;
+    let start = (start as usize).saturating_sub(1);
+    let end = (end as usize).min(source.len());
+    source
+        .get(start..end)
+        .unwrap_or_default()
+        .iter()
+        .map(String::as_str)
+        .collect()
+}
+
+/// The 64 bits FNV-1a hash.
+///
+/// The standard library hashers are not guaranteed to be stable
+/// between Rust releases, fingerprints must be.
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    pub(crate) fn write(&mut self, s: &str) {
+        for byte in s.bytes().chain(std::iter::once(0)) {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fingerprint, fingerprints, source_lines, Fingerprint};
+    use crate::linter::{Lint, Location};
+    use std::io::Write;
+
+    fn lint(path: &str, lines: [u32; 2]) -> Lint {
+        Lint {
+            message: format!("a lint at line {}", lines[0]),
+            code: Some("clippy::needless_borrow".to_string()),
+            location: Location {
+                path: path.to_string(),
+                lines,
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_line_numbers() {
+        let moved_lint = lint("/root/src/lib.rs", [12, 12]);
+        let original_lint = lint("/root/src/lib.rs", [2, 2]);
+        assert_eq!(
+            fingerprint(&original_lint, "/root", &["    foo(&bar);"]),
+            fingerprint(&moved_lint, "/root", &["foo(&bar);"])
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_the_root() {
+        let lint_here = lint("/here/src/lib.rs", [2, 2]);
+        let lint_there = lint("C:\\there\\src\\lib.rs", [2, 2]);
+        assert_eq!(
+            fingerprint(&lint_here, "/here", &["foo(&bar);"]),
+            fingerprint(&lint_there, "C:\\there", &["foo(&bar);"])
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_differences() {
+        let l = lint("/root/src/lib.rs", [2, 2]);
+        let reference = fingerprint(&l, "/root", &["foo(&bar);"]);
+
+        assert_ne!(reference, fingerprint(&l, "/root", &["foo(&baz);"]));
+
+        let other_file = lint("/root/src/main.rs", [2, 2]);
+        assert_ne!(
+            reference,
+            fingerprint(&other_file, "/root", &["foo(&bar);"])
+        );
+
+        let mut other_code = l.clone();
+        other_code.code = Some("clippy::pedantic".to_string());
+        assert_ne!(
+            reference,
+            fingerprint(&other_code, "/root", &["foo(&bar);"])
+        );
+    }
+
+    #[test]
+    fn test_fingerprints_read_sources() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lib.rs");
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"fn main() {\n    foo(&bar);\n}\n").unwrap();
+        let l = lint(path.to_str().unwrap(), [2, 2]);
+
+        assert_eq!(
+            vec![fingerprint(&l, dir.path(), &["foo(&bar);"])],
+            fingerprints(std::slice::from_ref(&l), dir.path())
+        );
+        assert_eq!(
+            fingerprint(&l, dir.path(), &["foo(&bar);"]),
+            l.fingerprint(dir.path())
+        );
+    }
+
+    #[test]
+    fn test_source_lines_windows_checkout() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lib.rs");
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all("\u{feff}fn main() {\r\n    foo(&bar);\r\n}\r\n".as_bytes())
+            .unwrap();
+
+        assert_eq!(
+            vec!["fn main() {", "    foo(&bar);", "}"],
+            source_lines(&path).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_roundtrip() {
+        let f = fingerprint(&lint("src/lib.rs", [1, 1]), ".", &[]);
+        assert_eq!(Ok(f), f.to_string().parse::<Fingerprint>());
+        assert_eq!(16, f.to_string().len());
+    }
+}
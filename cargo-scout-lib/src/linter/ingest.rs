@@ -0,0 +1,224 @@
+use crate::linter::{clippy, sarif, Invocation, Lint, Linter};
+use cargo_scout_macros::info;
+use colored::Colorize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The formats of the lints an `Ingested` linter reads.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LintsFormat {
+    /// The json messages of `cargo clippy --message-format json`, or of `cargo check`
+    ClippyJson,
+    /// A SARIF log
+    Sarif,
+}
+
+impl FromStr for LintsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clippy-json" => Ok(Self::ClippyJson),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(format!(
+                "unknown lints format {}, expected clippy-json or sarif",
+                s
+            )),
+        }
+    }
+}
+
+/// A linter reporting lints produced beforehand, by a separate build step, instead of running one.
+///
+/// Each member reports the lints of the files of its directory, nested members included,
+/// as clippy reports those of path dependencies: matching the diff deduplicates them.
+#[derive(Debug, Default)]
+pub struct Ingested {
+    lints: Vec<Lint>,
+}
+
+impl Ingested {
+    /// Parses the lints of `input`.
+    ///
+    /// Relative paths are relative to `root`, the workspace root, as they are for cargo.
+    ///
+    /// # Errors
+    ///
+    /// Fails when `input` isn't in `format`, like the human output of clippy read as its json,
+    /// which would otherwise report no lint.
+    pub fn parse(
+        input: &str,
+        format: LintsFormat,
+        root: &Path,
+    ) -> Result<Self, crate::error::Error> {
+        let mut lints = match format {
+            LintsFormat::ClippyJson => {
+                if !input.trim().is_empty() && !input.lines().any(is_cargo_message) {
+                    return Err(crate::error::Error::Ingest(
+                        "the lints have no cargo json message, \
+                         were they written with --message-format json?"
+                            .to_string(),
+                    ));
+                }
+                // Without a maximum the parse can't fail
+                clippy::lints(input, None).unwrap_or_default()
+            }
+            LintsFormat::Sarif => sarif::lints(input)?,
+        };
+        for lint in &mut lints {
+            lint.location.path = root
+                .join(&lint.location.path)
+                .to_string_lossy()
+                .into_owned();
+        }
+        Ok(Self { lints })
+    }
+
+    /// Reads the lints of `reader`, like stdin, until its end.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_reader(
+        mut reader: impl Read,
+        format: LintsFormat,
+        root: &Path,
+    ) -> Result<Self, crate::error::Error> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        Self::parse(&input, format, root)
+    }
+
+    /// Reads the lints of the file at `path`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        format: LintsFormat,
+        root: &Path,
+    ) -> Result<Self, crate::error::Error> {
+        Self::parse(&std::fs::read_to_string(path)?, format, root)
+    }
+
+    /// Adds the lints of `other`, to scout the lints of several tools at once.
+    pub fn extend(&mut self, other: Self) {
+        self.lints.extend(other.lints);
+    }
+
+    /// The number of lints read.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lints.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lints.is_empty()
+    }
+}
+
+/// Whether `line` is a json message of cargo, like a `compiler-message` or a `build-finished`.
+fn is_cargo_message(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line).is_ok_and(|message| {
+        message
+            .get("reason")
+            .is_some_and(serde_json::Value::is_string)
+    })
+}
+
+impl Linter for Ingested {
+    fn lints(&self, working_dir: impl Into<PathBuf>) -> Result<Vec<Lint>, crate::error::Error> {
+        self.invoke(&Invocation::new(working_dir))
+    }
+
+    fn invoke(&self, invocation: &Invocation) -> Result<Vec<Lint>, crate::error::Error> {
+        let lints: Vec<Lint> = self
+            .lints
+            .iter()
+            .filter(|lint| Path::new(&lint.location.path).starts_with(&invocation.working_dir))
+            .cloned()
+            .collect();
+        info!(
+            "[Ingested] - {} lints for directory {}",
+            lints.len(),
+            &invocation.working_dir.to_str().unwrap_or("<no directory>")
+        );
+        Ok(lints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ingested, LintsFormat};
+    use crate::linter::{Invocation, Linter};
+    use std::path::Path;
+
+    const CLIPPY_OUTPUT: &str = r#"{"reason": "compiler-artifact"}
+{"reason": "compiler-message", "message": {"rendered": "warning: unused variable", "code": {"code": "unused_variables"}, "spans": [{"file_name": "src/lib.rs", "line_start": 3, "line_end": 3}]}}
+{"reason": "compiler-message", "message": {"rendered": "warning: dead code", "code": {"code": "dead_code"}, "spans": [{"file_name": "member/src/lib.rs", "line_start": 1, "line_end": 1}]}}
+"#;
+
+    #[test]
+    fn test_ingested() -> Result<(), crate::error::Error> {
+        let ingested = Ingested::from_reader(
+            CLIPPY_OUTPUT.as_bytes(),
+            LintsFormat::ClippyJson,
+            Path::new("/repo"),
+        )?;
+        assert_eq!(2, ingested.len());
+
+        let member = ingested.invoke(&Invocation::new("/repo/member"))?;
+        assert_eq!(1, member.len());
+        assert_eq!("/repo/member/src/lib.rs", member[0].location.path);
+        assert_eq!(2, ingested.lints("/repo")?.len());
+        assert!(ingested.lints("/elsewhere")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sarif_file() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("semgrep.sarif");
+        std::fs::write(
+            &path,
+            r#"{"runs": [{"tool": {"driver": {"name": "semgrep"}}, "results": [{"ruleId": "unsafe", "message": {"text": "unsafe block"}, "locations": [{"physicalLocation": {"artifactLocation": {"uri": "member/src/lib.rs"}, "region": {"startLine": 2}}}]}]}]}"#,
+        )?;
+        let mut ingested = Ingested::from_reader(
+            CLIPPY_OUTPUT.as_bytes(),
+            LintsFormat::ClippyJson,
+            Path::new("/repo"),
+        )?;
+        ingested.extend(Ingested::from_path(
+            &path,
+            LintsFormat::Sarif,
+            Path::new("/repo"),
+        )?);
+        let lints = ingested.lints("/repo/member")?;
+        assert_eq!(
+            vec![Some("dead_code"), Some("unsafe")],
+            lints
+                .iter()
+                .map(|lint| lint.code.as_deref())
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_json() -> Result<(), crate::error::Error> {
+        let human = "warning: unused variable: `a`\n --> src/lib.rs:3:9\n";
+        assert!(matches!(
+            Ingested::parse(human, LintsFormat::ClippyJson, Path::new("/repo")),
+            Err(crate::error::Error::Ingest(_))
+        ));
+        // A build without messages, or without output, has no lints
+        let finished = r#"{"reason": "build-finished", "success": true}"#;
+        assert!(Ingested::parse(finished, LintsFormat::ClippyJson, Path::new("/repo"))?.is_empty());
+        assert!(Ingested::parse(" \n", LintsFormat::ClippyJson, Path::new("/repo"))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(LintsFormat::Sarif), "sarif".parse());
+        assert_eq!(Ok(LintsFormat::ClippyJson), "clippy-json".parse());
+        assert!("checkstyle".parse::<LintsFormat>().is_err());
+    }
+}
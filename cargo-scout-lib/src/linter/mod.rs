@@ -1,29 +1,375 @@
+use crate::limits::ResourceLimits;
+use crate::retry::RetryPolicy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
 
+pub mod anchor;
 pub mod clippy;
+pub mod command;
+pub mod container;
+pub mod crash;
+pub mod fingerprint;
+pub mod ingest;
+pub mod postprocess;
+pub mod runner;
 pub mod rustfmt;
+pub mod sarif;
 
-pub trait Linter {
+/// Linters are `Sync`, so that several members can be linted at once.
+pub trait Linter: Sync {
     #[allow(clippy::missing_errors_doc)]
     fn lints(&self, working_dir: impl Into<PathBuf>) -> Result<Vec<Lint>, crate::error::Error>;
+
+    /// Runs the linter as described by an `Invocation`.
+    ///
+    /// The default implementation only honors the working directory,
+    /// linters that run cargo should override it to pass the manifest path
+    /// and the extra arguments along.
+    #[allow(clippy::missing_errors_doc)]
+    fn invoke(&self, invocation: &Invocation) -> Result<Vec<Lint>, crate::error::Error> {
+        self.lints(invocation.working_dir.clone())
+    }
+
+    /// Describes the command `invoke` runs for an `Invocation`, without running it.
+    ///
+    /// The default implementation returns `None`, for linters that can't tell.
+    fn command(&self, _invocation: &Invocation) -> Option<LinterCommand> {
+        None
+    }
+
+    /// How many times the commands the linter ran so far were retried.
+    ///
+    /// The default implementation returns 0, for linters that run no command.
+    fn retries(&self) -> u32 {
+        0
+    }
+}
+
+impl<L: Linter> Linter for &L {
+    fn lints(&self, working_dir: impl Into<PathBuf>) -> Result<Vec<Lint>, crate::error::Error> {
+        (*self).lints(working_dir)
+    }
+
+    fn invoke(&self, invocation: &Invocation) -> Result<Vec<Lint>, crate::error::Error> {
+        (*self).invoke(invocation)
+    }
+
+    fn command(&self, invocation: &Invocation) -> Option<LinterCommand> {
+        (*self).command(invocation)
+    }
+
+    fn retries(&self) -> u32 {
+        (*self).retries()
+    }
+}
+
+/// An external command run by a linter.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct LinterCommand {
+    pub working_dir: PathBuf,
+    pub envs: Vec<(String, String)>,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl LinterCommand {
+    /// Runs the command within `limits`, retrying it according to `retry`,
+    /// with `runner`, or on this machine.
+    ///
+    /// Returns its output, and how many times it was retried.
+    pub fn run(
+        &self,
+        runner: Option<&dyn runner::CommandRunner>,
+        retry: &RetryPolicy,
+        limits: &ResourceLimits,
+    ) -> (std::io::Result<std::process::Output>, u32) {
+        match runner {
+            Some(runner) => runner.output(self, retry, limits),
+            None => self.output(retry, limits),
+        }
+    }
+
+    /// Runs the command within `limits`, retrying it according to `retry`.
+    ///
+    /// Returns its output, and how many times it was retried.
+    pub fn output(
+        &self,
+        retry: &RetryPolicy,
+        limits: &ResourceLimits,
+    ) -> (std::io::Result<std::process::Output>, u32) {
+        retry.run(|| {
+            let mut command = self.to_command();
+            limits.apply(&mut command);
+            crate::signal::output(&mut command)
+        })
+    }
+
+    /// Builds the `std::process::Command` running this command.
+    #[must_use]
+    pub fn to_command(&self) -> std::process::Command {
+        let mut command = std::process::Command::new(&self.program);
+        command
+            .current_dir(&self.working_dir)
+            .args(&self.args)
+            .envs(self.envs.iter().map(|(k, v)| (k, v)));
+        command
+    }
+}
+
+impl fmt::Display for LinterCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(in {}) ", self.working_dir.display())?;
+        for (key, value) in &self.envs {
+            write!(f, "{}={} ", key, value)?;
+        }
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// This struct describes how a linter should be run for a given member.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Invocation {
+    /// The directory the linter is run from
+    pub working_dir: PathBuf,
+    /// The manifest to pass to cargo with `--manifest-path`, if any
+    pub manifest_path: Option<PathBuf>,
+    /// Additional arguments for cargo
+    pub extra_args: Vec<String>,
+    /// Additional arguments for clippy, after the `--` and the flags of the linter
+    pub lint_args: Vec<String>,
+}
+
+impl Invocation {
+    #[must_use]
+    pub fn new(working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            working_dir: working_dir.into(),
+            ..Self::default()
+        }
+    }
+
+    /// The arguments that must be passed to cargo,
+    /// before the `--` separating them from the linter arguments.
+    #[must_use]
+    pub fn cargo_args(&self) -> Vec<&str> {
+        let mut args = Vec::new();
+        if let Some(manifest_path) = self.manifest_path.as_ref().and_then(|p| p.to_str()) {
+            args.append(&mut vec!["--manifest-path", manifest_path]);
+        }
+        args.extend(self.extra_args.iter().map(String::as_str));
+        args
+    }
+}
+
+/// Inserts `cargo_args` right before the `--` separator of `params`,
+/// or at the end if there is none.
+fn with_cargo_args<'a>(mut params: Vec<&'a str>, cargo_args: Vec<&'a str>) -> Vec<&'a str> {
+    let separator = params
+        .iter()
+        .position(|p| *p == "--")
+        .unwrap_or(params.len());
+    params.splice(separator..separator, cargo_args);
+    params
+}
+
+/// Appends `lint_args` to `params`, after a `--` separator,
+/// so that they override the flags of the linter.
+fn with_lint_args<'a>(mut params: Vec<&'a str>, lint_args: &'a [String]) -> Vec<&'a str> {
+    if !lint_args.is_empty() && !params.contains(&"--") {
+        params.push("--");
+    }
+    params.extend(lint_args.iter().map(String::as_str));
+    params
 }
 
 /// This struct contains the lint,
 /// It may contain a message, and a location.
-#[derive(PartialEq, Clone, Debug, Hash, Eq)]
+#[derive(PartialEq, Clone, Debug, Hash, Eq, Default)]
 pub struct Lint {
     /// The message string
     /// Example:
     /// unused variable `count`
     pub message: String,
+    /// The lint code, if the linter provides one
+    /// Example:
+    /// clippy::needless_borrow
+    pub code: Option<String>,
     /// The file names and lines the lint
     /// was reported on
     pub location: Location,
+    /// The machine-applicable fixes of the lint, if any
+    pub suggestions: Vec<Suggestion>,
 }
 
-/// A `Location` has a file name, a start and an end line
-#[derive(PartialEq, Clone, Debug, Hash, Eq)]
+/// A `Location` has a file name, a start and an end line, and a start and an end column.
+///
+/// Columns are like those of a `Suggestion`, and 0 when the linter doesn't report them, like rustfmt.
+#[derive(PartialEq, Clone, Debug, Hash, Eq, Default)]
 pub struct Location {
     pub path: String,
     pub lines: [u32; 2],
+    pub columns: [u32; 2],
+    /// The start and end byte offsets in the file, the end excluded, if the linter reports them
+    pub bytes: Option<[u32; 2]>,
+    /// The source lines spanned, as the linter read them, so that reporters
+    /// can highlight the span without reading the file again, which may have changed since
+    pub snippet: Vec<SnippetLine>,
+}
+
+impl Location {
+    /// The start column, `None` when the linter didn't report it.
+    #[must_use]
+    pub fn column(&self) -> Option<u32> {
+        Some(self.columns[0]).filter(|column| *column > 0)
+    }
+
+    /// The start line, followed by the start column when there is one, like `12:5`.
+    #[must_use]
+    pub fn position(&self) -> String {
+        match self.column() {
+            Some(column) => format!("{}:{}", self.lines[0], column),
+            None => self.lines[0].to_string(),
+        }
+    }
+}
+
+/// A line of source spanned by a lint, and the columns of the span on it.
+///
+/// Columns are like those of a `Suggestion`.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Clone, Debug, Hash, Eq, Default)]
+pub struct SnippetLine {
+    pub text: String,
+    pub highlight: [u32; 2],
+}
+
+impl SnippetLine {
+    /// The spanned part of the line, empty when the highlight is out of the line.
+    #[must_use]
+    pub fn highlighted(&self) -> &str {
+        let byte = |column: u32| {
+            self.text
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(self.text.len()))
+                .nth(column.saturating_sub(1) as usize)
+        };
+        match (byte(self.highlight[0]), byte(self.highlight[1])) {
+            (Some(start), Some(end)) if start <= end => &self.text[start..end],
+            _ => "",
+        }
+    }
+}
+
+/// A `Suggestion` replaces some text of a file.
+///
+/// Lines and columns start at 1, columns count characters and the end column is excluded.
+#[derive(PartialEq, Clone, Debug, Hash, Eq, Default)]
+pub struct Suggestion {
+    pub path: String,
+    pub lines: [u32; 2],
+    pub columns: [u32; 2],
+    pub replacement: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        with_cargo_args, with_lint_args, Invocation, LinterCommand, Location, SnippetLine,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_display_command() {
+        let command = LinterCommand {
+            working_dir: PathBuf::from("/repo"),
+            envs: vec![("RUST_BACKTRACE".to_string(), "full".to_string())],
+            program: "cargo".to_string(),
+            args: vec!["clippy".to_string(), "--locked".to_string()],
+        };
+        assert_eq!(
+            "(in /repo) RUST_BACKTRACE=full cargo clippy --locked",
+            command.to_string()
+        );
+    }
+
+    #[test]
+    fn test_cargo_args() {
+        let mut invocation = Invocation::new("foo");
+        assert!(invocation.cargo_args().is_empty());
+
+        invocation.manifest_path = Some(PathBuf::from("foo/Cargo.toml"));
+        invocation.extra_args = vec!["--locked".to_string()];
+        assert_eq!(
+            vec!["--manifest-path", "foo/Cargo.toml", "--locked"],
+            invocation.cargo_args()
+        );
+    }
+
+    #[test]
+    fn test_with_cargo_args() {
+        assert_eq!(
+            vec!["clippy", "--locked", "--", "-W", "clippy::all"],
+            with_cargo_args(vec!["clippy", "--", "-W", "clippy::all"], vec!["--locked"])
+        );
+        assert_eq!(
+            vec!["clippy", "--locked"],
+            with_cargo_args(vec!["clippy"], vec!["--locked"])
+        );
+    }
+
+    #[test]
+    fn test_with_lint_args() {
+        let lint_args = vec![
+            "-A".to_string(),
+            "clippy::module_name_repetitions".to_string(),
+        ];
+        assert_eq!(
+            vec![
+                "clippy",
+                "--",
+                "-W",
+                "clippy::pedantic",
+                "-A",
+                "clippy::module_name_repetitions"
+            ],
+            with_lint_args(vec!["clippy", "--", "-W", "clippy::pedantic"], &lint_args)
+        );
+        assert_eq!(
+            vec!["clippy", "--", "-A", "clippy::module_name_repetitions"],
+            with_lint_args(vec!["clippy"], &lint_args)
+        );
+        assert_eq!(vec!["clippy"], with_lint_args(vec!["clippy"], &[]));
+    }
+
+    #[test]
+    fn test_location_position() {
+        let mut location = Location {
+            path: "src/lib.rs".to_string(),
+            lines: [12, 14],
+            ..Location::default()
+        };
+        assert_eq!(None, location.column());
+        assert_eq!("12", location.position());
+        location.columns = [5, 9];
+        assert_eq!(Some(5), location.column());
+        assert_eq!("12:5", location.position());
+    }
+
+    #[test]
+    fn test_snippet_highlighted() {
+        let line = |highlight| SnippetLine {
+            text: "    let é = &a;".to_string(),
+            highlight,
+        };
+        assert_eq!("é", line([9, 10]).highlighted());
+        assert_eq!("&a;", line([13, 16]).highlighted());
+        assert_eq!("", line([13, 40]).highlighted());
+        assert_eq!("", line([10, 9]).highlighted());
+    }
 }
@@ -0,0 +1,237 @@
+use crate::linter::container::map_prefix;
+use crate::linter::Lint;
+use std::path::{Path, PathBuf};
+
+/// Rewrites a lint as the linter found it, before it is matched with the diff.
+///
+/// The members are linted in parallel with `--jobs`,
+/// so the post-processors are called from the linting threads.
+pub trait PostProcessor: Send + Sync {
+    fn process(&self, lint: &mut Lint);
+}
+
+/// The post-processors a run applies to each lint, in order.
+#[derive(Default)]
+pub struct PostProcessors {
+    processors: Vec<Box<dyn PostProcessor>>,
+}
+
+impl PostProcessors {
+    /// Applies `processor` after the others.
+    pub fn push(&mut self, processor: impl PostProcessor + 'static) -> &mut Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Applies every post-processor to `lints`.
+    pub fn process(&self, lints: &mut [Lint]) {
+        for lint in lints {
+            for processor in &self.processors {
+                processor.process(lint);
+            }
+        }
+    }
+}
+
+/// Strips the ANSI escape sequences of the messages, like the colors of `CARGO_TERM_COLOR=always`,
+/// so that they don't end up in the json and markdown outputs.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct StripAnsi;
+
+impl PostProcessor for StripAnsi {
+    fn process(&self, lint: &mut Lint) {
+        if lint.message.contains('\u{1b}') {
+            lint.message = strip_ansi(&lint.message);
+        }
+    }
+}
+
+/// `text` without its CSI sequences, like `\x1b[1;31m`, and OSC sequences, like hyperlinks.
+#[must_use]
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Parameters and intermediate bytes, up to the final byte
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Up to the bell, or the string terminator
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' {
+                        break;
+                    }
+                    if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Two characters sequences, like `\x1b(B`
+            _ => {}
+        }
+    }
+    stripped
+}
+
+/// Rewrites the paths of the lints and their suggestions from one prefix to another,
+/// like the directory a container built in back to the host directory.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PathPrefixMap {
+    prefixes: Vec<(PathBuf, PathBuf)>,
+}
+
+impl PathPrefixMap {
+    #[must_use]
+    pub fn new(prefixes: Vec<(PathBuf, PathBuf)>) -> Self {
+        Self { prefixes }
+    }
+
+    /// `path` with the longest of the prefixes it starts with replaced, unchanged without any.
+    #[must_use]
+    pub fn map(&self, path: &Path) -> PathBuf {
+        self.prefixes
+            .iter()
+            .filter(|(from, _)| path.starts_with(from))
+            .max_by_key(|(from, _)| from.components().count())
+            .map_or_else(
+                || path.to_path_buf(),
+                |(from, to)| map_prefix(path, from, to),
+            )
+    }
+}
+
+impl PostProcessor for PathPrefixMap {
+    fn process(&self, lint: &mut Lint) {
+        let map = |path: &mut String| {
+            *path = self.map(Path::new(path)).display().to_string();
+        };
+        map(&mut lint.location.path);
+        for suggestion in &mut lint.suggestions {
+            map(&mut suggestion.path);
+        }
+    }
+}
+
+/// Parses a `from=to` prefix mapping, see `PathPrefixMap`.
+#[allow(clippy::missing_errors_doc)]
+pub fn parse_prefix(s: &str) -> Result<(PathBuf, PathBuf), String> {
+    match s.split_once('=') {
+        Some((from, to)) if !from.is_empty() => Ok((PathBuf::from(from), PathBuf::from(to))),
+        _ => Err(format!(
+            "invalid path prefix mapping {}, expected from=to",
+            s
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_prefix, strip_ansi, PathPrefixMap, PostProcessors, StripAnsi};
+    use crate::linter::{Lint, Location, Suggestion};
+    use std::path::{Path, PathBuf};
+
+    fn lint() -> Lint {
+        Lint {
+            message: "\u{1b}[1m\u{1b}[33mwarning\u{1b}[0m: needless borrow".to_string(),
+            code: Some("clippy::needless_borrow".to_string()),
+            location: Location {
+                path: "/build/src/crates/foo/src/lib.rs".to_string(),
+                lines: [3, 3],
+                ..Location::default()
+            },
+            suggestions: vec![Suggestion {
+                path: "/build/src/crates/foo/src/lib.rs".to_string(),
+                lines: [3, 3],
+                columns: [9, 13],
+                replacement: "a".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            "warning: unused",
+            strip_ansi("\u{1b}[1;33mwarning\u{1b}[0m: unused")
+        );
+        assert_eq!(
+            "see docs",
+            strip_ansi("see \u{1b}]8;;https://rust-lang.github.io\u{1b}\\docs\u{1b}]8;;\u{7}")
+        );
+        assert_eq!("plain → text", strip_ansi("plain → text"));
+        // A truncated sequence is dropped
+        assert_eq!("cut", strip_ansi("cut\u{1b}[1;3"));
+    }
+
+    #[test]
+    fn test_path_prefix_map() {
+        let map = PathPrefixMap::new(vec![
+            (PathBuf::from("/build"), PathBuf::from("/elsewhere")),
+            (PathBuf::from("/build/src"), PathBuf::from("/home/me/repo")),
+        ]);
+        assert_eq!(
+            PathBuf::from("/home/me/repo/src/lib.rs"),
+            map.map(Path::new("/build/src/src/lib.rs"))
+        );
+        assert_eq!(
+            PathBuf::from("/elsewhere/out/gen.rs"),
+            map.map(Path::new("/build/out/gen.rs"))
+        );
+        // Prefixes are whole components
+        assert_eq!(
+            PathBuf::from("/builds/lib.rs"),
+            map.map(Path::new("/builds/lib.rs"))
+        );
+        assert_eq!(
+            PathBuf::from("src/lib.rs"),
+            map.map(Path::new("src/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn test_post_processors() {
+        let mut processors = PostProcessors::default();
+        assert!(processors.is_empty());
+        processors.push(StripAnsi).push(PathPrefixMap::new(vec![(
+            PathBuf::from("/build/src"),
+            PathBuf::from("/home/me/repo"),
+        )]));
+        let mut lints = vec![lint()];
+        processors.process(&mut lints);
+        assert_eq!("warning: needless borrow", lints[0].message);
+        assert_eq!(
+            "/home/me/repo/crates/foo/src/lib.rs",
+            lints[0].location.path
+        );
+        assert_eq!(
+            "/home/me/repo/crates/foo/src/lib.rs",
+            lints[0].suggestions[0].path
+        );
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        assert_eq!(
+            Ok((PathBuf::from("/build/src"), PathBuf::from("/home/me/repo"))),
+            parse_prefix("/build/src=/home/me/repo")
+        );
+        assert!(parse_prefix("/build/src").is_err());
+        assert!(parse_prefix("=/home/me/repo").is_err());
+    }
+}
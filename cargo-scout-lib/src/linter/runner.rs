@@ -0,0 +1,146 @@
+use crate::limits::ResourceLimits;
+use crate::linter::LinterCommand;
+use crate::retry::RetryPolicy;
+use std::io;
+use std::process::Output;
+
+/// Runs the commands of the linters, like `cargo clippy`.
+///
+/// Implementations can run them elsewhere, like on a build farm:
+/// the diff is still computed and the lints filtered by scout, where it runs.
+pub trait CommandRunner: Send + Sync {
+    /// Runs `command` within `limits`, retrying it according to `retry`, and captures its output.
+    ///
+    /// Returns the output, and how many times the command was retried.
+    fn output(
+        &self,
+        command: &LinterCommand,
+        retry: &RetryPolicy,
+        limits: &ResourceLimits,
+    ) -> (io::Result<Output>, u32);
+}
+
+/// Runs the commands on this machine.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Local;
+
+impl CommandRunner for Local {
+    fn output(
+        &self,
+        command: &LinterCommand,
+        retry: &RetryPolicy,
+        limits: &ResourceLimits,
+    ) -> (io::Result<Output>, u32) {
+        command.output(retry, limits)
+    }
+}
+
+/// Runs the commands through a wrapper program, like `ssh farm --` or a job submission script.
+///
+/// The wrapper gets the program and arguments of the command as its last arguments,
+/// and the directory it must run them from as `SCOUT_WORKING_DIR`.
+/// It must print the output of the command, and exit with its status.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Wrapper {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl Wrapper {
+    /// The wrapper of the `program` and `args` of `argv`, `None` if it is empty.
+    #[must_use]
+    pub fn from_argv(argv: Vec<String>) -> Option<Self> {
+        let mut argv = argv.into_iter();
+        Some(Self {
+            program: argv.next()?,
+            args: argv.collect(),
+        })
+    }
+
+    /// The command running `command` through the wrapper.
+    #[must_use]
+    pub fn wrap(&self, command: &LinterCommand) -> LinterCommand {
+        let mut envs = command.envs.clone();
+        envs.push((
+            "SCOUT_WORKING_DIR".to_string(),
+            command.working_dir.display().to_string(),
+        ));
+        let mut args = self.args.clone();
+        args.push(command.program.clone());
+        args.extend(command.args.iter().cloned());
+        LinterCommand {
+            working_dir: command.working_dir.clone(),
+            envs,
+            program: self.program.clone(),
+            args,
+        }
+    }
+}
+
+impl CommandRunner for Wrapper {
+    fn output(
+        &self,
+        command: &LinterCommand,
+        retry: &RetryPolicy,
+        limits: &ResourceLimits,
+    ) -> (io::Result<Output>, u32) {
+        self.wrap(command).output(retry, limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandRunner, Local, Wrapper};
+    use crate::limits::ResourceLimits;
+    use crate::linter::LinterCommand;
+    use crate::retry::RetryPolicy;
+    use std::path::PathBuf;
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_wrap() {
+        let wrapper = Wrapper::from_argv(strings(&["ssh", "farm", "--"])).unwrap();
+        let command = LinterCommand {
+            working_dir: PathBuf::from("/repo"),
+            envs: vec![("RUST_BACKTRACE".to_string(), "full".to_string())],
+            program: "cargo".to_string(),
+            args: strings(&["clippy", "--message-format", "json"]),
+        };
+        assert_eq!(
+            "(in /repo) RUST_BACKTRACE=full SCOUT_WORKING_DIR=/repo \
+             ssh farm -- cargo clippy --message-format json",
+            wrapper.wrap(&command).to_string()
+        );
+        assert_eq!(None, Wrapper::from_argv(Vec::new()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output() {
+        let command = LinterCommand {
+            working_dir: std::env::temp_dir(),
+            envs: Vec::new(),
+            program: "echo".to_string(),
+            args: strings(&["lint"]),
+        };
+        let retry = RetryPolicy::default();
+        let limits = ResourceLimits::default();
+        let local = Local.output(&command, &retry, &limits).0.unwrap();
+        assert_eq!(b"lint\n".to_vec(), local.stdout);
+        let wrapper = Wrapper::from_argv(strings(&[
+            "sh",
+            "-c",
+            "echo \"$@\" from $SCOUT_WORKING_DIR",
+            "sh",
+        ]))
+        .unwrap();
+        let wrapped = wrapper.output(&command, &retry, &limits).0.unwrap();
+        assert_eq!(
+            format!("echo lint from {}\n", std::env::temp_dir().display()).into_bytes(),
+            wrapped.stdout
+        );
+    }
+}
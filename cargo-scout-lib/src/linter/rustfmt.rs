@@ -1,28 +1,92 @@
 use crate::error::Error;
-use crate::linter::{Lint, Linter, Location};
-use crate::utils::get_absolute_file_path;
+use crate::limits::ResourceLimits;
+use crate::linter::container::Container;
+use crate::linter::runner::CommandRunner;
+use crate::linter::{with_cargo_args, Invocation, Lint, Linter, LinterCommand, Location};
+use crate::retry::RetryPolicy;
+use crate::utils::{decode_output, get_absolute_file_path};
 use cargo_scout_macros::info;
 use colored::Colorize;
 use serde::Deserialize;
-use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
 #[derive(Default)]
-pub struct RustFmt {}
+pub struct RustFmt {
+    retry: RetryPolicy,
+    limits: ResourceLimits,
+    container: Option<Container>,
+    runner: Option<Arc<dyn CommandRunner>>,
+    /// How many times the rustfmt commands were retried
+    retries: AtomicU32,
+}
 
 impl Linter for RustFmt {
     fn lints(&self, working_dir: impl Into<PathBuf>) -> Result<Vec<Lint>, Error> {
-        let working_dir = working_dir.into();
+        self.invoke(&Invocation::new(working_dir))
+    }
+
+    fn invoke(&self, invocation: &Invocation) -> Result<Vec<Lint>, Error> {
         info!(
             "[RustFmt] - checking format for directory {}",
-            &working_dir.to_str().unwrap_or("<no directory>")
+            &invocation.working_dir.to_str().unwrap_or("<no directory>")
         );
-        let rustfmt_output = Self::fmt(working_dir)?;
-        lints(&rustfmt_output)
+        let rustfmt_output = self.fmt(invocation)?;
+        let mut lints = lints(&rustfmt_output)?;
+        if let Some(container) = &self.container {
+            container.map_lints(&mut lints);
+        }
+        Ok(lints)
+    }
+
+    fn command(&self, invocation: &Invocation) -> Option<LinterCommand> {
+        let command = LinterCommand {
+            working_dir: invocation.working_dir.clone(),
+            envs: Vec::new(),
+            program: "cargo".to_string(),
+            // The lint args are clippy flags, which rustfmt doesn't take
+            args: with_cargo_args(Self::command_parameters(), invocation.cargo_args())
+                .into_iter()
+                .map(ToString::to_string)
+                .collect(),
+        };
+        Some(match &self.container {
+            Some(container) => container.wrap(&command),
+            None => command,
+        })
+    }
+
+    fn retries(&self) -> u32 {
+        self.retries.load(Ordering::Relaxed)
     }
 }
 
 impl RustFmt {
+    /// Sets how rustfmt is retried when cargo fails for a transient reason.
+    pub fn set_retry(&mut self, retry: RetryPolicy) -> &mut Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the resource limits cargo runs within.
+    pub fn set_limits(&mut self, limits: ResourceLimits) -> &mut Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Runs rustfmt in `container`, instead of on the host.
+    pub fn set_container(&mut self, container: Option<Container>) -> &mut Self {
+        self.container = container;
+        self
+    }
+
+    /// Runs rustfmt with `runner`, like on a build farm, instead of on this machine.
+    pub fn set_runner(&mut self, runner: Option<Arc<dyn CommandRunner>>) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
     fn command_parameters() -> Vec<&'static str> {
         vec!["+nightly", "fmt", "--", "--emit", "json"]
     }
@@ -31,18 +95,18 @@ impl RustFmt {
     // because an external command
     // cannot be easily unit tested
     #[cfg(not(tarpaulin_include))]
-    fn fmt(path: impl AsRef<Path>) -> Result<String, Error> {
+    fn fmt(&self, invocation: &Invocation) -> Result<String, Error> {
         println!("rustfmt init");
-        let fmt_output = Command::new("cargo")
-            .current_dir(path)
-            .args(Self::command_parameters())
-            .output()
-            .expect("failed to run cargo fmt");
+        let (output, retries) = Linter::command(self, invocation)
+            .expect("rustfmt runs a command")
+            .run(self.runner.as_deref(), &self.retry, &self.limits);
+        self.retries.fetch_add(retries, Ordering::Relaxed);
+        let fmt_output = output.map_err(crate::signal::command_error)?;
 
         if fmt_output.status.success() {
-            Ok(String::from_utf8(fmt_output.stdout)?)
+            Ok(decode_output(fmt_output.stdout, "rustfmt"))
         } else {
-            Err(Error::Command(String::from_utf8(fmt_output.stderr)?))
+            Err(Error::Command(decode_output(fmt_output.stderr, "rustfmt")))
         }
     }
 }
@@ -73,10 +137,14 @@ fn lints(fmt_output: &str) -> Result<Vec<Lint>, Error> {
                     if let Ok(path) = get_absolute_file_path(fmt_lint.name.clone()) {
                         Some(Lint {
                             message: display_mismatch(mismatch, &path),
+                            code: None,
                             location: Location {
                                 path,
                                 lines: [mismatch.original_begin_line, mismatch.original_end_line],
+                                // Mismatches are whole lines, without columns nor snippets
+                                ..Location::default()
                             },
+                            suggestions: Vec::new(),
                         })
                     } else {
                         None
@@ -119,8 +187,8 @@ fn display_mismatch(mismatch: &FmtMismatch, path: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::{
-        display_mismatch, get_absolute_file_path, lints, Error, FmtMismatch, Lint, Location,
-        RustFmt,
+        display_mismatch, get_absolute_file_path, lints, Error, FmtMismatch, Invocation, Lint,
+        Linter, Location, RustFmt,
     };
 
     #[test]
@@ -131,6 +199,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_command_without_lint_args() {
+        let mut invocation = Invocation::new("/repo/foo");
+        invocation.lint_args = vec!["-A".to_string(), "clippy::pedantic".to_string()];
+        let command = Linter::command(&RustFmt::default(), &invocation).unwrap();
+        assert_eq!(
+            vec!["+nightly", "fmt", "--", "--emit", "json"],
+            command.args
+        );
+    }
+
     #[test]
     fn test_display_mismatch_one_line() -> Result<(), Error> {
         let mismatch = FmtMismatch {
@@ -177,11 +256,14 @@ mod tests {
             location: Location {
                 lines: [1, 1],
                 path: path.clone(),
+                ..Location::default()
             },
             message: format!(
                 "Diff in {} at line 1:\n-    pub mod config;\n+pub mod config;\n",
                 path
             ),
+            code: None,
+            suggestions: Vec::new(),
         }];
 
         let actual_lints = lints(fmt_output).unwrap();
@@ -0,0 +1,492 @@
+use crate::linter::{Lint, Location, Suggestion};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A SARIF log, as written by analyzers with a SARIF output, like semgrep or CodeQL.
+///
+/// Only what maps to a `Lint` is read.
+#[derive(Deserialize)]
+struct Log {
+    #[serde(default)]
+    runs: Vec<Run>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    #[serde(default)]
+    tool: Tool,
+    #[serde(default)]
+    results: Vec<SarifResult>,
+    /// The files the results refer to by index
+    #[serde(default)]
+    artifacts: Vec<Artifact>,
+    /// The base uris the relative uris are relative to, like `%SRCROOT%`
+    #[serde(default)]
+    original_uri_base_ids: BTreeMap<String, ArtifactLocation>,
+}
+
+#[derive(Deserialize, Default)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Deserialize, Default)]
+struct ToolComponent {
+    name: String,
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    default_configuration: Option<Configuration>,
+    /// The messages results refer to by id
+    #[serde(default)]
+    message_strings: BTreeMap<String, Message>,
+}
+
+#[derive(Deserialize)]
+struct Configuration {
+    level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Artifact {
+    location: Option<ArtifactLocation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: Option<String>,
+    uri_base_id: Option<String>,
+    index: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: Option<String>,
+    rule_index: Option<usize>,
+    /// `fail` when absent, `pass` and the others aren't findings
+    kind: Option<String>,
+    /// `error`, `warning`, `note` or `none`, the level of the rule when absent
+    level: Option<String>,
+    message: Message,
+    #[serde(default)]
+    locations: Vec<ResultLocation>,
+    #[serde(default)]
+    fixes: Vec<Fix>,
+    #[serde(default)]
+    suppressions: Vec<Suppression>,
+    /// `absent` for the results of a baseline that are gone
+    baseline_state: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    text: Option<String>,
+    /// The id of a message string of the rule, whose `{0}` placeholders are the `arguments`
+    id: Option<String>,
+    #[serde(default)]
+    arguments: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultLocation {
+    physical_location: Option<PhysicalLocation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Option<Region>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: Option<u32>,
+    end_line: Option<u32>,
+    #[serde(default)]
+    start_column: u32,
+    #[serde(default)]
+    end_column: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    #[serde(default)]
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    #[serde(default)]
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: Option<ArtifactContent>,
+}
+
+#[derive(Deserialize)]
+struct ArtifactContent {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Suppression {
+    /// `rejected` suppressions don't suppress the result
+    status: Option<String>,
+}
+
+impl Run {
+    /// The path of `location`, resolved against the base uris of the run,
+    /// relative when the run doesn't tell what it is relative to.
+    fn path(&self, location: &ArtifactLocation) -> Option<String> {
+        let location = match (&location.uri, location.index) {
+            (None, Some(index)) => self.artifacts.get(index)?.location.as_ref()?,
+            _ => location,
+        };
+        let mut path = uri_path(location.uri.as_deref()?);
+        let mut base_id = location.uri_base_id.as_deref();
+        // Base uris can be relative to other base uris, without cycles
+        for _ in 0..self.original_uri_base_ids.len() {
+            let Some(base) = base_id.and_then(|id| self.original_uri_base_ids.get(id)) else {
+                break;
+            };
+            if std::path::Path::new(&path).is_absolute() {
+                break;
+            }
+            let Some(uri) = base.uri.as_deref() else {
+                break;
+            };
+            path = std::path::Path::new(&uri_path(uri))
+                .join(&path)
+                .to_string_lossy()
+                .into_owned();
+            base_id = base.uri_base_id.as_deref();
+        }
+        Some(path)
+    }
+
+    /// The rule of `result`, by index or by id.
+    fn rule(&self, result: &SarifResult) -> Option<&Rule> {
+        let rules = &self.tool.driver.rules;
+        match (result.rule_index, &result.rule_id) {
+            (Some(index), _) => rules.get(index),
+            (None, Some(id)) => rules.iter().find(|rule| rule.id == *id),
+            (None, None) => None,
+        }
+    }
+
+    /// The lints of `result`, one per physical location.
+    ///
+    /// Results without a line can't be matched to the diff, they are left out.
+    fn lints(&self, result: &SarifResult) -> Vec<Lint> {
+        let rule = self.rule(result);
+        let code = result
+            .rule_id
+            .clone()
+            .or_else(|| rule.map(|rule| rule.id.clone()));
+        let level =
+            match result.level.as_deref().or_else(|| {
+                rule.and_then(|rule| rule.default_configuration.as_ref()?.level.as_deref())
+            }) {
+                Some("error") => "error",
+                Some("note" | "none") => "note",
+                _ => "warning",
+            };
+        let text = text(&result.message, rule);
+        let suggestions: Vec<Suggestion> = result
+            .fixes
+            .iter()
+            .flat_map(|fix| &fix.artifact_changes)
+            .flat_map(|change| {
+                let path = self.path(&change.artifact_location);
+                change.replacements.iter().filter_map(move |replacement| {
+                    let region = &replacement.deleted_region;
+                    let start_line = region.start_line?;
+                    // Fixes without columns would replace a part of the lines no one can tell
+                    if region.start_column == 0 || region.end_column == 0 {
+                        return None;
+                    }
+                    Some(Suggestion {
+                        path: path.clone()?,
+                        lines: [start_line, region.end_line.unwrap_or(start_line)],
+                        columns: [region.start_column, region.end_column],
+                        replacement: replacement
+                            .inserted_content
+                            .as_ref()
+                            .and_then(|content| content.text.clone())
+                            .unwrap_or_default(),
+                    })
+                })
+            })
+            .collect();
+        let mut lints = Vec::new();
+        for location in &result.locations {
+            let Some(physical) = &location.physical_location else {
+                continue;
+            };
+            let Some(region) = &physical.region else {
+                continue;
+            };
+            let Some(start_line) = region.start_line else {
+                continue;
+            };
+            let Some(path) = self.path(&physical.artifact_location) else {
+                continue;
+            };
+            let location = Location {
+                path,
+                lines: [start_line, region.end_line.unwrap_or(start_line)],
+                columns: [region.start_column, region.end_column],
+                ..Location::default()
+            };
+            let mut message = format!(
+                "{}: {}\n --> {}:{}\n",
+                level,
+                text,
+                location.path,
+                location.position()
+            );
+            match (&code, self.tool.driver.name.as_str()) {
+                (Some(code), "") => message.push_str(&format!("  = note: `{}`\n", code)),
+                (Some(code), tool) => {
+                    message.push_str(&format!("  = note: `{}` of {}\n", code, tool));
+                }
+                (None, _) => {}
+            }
+            lints.push(Lint {
+                message,
+                code: code.clone(),
+                location,
+                suggestions: suggestions.clone(),
+            });
+        }
+        lints
+    }
+}
+
+impl SarifResult {
+    /// Whether the result is a finding to report: a failure or something to review,
+    /// neither suppressed nor gone since the baseline.
+    fn is_finding(&self) -> bool {
+        let suppressed = self
+            .suppressions
+            .iter()
+            .any(|suppression| suppression.status.as_deref() != Some("rejected"));
+        let kind = self.kind.as_deref().unwrap_or("fail");
+        matches!(kind, "fail" | "review" | "open")
+            && !suppressed
+            && self.baseline_state.as_deref() != Some("absent")
+    }
+}
+
+/// The text of `message`, or of the message string of `rule` it refers to, with its arguments.
+fn text(message: &Message, rule: Option<&Rule>) -> String {
+    let template = message.text.as_deref().or_else(|| {
+        let id = message.id.as_deref()?;
+        rule?.message_strings.get(id)?.text.as_deref()
+    });
+    let mut text = template.unwrap_or_default().to_string();
+    for (i, argument) in message.arguments.iter().enumerate() {
+        text = text.replace(&format!("{{{}}}", i), argument);
+    }
+    text
+}
+
+/// The path of a uri, like `file:///src/lib.rs` or `src/my%20lib.rs`, percent-decoded.
+fn uri_path(uri: &str) -> String {
+    let path = match uri.strip_prefix("file://") {
+        // The host of `file://host/path` is dropped
+        Some(path) => &path[path.find('/').unwrap_or(0)..],
+        None => uri,
+    };
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .filter(|_| bytes[i] == b'%');
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let decoded = String::from_utf8_lossy(&decoded).into_owned();
+    // `file:///C:/src/lib.rs` is `C:/src/lib.rs` on Windows
+    match decoded.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => decoded[1..].to_string(),
+        _ => decoded,
+    }
+}
+
+/// Parses the findings of a SARIF log, one lint per physical location.
+///
+/// The rule id is the code of the lints, and the fixes with columns their suggestions.
+/// Suppressed results, those that passed, and those gone since the baseline are left out.
+pub(crate) fn lints(sarif: &str) -> Result<Vec<Lint>, crate::error::Error> {
+    let log: Log = serde_json::from_str(sarif)?;
+    Ok(log
+        .runs
+        .iter()
+        .flat_map(|run| {
+            run.results
+                .iter()
+                .filter(|result| result.is_finding())
+                .flat_map(move |result| run.lints(result))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lints, uri_path};
+    use crate::linter::{Lint, Location, Suggestion};
+
+    #[test]
+    fn test_lints() -> Result<(), crate::error::Error> {
+        let sarif = r#"{
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {"driver": {"name": "semgrep"}},
+                "results": [
+                    {
+                        "ruleId": "rust.lang.security.unsafe-usage",
+                        "level": "error",
+                        "message": {"text": "unsafe block"},
+                        "locations": [{"physicalLocation": {
+                            "artifactLocation": {"uri": "src/lib.rs"},
+                            "region": {"startLine": 4, "startColumn": 5, "endColumn": 11}
+                        }}],
+                        "fixes": [{"artifactChanges": [{
+                            "artifactLocation": {"uri": "src/lib.rs"},
+                            "replacements": [{
+                                "deletedRegion": {"startLine": 4, "startColumn": 5, "endColumn": 11},
+                                "insertedContent": {"text": "safe"}
+                            }]
+                        }]}]
+                    },
+                    {
+                        "ruleId": "whole-file",
+                        "message": {"text": "no region"},
+                        "locations": [{"physicalLocation": {"artifactLocation": {"uri": "src/lib.rs"}}}]
+                    },
+                    {
+                        "ruleId": "suppressed",
+                        "message": {"text": "suppressed"},
+                        "locations": [{"physicalLocation": {
+                            "artifactLocation": {"uri": "src/lib.rs"},
+                            "region": {"startLine": 1}
+                        }}],
+                        "suppressions": [{"kind": "inSource"}]
+                    }
+                ]
+            }]
+        }"#;
+        assert_eq!(
+            vec![Lint {
+                message: "error: unsafe block\n --> src/lib.rs:4:5\n  = note: `rust.lang.security.unsafe-usage` of semgrep\n".to_string(),
+                code: Some("rust.lang.security.unsafe-usage".to_string()),
+                location: Location {
+                    path: "src/lib.rs".to_string(),
+                    lines: [4, 4],
+                    columns: [5, 11],
+                    ..Location::default()
+                },
+                suggestions: vec![Suggestion {
+                    path: "src/lib.rs".to_string(),
+                    lines: [4, 4],
+                    columns: [5, 11],
+                    replacement: "safe".to_string(),
+                }],
+            }],
+            lints(sarif)?
+        );
+        assert!(lints("{\"runs\": 1}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_codeql() -> Result<(), crate::error::Error> {
+        // CodeQL refers to its rules and files by index, relative to `%SRCROOT%`
+        let sarif = r#"{
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {"driver": {
+                    "name": "CodeQL",
+                    "rules": [{
+                        "id": "rust/hard-coded-cryptographic-value",
+                        "defaultConfiguration": {"level": "error"},
+                        "messageStrings": {"default": {"text": "Hard-coded value flows to {0}."}}
+                    }]
+                }},
+                "originalUriBaseIds": {"%SRCROOT%": {"uri": "file:///home/ci/my%20repo/"}},
+                "artifacts": [{"location": {"uri": "src/crypto.rs", "uriBaseId": "%SRCROOT%"}}],
+                "results": [
+                    {
+                        "ruleIndex": 0,
+                        "message": {"id": "default", "arguments": ["the key"]},
+                        "locations": [{"physicalLocation": {
+                            "artifactLocation": {"index": 0},
+                            "region": {"startLine": 12, "endLine": 14}
+                        }}]
+                    },
+                    {
+                        "ruleIndex": 0,
+                        "kind": "pass",
+                        "message": {"text": "passed"},
+                        "locations": [{"physicalLocation": {
+                            "artifactLocation": {"index": 0},
+                            "region": {"startLine": 1}
+                        }}]
+                    }
+                ]
+            }]
+        }"#;
+        let lints = lints(sarif)?;
+        assert_eq!(1, lints.len());
+        assert_eq!(
+            Some("rust/hard-coded-cryptographic-value".to_string()),
+            lints[0].code
+        );
+        assert_eq!("/home/ci/my repo/src/crypto.rs", lints[0].location.path);
+        assert_eq!([12, 14], lints[0].location.lines);
+        assert!(lints[0]
+            .message
+            .starts_with("error: Hard-coded value flows to the key.\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_uri_path() {
+        assert_eq!("/src/lib.rs", uri_path("file:///src/lib.rs"));
+        assert_eq!("/src/lib.rs", uri_path("file://host/src/lib.rs"));
+        assert_eq!("C:/src/lib.rs", uri_path("file:///C:/src/lib.rs"));
+        assert_eq!("src/my lib.rs", uri_path("src/my%20lib.rs"));
+        assert_eq!("src/100%.rs", uri_path("src/100%.rs"));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::error::Error;
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How locks on the persistent files of scout, like the store or `latest.json`, are taken.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LockOptions {
+    /// How long a busy lock is waited for
+    pub timeout: Duration,
+    /// How long to wait between two attempts
+    pub poll: Duration,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            poll: Duration::from_millis(50),
+        }
+    }
+}
+
+/// An exclusive lock on a file, held until the guard is dropped.
+///
+/// The lock is an advisory lock of the OS on a `<file>.lock` file next to it,
+/// so it works across processes and the OS releases it when the process holding it dies.
+/// The `.lock` file itself is left in place: removing it would let two processes lock
+/// two different files of the same name.
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl FileLock {
+    /// Locks `path`, with the default options.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn acquire(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::acquire_with(path, &LockOptions::default())
+    }
+
+    /// Locks `path`, waiting for the process holding the lock for up to `options.timeout`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn acquire_with(path: impl AsRef<Path>, options: &LockOptions) -> Result<Self, Error> {
+        let path = lock_path(path.as_ref());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let started = Instant::now();
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => return Ok(Self { path, file }),
+                Err(error) if error.kind() == fs2::lock_contended_error().kind() => {}
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+            if started.elapsed() >= options.timeout {
+                return Err(Error::Lock(format!(
+                    "{} is still locked after {:?} by another scout",
+                    path.display(),
+                    options.timeout
+                )));
+            }
+            std::thread::sleep(options.poll);
+        }
+    }
+
+    /// The lock file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock too
+        let _ = self.file.unlock();
+    }
+}
+
+/// Writes `contents` to `path` under its lock, through a temporary file renamed over it,
+/// so readers never see a partial file.
+#[allow(clippy::missing_errors_doc)]
+pub fn write_locked(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), Error> {
+    let path = path.as_ref();
+    let _lock = FileLock::acquire(path)?;
+    let mut temporary = path.as_os_str().to_os_string();
+    temporary.push(format!(".{}.tmp", std::process::id()));
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut lock = path.as_os_str().to_os_string();
+    lock.push(".lock");
+    PathBuf::from(lock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_locked, FileLock, LockOptions};
+    use std::time::Duration;
+
+    fn options() -> LockOptions {
+        LockOptions {
+            timeout: Duration::from_millis(100),
+            poll: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn test_acquire() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("scout/latest.json");
+        let lock = FileLock::acquire_with(&path, &options()).unwrap();
+        assert_eq!(dir.path().join("scout/latest.json.lock"), lock.path());
+        let error = FileLock::acquire_with(&path, &options()).unwrap_err();
+        assert!(error.to_string().contains("still locked"));
+        drop(lock);
+        assert!(FileLock::acquire_with(&path, &options()).is_ok());
+    }
+
+    #[test]
+    fn test_leftover_lock_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("store.db");
+        // A scout that died leaves its lock file, but not its lock
+        std::fs::write(dir.path().join("store.db.lock"), "4194305\n").unwrap();
+        assert!(FileLock::acquire_with(&path, &options()).is_ok());
+    }
+
+    #[test]
+    fn test_write_locked() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("latest.json");
+        write_locked(&path, "{}").unwrap();
+        assert_eq!("{}", std::fs::read_to_string(&path).unwrap());
+        // Only the lock file is left next to it
+        assert_eq!(2, std::fs::read_dir(dir.path()).unwrap().count());
+    }
+}
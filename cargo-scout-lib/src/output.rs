@@ -0,0 +1,200 @@
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+/// Whether the output should be colored.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ColorChoice {
+    /// Color the output if the environment allows it and stdout is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!(
+                "unknown color choice {}, expected auto, always or never",
+                s
+            )),
+        }
+    }
+}
+
+/// What the terminal cargo-scout writes to is capable of.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Capabilities {
+    /// stdout is a terminal
+    pub tty: bool,
+    /// The output should be colored
+    pub color: bool,
+    /// The columns of the terminal, if stdout is one
+    pub width: Option<usize>,
+}
+
+impl Capabilities {
+    /// Detects the capabilities from the environment and stdout.
+    #[must_use]
+    pub fn detect(choice: ColorChoice) -> Self {
+        let tty = std::io::stdout().is_terminal();
+        Self {
+            tty,
+            color: should_colorize(choice, tty, |name| std::env::var(name).ok()),
+            width: terminal_width(tty, |name| std::env::var(name).ok()),
+        }
+    }
+}
+
+/// The columns of the terminal: `COLUMNS` if set, or else the size of the terminal of stdout.
+#[must_use]
+pub fn terminal_width(tty: bool, env: impl Fn(&str) -> Option<String>) -> Option<usize> {
+    if !tty {
+        return None;
+    }
+    env("COLUMNS")
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|columns| *columns > 0)
+        .or_else(window_width)
+}
+
+#[cfg(unix)]
+fn window_width() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // Safety: TIOCGWINSZ only writes the size of the terminal to `size`
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 {
+        return None;
+    }
+    Some(usize::from(size.ws_col)).filter(|columns| *columns > 0)
+}
+
+#[cfg(not(unix))]
+fn window_width() -> Option<usize> {
+    None
+}
+
+/// Decides whether to color the output.
+///
+/// An explicit choice wins, then a non empty `CLICOLOR_FORCE` other than `0`,
+/// then a non empty `NO_COLOR` (see <https://no-color.org>),
+/// then `CLICOLOR=0`, and finally whether the output is a terminal.
+#[must_use]
+pub fn should_colorize(
+    choice: ColorChoice,
+    tty: bool,
+    env: impl Fn(&str) -> Option<String>,
+) -> bool {
+    let is_set = |name| env(name).filter(|value| !value.is_empty());
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            if is_set("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+                true
+            } else if is_set("NO_COLOR").is_some() || is_set("CLICOLOR").as_deref() == Some("0") {
+                false
+            } else {
+                tty
+            }
+        }
+    }
+}
+
+/// Detects the terminal capabilities and configures every colored output accordingly.
+///
+/// On Windows, ANSI escape codes are enabled on the console, and colors are
+/// turned off when that fails.
+/// This should be called once, before anything is printed.
+pub fn init(choice: ColorChoice) -> Capabilities {
+    let capabilities = Capabilities::detect(choice);
+    #[cfg(windows)]
+    let capabilities = Capabilities {
+        color: capabilities.color
+            && (!capabilities.tty || colored::control::set_virtual_terminal(true).is_ok()),
+        ..capabilities
+    };
+    colored::control::set_override(capabilities.color);
+    capabilities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{should_colorize, terminal_width, ColorChoice};
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_string())
+        }
+    }
+
+    #[test]
+    fn test_explicit_choice_wins() {
+        let vars = [("NO_COLOR", "1")];
+        assert!(should_colorize(ColorChoice::Always, false, env(&vars)));
+        assert!(!should_colorize(
+            ColorChoice::Never,
+            true,
+            env(&[("CLICOLOR_FORCE", "1")])
+        ));
+    }
+
+    #[test]
+    fn test_auto_follows_the_tty() {
+        assert!(should_colorize(ColorChoice::Auto, true, env(&[])));
+        assert!(!should_colorize(ColorChoice::Auto, false, env(&[])));
+    }
+
+    #[test]
+    fn test_environment() {
+        assert!(!should_colorize(
+            ColorChoice::Auto,
+            true,
+            env(&[("NO_COLOR", "1")])
+        ));
+        // An empty NO_COLOR doesn't disable colors
+        assert!(should_colorize(
+            ColorChoice::Auto,
+            true,
+            env(&[("NO_COLOR", "")])
+        ));
+        assert!(!should_colorize(
+            ColorChoice::Auto,
+            true,
+            env(&[("CLICOLOR", "0")])
+        ));
+        assert!(should_colorize(
+            ColorChoice::Auto,
+            false,
+            env(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")])
+        ));
+        assert!(!should_colorize(
+            ColorChoice::Auto,
+            false,
+            env(&[("CLICOLOR_FORCE", "0")])
+        ));
+    }
+
+    #[test]
+    fn test_terminal_width() {
+        assert_eq!(None, terminal_width(false, env(&[("COLUMNS", "120")])));
+        assert_eq!(Some(120), terminal_width(true, env(&[("COLUMNS", "120")])));
+    }
+
+    #[test]
+    fn test_color_choice_from_str() {
+        assert_eq!(Ok(ColorChoice::Never), "never".parse());
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+    }
+}
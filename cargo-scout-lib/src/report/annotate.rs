@@ -0,0 +1,210 @@
+use crate::error::Error;
+use crate::linter::Lint;
+use crate::utils::{normalize_path, text_lines};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+
+/// The marker of the comments injected under the lines of the lints.
+pub const MARKER: &str = "// ^ LINT:";
+
+/// Writes a copy of each of the Rust `files` in `dir`, at their path relative to `repo_root`,
+/// with a comment under the first line of each of their lints, and returns how many were written.
+///
+/// The files which no longer exist, like deleted ones, and those outside of the repository are skipped.
+///
+/// # Errors
+///
+/// Fails when `dir` is in the work tree of `repo_root`: the copies would be scouted as changes.
+pub fn write_annotated(
+    lints: &[Lint],
+    files: &[String],
+    repo_root: &Path,
+    dir: &Path,
+) -> Result<usize, Error> {
+    if resolved(dir)?.starts_with(resolved(repo_root)?) {
+        return Err(Error::Annotate(format!(
+            "{} is in the work tree of {}, the annotated copies would be changes",
+            dir.display(),
+            repo_root.display()
+        )));
+    }
+    let mut written = 0;
+    for file in files {
+        if Path::new(file).extension().is_none_or(|ext| ext != "rs") {
+            continue;
+        }
+        let Ok(relative) = Path::new(file).strip_prefix(repo_root) else {
+            continue;
+        };
+        let Ok(source) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let file_lints: Vec<&Lint> = lints
+            .iter()
+            .filter(|lint| normalize_path(&lint.location.path) == normalize_path(file))
+            .collect();
+        let target = dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, annotate(&source, &file_lints))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// `path` made absolute, with the symbolic links of the directories that exist resolved,
+/// whether it exists or not.
+fn resolved(path: &Path) -> Result<PathBuf, Error> {
+    let path = std::env::current_dir()?.join(path);
+    let mut missing = Vec::new();
+    let mut existing = path.as_path();
+    loop {
+        if let Ok(canonical) = std::fs::canonicalize(existing) {
+            return Ok(missing
+                .iter()
+                .rev()
+                .fold(canonical, |path, part| path.join(part)));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return Ok(path),
+        }
+    }
+}
+
+/// `source` with a `// ^ LINT: <message> [<code>]` comment under the first line of each lint,
+/// indented like the line.
+#[must_use]
+pub fn annotate(source: &str, lints: &[&Lint]) -> String {
+    let mut annotated = String::with_capacity(source.len());
+    for (index, line) in text_lines(source).enumerate() {
+        annotated.push_str(line);
+        annotated.push('\n');
+        let number = u32::try_from(index + 1).unwrap_or(u32::MAX);
+        let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+        let mut comments: Vec<String> = Vec::new();
+        for lint in lints.iter().filter(|lint| lint.location.lines[0] == number) {
+            // A lint reported on several spans of the line is only annotated once
+            let comment = comment(lint);
+            if !comments.contains(&comment) {
+                comments.push(comment);
+            }
+        }
+        for comment in comments {
+            annotated.push_str(&indent);
+            annotated.push_str(&comment);
+            annotated.push('\n');
+        }
+    }
+    annotated
+}
+
+fn comment(lint: &Lint) -> String {
+    let message = lint.message.lines().next().unwrap_or_default().trim();
+    match &lint.code {
+        Some(code) => format!("{} {} [{}]", MARKER, message, code),
+        None => format!("{} {}", MARKER, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotate, write_annotated};
+    use crate::error::Error;
+    use crate::linter::{Lint, Location};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn lint(path: &str, line: u32, message: &str, code: Option<&str>) -> Lint {
+        Lint {
+            message: message.to_string(),
+            code: code.map(ToString::to_string),
+            location: Location {
+                path: path.to_string(),
+                lines: [line, line + 1],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_annotate() {
+        let source = "fn main() {\n    let a = &1;\n}\n";
+        let borrow = lint(
+            "src/main.rs",
+            2,
+            "warning: needless borrow\n --> src/main.rs:2:13",
+            Some("clippy::needless_borrow"),
+        );
+        let unused = lint("src/main.rs", 2, "warning: unused variable: `a`", None);
+        assert_eq!(
+            "fn main() {
+    let a = &1;
+    // ^ LINT: warning: needless borrow [clippy::needless_borrow]
+    // ^ LINT: warning: unused variable: `a`
+}
+",
+            annotate(source, &[&borrow, &unused, &borrow])
+        );
+        assert_eq!(source, annotate(source, &[]));
+    }
+
+    #[test]
+    fn test_write_annotated() -> Result<(), crate::error::Error> {
+        let repo = TempDir::new()?;
+        let out = TempDir::new()?;
+        fs::create_dir_all(repo.path().join("src"))?;
+        let file = repo.path().join("src/lib.rs");
+        fs::write(&file, "pub fn a() {}\n")?;
+        let file = file.to_string_lossy().into_owned();
+        let deleted = repo
+            .path()
+            .join("src/gone.rs")
+            .to_string_lossy()
+            .into_owned();
+        let lints = [lint(&file, 1, "warning: missing docs", None)];
+        fs::write(repo.path().join("Cargo.toml"), "[package]\n")?;
+        let manifest = repo
+            .path()
+            .join("Cargo.toml")
+            .to_string_lossy()
+            .into_owned();
+        // Its path starts like the one of the repository, but it is out of it
+        let sibling = format!("{}2/src/lib.rs", repo.path().display());
+        fs::create_dir_all(format!("{}2/src", repo.path().display()))?;
+        fs::write(&sibling, "pub fn b() {}\n")?;
+
+        let written = write_annotated(
+            &lints,
+            &[file, deleted, manifest, sibling.clone()],
+            repo.path(),
+            out.path(),
+        )?;
+        fs::remove_dir_all(format!("{}2", repo.path().display()))?;
+        assert_eq!(1, written);
+        assert_eq!(
+            "pub fn a() {}\n// ^ LINT: warning: missing docs\n",
+            fs::read_to_string(out.path().join("src/lib.rs"))?
+        );
+        assert!(!out.path().join("src/gone.rs").exists());
+        assert!(!out.path().join("Cargo.toml").exists());
+        assert_eq!(1, fs::read_dir(out.path())?.count());
+
+        assert!(matches!(
+            write_annotated(
+                &lints,
+                &[],
+                repo.path(),
+                &repo.path().join("target/annotated")
+            ),
+            Err(Error::Annotate(_))
+        ));
+        assert!(!repo.path().join("target").exists());
+        Ok(())
+    }
+}
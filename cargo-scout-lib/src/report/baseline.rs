@@ -0,0 +1,141 @@
+use crate::error::Error;
+use crate::linter::fingerprint::{fingerprints, Fingerprint};
+use crate::linter::Lint;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The lints accepted as debt, each tracked by an issue, which aren't reported anymore.
+///
+/// The lints are matched by their fingerprint, so a deferred lint stays deferred
+/// when unrelated changes move it around, and is reported again once its lines change.
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct Baseline {
+    pub deferred: Vec<Deferred>,
+}
+
+/// A deferred lint, and the issue tracking it.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct Deferred {
+    pub fingerprint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// The path of the file of the lint, relative to the repository root
+    pub path: String,
+    /// The url of the issue
+    pub issue: String,
+}
+
+impl Baseline {
+    /// Reads the baseline at `path`, an empty one if there is no such file.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        crate::lock::write_locked(path.as_ref(), serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// The url of the issue tracking the lint of `fingerprint`, if it is deferred.
+    #[must_use]
+    pub fn issue(&self, fingerprint: Fingerprint) -> Option<&str> {
+        let fingerprint = fingerprint.to_string();
+        self.deferred
+            .iter()
+            .find(|deferred| deferred.fingerprint == fingerprint)
+            .map(|deferred| deferred.issue.as_str())
+    }
+
+    /// Records `deferred`, replacing the issue of its lint if it had one.
+    pub fn defer(&mut self, deferred: Deferred) {
+        self.deferred
+            .retain(|previous| previous.fingerprint != deferred.fingerprint);
+        self.deferred.push(deferred);
+    }
+
+    /// Drops the deferred lints of `lints`, whose fingerprints are computed relative to `root`,
+    /// and returns how many were dropped.
+    pub fn drop_deferred(&self, lints: &mut Vec<Lint>, root: &Path) -> usize {
+        if self.deferred.is_empty() {
+            return 0;
+        }
+        let fingerprints = fingerprints(lints, root);
+        let count = lints.len();
+        let mut fingerprints = fingerprints.into_iter();
+        lints.retain(|_| {
+            fingerprints
+                .next()
+                .is_none_or(|fingerprint| self.issue(fingerprint).is_none())
+        });
+        count - lints.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Baseline, Deferred};
+    use crate::linter::{Lint, Location};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn lint(path: &str, line: u32) -> Lint {
+        Lint {
+            message: "warning: called `unwrap`".to_string(),
+            code: Some("clippy::unwrap_used".to_string()),
+            location: Location {
+                path: path.to_string(),
+                lines: [line, line],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_baseline() -> Result<(), crate::error::Error> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "fn a() {\n    a.unwrap();\n    b.unwrap();\n}\n")?;
+        let path = path.to_string_lossy().into_owned();
+        let deferred = lint(&path, 2);
+
+        let baseline_path = dir.path().join(".scout-baseline.json");
+        let mut baseline = Baseline::from_path(&baseline_path)?;
+        assert_eq!(Baseline::default(), baseline);
+        let deferred_lint = |issue: &str| Deferred {
+            fingerprint: deferred.fingerprint(dir.path()).to_string(),
+            code: deferred.code.clone(),
+            path: "lib.rs".to_string(),
+            issue: issue.to_string(),
+        };
+        baseline.defer(deferred_lint("https://example.com/issues/1"));
+        baseline.defer(deferred_lint("https://example.com/issues/2"));
+        baseline.write(&baseline_path)?;
+
+        let baseline = Baseline::from_path(&baseline_path)?;
+        assert_eq!(
+            vec![deferred_lint("https://example.com/issues/2")],
+            baseline.deferred
+        );
+        assert_eq!(
+            Some("https://example.com/issues/2"),
+            baseline.issue(deferred.fingerprint(dir.path()))
+        );
+
+        let reported = lint(&path, 3);
+        let mut lints = vec![deferred.clone(), reported.clone()];
+        assert_eq!(1, baseline.drop_deferred(&mut lints, dir.path()));
+        assert_eq!(vec![reported], lints);
+
+        // The lint is reported again once its lines change
+        fs::write(&path, "fn a() {\n    a.expect(\"a\").unwrap();\n}\n")?;
+        let mut lints = vec![deferred];
+        assert_eq!(0, baseline.drop_deferred(&mut lints, dir.path()));
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use super::json::{JsonReport, FORMAT_VERSION};
+use crate::config::scout::ScoutConfig;
+use crate::error::Error;
+use crate::linter::fingerprint::Fnv1a;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the cache key computation changes.
+const SALT: &str = "cargo-scout-result-cache-v1";
+
+/// The final reports of earlier runs, reused when a run scouts the same commits again,
+/// like a CI job retried because of an unrelated flaky test.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    /// The cache of the workspace at `workspace_root`, in a `cache` directory next to `latest.json`.
+    #[must_use]
+    pub fn new(workspace_root: impl AsRef<Path>) -> Self {
+        let latest = super::json::latest_path(workspace_root);
+        Self::in_dir(latest.with_file_name("cache"))
+    }
+
+    #[must_use]
+    pub fn in_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The report of the run with the `key`, if one was cached in a layout this version reads.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<JsonReport> {
+        JsonReport::from_path(self.path(key))
+            .ok()
+            .filter(|report| report.version == FORMAT_VERSION)
+    }
+
+    /// Caches the `report` of the run with the `key`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn put(&self, key: &str, report: &JsonReport) -> Result<(), Error> {
+        report.write(self.path(key))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+/// The cache key of a run, a hash of the commits it compares, of the configuration,
+/// and of the `options` it runs with, as they change the lints reported too.
+#[must_use]
+pub fn key(commits: &[String], config: &ScoutConfig, options: &[String]) -> String {
+    let mut hasher = Fnv1a::default();
+    hasher.write(SALT);
+    for commit in commits {
+        hasher.write(commit);
+    }
+    hasher.write(&format!("{:?}", config));
+    for option in options {
+        // Keeps `-b main` apart from `-bmain` and such
+        hasher.write(&format!("{}\0", option));
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key, ResultCache};
+    use crate::config::scout::ScoutConfig;
+    use crate::linter::{Lint, Location};
+    use crate::report::json::JsonReport;
+
+    #[test]
+    fn test_key() {
+        let commits = vec!["main (abc)".to_string(), "def".to_string()];
+        let config = ScoutConfig::default();
+        let options = vec!["lint".to_string(), "-b".to_string(), "main".to_string()];
+        let id = key(&commits, &config, &options);
+        assert_eq!(16, id.len());
+        assert_eq!(id, key(&commits, &config, &options));
+        assert_ne!(id, key(&commits[..1], &config, &options));
+        assert_ne!(id, key(&commits, &config, &options[..1]));
+        assert_ne!(
+            key(&commits, &config, &["ab".to_string()]),
+            key(&commits, &config, &["a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_get_put() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        let cache = ResultCache::in_dir(dir.path().join("cache"));
+        assert_eq!(None, cache.get("0123"));
+        let lints = vec![Lint {
+            message: "unused variable".to_string(),
+            code: Some("unused_variables".to_string()),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [3, 3],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }];
+        let report = JsonReport::new(&lints, "/repo");
+        cache.put("0123", &report)?;
+        assert_eq!(Some(report), cache.get("0123"));
+        assert_eq!(None, cache.get("4567"));
+        Ok(())
+    }
+}
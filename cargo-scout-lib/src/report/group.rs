@@ -0,0 +1,161 @@
+use crate::linter::Lint;
+use crate::scout::items::SyntaxMap;
+use crate::utils::relative_path;
+use crate::vcs::blame::Blames;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// What the warnings are grouped by.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GroupBy {
+    /// The `git blame` author of the first line of the warning
+    Author,
+    /// The lint code
+    Code,
+    /// The file, relative to the repository root
+    File,
+    /// The file and the innermost item holding the first line of the warning, like `src/lib.rs: Foo::bar`
+    Item,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "author" => Ok(Self::Author),
+            "code" => Ok(Self::Code),
+            "file" => Ok(Self::File),
+            "item" => Ok(Self::Item),
+            _ => Err(format!(
+                "unknown grouping {}, expected author, code, file or item",
+                s
+            )),
+        }
+    }
+}
+
+/// The warnings sharing a key.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Group<'a> {
+    pub key: String,
+    pub lints: Vec<&'a Lint>,
+}
+
+/// Groups `lints` by `by`, the largest groups first, then by key.
+///
+/// Paths are relative to the repository at `root`, which authors are blamed in.
+/// Warnings without a key are grouped under `unknown`.
+#[must_use]
+pub fn group<'a>(lints: &'a [Lint], by: GroupBy, root: &Path) -> Vec<Group<'a>> {
+    let mut blames = Blames::new(root);
+    let mut syntax = SyntaxMap::new();
+    let mut groups: BTreeMap<String, Vec<&Lint>> = BTreeMap::new();
+    for lint in lints {
+        let path = relative_path(&lint.location.path, root).replace('\\', "/");
+        let key = match by {
+            GroupBy::Author => blames.author(&path, lint.location.lines[0]),
+            GroupBy::Code => lint.code.clone(),
+            GroupBy::File => Some(path),
+            GroupBy::Item => syntax
+                .enclosing(&lint.location.path, lint.location.lines[0])
+                .map(|item| format!("{}: {}", path, item.name)),
+        };
+        groups
+            .entry(key.unwrap_or_else(|| "unknown".to_string()))
+            .or_default()
+            .push(lint);
+    }
+    let mut groups: Vec<Group> = groups
+        .into_iter()
+        .map(|(key, lints)| Group { key, lints })
+        .collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.lints.len()));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group, GroupBy};
+    use crate::linter::{Lint, Location};
+    use std::path::Path;
+
+    fn lint(path: &str, code: Option<&str>) -> Lint {
+        Lint {
+            message: String::new(),
+            code: code.map(str::to_string),
+            location: Location {
+                path: path.to_string(),
+                lines: [1, 1],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let lints = vec![
+            lint("/repo/src/lib.rs", Some("dead_code")),
+            lint("/repo/src/main.rs", Some("clippy::unwrap_used")),
+            lint("/repo/src/lib.rs", Some("clippy::unwrap_used")),
+            lint("/repo/src/lib.rs", None),
+        ];
+        let keys = |by| -> Vec<(String, usize)> {
+            group(&lints, by, Path::new("/repo"))
+                .into_iter()
+                .map(|group| (group.key, group.lints.len()))
+                .collect()
+        };
+        assert_eq!(
+            vec![
+                ("clippy::unwrap_used".to_string(), 2),
+                ("dead_code".to_string(), 1),
+                ("unknown".to_string(), 1)
+            ],
+            keys(GroupBy::Code)
+        );
+        assert_eq!(
+            vec![
+                ("src/lib.rs".to_string(), 3),
+                ("src/main.rs".to_string(), 1)
+            ],
+            keys(GroupBy::File)
+        );
+        // Outside of a repository, there is no one to blame
+        assert_eq!(vec![("unknown".to_string(), 4)], keys(GroupBy::Author));
+    }
+
+    #[test]
+    fn test_group_by_from_str() {
+        assert_eq!(Ok(GroupBy::Author), "author".parse());
+        assert_eq!(Ok(GroupBy::Item), "item".parse());
+        assert!("team".parse::<GroupBy>().is_err());
+    }
+
+    #[test]
+    fn test_group_by_item() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "fn foo() {\n    let a = 1;\n}\n\nfn bar() {}\n",
+        )
+        .unwrap();
+        let path = dir.path().join("lib.rs").to_string_lossy().into_owned();
+        let mut lints = vec![lint(&path, None), lint(&path, None), lint(&path, None)];
+        lints[1].location.lines = [2, 2];
+        lints[2].location.lines = [5, 5];
+        let keys: Vec<(String, usize)> = group(&lints, GroupBy::Item, dir.path())
+            .into_iter()
+            .map(|group| (group.key, group.lints.len()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("lib.rs: foo".to_string(), 2),
+                ("lib.rs: bar".to_string(), 1)
+            ],
+            keys
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::config::scout::IssuesConfig;
+use crate::error::Error;
+use crate::linter::fingerprint::Fingerprint;
+use crate::linter::Lint;
+use crate::utils::{decode_output, relative_path};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The environment variable the token of the issue tracker is read from.
+pub const TOKEN_ENV: &str = "SCOUT_ISSUES_TOKEN";
+
+/// Files an issue for a deferred lint in a tracker, such as GitHub or Jira, through its REST api.
+#[derive(Debug, Clone)]
+pub struct IssueExporter {
+    config: IssuesConfig,
+    token: String,
+}
+
+impl IssueExporter {
+    /// An exporter of `config`, authenticated with the token of `SCOUT_ISSUES_TOKEN`.
+    #[must_use]
+    pub fn new(config: IssuesConfig) -> Self {
+        let token = std::env::var(TOKEN_ENV).unwrap_or_default();
+        Self { config, token }
+    }
+
+    /// Replaces the placeholders of `template` with the json-escaped values of `lint`,
+    /// whose path is made relative to `root`.
+    ///
+    /// `{{token}}` is only replaced in a `header`, the payload of the issue can't leak it.
+    /// The values aren't searched for placeholders themselves.
+    fn render(
+        &self,
+        template: &str,
+        lint: &Lint,
+        fingerprint: Fingerprint,
+        root: &Path,
+        header: bool,
+    ) -> String {
+        let path = relative_path(&lint.location.path, root);
+        let code = lint.code.as_deref().unwrap_or("lint");
+        let title = format!("`{}` in {}", code, path);
+        let body = format!(
+            "Deferred by cargo-scout, at {}:{}:\n\n```\n{}\n```\n\nFingerprint: `{}`",
+            path,
+            lint.location.position(),
+            lint.message.trim_end(),
+            fingerprint
+        );
+        let escape = |value: &str| {
+            let quoted = serde_json::to_string(value).unwrap_or_default();
+            quoted[1..quoted.len() - 1].to_string()
+        };
+        let value = |name: &str| match name {
+            "title" => Some(escape(&title)),
+            "body" => Some(escape(&body)),
+            "code" => Some(escape(code)),
+            "path" => Some(escape(&path)),
+            "line" => Some(lint.location.lines[0].to_string()),
+            "fingerprint" => Some(fingerprint.to_string()),
+            "token" if header => Some(self.token.clone()),
+            _ => None,
+        };
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start + 2..].find("}}").map(|end| start + 2 + end) else {
+                break;
+            };
+            rendered.push_str(&rest[..start]);
+            match value(&rest[start + 2..end]) {
+                Some(value) => rendered.push_str(&value),
+                // Unknown placeholders are kept as they are
+                None => rendered.push_str(&rest[start..end + 2]),
+            }
+            rest = &rest[end + 2..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+
+    /// The json payload creating the issue of `lint`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn payload(
+        &self,
+        lint: &Lint,
+        fingerprint: Fingerprint,
+        root: &Path,
+    ) -> Result<String, Error> {
+        let rendered = self.render(&self.config.template, lint, fingerprint, root, false);
+        let payload: serde_json::Value = serde_json::from_str(&rendered)?;
+        Ok(payload.to_string())
+    }
+
+    /// The url of the issue, from the json `response` of the tracker.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn issue_url(&self, response: &str) -> Result<String, Error> {
+        let response: serde_json::Value = serde_json::from_str(response)?;
+        match response.pointer(&self.config.url_field) {
+            Some(serde_json::Value::String(url)) => Ok(url.clone()),
+            Some(serde_json::Value::Number(id)) => Ok(id.to_string()),
+            _ => Err(Error::Issues(format!(
+                "the response of the tracker has no `{}`: {}",
+                self.config.url_field, response
+            ))),
+        }
+    }
+
+    /// The `curl` config posting the issue of `lint`: its headers and payload.
+    ///
+    /// The config is read from stdin, the headers and their token stay out of the command line.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn curl_config(
+        &self,
+        lint: &Lint,
+        fingerprint: Fingerprint,
+        root: &Path,
+    ) -> Result<String, Error> {
+        let quote = |value: &str| {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+                .replace('\t', "\\t");
+            format!("\"{}\"", escaped)
+        };
+        let mut config = format!("header = {}\n", quote("Content-Type: application/json"));
+        for header in &self.config.headers {
+            let header = self.render(header, lint, fingerprint, root, true);
+            config.push_str(&format!("header = {}\n", quote(&header)));
+        }
+        let payload = self.payload(lint, fingerprint, root)?;
+        config.push_str(&format!("data-binary = {}\n", quote(&payload)));
+        Ok(config)
+    }
+
+    /// Creates the issue of `lint` with `curl`, and returns its url.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn create(
+        &self,
+        lint: &Lint,
+        fingerprint: Fingerprint,
+        root: &Path,
+    ) -> Result<String, Error> {
+        let Some(url) = self.config.url.as_deref() else {
+            return Err(Error::Issues(
+                "`issues.url` is needed to file issues".to_string(),
+            ));
+        };
+        let config = self.curl_config(lint, fingerprint, root)?;
+        let mut child = Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--fail",
+                "--request",
+                "POST",
+                "--config",
+                "-",
+            ])
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(config.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::Command(decode_output(output.stderr, "curl")));
+        }
+        self.issue_url(&decode_output(output.stdout, "curl"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IssueExporter;
+    use crate::config::scout::IssuesConfig;
+    use crate::linter::{Lint, Location};
+    use std::path::Path;
+
+    fn lint() -> Lint {
+        Lint {
+            message: "warning: used `unwrap()` on an `Option` value".to_string(),
+            code: Some("clippy::unwrap_used".to_string()),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [4, 4],
+                columns: [9, 15],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn exporter(config: IssuesConfig) -> IssueExporter {
+        IssueExporter {
+            config,
+            token: "secret".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_payload() -> Result<(), crate::error::Error> {
+        let fingerprint = "0123456789abcdef".parse().unwrap();
+        let payload: serde_json::Value = serde_json::from_str(
+            &exporter(IssuesConfig::default()).payload(&lint(), fingerprint, Path::new("/repo"))?,
+        )?;
+        assert_eq!(
+            serde_json::json!({
+                "title": "`clippy::unwrap_used` in src/lib.rs",
+                "body": "Deferred by cargo-scout, at src/lib.rs:4:9:\n\n```\nwarning: used `unwrap()` on an `Option` value\n```\n\nFingerprint: `0123456789abcdef`",
+            }),
+            payload
+        );
+
+        let jira = exporter(IssuesConfig {
+            template: r#"{"fields": {"project": {"key": "DEBT"}, "summary": "{{title}}", "labels": ["{{code}}"], "line": {{line}}}}"#
+                .to_string(),
+            ..IssuesConfig::default()
+        });
+        let payload: serde_json::Value =
+            serde_json::from_str(&jira.payload(&lint(), fingerprint, Path::new("/repo"))?)?;
+        assert_eq!(
+            serde_json::json!("clippy::unwrap_used"),
+            payload["fields"]["labels"][0]
+        );
+        assert_eq!(serde_json::json!(4), payload["fields"]["line"]);
+        assert_eq!(
+            "Authorization: Bearer secret",
+            jira.render(
+                &jira.config.headers[0],
+                &lint(),
+                fingerprint,
+                Path::new("/repo"),
+                true
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() {
+        let fingerprint = "0123456789abcdef".parse().unwrap();
+        let mut lint = lint();
+        // A placeholder in a value isn't replaced in turn
+        lint.message = "warning: {{token}} {{fingerprint}}".to_string();
+        let github = exporter(IssuesConfig::default());
+        let rendered = github.render(
+            "{{body}} {{token}} {{unknown}} {{line",
+            &lint,
+            fingerprint,
+            Path::new("/repo"),
+            false,
+        );
+        assert!(rendered.contains("warning: {{token}} {{fingerprint}}"));
+        assert!(rendered.ends_with(" {{token}} {{unknown}} {{line"));
+        assert!(!rendered.contains("secret"));
+        assert_eq!(
+            "token secret",
+            github.render(
+                "token {{token}}",
+                &lint,
+                fingerprint,
+                Path::new("/repo"),
+                true
+            )
+        );
+    }
+
+    #[test]
+    fn test_curl_config() -> Result<(), crate::error::Error> {
+        let fingerprint = "0123456789abcdef".parse().unwrap();
+        let github = exporter(IssuesConfig {
+            headers: vec!["Authorization: Bearer {{token}}".to_string()],
+            template: r#"{"title": "{{title}}", "body": "a\tb"}"#.to_string(),
+            ..IssuesConfig::default()
+        });
+        assert_eq!(
+            r#"header = "Content-Type: application/json"
+header = "Authorization: Bearer secret"
+data-binary = "{\"body\":\"a\\tb\",\"title\":\"`clippy::unwrap_used` in src/lib.rs\"}"
+"#,
+            github.curl_config(&lint(), fingerprint, Path::new("/repo"))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_issue_url() {
+        let github = exporter(IssuesConfig::default());
+        assert_eq!(
+            "https://github.com/org/repo/issues/7",
+            github
+                .issue_url(r#"{"number": 7, "html_url": "https://github.com/org/repo/issues/7"}"#)
+                .unwrap()
+        );
+        assert!(matches!(
+            github.issue_url(r#"{"key": "DEBT-7"}"#),
+            Err(crate::error::Error::Issues(_))
+        ));
+        let jira = exporter(IssuesConfig {
+            url_field: "/key".to_string(),
+            ..IssuesConfig::default()
+        });
+        assert_eq!("DEBT-7", jira.issue_url(r#"{"key": "DEBT-7"}"#).unwrap());
+    }
+}
@@ -0,0 +1,749 @@
+use crate::error::Error;
+use crate::linter::anchor::{self, Anchor, Snapshot};
+use crate::linter::crash::Crash;
+use crate::linter::fingerprint;
+use crate::linter::{Lint, Location, SnippetLine};
+use crate::scout::empty_diff::Status;
+use crate::scout::explain::{Explanation, Step};
+use crate::scout::items::SyntaxMap;
+use crate::utils::relative_path;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the report layout changes in an incompatible way.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The results of a run, as written by the json format.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct JsonReport {
+    pub version: u32,
+    /// Whether the diff had changes, to tell a clean change from a run which compared nothing
+    #[serde(default)]
+    pub status: Status,
+    pub lints: Vec<JsonLint>,
+    /// Why each lint was reported or dropped, with `--explain-filtering`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explanations: Vec<JsonExplanation>,
+    /// How many times flaky subprocesses were retried
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub retries: u32,
+    /// The members skipped as the time budget ran out, the lints are then partial
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+    /// The members the linter crashed on, whose lints are missing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crashed: Vec<Crash>,
+    /// The id of the run, see `run_id::default_run_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// The configuration the run was made in, like `os=linux`, see `with_labels`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_unknown(columns: &[u32; 2]) -> bool {
+    *columns == [0, 0]
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Clone, Debug)]
+pub struct JsonLint {
+    /// The path of the file, relative to the repository root
+    pub path: String,
+    pub lines: [u32; 2],
+    /// The start and end columns, left out when the linter doesn't report them
+    #[serde(default, skip_serializing_if = "is_unknown")]
+    pub columns: [u32; 2],
+    /// The start and end byte offsets in the file, see `Location::bytes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<[u32; 2]>,
+    /// The source lines spanned, see `Location::snippet`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snippet: Vec<SnippetLine>,
+    pub code: Option<String>,
+    pub message: String,
+    pub fingerprint: String,
+    /// The innermost item holding the first line, like `Config::new`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+    /// The combinations of `--feature-matrix` that found the lint, `None` when all did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<Vec<String>>,
+    /// The labels of the run that found the lint, those of the merged runs that all found it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// The lints added and removed between two reports.
+#[derive(PartialEq, Debug, Default)]
+pub struct ReportDiff {
+    pub added: Vec<JsonLint>,
+    pub removed: Vec<JsonLint>,
+}
+
+/// The lints of `lints` that aren't in `other`, matched by fingerprint.
+///
+/// Lints sharing a fingerprint are counted, not deduplicated.
+fn missing_from(lints: &[JsonLint], other: &[JsonLint]) -> Vec<JsonLint> {
+    missing_by(lints, other, |lint| &lint.fingerprint)
+}
+
+/// The `items` that aren't in `other`, matched by `key` and counted like `missing_from`.
+fn missing_by<T: Clone>(items: &[T], other: &[T], key: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for item in other {
+        *counts.entry(key(item)).or_default() += 1;
+    }
+    items
+        .iter()
+        .filter(|item| match counts.get_mut(key(item)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// The snapshot of a report lint, holding only the source lines it spans.
+fn snapshot(lint: &JsonLint) -> Snapshot {
+    let location = Location {
+        path: lint.path.clone(),
+        lines: lint.lines,
+        ..Location::default()
+    };
+    let lint_at = Lint {
+        code: lint.code.clone(),
+        location,
+        ..Lint::default()
+    };
+    Snapshot {
+        // Hashing the report fingerprints keeps the equal ones equal
+        fingerprint: fingerprint::fingerprint(&lint_at, "", &[lint.fingerprint.as_str()]),
+        lint: lint_at,
+        path: lint.path.clone(),
+        before: Vec::new(),
+        lines: lint.snippet.iter().map(|line| line.text.clone()).collect(),
+        after: Vec::new(),
+    }
+}
+
+/// The decision chain of a lint, reported or not.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Clone, Debug)]
+pub struct JsonExplanation {
+    pub lint: JsonLint,
+    pub kept: bool,
+    pub steps: Vec<Step>,
+}
+
+fn json_lints(lints: &[Lint], features: &HashMap<Lint, Vec<String>>, root: &Path) -> Vec<JsonLint> {
+    let mut syntax = SyntaxMap::new();
+    lints
+        .iter()
+        .zip(fingerprint::fingerprints(lints, root))
+        .map(|(lint, fingerprint)| JsonLint {
+            path: relative_path(&lint.location.path, root),
+            lines: lint.location.lines,
+            columns: lint.location.columns,
+            bytes: lint.location.bytes,
+            snippet: lint.location.snippet.clone(),
+            code: lint.code.clone(),
+            message: lint.message.clone(),
+            fingerprint: fingerprint.to_string(),
+            item: syntax
+                .enclosing(&lint.location.path, lint.location.lines[0])
+                .map(|item| item.name.clone()),
+            features: features.get(lint).cloned(),
+            labels: BTreeMap::new(),
+        })
+        .collect()
+}
+
+impl JsonReport {
+    /// Builds the report of `lints`, paths are made relative to `root`.
+    #[must_use]
+    pub fn new(lints: &[Lint], root: impl AsRef<Path>) -> Self {
+        Self::with_features(lints, &HashMap::new(), root)
+    }
+
+    /// Builds the report of `lints` like `new`, with the combinations of `--feature-matrix`
+    /// that found the lints only some of them found.
+    #[must_use]
+    pub fn with_features(
+        lints: &[Lint],
+        features: &HashMap<Lint, Vec<String>>,
+        root: impl AsRef<Path>,
+    ) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            status: Status::Compared,
+            lints: json_lints(lints, features, root.as_ref()),
+            explanations: Vec::new(),
+            retries: 0,
+            skipped: Vec::new(),
+            crashed: Vec::new(),
+            run_id: None,
+            labels: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the explanations of why each lint was reported or dropped.
+    #[must_use]
+    pub fn with_explanations(
+        mut self,
+        explanations: &[Explanation],
+        root: impl AsRef<Path>,
+    ) -> Self {
+        let lints: Vec<Lint> = explanations.iter().map(|e| e.lint.clone()).collect();
+        self.explanations = json_lints(&lints, &HashMap::new(), root.as_ref())
+            .into_iter()
+            .zip(explanations)
+            .map(|(lint, explanation)| JsonExplanation {
+                lint,
+                kept: explanation.kept(),
+                steps: explanation.steps.clone(),
+            })
+            .collect();
+        self
+    }
+
+    /// Records how many times flaky subprocesses were retried during the run.
+    #[must_use]
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Records the members skipped as the time budget ran out.
+    #[must_use]
+    pub fn with_skipped(mut self, skipped: Vec<String>) -> Self {
+        self.skipped = skipped;
+        self
+    }
+
+    /// Records the crashes of the linter, with their internal compiler errors.
+    #[must_use]
+    pub fn with_crashed(mut self, crashed: Vec<Crash>) -> Self {
+        self.crashed = crashed;
+        self
+    }
+
+    /// Sets whether the diff had changes, see `Outcome::status`.
+    #[must_use]
+    pub fn with_status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets the id of the run the lints were found by.
+    #[must_use]
+    pub fn with_run_id(mut self, run_id: Option<String>) -> Self {
+        self.run_id = run_id;
+        self
+    }
+
+    /// Stamps the run and each of its lints with the `labels` of the configuration it was made in,
+    /// like `os=linux`, so that merged reports tell which configurations found each lint.
+    #[must_use]
+    pub fn with_labels(mut self, labels: BTreeMap<String, String>) -> Self {
+        for lint in self
+            .lints
+            .iter_mut()
+            .chain(self.explanations.iter_mut().map(|e| &mut e.lint))
+        {
+            lint.labels.clone_from(&labels);
+        }
+        self.labels = labels;
+        self
+    }
+
+    /// Combines the reports of the jobs that each scouted a part of the same change,
+    /// like the shards of `--shard`, or the runs of a feature matrix.
+    ///
+    /// A lint several reports found is only kept once, matched by fingerprint,
+    /// as many times as the report finding it the most. The run id is kept when
+    /// every report has the same, so are the labels. A lint found by several reports keeps
+    /// the labels they agree on, those found in one configuration only keep all of theirs.
+    #[must_use]
+    pub fn merge(reports: Vec<Self>) -> Self {
+        let run_ids: BTreeSet<Option<String>> =
+            reports.iter().map(|report| report.run_id.clone()).collect();
+        let mut merged = Self::new(&[], "");
+        // The jobs scouted the same change, unless one of them compared something
+        if !reports.is_empty()
+            && reports
+                .iter()
+                .all(|report| report.status == Status::NoChanges)
+        {
+            merged.status = Status::NoChanges;
+        }
+        let mut skipped = BTreeSet::new();
+        let mut labels: Option<BTreeMap<String, String>> = None;
+        for report in reports {
+            // Each lint of the report is matched with one of the merged lints, like `missing_from`
+            let mut matched = vec![false; merged.lints.len()];
+            for lint in &report.lints {
+                if let Some(i) = (0..merged.lints.len())
+                    .find(|&i| !matched[i] && merged.lints[i].fingerprint == lint.fingerprint)
+                {
+                    matched[i] = true;
+                    merged.lints[i]
+                        .labels
+                        .retain(|key, value| lint.labels.get(key) == Some(value));
+                }
+            }
+            let lints = missing_from(&report.lints, &merged.lints);
+            merged.lints.extend(lints);
+            let explanations = missing_by(&report.explanations, &merged.explanations, |e| {
+                &e.lint.fingerprint
+            });
+            merged.explanations.extend(explanations);
+            merged.retries += report.retries;
+            skipped.extend(report.skipped);
+            merged.crashed.extend(report.crashed);
+            let report_labels = report.labels;
+            match &mut labels {
+                Some(labels) => labels.retain(|key, value| report_labels.get(key) == Some(value)),
+                None => labels = Some(report_labels),
+            }
+        }
+        merged.labels = labels.unwrap_or_default();
+        merged.skipped = skipped.into_iter().collect();
+        merged.run_id = match run_ids.into_iter().collect::<Vec<_>>().as_slice() {
+            [run_id] => run_id.clone(),
+            _ => None,
+        };
+        merged
+    }
+
+    /// The lints of the report, at their paths in the repository at `root`.
+    ///
+    /// The suggestions of the lints aren't in the report, the lints have none.
+    #[must_use]
+    pub fn to_lints(&self, root: impl AsRef<Path>) -> Vec<Lint> {
+        self.lints
+            .iter()
+            .map(|lint| Lint {
+                message: lint.message.clone(),
+                code: lint.code.clone(),
+                location: Location {
+                    path: root.as_ref().join(&lint.path).display().to_string(),
+                    lines: lint.lines,
+                    columns: lint.columns,
+                    bytes: lint.bytes,
+                    snippet: lint.snippet.clone(),
+                },
+                suggestions: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// The feature combinations each lint of the report was found with, keyed like `to_lints`.
+    #[must_use]
+    pub fn to_features(&self, root: impl AsRef<Path>) -> HashMap<Lint, Vec<String>> {
+        self.to_lints(root)
+            .into_iter()
+            .zip(&self.lints)
+            .filter_map(|(lint, json)| Some((lint, json.features.clone()?)))
+            .collect()
+    }
+
+    /// Compares this report with a newer one, using the lint fingerprints,
+    /// so lints that only moved around aren't reported.
+    ///
+    /// The lints left over are re-anchored by the source lines they span, see `anchor::reanchor`,
+    /// so lints whose lines were slightly edited aren't reported either.
+    #[must_use]
+    pub fn diff(&self, new: &Self) -> ReportDiff {
+        let mut added = missing_from(&new.lints, &self.lints);
+        let mut removed = missing_from(&self.lints, &new.lints);
+        let anchors = anchor::reanchor(
+            &removed.iter().map(snapshot).collect::<Vec<_>>(),
+            &added.iter().map(snapshot).collect::<Vec<_>>(),
+        );
+        let mut anchored = vec![false; removed.len()];
+        for anchor in &anchors {
+            if let Anchor::Existing { previous } = anchor {
+                anchored[*previous] = true;
+            }
+        }
+        let mut anchors = anchors.into_iter();
+        added.retain(|_| anchors.next() == Some(Anchor::New));
+        let mut anchored = anchored.into_iter();
+        removed.retain(|_| anchored.next() == Some(false));
+        ReportDiff { added, removed }
+    }
+
+    /// Reads a report previously written by `JsonReport::write`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Writes the report to `path`, creating the parent directories if needed.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        crate::lock::write_locked(path, self.to_json()?)
+    }
+}
+
+/// Parses a `key=value` label, see `JsonReport::with_labels`.
+#[allow(clippy::missing_errors_doc)]
+pub fn parse_label(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("invalid label {}, expected key=value", s)),
+    }
+}
+
+/// The well-known location of the last run results, `target/scout/latest.json`.
+///
+/// `CARGO_TARGET_DIR` is honored, relative to the workspace root.
+/// Editor plugins can poll this file instead of parsing the output.
+#[must_use]
+pub fn latest_path(workspace_root: impl AsRef<Path>) -> PathBuf {
+    latest_path_with_target_dir(
+        workspace_root.as_ref(),
+        std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from),
+    )
+}
+
+fn latest_path_with_target_dir(workspace_root: &Path, target_dir: Option<PathBuf>) -> PathBuf {
+    workspace_root
+        .join(target_dir.unwrap_or_else(|| PathBuf::from("target")))
+        .join("scout")
+        .join("latest.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{latest_path_with_target_dir, parse_label, JsonReport, ReportDiff, FORMAT_VERSION};
+    use crate::linter::crash::Crash;
+    use crate::linter::{Lint, Location, SnippetLine};
+    use crate::scout::empty_diff::Status;
+    use crate::scout::explain::explain;
+    use crate::vcs::Section;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::{Path, PathBuf};
+
+    fn lint() -> Lint {
+        Lint {
+            message: "warning: redundant clone".to_string(),
+            code: Some("clippy::redundant_clone".to_string()),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [12, 13],
+                columns: [9, 22],
+                bytes: Some([310, 341]),
+                snippet: vec![SnippetLine {
+                    text: "    let b = a.clone();".to_string(),
+                    highlight: [13, 22],
+                }],
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_new_report() {
+        let report = JsonReport::new(&[lint()], "/repo");
+        assert_eq!(FORMAT_VERSION, report.version);
+        assert_eq!("src/lib.rs", report.lints[0].path);
+        assert_eq!([12, 13], report.lints[0].lines);
+        assert_eq!([9, 22], report.lints[0].columns);
+        assert_eq!(
+            lint().fingerprint("/repo").to_string(),
+            report.lints[0].fingerprint
+        );
+        assert_eq!(vec![lint()], report.to_lints("/repo"));
+    }
+
+    #[test]
+    fn test_to_features() -> Result<(), crate::error::Error> {
+        let mut other = lint();
+        other.location.lines = [20, 20];
+        let features: HashMap<Lint, Vec<String>> =
+            std::iter::once((lint(), vec!["default".to_string(), "serde".to_string()])).collect();
+        let json = JsonReport::with_features(&[lint(), other], &features, "/repo").to_json()?;
+        // Like a report read back from the result cache
+        let report: JsonReport = serde_json::from_str(&json)?;
+        assert_eq!(features, report.to_features("/repo"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_columns() -> Result<(), crate::error::Error> {
+        let report = JsonReport::new(&[lint()], "/repo");
+        assert!(report
+            .to_json()?
+            .contains("\"columns\": [\n        9,\n        22\n      ]"));
+        assert_eq!(Some([310, 341]), report.lints[0].bytes);
+        assert_eq!("a.clone()", report.lints[0].snippet[0].highlighted());
+        // Reports of linters without columns, and older reports, have none
+        let mut lint = lint();
+        lint.location = Location {
+            path: lint.location.path,
+            lines: lint.location.lines,
+            ..Location::default()
+        };
+        let json = JsonReport::new(&[lint], "/repo").to_json()?;
+        assert!(!json.contains("columns"));
+        assert!(!json.contains("bytes"));
+        assert!(!json.contains("snippet"));
+        let report: JsonReport = serde_json::from_str(&json)?;
+        assert_eq!([0, 0], report.lints[0].columns);
+        Ok(())
+    }
+
+    #[test]
+    fn test_item() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "impl Foo {\n    fn bar() {\n        let a = 1;\n    }\n}\n",
+        )?;
+        let mut lint = lint();
+        lint.location.path = dir.path().join("lib.rs").to_string_lossy().into_owned();
+        lint.location.lines = [3, 3];
+        let report = JsonReport::new(&[lint], dir.path());
+        assert_eq!(Some("Foo::bar"), report.lints[0].item.as_deref());
+        assert!(report.to_json()?.contains("\"item\": \"Foo::bar\""));
+        // Without a file to parse, there is no item
+        assert!(!JsonReport::new(&[self::lint()], "/repo")
+            .to_json()?
+            .contains("item"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_explanations() -> Result<(), crate::error::Error> {
+        let sections = vec![Section {
+            file_name: "/repo/src/main.rs".to_string(),
+            line_start: 1,
+            line_end: 2,
+        }];
+        let report = JsonReport::new(&[], "/repo");
+        assert!(!report.to_json()?.contains("explanations"));
+
+        let report = report.with_explanations(&explain(&[lint()], &sections), "/repo");
+        assert_eq!("src/lib.rs", report.explanations[0].lint.path);
+        assert!(!report.explanations[0].kept);
+        assert!(report.to_json()?.contains("\"stage\": \"section\""));
+        assert!(!report.to_json()?.contains("retries"));
+        let report = report.with_retries(2);
+        assert_eq!(2, report.retries);
+        assert!(!report.to_json()?.contains("skipped"));
+        let report = report.with_skipped(vec!["member2".to_string()]);
+        assert_eq!(vec!["member2"], report.skipped);
+        assert!(report.to_json()?.contains("\"skipped\": ["));
+        assert!(!report.to_json()?.contains("crashed"));
+        let report = report.with_crashed(vec![Crash {
+            member: "member1".to_string(),
+            message: "error: internal compiler error".to_string(),
+        }]);
+        assert!(report.to_json()?.contains("\"crashed\": ["));
+        assert!(report
+            .with_run_id(Some("0123456789abcdef".to_string()))
+            .to_json()?
+            .contains("\"run_id\": \"0123456789abcdef\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff() {
+        let moved = Lint {
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [20, 21],
+                ..Location::default()
+            },
+            ..lint()
+        };
+        let fixed = Lint {
+            message: "warning: needless borrow".to_string(),
+            code: Some("clippy::needless_borrow".to_string()),
+            ..lint()
+        };
+        let added = Lint {
+            message: "warning: unused variable".to_string(),
+            code: Some("unused_variables".to_string()),
+            ..lint()
+        };
+        let old = JsonReport::new(&[lint(), fixed.clone(), lint()], "/repo");
+        let new = JsonReport::new(&[moved, added.clone()], "/repo");
+
+        let diff = old.diff(&new);
+        assert_eq!(1, diff.added.len());
+        assert_eq!(added.message, diff.added[0].message);
+        // One of the two identical lints is gone, along with the fixed one
+        assert_eq!(2, diff.removed.len());
+        assert_eq!(fixed.message, diff.removed[0].message);
+        assert_eq!(lint().message, diff.removed[1].message);
+        assert_eq!(ReportDiff::default(), new.diff(&new));
+    }
+
+    #[test]
+    fn test_diff_edited_lines() {
+        let spanning = |lines: &[&str]| Lint {
+            location: Location {
+                lines: [12, 14],
+                snippet: lines
+                    .iter()
+                    .map(|text| SnippetLine {
+                        text: text.to_string(),
+                        highlight: [1, 1],
+                    })
+                    .collect(),
+                ..lint().location
+            },
+            ..lint()
+        };
+        let old = JsonReport::new(
+            &[spanning(&["let b = a", "    .clone()", "    .len();"])],
+            "/repo",
+        );
+        let mut edited = JsonReport::new(
+            &[spanning(&["let b = a", "    .clone()", "    .count();"])],
+            "/repo",
+        );
+        // The fingerprints hash the lines read from disk, those of an edited lint differ
+        edited.lints[0].fingerprint = "0123456789abcdef".to_string();
+        assert_eq!(ReportDiff::default(), old.diff(&edited));
+
+        let mut rewritten = JsonReport::new(&[spanning(&["let b = c.to_owned();"])], "/repo");
+        rewritten.lints[0].fingerprint = "0123456789abcdef".to_string();
+        let diff = old.diff(&rewritten);
+        assert_eq!((1, 1), (diff.added.len(), diff.removed.len()));
+    }
+
+    #[test]
+    fn test_merge() {
+        let other = Lint {
+            message: "warning: unused variable".to_string(),
+            code: Some("unused_variables".to_string()),
+            ..lint()
+        };
+        let run_id = Some("0123456789abcdef".to_string());
+        let first = JsonReport::new(&[lint()], "/repo")
+            .with_retries(1)
+            .with_skipped(vec!["member3".to_string()])
+            .with_run_id(run_id.clone());
+        let second = JsonReport::new(std::slice::from_ref(&other), "/repo")
+            .with_retries(2)
+            .with_run_id(run_id.clone());
+        let merged = JsonReport::merge(vec![first, second]);
+        assert_eq!(FORMAT_VERSION, merged.version);
+        assert_eq!(vec![lint(), other], merged.to_lints("/repo"));
+        assert_eq!(3, merged.retries);
+        assert_eq!(vec!["member3"], merged.skipped);
+        assert_eq!(run_id, merged.run_id);
+
+        let unrelated = JsonReport::new(&[], "/repo").with_run_id(Some("fedcba".to_string()));
+        assert_eq!(None, JsonReport::merge(vec![merged, unrelated]).run_id);
+
+        let empty = || JsonReport::new(&[], "/repo").with_status(Status::NoChanges);
+        assert_eq!(
+            Status::NoChanges,
+            JsonReport::merge(vec![empty(), empty()]).status
+        );
+        assert_eq!(
+            Status::Compared,
+            JsonReport::merge(vec![empty(), JsonReport::new(&[], "/repo")]).status
+        );
+
+        // The lints found by both reports are deduplicated, not those found twice by one
+        let both = JsonReport::new(&[lint(), lint()], "/repo");
+        let merged = JsonReport::merge(vec![JsonReport::new(&[lint()], "/repo"), both]);
+        assert_eq!(vec![lint(), lint()], merged.to_lints("/repo"));
+    }
+
+    #[test]
+    fn test_labels() {
+        let labels = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        let other = Lint {
+            message: "warning: unused variable".to_string(),
+            code: Some("unused_variables".to_string()),
+            ..lint()
+        };
+        let linux = JsonReport::new(&[lint(), other], "/repo")
+            .with_labels(labels(&[("os", "linux"), ("toolchain", "stable")]));
+        assert_eq!(linux.labels, linux.lints[1].labels);
+        let windows = JsonReport::new(&[lint()], "/repo")
+            .with_labels(labels(&[("os", "windows"), ("toolchain", "stable")]));
+        let merged = JsonReport::merge(vec![linux, windows]);
+        assert_eq!(labels(&[("toolchain", "stable")]), merged.labels);
+        // Found on both, the lint isn't specific to an os
+        assert_eq!(labels(&[("toolchain", "stable")]), merged.lints[0].labels);
+        assert_eq!(
+            labels(&[("os", "linux"), ("toolchain", "stable")]),
+            merged.lints[1].labels
+        );
+
+        // Found twice on linux and once on windows, the second lint is only found on linux
+        let twice =
+            JsonReport::new(&[lint(), lint()], "/repo").with_labels(labels(&[("os", "linux")]));
+        let once = JsonReport::new(&[lint()], "/repo").with_labels(labels(&[("os", "windows")]));
+        let merged = JsonReport::merge(vec![twice, once]);
+        assert!(merged.lints[0].labels.is_empty());
+        assert_eq!(labels(&[("os", "linux")]), merged.lints[1].labels);
+
+        assert_eq!(
+            Ok(("os".to_string(), "linux".to_string())),
+            parse_label("os=linux")
+        );
+        assert!(parse_label("linux").is_err());
+        assert!(parse_label("=linux").is_err());
+    }
+
+    #[test]
+    fn test_write_and_read() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("target").join("scout").join("latest.json");
+        let report = JsonReport::new(&[lint()], "/repo");
+        report.write(&path)?;
+        assert_eq!(report, JsonReport::from_path(&path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_latest_path() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            PathBuf::from("/repo/target/scout/latest.json"),
+            latest_path_with_target_dir(root, None)
+        );
+        assert_eq!(
+            PathBuf::from("/repo/build/scout/latest.json"),
+            latest_path_with_target_dir(root, Some(PathBuf::from("build")))
+        );
+        assert_eq!(
+            PathBuf::from("/tmp/target/scout/latest.json"),
+            latest_path_with_target_dir(root, Some(PathBuf::from("/tmp/target")))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::linter::Lint;
+use crate::report::json::{JsonLint, JsonReport};
+use crate::scout::observer::Observer;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A line of the json-lines format.
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event<'a> {
+    RunStart {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        run_id: Option<&'a str>,
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        #[schemars(with = "Option<BTreeMap<String, String>>")]
+        labels: &'a BTreeMap<String, String>,
+        members: &'a [String],
+    },
+    MemberStart {
+        member: &'a str,
+    },
+    /// A reported lint, once the run is over
+    Lint(JsonLint),
+    /// The number of lints the linter found in the member, before the diff filters them
+    MemberEnd {
+        member: &'a str,
+        lints: usize,
+    },
+    /// A member the time budget ran out before
+    MemberSkipped {
+        member: &'a str,
+    },
+    Summary {
+        lints: usize,
+    },
+}
+
+/// Streams the events of a run as json objects, one per line.
+pub struct JsonLinesObserver<W: Write + Send> {
+    out: Mutex<W>,
+    run_id: Option<String>,
+    labels: BTreeMap<String, String>,
+}
+
+impl<W: Write + Send> JsonLinesObserver<W> {
+    /// Writes to `out`.
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Mutex::new(out),
+            run_id: None,
+            labels: BTreeMap::new(),
+        }
+    }
+
+    /// Also writes the id of the run, in the event starting it.
+    #[must_use]
+    pub fn with_run_id(mut self, run_id: Option<String>) -> Self {
+        self.run_id = run_id;
+        self
+    }
+
+    /// Also writes the labels of the run, in the event starting it.
+    #[must_use]
+    pub fn with_labels(mut self, labels: BTreeMap<String, String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Writes the events, the run isn't failed by an output that went away.
+    fn emit(&self, events: &[Event<'_>]) {
+        let mut out = self
+            .out
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        for event in events {
+            if let Ok(line) = serde_json::to_string(event) {
+                let _ = writeln!(out, "{}", line);
+            }
+        }
+        let _ = out.flush();
+    }
+}
+
+impl<W: Write + Send> Observer for JsonLinesObserver<W> {
+    fn run_started(&self, members: &[String]) {
+        self.emit(&[Event::RunStart {
+            run_id: self.run_id.as_deref(),
+            labels: &self.labels,
+            members,
+        }]);
+    }
+
+    fn member_started(&self, member: &str) {
+        self.emit(&[Event::MemberStart { member }]);
+    }
+
+    fn member_finished(&self, member: &str, lints: &[Lint]) {
+        self.emit(&[Event::MemberEnd {
+            member,
+            lints: lints.len(),
+        }]);
+    }
+
+    fn member_skipped(&self, member: &str) {
+        self.emit(&[Event::MemberSkipped { member }]);
+    }
+}
+
+/// Renders the events ending a run: a `lint` per reported lint, then the `summary`.
+///
+/// These are written from the final report, once the deferred lints are dropped and the
+/// rest ranked, and so also when the result cache answers instead of a run.
+#[must_use]
+pub fn render_lints(
+    lints: &[Lint],
+    features: &HashMap<Lint, Vec<String>>,
+    root: &Path,
+    labels: BTreeMap<String, String>,
+) -> String {
+    let mut events: Vec<Event> = JsonReport::with_features(lints, features, root)
+        .with_labels(labels)
+        .lints
+        .into_iter()
+        .map(Event::Lint)
+        .collect();
+    events.push(Event::Summary { lints: lints.len() });
+    events
+        .iter()
+        .filter_map(|event| serde_json::to_string(event).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_lints, JsonLinesObserver};
+    use crate::linter::{Lint, Location};
+    use crate::report::schema::{validates, SchemaKind};
+    use crate::scout::observer::Observer;
+
+    #[test]
+    fn test_events() {
+        let lints = vec![Lint {
+            message: "unused variable".to_string(),
+            code: Some("unused_variables".to_string()),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [3, 3],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }];
+        let labels = std::iter::once(("os".to_string(), "linux".to_string())).collect();
+        let observer = JsonLinesObserver::new(Vec::new())
+            .with_run_id(Some("42".to_string()))
+            .with_labels(labels);
+        observer.run_started(&[".".to_string()]);
+        observer.member_started(".");
+        observer.member_finished(".", &lints);
+        observer.member_skipped("member2");
+        observer.run_finished(&lints);
+        let mut out = String::from_utf8(observer.out.into_inner().unwrap()).unwrap();
+        out.push_str(&render_lints(
+            &lints,
+            &std::collections::HashMap::new(),
+            std::path::Path::new("/repo"),
+            std::iter::once(("os".to_string(), "linux".to_string())).collect(),
+        ));
+        let lines: Vec<serde_json::Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(6, lines.len());
+        for line in &lines {
+            assert!(validates(SchemaKind::JsonLines, line), "{}", line);
+        }
+        assert_eq!(
+            serde_json::json!({
+                "event": "run-start",
+                "run_id": "42",
+                "labels": {"os": "linux"},
+                "members": ["."]
+            }),
+            lines[0]
+        );
+        assert_eq!(
+            serde_json::json!({"event": "member-start", "member": "."}),
+            lines[1]
+        );
+        assert_eq!(
+            serde_json::json!({"event": "member-end", "member": ".", "lints": 1}),
+            lines[2]
+        );
+        assert_eq!(
+            serde_json::json!({"event": "member-skipped", "member": "member2"}),
+            lines[3]
+        );
+        assert_eq!("lint", lines[4]["event"]);
+        assert_eq!("src/lib.rs", lines[4]["path"]);
+        assert_eq!("unused_variables", lines[4]["code"]);
+        assert_eq!(serde_json::json!({"os": "linux"}), lines[4]["labels"]);
+        assert_eq!(
+            serde_json::json!({"event": "summary", "lints": 1}),
+            lines[5]
+        );
+        // Runs without labels or id leave them out
+        assert!(validates(
+            SchemaKind::JsonLines,
+            &serde_json::json!({"event": "run-start", "members": ["."]})
+        ));
+    }
+}
@@ -0,0 +1,227 @@
+use super::run_id::marker;
+use super::text::summary;
+use crate::config::scout::{CodeHost, MarkdownConfig};
+use crate::linter::Lint;
+use crate::scout::budget::skipped_marker;
+use crate::utils::relative_path;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Renders the warnings as a summary comment for a pull request.
+///
+/// The summary counts the warnings of each lint,
+/// then lists them in a collapsible section per file, with links to their lines.
+/// With a run id, the comment starts with its `run_id::marker`.
+#[derive(Debug, Default, Clone)]
+pub struct MarkdownReporter {
+    config: MarkdownConfig,
+    run_id: Option<String>,
+    skipped: usize,
+}
+
+impl MarkdownReporter {
+    #[must_use]
+    pub fn new(config: MarkdownConfig) -> Self {
+        Self {
+            config,
+            run_id: None,
+            skipped: 0,
+        }
+    }
+
+    /// Marks the comment with the id of the run, so that a retried run can find and update it.
+    #[must_use]
+    pub fn with_run_id(mut self, run_id: Option<String>) -> Self {
+        self.run_id = run_id;
+        self
+    }
+
+    /// Marks the warnings as partial, as `skipped` members were skipped when the time budget ran out.
+    #[must_use]
+    pub fn with_skipped(mut self, skipped: usize) -> Self {
+        self.skipped = skipped;
+        self
+    }
+
+    /// Renders `lints`, whose paths are made relative to `root`.
+    #[must_use]
+    pub fn render(&self, lints: &[Lint], root: &Path) -> String {
+        let mut rendered = self
+            .run_id
+            .as_deref()
+            .map(|run_id| format!("{}\n", marker(run_id)))
+            .unwrap_or_default();
+        rendered.push_str(&format!("### {}\n", summary(lints.len())));
+        if self.skipped > 0 {
+            rendered.push_str(&format!("\n> {}\n", skipped_marker(self.skipped)));
+        }
+        if lints.is_empty() {
+            return rendered;
+        }
+        let mut by_code: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut by_file: BTreeMap<String, Vec<&Lint>> = BTreeMap::new();
+        for lint in lints {
+            *by_code
+                .entry(lint.code.as_deref().unwrap_or("other"))
+                .or_default() += 1;
+            by_file
+                .entry(relative_path(&lint.location.path, root).replace('\\', "/"))
+                .or_default()
+                .push(lint);
+        }
+        rendered.push_str("\n| Lint | Count |\n| --- | ---: |\n");
+        for (code, count) in by_code {
+            rendered.push_str(&format!("| `{}` | {} |\n", code, count));
+        }
+        for (path, lints) in by_file {
+            rendered.push_str(&format!(
+                "\n<details>\n<summary><code>{}</code> ({})</summary>\n",
+                escape_html(&path),
+                lints.len()
+            ));
+            for lint in lints {
+                rendered.push_str(&format!(
+                    "\n[`{}:{}`]({}) {}\n\n",
+                    path,
+                    lint.location.position(),
+                    self.link(&path, lint.location.lines),
+                    lint.code
+                        .as_ref()
+                        .map(|code| format!("`{}`", code))
+                        .unwrap_or_default()
+                ));
+                rendered.push_str(&code_block(lint.message.trim_end()));
+            }
+            rendered.push_str("\n</details>\n");
+        }
+        rendered
+    }
+
+    /// The link to `lines` of the file at `path`, relative to the repository root.
+    #[must_use]
+    pub fn link(&self, path: &str, lines: [u32; 2]) -> String {
+        let anchor = match (self.config.code_host, lines[0] == lines[1]) {
+            (CodeHost::GitHub | CodeHost::GitLab, true) => format!("#L{}", lines[0]),
+            (CodeHost::GitHub, false) => format!("#L{}-L{}", lines[0], lines[1]),
+            (CodeHost::GitLab, false) => format!("#L{}-{}", lines[0], lines[1]),
+            (CodeHost::Bitbucket, true) => format!("#lines-{}", lines[0]),
+            (CodeHost::Bitbucket, false) => format!("#lines-{}:{}", lines[0], lines[1]),
+        };
+        let prefix = self.config.link_prefix.trim_end_matches('/');
+        if prefix.is_empty() {
+            format!("{}{}", path, anchor)
+        } else {
+            format!("{}/{}{}", prefix, path, anchor)
+        }
+    }
+}
+
+/// A fenced code block of `text`, with a fence longer than the backticks it holds.
+fn code_block(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}text\n{}\n{}\n", fence, text, fence)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{code_block, MarkdownReporter};
+    use crate::config::scout::{CodeHost, MarkdownConfig};
+    use crate::linter::{Lint, Location};
+    use std::path::Path;
+
+    fn lint(path: &str, lines: [u32; 2], code: &str) -> Lint {
+        Lint {
+            message: format!("warning: {}\n", code),
+            code: Some(code.to_string()),
+            location: Location {
+                path: path.to_string(),
+                lines,
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut lints = vec![
+            lint("/repo/src/lib.rs", [3, 3], "clippy::unwrap_used"),
+            lint("/repo/src/main.rs", [7, 9], "clippy::unwrap_used"),
+            lint("/repo/src/lib.rs", [10, 10], "dead_code"),
+        ];
+        lints[0].location.columns = [5, 14];
+        let rendered = MarkdownReporter::default().render(&lints, Path::new("/repo"));
+        assert_eq!(
+            "### Cargo scout found 3 warnings\n\
+             \n| Lint | Count |\n| --- | ---: |\n\
+             | `clippy::unwrap_used` | 2 |\n| `dead_code` | 1 |\n\
+             \n<details>\n<summary><code>src/lib.rs</code> (2)</summary>\n\
+             \n[`src/lib.rs:3:5`](src/lib.rs#L3) `clippy::unwrap_used`\n\n\
+             ```text\nwarning: clippy::unwrap_used\n```\n\
+             \n[`src/lib.rs:10`](src/lib.rs#L10) `dead_code`\n\n\
+             ```text\nwarning: dead_code\n```\n\
+             \n</details>\n\
+             \n<details>\n<summary><code>src/main.rs</code> (1)</summary>\n\
+             \n[`src/main.rs:7`](src/main.rs#L7-L9) `clippy::unwrap_used`\n\n\
+             ```text\nwarning: clippy::unwrap_used\n```\n\
+             \n</details>\n",
+            rendered
+        );
+        assert_eq!(
+            "### Cargo scout found no warnings\n",
+            MarkdownReporter::default().render(&[], Path::new("/repo"))
+        );
+        assert_eq!(
+            "<!-- cargo-scout run-id: 42 -->\n### Cargo scout found no warnings\n",
+            MarkdownReporter::default()
+                .with_run_id(Some("42".to_string()))
+                .render(&[], Path::new("/repo"))
+        );
+        assert_eq!(
+            "### Cargo scout found no warnings\n\n> 2 members skipped due to the time budget\n",
+            MarkdownReporter::default()
+                .with_skipped(2)
+                .render(&[], Path::new("/repo"))
+        );
+    }
+
+    #[test]
+    fn test_link() {
+        let reporter = |code_host, link_prefix: &str| {
+            MarkdownReporter::new(MarkdownConfig {
+                code_host,
+                link_prefix: link_prefix.to_string(),
+            })
+        };
+        assert_eq!(
+            "https://github.com/o/r/blob/abc/src/lib.rs#L1-L2",
+            reporter(CodeHost::GitHub, "https://github.com/o/r/blob/abc/")
+                .link("src/lib.rs", [1, 2])
+        );
+        assert_eq!(
+            "src/lib.rs#L1-2",
+            reporter(CodeHost::GitLab, "").link("src/lib.rs", [1, 2])
+        );
+        assert_eq!(
+            "src/lib.rs#lines-4",
+            reporter(CodeHost::Bitbucket, "").link("src/lib.rs", [4, 4])
+        );
+    }
+
+    #[test]
+    fn test_code_block() {
+        assert_eq!("```text\nfoo\n```\n", code_block("foo"));
+        assert_eq!("`````text\n````\n`````\n", code_block("````"));
+    }
+}
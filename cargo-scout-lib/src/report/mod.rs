@@ -0,0 +1,58 @@
+pub mod annotate;
+pub mod baseline;
+pub mod cache;
+pub mod group;
+#[cfg(feature = "http")]
+pub mod issues;
+pub mod json;
+pub mod json_lines;
+pub mod markdown;
+pub mod patch;
+pub mod plan;
+pub mod problem_matcher;
+pub mod quickfix;
+pub mod rank;
+pub mod run_id;
+pub mod schema;
+#[cfg(feature = "store")]
+pub mod store;
+pub mod summary;
+pub mod text;
+#[cfg(feature = "http")]
+pub mod webhook;
+
+use std::str::FromStr;
+
+/// The format of the results.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum OutputFormat {
+    /// Human readable warnings
+    #[default]
+    Text,
+    /// A `json::JsonReport`
+    Json,
+    /// The events of the run as they happen, see `json_lines::JsonLinesObserver`
+    JsonLines,
+    /// A pull request summary, see `markdown::MarkdownReporter`
+    Markdown,
+    /// A `file:line:col: message` line per lint, see `quickfix::render`
+    Quickfix,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "json-lines" => Ok(Self::JsonLines),
+            "markdown" => Ok(Self::Markdown),
+            "quickfix" => Ok(Self::Quickfix),
+            _ => Err(format!(
+                "unknown output format {}, expected text, json, json-lines, markdown or quickfix",
+                s
+            )),
+        }
+    }
+}
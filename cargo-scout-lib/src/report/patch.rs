@@ -0,0 +1,358 @@
+use crate::error::Error;
+use crate::linter::{Lint, Suggestion};
+use crate::utils::relative_path;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// The lines of context around the changes, like `git diff`.
+const CONTEXT: usize = 3;
+
+/// Writes a `.patch` file in `dir` for each lint with machine-applicable suggestions,
+/// and returns how many were written.
+///
+/// Patches apply with `git apply` from `repo_root`,
+/// the paths of the lints are relative to `workspace_root`.
+#[allow(clippy::missing_errors_doc)]
+pub fn write_patches(
+    lints: &[Lint],
+    workspace_root: &Path,
+    repo_root: &Path,
+    dir: &Path,
+) -> Result<usize, Error> {
+    std::fs::create_dir_all(dir)?;
+    // A lint reported on several spans shares its suggestions between them
+    let mut seen = HashSet::new();
+    let mut written = 0;
+    for lint in lints {
+        if lint.suggestions.is_empty() || !seen.insert(&lint.suggestions) {
+            continue;
+        }
+        let Some(patch) = lint_patch(lint, workspace_root, repo_root) else {
+            continue;
+        };
+        written += 1;
+        std::fs::write(dir.join(patch_file_name(written, lint)), patch)?;
+    }
+    Ok(written)
+}
+
+/// Renders the suggestions of a lint as a patch, see `write_patches`.
+///
+/// Returns `None` if the lint has no suggestions, if they overlap,
+/// or if they don't match the files on disk.
+#[must_use]
+pub fn lint_patch(lint: &Lint, workspace_root: &Path, repo_root: &Path) -> Option<String> {
+    let mut by_file: BTreeMap<&str, Vec<&Suggestion>> = BTreeMap::new();
+    for suggestion in &lint.suggestions {
+        by_file
+            .entry(suggestion.path.as_str())
+            .or_default()
+            .push(suggestion);
+    }
+    let mut patch = String::new();
+    for (path, suggestions) in by_file {
+        let file = workspace_root.join(path);
+        let source = std::fs::read_to_string(&file).ok()?;
+        let diff_path = relative_path(&file.to_string_lossy(), repo_root);
+        patch.push_str(&file_patch(&diff_path, &source, &suggestions)?);
+    }
+    Some(patch).filter(|patch| !patch.is_empty())
+}
+
+/// Renders the suggestions made to the file at `path` as a unified diff, given its `source`.
+///
+/// Returns `None` if the suggestions overlap, or aren't within the file.
+#[must_use]
+pub fn file_patch(path: &str, source: &str, suggestions: &[&Suggestion]) -> Option<String> {
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let line_offsets = line_offsets(&lines);
+    let replacements = replacements(&lines, &line_offsets, suggestions)?;
+
+    // Groups of replacements sharing lines, as (first line, last line, new text)
+    let mut groups: Vec<(usize, usize, Vec<_>)> = Vec::new();
+    for replacement in replacements {
+        match groups.last_mut() {
+            Some((_, last, group)) if replacement.2 <= *last => {
+                *last = (*last).max(replacement.3);
+                group.push(replacement);
+            }
+            _ => groups.push((replacement.2, replacement.3, vec![replacement])),
+        }
+    }
+    let groups: Vec<(usize, usize, String)> = groups
+        .into_iter()
+        .map(|(first, last, group)| {
+            let mut text = String::new();
+            let mut at = line_offsets[first];
+            for (start, end, _, _, replacement) in group {
+                text.push_str(&source[at..start]);
+                text.push_str(replacement);
+                at = end;
+            }
+            text.push_str(&source[at..line_offsets[last + 1]]);
+            (first, last, text)
+        })
+        .collect();
+
+    let mut patch = format!(
+        "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n",
+        path.replace('\\', "/")
+    );
+    let mut delta: isize = 0;
+    let mut index = 0;
+    while index < groups.len() {
+        // Groups whose context overlaps are rendered in the same hunk
+        let mut end = index + 1;
+        while end < groups.len() && groups[end].0 <= groups[end - 1].1 + 2 * CONTEXT + 1 {
+            end += 1;
+        }
+        let hunk_groups = &groups[index..end];
+        let old_begin = hunk_groups[0].0.saturating_sub(CONTEXT);
+        let old_end = (hunk_groups[hunk_groups.len() - 1].1 + 1 + CONTEXT).min(lines.len());
+        let mut body = String::new();
+        let mut new_count = 0;
+        let mut line = old_begin;
+        for (first, last, text) in hunk_groups {
+            for context in &lines[line..*first] {
+                push_line(&mut body, ' ', context);
+            }
+            for removed in &lines[*first..=*last] {
+                push_line(&mut body, '-', removed);
+            }
+            for added in text.split_inclusive('\n') {
+                push_line(&mut body, '+', added);
+                new_count += 1;
+            }
+            new_count += first - line;
+            line = last + 1;
+        }
+        for context in &lines[line..old_end] {
+            push_line(&mut body, ' ', context);
+        }
+        new_count += old_end - line;
+        let old_count = old_end - old_begin;
+        let new_begin = (old_begin as isize + delta) as usize;
+        // An empty range starts at the line before it
+        let new_start = if new_count == 0 {
+            new_begin
+        } else {
+            new_begin + 1
+        };
+        patch.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_begin + 1,
+            old_count,
+            new_start,
+            new_count
+        ));
+        patch.push_str(&body);
+        delta += new_count as isize - old_count as isize;
+        index = end;
+    }
+    Some(patch)
+}
+
+/// Applies `suggestions` to the `source` of a file.
+///
+/// Returns `None` if the suggestions overlap, or aren't within the file.
+#[must_use]
+pub fn apply_suggestions(source: &str, suggestions: &[&Suggestion]) -> Option<String> {
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let line_offsets = line_offsets(&lines);
+    let mut fixed = String::with_capacity(source.len());
+    let mut at = 0;
+    for (start, end, _, _, replacement) in replacements(&lines, &line_offsets, suggestions)? {
+        fixed.push_str(&source[at..start]);
+        fixed.push_str(replacement);
+        at = end;
+    }
+    fixed.push_str(&source[at..]);
+    Some(fixed)
+}
+
+/// A replaced byte range, the lines it spans starting at 0, and its replacement.
+type Replacement<'a> = (usize, usize, usize, usize, &'a String);
+
+/// The byte offset of each line, followed by the length of the source.
+fn line_offsets(lines: &[&str]) -> Vec<usize> {
+    let mut line_offsets = Vec::with_capacity(lines.len() + 1);
+    let mut offset = 0;
+    for line in lines {
+        line_offsets.push(offset);
+        offset += line.len();
+    }
+    line_offsets.push(offset);
+    line_offsets
+}
+
+/// The replacements of `suggestions` in order, `None` if they overlap or aren't within the lines.
+fn replacements<'a>(
+    lines: &[&str],
+    line_offsets: &[usize],
+    suggestions: &[&'a Suggestion],
+) -> Option<Vec<Replacement<'a>>> {
+    let byte_offset = |line: u32, column: u32| -> Option<usize> {
+        let text = lines.get((line as usize).checked_sub(1)?)?;
+        let column = (column as usize).checked_sub(1)?;
+        let in_line = text
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(text.len()))
+            .nth(column)?;
+        Some(line_offsets[line as usize - 1] + in_line)
+    };
+    let mut replacements = Vec::new();
+    for suggestion in suggestions {
+        let start = byte_offset(suggestion.lines[0], suggestion.columns[0])?;
+        let end = byte_offset(suggestion.lines[1], suggestion.columns[1])?;
+        if end < start {
+            return None;
+        }
+        let first_line = suggestion.lines[0] as usize - 1;
+        let last_line = suggestion.lines[1] as usize - 1;
+        replacements.push((start, end, first_line, last_line, &suggestion.replacement));
+    }
+    replacements.sort_by_key(|(start, end, ..)| (*start, *end));
+    replacements.dedup();
+    if replacements.windows(2).any(|pair| pair[1].0 < pair[0].1) {
+        return None;
+    }
+    Some(replacements)
+}
+
+fn push_line(body: &mut String, origin: char, line: &str) {
+    body.push(origin);
+    body.push_str(line);
+    if !line.ends_with('\n') {
+        body.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// The name of the patch file number `index` of a lint, like `0001-clippy-needless_borrow.patch`.
+fn patch_file_name(index: usize, lint: &Lint) -> String {
+    let code: String = lint
+        .code
+        .as_deref()
+        .unwrap_or("lint")
+        .replace("::", "-")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{:04}-{}.patch", index, code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_suggestions, file_patch, write_patches};
+    use crate::linter::{Lint, Location, Suggestion};
+    use std::fs;
+    use std::process::Command;
+
+    fn suggestion(lines: [u32; 2], columns: [u32; 2], replacement: &str) -> Suggestion {
+        Suggestion {
+            path: "src/lib.rs".to_string(),
+            lines,
+            columns,
+            replacement: replacement.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_file_patch() {
+        let source = "fn main() {\n    let a = &x;\n}\n";
+        let borrow = suggestion([2, 2], [13, 15], "x");
+        assert_eq!(
+            Some(
+                "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+                 @@ -1,3 +1,3 @@\n fn main() {\n-    let a = &x;\n+    let a = x;\n }\n"
+                    .to_string()
+            ),
+            file_patch("src/lib.rs", source, &[&borrow])
+        );
+        // Overlapping suggestions and suggestions outside of the file can't be applied
+        let overlapping = suggestion([2, 2], [14, 16], "y");
+        assert_eq!(
+            None,
+            file_patch("src/lib.rs", source, &[&borrow, &overlapping])
+        );
+        let outside = suggestion([9, 9], [1, 2], "y");
+        assert_eq!(None, file_patch("src/lib.rs", source, &[&outside]));
+    }
+
+    #[test]
+    fn test_apply_suggestions() {
+        let source = "let a = &x;\nlet b = &y;\n";
+        let first = suggestion([1, 1], [9, 11], "x");
+        let second = suggestion([2, 2], [9, 11], "y");
+        assert_eq!(
+            Some("let a = x;\nlet b = y;\n".to_string()),
+            apply_suggestions(source, &[&second, &first])
+        );
+        let overlapping = suggestion([1, 1], [10, 12], "z");
+        assert_eq!(None, apply_suggestions(source, &[&first, &overlapping]));
+    }
+
+    #[test]
+    fn test_file_patch_without_trailing_newline() {
+        let patch = file_patch("a.rs", "let x = 1", &[&suggestion([1, 1], [9, 10], "2")]).unwrap();
+        assert!(patch.ends_with(
+            "-let x = 1\n\\ No newline at end of file\n+let x = 2\n\\ No newline at end of file\n"
+        ));
+    }
+
+    #[test]
+    fn test_write_patches_apply() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = dir.path();
+        let source: String = (1..=20)
+            .map(|i| format!("let v{} = &{};\n", i, i))
+            .collect();
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(repo.join("src/lib.rs"), &source).unwrap();
+        let lint = Lint {
+            message: "needless borrow".to_string(),
+            code: Some("clippy::needless_borrow".to_string()),
+            location: Location {
+                path: "src/lib.rs".to_string(),
+                lines: [2, 2],
+                ..Location::default()
+            },
+            suggestions: vec![
+                suggestion([2, 2], [10, 12], "2"),
+                suggestion([15, 15], [11, 14], "15"),
+            ],
+        };
+        let rustfmt = Lint {
+            suggestions: Vec::new(),
+            ..lint.clone()
+        };
+        let patches = repo.join("patches");
+        assert_eq!(
+            1,
+            write_patches(&[lint.clone(), lint, rustfmt], repo, repo, &patches).unwrap()
+        );
+
+        let patch = patches.join("0001-clippy-needless_borrow.patch");
+        assert!(patch.is_file());
+        let status = Command::new("git")
+            .current_dir(repo)
+            .arg("apply")
+            .arg(&patch)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let fixed = fs::read_to_string(repo.join("src/lib.rs")).unwrap();
+        assert_eq!(
+            source
+                .replace("let v2 = &2;", "let v2 = 2;")
+                .replace("let v15 = &15;", "let v15 = 15;"),
+            fixed
+        );
+    }
+}
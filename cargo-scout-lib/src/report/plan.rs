@@ -0,0 +1,192 @@
+use crate::error::Error;
+use crate::linter::LinterCommand;
+use crate::scout::Plan;
+use crate::utils::relative_path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Bumped whenever the plan layout changes in an incompatible way.
+pub const PLAN_VERSION: u32 = 1;
+
+/// The format of the plan `--emit-plan` prints.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PlanFormat {
+    /// A `JsonPlan`
+    Json,
+}
+
+impl FromStr for PlanFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown plan format {}, expected json", s)),
+        }
+    }
+}
+
+/// What a run would execute, for orchestrators splitting the work across agents.
+///
+/// Paths are relative to the repository root, so that the plan holds on any checkout.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct JsonPlan {
+    pub version: u32,
+    /// What the diff is computed against
+    pub targets: Vec<String>,
+    /// The files changed by the diff, `None` without a diff
+    pub files: Option<Vec<String>>,
+    /// Whether the result cache holds the report of the whole run, `None` when it isn't used
+    pub result_cache: Option<JsonCacheStatus>,
+    pub members: Vec<JsonPlannedMember>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct JsonCacheStatus {
+    pub key: String,
+    pub hit: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct JsonPlannedMember {
+    pub member: String,
+    /// The command the linter would run, `None` for linters that can't tell
+    pub command: Option<JsonCommand>,
+    /// Whether the result cache holds the report of the run of the agent linting the member,
+    /// with the same arguments and `--member <member>`, `None` when it isn't used
+    pub result_cache: Option<JsonCacheStatus>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct JsonCommand {
+    /// The directory the command runs from
+    pub cwd: String,
+    pub program: String,
+    pub args: Vec<String>,
+    /// The variables set on top of the environment of scout
+    pub env: BTreeMap<String, String>,
+}
+
+impl JsonPlan {
+    /// The `plan` of a run in the repository at `root`.
+    #[must_use]
+    pub fn new(plan: &Plan, root: &Path) -> Self {
+        Self {
+            version: PLAN_VERSION,
+            targets: plan.targets.clone(),
+            files: plan
+                .files
+                .as_ref()
+                .map(|files| files.iter().map(|file| relative_path(file, root)).collect()),
+            result_cache: None,
+            members: plan
+                .members
+                .iter()
+                .zip(&plan.commands)
+                .map(|(member, command)| JsonPlannedMember {
+                    member: member.clone(),
+                    command: command.as_ref().map(|c| JsonCommand::new(c, root)),
+                    result_cache: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Records whether the result cache holds the report of the run with the `key`.
+    #[must_use]
+    pub fn with_result_cache(mut self, key: String, hit: bool) -> Self {
+        self.result_cache = Some(JsonCacheStatus { key, hit });
+        self
+    }
+
+    /// Records whether the result cache holds the report of the run of each member alone,
+    /// `status` giving the key of that run and whether it is cached.
+    #[must_use]
+    pub fn with_member_caches(mut self, mut status: impl FnMut(&str) -> (String, bool)) -> Self {
+        for member in &mut self.members {
+            let (key, hit) = status(&member.member);
+            member.result_cache = Some(JsonCacheStatus { key, hit });
+        }
+        self
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl JsonCommand {
+    fn new(command: &LinterCommand, root: &Path) -> Self {
+        let cwd = relative_path(&command.working_dir.to_string_lossy(), root);
+        Self {
+            cwd: if cwd.is_empty() { ".".to_string() } else { cwd },
+            program: command.program.clone(),
+            args: command.args.clone(),
+            env: command.envs.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonPlan, PlanFormat};
+    use crate::linter::LinterCommand;
+    use crate::scout::Plan;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_json_plan() -> Result<(), crate::error::Error> {
+        let plan = Plan {
+            targets: vec!["HEAD (abc)".to_string()],
+            files: Some(vec!["/repo/member1/src/lib.rs".to_string()]),
+            members: vec!["member1".to_string(), "member2".to_string()],
+            commands: vec![
+                Some(LinterCommand {
+                    working_dir: PathBuf::from("/repo/member1"),
+                    envs: vec![("RUSTFLAGS".to_string(), "-Dwarnings".to_string())],
+                    program: "cargo".to_string(),
+                    args: vec!["clippy".to_string()],
+                }),
+                None,
+            ],
+        };
+        let json = JsonPlan::new(&plan, Path::new("/repo"))
+            .with_result_cache("0123".to_string(), false)
+            .with_member_caches(|member| (format!("{}-key", member), member == "member2"))
+            .to_json()?;
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(
+            serde_json::json!({
+                "version": 1,
+                "targets": ["HEAD (abc)"],
+                "files": ["member1/src/lib.rs"],
+                "result-cache": {"key": "0123", "hit": false},
+                "members": [
+                    {
+                        "member": "member1",
+                        "command": {
+                            "cwd": "member1",
+                            "program": "cargo",
+                            "args": ["clippy"],
+                            "env": {"RUSTFLAGS": "-Dwarnings"}
+                        },
+                        "result-cache": {"key": "member1-key", "hit": false}
+                    },
+                    {
+                        "member": "member2",
+                        "command": null,
+                        "result-cache": {"key": "member2-key", "hit": true}
+                    }
+                ]
+            }),
+            value
+        );
+        assert!("yaml".parse::<PlanFormat>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use crate::error::Error;
+use crate::linter::Lint;
+use crate::utils::relative_path;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// The problem matcher owner, used to unregister it.
+pub const OWNER: &str = "cargo-scout";
+
+/// The file name of the problem matcher written by `ProblemMatcher::register`.
+pub const MATCHER_FILE: &str = "cargo-scout-matcher.json";
+
+const PATTERN: &str = r"^cargo-scout:(.+):(\d+):(\d+): (warning|error): (.*)$";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Matchers {
+    problem_matcher: Vec<Matcher>,
+}
+
+#[derive(Serialize)]
+struct Matcher {
+    owner: &'static str,
+    pattern: Vec<Pattern>,
+}
+
+#[derive(Serialize)]
+struct Pattern {
+    regexp: &'static str,
+    file: u8,
+    line: u8,
+    column: u8,
+    severity: u8,
+    message: u8,
+}
+
+/// Returns the problem matcher definition, as expected by GitHub Actions.
+///
+/// # Panics
+/// Never, the definition is always serializable.
+#[must_use]
+pub fn definition() -> String {
+    let matchers = Matchers {
+        problem_matcher: vec![Matcher {
+            owner: OWNER,
+            pattern: vec![Pattern {
+                regexp: PATTERN,
+                file: 1,
+                line: 2,
+                column: 3,
+                severity: 4,
+                message: 5,
+            }],
+        }],
+    };
+    serde_json::to_string_pretty(&matchers).expect("the problem matcher is serializable")
+}
+
+/// A registered problem matcher.
+///
+/// Registering prints the `::add-matcher::` workflow command,
+/// dropping the registration prints the `::remove-matcher` one,
+/// so lines printed afterwards aren't matched anymore.
+pub struct ProblemMatcher {
+    path: PathBuf,
+}
+
+impl ProblemMatcher {
+    /// Writes the problem matcher definition in `dir` and registers it.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn register(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = dir.as_ref().join(MATCHER_FILE);
+        std::fs::write(&path, definition())?;
+        println!("::add-matcher::{}", path.display());
+        Ok(Self { path })
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ProblemMatcher {
+    fn drop(&mut self) {
+        println!("::remove-matcher owner={}::", OWNER);
+    }
+}
+
+/// Formats a lint so that it is picked up by the problem matcher.
+///
+/// Paths are made relative to `root`, which should be the repository root,
+/// and lints without a column point at the start of their line.
+#[must_use]
+pub fn format_lint(lint: &Lint, root: impl AsRef<Path>) -> String {
+    format!(
+        "cargo-scout:{}:{}:{}: warning: {}",
+        relative_path(&lint.location.path, root.as_ref()),
+        lint.location.lines[0],
+        lint.location.column().unwrap_or(1),
+        summary(lint)
+    )
+}
+
+/// The first line of the lint message, without its level,
+/// prefixed by the lint code if there is one.
+pub(crate) fn summary(lint: &Lint) -> String {
+    let first_line = lint
+        .message
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default();
+    let first_line = ["warning: ", "error: "]
+        .iter()
+        .find_map(|level| first_line.strip_prefix(level))
+        .unwrap_or(first_line);
+    match &lint.code {
+        Some(code) => format!("[{}] {}", code, first_line),
+        None => first_line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{definition, format_lint, ProblemMatcher, MATCHER_FILE, OWNER};
+    use crate::linter::{Lint, Location};
+
+    #[test]
+    fn test_definition() {
+        let json: serde_json::Value = serde_json::from_str(&definition()).unwrap();
+        let matcher = &json["problemMatcher"][0];
+        assert_eq!(OWNER, matcher["owner"]);
+        assert_eq!(1, matcher["pattern"][0]["file"]);
+        assert_eq!(3, matcher["pattern"][0]["column"]);
+        assert_eq!(5, matcher["pattern"][0]["message"]);
+    }
+
+    #[test]
+    fn test_format_lint() {
+        let lint = Lint {
+            message: "warning: redundant clone\n  --> src/lib.rs:12:5\n".to_string(),
+            code: Some("clippy::redundant_clone".to_string()),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [12, 12],
+                columns: [5, 18],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+        assert_eq!(
+            "cargo-scout:src/lib.rs:12:5: warning: [clippy::redundant_clone] redundant clone",
+            format_lint(&lint, "/repo")
+        );
+
+        let lint = Lint {
+            message: "\nDiff in /repo/src/lib.rs at line 1:".to_string(),
+            code: None,
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [1, 1],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+        assert_eq!(
+            "cargo-scout:src/lib.rs:1:1: warning: Diff in /repo/src/lib.rs at line 1:",
+            format_lint(&lint, "/repo")
+        );
+    }
+
+    #[test]
+    fn test_register_writes_the_definition() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        let matcher = ProblemMatcher::register(dir.path())?;
+        assert_eq!(dir.path().join(MATCHER_FILE), matcher.path());
+        assert_eq!(definition(), std::fs::read_to_string(matcher.path())?);
+        Ok(())
+    }
+}
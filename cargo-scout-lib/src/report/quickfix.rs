@@ -0,0 +1,83 @@
+use crate::linter::Lint;
+use crate::report::problem_matcher;
+use crate::utils::relative_path;
+use std::path::Path;
+
+/// Renders the lints as a quickfix list, a `file:line:col: warning: message` line per lint,
+/// which Vim's default `errorformat` and Emacs' `compilation-mode` both parse.
+///
+/// Paths are made relative to `base`, which should be the directory the editor runs from.
+#[must_use]
+pub fn render(lints: &[Lint], base: &Path) -> String {
+    lints
+        .iter()
+        .map(|lint| format_lint(lint, base))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Formats a single lint as a quickfix entry.
+#[must_use]
+pub fn format_lint(lint: &Lint, base: &Path) -> String {
+    format!(
+        "{}:{}:{}: warning: {}",
+        relative_path(&lint.location.path, base),
+        lint.location.lines[0],
+        lint.location.column().unwrap_or(1),
+        problem_matcher::summary(lint)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_lint, render};
+    use crate::linter::{Lint, Location};
+    use std::path::Path;
+
+    fn lint(message: &str, code: Option<&str>, column: u32) -> Lint {
+        Lint {
+            message: message.to_string(),
+            code: code.map(ToString::to_string),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [12, 12],
+                columns: [column, column],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_format_lint() {
+        let clone = lint(
+            "warning: redundant clone\n  --> src/lib.rs:12:14\n",
+            Some("clippy::redundant_clone"),
+            14,
+        );
+        assert_eq!(
+            "src/lib.rs:12:14: warning: [clippy::redundant_clone] redundant clone",
+            format_lint(&clone, Path::new("/repo"))
+        );
+        // Without a column, the entry points at the start of the line,
+        // and paths outside of the base directory stay absolute
+        let diff = lint("\nDiff in /repo/src/lib.rs at line 12:", None, 0);
+        assert_eq!(
+            "/repo/src/lib.rs:12:1: warning: Diff in /repo/src/lib.rs at line 12:",
+            format_lint(&diff, Path::new("/elsewhere"))
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let lints = [
+            lint("warning: a\n --> src/lib.rs:12:1", None, 1),
+            lint("warning: b\n --> src/lib.rs:12:5", None, 5),
+        ];
+        assert_eq!(
+            "src/lib.rs:12:1: warning: a\nsrc/lib.rs:12:5: warning: b",
+            render(&lints, Path::new("/repo"))
+        );
+        assert_eq!("", render(&[], Path::new("/repo")));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::config::scout::RankingConfig;
+use crate::linter::Lint;
+use crate::scout::build_output::glob_match;
+use crate::utils::{normalize_path, text_lines};
+use crate::vcs::Section;
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
+
+/// Scores the lints by likely usefulness, with the weights of a `RankingConfig`:
+/// errors, then deny-listed lints, then the lints of new files,
+/// then those of the added lines, before those only matching the boundaries of the diff.
+pub struct Ranker<'a> {
+    config: &'a RankingConfig,
+    added_sections: &'a [Section],
+    /// The normalized paths of the files the diff adds as a whole
+    new_files: HashSet<String>,
+}
+
+impl<'a> Ranker<'a> {
+    /// `added_sections` are the lines the diff adds, as `VCS::added_sections` lists them.
+    ///
+    /// A file is new when its added lines are all of its lines.
+    #[must_use]
+    pub fn new(config: &'a RankingConfig, added_sections: &'a [Section]) -> Self {
+        let mut added: BTreeMap<String, HashSet<u32>> = BTreeMap::new();
+        for section in added_sections {
+            added
+                .entry(normalize_path(&section.file_name))
+                .or_default()
+                .extend(section.line_start..=section.line_end);
+        }
+        let new_files = added_sections
+            .iter()
+            .map(|section| section.file_name.as_str())
+            .filter(|file| {
+                let lines =
+                    std::fs::read_to_string(file).map_or(0, |source| text_lines(&source).count());
+                let lines = u32::try_from(lines).unwrap_or(u32::MAX);
+                lines > 0
+                    && added
+                        .get(&normalize_path(file))
+                        .is_some_and(|added| (1..=lines).all(|line| added.contains(&line)))
+            })
+            .map(normalize_path)
+            .collect();
+        Self {
+            config,
+            added_sections,
+            new_files,
+        }
+    }
+
+    /// The score of `lint`, the sum of the weights of what it is.
+    #[must_use]
+    pub fn score(&self, lint: &Lint) -> u32 {
+        let path = normalize_path(&lint.location.path);
+        let [start, end] = lint.location.lines;
+        let on_added_lines = self.added_sections.iter().any(|section| {
+            normalize_path(&section.file_name) == path
+                && section.line_start <= end
+                && start <= section.line_end
+        });
+        let weights = [
+            (is_error(lint), self.config.error),
+            (self.is_deny_listed(lint), self.config.deny_listed),
+            (self.new_files.contains(&path), self.config.new_file),
+            (on_added_lines, self.config.modified_line),
+            (!on_added_lines, self.config.boundary),
+        ];
+        weights
+            .iter()
+            .filter(|(applies, _)| *applies)
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    /// Sorts `lints` by decreasing score, the lints of the same score keeping their order.
+    pub fn rank(&self, lints: &mut [Lint]) {
+        lints.sort_by_cached_key(|lint| std::cmp::Reverse(self.score(lint)));
+    }
+
+    fn is_deny_listed(&self, lint: &Lint) -> bool {
+        lint.code.as_ref().is_some_and(|code| {
+            self.config
+                .deny
+                .iter()
+                .any(|pattern| glob_match(pattern, code))
+        })
+    }
+}
+
+/// Whether `lint` is a compiler error rather than a warning, by its rendered message.
+fn is_error(lint: &Lint) -> bool {
+    lint.message.starts_with("error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ranker;
+    use crate::config::scout::RankingConfig;
+    use crate::linter::{Lint, Location};
+    use crate::vcs::Section;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn lint(message: &str, code: &str, path: &str, line: u32) -> Lint {
+        Lint {
+            message: message.to_string(),
+            code: Some(code.to_string()),
+            location: Location {
+                path: path.to_string(),
+                lines: [line, line],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn section(file_name: &str, line_start: u32, line_end: u32) -> Section {
+        Section {
+            file_name: file_name.to_string(),
+            line_start,
+            line_end,
+        }
+    }
+
+    #[test]
+    fn test_rank() -> Result<(), crate::error::Error> {
+        let dir = TempDir::new()?;
+        let new = dir.path().join("new.rs").to_string_lossy().into_owned();
+        let old = dir.path().join("old.rs").to_string_lossy().into_owned();
+        fs::write(&new, "fn a() {}\nfn b() {}\n")?;
+        fs::write(&old, "fn a() {}\nfn b() {}\nfn c() {}\n")?;
+        let added = [section(&new, 1, 2), section(&old, 2, 2)];
+        let config = RankingConfig {
+            deny: vec!["clippy::unwrap_*".to_string()],
+            ..RankingConfig::default()
+        };
+        let ranker = Ranker::new(&config, &added);
+
+        let boundary = lint("warning: a", "dead_code", &old, 3);
+        let modified = lint("warning: b", "dead_code", &old, 2);
+        let new_file = lint("warning: c", "dead_code", &new, 1);
+        let denied = lint("warning: d", "clippy::unwrap_used", &old, 3);
+        let error = lint("error[E0308]: e", "E0308", &old, 3);
+        assert_eq!(1, ranker.score(&boundary));
+        assert_eq!(2, ranker.score(&modified));
+        assert_eq!(6, ranker.score(&new_file));
+        assert_eq!(9, ranker.score(&denied));
+        assert_eq!(17, ranker.score(&error));
+
+        let mut lints = vec![
+            boundary.clone(),
+            modified.clone(),
+            new_file.clone(),
+            denied.clone(),
+            error.clone(),
+        ];
+        ranker.rank(&mut lints);
+        assert_eq!(vec![error, denied, new_file, modified, boundary], lints);
+        Ok(())
+    }
+
+    #[test]
+    fn test_weights() {
+        let config: crate::config::scout::ScoutConfig =
+            toml::from_str("[ranking]\nerror = 0\ndeny = [\"dead_code\"]\n").unwrap();
+        let ranker = Ranker::new(&config.ranking, &[]);
+        let mut lints = vec![
+            lint("error: a", "E0001", "src/lib.rs", 1),
+            lint("warning: b", "dead_code", "src/lib.rs", 2),
+        ];
+        ranker.rank(&mut lints);
+        assert_eq!("warning: b", lints[0].message);
+    }
+}
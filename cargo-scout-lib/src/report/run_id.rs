@@ -0,0 +1,50 @@
+use crate::config::scout::ScoutConfig;
+use crate::linter::fingerprint::Fnv1a;
+
+/// Bumped whenever the run id computation changes.
+const SALT: &str = "cargo-scout-run-id-v1";
+
+/// The default id of a run, a hash of the commits it compares and of the configuration.
+///
+/// A retried CI job scouts the same commits with the same configuration, so it gets the same id,
+/// which lets integrations update what an earlier attempt posted instead of posting it again.
+#[must_use]
+pub fn default_run_id(commits: &[String], config: &ScoutConfig) -> String {
+    let mut hasher = Fnv1a::default();
+    hasher.write(SALT);
+    for commit in commits {
+        hasher.write(commit);
+    }
+    hasher.write(&format!("{:?}", config));
+    format!("{:016x}", hasher.finish())
+}
+
+/// The html comment identifying a run in the markdown output, invisible once rendered.
+#[must_use]
+pub fn marker(run_id: &str) -> String {
+    format!("<!-- cargo-scout run-id: {} -->", run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_run_id, marker};
+    use crate::config::scout::ScoutConfig;
+
+    #[test]
+    fn test_default_run_id() {
+        let commits = vec!["main (abc)".to_string(), "def".to_string()];
+        let config = ScoutConfig::default();
+        let id = default_run_id(&commits, &config);
+        assert_eq!(16, id.len());
+        assert_eq!(id, default_run_id(&commits, &config));
+        assert_ne!(id, default_run_id(&commits[..1], &config));
+        let mut other = ScoutConfig::default();
+        other.dirty.report_all = true;
+        assert_ne!(id, default_run_id(&commits, &other));
+    }
+
+    #[test]
+    fn test_marker() {
+        assert_eq!("<!-- cargo-scout run-id: 42 -->", marker("42"));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::error::Error;
+use crate::report::json::JsonReport;
+use crate::report::json_lines::Event;
+use schemars::schema::RootSchema;
+use std::str::FromStr;
+
+/// The machine-readable outputs, whose JSON Schema `cargo scout schema` prints.
+///
+/// The schemas are generated from the types the outputs are serialized from,
+/// and published in the `schemas` directory of the repository.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SchemaKind {
+    /// A `json::JsonReport`
+    Json,
+    /// A line of the json-lines output, a `json_lines::Event`
+    JsonLines,
+}
+
+impl FromStr for SchemaKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "json-lines" => Ok(Self::JsonLines),
+            _ => Err(format!(
+                "no schema for the {} output format, expected json or json-lines",
+                s
+            )),
+        }
+    }
+}
+
+impl SchemaKind {
+    pub const ALL: [Self; 2] = [Self::Json, Self::JsonLines];
+
+    /// The name of the published schema.
+    #[must_use]
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "report.schema.json",
+            Self::JsonLines => "json-lines.schema.json",
+        }
+    }
+
+    #[must_use]
+    pub fn schema(self) -> RootSchema {
+        match self {
+            Self::Json => schemars::schema_for!(JsonReport),
+            Self::JsonLines => schemars::schema_for!(Event<'static>),
+        }
+    }
+
+    /// The schema, as pretty-printed json.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn render(self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(&self.schema())? + "\n")
+    }
+}
+
+/// Whether `instance` is valid against the schema of `kind`.
+#[cfg(test)]
+pub(crate) fn validates(kind: SchemaKind, instance: &serde_json::Value) -> bool {
+    let schema = serde_json::to_value(kind.schema()).unwrap();
+    jsonschema::JSONSchema::compile(&schema)
+        .unwrap()
+        .is_valid(instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validates, SchemaKind};
+    use crate::linter::crash::Crash;
+    use crate::linter::{Lint, Location, SnippetLine};
+    use crate::report::json::JsonReport;
+    use crate::scout::empty_diff::Status;
+    use crate::scout::explain::{Explanation, Stage, Step};
+
+    #[test]
+    fn test_published_schemas() -> Result<(), crate::error::Error> {
+        let schemas = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../schemas");
+        for kind in SchemaKind::ALL {
+            let published = std::fs::read_to_string(schemas.join(kind.file_name()))?;
+            assert_eq!(
+                kind.render()?,
+                published,
+                "{} is outdated, update it with `cargo scout schema`",
+                kind.file_name()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_validates() -> Result<(), crate::error::Error> {
+        let lint = Lint {
+            message: "warning: unused variable".to_string(),
+            code: Some("unused_variables".to_string()),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [3, 3],
+                columns: [9, 10],
+                bytes: Some([40, 41]),
+                snippet: vec![SnippetLine {
+                    text: "    let a = 1;".to_string(),
+                    highlight: [9, 10],
+                }],
+            },
+            suggestions: Vec::new(),
+        };
+        let labels = std::iter::once(("os".to_string(), "linux".to_string())).collect();
+        let explanation = Explanation {
+            lint: lint.clone(),
+            steps: vec![Step {
+                stage: Stage::Section,
+                kept: false,
+                reason: "no section of the diff holds the lint".to_string(),
+            }],
+        };
+        let report = JsonReport::new(&[lint], "/repo")
+            .with_explanations(&[explanation], "/repo")
+            .with_status(Status::NoChanges)
+            .with_retries(2)
+            .with_skipped(vec!["member".to_string()])
+            .with_crashed(vec![Crash {
+                member: "crashed".to_string(),
+                message: "error: internal compiler error".to_string(),
+            }])
+            .with_run_id(Some("42".to_string()))
+            .with_labels(labels);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()?)?;
+        assert!(validates(SchemaKind::Json, &json));
+        assert!(!validates(
+            SchemaKind::Json,
+            &serde_json::json!({"lints": []})
+        ));
+        assert!(!validates(
+            SchemaKind::JsonLines,
+            &serde_json::json!({"event": "finished"})
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(SchemaKind::JsonLines), "json-lines".parse());
+        assert!("markdown".parse::<SchemaKind>().is_err());
+    }
+}
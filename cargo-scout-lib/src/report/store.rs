@@ -0,0 +1,554 @@
+use super::json::JsonReport;
+use crate::error::Error;
+use crate::linter::Lint;
+use crate::utils::relative_path;
+use crate::vcs::blame::Blames;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, Row};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started_at INTEGER NOT NULL,
+    revision TEXT,
+    targets TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS lints (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    code TEXT,
+    path TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    member TEXT,
+    author TEXT,
+    message TEXT NOT NULL,
+    fingerprint TEXT NOT NULL
+);
+";
+
+/// A lint as it is stored, with the member and author it is attributed to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StoredLint {
+    pub code: Option<String>,
+    /// The path of the file, relative to the repository root
+    pub path: String,
+    pub line: u32,
+    /// The workspace member of the file, relative to the workspace root
+    pub member: Option<String>,
+    /// The author of the line, as `git blame` tells it
+    pub author: Option<String>,
+    /// The first line of the message
+    pub message: String,
+    pub fingerprint: String,
+}
+
+/// A run of scout, to record in the store.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Run {
+    /// The seconds since the Unix epoch when the run started
+    pub started_at: u64,
+    /// The commit checked out during the run
+    pub revision: Option<String>,
+    /// The branches the diff was made against
+    pub targets: Vec<String>,
+    pub lints: Vec<StoredLint>,
+}
+
+impl Run {
+    /// The run reporting `lints`.
+    ///
+    /// Paths are made relative to the repository at `root`,
+    /// and the lints are attributed to the `members` of the workspace at `workspace_root`.
+    #[must_use]
+    pub fn new(
+        lints: &[Lint],
+        root: &Path,
+        workspace_root: &Path,
+        members: &[String],
+        targets: &[String],
+    ) -> Self {
+        let started_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let mut blames = Blames::new(root);
+        let lints = lints
+            .iter()
+            .zip(JsonReport::new(lints, root).lints)
+            .map(|(lint, json)| StoredLint {
+                member: member_of(&relative_path(&lint.location.path, workspace_root), members),
+                author: blames.author(&json.path, json.lines[0]),
+                code: json.code,
+                line: json.lines[0],
+                message: json.message.lines().next().unwrap_or_default().to_string(),
+                fingerprint: json.fingerprint,
+                path: json.path,
+            })
+            .collect();
+        Self {
+            started_at,
+            revision: blames.head(),
+            targets: targets.to_vec(),
+            lints,
+        }
+    }
+
+    /// Inserts the run and its lints in `connection`, in a transaction.
+    fn insert(&self, connection: &mut Connection) -> rusqlite::Result<()> {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(SCHEMA)?;
+        transaction.execute(
+            "INSERT INTO runs (started_at, revision, targets) VALUES (?1, ?2, ?3)",
+            params![self.started_at, self.revision, self.targets.join(",")],
+        )?;
+        let run_id = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO lints (run_id, code, path, line, member, author, message, fingerprint) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for lint in &self.lints {
+                insert.execute(params![
+                    run_id,
+                    lint.code,
+                    lint.path,
+                    lint.line,
+                    lint.member,
+                    lint.author,
+                    lint.message,
+                    lint.fingerprint
+                ])?;
+            }
+        }
+        transaction.commit()
+    }
+}
+
+/// The column the matching lints are counted by.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CountBy {
+    Code,
+    Author,
+    Member,
+}
+
+impl CountBy {
+    fn column(self) -> &'static str {
+        match self {
+            Self::Code => "code",
+            Self::Author => "author",
+            Self::Member => "member",
+        }
+    }
+}
+
+impl FromStr for CountBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "code" => Ok(Self::Code),
+            "author" => Ok(Self::Author),
+            "member" => Ok(Self::Member),
+            _ => Err(format!(
+                "unknown column {}, expected code, author or member",
+                s
+            )),
+        }
+    }
+}
+
+/// The filters of a query of the stored lints.
+///
+/// Codes, authors and members are glob patterns, dates are `YYYY-MM-DD` and inclusive.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Query {
+    pub code: Option<String>,
+    pub author: Option<String>,
+    pub member: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub count_by: Option<CountBy>,
+}
+
+/// A lint matching a query, with the day of its run.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryRow {
+    pub date: String,
+    pub lint: StoredLint,
+}
+
+impl QueryRow {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            date: row.get("date")?,
+            lint: StoredLint {
+                code: row.get("code")?,
+                path: row.get("path")?,
+                line: row.get("line")?,
+                member: row.get("member")?,
+                author: row.get("author")?,
+                message: row.get("message")?,
+                fingerprint: row.get("fingerprint")?,
+            },
+        })
+    }
+
+    /// The row as a line of text: the date, the location, the lint and its author.
+    #[must_use]
+    pub fn line(&self) -> String {
+        format!(
+            "{} {}:{} {} ({}): {}",
+            self.date,
+            self.lint.path,
+            self.lint.line,
+            self.lint.code.as_deref().unwrap_or("unknown"),
+            self.lint.author.as_deref().unwrap_or("unknown author"),
+            self.lint.message
+        )
+    }
+}
+
+/// The number of lints matching a query, for a value of the counted column.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryCount {
+    pub key: Option<String>,
+    pub count: usize,
+}
+
+impl QueryCount {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            key: row.get("key")?,
+            count: row.get("count")?,
+        })
+    }
+}
+
+impl Query {
+    /// The select statement of the query and its bound parameters,
+    /// or an error naming an invalid date.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn sql(&self) -> Result<(String, Vec<String>), String> {
+        let mut conditions = Vec::new();
+        let mut parameters = Vec::new();
+        for (column, pattern) in [
+            ("lints.code", &self.code),
+            ("lints.author", &self.author),
+            ("lints.member", &self.member),
+        ] {
+            if let Some(pattern) = pattern {
+                conditions.push(format!("{} GLOB ?", column));
+                parameters.push(pattern.clone());
+            }
+        }
+        for (operator, date) in [(">=", &self.since), ("<=", &self.until)] {
+            if let Some(date) = date {
+                if !is_date(date) {
+                    return Err(format!("invalid date {}, expected YYYY-MM-DD", date));
+                }
+                conditions.push(format!("{} {} ?", DATE, operator));
+                parameters.push(date.clone());
+            }
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+        let from = "FROM lints JOIN runs ON runs.id = lints.run_id";
+        let sql = match self.count_by {
+            Some(count_by) => format!(
+                "SELECT lints.{} AS key, COUNT(*) AS count {}{} GROUP BY key ORDER BY count DESC, key;",
+                count_by.column(),
+                from,
+                filter
+            ),
+            None => format!(
+                "SELECT {} AS date, lints.* {}{} ORDER BY runs.started_at, lints.path, lints.line;",
+                DATE, from, filter
+            ),
+        };
+        Ok((sql, parameters))
+    }
+}
+
+/// The day a run started, in UTC.
+const DATE: &str = "date(runs.started_at, 'unixepoch')";
+
+/// A database of the runs of scout, in SQLite.
+#[derive(Debug, Clone)]
+pub struct Store {
+    path: PathBuf,
+}
+
+impl Store {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Records `run`, creating the database if it doesn't exist yet.
+    ///
+    /// The database is locked meanwhile, so that parallel jobs don't race to create it.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn record(&self, run: &Run) -> Result<(), Error> {
+        let _lock = crate::lock::FileLock::acquire(&self.path)?;
+        let mut connection = Connection::open(&self.path).map_err(store_error)?;
+        run.insert(&mut connection).map_err(store_error)
+    }
+
+    /// The lints matching `query`, in the order they were recorded.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn lints(&self, query: &Query) -> Result<Vec<QueryRow>, Error> {
+        self.select(query, QueryRow::from_row)
+    }
+
+    /// The counts of the lints matching `query`, the most frequent first.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn counts(&self, query: &Query) -> Result<Vec<QueryCount>, Error> {
+        self.select(query, QueryCount::from_row)
+    }
+
+    fn select<T>(
+        &self,
+        query: &Query,
+        from_row: impl FnMut(&Row<'_>) -> rusqlite::Result<T>,
+    ) -> Result<Vec<T>, Error> {
+        let (sql, parameters) = query.sql().map_err(Error::Command)?;
+        if !self.path.exists() {
+            return Err(Error::Command(format!(
+                "no run was stored in {}",
+                self.path.display()
+            )));
+        }
+        let connection = Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(store_error)?;
+        let mut statement = connection.prepare(&sql).map_err(store_error)?;
+        let rows = statement
+            .query_map(params_from_iter(parameters), from_row)
+            .map_err(store_error)?;
+        rows.collect::<Result<_, _>>().map_err(store_error)
+    }
+}
+
+fn store_error(error: rusqlite::Error) -> Error {
+    Error::Store(error.to_string())
+}
+
+/// The member whose directory holds the file at `path`, relative to the workspace root.
+///
+/// The deepest member wins, so that a workspace root package doesn't hide its members.
+#[must_use]
+pub fn member_of(path: &str, members: &[String]) -> Option<String> {
+    let path = path.replace('\\', "/");
+    members
+        .iter()
+        .map(|member| {
+            (
+                member,
+                member.trim_start_matches("./").trim_end_matches('/'),
+            )
+        })
+        .filter(|(_, dir)| {
+            dir.is_empty()
+                || *dir == "."
+                || path
+                    .strip_prefix(*dir)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .max_by_key(|(_, dir)| if *dir == "." { 0 } else { dir.len() })
+        .map(|(member, _)| member.clone())
+}
+
+/// Whether `date` is a `YYYY-MM-DD` date.
+fn is_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    matches!(parts.as_slice(), [year, month, day]
+        if year.len() == 4 && month.len() == 2 && day.len() == 2
+            && parts.iter().all(|part| part.bytes().all(|b| b.is_ascii_digit())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_date, member_of, CountBy, Query, QueryCount, QueryRow, Run, Store, StoredLint};
+    use crate::linter::{Lint, Location};
+    use std::fs;
+    use std::process::Command;
+
+    fn stored(code: &str, author: Option<&str>) -> StoredLint {
+        StoredLint {
+            code: Some(code.to_string()),
+            path: "lib/src/lib.rs".to_string(),
+            line: 3,
+            member: Some("lib".to_string()),
+            author: author.map(str::to_string),
+            message: "warning: it's used".to_string(),
+            fingerprint: "abc".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_record_and_query() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = Store::new(dir.path().join("runs.db"));
+        assert!(store.lints(&Query::default()).is_err());
+        let run = Run {
+            started_at: 1_700_000_000,
+            revision: None,
+            targets: vec!["main".to_string(), "HEAD".to_string()],
+            lints: vec![
+                stored("clippy::unwrap_used", Some("O'Brien")),
+                stored("clippy::expect_used", None),
+            ],
+        };
+        store.record(&run).unwrap();
+        store.record(&run).unwrap();
+
+        // The quotes of the values and patterns are bound, not spliced in the statements
+        let query = Query {
+            author: Some("O'*".to_string()),
+            since: Some("2023-11-14".to_string()),
+            ..Query::default()
+        };
+        let rows = store.lints(&query).unwrap();
+        assert_eq!(2, rows.len());
+        assert_eq!("2023-11-14", rows[0].date);
+        assert_eq!(stored("clippy::unwrap_used", Some("O'Brien")), rows[0].lint);
+        let query = Query {
+            count_by: Some(CountBy::Author),
+            ..Query::default()
+        };
+        assert_eq!(
+            vec![
+                QueryCount {
+                    key: None,
+                    count: 2
+                },
+                QueryCount {
+                    key: Some("O'Brien".to_string()),
+                    count: 2
+                }
+            ],
+            store.counts(&query).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_run_new() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = fs::canonicalize(dir.path()).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(&repo)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "committer"]);
+        git(&["config", "user.email", "committer@example.com"]);
+        fs::create_dir_all(repo.join("lib/src")).unwrap();
+        fs::write(repo.join("lib/src/lib.rs"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        git(&["config", "user.name", "local"]);
+        fs::write(repo.join("lib/src/lib.rs"), "one\ntwo\n").unwrap();
+
+        let lint = |line| Lint {
+            message: "warning: used\nmore".to_string(),
+            code: Some("clippy::unwrap_used".to_string()),
+            location: Location {
+                path: "lib/src/lib.rs".to_string(),
+                lines: [line, line],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+        let run = Run::new(
+            &[lint(1), lint(2)],
+            &repo,
+            &repo,
+            &["lib".to_string()],
+            &["HEAD".to_string()],
+        );
+        assert_eq!(Some(40), run.revision.as_ref().map(String::len));
+        assert_eq!(Some("committer"), run.lints[0].author.as_deref());
+        assert_eq!(Some("local"), run.lints[1].author.as_deref());
+        assert_eq!(Some("lib"), run.lints[1].member.as_deref());
+        assert_eq!("warning: used", run.lints[1].message);
+    }
+
+    #[test]
+    fn test_query_sql() {
+        assert_eq!(
+            "SELECT date(runs.started_at, 'unixepoch') AS date, lints.* \
+             FROM lints JOIN runs ON runs.id = lints.run_id \
+             ORDER BY runs.started_at, lints.path, lints.line;",
+            Query::default().sql().unwrap().0
+        );
+        let query = Query {
+            code: Some("clippy::unwrap_*".to_string()),
+            member: Some("lib".to_string()),
+            since: Some("2024-01-01".to_string()),
+            until: Some("2024-01-31".to_string()),
+            count_by: Some(CountBy::Author),
+            ..Query::default()
+        };
+        assert_eq!(
+            "SELECT lints.author AS key, COUNT(*) AS count \
+             FROM lints JOIN runs ON runs.id = lints.run_id \
+             WHERE lints.code GLOB ? AND lints.member GLOB ? \
+             AND date(runs.started_at, 'unixepoch') >= ? \
+             AND date(runs.started_at, 'unixepoch') <= ? \
+             GROUP BY key ORDER BY count DESC, key;",
+            query.sql().unwrap().0
+        );
+        assert_eq!(
+            vec!["clippy::unwrap_*", "lib", "2024-01-01", "2024-01-31"],
+            query.sql().unwrap().1
+        );
+        let query = Query {
+            since: Some("last week".to_string()),
+            ..Query::default()
+        };
+        assert!(query.sql().is_err());
+    }
+
+    #[test]
+    fn test_query_row() {
+        let row = QueryRow {
+            date: "2024-01-02".to_string(),
+            lint: stored("clippy::unwrap_used", None),
+        };
+        assert_eq!(
+            "2024-01-02 lib/src/lib.rs:3 clippy::unwrap_used (unknown author): warning: it's used",
+            row.line()
+        );
+    }
+
+    #[test]
+    fn test_member_of() {
+        let members = vec![".".to_string(), "lib".to_string(), "lib/nested".to_string()];
+        assert_eq!(
+            Some("lib/nested".to_string()),
+            member_of("lib/nested/src/lib.rs", &members)
+        );
+        assert_eq!(
+            Some("lib".to_string()),
+            member_of("lib/src/lib.rs", &members)
+        );
+        assert_eq!(
+            Some(".".to_string()),
+            member_of("library/src/lib.rs", &members)
+        );
+        assert_eq!(None, member_of("src/lib.rs", &["lib".to_string()]));
+    }
+
+    #[test]
+    fn test_dates() {
+        assert!(is_date("2024-02-29"));
+        assert!(!is_date("2024-2-29"));
+        assert!(!is_date("2024-02-29'"));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::scout::empty_diff;
+use crate::scout::Outcome;
+use std::time::Duration;
+
+/// A summary of the backends, filters, caches and timing a run involved, for bug reports.
+///
+/// It is only printed, nothing is sent anywhere.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct DebugSummary {
+    entries: Vec<(String, String)>,
+}
+
+impl DebugSummary {
+    pub fn add(&mut self, topic: &str, detail: impl Into<String>) -> &mut Self {
+        self.entries.push((topic.to_string(), detail.into()));
+        self
+    }
+
+    /// Adds what a run reported in its `outcome`: its notes, the members it skipped or
+    /// the linter crashed on, its empty diff, the members it linted and its retries.
+    pub fn add_run(&mut self, outcome: &Outcome) -> &mut Self {
+        for (topic, detail) in &outcome.notes {
+            self.add(topic, detail.clone());
+        }
+        if !outcome.skipped.is_empty() {
+            self.add("skipped", outcome.skipped.join(", "));
+        }
+        if let Some(targets) = &outcome.empty_diff {
+            self.add("diff", empty_diff::describe(targets));
+        }
+        if !outcome.crashes.is_empty() {
+            let crashed: Vec<&str> = outcome.crashes.iter().map(|c| c.member.as_str()).collect();
+            self.add("crashed", crashed.join(", "));
+        }
+        for (member, elapsed, lints) in &outcome.members {
+            self.add(
+                "member",
+                format!("{} in {}, {} lint(s)", member, seconds(*elapsed), lints),
+            );
+        }
+        self.add("retries", outcome.retries.to_string())
+    }
+
+    /// Adds the `elapsed` time of the whole program.
+    pub fn add_total(&mut self, elapsed: Duration) -> &mut Self {
+        self.add("total", seconds(elapsed))
+    }
+
+    /// The lines of the summary, with the details aligned.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        let width = self
+            .entries
+            .iter()
+            .map(|(topic, _)| topic.len())
+            .max()
+            .unwrap_or_default();
+        std::iter::once("--- cargo-scout debug summary ---".to_string())
+            .chain(
+                self.entries
+                    .iter()
+                    .map(|(topic, detail)| format!("{:width$}  {}", topic, detail, width = width)),
+            )
+            .collect()
+    }
+}
+
+/// A duration like `1.25s`.
+fn seconds(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{seconds, DebugSummary};
+    use crate::linter::crash::Crash;
+    use crate::scout::Outcome;
+    use std::time::Duration;
+
+    #[test]
+    fn test_lines() {
+        let mut summary = DebugSummary::default();
+        summary
+            .add("vcs", "git, against HEAD")
+            .add("result cache", "miss");
+        assert_eq!(
+            vec![
+                "--- cargo-scout debug summary ---",
+                "vcs           git, against HEAD",
+                "result cache  miss",
+            ],
+            summary.lines()
+        );
+        assert_eq!("1.25s", seconds(Duration::from_millis(1250)));
+    }
+
+    #[test]
+    fn test_add_run() {
+        let outcome = Outcome {
+            skipped: vec!["slow".to_string()],
+            crashes: vec![Crash {
+                member: "crashing".to_string(),
+                message: "error: internal compiler error".to_string(),
+            }],
+            notes: vec![("invalidator".to_string(), "Cargo.lock changed".to_string())],
+            members: vec![("linted".to_string(), Duration::from_millis(1500), 2)],
+            retries: 1,
+            ..Outcome::default()
+        };
+        let mut summary = DebugSummary::default();
+        summary.add_run(&outcome).add_total(Duration::from_secs(2));
+        assert_eq!(
+            vec![
+                "--- cargo-scout debug summary ---",
+                "invalidator  Cargo.lock changed",
+                "skipped      slow",
+                "crashed      crashing",
+                "member       linted in 1.50s, 2 lint(s)",
+                "retries      1",
+                "total        2.00s",
+            ],
+            summary.lines()
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use crate::linter::Lint;
+use std::collections::HashMap;
+
+/// How the text reporter displays lint messages.
+///
+/// Rendered messages can span several screens,
+/// these options keep hook and CI logs readable.
+#[derive(Debug, Default, Clone)]
+pub struct TextReporter {
+    max_message_lines: Option<usize>,
+    first_line_only: bool,
+    features: HashMap<Lint, Vec<String>>,
+}
+
+impl TextReporter {
+    /// Truncates each message after `max_message_lines` lines.
+    pub fn set_max_message_lines(&mut self, max_message_lines: Option<usize>) -> &mut Self {
+        self.max_message_lines = max_message_lines;
+        self
+    }
+
+    /// Only displays the first non blank line of each message.
+    pub fn set_first_line_only(&mut self, first_line_only: bool) -> &mut Self {
+        self.first_line_only = first_line_only;
+        self
+    }
+
+    /// Tells which combinations of `--feature-matrix` found the lints only some of them found.
+    pub fn set_features(&mut self, features: HashMap<Lint, Vec<String>>) -> &mut Self {
+        self.features = features;
+        self
+    }
+
+    /// Returns the lines of `message` to display.
+    ///
+    /// Messages are split on line boundaries only,
+    /// so a truncated message is always valid UTF-8.
+    /// When lines are left out, a last line tells how many.
+    #[must_use]
+    pub fn message_lines<'a>(&self, message: &'a str) -> Vec<std::borrow::Cow<'a, str>> {
+        let mut lines = message.split('\n').map(|line| line.trim_end_matches('\r'));
+        if self.first_line_only {
+            return lines
+                .find(|line| !line.trim().is_empty())
+                .map(Into::into)
+                .into_iter()
+                .collect();
+        }
+        let mut lines: Vec<std::borrow::Cow<str>> = lines.map(Into::into).collect();
+        // Rendered messages end with a blank line, it shouldn't count
+        let significant = lines
+            .iter()
+            .rposition(|l| !l.trim().is_empty())
+            .map_or(0, |i| i + 1);
+        if let Some(max) = self.max_message_lines {
+            if significant > max {
+                lines.truncate(max);
+                lines.push(match significant - max {
+                    1 => "... 1 more line".into(),
+                    more => format!("... {} more lines", more).into(),
+                });
+            }
+        }
+        lines
+    }
+
+    /// Returns the lines of `lint` to display, its message and the feature combinations
+    /// that found it, if only some of those of `--feature-matrix` did.
+    #[must_use]
+    pub fn lint_lines<'a>(&self, lint: &'a Lint) -> Vec<std::borrow::Cow<'a, str>> {
+        let mut lines = self.message_lines(&lint.message);
+        if let Some(features) = self.features.get(lint) {
+            let annotation = crate::scout::matrix::annotation(features);
+            // Before the blank line rendered messages end with
+            let end = lines
+                .iter()
+                .rposition(|l| !l.trim().is_empty())
+                .map_or(0, |i| i + 1);
+            lines.insert(end, annotation.into());
+        }
+        lines
+    }
+
+    /// Renders the warnings and their count, without colors.
+    #[must_use]
+    pub fn render(&self, lints: &[Lint]) -> String {
+        let mut rendered = String::new();
+        for lint in lints {
+            for line in self.lint_lines(lint) {
+                rendered.push_str(&line);
+                rendered.push('\n');
+            }
+        }
+        rendered.push_str(&summary(lints.len()));
+        rendered.push('\n');
+        rendered
+    }
+
+    /// Renders the warnings of each group under a heading counting them, without colors.
+    #[must_use]
+    pub fn render_groups(&self, groups: &[super::group::Group]) -> String {
+        let mut rendered = String::new();
+        let mut count = 0;
+        for group in groups {
+            rendered.push_str(&format!("{}: {}\n", group.key, group.lints.len()));
+            for lint in &group.lints {
+                for line in self.lint_lines(lint) {
+                    rendered.push_str(&line);
+                    rendered.push('\n');
+                }
+            }
+            count += group.lints.len();
+        }
+        rendered.push_str(&summary(count));
+        rendered.push('\n');
+        rendered
+    }
+}
+
+/// Tells how many warnings were found.
+#[must_use]
+pub fn summary(count: usize) -> String {
+    match count {
+        0 => "Cargo scout found no warnings".to_string(),
+        1 => "Cargo scout found a warning".to_string(),
+        count => format!("Cargo scout found {} warnings", count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextReporter;
+    use crate::linter::Lint;
+    use crate::report::group::Group;
+
+    const MESSAGE: &str =
+        "warning: redundant clone\n  --> src/lib.rs:12:5\n   |\n12 |     a.clone()\n\n";
+
+    #[test]
+    fn test_default_displays_everything() {
+        let lines = TextReporter::default().message_lines(MESSAGE);
+        assert_eq!(MESSAGE.split('\n').collect::<Vec<_>>(), lines);
+    }
+
+    #[test]
+    fn test_max_message_lines() {
+        let mut reporter = TextReporter::default();
+        reporter.set_max_message_lines(Some(2));
+        assert_eq!(
+            vec![
+                "warning: redundant clone",
+                "  --> src/lib.rs:12:5",
+                "... 2 more lines"
+            ],
+            reporter.message_lines(MESSAGE)
+        );
+
+        // The trailing blank lines don't count
+        reporter.set_max_message_lines(Some(4));
+        assert_eq!(
+            MESSAGE.split('\n').collect::<Vec<_>>(),
+            reporter.message_lines(MESSAGE)
+        );
+    }
+
+    #[test]
+    fn test_first_line_only() {
+        let mut reporter = TextReporter::default();
+        reporter.set_first_line_only(true);
+        assert_eq!(
+            vec!["warning: redundant clone"],
+            reporter.message_lines(MESSAGE)
+        );
+        assert_eq!(
+            vec!["Diff in /src/lib.rs at line 1:"],
+            reporter.message_lines("\r\nDiff in /src/lib.rs at line 1:\r\n")
+        );
+        assert!(reporter.message_lines("").is_empty());
+    }
+
+    #[test]
+    fn test_multibyte_messages() {
+        let mut reporter = TextReporter::default();
+        reporter.set_max_message_lines(Some(1));
+        assert_eq!(
+            vec![
+                "avertissement : variable inutilisée « é »",
+                "... 1 more line"
+            ],
+            reporter.message_lines("avertissement : variable inutilisée « é »\n--> src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let lint = Lint {
+            message: MESSAGE.to_string(),
+            ..Lint::default()
+        };
+        let mut reporter = TextReporter::default();
+        reporter.set_first_line_only(true);
+        assert_eq!(
+            "warning: redundant clone\nwarning: redundant clone\nCargo scout found 2 warnings\n",
+            reporter.render(&[lint.clone(), lint])
+        );
+        assert_eq!("Cargo scout found no warnings\n", reporter.render(&[]));
+    }
+
+    #[test]
+    fn test_features() {
+        let lint = Lint {
+            message: "warning: unused import\n".to_string(),
+            ..Lint::default()
+        };
+        let mut reporter = TextReporter::default();
+        assert_eq!(
+            vec!["warning: unused import", ""],
+            reporter.lint_lines(&lint)
+        );
+        reporter.set_features(std::iter::once((lint.clone(), vec!["serde".to_string()])).collect());
+        assert_eq!(
+            vec![
+                "warning: unused import",
+                "only with the features: serde",
+                ""
+            ],
+            reporter.lint_lines(&lint)
+        );
+    }
+
+    #[test]
+    fn test_render_groups() {
+        let lint = Lint {
+            message: MESSAGE.to_string(),
+            ..Lint::default()
+        };
+        let groups = vec![
+            Group {
+                key: "alice".to_string(),
+                lints: vec![&lint, &lint],
+            },
+            Group {
+                key: "bob".to_string(),
+                lints: vec![&lint],
+            },
+        ];
+        let mut reporter = TextReporter::default();
+        reporter.set_first_line_only(true);
+        assert_eq!(
+            "alice: 2\nwarning: redundant clone\nwarning: redundant clone\n\
+             bob: 1\nwarning: redundant clone\n\
+             Cargo scout found 3 warnings\n",
+            reporter.render_groups(&groups)
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use super::json::JsonReport;
+use super::text::summary;
+use crate::config::scout::WebhookConfig;
+use crate::error::Error;
+use crate::linter::Lint;
+use crate::utils::{decode_output, relative_path};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The environment variable the webhook url is read from, before the configuration.
+pub const URL_ENV: &str = "SCOUT_WEBHOOK_URL";
+
+/// Posts a summary of the warnings to a webhook, such as a Slack incoming webhook.
+#[derive(Debug, Clone)]
+pub struct WebhookReporter {
+    config: WebhookConfig,
+    url: Option<String>,
+}
+
+impl WebhookReporter {
+    /// A reporter of `config`, posting to the url of `SCOUT_WEBHOOK_URL` or of the configuration.
+    #[must_use]
+    pub fn new(config: WebhookConfig) -> Self {
+        let url = std::env::var(URL_ENV)
+            .ok()
+            .filter(|url| !url.is_empty())
+            .or_else(|| config.url.clone());
+        Self { config, url }
+    }
+
+    /// Whether a run reporting `count` lints is posted.
+    #[must_use]
+    pub fn notifies(&self, count: usize) -> bool {
+        self.url.is_some() && count > self.config.threshold
+    }
+
+    /// Renders the template for `lints`, whose paths are made relative to `root`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn payload(
+        &self,
+        lints: &[Lint],
+        root: &Path,
+        report: Option<&JsonReport>,
+    ) -> Result<String, Error> {
+        let listed: Vec<String> = lints
+            .iter()
+            .map(|lint| {
+                format!(
+                    "{}:{} {}",
+                    relative_path(&lint.location.path, root),
+                    lint.location.position(),
+                    lint.code.as_deref().unwrap_or_default()
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect();
+        let escape = |value: &str| {
+            let quoted = serde_json::to_string(value).unwrap_or_default();
+            quoted[1..quoted.len() - 1].to_string()
+        };
+        let rendered = self
+            .config
+            .template
+            .replace("{{count}}", &lints.len().to_string())
+            .replace("{{summary}}", &escape(&summary(lints.len())))
+            .replace("{{lints}}", &escape(&listed.join("\n")));
+        let mut payload: serde_json::Value = serde_json::from_str(&rendered)?;
+        if let (Some(report), Some(object)) = (report, payload.as_object_mut()) {
+            object.insert("report".to_string(), serde_json::to_value(report)?);
+        }
+        Ok(payload.to_string())
+    }
+
+    /// Posts the warnings if there are enough of them, with `curl`.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn notify(&self, lints: &[Lint], root: &Path, report: &JsonReport) -> Result<bool, Error> {
+        let Some(url) = self.url.as_deref().filter(|_| self.notifies(lints.len())) else {
+            return Ok(false);
+        };
+        let report = Some(report).filter(|_| self.config.include_json);
+        let payload = self.payload(lints, root, report)?;
+        let mut child = Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--fail",
+                "--request",
+                "POST",
+                "--header",
+                "Content-Type: application/json",
+                "--data-binary",
+                "@-",
+            ])
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(payload.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::Command(decode_output(output.stderr, "curl")));
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WebhookReporter;
+    use crate::config::scout::WebhookConfig;
+    use crate::linter::{Lint, Location};
+    use crate::report::json::JsonReport;
+    use std::path::Path;
+
+    fn lints() -> Vec<Lint> {
+        vec![Lint {
+            message: "warning: \"quoted\"".to_string(),
+            code: Some("clippy::unwrap_used".to_string()),
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines: [4, 4],
+                columns: [9, 15],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }]
+    }
+
+    fn reporter(config: WebhookConfig) -> WebhookReporter {
+        WebhookReporter {
+            config,
+            url: Some("https://hooks.example.com/scout".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_payload() {
+        let payload = reporter(WebhookConfig::default())
+            .payload(&lints(), Path::new("/repo"), None)
+            .unwrap();
+        assert_eq!(
+            r#"{"text":"Cargo scout found a warning\nsrc/lib.rs:4:9 clippy::unwrap_used"}"#,
+            payload
+        );
+
+        let config = WebhookConfig {
+            template: r#"{"content": "{{count}} new \"warnings\""}"#.to_string(),
+            include_json: true,
+            ..WebhookConfig::default()
+        };
+        let report = JsonReport::new(&lints(), "/repo");
+        let payload: serde_json::Value = serde_json::from_str(
+            &reporter(config)
+                .payload(&lints(), Path::new("/repo"), Some(&report))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!("1 new \"warnings\"", payload["content"]);
+        assert_eq!("clippy::unwrap_used", payload["report"]["lints"][0]["code"]);
+    }
+
+    #[test]
+    fn test_notifies() {
+        let config = WebhookConfig {
+            threshold: 2,
+            ..WebhookConfig::default()
+        };
+        assert!(!reporter(config.clone()).notifies(2));
+        assert!(reporter(config.clone()).notifies(3));
+        let without_url = WebhookReporter {
+            url: None,
+            ..reporter(config)
+        };
+        assert!(!without_url.notifies(3));
+    }
+}
@@ -0,0 +1,211 @@
+use std::io;
+use std::process::Output;
+use std::time::Duration;
+
+/// Messages cargo prints when it fails for a reason that may go away on its own.
+const RETRYABLE_MESSAGES: &[&str] = &[
+    "failed to acquire package cache lock",
+    "failed to lock file",
+    "Resource temporarily unavailable",
+    "Text file busy",
+    "spurious network error",
+    "Connection reset by peer",
+];
+
+/// How flaky subprocesses are retried.
+///
+/// The backoff doubles after each attempt, up to `max_backoff`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RetryPolicy {
+    /// How many times a failed subprocess is run again
+    pub retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy running subprocesses only once.
+    #[must_use]
+    pub fn never() -> Self {
+        Self {
+            retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// The time waited before the retry number `retry`, starting at 0.
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(2_u32.checked_pow(retry).unwrap_or(u32::MAX))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Runs `run` until it succeeds, fails for good, or runs out of retries.
+    ///
+    /// Returns the result of the last run, and how many times it was retried.
+    pub fn run(&self, run: impl FnMut() -> io::Result<Output>) -> (io::Result<Output>, u32) {
+        self.run_with_sleep(run, std::thread::sleep)
+    }
+
+    fn run_with_sleep(
+        &self,
+        mut run: impl FnMut() -> io::Result<Output>,
+        mut sleep: impl FnMut(Duration),
+    ) -> (io::Result<Output>, u32) {
+        let mut retry = 0;
+        loop {
+            let result = run();
+            let retryable = match &result {
+                Ok(output) => !output.status.success() && is_retryable_output(output),
+                Err(error) => is_retryable_error(error),
+            };
+            if !retryable || retry >= self.retries || crate::signal::received().is_some() {
+                return (result, retry);
+            }
+            sleep(self.backoff(retry));
+            retry += 1;
+        }
+    }
+}
+
+/// Whether a failed subprocess is worth running again.
+#[must_use]
+pub fn is_retryable_output(output: &Output) -> bool {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    RETRYABLE_MESSAGES
+        .iter()
+        .any(|message| stderr.contains(message))
+}
+
+/// Whether a subprocess that couldn't be started is worth starting again.
+#[must_use]
+pub fn is_retryable_error(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use std::io;
+    use std::process::Output;
+    use std::time::Duration;
+
+    /// The output of a process exiting with `code`.
+    fn output(code: u8, stderr: &str) -> Output {
+        #[cfg(unix)]
+        let status = std::os::unix::process::ExitStatusExt::from_raw(i32::from(code) << 8);
+        #[cfg(windows)]
+        let status = std::os::windows::process::ExitStatusExt::from_raw(u32::from(code));
+        Output {
+            status,
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = policy();
+        assert_eq!(Duration::from_millis(100), policy.backoff(0));
+        assert_eq!(Duration::from_millis(200), policy.backoff(1));
+        assert_eq!(Duration::from_millis(300), policy.backoff(2));
+        assert_eq!(Duration::from_millis(300), policy.backoff(40));
+    }
+
+    #[test]
+    fn test_retryable_failures_are_retried() {
+        let mut runs = 0;
+        let mut sleeps = Vec::new();
+        let (result, retries) = policy().run_with_sleep(
+            || {
+                runs += 1;
+                if runs < 3 {
+                    Ok(output(101, "error: failed to acquire package cache lock"))
+                } else {
+                    Ok(output(0, ""))
+                }
+            },
+            |backoff| sleeps.push(backoff),
+        );
+        assert!(result.unwrap().status.success());
+        assert_eq!(3, runs);
+        assert_eq!(2, retries);
+        assert_eq!(
+            vec![Duration::from_millis(100), Duration::from_millis(200)],
+            sleeps
+        );
+    }
+
+    #[test]
+    fn test_fatal_failures_are_not_retried() {
+        let mut runs = 0;
+        let (result, retries) = policy().run_with_sleep(
+            || {
+                runs += 1;
+                Ok(output(101, "error: could not compile `foo`"))
+            },
+            |_| {},
+        );
+        assert!(!result.unwrap().status.success());
+        assert_eq!(1, runs);
+        assert_eq!(0, retries);
+
+        let mut runs = 0;
+        let (result, _) = policy().run_with_sleep(
+            || {
+                runs += 1;
+                Err(io::Error::from(io::ErrorKind::NotFound))
+            },
+            |_| {},
+        );
+        assert!(result.is_err());
+        assert_eq!(1, runs);
+    }
+
+    #[test]
+    fn test_retries_run_out() {
+        let mut runs = 0;
+        let (result, retries) = policy().run_with_sleep(
+            || {
+                runs += 1;
+                Err(io::Error::from(io::ErrorKind::Interrupted))
+            },
+            |_| {},
+        );
+        assert!(result.is_err());
+        assert_eq!(4, runs);
+        assert_eq!(3, retries);
+        let mut runs = 0;
+        let (_, retries) = RetryPolicy::never().run_with_sleep(
+            || {
+                runs += 1;
+                Err(io::Error::from(io::ErrorKind::Interrupted))
+            },
+            |_| {},
+        );
+        assert_eq!(1, runs);
+        assert_eq!(0, retries);
+    }
+}
@@ -0,0 +1,250 @@
+use super::items::{holding, items};
+use crate::error::Error;
+use crate::linter::Lint;
+use crate::utils::relative_path;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The lints allowed on an item, by an `#[allow]` inserted above it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Allowed {
+    /// The file of the item
+    pub path: String,
+    /// The path of the item in its file, like `Config::new`
+    pub item: String,
+    /// The first line of the item, attributes and doc comments included, before the insertion
+    pub line: u32,
+    /// The lint codes, like `clippy::unwrap_used`
+    pub codes: Vec<String>,
+}
+
+/// Inserts an `#[allow]` for each of `lints` above the smallest item holding it,
+/// under a `// scout: reviewed by <reviewer>` comment.
+///
+/// The lints of the same item share their `#[allow]`.
+/// Nothing is written unless every lint can be allowed.
+///
+/// # Errors
+/// When a lint has no code or is a compiler error, or when no item of its file holds it.
+pub fn allow(lints: &[Lint], reviewer: &str, root: &Path) -> Result<Vec<Allowed>, Error> {
+    let mut sources: BTreeMap<&str, String> = BTreeMap::new();
+    let mut allowed: BTreeMap<(&str, u32), Allowed> = BTreeMap::new();
+    for lint in lints {
+        let path = lint.location.path.as_str();
+        let file = relative_path(path, root);
+        let code = allowable(lint).ok_or_else(|| {
+            Error::Allow(format!(
+                "the lint of {}:{} is a compiler error, which can't be allowed",
+                file, lint.location.lines[0]
+            ))
+        })?;
+        if !is_lint_path(code) {
+            return Err(Error::Allow(format!(
+                "`{}` of {}:{} isn't a rustc or clippy lint, which `#[allow]` can't allow",
+                code, file, lint.location.lines[0]
+            )));
+        }
+        if !sources.contains_key(path) {
+            sources.insert(path, std::fs::read_to_string(path)?);
+        }
+        let item = items(&sources[path])
+            .and_then(|items| holding(&items, lint.location.lines).cloned())
+            .ok_or_else(|| {
+                Error::Allow(format!(
+                    "no item of {} holds the `{}` lint of line {}",
+                    file, code, lint.location.lines[0]
+                ))
+            })?;
+        let entry = allowed
+            .entry((path, item.lines[0]))
+            .or_insert_with(|| Allowed {
+                path: path.to_string(),
+                item: item.name,
+                line: item.lines[0],
+                codes: Vec::new(),
+            });
+        if !entry.codes.contains(&code.to_string()) {
+            entry.codes.push(code.to_string());
+        }
+    }
+    let allowed: Vec<Allowed> = allowed.into_values().collect();
+    for (path, source) in sources {
+        let inserted: Vec<&Allowed> = allowed
+            .iter()
+            .filter(|allowed| allowed.path == path)
+            .collect();
+        std::fs::write(path, insert_allows(&source, &inserted, reviewer))?;
+    }
+    Ok(allowed)
+}
+
+/// The code of `lint` if it can be allowed: compiler errors like `E0308` can't.
+fn allowable(lint: &Lint) -> Option<&str> {
+    let code = lint.code.as_deref()?;
+    let error = code.len() == 5
+        && code.starts_with('E')
+        && code[1..].bytes().all(|byte| byte.is_ascii_digit());
+    (!error && !lint.message.starts_with("error")).then_some(code)
+}
+
+/// Whether `code` names a lint `#[allow]` takes, like `clippy::unwrap_used`,
+/// unlike the rules of other tools, like `rust/hard-coded-cryptographic-value`.
+fn is_lint_path(code: &str) -> bool {
+    code.split("::").all(|segment| {
+        segment.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Inserts the `#[allow]`s of `allowed`, the items of `source`, with the indentation of their items.
+fn insert_allows(source: &str, allowed: &[&Allowed], reviewer: &str) -> String {
+    let newline = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut inserted = String::with_capacity(source.len());
+    for (line, text) in (1..).zip(source.split_inclusive('\n')) {
+        for allowed in allowed.iter().filter(|allowed| allowed.line == line) {
+            let indentation = &text[..text.len() - text.trim_start().len()];
+            inserted.push_str(&format!(
+                "{}// scout: reviewed by {}{}{}#[allow({})]{}",
+                indentation,
+                reviewer,
+                newline,
+                indentation,
+                allowed.codes.join(", "),
+                newline
+            ));
+        }
+        inserted.push_str(text);
+    }
+    inserted
+}
+
+/// The `user.name` of the git configuration of the repository at `root`, to review the `#[allow]`s.
+#[must_use]
+pub fn reviewer(root: &Path) -> Option<String> {
+    git2::Repository::discover(root)
+        .ok()?
+        .config()
+        .ok()?
+        .get_string("user.name")
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allow, Allowed};
+    use crate::error::Error;
+    use crate::linter::{Lint, Location};
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = "\
+pub struct Foo;
+
+impl Foo {
+    /// Doubles the value
+    pub fn bar(value: Option<u32>) -> u32 {
+        value.unwrap() * 2
+    }
+}
+";
+
+    fn lint(path: &str, code: &str, lines: [u32; 2]) -> Lint {
+        Lint {
+            message: format!("warning: {}", code),
+            code: Some(code.to_string()),
+            location: Location {
+                path: path.to_string(),
+                lines,
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_allow() -> Result<(), Error> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("lib.rs").to_string_lossy().into_owned();
+        fs::write(&path, SOURCE)?;
+        let allowed = allow(
+            &[
+                lint(&path, "clippy::unwrap_used", [6, 6]),
+                lint(&path, "clippy::missing_panics_doc", [5, 5]),
+                lint(&path, "clippy::unwrap_used", [6, 6]),
+            ],
+            "Alice",
+            dir.path(),
+        )?;
+        assert_eq!(
+            vec![Allowed {
+                path: path.clone(),
+                item: "Foo::bar".to_string(),
+                line: 4,
+                codes: vec![
+                    "clippy::unwrap_used".to_string(),
+                    "clippy::missing_panics_doc".to_string()
+                ],
+            }],
+            allowed
+        );
+        assert_eq!(
+            "\
+pub struct Foo;
+
+impl Foo {
+    // scout: reviewed by Alice
+    #[allow(clippy::unwrap_used, clippy::missing_panics_doc)]
+    /// Doubles the value
+    pub fn bar(value: Option<u32>) -> u32 {
+        value.unwrap() * 2
+    }
+}
+",
+            fs::read_to_string(&path)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_allow_errors() -> Result<(), Error> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("lib.rs").to_string_lossy().into_owned();
+        fs::write(&path, format!("use std::fmt;\r\n{}", SOURCE))?;
+        let mut error = lint(&path, "E0308", [7, 7]);
+        error.message = "error[E0308]: mismatched types".to_string();
+        assert!(matches!(
+            allow(&[error], "Alice", dir.path()),
+            Err(Error::Allow(message)) if message == "the lint of lib.rs:7 is a compiler error, which can't be allowed"
+        ));
+        // Nothing is written when a lint can't be allowed
+        assert!(matches!(
+            allow(
+                &[
+                    lint(&path, "clippy::unwrap_used", [7, 7]),
+                    lint(&path, "unused_imports", [1, 1])
+                ],
+                "Alice",
+                dir.path()
+            ),
+            Err(Error::Allow(message)) if message == "no item of lib.rs holds the `unused_imports` lint of line 1"
+        ));
+        assert!(fs::read_to_string(&path)?.starts_with("use std::fmt;\r\npub struct Foo;\n"));
+        assert!(matches!(
+            allow(&[lint(&path, "rust/unsafe-usage", [7, 7])], "Alice", dir.path()),
+            Err(Error::Allow(message)) if message == "`rust/unsafe-usage` of lib.rs:7 isn't a rustc or clippy lint, which `#[allow]` can't allow"
+        ));
+
+        // The line endings of the file are kept
+        allow(&[lint(&path, "dead_code", [2, 2])], "Bob", dir.path())?;
+        assert!(fs::read_to_string(&path)?.starts_with(
+            "use std::fmt;\r\n// scout: reviewed by Bob\r\n#[allow(dead_code)]\r\npub struct Foo;\n"
+        ));
+        Ok(())
+    }
+}
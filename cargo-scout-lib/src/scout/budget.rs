@@ -0,0 +1,104 @@
+use crate::utils::is_in_dir;
+use crate::vcs::Section;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// The total time linting may take, past which the members left are skipped,
+/// and the lints of the members linted are reported as partial results.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TimeBudget {
+    deadline: Instant,
+}
+
+impl TimeBudget {
+    /// A budget of `budget`, starting now.
+    #[must_use]
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + budget,
+        }
+    }
+
+    /// Whether the budget ran out.
+    #[must_use]
+    pub fn is_spent(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+}
+
+/// The marker of partial results, like `3 members skipped due to the time budget`.
+#[must_use]
+pub fn skipped_marker(skipped: usize) -> String {
+    match skipped {
+        1 => "1 member skipped due to the time budget".to_string(),
+        n => format!("{} members skipped due to the time budget", n),
+    }
+}
+
+/// The lines `sections` change in the member at `member_path`.
+#[must_use]
+pub fn changed_lines(member_path: &Path, sections: &[Section]) -> u64 {
+    // The `.` member is the root package
+    let member_path: PathBuf = member_path.components().collect();
+    sections
+        .iter()
+        .filter(|section| is_in_dir(&section.file_name, &member_path))
+        .map(|section| u64::from(section.line_end.saturating_sub(section.line_start)) + 1)
+        .sum()
+}
+
+/// Orders `members` of the workspace at `root` by the lines `sections` change in them, most first.
+///
+/// Members changing as many lines keep their order.
+pub fn by_changed_lines(members: &mut [String], root: &Path, sections: &[Section]) {
+    members.sort_by_cached_key(|member| {
+        std::cmp::Reverse(changed_lines(&root.join(member), sections))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{by_changed_lines, changed_lines, skipped_marker, TimeBudget};
+    use crate::vcs::Section;
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn section(file_name: &str, line_start: u32, line_end: u32) -> Section {
+        Section {
+            file_name: file_name.to_string(),
+            line_start,
+            line_end,
+        }
+    }
+
+    #[test]
+    fn test_by_changed_lines() {
+        let sections = vec![
+            section("/repo/a/src/lib.rs", 1, 2),
+            section("/repo/b/src/lib.rs", 1, 10),
+            section("/repo/c/src/lib.rs", 5, 6),
+        ];
+        assert_eq!(10, changed_lines(Path::new("/repo/b"), &sections));
+        assert_eq!(0, changed_lines(Path::new("/repo/d"), &sections));
+        assert_eq!(14, changed_lines(Path::new("/repo/."), &sections));
+        let mut members = vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+            "d".to_string(),
+        ];
+        by_changed_lines(&mut members, Path::new("/repo"), &sections);
+        assert_eq!(vec!["b", "a", "c", "d"], members);
+    }
+
+    #[test]
+    fn test_budget() {
+        assert!(TimeBudget::new(Duration::from_secs(0)).is_spent());
+        assert!(!TimeBudget::new(Duration::from_secs(3600)).is_spent());
+        assert_eq!("1 member skipped due to the time budget", skipped_marker(1));
+        assert_eq!(
+            "2 members skipped due to the time budget",
+            skipped_marker(2)
+        );
+    }
+}
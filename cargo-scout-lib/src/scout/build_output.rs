@@ -0,0 +1,191 @@
+use crate::config::scout::BuildOutput;
+use crate::linter::Lint;
+use crate::utils::{normalize_path, relative_path};
+use std::path::Path;
+
+/// Attributes lints in files generated into a build script `OUT_DIR`
+/// back to the changed inputs they are generated from.
+pub struct OutputMapping<'a> {
+    build_outputs: &'a [BuildOutput],
+    /// The changed files, relative to the workspace root
+    changed: Vec<String>,
+}
+
+impl<'a> OutputMapping<'a> {
+    /// Maps the outputs to the files of the diff, Rust files or not, like `.proto` inputs.
+    #[must_use]
+    pub fn new(build_outputs: &'a [BuildOutput], changed_files: &[String], root: &Path) -> Self {
+        let mut changed: Vec<String> = if build_outputs.is_empty() {
+            Vec::new()
+        } else {
+            changed_files
+                .iter()
+                .map(|file| relative_path(file, root))
+                .collect()
+        };
+        changed.sort();
+        changed.dedup();
+        Self {
+            build_outputs,
+            changed,
+        }
+    }
+
+    /// Returns the changed input `lint` is attributed to,
+    /// if it is reported on a generated file.
+    #[must_use]
+    pub fn changed_input(&self, lint: &Lint) -> Option<&str> {
+        let output = out_dir_path(&lint.location.path)?;
+        self.build_outputs
+            .iter()
+            .filter(|b| b.is_output(&output))
+            .find_map(|b| self.changed.iter().find(|c| b.is_input(c)))
+            .map(String::as_str)
+    }
+}
+
+impl BuildOutput {
+    /// Whether `path`, relative to `OUT_DIR`, is one of the outputs.
+    fn is_output(&self, path: &str) -> bool {
+        self.outputs.iter().any(|o| glob_match(o, path))
+    }
+
+    /// Whether `path`, relative to the workspace root, is one of the inputs.
+    pub(crate) fn is_input(&self, path: &str) -> bool {
+        self.inputs.iter().any(|i| glob_match(i, path))
+    }
+}
+
+/// Whether the file at `path` is generated into an `OUT_DIR` by one of `build_outputs`.
+///
+/// These files are mapped to their inputs,
+/// even when they carry a generated marker.
+#[must_use]
+pub fn is_build_output(build_outputs: &[BuildOutput], path: &str) -> bool {
+    out_dir_path(path).is_some_and(|output| build_outputs.iter().any(|b| b.is_output(&output)))
+}
+
+/// Returns the path of a file relative to the `OUT_DIR` it was generated in,
+/// which cargo lays out as `build/<package>-<hash>/out`.
+///
+/// ```
+/// # use cargo_scout_lib::scout::build_output::out_dir_path;
+/// assert_eq!(
+///     Some("protos/api.rs".to_string()),
+///     out_dir_path("/repo/target/debug/build/api-0123abcd/out/protos/api.rs")
+/// );
+/// assert_eq!(None, out_dir_path("/repo/src/lib.rs"));
+/// ```
+#[must_use]
+pub fn out_dir_path(path: &str) -> Option<String> {
+    let path = normalize_path(path);
+    let segments: Vec<&str> = path.split('/').collect();
+    segments
+        .windows(3)
+        .position(|w| w[0] == "build" && w[1].contains('-') && w[2] == "out")
+        .map(|index| segments[index + 3..].join("/"))
+        .filter(|relative| !relative.is_empty())
+}
+
+/// Matches a path against a glob pattern.
+///
+/// `*` and `?` don't match `/`, `**` matches any number of directories.
+#[must_use]
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    glob_match_chars(&pattern, &path)
+}
+
+fn glob_match_chars(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob_match_chars(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(index, c)| *c == '/' && glob_match_chars(rest, &path[index + 1..]))
+        }
+        ['*', '*', rest @ ..] => {
+            (0..=path.len()).any(|index| glob_match_chars(rest, &path[index..]))
+        }
+        ['*', rest @ ..] => (0..=path.len())
+            .take_while(|index| *index == 0 || path[index - 1] != '/')
+            .any(|index| glob_match_chars(rest, &path[index..])),
+        ['?', rest @ ..] => match path {
+            [c, path @ ..] if *c != '/' => glob_match_chars(rest, path),
+            _ => false,
+        },
+        [p, rest @ ..] => match path {
+            [c, path @ ..] if c == p => glob_match_chars(rest, path),
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_match, is_build_output, out_dir_path, OutputMapping};
+    use crate::config::scout::BuildOutput;
+    use crate::linter::{Lint, Location};
+    use std::path::Path;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.rs", "api.rs"));
+        assert!(!glob_match("*.rs", "protos/api.rs"));
+        assert!(glob_match("**/*.rs", "api.rs"));
+        assert!(glob_match("**/*.rs", "protos/v1/api.rs"));
+        assert!(glob_match("proto/**", "proto/v1/api.proto"));
+        assert!(glob_match("include/?.h", "include/a.h"));
+        assert!(!glob_match("include/?.h", "include/ab.h"));
+        assert!(!glob_match("proto/*.proto", "proto/api.rs"));
+    }
+
+    #[test]
+    fn test_out_dir_path() {
+        assert_eq!(
+            Some("bindings.rs".to_string()),
+            out_dir_path("C:\\repo\\target\\debug\\build\\sys-0a1b\\out\\bindings.rs")
+        );
+        assert_eq!(None, out_dir_path("/repo/build/out/lib.rs"));
+        assert_eq!(None, out_dir_path("/repo/target/debug/build/sys-0a1b/out"));
+    }
+
+    #[test]
+    fn test_changed_input() {
+        let build_outputs = vec![BuildOutput {
+            inputs: vec!["proto/**/*.proto".to_string()],
+            outputs: vec!["*.rs".to_string()],
+        }];
+        let changed_files = vec!["/repo/proto/v1/api.proto".to_string()];
+        let mapping = OutputMapping::new(&build_outputs, &changed_files, Path::new("/repo"));
+        assert!(is_build_output(
+            &build_outputs,
+            "/repo/target/debug/build/api-0a1b/out/api.rs"
+        ));
+        assert!(!is_build_output(&build_outputs, "/repo/src/api.rs"));
+        let lint = |path: &str| Lint {
+            message: "Test lint".to_string(),
+            code: None,
+            location: Location {
+                path: path.to_string(),
+                lines: [100, 100],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+
+        assert_eq!(
+            Some("proto/v1/api.proto"),
+            mapping.changed_input(&lint("/repo/target/debug/build/api-0a1b/out/api.rs"))
+        );
+        assert_eq!(None, mapping.changed_input(&lint("/repo/src/api.rs")));
+        assert_eq!(
+            None,
+            OutputMapping::new(&build_outputs, &[], Path::new("/repo"))
+                .changed_input(&lint("/repo/target/debug/build/api-0a1b/out/api.rs"))
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use super::Scout;
+use crate::config::Config;
+use crate::error::Error;
+use crate::linter::{Lint, Linter};
+use crate::utils::normalize_path;
+use crate::vcs::VCS;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// A member the diff changes, as `changed_members` computes it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Member {
+    /// The path of the member relative to the workspace root, as the config lists it
+    pub path: String,
+    /// The directory of the member
+    pub dir: PathBuf,
+    /// The files of the member the diff changes, whatever their language.
+    /// Empty when a change elsewhere made it relevant, like a `clippy.toml` at the workspace root.
+    pub files: Vec<String>,
+}
+
+/// The members of `config` that scout would lint for the diff of `vcs`, without linting them,
+/// for scripts that only need to know which crates a change touches.
+///
+/// The members are computed as `Scout` does by default, `Scout::changed_members`
+/// computes them with its settings, like the dirty files or the invalidators.
+#[allow(clippy::missing_errors_doc)]
+pub fn changed_members<V: VCS, C: Config>(vcs: V, config: C) -> Result<Vec<Member>, Error> {
+    Scout::new(vcs, config, Unlinted).changed_members()
+}
+
+/// The linter of a scout that never lints.
+struct Unlinted;
+
+impl Linter for Unlinted {
+    fn lints(&self, _working_dir: impl Into<PathBuf>) -> Result<Vec<Lint>, Error> {
+        Ok(Vec::new())
+    }
+}
+
+impl<V, C, L> Scout<V, C, L>
+where
+    V: VCS,
+    C: Config,
+    L: Linter,
+{
+    /// The members `run` would lint, without their dependents, and the files the diff changes in them.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn changed_members(&self) -> Result<Vec<Member>, Error> {
+        if self.no_diff {
+            return Ok(self
+                .unfiltered_members()
+                .into_iter()
+                .map(|path| self.member(path, &BTreeSet::new()))
+                .collect());
+        }
+        let diff_sections = self.diff_sections()?;
+        let changed_files = self.changed_files()?;
+        let files: BTreeSet<String> = diff_sections
+            .iter()
+            .map(|s| normalize_path(&s.file_name))
+            .chain(changed_files.iter().map(|file| normalize_path(file)))
+            .collect();
+        Ok(self
+            .sharded(self.relevant_members(&diff_sections, &changed_files))
+            .into_iter()
+            .map(|path| self.member(path, &files))
+            .collect())
+    }
+
+    /// The member at `path`, with the changed `files` it holds.
+    fn member(&self, path: String, files: &BTreeSet<String>) -> Member {
+        // The root package is the `.` member, whose directory must not end with `/.`
+        let dir: PathBuf = self.config.root().join(&path).components().collect();
+        let prefix = format!("{}/", normalize_path(&dir.to_string_lossy()));
+        Member {
+            files: files
+                .iter()
+                .filter(|file| file.starts_with(&prefix))
+                .cloned()
+                .collect(),
+            path,
+            dir,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{changed_members, Member};
+    use crate::config::Config;
+    use crate::error::Error;
+    use crate::vcs::{Section, VCS};
+    use std::path::{Path, PathBuf};
+
+    struct TestVCS(Vec<Section>);
+
+    impl VCS for TestVCS {
+        fn root<P: AsRef<Path>>(&self, _: P) -> Result<PathBuf, Error> {
+            Ok(PathBuf::from("/repo"))
+        }
+        fn sections<P: AsRef<Path>>(&self, _: P) -> Result<Vec<Section>, Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    struct TestConfig(PathBuf);
+
+    impl Config for TestConfig {
+        fn members(&self) -> Vec<String> {
+            vec!["member1".to_string(), "member2".to_string()]
+        }
+        fn root(&self) -> &PathBuf {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_changed_members() -> Result<(), Error> {
+        let section = |file_name: &str| Section {
+            file_name: file_name.to_string(),
+            line_start: 1,
+            line_end: 2,
+        };
+        let vcs = TestVCS(vec![
+            section("/repo/member1/src/lib.rs"),
+            section("/repo/member1/src/main.rs"),
+            section("/repo/member2/tests/golden/output.txt"),
+        ]);
+        assert_eq!(
+            vec![Member {
+                path: "member1".to_string(),
+                dir: PathBuf::from("/repo/member1"),
+                files: vec![
+                    "/repo/member1/src/lib.rs".to_string(),
+                    "/repo/member1/src/main.rs".to_string()
+                ],
+            }],
+            changed_members(vcs, TestConfig(PathBuf::from("/repo")))?
+        );
+
+        // The lint configuration of every member changed
+        let vcs = TestVCS(vec![section("/repo/clippy.toml")]);
+        let members = changed_members(vcs, TestConfig(PathBuf::from("/repo")))?;
+        assert_eq!(
+            vec!["member1", "member2"],
+            members.iter().map(|m| m.path.as_str()).collect::<Vec<_>>()
+        );
+        assert!(members.iter().all(|m| m.files.is_empty()));
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use super::build_output::glob_match;
+use crate::config::scout::DirtyConfig;
+use crate::utils::{normalize_path, relative_path};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The members a change to a file like `build.rs` or `Cargo.toml` made dirty.
+///
+/// Such changes can alter lints anywhere in the member.
+/// Files at the workspace root make every member dirty.
+pub struct DirtyMembers {
+    /// The member directory, and the changed file that made it dirty
+    members: BTreeMap<String, String>,
+    report_all: bool,
+}
+
+impl DirtyMembers {
+    /// Finds the dirty `members` among the files of the diff, Rust files or not.
+    #[must_use]
+    pub fn new(
+        config: &DirtyConfig,
+        members: &[String],
+        changed_files: &[String],
+        root: &Path,
+    ) -> Self {
+        let mut dirty = BTreeMap::new();
+        for file in changed_files {
+            let at_root = relative_path(file, root);
+            let root_trigger = !at_root.contains('/') && is_trigger(config, &at_root);
+            for member in members {
+                let member_path = member_dir(root, member);
+                let file_name = normalize_path(file);
+                let in_member = file_name
+                    .strip_prefix(&member_path)
+                    .filter(|relative| relative.starts_with('/'))
+                    .map(|relative| relative.trim_start_matches('/'));
+                if root_trigger || in_member.is_some_and(|relative| is_trigger(config, relative)) {
+                    dirty.entry(member_path).or_insert_with(|| at_root.clone());
+                }
+            }
+        }
+        Self {
+            members: dirty,
+            report_all: config.report_all,
+        }
+    }
+
+    /// Whether `member`, relative to `root`, is dirty.
+    #[must_use]
+    pub fn is_dirty(&self, member: &str, root: &Path) -> bool {
+        self.members.contains_key(&member_dir(root, member))
+    }
+
+    /// Returns the changed file that made the member of the file at `path` dirty,
+    /// when every lint of dirty members is reported.
+    #[must_use]
+    pub fn trigger(&self, path: &str) -> Option<&str> {
+        if !self.report_all {
+            return None;
+        }
+        let path = normalize_path(path);
+        self.members
+            .iter()
+            .find(|(member, _)| {
+                path.strip_prefix(member.as_str())
+                    .is_some_and(|relative| relative.starts_with('/'))
+            })
+            .map(|(_, trigger)| trigger.as_str())
+    }
+}
+
+/// The directory of a member, `.` components are dropped.
+fn member_dir(root: &Path, member: &str) -> String {
+    let dir: PathBuf = root.join(member).components().collect();
+    normalize_path(&dir.to_string_lossy())
+}
+
+fn is_trigger(config: &DirtyConfig, relative: &str) -> bool {
+    config
+        .files
+        .iter()
+        .any(|pattern| glob_match(pattern, relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DirtyMembers;
+    use crate::config::scout::DirtyConfig;
+    use std::path::Path;
+
+    fn files(files: &[&str]) -> Vec<String> {
+        files.iter().map(ToString::to_string).collect()
+    }
+
+    fn members() -> Vec<String> {
+        vec!["foo".to_string(), "bar".to_string()]
+    }
+
+    #[test]
+    fn test_member_file_makes_the_member_dirty() {
+        let root = Path::new("/repo");
+        let config = DirtyConfig {
+            report_all: true,
+            ..DirtyConfig::default()
+        };
+        let dirty = DirtyMembers::new(
+            &config,
+            &members(),
+            &files(&["/repo/foo/build.rs", "/repo/bar/src/build.rs"]),
+            root,
+        );
+        assert!(dirty.is_dirty("foo", root));
+        assert!(!dirty.is_dirty("bar", root));
+        assert_eq!(Some("foo/build.rs"), dirty.trigger("/repo/foo/src/lib.rs"));
+        assert_eq!(None, dirty.trigger("/repo/bar/src/lib.rs"));
+        // A member directory sharing a prefix isn't the member
+        assert_eq!(None, dirty.trigger("/repo/foobar/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_root_file_makes_every_member_dirty() {
+        let root = Path::new("/repo");
+        let dirty = DirtyMembers::new(
+            &DirtyConfig::default(),
+            &members(),
+            &files(&["/repo/rust-toolchain.toml"]),
+            root,
+        );
+        assert!(dirty.is_dirty("foo", root));
+        assert!(dirty.is_dirty("bar", root));
+        // Only the members are linted by default, not every lint is reported
+        assert_eq!(None, dirty.trigger("/repo/foo/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_single_crate() {
+        let root = Path::new("/repo");
+        let dirty = DirtyMembers::new(
+            &DirtyConfig::default(),
+            &[".".to_string()],
+            &files(&["/repo/Cargo.toml"]),
+            root,
+        );
+        assert!(dirty.is_dirty(".", root));
+    }
+}
@@ -0,0 +1,49 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Whether a run had changes to scout, as the json report tells it.
+///
+/// A run without lints is either a clean change or a run which compared nothing,
+/// like one against `HEAD` with a clean working tree.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    /// The diff changes files, whether they have lints or not
+    #[default]
+    Compared,
+    /// The diff changes no file at all
+    NoChanges,
+}
+
+/// The status line of an empty diff, like `no changes vs origin/main (1a2b3c)`.
+#[must_use]
+pub fn describe(targets: &[String]) -> String {
+    if targets.is_empty() {
+        "no changes".to_string()
+    } else {
+        format!("no changes vs {}", targets.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe, Status};
+
+    #[test]
+    fn test_describe() {
+        assert_eq!("no changes", describe(&[]));
+        assert_eq!(
+            "no changes vs main (1a2b), release (3c4d)",
+            describe(&["main (1a2b)".to_string(), "release (3c4d)".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_status_json() {
+        assert_eq!(
+            "\"no-changes\"",
+            serde_json::to_string(&Status::NoChanges).unwrap()
+        );
+        assert_eq!(Status::Compared, Status::default());
+    }
+}
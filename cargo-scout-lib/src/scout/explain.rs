@@ -0,0 +1,400 @@
+use super::files_match;
+use super::matching::MatchStrategy;
+use crate::linter::Lint;
+use crate::vcs::Section;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A filtering stage a lint goes through.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stage {
+    /// Lints of generated files are dropped
+    Generated,
+    /// Some lints of test code are skipped
+    TestCode,
+    /// The profile of the team owning the file may not report the lint
+    Policy,
+    /// The diff isn't used, every lint is kept
+    NoDiff,
+    /// The lint must match a diff section, as the match strategy says
+    Section,
+    /// The same lint is only reported once
+    Dedup,
+    /// Only some lints are reported in the dependents of changed members
+    Dependent,
+}
+
+/// The decision a filtering stage made about a lint.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Clone, Debug)]
+pub struct Step {
+    pub stage: Stage,
+    pub kept: bool,
+    pub reason: String,
+}
+
+impl Step {
+    fn kept(stage: Stage, reason: impl Into<String>) -> Self {
+        Self {
+            stage,
+            kept: true,
+            reason: reason.into(),
+        }
+    }
+
+    fn dropped(stage: Stage, reason: impl Into<String>) -> Self {
+        Self {
+            stage,
+            kept: false,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Why a lint was reported or dropped.
+///
+/// The steps are in the order the stages ran,
+/// and stop at the first stage dropping the lint.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Explanation {
+    pub lint: Lint,
+    pub steps: Vec<Step>,
+}
+
+impl Explanation {
+    /// Whether the lint is reported.
+    #[must_use]
+    pub fn kept(&self) -> bool {
+        self.steps.iter().all(|step| step.kept)
+    }
+}
+
+/// Explains, for each lint, the decisions `lints_from_diff` makes.
+///
+/// The lints kept are the ones `lints_from_diff` returns.
+#[must_use]
+pub fn explain(lints: &[Lint], diffs: &[Section]) -> Vec<Explanation> {
+    explain_with(lints, diffs, MatchStrategy::Overlap, |_| None)
+}
+
+/// Explains like `explain`, matching the lints with `strategy`.
+/// `attribute` returns why a lint outside of the diff is kept anyway, if it is.
+#[must_use]
+pub fn explain_with(
+    lints: &[Lint],
+    diffs: &[Section],
+    strategy: MatchStrategy,
+    attribute: impl Fn(&Lint) -> Option<String>,
+) -> Vec<Explanation> {
+    let mut seen = HashSet::new();
+    lints
+        .iter()
+        .map(|lint| {
+            let mut steps = vec![
+                match (section_step(lint, diffs, strategy), attribute(lint)) {
+                    (step, Some(reason)) if !step.kept => Step::kept(Stage::Section, reason),
+                    (step, _) => step,
+                },
+            ];
+            if steps[0].kept {
+                steps.push(if seen.insert(lint) {
+                    Step::kept(Stage::Dedup, "first occurrence")
+                } else {
+                    Step::dropped(Stage::Dedup, "duplicate of an earlier lint")
+                });
+            }
+            Explanation {
+                lint: lint.clone(),
+                steps,
+            }
+        })
+        .collect()
+}
+
+/// Explains lints reported without a diff, they are all kept.
+#[must_use]
+pub fn explain_without_diff(lints: &[Lint]) -> Vec<Explanation> {
+    lints
+        .iter()
+        .map(|lint| Explanation {
+            lint: lint.clone(),
+            steps: vec![Step::kept(Stage::NoDiff, "the diff isn't used")],
+        })
+        .collect()
+}
+
+/// Explains lints of the dependents of changed members,
+/// `reports` tells which ones are reported.
+#[must_use]
+pub fn explain_dependents(lints: &[Lint], reports: impl Fn(&Lint) -> bool) -> Vec<Explanation> {
+    let mut seen = HashSet::new();
+    lints
+        .iter()
+        .map(|lint| {
+            let step = if !reports(lint) {
+                Step::dropped(
+                    Stage::Dependent,
+                    "not one of the lints reported in dependents",
+                )
+            } else if seen.insert(lint) {
+                Step::kept(Stage::Dependent, "reported in dependents")
+            } else {
+                Step::dropped(Stage::Dependent, "duplicate of an earlier lint")
+            };
+            Explanation {
+                lint: lint.clone(),
+                steps: vec![step],
+            }
+        })
+        .collect()
+}
+
+/// Adds the lints of generated files, dropped before any other stage,
+/// to the explanations of the other lints.
+#[must_use]
+pub fn with_generated(
+    explanations: Vec<Explanation>,
+    generated: Vec<(Lint, String)>,
+) -> Vec<Explanation> {
+    explanations
+        .into_iter()
+        .map(|mut explanation| {
+            explanation
+                .steps
+                .insert(0, Step::kept(Stage::Generated, "no generated marker"));
+            explanation
+        })
+        .chain(generated.into_iter().map(|(lint, marker)| Explanation {
+            lint,
+            steps: vec![Step::dropped(
+                Stage::Generated,
+                format!("the file is generated, {}", marker),
+            )],
+        }))
+        .collect()
+}
+
+/// Adds the lints of test code that are skipped, dropped before they are matched with the diff,
+/// to the explanations of the other lints.
+#[must_use]
+pub fn with_test_code(
+    explanations: Vec<Explanation>,
+    test_code: Vec<(Lint, String)>,
+) -> Vec<Explanation> {
+    explanations
+        .into_iter()
+        .map(|mut explanation| {
+            explanation
+                .steps
+                .insert(0, Step::kept(Stage::TestCode, "not skipped test code"));
+            explanation
+        })
+        .chain(test_code.into_iter().map(|(lint, reason)| Explanation {
+            lint,
+            steps: vec![Step::dropped(
+                Stage::TestCode,
+                format!("the lint is skipped in test code, {}", reason),
+            )],
+        }))
+        .collect()
+}
+
+/// Adds the lints the profiles of the owners of their files don't report,
+/// dropped before they are matched with the diff, to the explanations of the other lints.
+#[must_use]
+pub fn with_policy(
+    explanations: Vec<Explanation>,
+    policy: Vec<(Lint, String)>,
+) -> Vec<Explanation> {
+    explanations
+        .into_iter()
+        .map(|mut explanation| {
+            explanation.steps.insert(
+                0,
+                Step::kept(Stage::Policy, "reported by the owners' profile"),
+            );
+            explanation
+        })
+        .chain(policy.into_iter().map(|(lint, reason)| Explanation {
+            lint,
+            steps: vec![Step::dropped(Stage::Policy, reason)],
+        }))
+        .collect()
+}
+
+fn section_step(lint: &Lint, diffs: &[Section], strategy: MatchStrategy) -> Step {
+    let in_file: Vec<&Section> = diffs.iter().filter(|s| files_match(lint, s)).collect();
+    if in_file.is_empty() {
+        return Step::dropped(Stage::Section, "the file isn't changed");
+    }
+    match in_file.iter().find(|s| strategy.matches(lint, s)) {
+        Some(section) => Step::kept(Stage::Section, strategy.kept_reason(section)),
+        None => Step::dropped(Stage::Section, strategy.dropped_reason(in_file.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        explain, explain_dependents, explain_with, explain_without_diff, with_generated,
+        with_test_code, Stage, Step,
+    };
+    use crate::linter::{Lint, Location};
+    use crate::scout::lints_from_diff;
+    use crate::scout::matching::MatchStrategy;
+    use crate::vcs::Section;
+
+    fn lint(path: &str, lines: [u32; 2]) -> Lint {
+        Lint {
+            message: "Test lint".to_string(),
+            code: None,
+            location: Location {
+                path: path.to_string(),
+                lines,
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn section(file_name: &str, line_start: u32, line_end: u32) -> Section {
+        Section {
+            file_name: file_name.to_string(),
+            line_start,
+            line_end,
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let lints = vec![
+            lint("/repo/src/lib.rs", [10, 12]),
+            lint("/repo/src/lib.rs", [30, 30]),
+            lint("/repo/src/main.rs", [1, 1]),
+            lint("/repo/src/lib.rs", [10, 12]),
+        ];
+        let sections = vec![section("/repo/src/lib.rs", 11, 14)];
+        let explanations = explain(&lints, &sections);
+
+        assert_eq!(
+            vec![
+                Step::kept(Stage::Section, "overlaps the changed lines 11-14"),
+                Step::kept(Stage::Dedup, "first occurrence"),
+            ],
+            explanations[0].steps
+        );
+        assert_eq!(
+            vec![Step::dropped(
+                Stage::Section,
+                "doesn't overlap any of the 1 changed section(s) of the file"
+            )],
+            explanations[1].steps
+        );
+        assert_eq!(
+            vec![Step::dropped(Stage::Section, "the file isn't changed")],
+            explanations[2].steps
+        );
+        assert_eq!(
+            Step::dropped(Stage::Dedup, "duplicate of an earlier lint"),
+            explanations[3].steps[1]
+        );
+
+        // The explanations agree with the intersection
+        let kept: Vec<Lint> = explanations
+            .into_iter()
+            .filter(super::Explanation::kept)
+            .map(|e| e.lint)
+            .collect();
+        assert_eq!(lints_from_diff(&lints, &sections), kept);
+    }
+
+    #[test]
+    fn test_explain_with() {
+        let lints = vec![lint("/repo/target/debug/build/a-1/out/api.rs", [1, 1])];
+        let explanations = explain_with(&lints, &[], MatchStrategy::Overlap, |_| {
+            Some("generated from the changed `proto/api.proto`".to_string())
+        });
+        assert_eq!(
+            Step::kept(
+                Stage::Section,
+                "generated from the changed `proto/api.proto`"
+            ),
+            explanations[0].steps[0]
+        );
+        assert!(explanations[0].kept());
+    }
+
+    #[test]
+    fn test_explain_dependents() {
+        let lints = vec![
+            lint("/repo/app/src/lib.rs", [1, 1]),
+            lint("/repo/app/src/lib.rs", [2, 2]),
+        ];
+        let explanations = explain_dependents(&lints, |l| l.location.lines[0] == 1);
+        assert_eq!(
+            vec![Step::kept(Stage::Dependent, "reported in dependents")],
+            explanations[0].steps
+        );
+        assert!(!explanations[1].kept());
+    }
+
+    #[test]
+    fn test_explain_without_diff() {
+        let explanations = explain_without_diff(&[lint("/repo/src/lib.rs", [1, 1])]);
+        assert!(explanations[0].kept());
+        assert_eq!(Stage::NoDiff, explanations[0].steps[0].stage);
+    }
+
+    #[test]
+    fn test_with_generated() {
+        let generated = lint("/repo/src/generated.rs", [1, 1]);
+        let explanations = with_generated(
+            explain_without_diff(&[lint("/repo/src/lib.rs", [1, 1])]),
+            vec![(generated.clone(), "`@generated` on line 1".to_string())],
+        );
+        assert!(explanations[0].kept());
+        assert_eq!(Stage::Generated, explanations[0].steps[0].stage);
+        assert_eq!(generated, explanations[1].lint);
+        assert_eq!(
+            vec![Step::dropped(
+                Stage::Generated,
+                "the file is generated, `@generated` on line 1"
+            )],
+            explanations[1].steps
+        );
+    }
+
+    #[test]
+    fn test_with_test_code() {
+        let test_lint = lint("/repo/src/lib.rs", [9, 9]);
+        let explanations = with_generated(
+            with_test_code(
+                explain_without_diff(&[lint("/repo/src/lib.rs", [1, 1])]),
+                vec![(test_lint.clone(), "`tests::foo` is test code".to_string())],
+            ),
+            Vec::new(),
+        );
+        assert!(explanations[0].kept());
+        assert_eq!(test_lint, explanations[1].lint);
+        assert_eq!(
+            vec![
+                Step::kept(Stage::Generated, "no generated marker"),
+                Step::dropped(
+                    Stage::TestCode,
+                    "the lint is skipped in test code, `tests::foo` is test code"
+                )
+            ],
+            explanations[1].steps
+        );
+    }
+
+    #[test]
+    fn test_stage_serialization() -> Result<(), serde_json::Error> {
+        assert_eq!("\"no-diff\"", serde_json::to_string(&Stage::NoDiff)?);
+        assert_eq!("\"section\"", serde_json::to_string(&Stage::Section)?);
+        assert_eq!("\"test-code\"", serde_json::to_string(&Stage::TestCode)?);
+        assert_eq!("\"policy\"", serde_json::to_string(&Stage::Policy)?);
+        Ok(())
+    }
+}
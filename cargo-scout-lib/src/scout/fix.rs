@@ -0,0 +1,277 @@
+use crate::error::Error;
+use crate::utils::{decode_output, relative_path};
+use crate::vcs::patch::{Hunk, Parser};
+use crate::vcs::Section;
+use std::path::{Path, PathBuf};
+
+/// Keeps the changes of `cargo clippy --fix` within the scouted sections.
+///
+/// The working tree is recorded before the fix runs,
+/// the hunks the fix made outside of the sections are then reverted.
+/// Files the fix creates, or that git doesn't track, aren't guarded.
+pub struct FixGuard {
+    repo: PathBuf,
+    /// The commit recording the working tree before the fix
+    snapshot: String,
+}
+
+/// The hunks of a fix that were kept and reverted.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct FixSummary {
+    pub kept: usize,
+    pub reverted: usize,
+}
+
+impl FixGuard {
+    /// Records the working tree of the repository at `repo`, without touching it.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn record(repo: impl Into<PathBuf>) -> Result<Self, Error> {
+        let repo = repo.into();
+        // Without local changes, `git stash create` prints nothing and HEAD is the snapshot
+        let stash = git(&repo, &["stash", "create"])?;
+        let snapshot = match stash.trim() {
+            "" => git(&repo, &["rev-parse", "HEAD"])?.trim().to_string(),
+            stash => stash.to_string(),
+        };
+        Ok(Self { repo, snapshot })
+    }
+
+    /// Reverts the hunks changed since the snapshot outside of `sections`.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn restrict(&self, sections: &[Section]) -> Result<FixSummary, Error> {
+        let diff = git(
+            &self.repo,
+            &[
+                "diff",
+                "-U0",
+                "--no-color",
+                "--no-ext-diff",
+                &self.snapshot,
+                "--",
+            ],
+        )?;
+        let mut summary = FixSummary::default();
+        for file in Parser::new(&diff)
+            .files()
+            .map_err(|error| Error::DiffParse("git diff".to_string(), error))?
+        {
+            let (Some(old_path), Some(new_path)) = (&file.old_path, &file.new_path) else {
+                continue;
+            };
+            if old_path != new_path {
+                continue;
+            }
+            let file_sections: Vec<&Section> = sections
+                .iter()
+                .filter(|s| relative_path(&s.file_name, &self.repo) == *new_path)
+                .collect();
+            let keep: Vec<bool> = file
+                .hunks
+                .iter()
+                .map(|hunk| in_sections(hunk, &file_sections))
+                .collect();
+            let kept = keep.iter().filter(|keep| **keep).count();
+            summary.kept += kept;
+            summary.reverted += keep.len() - kept;
+            if kept == keep.len() {
+                continue;
+            }
+            let path = self.repo.join(new_path);
+            let original = git(
+                &self.repo,
+                &["show", &format!("{}:{}", self.snapshot, new_path)],
+            )?;
+            let fixed = std::fs::read_to_string(&path)?;
+            std::fs::write(&path, revert_hunks(&original, &fixed, &file.hunks, &keep))?;
+        }
+        Ok(summary)
+    }
+}
+
+/// Whether every line a hunk changes is in one of `sections`.
+///
+/// Lines are those of the file before the fix, which the sections refer to.
+/// A hunk only adding lines is in a section if the line before or after it is.
+#[must_use]
+pub fn in_sections(hunk: &Hunk, sections: &[&Section]) -> bool {
+    let covered = |line: u32| {
+        sections
+            .iter()
+            .any(|s| s.line_start <= line && line <= s.line_end)
+    };
+    if hunk.old_lines == 0 {
+        return covered(hunk.old_start) || covered(hunk.old_start + 1);
+    }
+    (hunk.old_start..hunk.old_start + hunk.old_lines).all(covered)
+}
+
+/// Rebuilds a file from its `original` and `fixed` content,
+/// keeping the hunks of the diff between them for which `keep` is true.
+///
+/// The hunks have no context lines and are in order, like those of `git diff -U0`.
+#[must_use]
+pub fn revert_hunks(original: &str, fixed: &str, hunks: &[Hunk], keep: &[bool]) -> String {
+    let old: Vec<&str> = original.split_inclusive('\n').collect();
+    let new: Vec<&str> = fixed.split_inclusive('\n').collect();
+    let mut result = String::with_capacity(fixed.len());
+    let mut old_index = 0;
+    for (hunk, keep) in hunks.iter().zip(keep) {
+        // An empty range starts after the line before it
+        let old_begin = if hunk.old_lines == 0 {
+            hunk.old_start
+        } else {
+            hunk.old_start - 1
+        } as usize;
+        let new_begin = if hunk.new_lines == 0 {
+            hunk.new_start
+        } else {
+            hunk.new_start - 1
+        } as usize;
+        let old_end = old_begin + hunk.old_lines as usize;
+        let new_end = new_begin + hunk.new_lines as usize;
+        result.extend(
+            old.get(old_index..old_begin)
+                .unwrap_or_default()
+                .iter()
+                .copied(),
+        );
+        let lines = if *keep {
+            new.get(new_begin..new_end)
+        } else {
+            old.get(old_begin..old_end)
+        };
+        result.extend(lines.unwrap_or_default().iter().copied());
+        old_index = old_end;
+    }
+    result.extend(old.get(old_index..).unwrap_or_default().iter().copied());
+    result
+}
+
+/// Runs git in `repo`, and returns its standard output.
+#[cfg(not(tarpaulin_include))]
+pub(crate) fn git(repo: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = std::process::Command::new("git")
+        .current_dir(repo)
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::Command(decode_output(output.stderr, "git")));
+    }
+    Ok(decode_output(output.stdout, "git"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{in_sections, revert_hunks, FixGuard, FixSummary};
+    use crate::vcs::patch::{Hunk, Parser};
+    use crate::vcs::Section;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn hunks(original: &str, fixed: &str) -> Vec<Hunk> {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("old"), original).unwrap();
+        fs::write(dir.path().join("new"), fixed).unwrap();
+        let output = Command::new("git")
+            .current_dir(dir.path())
+            .args(["diff", "--no-index", "-U0", "old", "new"])
+            .output()
+            .unwrap();
+        let diff = String::from_utf8(output.stdout).unwrap();
+        Parser::new(&diff).files().unwrap().remove(0).hunks
+    }
+
+    fn section(line_start: u32, line_end: u32) -> Section {
+        Section {
+            file_name: "/repo/src/lib.rs".to_string(),
+            line_start,
+            line_end,
+        }
+    }
+
+    #[test]
+    fn test_revert_hunks() {
+        let original = "a\nb\nc\nd\ne\n";
+        let fixed = "A\nb\nc\nC\nd\n";
+        let hunks = hunks(original, fixed);
+        assert_eq!(3, hunks.len());
+        assert_eq!(fixed, revert_hunks(original, fixed, &hunks, &[true; 3]));
+        assert_eq!(original, revert_hunks(original, fixed, &hunks, &[false; 3]));
+        assert_eq!(
+            "a\nb\nc\nC\nd\ne\n",
+            revert_hunks(original, fixed, &hunks, &[false, true, false])
+        );
+        // Without a newline at the end of the files
+        let hunks = self::hunks("a\nb", "a\nB");
+        assert_eq!("a\nb", revert_hunks("a\nb", "a\nB", &hunks, &[false]));
+    }
+
+    #[test]
+    fn test_in_sections() {
+        let sections = [section(3, 5)];
+        let sections: Vec<&Section> = sections.iter().collect();
+        let changed = |old_start, old_lines| Hunk {
+            old_start,
+            old_lines,
+            new_start: old_start,
+            new_lines: 1,
+        };
+        assert!(in_sections(&changed(3, 3), &sections));
+        assert!(!in_sections(&changed(4, 3), &sections));
+        assert!(!in_sections(&changed(1, 1), &sections));
+        // Lines added right after a section, or right before it
+        assert!(in_sections(&changed(5, 0), &sections));
+        assert!(in_sections(&changed(2, 0), &sections));
+        assert!(!in_sections(&changed(6, 0), &sections));
+        assert!(!in_sections(&changed(3, 1), &[]));
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args([
+                "-c",
+                "user.name=scout",
+                "-c",
+                "user.email=scout@example.com",
+            ])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_restrict() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = fs::canonicalize(dir.path()).unwrap();
+        git(&repo, &["init", "-q"]);
+        fs::write(repo.join("lib.rs"), "a\nb\nc\nd\n").unwrap();
+        git(&repo, &["add", "lib.rs"]);
+        git(&repo, &["commit", "-q", "-m", "init"]);
+        // A local change, which the fix must keep
+        fs::write(repo.join("lib.rs"), "a\nb\nc\nd\nlocal\n").unwrap();
+
+        let guard = FixGuard::record(&repo).unwrap();
+        fs::write(repo.join("lib.rs"), "A\nb\nC\nd\nlocal\n").unwrap();
+        let sections = [Section {
+            file_name: repo.join("lib.rs").to_string_lossy().into_owned(),
+            line_start: 3,
+            line_end: 3,
+        }];
+        assert_eq!(
+            FixSummary {
+                kept: 1,
+                reverted: 1
+            },
+            guard.restrict(&sections).unwrap()
+        );
+        assert_eq!(
+            "a\nb\nC\nd\nlocal\n",
+            fs::read_to_string(repo.join("lib.rs")).unwrap()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::error::Error;
+use crate::vcs::Section;
+use std::collections::BTreeSet;
+
+/// The largest diff scouted, larger ones like generated mass refactors are skipped.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct DiffGate {
+    /// The most changed lines
+    pub max_lines: Option<u64>,
+    /// The most changed files
+    pub max_files: Option<usize>,
+}
+
+impl DiffGate {
+    /// Fails with `Error::DiffTooLarge` when `sections` change too many lines or files.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn check(&self, sections: &[Section]) -> Result<(), Error> {
+        let (lines, files) = diff_stats(sections);
+        self.check_stats(lines, files)
+    }
+
+    /// Like `check`, with the lines and files changed, such as those of a diff computed so far.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn check_stats(&self, lines: u64, files: usize) -> Result<(), Error> {
+        if let Some(max_files) = self.max_files.filter(|max_files| files > *max_files) {
+            return Err(Error::DiffTooLarge(format!(
+                "{} files changed, more than {}",
+                files, max_files
+            )));
+        }
+        if let Some(max_lines) = self.max_lines.filter(|max_lines| lines > *max_lines) {
+            return Err(Error::DiffTooLarge(format!(
+                "{} lines changed, more than {}",
+                lines, max_lines
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The lines and the files `sections` change.
+#[must_use]
+pub fn diff_stats(sections: &[Section]) -> (u64, usize) {
+    let lines = sections
+        .iter()
+        .map(|section| u64::from(section.line_end.saturating_sub(section.line_start)) + 1)
+        .sum();
+    let files: BTreeSet<&str> = sections
+        .iter()
+        .map(|section| section.file_name.as_str())
+        .collect();
+    (lines, files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_stats, DiffGate};
+    use crate::error::Error;
+    use crate::vcs::Section;
+
+    fn sections() -> Vec<Section> {
+        vec![
+            Section {
+                file_name: "src/lib.rs".to_string(),
+                line_start: 1,
+                line_end: 10,
+            },
+            Section {
+                file_name: "src/lib.rs".to_string(),
+                line_start: 20,
+                line_end: 20,
+            },
+            Section {
+                file_name: "src/main.rs".to_string(),
+                line_start: 5,
+                line_end: 9,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_diff_stats() {
+        assert_eq!((16, 2), diff_stats(&sections()));
+        assert_eq!((0, 0), diff_stats(&[]));
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(DiffGate::default().check(&sections()).is_ok());
+        let gate = DiffGate {
+            max_lines: Some(16),
+            max_files: Some(2),
+        };
+        assert!(gate.check(&sections()).is_ok());
+        let gate = DiffGate {
+            max_lines: Some(15),
+            max_files: None,
+        };
+        assert!(matches!(
+            gate.check(&sections()),
+            Err(Error::DiffTooLarge(reason)) if reason == "16 lines changed, more than 15"
+        ));
+        let gate = DiffGate {
+            max_lines: None,
+            max_files: Some(1),
+        };
+        assert!(matches!(
+            gate.check(&sections()),
+            Err(Error::DiffTooLarge(reason)) if reason == "2 files changed, more than 1"
+        ));
+    }
+}
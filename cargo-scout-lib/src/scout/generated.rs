@@ -0,0 +1,124 @@
+use crate::config::scout::GeneratedConfig;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// Finds the generated files lints are reported on.
+///
+/// Each file is only read once.
+pub struct GeneratedFiles<'a> {
+    config: &'a GeneratedConfig,
+    markers: HashMap<String, Option<String>>,
+}
+
+impl<'a> GeneratedFiles<'a> {
+    #[must_use]
+    pub fn new(config: &'a GeneratedConfig) -> Self {
+        Self {
+            config,
+            markers: HashMap::new(),
+        }
+    }
+
+    /// Returns why the file at `path` is generated, if it is.
+    ///
+    /// Files that can't be read are not generated.
+    pub fn marker(&mut self, path: &str) -> Option<String> {
+        if !self.config.enabled {
+            return None;
+        }
+        let config = self.config;
+        self.markers
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                let file = File::open(path).ok()?;
+                find_marker(BufReader::new(file), config)
+                    .map(|(line, marker)| format!("`{}` on line {}", marker, line))
+            })
+            .clone()
+    }
+}
+
+/// Searches the first `header_lines` lines of `source` for a marker,
+/// and returns its line, starting at 1, and the marker.
+#[must_use]
+pub fn find_marker(source: impl BufRead, config: &GeneratedConfig) -> Option<(usize, &str)> {
+    source
+        .lines()
+        .take(config.header_lines)
+        .map_while(Result::ok)
+        .enumerate()
+        .find_map(|(index, line)| {
+            config
+                .markers
+                .iter()
+                .find(|marker| line.contains(marker.as_str()))
+                .map(|marker| (index + 1, marker.as_str()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_marker, GeneratedFiles};
+    use crate::config::scout::GeneratedConfig;
+    use std::io::Write;
+
+    #[test]
+    fn test_find_marker() {
+        let config = GeneratedConfig::default();
+        assert_eq!(
+            Some((1, "@generated")),
+            find_marker(
+                &b"// @generated by prost-build\nfn main() {}\n"[..],
+                &config
+            )
+        );
+        assert_eq!(
+            Some((2, "DO NOT EDIT")),
+            find_marker(
+                &b"/* automatically generated by rust-bindgen */\n// DO NOT EDIT\n"[..],
+                &config
+            )
+        );
+        assert_eq!(None, find_marker(&b"fn main() {}\n"[..], &config));
+    }
+
+    #[test]
+    fn test_only_header_lines_are_searched() {
+        let config = GeneratedConfig {
+            header_lines: 2,
+            markers: vec!["Code generated by".to_string()],
+            ..GeneratedConfig::default()
+        };
+        assert_eq!(
+            None,
+            find_marker(&b"\n\n// Code generated by tool\n"[..], &config)
+        );
+        assert_eq!(
+            Some((2, "Code generated by")),
+            find_marker(&b"\n// Code generated by tool\n"[..], &config)
+        );
+    }
+
+    #[test]
+    fn test_generated_files() -> Result<(), std::io::Error> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "// @generated")?;
+        let path = file.path().to_string_lossy().to_string();
+
+        let config = GeneratedConfig::default();
+        let mut generated = GeneratedFiles::new(&config);
+        assert_eq!(
+            Some("`@generated` on line 1".to_string()),
+            generated.marker(&path)
+        );
+        assert_eq!(None, generated.marker("/does/not/exist.rs"));
+
+        let disabled = GeneratedConfig {
+            enabled: false,
+            ..GeneratedConfig::default()
+        };
+        assert_eq!(None, GeneratedFiles::new(&disabled).marker(&path));
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// An item of a Rust file, like a function, a struct or an impl block.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Item {
+    /// The path of the item in its file, like `Config::new` for a method of an impl
+    pub name: String,
+    /// The first and last lines of the item, its attributes and doc comments included
+    pub lines: [u32; 2],
+    /// Whether the item is test code, it or an item holding it being `#[cfg(test)]` or a `#[test]`
+    pub test: bool,
+}
+
+impl Item {
+    fn holds(&self, [start, end]: [u32; 2]) -> bool {
+        self.lines[0] <= start && end <= self.lines[1]
+    }
+}
+
+/// The items of a Rust source, nested ones included, in the order they start.
+///
+/// `use` declarations and other unnamed items aren't listed.
+/// Returns `None` if the source doesn't parse.
+#[must_use]
+pub fn items(source: &str) -> Option<Vec<Item>> {
+    let file = syn::parse_file(source).ok()?;
+    let mut visitor = ItemVisitor {
+        items: Vec::new(),
+        path: Vec::new(),
+        test: false,
+    };
+    visitor.visit_file(&file);
+    Some(visitor.items)
+}
+
+/// The innermost of `items` that holds `line`.
+#[must_use]
+pub fn enclosing(items: &[Item], line: u32) -> Option<&Item> {
+    holding(items, [line, line])
+}
+
+/// The innermost of `items` that holds all of `lines`, the first and last ones.
+#[must_use]
+pub fn holding(items: &[Item], lines: [u32; 2]) -> Option<&Item> {
+    items
+        .iter()
+        .filter(|item| item.holds(lines))
+        .min_by_key(|item| item.lines[1] - item.lines[0])
+}
+
+/// Maps the lines of Rust files to the items holding them, each file is parsed once.
+#[derive(Debug, Default)]
+pub struct SyntaxMap {
+    /// The items of the files read so far, `None` for files that can't be read or parsed
+    files: HashMap<String, Option<Vec<Item>>>,
+}
+
+impl SyntaxMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The items of the file at `path`, none if it can't be read or parsed.
+    pub fn items(&mut self, path: &str) -> &[Item] {
+        self.files
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                std::fs::read_to_string(path)
+                    .ok()
+                    .and_then(|source| items(&source))
+            })
+            .as_deref()
+            .unwrap_or_default()
+    }
+
+    /// The innermost item of the file at `path` holding `line`.
+    pub fn enclosing(&mut self, path: &str, line: u32) -> Option<&Item> {
+        enclosing(self.items(path), line)
+    }
+}
+
+struct ItemVisitor {
+    items: Vec<Item>,
+    /// The names of the items being visited
+    path: Vec<String>,
+    /// Whether the items being visited are test code
+    test: bool,
+}
+
+impl ItemVisitor {
+    /// Records an item, and visits its nested items under its name.
+    fn named(
+        &mut self,
+        name: String,
+        attrs: &[syn::Attribute],
+        span: proc_macro2::Span,
+        visit_nested: impl FnOnce(&mut Self),
+    ) {
+        let lines = [span.start().line, span.end().line]
+            .map(|line| u32::try_from(line).unwrap_or(u32::MAX));
+        let outer_test = self.test;
+        self.test = outer_test || attrs.iter().any(is_test_attribute);
+        self.path.push(name);
+        self.items.push(Item {
+            name: self.path.join("::"),
+            lines,
+            test: self.test,
+        });
+        visit_nested(self);
+        self.path.pop();
+        self.test = outer_test;
+    }
+}
+
+/// Whether `attr` makes an item test code, like `#[cfg(test)]`, `#[test]` or `#[tokio::test]`.
+fn is_test_attribute(attr: &syn::Attribute) -> bool {
+    if attr.path().is_ident("cfg") {
+        return attr
+            .parse_args::<syn::Meta>()
+            .is_ok_and(|meta| cfg_requires_test(&meta));
+    }
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "test")
+}
+
+/// Whether a `cfg` predicate only holds when testing, like `test` or `all(test, unix)`.
+fn cfg_requires_test(meta: &syn::Meta) -> bool {
+    match meta {
+        syn::Meta::Path(path) => path.is_ident("test"),
+        syn::Meta::List(list) if list.path.is_ident("all") => list
+            .parse_args_with(
+                syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+            )
+            .is_ok_and(|predicates| predicates.iter().any(cfg_requires_test)),
+        _ => false,
+    }
+}
+
+/// The name an impl block gives to the path of its items, the last segment of its type.
+fn impl_name(item: &syn::ItemImpl) -> String {
+    match &*item.self_ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or_else(|| "_".to_string(), |segment| segment.ident.to_string()),
+        _ => "_".to_string(),
+    }
+}
+
+impl<'ast> Visit<'ast> for ItemVisitor {
+    fn visit_item(&mut self, item: &'ast syn::Item) {
+        let named = match item {
+            syn::Item::Const(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::Enum(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::Fn(item) => Some((item.sig.ident.to_string(), &item.attrs)),
+            syn::Item::Impl(item) => Some((impl_name(item), &item.attrs)),
+            syn::Item::Macro(item) => item
+                .ident
+                .as_ref()
+                .map(|ident| (ident.to_string(), &item.attrs)),
+            syn::Item::Mod(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::Static(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::Struct(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::Trait(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::TraitAlias(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::Type(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::Item::Union(item) => Some((item.ident.to_string(), &item.attrs)),
+            _ => None,
+        };
+        match named {
+            Some((name, attrs)) => self.named(name, attrs, item.span(), |visitor| {
+                syn::visit::visit_item(visitor, item);
+            }),
+            None => syn::visit::visit_item(self, item),
+        }
+    }
+
+    fn visit_impl_item(&mut self, item: &'ast syn::ImplItem) {
+        let named = match item {
+            syn::ImplItem::Const(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::ImplItem::Fn(item) => Some((item.sig.ident.to_string(), &item.attrs)),
+            syn::ImplItem::Type(item) => Some((item.ident.to_string(), &item.attrs)),
+            _ => None,
+        };
+        match named {
+            Some((name, attrs)) => self.named(name, attrs, item.span(), |visitor| {
+                syn::visit::visit_impl_item(visitor, item);
+            }),
+            None => syn::visit::visit_impl_item(self, item),
+        }
+    }
+
+    fn visit_trait_item(&mut self, item: &'ast syn::TraitItem) {
+        let named = match item {
+            syn::TraitItem::Const(item) => Some((item.ident.to_string(), &item.attrs)),
+            syn::TraitItem::Fn(item) => Some((item.sig.ident.to_string(), &item.attrs)),
+            syn::TraitItem::Type(item) => Some((item.ident.to_string(), &item.attrs)),
+            _ => None,
+        };
+        match named {
+            Some((name, attrs)) => self.named(name, attrs, item.span(), |visitor| {
+                syn::visit::visit_trait_item(visitor, item);
+            }),
+            None => syn::visit::visit_trait_item(self, item),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enclosing, items};
+
+    const SOURCE: &str = "\
+/// A struct
+pub struct Foo;
+
+impl Foo {
+    #[must_use]
+    pub fn bar() -> u32 {
+        1
+    }
+}
+
+mod nested {
+    use super::Foo;
+    fn baz() {}
+}
+";
+
+    #[test]
+    fn test_items() {
+        let items: Vec<(String, [u32; 2])> = items(SOURCE)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.name, item.lines))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Foo".to_string(), [1, 2]),
+                ("Foo".to_string(), [4, 9]),
+                ("Foo::bar".to_string(), [5, 8]),
+                ("nested".to_string(), [11, 14]),
+                ("nested::baz".to_string(), [13, 13]),
+            ],
+            items
+        );
+        assert_eq!(None, super::items("fn {"));
+    }
+
+    #[test]
+    fn test_test_items() {
+        let source = "\
+fn foo() {}
+
+#[cfg(test)]
+mod tests {
+    fn helper() {}
+}
+
+#[cfg(all(test, unix))]
+fn unix_helper() {}
+
+#[cfg(not(test))]
+fn not_test() {}
+
+#[tokio::test]
+async fn bar() {}
+";
+        let tests: Vec<(String, bool)> = items(source)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.name, item.test))
+            .collect();
+        assert_eq!(
+            vec![
+                ("foo".to_string(), false),
+                ("tests".to_string(), true),
+                ("tests::helper".to_string(), true),
+                ("unix_helper".to_string(), true),
+                ("not_test".to_string(), false),
+                ("bar".to_string(), true),
+            ],
+            tests
+        );
+    }
+
+    #[test]
+    fn test_enclosing() {
+        let items = items(SOURCE).unwrap();
+        assert_eq!(Some([5, 8]), enclosing(&items, 7).map(|item| item.lines));
+        assert_eq!(Some([4, 9]), enclosing(&items, 9).map(|item| item.lines));
+        assert_eq!(None, enclosing(&items, 3));
+    }
+}
@@ -0,0 +1,135 @@
+use super::lines_in_range;
+use crate::linter::Lint;
+use crate::vcs::Section;
+use std::str::FromStr;
+
+/// How strictly a lint must match the diff to be reported.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum MatchStrategy {
+    /// The lint overlaps a section of the diff
+    #[default]
+    Overlap,
+    /// Every line of the lint is in a section of the diff
+    Containment,
+    /// The first line of the lint is a line the diff adds
+    ChangedLinesOnly,
+}
+
+impl FromStr for MatchStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overlap" => Ok(Self::Overlap),
+            "containment" => Ok(Self::Containment),
+            "changed-lines-only" => Ok(Self::ChangedLinesOnly),
+            _ => Err(format!(
+                "unknown match strategy {}, expected overlap, containment or changed-lines-only",
+                s
+            )),
+        }
+    }
+}
+
+impl MatchStrategy {
+    /// Whether the strategy matches lints against the added lines, see `VCS::added_sections`.
+    #[must_use]
+    pub fn uses_added_lines(self) -> bool {
+        self == Self::ChangedLinesOnly
+    }
+
+    /// Whether `lint` matches `section`, a section of the same file.
+    #[must_use]
+    pub fn matches(self, lint: &Lint, section: &Section) -> bool {
+        let [start, end] = lint.location.lines;
+        match self {
+            Self::Overlap => lines_in_range(lint, section),
+            Self::Containment => section.line_start <= start && end <= section.line_end,
+            Self::ChangedLinesOnly => section.line_start <= start && start <= section.line_end,
+        }
+    }
+
+    /// Why a lint matching `section` is kept.
+    #[must_use]
+    pub fn kept_reason(self, section: &Section) -> String {
+        let verb = match self {
+            Self::Overlap => "overlaps",
+            Self::Containment => "is within",
+            Self::ChangedLinesOnly => "starts on",
+        };
+        format!(
+            "{} the changed lines {}-{}",
+            verb, section.line_start, section.line_end
+        )
+    }
+
+    /// Why a lint matching none of the `sections` sections of its file is dropped.
+    #[must_use]
+    pub fn dropped_reason(self, sections: usize) -> String {
+        let verb = match self {
+            Self::Overlap => "doesn't overlap",
+            Self::Containment => "isn't within",
+            Self::ChangedLinesOnly => "doesn't start on",
+        };
+        format!(
+            "{} any of the {} changed section(s) of the file",
+            verb, sections
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchStrategy;
+    use crate::linter::{Lint, Location};
+    use crate::vcs::Section;
+
+    fn lint(lines: [u32; 2]) -> Lint {
+        Lint {
+            message: String::new(),
+            code: None,
+            location: Location {
+                path: "/repo/src/lib.rs".to_string(),
+                lines,
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let section = Section {
+            file_name: "/repo/src/lib.rs".to_string(),
+            line_start: 10,
+            line_end: 20,
+        };
+        let matching = |strategy: MatchStrategy| -> Vec<bool> {
+            [[5, 12], [12, 14], [18, 25], [21, 22]]
+                .iter()
+                .map(|lines| strategy.matches(&lint(*lines), &section))
+                .collect()
+        };
+        assert_eq!(
+            vec![true, true, true, false],
+            matching(MatchStrategy::Overlap)
+        );
+        assert_eq!(
+            vec![false, true, false, false],
+            matching(MatchStrategy::Containment)
+        );
+        assert_eq!(
+            vec![false, true, true, false],
+            matching(MatchStrategy::ChangedLinesOnly)
+        );
+    }
+
+    #[test]
+    fn test_match_strategy_from_str() {
+        assert_eq!(
+            Ok(MatchStrategy::ChangedLinesOnly),
+            "changed-lines-only".parse()
+        );
+        assert!("strict".parse::<MatchStrategy>().is_err());
+    }
+}
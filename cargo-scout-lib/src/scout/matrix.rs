@@ -0,0 +1,148 @@
+use crate::config::scout::FeatureMatrixConfig;
+use crate::linter::Lint;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// The line telling that only the combinations labelled `features` found a lint.
+#[must_use]
+pub fn annotation(features: &[String]) -> String {
+    format!("only with the features: {}", features.join(" | "))
+}
+
+/// The combinations of the matrix that the package of `manifest_path` has all the features of,
+/// with their labels. Every combination is kept when the manifest can't be read.
+pub(crate) fn combinations(
+    matrix: &FeatureMatrixConfig,
+    manifest_path: &Path,
+) -> Vec<(String, Vec<String>)> {
+    let available = package_features(manifest_path);
+    matrix
+        .sets
+        .iter()
+        .filter(|set| {
+            available
+                .as_ref()
+                .is_none_or(|available| set.iter().all(|f| available.contains(f)))
+        })
+        .map(|set| (matrix.label(set), matrix.args(set)))
+        .collect()
+}
+
+/// The features of the package, declared or implied by its optional dependencies.
+fn package_features(manifest_path: &Path) -> Option<BTreeSet<String>> {
+    let manifest = cargo_toml::Manifest::from_path(manifest_path).ok()?;
+    let optional = manifest
+        .dependencies
+        .iter()
+        .chain(&manifest.build_dependencies)
+        .filter(|(_, dependency)| dependency.optional())
+        .map(|(name, _)| name.clone());
+    Some(manifest.features.keys().cloned().chain(optional).collect())
+}
+
+/// Combines the lints each combination found, labelled, keeping each lint once.
+///
+/// The lints only some combinations found are returned with their labels too.
+pub(crate) fn combine(runs: Vec<(String, Vec<Lint>)>) -> (Vec<Lint>, HashMap<Lint, Vec<String>>) {
+    let count = runs.len();
+    let mut found: Vec<(Lint, Vec<String>)> = Vec::new();
+    let mut index: HashMap<Lint, usize> = HashMap::new();
+    for (label, lints) in runs {
+        for lint in lints {
+            let i = *index.entry(lint.clone()).or_insert_with(|| {
+                found.push((lint, Vec::new()));
+                found.len() - 1
+            });
+            if !found[i].1.contains(&label) {
+                found[i].1.push(label.clone());
+            }
+        }
+    }
+    let mut features = HashMap::new();
+    let lints = found
+        .into_iter()
+        .map(|(lint, labels)| {
+            if labels.len() < count {
+                features.insert(lint.clone(), labels);
+            }
+            lint
+        })
+        .collect();
+    (lints, features)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotation, combinations, combine};
+    use crate::config::scout::FeatureMatrixConfig;
+    use crate::linter::{Lint, Location};
+    use std::path::Path;
+
+    fn lint(message: &str) -> Lint {
+        Lint {
+            message: message.to_string(),
+            code: None,
+            location: Location {
+                path: "/matrix/src/lib.rs".to_string(),
+                lines: [1, 1],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_combine() {
+        let (lints, features) = combine(vec![
+            (
+                "default".to_string(),
+                vec![lint("everywhere"), lint("default only")],
+            ),
+            (
+                "serde".to_string(),
+                vec![lint("serde only"), lint("everywhere")],
+            ),
+        ]);
+        assert_eq!(
+            vec![lint("everywhere"), lint("default only"), lint("serde only")],
+            lints
+        );
+        assert_eq!(None, features.get(&lint("everywhere")));
+        assert_eq!(
+            Some(&vec!["default".to_string()]),
+            features.get(&lint("default only"))
+        );
+        assert_eq!(
+            "only with the features: serde",
+            annotation(&features[&lint("serde only")])
+        );
+    }
+
+    #[test]
+    fn test_combinations() {
+        let matrix = FeatureMatrixConfig {
+            sets: vec![
+                Vec::new(),
+                vec!["tempfile".to_string()],
+                vec!["http".to_string()],
+            ],
+            no_default_features: false,
+        };
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        // `tempfile` is a dev dependency, it isn't a feature
+        assert_eq!(
+            vec![
+                ("default".to_string(), Vec::new()),
+                (
+                    "http".to_string(),
+                    vec!["--features".to_string(), "http".to_string()]
+                ),
+            ],
+            combinations(&matrix, &manifest)
+        );
+        assert_eq!(
+            3,
+            combinations(&matrix, Path::new("/missing/Cargo.toml")).len()
+        );
+    }
+}
@@ -1,7 +1,62 @@
+use crate::config::graph::DependencyGraph;
+use crate::config::scout::{
+    BuildOutput, DependentsConfig, DirtyConfig, FeatureMatrixConfig, GeneratedConfig,
+    InvalidatorsConfig, MemberOverride, PaddingRule, RelevanceConfig, TestCodeConfig,
+};
 use crate::config::Config;
-use crate::linter::{Lint, Linter};
-use crate::vcs::{Section, VCS};
-use std::collections::HashSet;
+use crate::linter::postprocess::PostProcessors;
+use crate::linter::{crash::Crash, Invocation, Lint, Linter, LinterCommand};
+use crate::signal;
+use crate::utils::{is_in_dir, normalize_path, relative_path};
+use crate::vcs::pathspec::Pathspec;
+use crate::vcs::{self, LineRanges, Section, VCS};
+use cargo_scout_macros::info;
+use colored::Colorize;
+use matching::MatchStrategy;
+use observer::Observer;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+pub mod allow;
+pub mod budget;
+pub mod build_output;
+pub mod changed;
+pub mod dirty;
+pub mod empty_diff;
+pub mod explain;
+pub mod fix;
+pub mod gate;
+pub mod generated;
+pub mod items;
+pub mod matching;
+pub mod matrix;
+pub mod observer;
+pub mod order;
+pub mod outcome;
+pub mod padding;
+pub mod policy;
+pub mod schedule;
+pub mod selection;
+pub mod shard;
+pub mod suggest;
+pub mod targets;
+pub mod test_code;
+
+pub use changed::{changed_members, Member};
+pub use outcome::Outcome;
+
+/// What `Scout::run` would do, as computed by `Scout::plan`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Plan {
+    /// What the diff is computed against
+    pub targets: Vec<String>,
+    /// The files changed by the diff, `None` without a diff
+    pub files: Option<Vec<String>>,
+    /// The members the linter would run on
+    pub members: Vec<String>,
+    /// The command the linter would run, for each member
+    pub commands: Vec<Option<LinterCommand>>,
+}
 
 pub struct Scout<V, C, L>
 where
@@ -12,6 +67,33 @@ where
     vcs: V,
     config: C,
     linter: L,
+    member_overrides: BTreeMap<String, MemberOverride>,
+    no_diff: bool,
+    workspace: bool,
+    generated: GeneratedConfig,
+    build_outputs: Vec<BuildOutput>,
+    dirty: DirtyConfig,
+    relevance: RelevanceConfig,
+    invalidators: InvalidatorsConfig,
+    shard: Option<shard::Shard>,
+    feature_matrix: Option<FeatureMatrixConfig>,
+    dependency_graph: Option<DependencyGraph>,
+    dependents: DependentsConfig,
+    jobs: usize,
+    jobs_per_target_dir: usize,
+    observer: Option<Box<dyn Observer>>,
+    post_processors: PostProcessors,
+    pathspec: Pathspec,
+    pipelined: bool,
+    match_strategy: MatchStrategy,
+    padding: Vec<PaddingRule>,
+    test_code: TestCodeConfig,
+    policy: Option<policy::Policy>,
+    diff_gate: gate::DiffGate,
+    budget: Option<budget::TimeBudget>,
+    member_order: Option<order::MemberOrder>,
+    order_graph: Option<DependencyGraph>,
+    target_selection: Option<selection::TargetSelection>,
 }
 
 impl<V, C, L> Scout<V, C, L>
@@ -25,68 +107,1133 @@ where
             vcs,
             config,
             linter,
+            member_overrides: BTreeMap::new(),
+            no_diff: false,
+            workspace: false,
+            generated: GeneratedConfig::default(),
+            build_outputs: Vec::new(),
+            dirty: DirtyConfig::default(),
+            relevance: RelevanceConfig::default(),
+            invalidators: InvalidatorsConfig::default(),
+            shard: None,
+            feature_matrix: None,
+            dependency_graph: None,
+            dependents: DependentsConfig::default(),
+            jobs: 1,
+            jobs_per_target_dir: 1,
+            observer: None,
+            post_processors: PostProcessors::default(),
+            pathspec: Pathspec::default(),
+            pipelined: false,
+            match_strategy: MatchStrategy::default(),
+            padding: Vec::new(),
+            test_code: TestCodeConfig::default(),
+            policy: None,
+            diff_gate: gate::DiffGate::default(),
+            budget: None,
+            member_order: None,
+            order_graph: None,
+            target_selection: None,
+        }
+    }
+
+    /// Sets the per member linter overrides, keyed by member name.
+    pub fn set_member_overrides(
+        &mut self,
+        member_overrides: BTreeMap<String, MemberOverride>,
+    ) -> &mut Self {
+        self.member_overrides = member_overrides;
+        self
+    }
+
+    /// Skips the diff computation and reports every lint
+    /// of the linted members, like a regular linter run would.
+    ///
+    /// In a workspace, only the `default-members` are linted,
+    /// unless `set_workspace` is enabled.
+    pub fn set_no_diff(&mut self, no_diff: bool) -> &mut Self {
+        self.no_diff = no_diff;
+        self
+    }
+
+    /// Lints every workspace member when running without a diff,
+    /// regardless of the `default-members`.
+    pub fn set_workspace(&mut self, workspace: bool) -> &mut Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Sets how generated files, whose lints are never reported, are detected.
+    pub fn set_generated(&mut self, generated: GeneratedConfig) -> &mut Self {
+        self.generated = generated;
+        self
+    }
+
+    /// Sets the mapping of build script outputs to their inputs,
+    /// so lints of generated files are kept when their inputs change.
+    pub fn set_build_outputs(&mut self, build_outputs: Vec<BuildOutput>) -> &mut Self {
+        self.build_outputs = build_outputs;
+        self
+    }
+
+    /// Sets which changed files make a whole member dirty.
+    pub fn set_dirty(&mut self, dirty: DirtyConfig) -> &mut Self {
+        self.dirty = dirty;
+        self
+    }
+
+    /// Sets which changed files make a member worth linting,
+    /// the members only changing others, like test data, are skipped.
+    pub fn set_relevance(&mut self, relevance: RelevanceConfig) -> &mut Self {
+        self.relevance = relevance;
+        self
+    }
+
+    /// Sets which changed files, like `clippy.toml`, make every member relevant.
+    pub fn set_invalidators(&mut self, invalidators: InvalidatorsConfig) -> &mut Self {
+        self.invalidators = invalidators;
+        self
+    }
+
+    /// Only lints the members of `shard`, the other jobs lint the others.
+    pub fn set_shard(&mut self, shard: Option<shard::Shard>) -> &mut Self {
+        self.shard = shard;
+        self
+    }
+
+    /// Lints each member once per combination of the matrix, see `matrix::features`.
+    pub fn set_feature_matrix(&mut self, feature_matrix: Option<FeatureMatrixConfig>) -> &mut Self {
+        self.feature_matrix = feature_matrix;
+        self
+    }
+
+    /// Also lints the members depending on the changed ones, with this graph.
+    ///
+    /// Only the lints selected by `set_dependents` are reported in these members.
+    pub fn set_dependency_graph(&mut self, dependency_graph: Option<DependencyGraph>) -> &mut Self {
+        self.dependency_graph = dependency_graph;
+        self
+    }
+
+    /// Sets which lints are reported in the dependents of changed members.
+    pub fn set_dependents(&mut self, dependents: DependentsConfig) -> &mut Self {
+        self.dependents = dependents;
+        self
+    }
+
+    /// Sets how many members are linted at once, 1 by default.
+    pub fn set_jobs(&mut self, jobs: usize) -> &mut Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Sets how many members sharing a target directory are linted at once, 1 by default.
+    ///
+    /// Concurrent cargo processes contend for the lock of their target directory,
+    /// members building in different ones run in parallel.
+    pub fn set_jobs_per_target_dir(&mut self, jobs_per_target_dir: usize) -> &mut Self {
+        self.jobs_per_target_dir = jobs_per_target_dir;
+        self
+    }
+
+    /// Sets the rules widening the lines of some lints before they are matched with the diff.
+    pub fn set_padding(&mut self, padding: Vec<PaddingRule>) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets which lints of test code are skipped.
+    pub fn set_test_code(&mut self, test_code: TestCodeConfig) -> &mut Self {
+        self.test_code = test_code;
+        self
+    }
+
+    /// Only reports the lints of the files of a team that its profile reports, see `Policy`.
+    pub fn set_policy(&mut self, policy: Option<policy::Policy>) -> &mut Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Skips the diffs larger than `diff_gate`, failing with `Error::DiffTooLarge`.
+    pub fn set_diff_gate(&mut self, diff_gate: gate::DiffGate) -> &mut Self {
+        self.diff_gate = diff_gate;
+        self
+    }
+
+    /// Lints the most changed members first, within `budget` from now:
+    /// the members left once it runs out are skipped, and the lints found so far reported.
+    pub fn set_budget(&mut self, budget: Option<std::time::Duration>) -> &mut Self {
+        self.budget = budget.map(budget::TimeBudget::new);
+        self
+    }
+
+    /// Sets the order members are linted in.
+    ///
+    /// Defaults to the order of the config, or the `diff-size` order with a time budget.
+    pub fn set_member_order(&mut self, member_order: Option<order::MemberOrder>) -> &mut Self {
+        self.member_order = member_order;
+        self
+    }
+
+    /// Sets the graph the `deps` order ranks members with, without linting their dependents.
+    ///
+    /// The dependency graph is used when there is none.
+    pub fn set_order_graph(&mut self, order_graph: Option<DependencyGraph>) -> &mut Self {
+        self.order_graph = order_graph;
+        self
+    }
+
+    /// Only lints the targets of the members holding the changed files, when they can be told.
+    pub fn set_target_selection(
+        &mut self,
+        target_selection: Option<selection::TargetSelection>,
+    ) -> &mut Self {
+        self.target_selection = target_selection;
+        self
+    }
+
+    /// Sets how strictly the lints must match the diff.
+    pub fn set_match_strategy(&mut self, match_strategy: MatchStrategy) -> &mut Self {
+        self.match_strategy = match_strategy;
+        self
+    }
+
+    /// Sets what follows the progress of the runs.
+    pub fn set_observer(&mut self, observer: Option<Box<dyn Observer>>) -> &mut Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Sets what rewrites the lints as the linter found them, like their paths in a container.
+    pub fn set_post_processors(&mut self, post_processors: PostProcessors) -> &mut Self {
+        self.post_processors = post_processors;
+        self
+    }
+
+    /// Restricts the run to the paths of `pathspec`: the changes and the lints of other files
+    /// are left out, even those of dirty members or without a diff.
+    pub fn set_pathspec(&mut self, pathspec: Pathspec) -> &mut Self {
+        self.pathspec = pathspec;
+        self
+    }
+
+    /// Lints the members a changed file makes relevant as soon as the VCS diffed it,
+    /// while it diffs the next files, rather than once the whole diff is computed.
+    ///
+    /// The members are linted one at a time as the diff is computed, in the order of their files,
+    /// the others with `set_jobs` once it is. The run isn't pipelined when the members
+    /// can only be ordered or selected with the whole diff: with invalidators, a target selection,
+    /// a member order, a time budget or a diff gate.
+    pub fn set_pipelined(&mut self, pipelined: bool) -> &mut Self {
+        self.pipelined = pipelined;
+        self
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn run(&self) -> Result<Outcome, crate::error::Error> {
+        self.counting_retries(|| {
+            if self.no_diff {
+                let members = self.unfiltered_members();
+                self.notify(|o| o.run_started(&members));
+                let mut outcome = self.lint_members(members, &[])?;
+                outcome.lints = self.kept(outcome.lints);
+                self.notify(|o| o.run_finished(&outcome.lints));
+                return Ok(outcome);
+            }
+            if self.pipelines() {
+                return self.run_pipelined();
+            }
+            let diff_sections = self.diff_sections()?;
+            let matched_sections = self.matched_sections(&diff_sections)?;
+            let mut outcome =
+                self.run_for_sections(&diff_sections, &matched_sections, &self.changed_files()?)?;
+            outcome.empty_diff = self.empty_diff(&diff_sections)?;
+            Ok(outcome)
+        })
+    }
+
+    /// Runs like `run`, but scouts `files` instead of the VCS diff.
+    ///
+    /// Only the given line ranges of the files are scouted, whole files without ranges.
+    /// Relative paths are relative to the current directory.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn run_for_files(
+        &self,
+        files: &[PathBuf],
+        ranges: Option<LineRanges>,
+    ) -> Result<Outcome, crate::error::Error> {
+        self.counting_retries(|| {
+            let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+            let sections = vcs::sections_for_files(files, ranges.as_ref(), &current_dir);
+            let changed_files: Vec<String> = sections.iter().map(|s| s.file_name.clone()).collect();
+            // Every line of the files is changed, whatever the strategy
+            self.run_for_sections(&sections, &sections, &changed_files)
+        })
+    }
+
+    /// Lints the members `diff_sections` or `changed_files` change,
+    /// and keeps the lints which match `matched_sections`.
+    fn run_for_sections(
+        &self,
+        diff_sections: &[Section],
+        matched_sections: &[Section],
+        changed_files: &[String],
+    ) -> Result<Outcome, crate::error::Error> {
+        let members = self.relevant_members(diff_sections, changed_files);
+        let dependent_members = self.sharded(self.dependent_members(&members));
+        let members = self.sharded(members);
+        self.notify(|o| o.run_started(&[members.clone(), dependent_members.clone()].concat()));
+        let mut outcome = self.lint_members(dependent_members, diff_sections)?;
+        let dependent_lints = self.kept(std::mem::take(&mut outcome.lints));
+        outcome.extend(self.lint_members(members, diff_sections)?);
+        let lints = self.kept(std::mem::take(&mut outcome.lints));
+        outcome.lints =
+            self.relevant_lints(lints, dependent_lints, matched_sections, changed_files);
+        outcome.notes.extend(self.notes(changed_files));
+        Ok(outcome)
+    }
+
+    /// Whether the run is pipelined, see `set_pipelined`.
+    fn pipelines(&self) -> bool {
+        if !self.pipelined {
+            return false;
+        }
+        let needs_whole_diff = !self.invalidators.files.is_empty()
+            || self.target_selection.is_some()
+            || self.member_order.is_some()
+            || self.budget.is_some()
+            || self.diff_gate != gate::DiffGate::default();
+        if needs_whole_diff {
+            info!("[Scout] - the members are ordered or selected with the whole diff, it isn't pipelined");
+        }
+        !needs_whole_diff
+    }
+
+    /// Runs like `run`, linting the members as the VCS streams the changed files.
+    ///
+    /// The observer is told the run started before the members are known.
+    fn run_pipelined(&self) -> Result<Outcome, crate::error::Error> {
+        let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+        let repo_root = self.vcs.root(&current_dir)?;
+        let root = self.config.root();
+        let linter = &self.linter;
+        let observer = self.observer.as_deref();
+        let post_processors = &self.post_processors;
+        let matrix = self.feature_matrix.as_ref();
+        // The files which aren't Rust, like `Cargo.toml`, aren't streamed
+        let changed_files = self.changed_files()?;
+        let dirty = self.dirty_members(&changed_files);
+        self.notify(|o| o.run_started(&[]));
+        let (sender, receiver) = std::sync::mpsc::channel::<(String, Invocation)>();
+        let (streamed, early) = std::thread::scope(|scope| {
+            let linting = scope.spawn(move || {
+                let mut outcome = Outcome::default();
+                for (member, invocation) in receiver {
+                    // The members linted before a signal are still reported
+                    if signal::received().is_some() {
+                        break;
+                    }
+                    outcome.extend(interruptible(lint_combinations(
+                        linter,
+                        observer,
+                        post_processors,
+                        root,
+                        &member,
+                        &invocation,
+                        matrix,
+                    ))?);
+                }
+                Ok::<_, crate::error::Error>(outcome)
+            });
+            let candidates = self.sharded(self.config.members());
+            let mut diff_sections = Vec::new();
+            let mut dispatched = BTreeSet::new();
+            let streamed = self.vcs.stream_sections(&repo_root, |mut sections| {
+                sections.retain(|section| self.pathspec.matches(&section.file_name));
+                for member in &candidates {
+                    if dispatched.contains(member)
+                        || !self.is_relevant(member, &sections, &dirty, false)
+                    {
+                        continue;
+                    }
+                    let invocation = self.invocation(member, &[]);
+                    dispatched.insert(member.clone());
+                    if sender.send((member.clone(), invocation)).is_err() {
+                        // The linting failed, its error is returned rather than this one
+                        return Err(crate::error::Error::Command(
+                            "the linting stopped".to_string(),
+                        ));
+                    }
+                }
+                diff_sections.extend(sections);
+                Ok(())
+            });
+            drop(sender);
+            let early = linting
+                .join()
+                .unwrap_or_else(|panic| Err(panicked(panic.as_ref())));
+            (streamed.map(|()| (diff_sections, dispatched)), early)
+        });
+        let mut early = early?;
+        let mut lints = self.kept(std::mem::take(&mut early.lints));
+        let (diff_sections, dispatched) = streamed?;
+        let empty_diff = self.empty_diff(&diff_sections)?;
+        lints.retain(|lint| self.pathspec.matches(&lint.location.path));
+        // Every relevant member was dispatched, unless a signal interrupted the run
+        let members = self.sharded(self.relevant_members(&diff_sections, &changed_files));
+        let remaining: Vec<String> = members
+            .iter()
+            .filter(|member| !dispatched.contains(*member))
+            .cloned()
+            .collect();
+        let mut outcome = self.lint_members(remaining, &diff_sections)?;
+        lints.extend(self.kept(std::mem::take(&mut outcome.lints)));
+        outcome.extend(early);
+        let dependent_members = self.sharded(self.dependent_members(&members));
+        let mut dependents = self.lint_members(dependent_members, &diff_sections)?;
+        let dependent_lints = self.kept(std::mem::take(&mut dependents.lints));
+        outcome.extend(dependents);
+        let matched_sections = self.matched_sections(&diff_sections)?;
+        outcome.lints =
+            self.relevant_lints(lints, dependent_lints, &matched_sections, &changed_files);
+        outcome.empty_diff = empty_diff;
+        Ok(outcome)
+    }
+
+    /// The `lints` matching `matched_sections`, or attributed to the diff otherwise,
+    /// and the `dependent_lints` the dependents configuration reports.
+    fn relevant_lints(
+        &self,
+        lints: Vec<Lint>,
+        dependent_lints: Vec<Lint>,
+        matched_sections: &[Section],
+        changed_files: &[String],
+    ) -> Vec<Lint> {
+        let mut relevant_lints = lints_matching(&lints, matched_sections, self.match_strategy);
+        let mapping = self.output_mapping(changed_files);
+        let dirty = self.dirty_members(changed_files);
+        let padding = padding::Padding::new(&self.padding, matched_sections, self.match_strategy);
+        let mut seen: HashSet<Lint> = relevant_lints.iter().cloned().collect();
+        for lint in lints {
+            if attribute(&lint, &mapping, &dirty, &padding).is_some() && seen.insert(lint.clone()) {
+                relevant_lints.push(lint);
+            }
+        }
+        for lint in dependent_lints {
+            if self.dependents.reports(&lint) && seen.insert(lint.clone()) {
+                relevant_lints.push(lint);
+            }
+        }
+
+        self.notify(|o| o.run_finished(&relevant_lints));
+        relevant_lints
+    }
+
+    /// Runs like `run`, but explains why each lint was reported or dropped.
+    ///
+    /// The lints `run` reports are the explained lints that are kept.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn explain(&self) -> Result<Outcome, crate::error::Error> {
+        self.counting_retries(|| {
+            if self.no_diff {
+                let members = self.unfiltered_members();
+                self.notify(|o| o.run_started(&members));
+                let mut outcome = self.lint_members(members, &[])?;
+                let (lints, generated) = self.split_generated(std::mem::take(&mut outcome.lints));
+                let (lints, test_code) = self.split_test_code(lints);
+                let (lints, policy) = self.split_policy(lints);
+                let explanations = self.explain_skipped(
+                    explain::explain_without_diff(&lints),
+                    generated,
+                    test_code,
+                    policy,
+                );
+                return Ok(self.explained(outcome, explanations));
+            }
+            let diff_sections = self.diff_sections()?;
+            let changed_files = self.changed_files()?;
+            let members = self.relevant_members(&diff_sections, &changed_files);
+            let dependent_members = self.sharded(self.dependent_members(&members));
+            let members = self.sharded(members);
+            self.notify(|o| o.run_started(&[members.clone(), dependent_members.clone()].concat()));
+            let mut outcome = self.lint_members(dependent_members, &diff_sections)?;
+            let (dependent_lints, dependent_generated) =
+                self.split_generated(std::mem::take(&mut outcome.lints));
+            outcome.extend(self.lint_members(members, &diff_sections)?);
+            let (lints, mut generated) = self.split_generated(std::mem::take(&mut outcome.lints));
+            generated.extend(dependent_generated);
+            let (dependent_lints, mut test_code) = self.split_test_code(dependent_lints);
+            let (lints, member_test_code) = self.split_test_code(lints);
+            test_code.extend(member_test_code);
+            let (dependent_lints, mut policy) = self.split_policy(dependent_lints);
+            let (lints, member_policy) = self.split_policy(lints);
+            policy.extend(member_policy);
+
+            let mapping = self.output_mapping(&changed_files);
+            let dirty = self.dirty_members(&changed_files);
+            let matched_sections = self.matched_sections(&diff_sections)?;
+            let padding =
+                padding::Padding::new(&self.padding, &matched_sections, self.match_strategy);
+            let mut explanations =
+                explain::explain_with(&lints, &matched_sections, self.match_strategy, |l| {
+                    attribute(l, &mapping, &dirty, &padding)
+                });
+            explanations.extend(explain::explain_dependents(&dependent_lints, |l| {
+                self.dependents.reports(l)
+            }));
+
+            let explanations = self.explain_skipped(explanations, generated, test_code, policy);
+            outcome.empty_diff = self.empty_diff(&diff_sections)?;
+            outcome.notes.extend(self.notes(&changed_files));
+            Ok(self.explained(outcome, explanations))
+        })
+    }
+
+    /// Runs `run`, its outcome counts the retries of the linter along the way,
+    /// and those of loading the dependency graphs and the target selection.
+    fn counting_retries(
+        &self,
+        run: impl FnOnce() -> Result<Outcome, crate::error::Error>,
+    ) -> Result<Outcome, crate::error::Error> {
+        let linted_before = self.linter.retries();
+        let mut outcome = run()?;
+        outcome.retries = self.linter.retries().saturating_sub(linted_before)
+            + self
+                .dependency_graph
+                .as_ref()
+                .map_or(0, DependencyGraph::retries)
+            + self
+                .order_graph
+                .as_ref()
+                .map_or(0, DependencyGraph::retries)
+            + self
+                .target_selection
+                .as_ref()
+                .map_or(0, selection::TargetSelection::retries);
+        Ok(outcome)
+    }
+
+    fn notify(&self, event: impl FnOnce(&dyn Observer)) {
+        if let Some(observer) = &self.observer {
+            event(observer.as_ref());
         }
     }
+
+    /// Ends an explained run, which reports the kept lints along with the `explanations`.
+    fn explained(&self, mut outcome: Outcome, explanations: Vec<explain::Explanation>) -> Outcome {
+        outcome.lints = explanations
+            .iter()
+            .filter(|e| e.kept())
+            .map(|e| e.lint.clone())
+            .collect();
+        outcome.explanations = explanations;
+        self.notify(|o| o.run_finished(&outcome.lints));
+        outcome
+    }
+
+    fn output_mapping(&self, changed_files: &[String]) -> build_output::OutputMapping<'_> {
+        build_output::OutputMapping::new(&self.build_outputs, changed_files, self.config.root())
+    }
+
+    /// The members depending on `members`, when a dependency graph is set.
+    fn dependent_members(&self, members: &[String]) -> Vec<String> {
+        self.dependency_graph
+            .as_ref()
+            .map_or_else(Vec::new, |graph| graph.dependents(members))
+    }
+
+    fn dirty_members(&self, changed_files: &[String]) -> dirty::DirtyMembers {
+        dirty::DirtyMembers::new(
+            &self.dirty,
+            &self.config.members(),
+            changed_files,
+            self.config.root(),
+        )
+    }
+
+    /// Adds the stages skipping lints before they are matched with the diff to `explanations`.
+    fn explain_skipped(
+        &self,
+        explanations: Vec<explain::Explanation>,
+        generated: Vec<(Lint, String)>,
+        test_code: Vec<(Lint, String)>,
+        policy: Vec<(Lint, String)>,
+    ) -> Vec<explain::Explanation> {
+        let explanations = if self.policy.is_some() {
+            explain::with_policy(explanations, policy)
+        } else {
+            explanations
+        };
+        let explanations = if self.test_code.skip {
+            explain::with_test_code(explanations, test_code)
+        } else {
+            explanations
+        };
+        if self.generated.enabled {
+            explain::with_generated(explanations, generated)
+        } else {
+            explanations
+        }
+    }
+
+    /// The lints neither of generated files, of skipped test code,
+    /// nor dropped by the profile of their owners.
+    fn kept(&self, lints: Vec<Lint>) -> Vec<Lint> {
+        let (lints, _) = self.split_generated(lints);
+        let (lints, _) = self.split_test_code(lints);
+        let (lints, _) = self.split_policy(lints);
+        lints
+    }
+
+    /// Splits the lints the profiles of the owners of their files don't report out, along with why.
+    fn split_policy(&self, lints: Vec<Lint>) -> (Vec<Lint>, Vec<(Lint, String)>) {
+        let Some(policy) = &self.policy else {
+            return (lints, Vec::new());
+        };
+        let mut kept = Vec::new();
+        let mut dropped = Vec::new();
+        for lint in lints {
+            match policy.reason(&lint) {
+                Some(reason) => dropped.push((lint, reason)),
+                None => kept.push(lint),
+            }
+        }
+        (kept, dropped)
+    }
+
+    /// Splits the lints of test code that are skipped out, along with why.
+    fn split_test_code(&self, lints: Vec<Lint>) -> (Vec<Lint>, Vec<(Lint, String)>) {
+        if !self.test_code.skip {
+            return (lints, Vec::new());
+        }
+        let mut test_code = test_code::TestCode::new(&self.test_code, self.config.root());
+        let mut kept = Vec::new();
+        let mut dropped = Vec::new();
+        for lint in lints {
+            match test_code.reason(&lint) {
+                Some(reason) => dropped.push((lint, reason)),
+                None => kept.push(lint),
+            }
+        }
+        (kept, dropped)
+    }
+
+    /// Splits the lints of generated files out, along with their marker.
+    fn split_generated(&self, lints: Vec<Lint>) -> (Vec<Lint>, Vec<(Lint, String)>) {
+        let mut files = generated::GeneratedFiles::new(&self.generated);
+        let mut kept = Vec::new();
+        let mut dropped = Vec::new();
+        for lint in lints {
+            if build_output::is_build_output(&self.build_outputs, &lint.location.path) {
+                kept.push(lint);
+                continue;
+            }
+            match files.marker(&lint.location.path) {
+                Some(marker) => dropped.push((lint, marker)),
+                None => kept.push(lint),
+            }
+        }
+        (kept, dropped)
+    }
+
+    /// Computes what `run` would do, without running the linter.
     #[allow(clippy::missing_errors_doc)]
-    pub fn run(&self) -> Result<Vec<Lint>, crate::error::Error> {
+    pub fn plan(&self) -> Result<Plan, crate::error::Error> {
+        let mut diff_sections = Vec::new();
+        let (targets, files, members) = if self.no_diff {
+            (Vec::new(), None, self.unfiltered_members())
+        } else {
+            let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+            let targets = self.vcs.targets(self.vcs.root(&current_dir)?)?;
+            diff_sections = self.diff_sections()?;
+            let files: BTreeSet<String> =
+                diff_sections.iter().map(|s| s.file_name.clone()).collect();
+            let mut members = self.relevant_members(&diff_sections, &self.changed_files()?);
+            members.extend(self.dependent_members(&members));
+            (
+                targets,
+                Some(files.into_iter().collect()),
+                self.sharded(members),
+            )
+        };
+        let commands = members
+            .iter()
+            .map(|m| self.linter.command(&self.invocation(m, &diff_sections)))
+            .collect();
+        Ok(Plan {
+            targets,
+            files,
+            members,
+            commands,
+        })
+    }
+
+    /// The sections the lints are matched with, the added lines for some strategies.
+    fn matched_sections(
+        &self,
+        diff_sections: &[Section],
+    ) -> Result<Vec<Section>, crate::error::Error> {
+        if self.match_strategy.uses_added_lines() {
+            let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+            self.vcs.added_sections(&self.vcs.root(&current_dir)?)
+        } else {
+            Ok(diff_sections.to_vec())
+        }
+    }
+
+    fn diff_sections(&self) -> Result<Vec<Section>, crate::error::Error> {
+        let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+        let repo_root = self.vcs.root(&current_dir)?;
+        let mut sections = self.vcs.sections(&repo_root)?;
+        sections.retain(|section| self.pathspec.matches(&section.file_name));
+        self.diff_gate.check(&sections)?;
+        Ok(sections)
+    }
+
+    /// The targets of the diff when `diff_sections` are empty because no file changed at all,
+    /// rather than only files which aren't Rust, see `Outcome::empty_diff`.
+    fn empty_diff(
+        &self,
+        diff_sections: &[Section],
+    ) -> Result<Option<Vec<String>>, crate::error::Error> {
+        if !diff_sections.is_empty() {
+            return Ok(None);
+        }
+        let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+        let repo_root = self.vcs.root(&current_dir)?;
+        let mut files = self.vcs.changed_files(&repo_root)?;
+        files.retain(|file| self.pathspec.matches(file));
+        if !files.is_empty() {
+            return Ok(None);
+        }
+        let targets = self.vcs.targets(&repo_root)?;
+        info!(
+            "[Scout] - {}, nothing to scout",
+            empty_diff::describe(&targets)
+        );
+        Ok(Some(targets))
+    }
+
+    /// The files the diff changes, Rust files or not, only computed when some of them
+    /// can invalidate every member, make members dirty or be inputs of build outputs.
+    fn changed_files(&self) -> Result<Vec<String>, crate::error::Error> {
+        if self.invalidators.files.is_empty()
+            && self.dirty.files.is_empty()
+            && self.build_outputs.is_empty()
+        {
+            return Ok(Vec::new());
+        }
         let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
-        let diff_sections = self.vcs.sections(&self.vcs.root(&current_dir)?)?;
-        let mut lints = Vec::new();
-        // There's no need to run the linter on members where no changes have been made
-        let relevant_members = self
+        let mut files = self.vcs.changed_files(&self.vcs.root(&current_dir)?)?;
+        files.retain(|file| self.pathspec.matches(file));
+        Ok(files)
+    }
+
+    /// The notes of a run on `changed_files` for its debug summary, like the invalidator they change.
+    fn notes(&self, changed_files: &[String]) -> Vec<(String, String)> {
+        self.invalidators
+            .trigger(changed_files, self.config.root())
+            .map(|trigger| ("invalidator".to_string(), format!("{} changed", trigger)))
+            .into_iter()
+            .collect()
+    }
+
+    // There's no need to run the linter on members where no changes have been made
+    fn relevant_members(&self, diff_sections: &[Section], changed_files: &[String]) -> Vec<String> {
+        if let Some(trigger) = self.invalidators.trigger(changed_files, self.config.root()) {
+            info!("[Scout] - {} changed, every member is relevant", trigger);
+            let mut members = self.config.members();
+            self.sort_members(&mut members, diff_sections);
+            return members;
+        }
+        let dirty = self.dirty_members(changed_files);
+        let mut members: Vec<String> = self
             .config
             .members()
             .into_iter()
-            .map(|m| {
-                self.config
-                    .root()
-                    .join(m)
-                    .to_str()
-                    .map(ToString::to_string)
-                    .unwrap()
-            })
-            .filter(|m| diff_in_member(m, &diff_sections));
-        for m in relevant_members {
-            lints.extend(
-                self.linter
-                    .lints(current_dir.clone().join("rippling-rust/").join(m))?,
+            .filter(|m| self.is_relevant(m, diff_sections, &dirty, true))
+            .collect();
+        self.sort_members(&mut members, diff_sections);
+        members
+    }
+
+    /// Whether `member` is dirty, or `diff_sections` change one of its compiled files.
+    ///
+    /// When `verbose`, a member whose changed files are all left out is logged with them.
+    fn is_relevant(
+        &self,
+        member: &str,
+        diff_sections: &[Section],
+        dirty: &dirty::DirtyMembers,
+        verbose: bool,
+    ) -> bool {
+        // The root package is the `.` member, whose path must not end with `/.`
+        let member_path: PathBuf = self.config.root().join(member).components().collect();
+        if dirty.is_dirty(member, self.config.root()) {
+            return true;
+        }
+        let nested = self.nested_members(&member_path);
+        if !diff_in_member(&member_path, &nested, diff_sections) {
+            return false;
+        }
+        let changed: BTreeSet<String> = diff_sections
+            .iter()
+            .filter(|s| diff_in_member(&member_path, &nested, std::slice::from_ref(s)))
+            .map(|s| s.file_name.clone())
+            .collect();
+        let compiled = changed
+            .iter()
+            .any(|file| self.is_compiled(member, &member_path, file));
+        if !compiled && verbose {
+            info!(
+                "[Scout] - skipping {}, none of its changed files is compiled: {}",
+                member,
+                changed
+                    .iter()
+                    .map(|file| relative_path(file, &member_path))
+                    .collect::<Vec<_>>()
+                    .join(", ")
             );
         }
-        // strip the full rippling-rust path from lints
-        let root = self.config.root();
+        compiled
+    }
+
+    /// The paths of the members nested in the directory of the member at `member_path`,
+    /// which own the files under them.
+    fn nested_members(&self, member_path: &Path) -> Vec<PathBuf> {
+        self.config
+            .members()
+            .iter()
+            .map(|member| self.config.root().join(member).components().collect())
+            .filter(|path: &PathBuf| path != member_path && path.starts_with(member_path))
+            .collect()
+    }
+
+    /// Whether the changed `file` of `member` is compiled, or is the input of a build script.
+    fn is_compiled(&self, member: &str, member_path: &Path, file: &str) -> bool {
+        self.relevance
+            .is_compiled(&relative_path(file, member_path))
+            || self
+                .build_outputs
+                .iter()
+                .any(|b| b.is_input(&relative_path(file, self.config.root())))
+            || self
+                .target_selection
+                .as_ref()
+                .is_some_and(|selection| selection.holds(member, file))
+    }
+
+    /// Orders `members` as they are linted.
+    fn sort_members(&self, members: &mut [String], diff_sections: &[Section]) {
+        let member_order = self.member_order.unwrap_or(if self.budget.is_some() {
+            order::MemberOrder::DiffSize
+        } else {
+            order::MemberOrder::Config
+        });
+        let graph = self.order_graph.as_ref().or(self.dependency_graph.as_ref());
+        member_order.sort(members, self.config.root(), diff_sections, graph);
+    }
+
+    /// The members linted without a diff.
+    fn unfiltered_members(&self) -> Vec<String> {
+        let mut members = if self.workspace {
+            self.config.members()
+        } else {
+            self.config.default_members()
+        };
+        self.sort_members(&mut members, &[]);
+        self.sharded(members)
+    }
+
+    /// The `members` linted by the shard of this scout, all of them without one.
+    ///
+    /// Dependents are sharded along with the others, whatever the shard of what they depend on.
+    fn sharded(&self, members: Vec<String>) -> Vec<String> {
+        match self.shard {
+            Some(shard) => members.into_iter().filter(|m| shard.includes(m)).collect(),
+            None => members,
+        }
+    }
 
-        let lints = lints
+    /// Lints `members`, only the targets `diff_sections` change with a target selection.
+    fn lint_members(
+        &self,
+        members: impl IntoIterator<Item = String>,
+        diff_sections: &[Section],
+    ) -> Result<Outcome, crate::error::Error> {
+        let root = self.config.root();
+        let invocations: Vec<(String, Invocation)> = members
             .into_iter()
-            .map(|mut l| {
-                l.location.path = root
-                    .clone()
-                    .join(l.location.path)
-                    .to_str()
-                    .unwrap()
-                    .to_owned();
-                l
+            .map(|m| {
+                let invocation = self.invocation(&m, diff_sections);
+                (m, invocation)
             })
-            .collect::<Vec<_>>();
+            .collect();
+        let env_target_dir = std::env::var_os("CARGO_TARGET_DIR").map(std::path::PathBuf::from);
+        let waves = schedule::waves(
+            invocations,
+            |(_, i)| schedule::target_dir(i, root, env_target_dir.as_deref()),
+            self.jobs,
+            self.jobs_per_target_dir,
+        );
+        let linter = &self.linter;
+        let observer = self.observer.as_deref();
+        let post_processors = &self.post_processors;
+        let matrix = self.feature_matrix.as_ref();
+        let mut outcome = Outcome::default();
+        let mut waves = waves.into_iter();
+        while let Some(wave) = waves.next() {
+            // The members linted before a signal are still reported
+            if signal::received().is_some() {
+                break;
+            }
+            if self.budget.is_some_and(|budget| budget.is_spent()) {
+                outcome.skipped = std::iter::once(wave)
+                    .chain(waves)
+                    .flatten()
+                    .map(|(member, _)| member)
+                    .collect();
+                for member in &outcome.skipped {
+                    self.notify(|o| o.member_skipped(member));
+                }
+                break;
+            }
+            if let [(member, invocation)] = wave.as_slice() {
+                outcome.extend(interruptible(lint_combinations(
+                    linter,
+                    observer,
+                    post_processors,
+                    root,
+                    member,
+                    invocation,
+                    matrix,
+                ))?);
+                continue;
+            }
+            let results: Vec<_> = std::thread::scope(|scope| {
+                let handles: Vec<_> = wave
+                    .iter()
+                    .map(|(member, invocation)| {
+                        scope.spawn(move || {
+                            lint_combinations(
+                                linter,
+                                observer,
+                                post_processors,
+                                root,
+                                member,
+                                invocation,
+                                matrix,
+                            )
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|panic| Err(panicked(panic.as_ref())))
+                    })
+                    .collect()
+            });
+            for result in results {
+                outcome.extend(interruptible(result)?);
+            }
+        }
+        outcome
+            .lints
+            .retain(|lint| self.pathspec.matches(&lint.location.path));
+        Ok(outcome)
+    }
 
-        Ok(lints_from_diff(&lints, &diff_sections))
+    /// Builds the linter invocation for a member,
+    /// applying its overrides if there are any.
+    fn invocation(&self, member: &str, diff_sections: &[Section]) -> Invocation {
+        let root = self.config.root();
+        let mut invocation = Invocation::new(root.join(member));
+        if let Some(member_override) =
+            crate::config::member_name(member).and_then(|name| self.member_overrides.get(name))
+        {
+            if let Some(cwd) = &member_override.cwd {
+                invocation.working_dir = root.join(cwd);
+            }
+            invocation.manifest_path = member_override
+                .manifest_path
+                .as_ref()
+                .map(|manifest_path| root.join(manifest_path));
+            invocation
+                .extra_args
+                .clone_from(&member_override.extra_args);
+            invocation.lint_args.clone_from(&member_override.lint_args);
+        }
+        if let Some(policy) = &self.policy {
+            // The lints of the member come last, so that they can allow those of the profiles
+            let mut lint_args: Vec<String> = policy
+                .warned_lints()
+                .into_iter()
+                .flat_map(|lint| ["-W".to_string(), lint])
+                .collect();
+            lint_args.append(&mut invocation.lint_args);
+            invocation.lint_args = lint_args;
+        }
+        // The targets the overrides select are kept
+        let selects_targets = invocation
+            .extra_args
+            .iter()
+            .any(|arg| selection::TARGET_FLAGS.contains(&arg.split('=').next().unwrap_or(arg)));
+        if let Some(args) = self
+            .target_selection
+            .as_ref()
+            .filter(|_| !selects_targets)
+            .and_then(|selection| selection.args(member, diff_sections))
+        {
+            invocation.extra_args.extend(args);
+        }
+        invocation
     }
 }
 
-fn diff_in_member(member: &String, sections: &[Section]) -> bool {
-    for s in sections {
-        /*
-        info!(
-            "check if diff path {} is in crate {} => {}",
-            s.file_name,
-            member,
-            s.file_name.starts_with(member)
-        );
-        */
-        if s.file_name.starts_with(member) {
-            return true;
+/// Invokes the linter on a member once per combination of the feature `matrix`,
+/// or once without one, see `lint_member`.
+///
+/// The outcome has the combinations that found the lints only some of them found.
+fn lint_combinations(
+    linter: &impl Linter,
+    observer: Option<&dyn Observer>,
+    post_processors: &PostProcessors,
+    root: &Path,
+    member: &str,
+    invocation: &Invocation,
+    matrix: Option<&FeatureMatrixConfig>,
+) -> Result<Outcome, crate::error::Error> {
+    let manifest_path = invocation
+        .manifest_path
+        .clone()
+        .unwrap_or_else(|| invocation.working_dir.join("Cargo.toml"));
+    let combinations = matrix.map_or_else(Vec::new, |m| matrix::combinations(m, &manifest_path));
+    if combinations.is_empty() {
+        return lint_member(linter, observer, post_processors, root, member, invocation);
+    }
+    if let Some(observer) = observer {
+        observer.member_started(member);
+    }
+    let mut runs = Vec::new();
+    let mut crashes = Vec::new();
+    let mut members = Vec::new();
+    for (label, args) in combinations {
+        let mut combination = invocation.clone();
+        combination.extra_args.extend(args);
+        let name = format!("{} [{}]", member, label);
+        let outcome = lint_member(linter, None, post_processors, root, &name, &combination)?;
+        crashes.extend(outcome.crashes);
+        members.extend(outcome.members);
+        runs.push((label, outcome.lints));
+    }
+    let (lints, features) = matrix::combine(runs);
+    if let Some(observer) = observer {
+        observer.member_finished(member, &lints);
+    }
+    Ok(Outcome {
+        lints,
+        features,
+        crashes,
+        members,
+        ..Outcome::default()
+    })
+}
+
+/// Invokes the linter on a member, its lints are post-processed and their paths made absolute.
+///
+/// The outcome has the crash of the linter instead of lints if it crashed.
+fn lint_member(
+    linter: &impl Linter,
+    observer: Option<&dyn Observer>,
+    post_processors: &PostProcessors,
+    root: &Path,
+    member: &str,
+    invocation: &Invocation,
+) -> Result<Outcome, crate::error::Error> {
+    if let Some(observer) = observer {
+        observer.member_started(member);
+    }
+    let started = std::time::Instant::now();
+    let mut crashes = Vec::new();
+    let mut lints = match linter.invoke(invocation) {
+        // The other members are still linted, the crash is reported with their lints
+        Err(crate::error::Error::LinterCrashed(_, message)) => {
+            info!("[Scout] - the linter crashed on {}", member);
+            crashes.push(Crash {
+                member: member.to_string(),
+                message,
+            });
+            Vec::new()
         }
+        result => result?,
+    };
+    post_processors.process(&mut lints);
+    let lints: Vec<Lint> = lints
+        .into_iter()
+        .map(|mut l| {
+            l.location.path = root.join(l.location.path).to_string_lossy().into_owned();
+            l
+        })
+        .collect();
+    let members = vec![(member.to_string(), started.elapsed(), lints.len())];
+    if let Some(observer) = observer {
+        observer.member_finished(member, &lints);
+    }
+    Ok(Outcome {
+        lints,
+        crashes,
+        members,
+        ..Outcome::default()
+    })
+}
+
+/// Why a lint outside of the diff is reported anyway, if it is.
+fn attribute(
+    lint: &Lint,
+    mapping: &build_output::OutputMapping<'_>,
+    dirty: &dirty::DirtyMembers,
+    padding: &padding::Padding<'_>,
+) -> Option<String> {
+    mapping
+        .changed_input(lint)
+        .map(|input| format!("generated from the changed `{}`", input))
+        .or_else(|| {
+            dirty
+                .trigger(&lint.location.path)
+                .map(|trigger| format!("the member is dirty, `{}` changed", trigger))
+        })
+        .or_else(|| padding.reason(lint))
+}
+
+/// The error of a linting thread which panicked with `panic`.
+fn panicked(panic: &(dyn std::any::Any + Send)) -> crate::error::Error {
+    let message = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message");
+    crate::error::Error::Command(format!("a linting thread panicked: {}", message))
+}
+
+/// Drops the error of a linter interrupted by a signal, the lints found until then are reported.
+fn interruptible<T: Default>(
+    result: Result<T, crate::error::Error>,
+) -> Result<T, crate::error::Error> {
+    match result {
+        Err(_) if signal::received().is_some() => Ok(T::default()),
+        result => result,
     }
-    false
+}
+
+/// Whether `sections` change a file of the member at `member_path`,
+/// rather than one of its `nested` members.
+fn diff_in_member(member_path: &Path, nested: &[PathBuf], sections: &[Section]) -> bool {
+    sections.iter().any(|s| {
+        is_in_dir(&s.file_name, member_path) && !nested.iter().any(|n| is_in_dir(&s.file_name, n))
+    })
 }
 
 // Check if lint and git_section have overlapped lines
@@ -99,10 +1246,21 @@ fn lines_in_range(lint: &Lint, git_section: &Section) -> bool {
 
 fn files_match(lint: &Lint, git_section: &Section) -> bool {
     // Git diff paths and clippy paths don't get along too well on Windows...
-    lint.location.path.replace("\\", "/") == git_section.file_name.replace("\\", "/")
+    normalize_path(&lint.location.path) == normalize_path(&git_section.file_name)
+}
+
+/// Keeps the lints overlapping at least one of the diff sections.
+///
+/// This is the intersection engine `Scout::run` relies on,
+/// it is public so that it can be benchmarked and driven on its own.
+#[must_use]
+pub fn lints_from_diff(lints: &[Lint], diffs: &[Section]) -> Vec<Lint> {
+    lints_matching(lints, diffs, MatchStrategy::Overlap)
 }
 
-fn lints_from_diff(lints: &[Lint], diffs: &[Section]) -> Vec<Lint> {
+/// Keeps the lints matching at least one of the diff sections, as `strategy` says.
+#[must_use]
+pub fn lints_matching(lints: &[Lint], diffs: &[Section], strategy: MatchStrategy) -> Vec<Lint> {
     let mut lints_in_diff = HashSet::new();
     for diff in diffs {
         let diff_lints = lints.iter().filter(|lint| {
@@ -117,7 +1275,7 @@ fn lints_from_diff(lints: &[Lint], diffs: &[Section]) -> Vec<Lint> {
                 diff.line_end
             );
             */
-            files_match(&lint, &diff) && lines_in_range(&lint, &diff)
+            files_match(lint, diff) && strategy.matches(lint, diff)
         });
         for l in diff_lints {
             lints_in_diff.insert(l.clone());
@@ -128,52 +1286,82 @@ fn lints_from_diff(lints: &[Lint], diffs: &[Section]) -> Vec<Lint> {
 
 #[cfg(test)]
 mod scout_tests {
-    use super::{Scout, Section, VCS};
+    use super::{LineRanges, MatchStrategy, Observer, Scout, Section, VCS};
+    use crate::config::graph::DependencyGraph;
+    use crate::config::scout::{
+        BuildOutput, DirtyConfig, FeatureMatrixConfig, InvalidatorsConfig, MemberOverride,
+        RelevanceConfig, TestCodeConfig,
+    };
     use crate::config::Config;
     use crate::error::Error;
-    use crate::linter::{Lint, Linter, Location};
+    use crate::linter::postprocess::{PathPrefixMap, PostProcessors, StripAnsi};
+    use crate::linter::{Invocation, Lint, Linter, Location};
     use crate::utils::get_absolute_file_path;
-    use std::cell::RefCell;
+    use crate::vcs::pathspec::Pathspec;
     use std::clone::Clone;
     use std::path::{Path, PathBuf};
-    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
     struct TestVCS {
         sections: Vec<Section>,
-        sections_called: RefCell<bool>,
+        sections_called: Arc<Mutex<bool>>,
+        // The changed files which aren't Rust, without sections
+        other_files: Vec<String>,
     }
     impl TestVCS {
         pub fn new(sections: Vec<Section>) -> Self {
             Self {
                 sections,
-                sections_called: RefCell::new(false),
+                sections_called: Arc::new(Mutex::new(false)),
+                other_files: Vec::new(),
+            }
+        }
+
+        pub fn with_other_files(sections: Vec<Section>, other_files: Vec<String>) -> Self {
+            Self {
+                other_files,
+                ..Self::new(sections)
             }
         }
     }
     impl VCS for TestVCS {
         fn sections<P: AsRef<Path>>(&self, _: P) -> Result<Vec<Section>, Error> {
-            *self.sections_called.borrow_mut() = true;
+            *self.sections_called.lock().unwrap() = true;
             Ok(self.sections.clone())
         }
+        fn changed_files<P: AsRef<Path>>(&self, _: P) -> Result<Vec<String>, Error> {
+            let mut files: Vec<String> =
+                self.sections.iter().map(|s| s.file_name.clone()).collect();
+            files.extend(self.other_files.iter().cloned());
+            files.sort();
+            files.dedup();
+            Ok(files)
+        }
+        fn root<P: AsRef<Path>>(&self, repo_path: P) -> Result<PathBuf, Error> {
+            Ok(repo_path.as_ref().to_path_buf())
+        }
     }
     struct TestLinter {
-        // Using a RefCell here because lints
+        // Using a Mutex here because lints
         // takes &self and not &mut self.
         // We use usize here because we will compare it to a Vec::len()
-        lints_times_called: Rc<RefCell<usize>>,
+        lints_times_called: Arc<Mutex<usize>>,
+        invocations: Arc<Mutex<Vec<Invocation>>>,
         lints: Vec<Lint>,
+        // The member the linter crashes on
+        crashes_on: Option<String>,
     }
     impl TestLinter {
         pub fn new() -> Self {
-            Self {
-                lints_times_called: Rc::new(RefCell::new(0)),
-                lints: Vec::new(),
-            }
+            Self::with_lints(Vec::new())
         }
 
         pub fn with_lints(lints: Vec<Lint>) -> Self {
             Self {
-                lints_times_called: Rc::new(RefCell::new(0)),
+                lints_times_called: Arc::new(Mutex::new(0)),
+                invocations: Arc::new(Mutex::new(Vec::new())),
                 lints,
+                crashes_on: None,
             }
         }
     }
@@ -182,22 +1370,56 @@ mod scout_tests {
             &self,
             _working_dir: impl Into<PathBuf>,
         ) -> Result<Vec<Lint>, crate::error::Error> {
-            *self.lints_times_called.borrow_mut() += 1;
+            *self.lints_times_called.lock().unwrap() += 1;
             Ok(self.lints.clone())
         }
+
+        fn invoke(&self, invocation: &Invocation) -> Result<Vec<Lint>, crate::error::Error> {
+            self.invocations.lock().unwrap().push(invocation.clone());
+            if let Some(member) = &self.crashes_on {
+                if invocation.working_dir.ends_with(member) {
+                    return Err(crate::error::Error::LinterCrashed(
+                        member.clone(),
+                        "error: internal compiler error: boom".to_string(),
+                    ));
+                }
+            }
+            self.lints(invocation.working_dir.clone())
+        }
+
+        // Every call is retried once
+        fn retries(&self) -> u32 {
+            std::convert::TryFrom::try_from(*self.lints_times_called.lock().unwrap()).unwrap()
+        }
     }
     struct TestConfig {
+        root: PathBuf,
         members: Vec<String>,
+        default_members: Vec<String>,
     }
     impl TestConfig {
         pub fn new(members: Vec<String>) -> Self {
-            Self { members }
+            Self::with_default_members(members.clone(), members)
+        }
+
+        pub fn with_default_members(members: Vec<String>, default_members: Vec<String>) -> Self {
+            Self {
+                root: std::env::current_dir().unwrap(),
+                members,
+                default_members,
+            }
         }
     }
     impl Config for TestConfig {
         fn members(&self) -> Vec<String> {
             self.members.clone()
         }
+        fn default_members(&self) -> Vec<String> {
+            self.default_members.clone()
+        }
+        fn root(&self) -> &PathBuf {
+            &self.root
+        }
     }
 
     #[test]
@@ -207,13 +1429,52 @@ mod scout_tests {
         // No members so we won't have to iterate
         let config = TestConfig::new(Vec::new());
         let expected_times_called = 0;
-        let actual_times_called = Rc::clone(&linter.lints_times_called);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
         let scout = Scout::new(vcs, config, linter);
         // We don't check for the lints result here.
         // It is already tested in the linter tests
         // and in intersection tests
         let _ = scout.run()?;
-        assert_eq!(expected_times_called, *actual_times_called.borrow());
+        assert_eq!(expected_times_called, *actual_times_called.lock().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_empty_diff() -> Result<(), crate::error::Error> {
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let scout = Scout::new(TestVCS::new(Vec::new()), config, TestLinter::new());
+        let outcome = scout.run()?;
+        assert!(outcome.empty_diff.is_some());
+        assert_eq!(super::empty_diff::Status::NoChanges, outcome.status());
+
+        // Only files which aren't Rust changed
+        let vcs = TestVCS::with_other_files(Vec::new(), vec!["README.md".to_string()]);
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let outcome = Scout::new(vcs, config, TestLinter::new()).run()?;
+        assert_eq!(None, outcome.empty_diff);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_diff_gate() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("foo/bar.rs")?,
+            line_start: 1,
+            line_end: 10,
+        }];
+        let linter = TestLinter::new();
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["foo".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        scout.set_diff_gate(super::gate::DiffGate {
+            max_lines: Some(9),
+            max_files: None,
+        });
+        assert!(matches!(
+            scout.run(),
+            Err(crate::error::Error::DiffTooLarge(_))
+        ));
+        assert_eq!(0, *actual_times_called.lock().unwrap());
         Ok(())
     }
 
@@ -230,15 +1491,21 @@ mod scout_tests {
                 location: Location {
                     lines: [2, 2],
                     path: get_absolute_file_path("foo/bar.rs")?,
+                    ..Location::default()
                 },
                 message: "Test lint".to_string(),
+                code: None,
+                suggestions: Vec::new(),
             },
             Lint {
                 location: Location {
                     lines: [12, 22],
                     path: get_absolute_file_path("foo/bar.rs")?,
+                    ..Location::default()
                 },
                 message: "This lint is not in diff".to_string(),
+                code: None,
+                suggestions: Vec::new(),
             },
         ];
 
@@ -246,8 +1513,11 @@ mod scout_tests {
             location: Location {
                 lines: [2, 2],
                 path: get_absolute_file_path("foo/bar.rs")?,
+                ..Location::default()
             },
             message: "Test lint".to_string(),
+            code: None,
+            suggestions: Vec::new(),
         }];
 
         let linter = TestLinter::with_lints(lints);
@@ -255,17 +1525,36 @@ mod scout_tests {
         // The member matches the file name
         let config = TestConfig::new(vec!["foo".to_string()]);
         let expected_times_called = 1;
-        let actual_times_called = Rc::clone(&linter.lints_times_called);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
         let scout = Scout::new(vcs, config, linter);
         // We don't check for the lints result here.
         // It is already tested in the linter tests
         // and in intersection tests
-        let actual_lints_from_diff = scout.run()?;
-        assert_eq!(expected_times_called, *actual_times_called.borrow());
+        let actual_lints_from_diff = scout.run()?.lints;
+        assert_eq!(expected_times_called, *actual_times_called.lock().unwrap());
         assert_eq!(expected_lints_from_diff, actual_lints_from_diff);
         Ok(())
     }
 
+    #[test]
+    fn test_scout_root_package() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("src/lib.rs")?,
+            line_start: 0,
+            line_end: 10,
+        }];
+        let linter = TestLinter::new();
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let scout = Scout::new(
+            TestVCS::new(diff),
+            TestConfig::new(vec![".".to_string()]),
+            linter,
+        );
+        scout.run()?;
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+        Ok(())
+    }
+
     #[test]
     fn test_scout_no_workspace_one_diff_not_relevant_member() -> Result<(), crate::error::Error> {
         let diff = vec![Section {
@@ -278,13 +1567,13 @@ mod scout_tests {
         // The member does not match the file name
         let config = TestConfig::new(vec!["foo".to_string()]);
         let expected_times_called = 0;
-        let actual_times_called = Rc::clone(&linter.lints_times_called);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
         let scout = Scout::new(vcs, config, linter);
         // We don't check for the lints result here.
         // It is already tested in the linter tests
         // and in intersection tests
         let _ = scout.run()?;
-        assert_eq!(expected_times_called, *actual_times_called.borrow());
+        assert_eq!(expected_times_called, *actual_times_called.lock().unwrap());
         Ok(())
     }
 
@@ -312,14 +1601,1113 @@ mod scout_tests {
         ]);
         // We should run the linter on member1 and member2
         let expected_times_called = 2;
-        let actual_times_called = Rc::clone(&linter.lints_times_called);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
         let scout = Scout::new(vcs, config, linter);
         // We don't check for the lints result here.
         // It is already tested in the linter tests
         // and in intersection tests
         let _ = scout.run()?;
 
-        assert_eq!(expected_times_called, *actual_times_called.borrow());
+        assert_eq!(expected_times_called, *actual_times_called.lock().unwrap());
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct TestObserver {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+    impl Observer for TestObserver {
+        fn run_started(&self, members: &[String]) {
+            let event = format!("run-start {}", members.join(","));
+            self.events.lock().unwrap().push(event);
+        }
+        fn member_started(&self, member: &str) {
+            let event = format!("member-start {}", member);
+            self.events.lock().unwrap().push(event);
+        }
+        fn member_finished(&self, member: &str, lints: &[Lint]) {
+            let event = format!("member-end {} {}", member, lints.len());
+            self.events.lock().unwrap().push(event);
+        }
+        fn member_skipped(&self, member: &str) {
+            let event = format!("member-skipped {}", member);
+            self.events.lock().unwrap().push(event);
+        }
+        fn run_finished(&self, lints: &[Lint]) {
+            let event = format!("summary {}", lints.len());
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[test]
+    fn test_scout_observer() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/bar.rs")?,
+            line_start: 0,
+            line_end: 10,
+        }];
+        let linter = TestLinter::with_lints(vec![Lint {
+            message: "warning".to_string(),
+            code: None,
+            location: Location {
+                path: "member1/bar.rs".to_string(),
+                lines: [2, 2],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }]);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let observer = TestObserver::default();
+        let events = Arc::clone(&observer.events);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        scout.set_observer(Some(Box::new(observer)));
+        assert_eq!(1, scout.run()?.lints.len());
+        assert_eq!(
+            vec![
+                "run-start member1",
+                "member-start member1",
+                "member-end member1 1",
+                "summary 1"
+            ],
+            *events.lock().unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_budget() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 1,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+        ];
+        let scout = |budget| -> Result<(Vec<String>, Vec<String>), crate::error::Error> {
+            let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+            let observer = TestObserver::default();
+            let events = Arc::clone(&observer.events);
+            let mut scout = Scout::new(TestVCS::new(diff.clone()), config, TestLinter::new());
+            scout.set_observer(Some(Box::new(observer)));
+            scout.set_budget(budget);
+            let skipped = scout.run()?.skipped;
+            let events = events.lock().unwrap().clone();
+            Ok((events, skipped))
+        };
+        assert_eq!(
+            vec![
+                "run-start member2,member1",
+                "member-start member2",
+                "member-end member2 0",
+                "member-start member1",
+                "member-end member1 0",
+                "summary 0"
+            ],
+            scout(Some(Duration::from_secs(3600)))?.0
+        );
+        assert_eq!(
+            vec![
+                "run-start member2,member1",
+                "member-skipped member2",
+                "member-skipped member1",
+                "summary 0"
+            ],
+            scout(Some(Duration::from_secs(0)))?.0
+        );
+        assert_eq!(
+            vec!["member2", "member1"],
+            scout(Some(Duration::from_secs(0)))?.1
+        );
+        assert!(scout(Some(Duration::from_secs(3600)))?.1.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_linter_crash() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("crashing1/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+        ];
+        let mut linter = TestLinter::with_lints(vec![Lint {
+            message: "warning".to_string(),
+            code: None,
+            location: Location {
+                path: "member2/bar.rs".to_string(),
+                lines: [2, 2],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }]);
+        linter.crashes_on = Some("crashing1".to_string());
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["crashing1".to_string(), "member2".to_string()]);
+        let scout = Scout::new(TestVCS::new(diff), config, linter);
+        // The member after the crash is still linted
+        let outcome = scout.run()?;
+        assert_eq!(1, outcome.lints.len());
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+        assert_eq!(1, outcome.crashes.len());
+        assert_eq!("crashing1", outcome.crashes[0].member);
+        assert_eq!(
+            "error: internal compiler error: boom",
+            outcome.crashes[0].message
+        );
+        assert!(!outcome.is_complete());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_post_processors() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/bar.rs")?,
+            line_start: 0,
+            line_end: 10,
+        }];
+        // Built in /build, the member is found on the host
+        let linter = TestLinter::with_lints(vec![Lint {
+            message: "\u{1b}[33mwarning\u{1b}[0m".to_string(),
+            code: None,
+            location: Location {
+                path: "/build/member1/bar.rs".to_string(),
+                lines: [2, 2],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }]);
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let mut post_processors = PostProcessors::default();
+        post_processors
+            .push(StripAnsi)
+            .push(PathPrefixMap::new(vec![(
+                PathBuf::from("/build"),
+                std::env::current_dir()?,
+            )]));
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        scout.set_post_processors(post_processors);
+        let lints = scout.run()?.lints;
+        assert_eq!(1, lints.len());
+        assert_eq!("warning", lints[0].message);
+        assert_eq!(
+            get_absolute_file_path("member1/bar.rs")?,
+            lints[0].location.path
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_pipelined() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+        ];
+        let lint = |path: &str, line| Lint {
+            message: "warning".to_string(),
+            code: None,
+            location: Location {
+                path: path.to_string(),
+                lines: [line, line],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+        let lints = vec![
+            lint("member1/bar.rs", 2),
+            lint("member2/bar.rs", 3),
+            lint("member2/bar.rs", 30),
+        ];
+        let members = vec![
+            "member1".to_string(),
+            "member2".to_string(),
+            "member3".to_string(),
+        ];
+        // The lints matching the diff come in no particular order
+        let sorted = |mut lints: Vec<Lint>| {
+            lints.sort_by(|a, b| a.location.path.cmp(&b.location.path));
+            lints
+        };
+        let sequential = Scout::new(
+            TestVCS::new(diff.clone()),
+            TestConfig::new(members.clone()),
+            TestLinter::with_lints(lints.clone()),
+        )
+        .run()?
+        .lints;
+
+        let linter = TestLinter::with_lints(lints.clone());
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let mut scout = Scout::new(
+            TestVCS::new(diff.clone()),
+            TestConfig::new(members.clone()),
+            linter,
+        );
+        scout.set_pipelined(true);
+        assert_eq!(sorted(sequential), sorted(scout.run()?.lints));
+        // The member without changes isn't linted
+        assert_eq!(2, *actual_times_called.lock().unwrap());
+
+        // With a diff gate the run isn't pipelined, nothing is linted before the whole diff is checked
+        scout.set_diff_gate(super::gate::DiffGate {
+            max_lines: None,
+            max_files: Some(1),
+        });
+        assert!(matches!(
+            scout.run(),
+            Err(crate::error::Error::DiffTooLarge(_))
+        ));
+        assert_eq!(2, *actual_times_called.lock().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_panicked() {
+        let panic = std::thread::spawn(|| panic!("boom {}", 1))
+            .join()
+            .unwrap_err();
+        assert!(matches!(
+            super::panicked(panic.as_ref()),
+            crate::error::Error::Command(message) if message == "a linting thread panicked: boom 1"
+        ));
+    }
+
+    #[test]
+    fn test_scout_pathspec() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+        ];
+        let lint = |path: &str| Lint {
+            message: "warning".to_string(),
+            code: None,
+            location: Location {
+                path: path.to_string(),
+                lines: [2, 2],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+        let linter = TestLinter::with_lints(vec![lint("member1/bar.rs"), lint("member2/bar.rs")]);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        scout.set_pathspec(Pathspec::new(
+            std::env::current_dir()?,
+            vec!["member1/**".to_string()],
+        ));
+        let lints = scout.run()?.lints;
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+        assert_eq!(
+            vec![get_absolute_file_path("member1/bar.rs")?],
+            lints
+                .into_iter()
+                .map(|lint| lint.location.path)
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_member_order() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 1,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 1,
+            },
+        ];
+        let config = TestConfig::new(vec!["member2".to_string(), "member1".to_string()]);
+        let observer = TestObserver::default();
+        let events = Arc::clone(&observer.events);
+        let mut scout = Scout::new(TestVCS::new(diff), config, TestLinter::new());
+        scout.set_observer(Some(Box::new(observer)));
+        scout.set_member_order(Some(super::order::MemberOrder::Alpha));
+        scout.run()?;
+        assert_eq!("run-start member1,member2", events.lock().unwrap()[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_shard() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 1,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 1,
+            },
+        ];
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, TestLinter::new());
+        let mut linted = Vec::new();
+        for shard in ["1/2", "2/2"] {
+            scout.set_shard(Some(shard.parse().unwrap()));
+            linted.extend(scout.plan()?.members);
+        }
+        // Both members are linted, by one shard each
+        linted.sort();
+        assert_eq!(vec!["member1", "member2"], linted);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_feature_matrix() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/bar.rs")?,
+            line_start: 1,
+            line_end: 3,
+        }];
+        let lint = Lint {
+            location: Location {
+                lines: [2, 2],
+                path: get_absolute_file_path("member1/bar.rs")?,
+                ..Location::default()
+            },
+            message: "Found by every combination".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        let linter = TestLinter::with_lints(vec![lint.clone()]);
+        let invocations = linter.invocations.clone();
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        scout.set_feature_matrix(Some(FeatureMatrixConfig {
+            sets: vec![Vec::new(), vec!["serde".to_string(), "async".to_string()]],
+            no_default_features: true,
+        }));
+        // The lint is reported once, without the combinations that found it
+        let outcome = scout.run()?;
+        assert_eq!(vec![lint.clone()], outcome.lints);
+        assert!(outcome.features.is_empty());
+        let extra_args: Vec<Vec<String>> = invocations
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|i| i.extra_args.clone())
+            .collect();
+        assert_eq!(
+            vec![
+                vec!["--no-default-features".to_string()],
+                vec![
+                    "--no-default-features".to_string(),
+                    "--features".to_string(),
+                    "serde,async".to_string()
+                ],
+            ],
+            extra_args
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_target_selection() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 1,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 1,
+            },
+        ];
+        let root = std::env::current_dir()?;
+        // The root of a target is one of its files, even if it can't be read
+        let metadata = format!(
+            r#"{{"packages": [
+                {{
+                    "manifest_path": "{0}/member1/Cargo.toml",
+                    "targets": [{{"name": "member1", "kind": ["lib"], "src_path": "{0}/member1/bar.rs"}}]
+                }},
+                {{
+                    "manifest_path": "{0}/member2/Cargo.toml",
+                    "targets": [{{"name": "member2", "kind": ["bin"], "src_path": "{0}/member2/main.rs"}}]
+                }}
+            ]}}"#,
+            root.display()
+        );
+        let members = vec!["member1".to_string(), "member2".to_string()];
+        let selection =
+            super::selection::TargetSelection::from_metadata(&metadata, &root, &members)?;
+        let linter = TestLinter::new();
+        let invocations = Arc::clone(&linter.invocations);
+        let mut scout = Scout::new(TestVCS::new(diff), TestConfig::new(members), linter);
+        scout.set_target_selection(Some(selection));
+        scout.run()?;
+        let invocations = invocations.lock().unwrap();
+        assert_eq!(vec!["--lib".to_string()], invocations[0].extra_args);
+        // member2/bar.rs isn't a file of its binary, the whole member is linted
+        assert!(invocations[1].extra_args.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_match_strategy() -> Result<(), crate::error::Error> {
+        let scout = |strategy| -> Result<usize, crate::error::Error> {
+            let diff = vec![Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            }];
+            let linter = TestLinter::with_lints(vec![Lint {
+                message: "warning".to_string(),
+                code: None,
+                location: Location {
+                    path: "member1/bar.rs".to_string(),
+                    lines: [8, 12],
+                    ..Location::default()
+                },
+                suggestions: Vec::new(),
+            }]);
+            let config = TestConfig::new(vec!["member1".to_string()]);
+            let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+            scout.set_match_strategy(strategy);
+            Ok(scout.run()?.lints.len())
+        };
+        assert_eq!(1, scout(MatchStrategy::Overlap)?);
+        assert_eq!(0, scout(MatchStrategy::Containment)?);
+        assert_eq!(1, scout(MatchStrategy::ChangedLinesOnly)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_test_code() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/tests/it.rs")?,
+            line_start: 0,
+            line_end: 10,
+        }];
+        let linter = TestLinter::with_lints(vec![Lint {
+            message: "warning".to_string(),
+            code: Some("clippy::unwrap_used".to_string()),
+            location: Location {
+                path: "member1/tests/it.rs".to_string(),
+                lines: [2, 2],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }]);
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        assert_eq!(1, scout.run()?.lints.len());
+
+        scout.set_test_code(TestCodeConfig {
+            skip: true,
+            ..TestCodeConfig::default()
+        });
+        assert!(scout.run()?.lints.is_empty());
+        let explanations = scout.explain()?.explanations;
+        assert_eq!(
+            Some(&super::explain::Step {
+                stage: super::explain::Stage::TestCode,
+                kept: false,
+                reason: "the lint is skipped in test code, `member1/tests/it.rs` is a test file"
+                    .to_string(),
+            }),
+            explanations[0].steps.last()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_policy() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/lib.rs")?,
+            line_start: 0,
+            line_end: 10,
+        }];
+        let lint = |code: &str| Lint {
+            message: "warning".to_string(),
+            code: Some(code.to_string()),
+            location: Location {
+                path: "member1/lib.rs".to_string(),
+                lines: [2, 2],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+        let linter =
+            TestLinter::with_lints(vec![lint("clippy::needless_borrow"), lint("clippy::todo")]);
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        let mut profiles = std::collections::BTreeMap::new();
+        profiles.insert(
+            "@org/legacy".to_string(),
+            super::policy::Profile {
+                name: "lenient".to_string(),
+                preset: crate::config::preset::Preset {
+                    warn: vec!["clippy::todo".to_string()],
+                    allow: Vec::new(),
+                },
+            },
+        );
+        let owners = crate::config::owners::CodeOwners::parse(
+            "/member1/ @org/legacy\n",
+            std::env::current_dir()?,
+        );
+        scout.set_policy(Some(super::policy::Policy::new(owners, profiles)));
+
+        let lints = scout.run()?.lints;
+        assert_eq!(1, lints.len());
+        assert_eq!(Some("clippy::todo".to_string()), lints[0].code);
+        let explanations = scout.explain()?.explanations;
+        assert_eq!(2, explanations.len());
+        assert_eq!(
+            Some(&super::explain::Step {
+                stage: super::explain::Stage::Policy,
+                kept: false,
+                reason: "the file is owned by @org/legacy, whose `lenient` profile doesn't report the lint"
+                    .to_string(),
+            }),
+            explanations[1].steps.last()
+        );
+        assert_eq!(
+            vec!["-W", "clippy::todo"],
+            scout.invocation("member1", &[]).lint_args
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_plan() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+            Section {
+                file_name: get_absolute_file_path("member1/bar.rs")?,
+                line_start: 20,
+                line_end: 30,
+            },
+        ];
+        let linter = TestLinter::new();
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+
+        let plan = scout.plan()?;
+        assert_eq!(
+            Some(vec![get_absolute_file_path("member1/bar.rs")?]),
+            plan.files
+        );
+        assert_eq!(vec!["member1".to_string()], plan.members);
+        assert_eq!(vec![None], plan.commands);
+
+        scout.set_no_diff(true);
+        let plan = scout.plan()?;
+        assert_eq!(None, plan.files);
+        assert_eq!(2, plan.members.len());
+
+        // The linter never ran
+        assert_eq!(0, *actual_times_called.lock().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_explain() -> Result<(), crate::error::Error> {
+        let in_diff = Lint {
+            location: Location {
+                lines: [5, 6],
+                path: get_absolute_file_path("member1/bar.rs")?,
+                ..Location::default()
+            },
+            message: "This lint is in a diff".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        let not_in_diff = Lint {
+            location: Location {
+                lines: [15, 16],
+                path: get_absolute_file_path("member1/bar.rs")?,
+                ..Location::default()
+            },
+            message: "This lint is not in a diff".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/bar.rs")?,
+            line_start: 0,
+            line_end: 10,
+        }];
+        let linter = TestLinter::with_lints(vec![in_diff.clone(), not_in_diff.clone()]);
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+
+        let explanations = scout.explain()?.explanations;
+        assert_eq!(2, explanations.len());
+        assert!(explanations[0].kept());
+        assert_eq!(in_diff, explanations[0].lint);
+        assert!(!explanations[1].kept());
+        assert_eq!(not_in_diff, explanations[1].lint);
+        assert_eq!(vec![in_diff], scout.run()?.lints);
+
+        scout.set_no_diff(true);
+        assert!(scout
+            .explain()?
+            .explanations
+            .iter()
+            .all(super::explain::Explanation::kept));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_build_outputs() -> Result<(), crate::error::Error> {
+        let generated = Lint {
+            location: Location {
+                lines: [120, 120],
+                path: "/repo/target/debug/build/member1-0a1b/out/api.rs".to_string(),
+                ..Location::default()
+            },
+            message: "This lint is in a generated file".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/src/lib.rs")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        // The proto file has no section, only Rust files do
+        let vcs = TestVCS::with_other_files(
+            diff,
+            vec![get_absolute_file_path("member1/proto/api.proto")?],
+        );
+        let linter = TestLinter::with_lints(vec![generated.clone()]);
+        let config = TestConfig::new(vec!["member1".to_string()]);
+        let mut scout = Scout::new(vcs, config, linter);
+        assert!(scout.run()?.lints.is_empty());
+
+        scout.set_build_outputs(vec![BuildOutput {
+            inputs: vec!["member1/proto/*.proto".to_string()],
+            outputs: vec!["*.rs".to_string()],
+        }]);
+        assert_eq!(vec![generated], scout.run()?.lints);
+        assert!(scout.explain()?.explanations[0].kept());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_dirty_member() -> Result<(), crate::error::Error> {
+        let lint = Lint {
+            location: Location {
+                lines: [12, 22],
+                path: get_absolute_file_path("member1/src/lib.rs")?,
+                ..Location::default()
+            },
+            message: "This lint is not in a diff".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/build.rs")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        let linter = TestLinter::with_lints(vec![lint.clone()]);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+
+        // The dirty member is linted, its lints are still filtered
+        assert!(scout.run()?.lints.is_empty());
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+
+        scout.set_dirty(DirtyConfig {
+            report_all: true,
+            ..DirtyConfig::default()
+        });
+        assert_eq!(vec![lint], scout.run()?.lints);
+        assert_eq!(
+            "the member is dirty, `member1/build.rs` changed",
+            scout.explain()?.explanations[0].steps[1].reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_dirty_member_without_rust_changes() -> Result<(), crate::error::Error> {
+        let lint = Lint {
+            location: Location {
+                lines: [12, 22],
+                path: get_absolute_file_path("member1/src/lib.rs")?,
+                ..Location::default()
+            },
+            message: "This lint is not in a diff".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        // Only the manifest changed, there are no Rust sections
+        let vcs = TestVCS::with_other_files(
+            Vec::new(),
+            vec![get_absolute_file_path("member1/Cargo.toml")?],
+        );
+        let linter = TestLinter::with_lints(vec![lint.clone()]);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let mut scout = Scout::new(vcs, config, linter);
+        scout.set_dirty(DirtyConfig {
+            report_all: true,
+            ..DirtyConfig::default()
+        });
+        assert_eq!(vec![lint], scout.run()?.lints);
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+        assert_eq!(
+            "the member is dirty, `member1/Cargo.toml` changed",
+            scout.explain()?.explanations[0].steps[1].reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_invalidators() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("clippy.toml")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        let linter = TestLinter::new();
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+
+        // The lint configuration changed, every member may have new lints
+        let outcome = scout.run()?;
+        assert_eq!(2, *actual_times_called.lock().unwrap());
+        assert_eq!(
+            vec![("invalidator".to_string(), "clippy.toml changed".to_string())],
+            outcome.notes
+        );
+        let linted: Vec<&str> = outcome.members.iter().map(|m| m.0.as_str()).collect();
+        assert_eq!(vec!["member1", "member2"], linted);
+
+        scout.set_invalidators(InvalidatorsConfig { files: Vec::new() });
+        let outcome = scout.run()?;
+        assert_eq!(2, *actual_times_called.lock().unwrap());
+        assert!(outcome.notes.is_empty());
+        assert!(outcome.members.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_retries() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("clippy.toml")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let scout = Scout::new(TestVCS::new(diff), config, TestLinter::new());
+
+        // Each run only counts its own retries
+        assert_eq!(2, scout.run()?.retries);
+        assert_eq!(2, scout.run()?.retries);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_sibling_members() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("crates/foo-bar/src/lib.rs")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        let linter = TestLinter::new();
+        let invocations = Arc::clone(&linter.invocations);
+        let config = TestConfig::new(vec!["crates/foo".to_string(), "crates/foo-bar".to_string()]);
+        Scout::new(TestVCS::new(diff), config, linter).run()?;
+
+        // `crates/foo` is only a prefix of the changed file
+        let invocations = invocations.lock().unwrap();
+        assert_eq!(1, invocations.len());
+        assert!(invocations[0].working_dir.ends_with("crates/foo-bar"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_nested_members() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("crates/foo/sub/src/lib.rs")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        let linter = TestLinter::new();
+        let invocations = Arc::clone(&linter.invocations);
+        let config = TestConfig::new(vec!["crates/foo".to_string(), "crates/foo/sub".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+        scout.run()?;
+
+        // The file is in the directory of `crates/foo`, but the nested member owns it
+        assert_eq!(1, invocations.lock().unwrap().len());
+        assert!(invocations.lock().unwrap()[0]
+            .working_dir
+            .ends_with("crates/foo/sub"));
+
+        scout.vcs = TestVCS::new(vec![Section {
+            file_name: get_absolute_file_path("crates/foo/src/lib.rs")?,
+            line_start: 1,
+            line_end: 2,
+        }]);
+        scout.run()?;
+        assert_eq!(2, invocations.lock().unwrap().len());
+        assert!(invocations.lock().unwrap()[1]
+            .working_dir
+            .ends_with("crates/foo"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_relevance() -> Result<(), crate::error::Error> {
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/tests/fixtures/input.rs")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        let linter = TestLinter::new();
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let mut scout = Scout::new(TestVCS::new(diff), config, linter);
+
+        // A fixtures directory may hold modules, every Rust file is relevant by default
+        scout.run()?;
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+
+        // The excluded fixture isn't compiled, the member isn't worth linting
+        scout.set_relevance(RelevanceConfig {
+            exclude: vec!["**/fixtures/**".to_string()],
+            ..RelevanceConfig::default()
+        });
+        assert!(scout.run()?.lints.is_empty());
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_with_dependents() -> Result<(), crate::error::Error> {
+        let deprecated = Lint {
+            location: Location {
+                lines: [12, 22],
+                path: get_absolute_file_path("member2/src/lib.rs")?,
+                ..Location::default()
+            },
+            message: "use of deprecated function".to_string(),
+            code: Some("deprecated".to_string()),
+            suggestions: Vec::new(),
+        };
+        let other = Lint {
+            code: Some("clippy::needless_borrow".to_string()),
+            ..deprecated.clone()
+        };
+        let diff = vec![Section {
+            file_name: get_absolute_file_path("member1/src/lib.rs")?,
+            line_start: 1,
+            line_end: 2,
+        }];
+        let root = std::env::current_dir()?;
+        let metadata = format!(
+            r#"{{"packages": [
+                {{"manifest_path": "{0}/member1/Cargo.toml", "dependencies": []}},
+                {{"manifest_path": "{0}/member2/Cargo.toml", "dependencies": [{{"path": "{0}/member1"}}]}}
+            ]}}"#,
+            root.to_string_lossy().replace('\\', "/")
+        );
+        let members = vec!["member1".to_string(), "member2".to_string()];
+        let graph = DependencyGraph::from_metadata(&metadata, &root, &members)?;
+        let linter = TestLinter::with_lints(vec![deprecated.clone(), other]);
+        let invocations = Arc::clone(&linter.invocations);
+        let mut scout = Scout::new(TestVCS::new(diff), TestConfig::new(members), linter);
+        scout.set_dependency_graph(Some(graph));
+
+        // Only the deprecation lints of the dependent are reported
+        assert_eq!(vec![deprecated], scout.run()?.lints);
+        assert_eq!(2, invocations.lock().unwrap().len());
+        let explanations = scout.explain()?.explanations;
+        assert_eq!(
+            vec![false, false, true, false],
+            explanations
+                .iter()
+                .map(super::explain::Explanation::kept)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, scout.plan()?.members.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_parallel_members() -> Result<(), crate::error::Error> {
+        let linter = TestLinter::new();
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let invocations = Arc::clone(&linter.invocations);
+        let members = vec![
+            "member1".to_string(),
+            "member2".to_string(),
+            "member3".to_string(),
+        ];
+        let mut scout = Scout::new(TestVCS::new(Vec::new()), TestConfig::new(members), linter);
+        scout
+            .set_no_diff(true)
+            .set_jobs(2)
+            .set_jobs_per_target_dir(2);
+
+        assert!(scout.run()?.lints.is_empty());
+        assert_eq!(3, *actual_times_called.lock().unwrap());
+        assert_eq!(3, invocations.lock().unwrap().len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_no_diff_default_members() -> Result<(), crate::error::Error> {
+        let lint = Lint {
+            location: Location {
+                lines: [12, 22],
+                path: get_absolute_file_path("member1/bar.rs")?,
+                ..Location::default()
+            },
+            message: "This lint is not in a diff".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        let linter = TestLinter::with_lints(vec![lint.clone()]);
+        let vcs = TestVCS::new(Vec::new());
+        let sections_called = Arc::clone(&vcs.sections_called);
+        let config = TestConfig::with_default_members(
+            vec!["member1".to_string(), "member2".to_string()],
+            vec!["member1".to_string()],
+        );
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let mut scout = Scout::new(vcs, config, linter);
+        scout.set_no_diff(true);
+
+        // Only the default member is linted, and its lints are all reported
+        assert_eq!(vec![lint], scout.run()?.lints);
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+        assert!(!*sections_called.lock().unwrap());
+
+        // --workspace lints every member
+        scout.set_workspace(true);
+        let _ = scout.run()?;
+        assert_eq!(3, *actual_times_called.lock().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_run_for_files() -> Result<(), crate::error::Error> {
+        let lint = Lint {
+            location: Location {
+                lines: [12, 22],
+                path: get_absolute_file_path("member1/bar.rs")?,
+                ..Location::default()
+            },
+            message: "This lint is in a changed buffer".to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        };
+        let linter = TestLinter::with_lints(vec![lint.clone()]);
+        let actual_times_called = Arc::clone(&linter.lints_times_called);
+        let vcs = TestVCS::new(Vec::new());
+        let sections_called = Arc::clone(&vcs.sections_called);
+        let config = TestConfig::new(vec!["member1".to_string(), "member2".to_string()]);
+        let scout = Scout::new(vcs, config, linter);
+        let files = vec![PathBuf::from("member1/bar.rs")];
+
+        // Only the member of the file is linted, and the whole file is scouted
+        assert_eq!(vec![lint], scout.run_for_files(&files, None)?.lints);
+        assert_eq!(1, *actual_times_called.lock().unwrap());
+
+        let mut ranges = LineRanges::new();
+        ranges.insert(PathBuf::from("member1/bar.rs"), vec![[1, 5]]);
+        assert!(scout.run_for_files(&files, Some(ranges))?.lints.is_empty());
+        assert!(!*sections_called.lock().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scout_member_overrides() -> Result<(), crate::error::Error> {
+        let diff = vec![
+            Section {
+                file_name: get_absolute_file_path("crates/member1/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+            Section {
+                file_name: get_absolute_file_path("member2/bar.rs")?,
+                line_start: 0,
+                line_end: 10,
+            },
+        ];
+        let linter = TestLinter::new();
+        let vcs = TestVCS::new(diff);
+        let config = TestConfig::new(vec!["crates/member1".to_string(), "member2".to_string()]);
+        let root = config.root().clone();
+        let invocations = Arc::clone(&linter.invocations);
+        let mut scout = Scout::new(vcs, config, linter);
+        scout.set_member_overrides(
+            vec![(
+                "member1".to_string(),
+                MemberOverride {
+                    manifest_path: Some(PathBuf::from("special/Cargo.toml")),
+                    cwd: Some(PathBuf::from("special")),
+                    extra_args: vec!["--locked".to_string()],
+                    lint_args: vec!["-A".to_string(), "clippy::pedantic".to_string()],
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let _ = scout.run()?;
+
+        assert_eq!(
+            vec![
+                Invocation {
+                    working_dir: root.join("special"),
+                    manifest_path: Some(root.join("special/Cargo.toml")),
+                    extra_args: vec!["--locked".to_string()],
+                    lint_args: vec!["-A".to_string(), "clippy::pedantic".to_string()],
+                },
+                Invocation::new(root.join("member2")),
+            ],
+            *invocations.lock().unwrap()
+        );
         Ok(())
     }
 }
@@ -387,10 +2775,13 @@ mod intersections_tests {
             let git_section = range.1;
             let lint = Lint {
                 message: String::new(),
+                code: None,
                 location: Location {
                     path: String::from(lint_section.0),
                     lines: [lint_section.1, lint_section.2],
+                    ..Location::default()
                 },
+                suggestions: Vec::new(),
             };
             let git = Section {
                 file_name: String::from(git_section.0),
@@ -399,10 +2790,9 @@ mod intersections_tests {
             };
             assert!(
                 files_match(&lint, &git),
-                print!(
-                    "Expected files match for {} and {}",
-                    lint_section.0, git_section.0
-                )
+                "Expected files match for {} and {}",
+                lint_section.0,
+                git_section.0
             );
         }
     }
@@ -414,10 +2804,13 @@ mod intersections_tests {
             let git_section = range.1;
             let lint = Lint {
                 message: String::new(),
+                code: None,
                 location: Location {
                     path: String::from(lint_section.0),
                     lines: [lint_section.1, lint_section.2],
+                    ..Location::default()
                 },
+                suggestions: Vec::new(),
             };
             let git = Section {
                 file_name: String::from(git_section.0),
@@ -426,10 +2819,9 @@ mod intersections_tests {
             };
             assert!(
                 !files_match(&lint, &git),
-                print!(
-                    "Expected files not to match for {} and {}",
-                    lint_section.0, git_section.0
-                )
+                "Expected files not to match for {} and {}",
+                lint_section.0,
+                git_section.0
             );
         }
     }
@@ -440,10 +2832,9 @@ mod intersections_tests {
             let section = range.1;
             assert!(
                 in_range(lint, section),
-                print!(
-                    "Expected in range, found not in range for \n {:#?} and {:#?}",
-                    lint, section
-                )
+                "Expected in range, found not in range for \n {:#?} and {:#?}",
+                lint,
+                section
             );
         }
     }
@@ -454,10 +2845,9 @@ mod intersections_tests {
             let section = range.1;
             assert!(
                 !in_range(lint, section),
-                print!(
-                    "Expected not in range, found in range for \n {:#?} and {:#?}",
-                    lint, section
-                )
+                "Expected not in range, found in range for \n {:#?} and {:#?}",
+                lint,
+                section
             );
         }
     }
@@ -466,10 +2856,13 @@ mod intersections_tests {
         use crate::scout::lines_in_range;
         let lint = Lint {
             message: String::new(),
+            code: None,
             location: Location {
                 path: String::from(lint_section.0),
                 lines: [lint_section.1, lint_section.2],
+                ..Location::default()
             },
+            suggestions: Vec::new(),
         };
 
         let git_section = Section {
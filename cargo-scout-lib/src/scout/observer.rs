@@ -0,0 +1,22 @@
+use crate::linter::Lint;
+
+/// Follows the progress of a run, as it happens.
+///
+/// The members are linted in parallel with `--jobs`, so the methods are called
+/// from the linting threads. They do nothing by default.
+pub trait Observer: Send + Sync {
+    /// The run is about to lint `members`, their dependents included.
+    fn run_started(&self, _members: &[String]) {}
+
+    /// The linter is invoked on `member`.
+    fn member_started(&self, _member: &str) {}
+
+    /// The linter is done with `member`, and found `lints` before any filtering.
+    fn member_finished(&self, _member: &str, _lints: &[Lint]) {}
+
+    /// `member` is skipped, as the time budget ran out.
+    fn member_skipped(&self, _member: &str) {}
+
+    /// The run is over and reports `lints`.
+    fn run_finished(&self, _lints: &[Lint]) {}
+}
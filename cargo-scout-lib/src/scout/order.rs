@@ -0,0 +1,128 @@
+use crate::config::graph::DependencyGraph;
+use crate::vcs::Section;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The order members are linted in, the most likely to fail first for fail-fast runs.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum MemberOrder {
+    /// The order of the workspace members, in the manifest
+    #[default]
+    Config,
+    /// The members changing the most lines first
+    DiffSize,
+    /// By name
+    Alpha,
+    /// The members the most other members depend on first, directly or not
+    Deps,
+}
+
+impl FromStr for MemberOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "config" => Ok(Self::Config),
+            "diff-size" => Ok(Self::DiffSize),
+            "alpha" => Ok(Self::Alpha),
+            "deps" => Ok(Self::Deps),
+            _ => Err(format!(
+                "unknown order {}, expected config, diff-size, alpha or deps",
+                s
+            )),
+        }
+    }
+}
+
+impl MemberOrder {
+    /// Orders `members` of the workspace at `root`, changed by `sections`.
+    ///
+    /// The `deps` order ranks members with `graph`, and keeps them unchanged without one.
+    /// Members ranking the same keep their order.
+    pub fn sort(
+        self,
+        members: &mut [String],
+        root: &Path,
+        sections: &[Section],
+        graph: Option<&DependencyGraph>,
+    ) {
+        match self {
+            Self::Config => {}
+            Self::DiffSize => super::budget::by_changed_lines(members, root, sections),
+            Self::Alpha => members.sort(),
+            Self::Deps => {
+                if let Some(graph) = graph {
+                    members.sort_by_cached_key(|member| {
+                        std::cmp::Reverse(graph.dependents(std::slice::from_ref(member)).len())
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemberOrder;
+    use crate::config::graph::DependencyGraph;
+    use crate::vcs::Section;
+    use std::path::Path;
+
+    fn members() -> Vec<String> {
+        vec!["cli".to_string(), "app".to_string(), "core".to_string()]
+    }
+
+    fn sorted(order: MemberOrder, graph: Option<&DependencyGraph>) -> Vec<String> {
+        let sections = vec![
+            Section {
+                file_name: "/repo/app/src/lib.rs".to_string(),
+                line_start: 1,
+                line_end: 10,
+            },
+            Section {
+                file_name: "/repo/core/src/lib.rs".to_string(),
+                line_start: 1,
+                line_end: 1,
+            },
+        ];
+        let mut members = members();
+        order.sort(&mut members, Path::new("/repo"), &sections, graph);
+        members
+    }
+
+    #[test]
+    fn test_sort() -> Result<(), crate::error::Error> {
+        let metadata = r#"{
+            "packages": [
+                {
+                    "manifest_path": "/repo/cli/Cargo.toml",
+                    "dependencies": [{"path": "/repo/app"}]
+                },
+                {
+                    "manifest_path": "/repo/app/Cargo.toml",
+                    "dependencies": [{"path": "/repo/core"}]
+                },
+                {"manifest_path": "/repo/core/Cargo.toml", "dependencies": []}
+            ]
+        }"#;
+        let graph = DependencyGraph::from_metadata(metadata, Path::new("/repo"), &members())?;
+        assert_eq!(members(), sorted(MemberOrder::Config, None));
+        assert_eq!(
+            vec!["app", "core", "cli"],
+            sorted(MemberOrder::DiffSize, None)
+        );
+        assert_eq!(vec!["app", "cli", "core"], sorted(MemberOrder::Alpha, None));
+        assert_eq!(
+            vec!["core", "app", "cli"],
+            sorted(MemberOrder::Deps, Some(&graph))
+        );
+        assert_eq!(members(), sorted(MemberOrder::Deps, None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(MemberOrder::DiffSize), "diff-size".parse());
+        assert!("size".parse::<MemberOrder>().is_err());
+    }
+}
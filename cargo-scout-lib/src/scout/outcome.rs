@@ -0,0 +1,109 @@
+use crate::linter::crash::Crash;
+use crate::linter::Lint;
+use crate::scout::empty_diff::Status;
+use crate::scout::explain::Explanation;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// What a run of `Scout` reports: its lints, and how complete the run was.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Outcome {
+    /// The lints reported
+    pub lints: Vec<Lint>,
+    /// Why each lint was reported or dropped, only computed by `Scout::explain`
+    pub explanations: Vec<Explanation>,
+    /// The members skipped because the time budget ran out, in the order they would have been linted
+    pub skipped: Vec<String>,
+    /// The combinations of `--feature-matrix` that found each lint only some of them found
+    pub features: HashMap<Lint, Vec<String>>,
+    /// The crashes of the linter, whose members' lints are missing, in the order they happened
+    pub crashes: Vec<Crash>,
+    /// The targets of the diff, as `VCS::targets` describes them, when it changed nothing
+    pub empty_diff: Option<Vec<String>>,
+    /// What the run involved for its debug summary, by topic, like the invalidator that changed
+    pub notes: Vec<(String, String)>,
+    /// The members linted, with how long it took and how many lints the linter reported for them
+    pub members: Vec<(String, Duration, usize)>,
+    /// How many times the flaky commands of the run were retried
+    pub retries: u32,
+}
+
+impl Outcome {
+    /// Adds what `other` reports to this outcome, like the run of another workspace.
+    pub fn extend(&mut self, other: Self) {
+        self.lints.extend(other.lints);
+        self.explanations.extend(other.explanations);
+        self.skipped.extend(other.skipped);
+        self.features.extend(other.features);
+        self.crashes.extend(other.crashes);
+        self.empty_diff = self.empty_diff.take().or(other.empty_diff);
+        self.notes.extend(other.notes);
+        self.members.extend(other.members);
+        self.retries += other.retries;
+    }
+
+    /// Whether every member was linted, so that the results can be reused.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty() && self.crashes.is_empty()
+    }
+
+    /// Whether the diff had changes to scout.
+    #[must_use]
+    pub fn status(&self) -> Status {
+        if self.empty_diff.is_some() {
+            Status::NoChanges
+        } else {
+            Status::Compared
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Outcome;
+    use crate::linter::crash::Crash;
+    use crate::linter::Lint;
+    use crate::scout::empty_diff::Status;
+    use std::time::Duration;
+
+    #[test]
+    fn test_extend() {
+        let mut outcome = Outcome {
+            lints: vec![Lint::default()],
+            ..Outcome::default()
+        };
+        assert!(outcome.is_complete());
+        assert_eq!(Status::Compared, outcome.status());
+        outcome.extend(Outcome {
+            empty_diff: Some(vec!["main (1a2b)".to_string()]),
+            ..Outcome::default()
+        });
+        assert_eq!(Status::NoChanges, outcome.status());
+        outcome.extend(Outcome {
+            crashes: vec![Crash {
+                member: "crashing".to_string(),
+                message: "error: internal compiler error".to_string(),
+            }],
+            ..Outcome::default()
+        });
+        assert!(!outcome.is_complete());
+        outcome.crashes.clear();
+        outcome.extend(Outcome {
+            lints: vec![Lint::default()],
+            skipped: vec!["member".to_string()],
+            features: std::iter::once((Lint::default(), vec!["serde".to_string()])).collect(),
+            notes: vec![("invalidator".to_string(), "Cargo.lock changed".to_string())],
+            members: vec![("member".to_string(), Duration::from_secs(1), 1)],
+            retries: 2,
+            ..Outcome::default()
+        });
+        assert_eq!(2, outcome.lints.len());
+        assert_eq!(1, outcome.features.len());
+        assert_eq!(vec!["member"], outcome.skipped);
+        assert_eq!(1, outcome.notes.len());
+        assert_eq!(1, outcome.members.len());
+        assert_eq!(2, outcome.retries);
+        assert!(!outcome.is_complete());
+    }
+}
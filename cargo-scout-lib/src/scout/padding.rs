@@ -0,0 +1,122 @@
+use super::files_match;
+use super::items::SyntaxMap;
+use super::matching::MatchStrategy;
+use crate::config::scout::PaddingRule;
+use crate::linter::Lint;
+use crate::vcs::Section;
+use std::cell::RefCell;
+
+/// Matches the lints the padding rules apply to with the diff, once their lines are widened.
+pub struct Padding<'a> {
+    rules: &'a [PaddingRule],
+    sections: &'a [Section],
+    strategy: MatchStrategy,
+    syntax: RefCell<SyntaxMap>,
+}
+
+impl<'a> Padding<'a> {
+    #[must_use]
+    pub fn new(rules: &'a [PaddingRule], sections: &'a [Section], strategy: MatchStrategy) -> Self {
+        Self {
+            rules,
+            sections,
+            strategy,
+            syntax: RefCell::new(SyntaxMap::new()),
+        }
+    }
+
+    /// Returns why `lint` matches the diff once padded, if it does.
+    #[must_use]
+    pub fn reason(&self, lint: &Lint) -> Option<String> {
+        let padded = self.padded(lint)?;
+        self.sections
+            .iter()
+            .any(|section| files_match(&padded, section) && self.strategy.matches(&padded, section))
+            .then(|| {
+                format!(
+                    "padded to the lines {}-{}, which match the diff",
+                    padded.location.lines[0], padded.location.lines[1]
+                )
+            })
+    }
+
+    /// The lint with its lines widened by the first rule applying to it.
+    fn padded(&self, lint: &Lint) -> Option<Lint> {
+        let rule = self.rules.iter().find(|rule| rule.applies(lint))?;
+        let [mut start, mut end] = lint.location.lines;
+        if rule.enclosing_item {
+            if let Some(item) = self
+                .syntax
+                .borrow_mut()
+                .enclosing(&lint.location.path, start)
+            {
+                start = start.min(item.lines[0]);
+                end = end.max(item.lines[1]);
+            }
+        }
+        let mut padded = lint.clone();
+        padded.location.lines = [
+            start.saturating_sub(rule.before).max(1),
+            end.saturating_add(rule.after),
+        ];
+        Some(padded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Padding;
+    use crate::config::scout::PaddingRule;
+    use crate::linter::{Lint, Location};
+    use crate::scout::matching::MatchStrategy;
+    use crate::vcs::Section;
+
+    fn lint(path: &str, code: &str, lines: [u32; 2]) -> Lint {
+        Lint {
+            message: String::new(),
+            code: Some(code.to_string()),
+            location: Location {
+                path: path.to_string(),
+                lines,
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_reason() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, "pub fn foo() -> u32 {\n    let a = 1;\n    a\n}\n").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let sections = vec![Section {
+            file_name: path.clone(),
+            line_start: 3,
+            line_end: 3,
+        }];
+        let rules = vec![
+            PaddingRule {
+                lints: vec!["missing_docs".to_string()],
+                enclosing_item: true,
+                ..PaddingRule::default()
+            },
+            PaddingRule {
+                lints: vec!["clippy::*".to_string()],
+                after: 1,
+                ..PaddingRule::default()
+            },
+        ];
+        let padding = Padding::new(&rules, &sections, MatchStrategy::Overlap);
+        assert_eq!(
+            Some("padded to the lines 1-4, which match the diff".to_string()),
+            padding.reason(&lint(&path, "missing_docs", [1, 1]))
+        );
+        assert_eq!(
+            Some("padded to the lines 2-3, which match the diff".to_string()),
+            padding.reason(&lint(&path, "clippy::let_and_return", [2, 2]))
+        );
+        assert_eq!(None, padding.reason(&lint(&path, "clippy::foo", [1, 1])));
+        assert_eq!(None, padding.reason(&lint(&path, "dead_code", [1, 1])));
+    }
+}
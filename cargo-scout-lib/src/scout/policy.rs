@@ -0,0 +1,202 @@
+use crate::config::owners::CodeOwners;
+use crate::config::preset::Preset;
+use crate::config::scout::ScoutConfig;
+use crate::error::Error;
+use crate::linter::Lint;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The policy profile of a team, a preset by name.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub preset: Preset,
+}
+
+/// Which lints are reported in each file, by the profile of the team owning it.
+///
+/// A file is governed by the profile of its first owner having one,
+/// the lints of the files of no such team are all reported, but those only the profiles enable.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Policy {
+    owners: CodeOwners,
+    /// The profile of each team
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Policy {
+    #[must_use]
+    pub fn new(owners: CodeOwners, profiles: BTreeMap<String, Profile>) -> Self {
+        Self { owners, profiles }
+    }
+
+    /// The policy of the `[owners]` of `config`, if it maps teams to profiles.
+    ///
+    /// # Errors
+    /// When a profile isn't a known preset, or when there is no `CODEOWNERS` file.
+    pub fn load(config: &ScoutConfig, workspace_root: &Path) -> Result<Option<Self>, Error> {
+        if config.owners.profiles.is_empty() {
+            return Ok(None);
+        }
+        let profiles = config
+            .owners
+            .profiles
+            .iter()
+            .map(|(team, name)| {
+                let preset = config
+                    .preset(name)
+                    .ok_or_else(|| Error::UnknownPreset(name.clone()))?;
+                let profile = Profile {
+                    name: name.clone(),
+                    preset,
+                };
+                Ok((team.clone(), profile))
+            })
+            .collect::<Result<_, Error>>()?;
+        let owners = CodeOwners::discover(config.owners.file.as_deref(), workspace_root)?
+            .ok_or_else(|| {
+                Error::Owners(format!(
+                    "no CODEOWNERS file to resolve the profiles with, in {} or its parents",
+                    workspace_root.display()
+                ))
+            })?;
+        Ok(Some(Self::new(owners, profiles)))
+    }
+
+    /// The lints the profiles warn about, which the linter must enable on top of its own.
+    #[must_use]
+    pub fn warned_lints(&self) -> Vec<String> {
+        let mut lints: Vec<String> = self
+            .profiles
+            .values()
+            .flat_map(|profile| profile.preset.warn.iter().cloned())
+            .collect();
+        lints.sort();
+        lints.dedup();
+        lints
+    }
+
+    /// The team owning the file at `path` and its profile, if it has one.
+    #[must_use]
+    pub fn profile(&self, path: &str) -> Option<(&str, &Profile)> {
+        self.owners.owners(path).iter().find_map(|owner| {
+            self.profiles
+                .get(owner)
+                .map(|profile| (owner.as_str(), profile))
+        })
+    }
+
+    /// Returns why `lint` is dropped by the profile of its file, if it is.
+    ///
+    /// Clippy warns about the lints of every profile in every member, see `warned_lints`:
+    /// the files without a profile don't report them.
+    #[must_use]
+    pub fn reason(&self, lint: &Lint) -> Option<String> {
+        let Some((team, profile)) = self.profile(&lint.location.path) else {
+            let enabling = self
+                .profiles
+                .values()
+                .find(|profile| profile.preset.reports(lint))?;
+            return Some(format!(
+                "the file has no profile, and the lint is only enabled for the `{}` profile",
+                enabling.name
+            ));
+        };
+        if profile.preset.reports(lint) {
+            return None;
+        }
+        Some(format!(
+            "the file is owned by {}, whose `{}` profile doesn't report the lint",
+            team, profile.name
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Policy, Profile};
+    use crate::config::owners::CodeOwners;
+    use crate::config::preset::Preset;
+    use crate::linter::{Lint, Location};
+    use std::collections::BTreeMap;
+
+    fn lint(path: &str, code: &str) -> Lint {
+        Lint {
+            message: String::new(),
+            code: Some(code.to_string()),
+            location: Location {
+                path: path.to_string(),
+                lines: [1, 1],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn policy() -> Policy {
+        let profile = |name: &str, warn: &[&str]| Profile {
+            name: name.to_string(),
+            preset: Preset {
+                warn: warn.iter().map(ToString::to_string).collect(),
+                allow: Vec::new(),
+            },
+        };
+        let mut profiles = BTreeMap::new();
+        profiles.insert(
+            "@org/team-a".to_string(),
+            profile("strict", &["clippy::unwrap_used", "missing_docs"]),
+        );
+        profiles.insert(
+            "@org/team-legacy".to_string(),
+            profile("lenient", &["clippy::unwrap_used", "clippy::todo"]),
+        );
+        Policy::new(
+            CodeOwners::parse(
+                "/a/ @org/team-a\n/legacy/ @bob @org/team-legacy\n/shared/ @org/other\n",
+                "/repo",
+            ),
+            profiles,
+        )
+    }
+
+    #[test]
+    fn test_reason() {
+        let policy = policy();
+        assert_eq!(None, policy.reason(&lint("/repo/a/lib.rs", "missing_docs")));
+        assert_eq!(
+            Some(
+                "the file is owned by @org/team-legacy, whose `lenient` profile doesn't report the lint"
+                    .to_string()
+            ),
+            policy.reason(&lint("/repo/legacy/lib.rs", "clippy::needless_borrow"))
+        );
+        assert_eq!(
+            None,
+            policy.reason(&lint("/repo/legacy/lib.rs", "clippy::unwrap_used"))
+        );
+        // Files of teams without a profile keep every lint, but those the profiles enable
+        assert_eq!(
+            None,
+            policy.reason(&lint("/repo/shared/lib.rs", "dead_code"))
+        );
+        assert_eq!(
+            None,
+            policy.reason(&lint("/repo/other/lib.rs", "dead_code"))
+        );
+        assert_eq!(
+            Some(
+                "the file has no profile, and the lint is only enabled for the `lenient` profile"
+                    .to_string()
+            ),
+            policy.reason(&lint("/repo/other/lib.rs", "clippy::todo"))
+        );
+    }
+
+    #[test]
+    fn test_warned_lints() {
+        assert_eq!(
+            vec!["clippy::todo", "clippy::unwrap_used", "missing_docs"],
+            policy().warned_lints()
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use crate::linter::Invocation;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Returns the target directory cargo builds an invocation in.
+///
+/// `--target-dir` in the extra arguments wins over `env_target_dir`, the value of `CARGO_TARGET_DIR`.
+/// Otherwise members build in the `target` directory of the workspace at `root`,
+/// and manifests outside of the workspace in the `target` directory next to them.
+#[must_use]
+pub fn target_dir(invocation: &Invocation, root: &Path, env_target_dir: Option<&Path>) -> PathBuf {
+    let from_args = invocation
+        .extra_args
+        .iter()
+        .position(|arg| arg == "--target-dir")
+        .and_then(|index| invocation.extra_args.get(index + 1))
+        .map(PathBuf::from)
+        .or_else(|| {
+            invocation
+                .extra_args
+                .iter()
+                .find_map(|arg| arg.strip_prefix("--target-dir="))
+                .map(PathBuf::from)
+        });
+    if let Some(dir) = from_args.as_deref().or(env_target_dir) {
+        return invocation.working_dir.join(dir);
+    }
+    match invocation.manifest_path.as_ref().and_then(|m| m.parent()) {
+        Some(manifest_dir) if !manifest_dir.starts_with(root) => manifest_dir.join("target"),
+        _ => root.join("target"),
+    }
+}
+
+/// Splits `items` in waves run one after the other.
+///
+/// A wave holds at most `jobs` items, and at most `jobs_per_key` items sharing a key,
+/// so that concurrent cargo processes don't thrash the lock of a shared target directory.
+/// Items keep their order within the waves.
+#[must_use]
+pub fn waves<T, K: std::hash::Hash + Eq>(
+    items: Vec<T>,
+    key: impl Fn(&T) -> K,
+    jobs: usize,
+    jobs_per_key: usize,
+) -> Vec<Vec<T>> {
+    let jobs = jobs.max(1);
+    let jobs_per_key = jobs_per_key.max(1);
+    let mut waves = Vec::new();
+    let mut remaining = items;
+    while !remaining.is_empty() {
+        let mut wave = Vec::new();
+        let mut next = Vec::new();
+        let mut per_key: HashMap<K, usize> = HashMap::new();
+        for item in remaining {
+            let count = per_key.entry(key(&item)).or_default();
+            if wave.len() < jobs && *count < jobs_per_key {
+                *count += 1;
+                wave.push(item);
+            } else {
+                next.push(item);
+            }
+        }
+        waves.push(wave);
+        remaining = next;
+    }
+    waves
+}
+
+/// How the `make` running scout bounds its parallelism.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum JobLimit {
+    /// A jobserver is shared with the cargo processes, which bound themselves
+    Jobserver,
+    /// At most this many jobs run at once
+    Jobs(usize),
+}
+
+/// Reads the job limit of `CARGO_MAKEFLAGS` or `MAKEFLAGS`,
+/// from a `--jobserver-auth`/`--jobserver-fds` or the last `-j N`.
+#[must_use]
+pub fn makeflags_limit(makeflags: &str) -> Option<JobLimit> {
+    let flags: Vec<&str> = makeflags.split_whitespace().collect();
+    if flags
+        .iter()
+        .any(|f| f.starts_with("--jobserver-auth=") || f.starts_with("--jobserver-fds="))
+    {
+        return Some(JobLimit::Jobserver);
+    }
+    flags
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, flag)| {
+            let value = flag
+                .strip_prefix("--jobs=")
+                .or_else(|| flag.strip_prefix("-j"))?;
+            if value.is_empty() {
+                flags.get(index + 1).and_then(|next| next.parse().ok())
+            } else {
+                value.parse().ok()
+            }
+        })
+        .filter(|jobs| *jobs > 0)
+        .map(JobLimit::Jobs)
+}
+
+/// Splits a total of `jobs` between `member_jobs` concurrent cargo processes.
+///
+/// Returns how many members are linted at once, and the `-j` of each cargo process.
+#[must_use]
+pub fn split_jobs(jobs: usize, member_jobs: usize) -> (usize, usize) {
+    let jobs = jobs.max(1);
+    let member_jobs = member_jobs.clamp(1, jobs);
+    (member_jobs, jobs / member_jobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{makeflags_limit, split_jobs, target_dir, waves, JobLimit};
+    use crate::linter::Invocation;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_target_dir() {
+        let root = Path::new("/repo");
+        let member = Invocation::new("/repo/foo");
+        assert_eq!(
+            PathBuf::from("/repo/target"),
+            target_dir(&member, root, None)
+        );
+        assert_eq!(
+            PathBuf::from("/tmp/target"),
+            target_dir(&member, root, Some(Path::new("/tmp/target")))
+        );
+
+        let outside = Invocation {
+            manifest_path: Some(PathBuf::from("/elsewhere/bar/Cargo.toml")),
+            ..Invocation::new("/elsewhere/bar")
+        };
+        assert_eq!(
+            PathBuf::from("/elsewhere/bar/target"),
+            target_dir(&outside, root, None)
+        );
+
+        let with_args = Invocation {
+            extra_args: vec!["--target-dir".to_string(), "build".to_string()],
+            ..Invocation::new("/repo/foo")
+        };
+        assert_eq!(
+            PathBuf::from("/repo/foo/build"),
+            target_dir(&with_args, root, Some(Path::new("/tmp/target")))
+        );
+        let with_args = Invocation {
+            extra_args: vec!["--target-dir=/cache".to_string()],
+            ..Invocation::new("/repo/foo")
+        };
+        assert_eq!(PathBuf::from("/cache"), target_dir(&with_args, root, None));
+    }
+
+    #[test]
+    fn test_waves() {
+        let items = vec![("a", 1), ("b", 1), ("c", 2), ("d", 3), ("e", 1)];
+        assert_eq!(
+            vec![
+                vec![("a", 1), ("c", 2), ("d", 3)],
+                vec![("b", 1)],
+                vec![("e", 1)]
+            ],
+            waves(items.clone(), |item| item.1, 4, 1)
+        );
+        assert_eq!(
+            vec![
+                vec![("a", 1), ("b", 1)],
+                vec![("c", 2), ("d", 3)],
+                vec![("e", 1)]
+            ],
+            waves(items.clone(), |item| item.1, 2, 2)
+        );
+        // A single job runs everything sequentially
+        assert_eq!(5, waves(items, |item| item.1, 1, 4).len());
+    }
+
+    #[test]
+    fn test_makeflags_limit() {
+        assert_eq!(Some(JobLimit::Jobs(4)), makeflags_limit("-j4"));
+        assert_eq!(Some(JobLimit::Jobs(8)), makeflags_limit("k -j 8"));
+        assert_eq!(Some(JobLimit::Jobs(2)), makeflags_limit("--jobs=2"));
+        assert_eq!(
+            Some(JobLimit::Jobserver),
+            makeflags_limit(" -j4 --jobserver-auth=3,4")
+        );
+        assert_eq!(
+            Some(JobLimit::Jobserver),
+            makeflags_limit("--jobserver-fds=3,4 -j")
+        );
+        // An unbounded `-j` doesn't limit anything
+        assert_eq!(None, makeflags_limit("-j"));
+        assert_eq!(None, makeflags_limit("-j0"));
+        assert_eq!(None, makeflags_limit(""));
+    }
+
+    #[test]
+    fn test_split_jobs() {
+        assert_eq!((1, 8), split_jobs(8, 1));
+        assert_eq!((2, 4), split_jobs(8, 2));
+        assert_eq!((3, 2), split_jobs(8, 3));
+        // Never more members than jobs
+        assert_eq!((2, 1), split_jobs(2, 4));
+        assert_eq!((1, 1), split_jobs(0, 0));
+    }
+}
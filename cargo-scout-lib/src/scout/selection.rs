@@ -0,0 +1,412 @@
+use crate::config::graph::{self, member_dir};
+use crate::error::Error;
+use crate::retry::RetryPolicy;
+use crate::utils::normalize_path;
+use crate::vcs::Section;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    manifest_path: PathBuf,
+    targets: Vec<Target>,
+}
+
+/// The cargo flags selecting targets, a member whose arguments have one lints those.
+pub const TARGET_FLAGS: &[&str] = &[
+    "--lib",
+    "--bin",
+    "--bins",
+    "--test",
+    "--tests",
+    "--example",
+    "--examples",
+    "--bench",
+    "--benches",
+    "--all-targets",
+];
+
+/// A cargo target of a member, as `cargo metadata` describes it.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+pub struct Target {
+    pub name: String,
+    pub kind: Vec<String>,
+    pub src_path: PathBuf,
+}
+
+impl Target {
+    /// The cargo arguments selecting the target, `None` for build scripts, which can't be.
+    #[must_use]
+    pub fn args(&self) -> Option<Vec<String>> {
+        let flag = match self.kind.first().map(String::as_str) {
+            Some("custom-build") => return None,
+            Some("bin") => "--bin",
+            Some("test") => "--test",
+            Some("example") => "--example",
+            Some("bench") => "--bench",
+            // lib, rlib, proc-macro and the other library kinds
+            _ => return Some(vec!["--lib".to_string()]),
+        };
+        Some(vec![flag.to_string(), self.name.clone()])
+    }
+}
+
+/// The targets of a member, with the files each of them compiles.
+#[derive(Debug, PartialEq, Clone)]
+struct MemberTargets {
+    dir: String,
+    targets: Vec<(Target, BTreeSet<String>)>,
+}
+
+/// Which targets of the members hold which files, to only lint the targets a diff changes
+/// rather than every target of a member, like its heavy integration tests.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct TargetSelection {
+    members: BTreeMap<String, MemberTargets>,
+    /// How many times `cargo metadata` was retried to find the targets
+    retries: u32,
+}
+
+impl TargetSelection {
+    /// The targets of `members`, relative to `root`, from the output of
+    /// `cargo metadata --format-version 1 --no-deps`.
+    ///
+    /// The files of each target are found by following the `mod` declarations from its root.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_metadata(metadata: &str, root: &Path, members: &[String]) -> Result<Self, Error> {
+        let metadata: Metadata = serde_json::from_str(metadata)?;
+        let member_dirs: BTreeMap<String, &String> = members
+            .iter()
+            .map(|m| (member_dir(&root.join(m)), m))
+            .collect();
+        let mut selection = BTreeMap::new();
+        for package in metadata.packages {
+            let Some(dir) = package.manifest_path.parent().map(member_dir) else {
+                continue;
+            };
+            let Some(member) = member_dirs.get(&dir) else {
+                continue;
+            };
+            let targets = package
+                .targets
+                .into_iter()
+                .map(|target| {
+                    let files = module_files(&target.src_path);
+                    (target, files)
+                })
+                .collect();
+            selection.insert((*member).clone(), MemberTargets { dir, targets });
+        }
+        Ok(Self {
+            members: selection,
+            retries: 0,
+        })
+    }
+
+    /// Runs `cargo metadata` on the manifest at `manifest_path` to find the targets.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn load(
+        manifest_path: impl AsRef<Path>,
+        root: &Path,
+        members: &[String],
+        retry: &RetryPolicy,
+    ) -> Result<Self, Error> {
+        let (metadata, retries) = graph::metadata(manifest_path.as_ref(), retry)?;
+        Ok(Self {
+            retries,
+            ..Self::from_metadata(&metadata, root, members)?
+        })
+    }
+
+    /// How many times `cargo metadata` was retried to find the targets.
+    #[must_use]
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Whether a target of `member` compiles the file at `path`.
+    #[must_use]
+    pub fn holds(&self, member: &str, path: &str) -> bool {
+        let path = normalize_path(path);
+        self.members.get(member).is_some_and(|member| {
+            member
+                .targets
+                .iter()
+                .any(|(_, files)| files.contains(&path))
+        })
+    }
+
+    /// The cargo arguments selecting the targets of `member` holding the files `sections` change.
+    ///
+    /// Returns `None` when the whole member must be linted: it changes no file, or a file no
+    /// target is known to hold, like one a macro includes, or the build script.
+    #[must_use]
+    pub fn args(&self, member: &str, sections: &[Section]) -> Option<Vec<String>> {
+        let member = self.members.get(member)?;
+        let changed: BTreeSet<String> = sections
+            .iter()
+            .map(|section| normalize_path(&section.file_name))
+            .filter(|file| file.starts_with(&format!("{}/", member.dir)))
+            .collect();
+        if changed.is_empty() {
+            return None;
+        }
+        let mut selected: Vec<Vec<String>> = Vec::new();
+        for file in &changed {
+            let mut holding = member
+                .targets
+                .iter()
+                .filter(|(_, files)| files.contains(file))
+                .peekable();
+            holding.peek()?;
+            for (target, _) in holding {
+                let args = target.args()?;
+                if !selected.contains(&args) {
+                    selected.push(args);
+                }
+            }
+        }
+        Some(selected.concat())
+    }
+}
+
+/// The files of the target rooted at `src_path`, normalized, following its `mod` declarations.
+///
+/// The modules declared inline, or by macros, aren't followed.
+#[must_use]
+pub fn module_files(src_path: &Path) -> BTreeSet<String> {
+    let mut files = BTreeSet::new();
+    // Each file, and whether its modules are in its own directory, like for `mod.rs`
+    let mut pending = vec![(src_path.to_path_buf(), true)];
+    while let Some((file, owns_dir)) = pending.pop() {
+        if !files.insert(normalize_path(&file.to_string_lossy())) {
+            continue;
+        }
+        let (Ok(source), Some(dir)) = (std::fs::read_to_string(&file), file.parent()) else {
+            continue;
+        };
+        let modules_dir = match file.file_stem() {
+            Some(stem) if !owns_dir && file.file_name() != Some(OsStr::new("mod.rs")) => {
+                dir.join(stem)
+            }
+            _ => dir.to_path_buf(),
+        };
+        let mut path_attribute = None;
+        for line in source.lines() {
+            let (attributes, item) = split_attributes(line.trim());
+            path_attribute = attributes
+                .iter()
+                .find_map(|attribute| path_of(attribute))
+                .or(path_attribute);
+            if item.is_empty() {
+                continue;
+            }
+            if let Some(module) = declared_module(item) {
+                match path_attribute {
+                    // The file of a `#[path]` is relative to the declaring file
+                    Some(path) => pending.push((dir.join(path), true)),
+                    None => {
+                        let flat = modules_dir.join(format!("{}.rs", module));
+                        if flat.is_file() {
+                            pending.push((flat, false));
+                        } else {
+                            pending.push((modules_dir.join(module).join("mod.rs"), true));
+                        }
+                    }
+                }
+            }
+            path_attribute = None;
+        }
+    }
+    files
+}
+
+/// The attributes leading `line`, like `#[cfg(test)]`, and the rest of it.
+fn split_attributes(mut line: &str) -> (Vec<&str>, &str) {
+    let mut attributes = Vec::new();
+    while line.starts_with("#[") {
+        let mut depth = 0;
+        let Some(end) = line.char_indices().find_map(|(index, c)| {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            (c == ']' && depth == 0).then_some(index)
+        }) else {
+            break;
+        };
+        attributes.push(&line[..=end]);
+        line = line[end + 1..].trim_start();
+    }
+    (attributes, line)
+}
+
+/// The path of a `#[path = "file.rs"]` attribute.
+fn path_of(attribute: &str) -> Option<&str> {
+    let value = attribute
+        .strip_prefix("#[")?
+        .strip_suffix(']')?
+        .trim()
+        .strip_prefix("path")?
+        .trim_start()
+        .strip_prefix('=')?
+        .trim();
+    value.strip_prefix('"')?.strip_suffix('"')
+}
+
+/// The name of the module a `mod name;` item declares, whatever its visibility.
+fn declared_module(item: &str) -> Option<&str> {
+    let item = match item.strip_prefix("pub") {
+        Some(rest) if rest.starts_with('(') => rest.split_once(')')?.1,
+        Some(rest) => rest,
+        None => item,
+    };
+    let name = item
+        .trim_start()
+        .strip_prefix("mod ")?
+        .strip_suffix(';')?
+        .trim();
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    (!name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{declared_module, module_files, path_of, split_attributes, TargetSelection};
+    use crate::vcs::Section;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(root: &Path, files: &[(&str, &str)]) -> Result<(), crate::error::Error> {
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+
+    fn section(file_name: &Path) -> Section {
+        Section {
+            file_name: file_name.to_string_lossy().into_owned(),
+            line_start: 1,
+            line_end: 1,
+        }
+    }
+
+    #[test]
+    fn test_module_files() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        let root = fs::canonicalize(dir.path())?;
+        write(
+            &root,
+            &[
+                (
+                    "src/lib.rs",
+                    "pub mod config;\nmod parse;\n#[cfg(test)] mod tests;\n#[path = \"gen/out.rs\"]\nmod generated;\nmod inline {}\n",
+                ),
+                ("src/config.rs", "pub(crate) mod layers;\n"),
+                ("src/config/layers.rs", ""),
+                ("src/parse/mod.rs", "mod lexer;\n"),
+                ("src/parse/lexer.rs", ""),
+                ("src/tests.rs", ""),
+                ("src/gen/out.rs", ""),
+                ("src/unused.rs", ""),
+            ],
+        )?;
+        let files = module_files(&root.join("src/lib.rs"));
+        let expected: Vec<String> = [
+            "src/config.rs",
+            "src/config/layers.rs",
+            "src/gen/out.rs",
+            "src/lib.rs",
+            "src/parse/lexer.rs",
+            "src/parse/mod.rs",
+            "src/tests.rs",
+        ]
+        .iter()
+        .map(|file| root.join(file).to_string_lossy().into_owned())
+        .collect();
+        assert_eq!(expected, files.into_iter().collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_args() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        let root = fs::canonicalize(dir.path())?;
+        write(
+            &root,
+            &[
+                ("app/src/lib.rs", "mod util;\n"),
+                ("app/src/util.rs", ""),
+                ("app/src/main.rs", ""),
+                ("app/tests/heavy.rs", "mod common;\n"),
+                ("app/tests/common/mod.rs", ""),
+                ("app/build.rs", ""),
+                ("app/src/orphan.rs", ""),
+            ],
+        )?;
+        let app = root.join("app");
+        let target = |name: &str, kind: &str, src_path: &str| {
+            format!(
+                r#"{{"name": "{}", "kind": ["{}"], "src_path": "{}"}}"#,
+                name,
+                kind,
+                app.join(src_path).display()
+            )
+        };
+        let metadata = format!(
+            r#"{{"packages": [{{"manifest_path": "{}", "targets": [{}, {}, {}, {}]}}]}}"#,
+            app.join("Cargo.toml").display(),
+            target("app", "lib", "src/lib.rs"),
+            target("app", "bin", "src/main.rs"),
+            target("heavy", "test", "tests/heavy.rs"),
+            target("build-script-build", "custom-build", "build.rs"),
+        );
+        let selection = TargetSelection::from_metadata(&metadata, &root, &["app".to_string()])?;
+        let args = |files: &[&str]| {
+            let sections: Vec<Section> = files.iter().map(|f| section(&app.join(f))).collect();
+            selection.args("app", &sections)
+        };
+        assert_eq!(Some(vec!["--lib".to_string()]), args(&["src/util.rs"]));
+        assert_eq!(
+            Some(vec![
+                "--bin".to_string(),
+                "app".to_string(),
+                "--test".to_string(),
+                "heavy".to_string()
+            ]),
+            args(&["src/main.rs", "tests/common/mod.rs"])
+        );
+        assert_eq!(None, args(&["build.rs"]));
+        assert_eq!(None, args(&["src/orphan.rs"]));
+        assert_eq!(None, args(&[]));
+        assert_eq!(None, selection.args("other", &[]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parsing() {
+        assert_eq!(Some("util"), declared_module("mod util;"));
+        assert_eq!(Some("util"), declared_module("pub(crate) mod util;"));
+        assert_eq!(Some("type"), declared_module("pub mod r#type;"));
+        assert_eq!(None, declared_module("mod util {"));
+        assert_eq!(None, declared_module("let module = 1;"));
+        assert_eq!(
+            (vec!["#[cfg(test)]", "#[path = \"t.rs\"]"], "mod tests;"),
+            split_attributes("#[cfg(test)] #[path = \"t.rs\"] mod tests;")
+        );
+        assert_eq!(Some("t.rs"), path_of("#[path = \"t.rs\"]"));
+        assert_eq!(None, path_of("#[cfg(test)]"));
+    }
+}
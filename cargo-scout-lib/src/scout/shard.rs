@@ -0,0 +1,84 @@
+use crate::linter::fingerprint::Fnv1a;
+use std::str::FromStr;
+
+/// One of the `count` jobs scouting a workspace together, `index` being 1-based like `1/4`.
+///
+/// Members are assigned by a stable hash of their path, so every job agrees on
+/// who lints what without talking to the others, and a member keeps its shard
+/// when others are added.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Shard {
+    index: u64,
+    count: u64,
+}
+
+impl Shard {
+    /// Whether the member at `member`, relative to the workspace root, is linted by this shard.
+    #[must_use]
+    pub fn includes(&self, member: &str) -> bool {
+        let mut hasher = Fnv1a::default();
+        hasher.write(member);
+        mix(hasher.finish()) % self.count == self.index - 1
+    }
+}
+
+/// The finalizer of MurmurHash3, FNV-1a alone spreads paths differing by a suffix poorly.
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid shard {}, expected k/n with 1 <= k <= n", s);
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index: u64 = index.trim().parse().map_err(|_| invalid())?;
+        let count: u64 = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(invalid());
+        }
+        Ok(Self { index, count })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shard;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("2/4", "2/4".parse::<Shard>().unwrap().to_string());
+        assert!("0/4".parse::<Shard>().is_err());
+        assert!("5/4".parse::<Shard>().is_err());
+        assert!("1".parse::<Shard>().is_err());
+        assert!("a/b".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn test_includes() {
+        let members: Vec<String> = (0..50).map(|i| format!("crates/member{}", i)).collect();
+        let shards: Vec<Shard> = (1..=3)
+            .map(|k| format!("{}/3", k).parse().unwrap())
+            .collect();
+        // Each member is in exactly one shard
+        for member in &members {
+            assert_eq!(1, shards.iter().filter(|s| s.includes(member)).count());
+        }
+        for shard in &shards {
+            assert!(members.iter().any(|m| shard.includes(m)));
+        }
+        let whole: Shard = "1/1".parse().unwrap();
+        assert!(members.iter().all(|m| whole.includes(m)));
+    }
+}
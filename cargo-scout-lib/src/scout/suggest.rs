@@ -0,0 +1,243 @@
+use super::fix::git;
+use crate::error::Error;
+use crate::linter::{Lint, Suggestion};
+use crate::report::patch::apply_suggestions;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// The machine-applicable fixes of some lints, applied to the working tree.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct AppliedFixes {
+    /// The lints that were fixed
+    pub lints: Vec<Lint>,
+    /// The files that were changed
+    pub files: Vec<PathBuf>,
+}
+
+impl AppliedFixes {
+    /// Applies the suggestions of `lints`, whose paths are relative to `workspace_root`.
+    ///
+    /// A lint is fixed only if all of its suggestions apply,
+    /// and don't overlap the suggestions of a lint fixed before it.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn apply(lints: &[Lint], workspace_root: &Path) -> Result<Self, Error> {
+        let mut seen = HashSet::new();
+        let mut accepted: BTreeMap<PathBuf, Vec<&Suggestion>> = BTreeMap::new();
+        let mut sources: BTreeMap<PathBuf, String> = BTreeMap::new();
+        let mut fixed = Vec::new();
+        for lint in lints {
+            // A lint reported on several spans shares its suggestions between them
+            if lint.suggestions.is_empty() || !seen.insert(&lint.suggestions) {
+                continue;
+            }
+            let mut candidate = accepted.clone();
+            for suggestion in &lint.suggestions {
+                candidate
+                    .entry(workspace_root.join(&suggestion.path))
+                    .or_default()
+                    .push(suggestion);
+            }
+            let mut applies = true;
+            for (file, suggestions) in &candidate {
+                if !sources.contains_key(file) {
+                    sources.insert(file.clone(), std::fs::read_to_string(file)?);
+                }
+                if apply_suggestions(&sources[file], suggestions).is_none() {
+                    applies = false;
+                    break;
+                }
+            }
+            if applies {
+                accepted = candidate;
+                fixed.push(lint.clone());
+            }
+        }
+        let mut files = Vec::new();
+        for (file, suggestions) in accepted {
+            if let Some(source) = apply_suggestions(&sources[&file], &suggestions) {
+                std::fs::write(&file, source)?;
+                files.push(file);
+            }
+        }
+        Ok(Self {
+            lints: fixed,
+            files,
+        })
+    }
+
+    /// Whether no lint was fixed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lints.is_empty()
+    }
+
+    /// The message of a commit of the fixes, listing the lints fixed by code.
+    #[must_use]
+    pub fn commit_message(&self) -> String {
+        let mut by_code: BTreeMap<&str, Vec<&Lint>> = BTreeMap::new();
+        for lint in &self.lints {
+            by_code
+                .entry(lint.code.as_deref().unwrap_or("unknown"))
+                .or_default()
+                .push(lint);
+        }
+        let mut message = format!(
+            "Fix {} lint(s) reported by cargo-scout\n\n",
+            self.lints.len()
+        );
+        for (code, lints) in by_code {
+            message.push_str(&format!("{} ({}):\n", code, lints.len()));
+            for lint in lints {
+                message.push_str(&format!(
+                    "- {}:{}\n",
+                    lint.location.path.replace('\\', "/"),
+                    lint.location.lines[0]
+                ));
+            }
+        }
+        message
+    }
+
+    /// Commits the changed files in the repository at `repo`, leaving other changes alone,
+    /// and returns the id of the commit.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn commit(&self, repo: &Path) -> Result<String, Error> {
+        let message = self.commit_message();
+        let mut args = vec!["commit", "--quiet", "-m", &message, "--"];
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect();
+        args.extend(files.iter().map(String::as_str));
+        git(repo, &args)?;
+        Ok(git(repo, &["rev-parse", "HEAD"])?.trim().to_string())
+    }
+}
+
+/// Pushes the commit checked out in `repo` to `branch` of `remote`.
+#[cfg(not(tarpaulin_include))]
+#[allow(clippy::missing_errors_doc)]
+pub fn push(repo: &Path, remote: &str, branch: &str) -> Result<(), Error> {
+    git(
+        repo,
+        &[
+            "push",
+            "--quiet",
+            remote,
+            &format!("HEAD:refs/heads/{}", branch),
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AppliedFixes;
+    use crate::linter::{Lint, Location, Suggestion};
+    use std::fs;
+    use std::process::Command;
+
+    fn lint(code: &str, line: u32, suggestions: Vec<Suggestion>) -> Lint {
+        Lint {
+            message: code.to_string(),
+            code: Some(code.to_string()),
+            location: Location {
+                path: "src/lib.rs".to_string(),
+                lines: [line, line],
+                ..Location::default()
+            },
+            suggestions,
+        }
+    }
+
+    fn suggestion(line: u32, columns: [u32; 2], replacement: &str) -> Suggestion {
+        Suggestion {
+            path: "src/lib.rs".to_string(),
+            lines: [line, line],
+            columns,
+            replacement: replacement.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "let a = &x;\nlet b = &y;\n").unwrap();
+        let lints = vec![
+            lint(
+                "clippy::needless_borrow",
+                1,
+                vec![suggestion(1, [9, 11], "x")],
+            ),
+            // Reported on another span, with the same suggestions
+            lint(
+                "clippy::needless_borrow",
+                1,
+                vec![suggestion(1, [9, 11], "x")],
+            ),
+            // Overlaps the first fix
+            lint("clippy::other", 1, vec![suggestion(1, [10, 11], "z")]),
+            lint(
+                "clippy::needless_borrow",
+                2,
+                vec![suggestion(2, [9, 11], "y")],
+            ),
+            lint("clippy::no_fix", 2, Vec::new()),
+        ];
+        let fixes = AppliedFixes::apply(&lints, root).unwrap();
+        assert_eq!(vec![lints[0].clone(), lints[3].clone()], fixes.lints);
+        assert_eq!(vec![root.join("src/lib.rs")], fixes.files);
+        assert_eq!(
+            "let a = x;\nlet b = y;\n",
+            fs::read_to_string(root.join("src/lib.rs")).unwrap()
+        );
+        assert_eq!(
+            "Fix 2 lint(s) reported by cargo-scout\n\n\
+             clippy::needless_borrow (2):\n- src/lib.rs:1\n- src/lib.rs:2\n",
+            fixes.commit_message()
+        );
+    }
+
+    #[test]
+    fn test_commit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = fs::canonicalize(dir.path()).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(&repo)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "scout"]);
+        git(&["config", "user.email", "scout@example.com"]);
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(repo.join("src/lib.rs"), "let a = &x;\n").unwrap();
+        fs::write(repo.join("other.rs"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        // An unrelated change, which isn't committed
+        fs::write(repo.join("other.rs"), "two\n").unwrap();
+
+        let lints = vec![lint(
+            "clippy::needless_borrow",
+            1,
+            vec![suggestion(1, [9, 11], "x")],
+        )];
+        let fixes = AppliedFixes::apply(&lints, &repo).unwrap();
+        let id = fixes.commit(&repo).unwrap();
+        assert_eq!(40, id.len());
+        let status = Command::new("git")
+            .current_dir(&repo)
+            .args(["status", "--porcelain"])
+            .output()
+            .unwrap();
+        assert_eq!(" M other.rs\n", String::from_utf8_lossy(&status.stdout));
+    }
+}
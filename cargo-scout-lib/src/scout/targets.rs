@@ -0,0 +1,60 @@
+/// The kind of cargo target a source file belongs to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TargetKind {
+    /// The library, and the files no other kind holds, like `build.rs`
+    Lib,
+    /// `src/main.rs` and the files of `src/bin`
+    Bin,
+    /// The files of `tests`
+    Test,
+    /// The files of `examples`
+    Example,
+    /// The files of `benches`
+    Bench,
+}
+
+impl TargetKind {
+    /// The kind of the file at `path`, by the innermost `src`, `tests`, `examples`
+    /// or `benches` directory holding it, which is how cargo lays targets out.
+    #[must_use]
+    pub fn of(path: &str) -> Self {
+        let components: Vec<&str> = path.split('/').collect();
+        let Some((file, dirs)) = components.split_last() else {
+            return Self::Lib;
+        };
+        for (index, dir) in dirs.iter().enumerate().rev() {
+            match *dir {
+                "src" if dirs.get(index + 1) == Some(&"bin") => return Self::Bin,
+                "src" if index + 1 == dirs.len() && *file == "main.rs" => return Self::Bin,
+                "src" => return Self::Lib,
+                "tests" => return Self::Test,
+                "examples" => return Self::Example,
+                "benches" => return Self::Bench,
+                _ => {}
+            }
+        }
+        Self::Lib
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TargetKind;
+
+    #[test]
+    fn test_of() {
+        assert_eq!(TargetKind::Lib, TargetKind::of("member/src/lib.rs"));
+        assert_eq!(TargetKind::Lib, TargetKind::of("member/src/tests.rs"));
+        assert_eq!(TargetKind::Lib, TargetKind::of("build.rs"));
+        assert_eq!(TargetKind::Bin, TargetKind::of("member/src/main.rs"));
+        assert_eq!(TargetKind::Bin, TargetKind::of("src/bin/tool/main.rs"));
+        assert_eq!(TargetKind::Test, TargetKind::of("tests/common/mod.rs"));
+        assert_eq!(
+            TargetKind::Example,
+            TargetKind::of("member/examples/demo.rs")
+        );
+        assert_eq!(TargetKind::Bench, TargetKind::of("benches/parse.rs"));
+        // A member living in an examples directory
+        assert_eq!(TargetKind::Lib, TargetKind::of("examples/demo/src/lib.rs"));
+    }
+}
@@ -0,0 +1,112 @@
+use super::items::SyntaxMap;
+use crate::config::scout::TestCodeConfig;
+use crate::linter::Lint;
+use crate::utils::relative_path;
+use std::path::Path;
+
+/// Finds the lints of test code that are skipped.
+///
+/// Each file is only parsed once.
+pub struct TestCode<'a> {
+    config: &'a TestCodeConfig,
+    root: &'a Path,
+    syntax: SyntaxMap,
+}
+
+impl<'a> TestCode<'a> {
+    /// Paths are relative to `root` when they are checked for test directories.
+    #[must_use]
+    pub fn new(config: &'a TestCodeConfig, root: &'a Path) -> Self {
+        Self {
+            config,
+            root,
+            syntax: SyntaxMap::new(),
+        }
+    }
+
+    /// Returns why `lint` is skipped as test code, if it is.
+    pub fn reason(&mut self, lint: &Lint) -> Option<String> {
+        if !self.config.skips(lint) {
+            return None;
+        }
+        let path = relative_path(&lint.location.path, self.root).replace('\\', "/");
+        if is_test_path(&path) {
+            return Some(format!("`{}` is a test file", path));
+        }
+        self.syntax
+            .enclosing(&lint.location.path, lint.location.lines[0])
+            .filter(|item| item.test)
+            .map(|item| format!("`{}` is test code", item.name))
+    }
+}
+
+/// Whether the file at `path` only holds tests by its path.
+///
+/// That is the files of `tests` directories, and the `tests.rs` or `*_test.rs` modules.
+#[must_use]
+pub fn is_test_path(path: &str) -> bool {
+    let mut components: Vec<&str> = path.split('/').collect();
+    let Some(file) = components.pop() else {
+        return false;
+    };
+    let stem = file.strip_suffix(".rs").unwrap_or(file);
+    components.contains(&"tests")
+        || ["test", "tests"].contains(&stem)
+        || stem.ends_with("_test")
+        || stem.ends_with("_tests")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_test_path, TestCode};
+    use crate::config::scout::TestCodeConfig;
+    use crate::linter::{Lint, Location};
+
+    #[test]
+    fn test_is_test_path() {
+        assert!(is_test_path("tests/integration.rs"));
+        assert!(is_test_path("member/tests/common/mod.rs"));
+        assert!(is_test_path("src/config/tests.rs"));
+        assert!(is_test_path("src/parser_test.rs"));
+        assert!(!is_test_path("src/testing.rs"));
+        assert!(!is_test_path("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_reason() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "fn foo() {}\n\n#[cfg(test)]\nmod tests {\n    fn helper() {}\n}\n",
+        )
+        .unwrap();
+        let lint = |path: &str, line| Lint {
+            code: Some("clippy::unwrap_used".to_string()),
+            location: Location {
+                path: dir.path().join(path).to_string_lossy().into_owned(),
+                lines: [line, line],
+                ..Location::default()
+            },
+            ..Lint::default()
+        };
+        let config = TestCodeConfig {
+            skip: true,
+            ..TestCodeConfig::default()
+        };
+        let mut test_code = TestCode::new(&config, dir.path());
+        assert_eq!(
+            Some("`tests::helper` is test code".to_string()),
+            test_code.reason(&lint("src/lib.rs", 5))
+        );
+        assert_eq!(None, test_code.reason(&lint("src/lib.rs", 1)));
+        assert_eq!(
+            Some("`tests/it.rs` is a test file".to_string()),
+            test_code.reason(&lint("tests/it.rs", 1))
+        );
+
+        let config = TestCodeConfig::default();
+        let mut test_code = TestCode::new(&config, dir.path());
+        assert_eq!(None, test_code.reason(&lint("src/lib.rs", 5)));
+    }
+}
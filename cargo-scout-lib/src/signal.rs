@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+use std::io;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::Mutex;
+
+/// The termination signal received, or 0.
+static SIGNAL: AtomicI32 = AtomicI32::new(0);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+/// The process ids of the running children.
+static CHILDREN: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/// Installs handlers for SIGINT and SIGTERM, which forward the signal to the running children.
+///
+/// Once a signal is received, no more children are started,
+/// and a second signal terminates scout right away.
+/// Signals are only handled on Unix.
+pub fn install() {
+    #[cfg(unix)]
+    {
+        if INSTALLED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Safety: the handler only calls async-signal-safe functions
+        unsafe {
+            libc::signal(libc::SIGINT, handle as *const () as libc::sighandler_t);
+            libc::signal(libc::SIGTERM, handle as *const () as libc::sighandler_t);
+        }
+        std::thread::spawn(forward);
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn handle(signal: libc::c_int) {
+    SIGNAL.store(signal, Ordering::SeqCst);
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+    }
+}
+
+/// Forwards the received signal to the process group of every child, once.
+#[cfg(unix)]
+fn forward() {
+    let mut signaled = BTreeSet::new();
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let Some(signal) = received() else {
+            continue;
+        };
+        let children = CHILDREN.lock().unwrap_or_else(|e| e.into_inner());
+        let pending: Vec<u32> = children.difference(&signaled).copied().collect();
+        for pid in pending {
+            unsafe {
+                libc::kill(-(pid as libc::pid_t), signal);
+            }
+            signaled.insert(pid);
+        }
+    }
+}
+
+/// The termination signal received, if any.
+#[must_use]
+pub fn received() -> Option<i32> {
+    Some(SIGNAL.load(Ordering::SeqCst)).filter(|signal| *signal != 0)
+}
+
+/// The exit code of a process terminated by `signal`, as shells report it.
+#[must_use]
+pub fn exit_code(signal: i32) -> i32 {
+    128 + signal
+}
+
+/// The error of a command that wasn't run, or was terminated, because of a signal.
+#[must_use]
+pub fn interrupted() -> io::Error {
+    io::Error::other("interrupted by a signal")
+}
+
+/// The error of a command that failed to run, `Error::Interrupted` once a signal was received.
+#[must_use]
+pub fn command_error(error: io::Error) -> crate::error::Error {
+    match received() {
+        Some(signal) => crate::error::Error::Interrupted(signal),
+        None => crate::error::Error::Io(error),
+    }
+}
+
+/// Runs `command` like `Command::output`, tracking the child so that signals are forwarded to it.
+///
+/// Children run in their own process group, so that the processes they spawn get the signal too.
+#[allow(clippy::missing_errors_doc)]
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    if received().is_some() {
+        return Err(interrupted());
+    }
+    #[cfg(unix)]
+    {
+        if INSTALLED.load(Ordering::SeqCst) {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+    }
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pid = child.id();
+    CHILDREN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(pid);
+    let output = child.wait_with_output();
+    CHILDREN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&pid);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_error, exit_code, output};
+    use crate::error::Error;
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(130, exit_code(2));
+        assert_eq!(143, exit_code(15));
+    }
+
+    #[test]
+    fn test_command_error() {
+        // Without a signal, failing to run is a plain tool failure
+        assert!(matches!(
+            command_error(std::io::Error::other("no such file")),
+            Error::Io(_)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output() {
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2; exit 3"]);
+        let output = output(&mut command).expect("failed to run sh");
+        assert_eq!(Some(3), output.status.code());
+        assert_eq!("out\n", String::from_utf8_lossy(&output.stdout));
+        assert_eq!("err\n", String::from_utf8_lossy(&output.stderr));
+    }
+}
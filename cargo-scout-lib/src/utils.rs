@@ -1,4 +1,6 @@
 use crate::error::Error;
+use cargo_scout_macros::warn;
+use colored::Colorize;
 use std::path::Path;
 
 pub fn get_absolute_file_path(file_path: impl AsRef<Path>) -> Result<String, Error> {
@@ -6,3 +8,134 @@ pub fn get_absolute_file_path(file_path: impl AsRef<Path>) -> Result<String, Err
     absolute_path.push(file_path);
     Ok(absolute_path.to_string_lossy().to_string())
 }
+
+/// Decodes the output of a subprocess, such as `cargo` or `git`.
+///
+/// A latin-1 file name or a localized message shouldn't stop the whole run,
+/// so invalid UTF-8 sequences are replaced with a warning instead of failing.
+pub fn decode_output(bytes: Vec<u8>, source: &str) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|error| {
+        warn!(
+            "The output of {} is not valid UTF-8, invalid sequences were replaced",
+            source
+        );
+        String::from_utf8_lossy(error.as_bytes()).into_owned()
+    })
+}
+
+/// Normalizes a path to compare it with paths coming from other tools:
+/// separators are turned into `/`.
+///
+/// The extended-length prefix `canonicalize` adds on Windows is dropped,
+/// `\\?\C:\foo` becomes `C:/foo` and `\\?\UNC\server\share` becomes `//server/share`.
+pub fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    if let Some(unc) = path.strip_prefix("//?/UNC/") {
+        format!("//{}", unc)
+    } else if let Some(local) = path.strip_prefix("//?/") {
+        local.to_string()
+    } else {
+        path
+    }
+}
+
+/// The lines of a text, as returned by `text_lines`.
+pub type TextLines<'a> = std::iter::Map<std::str::Lines<'a>, fn(&'a str) -> &'a str>;
+
+/// Splits a text into lines, tolerating Windows checkouts:
+/// a leading byte order mark is dropped, and so are the carriage returns ending each line.
+pub fn text_lines(text: &str) -> TextLines<'_> {
+    text.strip_prefix('\u{feff}')
+        .unwrap_or(text)
+        .lines()
+        .map(trim_carriage_returns as fn(&str) -> &str)
+}
+
+fn trim_carriage_returns(line: &str) -> &str {
+    line.trim_end_matches('\r')
+}
+
+/// Returns `path` relative to `root`, with normalized separators.
+///
+/// Paths outside of `root` are returned as is, like those of a sibling sharing its prefix.
+pub fn relative_path(path: &str, root: &Path) -> String {
+    let path = normalize_path(path);
+    let root = normalize_path(&root.to_string_lossy());
+    Path::new(&path)
+        .strip_prefix(&root)
+        .map_or(path.clone(), |relative| {
+            relative.to_string_lossy().into_owned()
+        })
+}
+
+/// Whether `path` is in the directory `dir`, comparing whole components:
+/// `crates/foo-bar/src/lib.rs` isn't in `crates/foo`.
+pub fn is_in_dir(path: &str, dir: &Path) -> bool {
+    Path::new(&normalize_path(path)).starts_with(normalize_path(&dir.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_output, is_in_dir, normalize_path, relative_path};
+    use std::path::Path;
+
+    #[test]
+    fn test_decode_output() {
+        assert_eq!(
+            "caf\u{e9}",
+            decode_output("café".as_bytes().to_vec(), "git")
+        );
+        // `café` encoded in latin-1
+        assert_eq!(
+            "caf\u{fffd}.rs",
+            decode_output(b"caf\xe9.rs".to_vec(), "git")
+        );
+    }
+
+    #[test]
+    fn test_normalize_extended_length_paths() {
+        assert_eq!(
+            "C:/repo/src/lib.rs",
+            normalize_path("\\\\?\\C:\\repo\\src\\lib.rs")
+        );
+        assert_eq!(
+            "//server/share/repo",
+            normalize_path("\\\\?\\UNC\\server\\share\\repo")
+        );
+        assert_eq!("//server/share", normalize_path("\\\\server\\share"));
+        assert_eq!("/repo/src/lib.rs", normalize_path("/repo/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_sibling_prefix() {
+        let root = Path::new("/repo/crates/foo");
+        assert_eq!(
+            "src/lib.rs",
+            relative_path("/repo/crates/foo/src/lib.rs", root)
+        );
+        assert_eq!(
+            "/repo/crates/foo-bar/src/lib.rs",
+            relative_path("/repo/crates/foo-bar/src/lib.rs", root)
+        );
+        assert!(is_in_dir("/repo/crates/foo/src/lib.rs", root));
+        assert!(is_in_dir(
+            "C:\\repo\\crates\\foo\\src\\lib.rs",
+            Path::new("C:/repo/crates/foo")
+        ));
+        assert!(!is_in_dir("/repo/crates/foo-bar/src/lib.rs", root));
+    }
+
+    #[test]
+    fn test_relative_long_paths() {
+        // Deeply nested workspaces go past the 260 characters of MAX_PATH
+        let nested = vec!["nested-directory"; 20].join("\\");
+        let root = format!("\\\\?\\C:\\{}", nested);
+        let file = format!("C:\\{}\\member\\src\\lib.rs", nested);
+        assert!(root.len() > 260);
+        assert_eq!("member/src/lib.rs", relative_path(&file, Path::new(&root)));
+        assert_eq!(
+            normalize_path(&format!("{}\\member\\src\\lib.rs", root)),
+            normalize_path(&file)
+        );
+    }
+}
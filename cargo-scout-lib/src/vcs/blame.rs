@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The authors of the lines of the repository, blamed once per file.
+pub struct Blames {
+    repo: Option<git2::Repository>,
+    files: HashMap<String, Option<Vec<Option<String>>>>,
+}
+
+impl Blames {
+    /// The authors of the repository holding `root`, if there is one.
+    #[must_use]
+    pub fn new(root: &Path) -> Self {
+        Self {
+            repo: super::git::open(root).ok(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// The commit checked out in the repository.
+    #[must_use]
+    pub fn head(&self) -> Option<String> {
+        let head = self.repo.as_ref()?.head().ok()?.target()?;
+        Some(head.to_string())
+    }
+
+    /// The author of `line` of the file at `path`, relative to the repository root.
+    ///
+    /// Lines that aren't committed yet are attributed to the configured user.
+    pub fn author(&mut self, path: &str, line: u32) -> Option<String> {
+        let repo = self.repo.as_ref()?;
+        let authors = self
+            .files
+            .entry(path.to_string())
+            .or_insert_with(|| blame(repo, path))
+            .as_ref()?;
+        authors.get(line.checked_sub(1)? as usize)?.clone()
+    }
+}
+
+fn blame(repo: &git2::Repository, path: &str) -> Option<Vec<Option<String>>> {
+    let content = std::fs::read(repo.workdir()?.join(path)).ok()?;
+    let blame = repo.blame_file(Path::new(path), None).ok()?;
+    let blame = blame.blame_buffer(&content).ok()?;
+    let user = repo
+        .config()
+        .ok()
+        .and_then(|config| config.get_string("user.name").ok());
+    let lines = content.split(|byte| *byte == b'\n').count();
+    Some(
+        (1..=lines)
+            .map(|line| {
+                let hunk = blame.get_line(line)?;
+                if hunk.final_commit_id().is_zero() {
+                    return user.clone();
+                }
+                let author = hunk.final_signature().name().map(str::to_string);
+                author
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Blames;
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn test_author() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = fs::canonicalize(dir.path()).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(&repo)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "committer"]);
+        git(&["config", "user.email", "committer@example.com"]);
+        fs::write(repo.join("lib.rs"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        git(&["config", "user.name", "local"]);
+        fs::write(repo.join("lib.rs"), "one\ntwo\n").unwrap();
+
+        let mut blames = Blames::new(&repo);
+        assert_eq!(Some(40), blames.head().as_ref().map(String::len));
+        assert_eq!(Some("committer".to_string()), blames.author("lib.rs", 1));
+        assert_eq!(Some("local".to_string()), blames.author("lib.rs", 2));
+        assert_eq!(None, blames.author("lib.rs", 0));
+        assert_eq!(None, blames.author("missing.rs", 1));
+    }
+}
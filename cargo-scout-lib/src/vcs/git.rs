@@ -1,28 +1,192 @@
-use super::{Section, VCS};
+use super::patch::{self, ChangeFilter, FileDiff, Parser};
+use super::pathspec::Pathspec;
+use super::{combine_sections, BaseMode, Section, VCS};
 use crate::error::Error;
+use crate::utils::decode_output;
 use cargo_scout_macros::info;
 use colored::Colorize;
-use git2::{Delta, DiffOptions, Repository};
+use git2::{
+    AttrCheckFlags, AttrValue, Diff, DiffFormat, DiffLine, DiffOptions, Repository, StatusOptions,
+};
 use std::path::{Path, PathBuf};
 
+/// The `.gitattributes` attributes hiding a file from code review, its changes aren't scouted.
+pub const HIDING_ATTRIBUTES: &[&str] = &["linguist-generated", "export-ignore"];
+
 pub struct Git {
-    target_branch: String,
+    target_branches: Vec<String>,
+    base_mode: BaseMode,
+    change_filter: ChangeFilter,
+    pathspec: Pathspec,
 }
 
 impl Default for Git {
-    #[must_use]
     fn default() -> Self {
-        Self {
-            target_branch: "HEAD".to_string(),
-        }
+        Self::with_target("HEAD".to_string())
     }
 }
 
 impl Git {
     #[must_use]
     pub fn with_target(target_branch: String) -> Self {
-        Self { target_branch }
+        Self::with_targets(vec![target_branch], BaseMode::default())
+    }
+
+    /// Computes the diff against several targets,
+    /// and combines them according to `base_mode`.
+    #[must_use]
+    pub fn with_targets(target_branches: Vec<String>, base_mode: BaseMode) -> Self {
+        Self {
+            target_branches,
+            base_mode,
+            change_filter: ChangeFilter::default(),
+            pathspec: Pathspec::default(),
+        }
+    }
+
+    /// Leaves out the hunks `filter` ignores, git itself ignores the whitespace like `-w` does.
+    #[must_use]
+    pub fn with_change_filter(mut self, filter: ChangeFilter) -> Self {
+        self.change_filter = filter;
+        self
     }
+
+    /// Only diffs the paths of `pathspec`, like `git diff -- <pathspec>`.
+    #[must_use]
+    pub fn with_pathspec(mut self, pathspec: Pathspec) -> Self {
+        self.pathspec = pathspec;
+        self
+    }
+
+    /// Returns the working tree of the superproject,
+    /// if the repository at `repo_path` is a submodule.
+    #[cfg(not(tarpaulin_include))]
+    #[allow(clippy::missing_errors_doc)]
+    pub fn superproject_root(repo_path: impl AsRef<Path>) -> Result<Option<PathBuf>, Error> {
+        rev_parse(repo_path.as_ref(), "--show-superproject-working-tree")
+    }
+
+    /// The changes made since `target_branch`, and the root of the working tree.
+    fn target_files(
+        &self,
+        repo: &Repository,
+        target_branch: &str,
+    ) -> Result<(PathBuf, Vec<FileDiff>), Error> {
+        let (root, diff) = self.target_diff(repo, target_branch)?;
+        // The diff goes through the same parser as patch files,
+        // deleted files and non Rust files are skipped there.
+        let mut patch = Vec::new();
+        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+            push_line(&mut patch, &line);
+            true
+        })?;
+        Ok((root, self.parse(repo, target_branch, &patch)?))
+    }
+
+    /// The diff with `target_branch`, whose file contents are only diffed as it is printed.
+    fn target_diff<'r>(
+        &self,
+        repo: &'r Repository,
+        target_branch: &str,
+    ) -> Result<(PathBuf, Diff<'r>), Error> {
+        info!("[VCS] - Getting diff with target {}", target_branch);
+        let root = repo
+            .workdir()
+            .unwrap_or_else(|| Path::new("/"))
+            .to_path_buf();
+        let tree = repo.revparse_single(target_branch)?.peel_to_tree()?;
+        let mut config = DiffOptions::default();
+        config
+            .context_lines(0)
+            .show_untracked_content(true)
+            .recurse_untracked_dirs(true)
+            .ignore_whitespace(self.change_filter.whitespace);
+        for pattern in self.pathspec.patterns_from(&root)? {
+            config.pathspec(pattern);
+        }
+        // The work tree exported from a bare repository has no index, its files aren't deleted
+        let diff = if repo.path().join("index").exists() {
+            repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut config))?
+        } else {
+            repo.diff_tree_to_workdir(Some(&tree), Some(&mut config))?
+        };
+        Ok((root, diff))
+    }
+
+    /// The files of the printed `patch` of the diff with `target_branch`, but the hidden ones.
+    fn parse(
+        &self,
+        repo: &Repository,
+        target_branch: &str,
+        patch: &[u8],
+    ) -> Result<Vec<FileDiff>, Error> {
+        let files = Parser::new(&String::from_utf8_lossy(patch))
+            .with_filter(self.change_filter)
+            .files()
+            .map_err(|error| Error::DiffParse(format!("the diff with {}", target_branch), error))?;
+        Ok(without_hidden(repo, files))
+    }
+
+    /// The sections `to_sections` computes for each target, combined by the base mode.
+    fn combined_sections(
+        &self,
+        repo_path: &Path,
+        to_sections: fn(&[FileDiff], &Path) -> Vec<Section>,
+    ) -> Result<Vec<Section>, Error> {
+        let repo = open(repo_path)?;
+        let mut per_base = self
+            .target_branches
+            .iter()
+            .map(|target_branch| {
+                let (root, files) = self.target_files(&repo, target_branch)?;
+                Ok(to_sections(&files, &root))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        if per_base.len() == 1 {
+            return Ok(per_base.remove(0));
+        }
+        Ok(combine_sections(per_base, self.base_mode))
+    }
+}
+
+/// Appends a printed line of a diff to `patch`, prefixed by its origin for the lines of hunks.
+fn push_line(patch: &mut Vec<u8>, line: &DiffLine<'_>) {
+    if let origin @ ('+' | '-' | ' ') = line.origin() {
+        patch.push(origin as u8);
+    }
+    patch.extend_from_slice(line.content());
+}
+
+/// Drops the files `.gitattributes` hides from review, see `HIDING_ATTRIBUTES`.
+fn without_hidden(repo: &Repository, mut files: Vec<FileDiff>) -> Vec<FileDiff> {
+    files.retain(|file| {
+        let Some(path) = &file.new_path else {
+            return true;
+        };
+        let hidden = is_hidden(repo, Path::new(path));
+        if hidden {
+            info!("[VCS] - {} is hidden by .gitattributes, skipping it", path);
+        }
+        !hidden
+    });
+    files
+}
+
+/// Whether one of the `HIDING_ATTRIBUTES` is set for `path`, relative to the working tree.
+///
+/// An attribute is set without a value, or with any value but `false`.
+fn is_hidden(repo: &Repository, path: &Path) -> bool {
+    HIDING_ATTRIBUTES.iter().any(|attribute| {
+        let value = repo
+            .get_attr(path, attribute, AttrCheckFlags::FILE_THEN_INDEX)
+            .ok()
+            .flatten();
+        match AttrValue::from_string(value) {
+            AttrValue::True => true,
+            AttrValue::String(value) => value != "false",
+            _ => false,
+        }
+    })
 }
 
 impl VCS for Git {
@@ -30,61 +194,250 @@ impl VCS for Git {
     where
         P: AsRef<Path>,
     {
-        info!("[VCS] - Getting diff with target {}", &self.target_branch);
-        let repo = Repository::discover(repo_path)?;
-        let root = repo.path().parent().unwrap().to_path_buf();
-        let tree = repo.revparse_single(&self.target_branch)?.peel_to_tree()?;
-        let mut config = DiffOptions::default();
-        config
-            .context_lines(0)
-            .show_untracked_content(true)
-            .recurse_untracked_dirs(true);
-        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut config))?;
-        let mut sections = Vec::new();
-        diff.foreach(
-            &mut |_delta, _progress| true,
-            None,
-            Some(&mut |delta, hunk| {
-                match delta.status() {
-                    Delta::Modified | Delta::Added | Delta::Untracked => {
-                        if let Some(file_path) = delta.new_file().path() {
-                            // Path returns the path of the entry relative to the working directory.
-                            // We can get the absolute path
-                            if file_path.to_str().unwrap().ends_with(".rs") {
-                                sections.push(Section {
-                                    file_name: root.join(file_path).to_str().unwrap().to_string(),
-                                    line_start: hunk.new_start(),
-                                    line_end: hunk.new_start() + hunk.new_lines(),
-                                });
-                            }
-                        }
-                    }
-                    _ => {}
+        self.combined_sections(repo_path.as_ref(), patch::file_sections)
+    }
+
+    /// Streams the files as libgit2 diffs their contents, when there is a single target.
+    fn stream_sections<P, F>(&self, repo_path: P, mut on_file: F) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(Vec<Section>) -> Result<(), Error>,
+    {
+        let [target_branch] = self.target_branches.as_slice() else {
+            // The sections of several targets are combined once they are all computed
+            return super::sections_by_file(self.sections(repo_path)?)
+                .into_iter()
+                .try_for_each(on_file);
+        };
+        let repo = open(repo_path.as_ref())?;
+        let (root, diff) = self.target_diff(&repo, target_branch)?;
+        let mut flush = |patch: &mut Vec<u8>| -> Result<(), Error> {
+            if patch.is_empty() {
+                return Ok(());
+            }
+            let files = self.parse(&repo, target_branch, &std::mem::take(patch))?;
+            match patch::file_sections(&files, &root) {
+                sections if sections.is_empty() => Ok(()),
+                sections => on_file(sections),
+            }
+        };
+        let mut patch = Vec::new();
+        let mut file = None;
+        let mut failure = None;
+        let printed = diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+            let path = delta.new_file().path().or_else(|| delta.old_file().path());
+            if path != file.as_deref() {
+                if let Err(error) = flush(&mut patch) {
+                    failure = Some(error);
+                    return false;
                 }
-                true
-            }),
-            None,
-        )?;
-        Ok(sections)
+                file = path.map(Path::to_path_buf);
+            }
+            push_line(&mut patch, &line);
+            true
+        });
+        if let Some(error) = failure {
+            return Err(error);
+        }
+        printed?;
+        flush(&mut patch)
     }
 
+    fn added_sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.combined_sections(repo_path.as_ref(), patch::added_sections)
+    }
+
+    /// The files changed against any of the targets.
+    fn changed_files<P>(&self, repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let repo = open(repo_path.as_ref())?;
+        let mut changed = Vec::new();
+        for target_branch in &self.target_branches {
+            let (root, files) = self.target_files(&repo, target_branch)?;
+            changed.extend(patch::changed_files(&files, &root));
+        }
+        changed.sort();
+        changed.dedup();
+        Ok(changed)
+    }
+
+    fn targets<P>(&self, repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let repo = open(repo_path)?;
+        self.target_branches
+            .iter()
+            .map(|target_branch| {
+                let commit = repo.revparse_single(target_branch)?.peel_to_commit()?;
+                Ok(format!("{} ({})", target_branch, commit.id()))
+            })
+            .collect()
+    }
+
+    /// Returns the top level of the working tree, as `git rev-parse --show-toplevel` does.
+    ///
+    /// Linked worktrees and submodules are their own root, the `.git` of a worktree is a file.
+    /// Without a git executable, the working tree is discovered by libgit2.
     #[allow(clippy::missing_errors_doc)]
     fn root<P>(&self, repo_path: P) -> Result<PathBuf, Error>
     where
         P: AsRef<Path>,
     {
-        let repo = Repository::discover(repo_path)?;
-        Ok(repo
-            .path()
-            .parent()
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| PathBuf::from("/"))) // return the parent of the .git dir
+        // Git would look for a repository above the worktree, or fail without saying why
+        let pointer = std::env::var_os("GIT_DIR")
+            .is_none()
+            .then(|| broken_gitdir_pointer(repo_path.as_ref()))
+            .flatten();
+        if let Some((dot_git, gitdir)) = pointer {
+            return Err(Error::BrokenWorktree(
+                dot_git.display().to_string(),
+                gitdir.display().to_string(),
+            ));
+        }
+        if !is_repository(repo_path.as_ref()) {
+            return Err(Error::NotARepository(
+                repo_path.as_ref().display().to_string(),
+            ));
+        }
+        if let Some(root) = rev_parse(repo_path.as_ref(), "--show-toplevel")? {
+            return Ok(root);
+        }
+        discovered_root(repo_path.as_ref())
+    }
+}
+
+/// The top level of the working tree holding `repo_path`, as libgit2 discovers it.
+fn discovered_root(repo_path: &Path) -> Result<PathBuf, Error> {
+    let repo = open(repo_path)?;
+    let root = repo
+        .workdir()
+        .or_else(|| repo.path().parent())
+        .map_or_else(|| PathBuf::from("/"), Path::to_path_buf);
+    Ok(std::fs::canonicalize(root)?)
+}
+
+/// The git directory a `.git` file points to, like the `gitdir: /repo/.git/worktrees/wt`
+/// of a linked worktree or the relative one of a submodule.
+///
+/// Returns `None` if `dot_git` isn't such a file.
+#[must_use]
+pub fn gitdir_pointer(dot_git: &Path) -> Option<PathBuf> {
+    if !dot_git.is_file() {
+        return None;
+    }
+    let contents = std::fs::read_to_string(dot_git).ok()?;
+    let gitdir = contents.lines().next()?.strip_prefix("gitdir:")?.trim();
+    Some(dot_git.parent()?.join(gitdir))
+}
+
+/// The `.git` file of the working tree holding `path`, and the git directory it points to,
+/// if that directory is gone, like when the main working tree of a worktree moved.
+fn broken_gitdir_pointer(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let dot_git = path
+        .ancestors()
+        .map(|dir| dir.join(".git"))
+        .find(|dot_git| dot_git.exists())?;
+    let gitdir = gitdir_pointer(&dot_git)?;
+    (!gitdir.is_dir()).then_some((dot_git, gitdir))
+}
+
+/// Whether `path` is in a git repository.
+#[must_use]
+pub fn is_repository(path: &Path) -> bool {
+    open(path).is_ok()
+}
+
+/// Opens the repository holding `repo_path`, or the one `GIT_DIR` and `GIT_WORK_TREE` set,
+/// as the git executable would.
+///
+/// `--git-dir` and `--work-tree` set them, so that the git invocations get them too.
+#[allow(clippy::missing_errors_doc)]
+pub fn open(repo_path: impl AsRef<Path>) -> Result<Repository, git2::Error> {
+    open_with(
+        repo_path.as_ref(),
+        std::env::var_os("GIT_DIR").map(PathBuf::from).as_deref(),
+        std::env::var_os("GIT_WORK_TREE")
+            .map(PathBuf::from)
+            .as_deref(),
+    )
+}
+
+/// Opens the repository at `git_dir` with the working tree at `work_tree`,
+/// discovering from `repo_path` what isn't set.
+fn open_with(
+    repo_path: &Path,
+    git_dir: Option<&Path>,
+    work_tree: Option<&Path>,
+) -> Result<Repository, git2::Error> {
+    let repo = match git_dir {
+        Some(git_dir) => Repository::open(git_dir)?,
+        None => Repository::discover(repo_path)?,
+    };
+    if let Some(work_tree) = work_tree {
+        repo.set_workdir(work_tree, false)?;
+    }
+    Ok(repo)
+}
+
+/// The id of the commit checked out in the repository holding `repo_path`, if there is one.
+#[must_use]
+pub fn head_commit(repo_path: &Path) -> Option<String> {
+    let repo = open(repo_path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
+/// Whether the working tree of the repository holding `repo_path` has no change, untracked files included.
+///
+/// A path that isn't in a repository isn't clean.
+#[must_use]
+pub fn is_clean(repo_path: &Path) -> bool {
+    let Ok(repo) = open(repo_path) else {
+        return false;
+    };
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    repo.statuses(Some(&mut options))
+        .is_ok_and(|statuses| statuses.is_empty())
+}
+
+/// Runs `git rev-parse` with `option` in `dir`, and returns the path it prints.
+///
+/// Returns `None` if git isn't installed, or if it printed nothing.
+#[cfg(not(tarpaulin_include))]
+fn rev_parse(dir: &Path, option: &str) -> Result<Option<PathBuf>, Error> {
+    let output = match std::process::Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", option])
+        .output()
+    {
+        Ok(output) => output,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+    if !output.status.success() {
+        return Err(Error::Command(decode_output(
+            output.stderr,
+            "git rev-parse",
+        )));
+    }
+    let path = decode_output(output.stdout, "git rev-parse");
+    let path = path.trim_end_matches(['\r', '\n']);
+    if path.is_empty() {
+        return Ok(None);
     }
+    Ok(Some(std::fs::canonicalize(path)?))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{get_absolute_file_path, Error, Git, Path, Repository, Section, VCS};
+    use super::{BaseMode, ChangeFilter, Error, Git, Path, Pathspec, Repository, Section, VCS};
     use std::fs::{self, File};
     use std::io::Write;
     use tempfile::TempDir;
@@ -100,6 +453,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn not_a_repository() -> Result<()> {
+        let dir = TempDir::new()?;
+        if Repository::discover(dir.path()).is_ok() {
+            // The temporary directory is inside of a repository
+            return Ok(());
+        }
+        assert!(!super::is_repository(dir.path()));
+        assert!(matches!(
+            Git::default().root(dir.path()),
+            Err(Error::NotARepository(_))
+        ));
+        let repo = RepoFixture::new()?;
+        assert!(super::is_repository(repo.path()));
+        Ok(())
+    }
+
+    #[test]
+    fn clean_working_tree() -> Result<()> {
+        let repo = RepoFixture::new()?;
+        assert!(super::is_clean(repo.path()));
+        let repo = repo.write("foo.rs", "test_files/git/added/foo.rs")?;
+        assert!(!super::is_clean(repo.path()));
+        fs::remove_file(repo.path().join("foo.rs"))?;
+        assert!(super::is_clean(repo.path()));
+        Ok(())
+    }
+
     #[test]
     fn added_files() -> Result<()> {
         let repo = RepoFixture::new()?
@@ -109,12 +490,12 @@ mod tests {
 
         let expected = vec![
             Section {
-                file_name: get_absolute_file_path(&"bar.rs")?,
+                file_name: repo.absolute_path("bar.rs")?,
                 line_start: 1,
                 line_end: 5,
             },
             Section {
-                file_name: get_absolute_file_path(&"foo.rs")?,
+                file_name: repo.absolute_path("foo.rs")?,
                 line_start: 1,
                 line_end: 7,
             },
@@ -126,6 +507,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn changed_files() -> Result<()> {
+        let repo = RepoFixture::new()?.write("foo.rs", "test_files/git/added/foo.rs")?;
+        fs::write(repo.path().join("clippy.toml"), "msrv = \"1.40\"\n")?;
+
+        // The sections only have Rust files, the changed files have every file
+        let git = Git::default();
+        assert_eq!(1, git.sections(repo.path())?.len());
+        assert_eq!(
+            vec![
+                repo.absolute_path("clippy.toml")?,
+                repo.absolute_path("foo.rs")?
+            ],
+            git.changed_files(repo.path())?
+        );
+        Ok(())
+    }
+
     #[test]
     fn untracked_files() -> Result<()> {
         let repo = RepoFixture::new()?
@@ -134,12 +533,12 @@ mod tests {
 
         let expected = vec![
             Section {
-                file_name: get_absolute_file_path(&"foo.rs")?,
+                file_name: repo.absolute_path("foo.rs")?,
                 line_start: 1,
                 line_end: 7,
             },
             Section {
-                file_name: get_absolute_file_path(&"inside/some/dir/bar.rs")?,
+                file_name: repo.absolute_path("inside/some/dir/bar.rs")?,
                 line_start: 1,
                 line_end: 5,
             },
@@ -151,6 +550,162 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn hidden_files() -> Result<()> {
+        let repo = RepoFixture::new()?
+            .write("foo.rs", "test_files/git/added/foo.rs")?
+            .write("generated/bar.rs", "test_files/git/added/bar.rs")?
+            .write("vendor/bar.rs", "test_files/git/added/bar.rs")?
+            .write("kept/bar.rs", "test_files/git/added/bar.rs")?;
+        fs::write(
+            repo.path().join(".gitattributes"),
+            "generated/** linguist-generated=true
+vendor/** export-ignore
+kept/** linguist-generated=false
+",
+        )?;
+
+        let expected = vec![
+            Section {
+                file_name: repo.absolute_path("foo.rs")?,
+                line_start: 1,
+                line_end: 7,
+            },
+            Section {
+                file_name: repo.absolute_path("kept/bar.rs")?,
+                line_start: 1,
+                line_end: 5,
+            },
+        ];
+        assert_eq!(expected, Git::default().sections(repo.path())?);
+        Ok(())
+    }
+
+    #[test]
+    fn from_subdirectory() -> Result<()> {
+        let repo = RepoFixture::new()?
+            .write("foo.rs", "test_files/git/added/foo.rs")?
+            .write("inside/some/dir/bar.rs", "test_files/git/added/bar.rs")?;
+
+        // The sections and the root don't depend on the directory scout runs from
+        let git = Git::default();
+        let nested = repo.path().join("inside/some");
+        assert_eq!(git.sections(repo.path())?, git.sections(&nested)?);
+        assert_eq!(git.root(repo.path())?, git.root(&nested)?);
+        Ok(())
+    }
+
+    #[test]
+    fn worktree_root() -> Result<()> {
+        let repo = RepoFixture::new()?;
+        let dir = TempDir::new()?;
+        let path = dir.path().join("wt");
+        repo.repo.worktree("wt", &path, None)?;
+
+        let git = Git::default();
+        assert_eq!(fs::canonicalize(&path)?, git.root(&path)?);
+        assert_eq!(fs::canonicalize(repo.path())?, git.root(repo.path())?);
+        // Without a git executable
+        assert_eq!(fs::canonicalize(&path)?, super::discovered_root(&path)?);
+        assert!(super::gitdir_pointer(&path.join(".git")).is_some_and(|gitdir| gitdir.is_dir()));
+        assert_eq!(None, super::gitdir_pointer(&repo.path().join(".git")));
+        Ok(())
+    }
+
+    #[test]
+    fn worktree_sections() -> Result<()> {
+        let repo = RepoFixture::new()?;
+        let dir = TempDir::new()?;
+        let path = dir.path().join("wt");
+        repo.repo.worktree("wt", &path, None)?;
+        fs::copy("test_files/git/added/foo.rs", path.join("foo.rs"))?;
+
+        let sections = Git::default().sections(&path)?;
+        assert_eq!(1, sections.len());
+        assert_eq!(
+            path.join("foo.rs").to_string_lossy(),
+            sections[0].file_name.as_str()
+        );
+        // The main working tree has no change
+        assert!(Git::default().sections(repo.path())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn bare_repository() -> Result<()> {
+        let repo = RepoFixture::new()?.write("foo.rs", "test_files/git/added/foo.rs")?;
+        let dir = TempDir::new()?;
+        let bare = dir.path().join("bare.git");
+        let status = std::process::Command::new("git")
+            .args(["clone", "--quiet", "--bare"])
+            .arg(repo.path())
+            .arg(&bare)
+            .status()?;
+        assert!(status.success());
+        let work_tree = dir.path().join("export");
+        fs::create_dir_all(&work_tree)?;
+        fs::copy(repo.path().join("foo.rs"), work_tree.join("foo.rs"))?;
+
+        let opened = super::open_with(repo.path(), Some(&bare), Some(&work_tree))?;
+        assert_eq!(
+            Some(fs::canonicalize(&work_tree)?),
+            opened.workdir().map(fs::canonicalize).transpose()?
+        );
+        // Without an index, only the files changed since the commit are in the diff
+        let (_, files) = Git::default().target_files(&opened, "HEAD")?;
+        let paths: Vec<_> = files.into_iter().map(|file| file.new_path).collect();
+        assert_eq!(vec![Some("foo.rs".to_string())], paths);
+        let discovered = super::open_with(repo.path(), None, None)?;
+        assert_eq!(repo.repo.path(), discovered.path());
+        Ok(())
+    }
+
+    #[test]
+    fn broken_worktree() -> Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("wt");
+        fs::create_dir_all(path.join("src"))?;
+        fs::write(path.join(".git"), "gitdir: ../moved/.git/worktrees/wt\n")?;
+
+        assert_eq!(
+            Some(path.join("../moved/.git/worktrees/wt")),
+            super::gitdir_pointer(&path.join(".git"))
+        );
+        assert!(matches!(
+            Git::default().root(path.join("src")),
+            Err(Error::BrokenWorktree(dot_git, _)) if dot_git == path.join(".git").display().to_string()
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn submodule_superproject_root() -> Result<()> {
+        let superproject = RepoFixture::new()?;
+        let submodule = RepoFixture::new()?;
+        let status = std::process::Command::new("git")
+            .current_dir(superproject.path())
+            .args([
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "--quiet",
+            ])
+            .arg(submodule.path())
+            .arg("sub")
+            .status()?;
+        assert!(status.success());
+
+        let sub = superproject.path().join("sub");
+        assert_eq!(fs::canonicalize(&sub)?, Git::default().root(&sub)?);
+        assert_eq!(
+            Some(fs::canonicalize(superproject.path())?),
+            Git::superproject_root(&sub)?
+        );
+        assert_eq!(None, Git::superproject_root(superproject.path())?);
+        Ok(())
+    }
+
     #[test]
     fn modified_files() -> Result<()> {
         let files = &["foo.rs", "bar.rs"];
@@ -164,22 +719,22 @@ mod tests {
 
         let expected = vec![
             Section {
-                file_name: get_absolute_file_path(&"bar.rs")?,
+                file_name: repo.absolute_path("bar.rs")?,
                 line_start: 1,
                 line_end: 2,
             },
             Section {
-                file_name: get_absolute_file_path(&"bar.rs")?,
+                file_name: repo.absolute_path("bar.rs")?,
                 line_start: 5,
                 line_end: 9,
             },
             Section {
-                file_name: get_absolute_file_path(&"foo.rs")?,
+                file_name: repo.absolute_path("foo.rs")?,
                 line_start: 3,
                 line_end: 4,
             },
             Section {
-                file_name: get_absolute_file_path(&"foo.rs")?,
+                file_name: repo.absolute_path("foo.rs")?,
                 line_start: 6,
                 line_end: 7,
             },
@@ -188,6 +743,102 @@ mod tests {
         let git = Git::default();
         let actual = git.sections(repo.path())?;
         assert_eq!(expected, actual);
+
+        // The added lines don't include the line after each change
+        let added: Vec<[u32; 2]> = git
+            .added_sections(repo.path())?
+            .iter()
+            .map(|section| [section.line_start, section.line_end])
+            .collect();
+        assert_eq!(vec![[1, 1], [5, 8], [3, 3], [6, 6]], added);
+        Ok(())
+    }
+
+    #[test]
+    fn streamed_files() -> Result<()> {
+        let files = &["foo.rs", "bar.rs"];
+        let repo = RepoFixture::new()?
+            .write("foo.rs", "test_files/git/modified/old/foo.rs")?
+            .write("bar.rs", "test_files/git/modified/old/bar.rs")?
+            .stage(files)?
+            .commit("master", files)?
+            .write("foo.rs", "test_files/git/modified/new/foo.rs")?
+            .write("bar.rs", "test_files/git/modified/new/bar.rs")?;
+        fs::write(repo.path().join("clippy.toml"), "msrv = \"1.40\"\n")?;
+
+        // A call per Rust file, with the sections of the whole diff
+        let git = Git::default();
+        let mut streamed = Vec::new();
+        git.stream_sections(repo.path(), |sections| {
+            streamed.push(sections);
+            Ok(())
+        })?;
+        assert_eq!(2, streamed.len());
+        assert!(streamed[0]
+            .iter()
+            .all(|section| section.file_name == repo.absolute_path("bar.rs").unwrap()));
+        assert_eq!(git.sections(repo.path())?, streamed.concat());
+
+        // An error stops the diff
+        let mut calls = 0;
+        let stopped = git.stream_sections(repo.path(), |_| {
+            calls += 1;
+            Err(Error::DiffTooLarge("stop".to_string()))
+        });
+        assert!(matches!(stopped, Err(Error::DiffTooLarge(_))));
+        assert_eq!(1, calls);
+        Ok(())
+    }
+
+    #[test]
+    fn ignored_changes() -> Result<()> {
+        let files = &["foo.rs"];
+        let repo = RepoFixture::new()?
+            .write("foo.rs", "test_files/git/modified/old/foo.rs")?
+            .stage(files)?
+            .commit("master", files)?;
+        let path = repo.path().join("foo.rs");
+        let old = fs::read_to_string(&path)?;
+        // Comments the file, and reindents every line
+        let new: String = old.lines().map(|line| format!("    {}\n", line)).collect();
+        fs::write(&path, format!("// Reindented\n{}", new))?;
+
+        assert!(!Git::default().sections(repo.path())?.is_empty());
+        let filter = ChangeFilter {
+            whitespace: true,
+            comments: true,
+        };
+        let git = Git::default().with_change_filter(filter);
+        assert!(git.sections(repo.path())?.is_empty());
+        assert_eq!(
+            vec![repo.absolute_path("foo.rs")?],
+            git.changed_files(repo.path())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pathspec() -> Result<()> {
+        let repo = RepoFixture::new()?
+            .write("api/foo.rs", "test_files/git/mixed/foo.rs")?
+            .write("core/foo.rs", "test_files/git/mixed/foo.rs")?
+            .stage(&["api/foo.rs", "core/foo.rs"])?;
+        let root = repo.path().to_path_buf();
+
+        let git = Git::default().with_pathspec(Pathspec::new(&root, vec!["api/**".to_string()]));
+        let files: Vec<_> = git
+            .sections(repo.path())?
+            .into_iter()
+            .map(|section| section.file_name)
+            .collect();
+        assert_eq!(vec![repo.absolute_path("api/foo.rs")?], files);
+        // The patterns are relative to their own directory
+        let git =
+            Git::default().with_pathspec(Pathspec::new(root.join("core"), vec![".".to_string()]));
+        assert_eq!(
+            vec![repo.absolute_path("core/foo.rs")?],
+            git.changed_files(repo.path())?
+        );
         Ok(())
     }
 
@@ -199,7 +850,7 @@ mod tests {
             .stage(&["foo.rs", "bar.txt"])?;
 
         let expected = vec![Section {
-            file_name: get_absolute_file_path(&"foo.rs")?,
+            file_name: repo.absolute_path("foo.rs")?,
             line_start: 1,
             line_end: 7,
         }];
@@ -221,12 +872,12 @@ mod tests {
 
         let expected = vec![
             Section {
-                file_name: get_absolute_file_path(&"foo.rs")?,
+                file_name: repo.absolute_path("foo.rs")?,
                 line_start: 3,
                 line_end: 4,
             },
             Section {
-                file_name: get_absolute_file_path(&"foo.rs")?,
+                file_name: repo.absolute_path("foo.rs")?,
                 line_start: 6,
                 line_end: 7,
             },
@@ -238,6 +889,78 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn targets() -> Result<()> {
+        let repo = RepoFixture::new()?.branch("other")?;
+        let head = repo.repo.head()?.target().unwrap();
+
+        let git = Git::with_targets(
+            vec!["HEAD".to_string(), "other".to_string()],
+            BaseMode::Union,
+        );
+        assert_eq!(
+            vec![format!("HEAD ({})", head), format!("other ({})", head)],
+            git.targets(repo.path())?
+        );
+        assert!(Git::with_target("missing".to_string())
+            .targets(repo.path())
+            .is_err());
+        assert_eq!(Some(head.to_string()), super::head_commit(repo.path()));
+        Ok(())
+    }
+
+    #[test]
+    fn several_branches() -> Result<()> {
+        let repo = RepoFixture::new()?
+            .branch("other")?
+            .write("foo.rs", "test_files/git/modified/old/foo.rs")?
+            .stage(&["foo.rs"])?
+            .commit("other", &["foo.rs"])?
+            .write("foo.rs", "test_files/git/modified/new/foo.rs")?;
+
+        let union = Git::with_targets(
+            vec!["HEAD".to_string(), "other".to_string()],
+            BaseMode::Union,
+        );
+        let expected = vec![
+            Section {
+                file_name: repo.absolute_path("foo.rs")?,
+                line_start: 1,
+                line_end: 8,
+            },
+            Section {
+                file_name: repo.absolute_path("foo.rs")?,
+                line_start: 3,
+                line_end: 4,
+            },
+            Section {
+                file_name: repo.absolute_path("foo.rs")?,
+                line_start: 6,
+                line_end: 7,
+            },
+        ];
+        assert_eq!(expected, union.sections(repo.path())?);
+
+        let intersection = Git::with_targets(
+            vec!["HEAD".to_string(), "other".to_string()],
+            BaseMode::Intersection,
+        );
+        let expected = vec![
+            Section {
+                file_name: repo.absolute_path("foo.rs")?,
+                line_start: 3,
+                line_end: 4,
+            },
+            Section {
+                file_name: repo.absolute_path("foo.rs")?,
+                line_start: 6,
+                line_end: 7,
+            },
+        ];
+        assert_eq!(expected, intersection.sections(repo.path())?);
+        Ok(())
+    }
+
     struct RepoFixture {
         dir: TempDir,
         repo: Repository,
@@ -315,5 +1038,10 @@ mod tests {
         pub fn path(&self) -> &Path {
             self.dir.path()
         }
+
+        pub fn absolute_path<P: AsRef<Path>>(&self, path: P) -> Result<String> {
+            let root = self.repo.path().parent().unwrap();
+            Ok(root.join(path).to_str().unwrap().to_string())
+        }
     }
 }
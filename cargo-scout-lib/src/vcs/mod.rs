@@ -1,6 +1,11 @@
+pub mod blame;
 pub mod git;
+pub mod patch;
+pub mod pathspec;
 use crate::error::Error;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 pub trait VCS {
     #[allow(clippy::missing_errors_doc)]
@@ -11,6 +16,124 @@ pub trait VCS {
     fn sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
     where
         P: AsRef<Path>;
+
+    /// The lines the diff adds, as sections with an inclusive end.
+    ///
+    /// The sections of a diff also cover the line after an addition
+    /// and the line before a removal, these don't. Defaults to the sections.
+    #[allow(clippy::missing_errors_doc)]
+    fn added_sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.sections(repo_path)
+    }
+
+    /// The files the diff changes, Rust files or not, joined to the repository root.
+    ///
+    /// Defaults to the files of the sections.
+    #[allow(clippy::missing_errors_doc)]
+    fn changed_files<P>(&self, repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut files: Vec<String> = self
+            .sections(repo_path)?
+            .into_iter()
+            .map(|s| s.file_name)
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Calls `on_file` with the sections of each changed file as soon as they are computed,
+    /// so that the members they change can be linted while the rest of the diff is.
+    ///
+    /// An error of `on_file` stops the diff. Defaults to the sections, once they all are computed.
+    #[allow(clippy::missing_errors_doc)]
+    fn stream_sections<P, F>(&self, repo_path: P, on_file: F) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(Vec<Section>) -> Result<(), Error>,
+    {
+        sections_by_file(self.sections(repo_path)?)
+            .into_iter()
+            .try_for_each(on_file)
+    }
+
+    /// Describes what the diff is computed against, such as resolved branches.
+    #[allow(clippy::missing_errors_doc)]
+    fn targets<P>(&self, _repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Vec::new())
+    }
+}
+
+impl<V: VCS> VCS for &V {
+    fn root<P>(&self, repo_path: P) -> Result<PathBuf, Error>
+    where
+        P: AsRef<Path>,
+    {
+        (*self).root(repo_path)
+    }
+
+    fn sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        (*self).sections(repo_path)
+    }
+
+    fn added_sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        (*self).added_sections(repo_path)
+    }
+
+    fn changed_files<P>(&self, repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        (*self).changed_files(repo_path)
+    }
+
+    fn stream_sections<P, F>(&self, repo_path: P, on_file: F) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(Vec<Section>) -> Result<(), Error>,
+    {
+        (*self).stream_sections(repo_path, on_file)
+    }
+
+    fn targets<P>(&self, repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        (*self).targets(repo_path)
+    }
+}
+
+/// `sections` grouped by file, consecutive sections of the same file together.
+pub(crate) fn sections_by_file(sections: Vec<Section>) -> Vec<Vec<Section>> {
+    let mut files: Vec<Vec<Section>> = Vec::new();
+    for section in sections {
+        match files.last_mut() {
+            Some(file) if file[0].file_name == section.file_name => file.push(section),
+            _ => files.push(vec![section]),
+        }
+    }
+    files
+}
+
+/// The sections of the diff `vcs` computes for the repository holding `path`,
+/// as scout lints them, without the limits of its diff gate.
+#[allow(clippy::missing_errors_doc)]
+pub fn sections(vcs: &impl VCS, path: impl AsRef<Path>) -> Result<Vec<Section>, Error> {
+    vcs.sections(vcs.root(path)?)
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -19,3 +142,271 @@ pub struct Section {
     pub line_start: u32,
     pub line_end: u32,
 }
+
+/// The lines to scout in some files, as inclusive `[start, end]` ranges.
+pub type LineRanges = BTreeMap<PathBuf, Vec<[u32; 2]>>;
+
+/// Treats a list of files as a diff, such as the buffers an editor knows changed.
+///
+/// Files without line ranges are changed as a whole.
+/// Relative paths are relative to `current_dir`.
+#[must_use]
+pub fn sections_for_files(
+    files: &[PathBuf],
+    ranges: Option<&LineRanges>,
+    current_dir: &Path,
+) -> Vec<Section> {
+    let mut sections = Vec::new();
+    for file in files {
+        let absolute = current_dir.join(file);
+        let file_name = std::fs::canonicalize(&absolute)
+            .unwrap_or(absolute)
+            .to_string_lossy()
+            .into_owned();
+        match ranges.and_then(|r| r.get(file)) {
+            Some(lines) => sections.extend(lines.iter().map(|[line_start, line_end]| Section {
+                file_name: file_name.clone(),
+                line_start: *line_start,
+                line_end: *line_end,
+            })),
+            None => sections.push(Section {
+                file_name,
+                line_start: 1,
+                line_end: u32::MAX,
+            }),
+        }
+    }
+    sections
+}
+
+/// A directory outside of any repository, like an exported tarball.
+///
+/// It has no diff, so it can only be scouted without one.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Unversioned {
+    root: PathBuf,
+}
+
+impl Unversioned {
+    /// The directory at `root`, which paths are relative to.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl VCS for Unversioned {
+    fn root<P>(&self, _repo_path: P) -> Result<PathBuf, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(self.root.clone())
+    }
+
+    fn sections<P>(&self, _repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Err(Error::NotARepository(self.root.display().to_string()))
+    }
+}
+
+/// How the sections computed against several bases are combined.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum BaseMode {
+    /// A line is changed if it is changed against any of the bases
+    #[default]
+    Union,
+    /// A line is changed if it is changed against every base
+    Intersection,
+}
+
+impl FromStr for BaseMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "union" => Ok(Self::Union),
+            "intersection" => Ok(Self::Intersection),
+            _ => Err(format!(
+                "unknown base mode {}, expected union or intersection",
+                s
+            )),
+        }
+    }
+}
+
+/// Combines the sections computed against several bases.
+///
+/// The resulting sections are sorted by file name and start line.
+#[must_use]
+pub fn combine_sections(per_base: Vec<Vec<Section>>, mode: BaseMode) -> Vec<Section> {
+    let base_count = per_base.len();
+    let mut files: BTreeMap<String, Vec<Vec<[u32; 2]>>> = BTreeMap::new();
+    for (index, sections) in per_base.into_iter().enumerate() {
+        for section in sections {
+            let ranges = files
+                .entry(section.file_name)
+                .or_insert_with(|| vec![Vec::new(); base_count]);
+            ranges[index].push([section.line_start, section.line_end]);
+        }
+    }
+
+    let mut combined = Vec::new();
+    for (file_name, ranges) in files {
+        let mut ranges = match mode {
+            BaseMode::Union => {
+                let mut union: Vec<[u32; 2]> = ranges.into_iter().flatten().collect();
+                union.sort_unstable();
+                union.dedup();
+                union
+            }
+            BaseMode::Intersection => ranges
+                .into_iter()
+                .reduce(|acc, next| intersect(&acc, &next))
+                .unwrap_or_default(),
+        };
+        ranges.sort_unstable();
+        combined.extend(ranges.into_iter().map(|[line_start, line_end]| Section {
+            file_name: file_name.clone(),
+            line_start,
+            line_end,
+        }));
+    }
+    combined
+}
+
+fn intersect(a: &[[u32; 2]], b: &[[u32; 2]]) -> Vec<[u32; 2]> {
+    let mut intersection = Vec::new();
+    for [a_start, a_end] in a {
+        for [b_start, b_end] in b {
+            let start = *a_start.max(b_start);
+            let end = *a_end.min(b_end);
+            if start <= end {
+                intersection.push([start, end]);
+            }
+        }
+    }
+    intersection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        combine_sections, sections_by_file, sections_for_files, BaseMode, LineRanges, Section,
+        Unversioned, VCS,
+    };
+    use crate::error::Error;
+    use std::path::{Path, PathBuf};
+
+    fn section(file_name: &str, line_start: u32, line_end: u32) -> Section {
+        Section {
+            file_name: file_name.to_string(),
+            line_start,
+            line_end,
+        }
+    }
+
+    #[test]
+    fn test_unversioned() {
+        let vcs = Unversioned::new("/export");
+        assert_eq!(PathBuf::from("/export"), vcs.root("/export/src").unwrap());
+        assert!(matches!(
+            vcs.sections("/export"),
+            Err(Error::NotARepository(root)) if root == "/export"
+        ));
+        assert!(vcs.targets("/export").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sections() -> Result<(), Error> {
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("change.patch");
+        std::fs::write(&path, "--- a/foo.rs\n+++ b/foo.rs\n@@ -1,0 +2 @@\n+a\n")?;
+        let patch = super::patch::Patch::from_path(&path);
+        let root = patch.root(dir.path())?;
+        assert_eq!(
+            vec![section(&root.join("foo.rs").to_string_lossy(), 2, 3)],
+            super::sections(&patch, dir.path())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sections_by_file() {
+        let sections = vec![
+            section("bar.rs", 3, 4),
+            section("foo.rs", 1, 5),
+            section("foo.rs", 10, 12),
+        ];
+        assert_eq!(
+            vec![
+                vec![section("bar.rs", 3, 4)],
+                vec![section("foo.rs", 1, 5), section("foo.rs", 10, 12)]
+            ],
+            sections_by_file(sections)
+        );
+        assert!(sections_by_file(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_combine_union() {
+        let main = vec![section("foo.rs", 1, 5), section("bar.rs", 3, 4)];
+        let release = vec![section("foo.rs", 1, 5), section("foo.rs", 10, 12)];
+        assert_eq!(
+            vec![
+                section("bar.rs", 3, 4),
+                section("foo.rs", 1, 5),
+                section("foo.rs", 10, 12)
+            ],
+            combine_sections(vec![main, release], BaseMode::Union)
+        );
+    }
+
+    #[test]
+    fn test_combine_intersection() {
+        let main = vec![section("foo.rs", 1, 5), section("bar.rs", 3, 4)];
+        let release = vec![section("foo.rs", 4, 12), section("foo.rs", 20, 22)];
+        assert_eq!(
+            vec![section("foo.rs", 4, 5)],
+            combine_sections(vec![main, release], BaseMode::Intersection)
+        );
+    }
+
+    #[test]
+    fn test_combine_single_base() {
+        let main = vec![section("foo.rs", 1, 5), section("foo.rs", 7, 8)];
+        for mode in &[BaseMode::Union, BaseMode::Intersection] {
+            assert_eq!(main, combine_sections(vec![main.clone()], *mode));
+        }
+    }
+
+    #[test]
+    fn test_sections_for_files() {
+        let files = vec![
+            PathBuf::from("src/lib.rs"),
+            PathBuf::from("/elsewhere/main.rs"),
+        ];
+        let mut ranges = LineRanges::new();
+        ranges.insert(PathBuf::from("src/lib.rs"), vec![[3, 4], [10, 10]]);
+        assert_eq!(
+            vec![
+                section("/repo/src/lib.rs", 3, 4),
+                section("/repo/src/lib.rs", 10, 10),
+                section("/elsewhere/main.rs", 1, u32::MAX)
+            ],
+            sections_for_files(&files, Some(&ranges), Path::new("/repo"))
+        );
+        assert_eq!(
+            vec![section("/repo/src/lib.rs", 1, u32::MAX)],
+            sections_for_files(&files[..1], None, Path::new("/repo"))
+        );
+    }
+
+    #[test]
+    fn test_base_mode_from_str() {
+        assert_eq!(Ok(BaseMode::Union), "union".parse());
+        assert_eq!(Ok(BaseMode::Intersection), "intersection".parse());
+        assert!("both".parse::<BaseMode>().is_err());
+    }
+}
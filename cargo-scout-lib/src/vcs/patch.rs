@@ -0,0 +1,1204 @@
+use super::{Section, VCS};
+use crate::error::Error;
+use crate::linter::Lint;
+use crate::utils::{decode_output, text_lines, TextLines};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+
+/// A hunk of a unified diff, without context lines.
+///
+/// Like git does, a hunk that only removes lines starts at the line
+/// before the removal, and a hunk that only adds lines starts at the line
+/// before the addition in the original file.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+}
+
+/// The hunks a parser leaves out, so that changes not touching the logic
+/// don't make the lints around them reported.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct ChangeFilter {
+    /// Leaves out the hunks only changing whitespace, like reindented or reflowed code
+    pub whitespace: bool,
+    /// Leaves out the hunks only adding comments and blank lines.
+    /// Doc comments aren't comments, rustdoc and clippy lint them
+    pub comments: bool,
+}
+
+impl ChangeFilter {
+    /// Whether the hunk replacing the `removed` lines with the `added` ones is left out.
+    #[must_use]
+    pub fn ignores(&self, removed: &[&str], added: &[&str]) -> bool {
+        (self.whitespace && without_whitespace(removed) == without_whitespace(added))
+            || (self.comments && is_comment_only(added))
+    }
+}
+
+fn without_whitespace(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .flat_map(|line| line.chars())
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// Whether the lines are comments and blank lines, with at least one comment.
+///
+/// Without context lines, a line in a block comment the lines don't open is taken for code.
+fn is_comment_only(lines: &[&str]) -> bool {
+    let mut in_block = false;
+    let mut comments = 0;
+    for line in lines.iter().map(|line| line.trim()) {
+        if !in_block && line.is_empty() {
+            continue;
+        }
+        let rest = if in_block {
+            Some(line)
+        } else if is_doc_comment(line) {
+            return false;
+        } else if line.starts_with("//") {
+            comments += 1;
+            continue;
+        } else {
+            line.strip_prefix("/*")
+        };
+        let Some(rest) = rest else {
+            return false;
+        };
+        comments += 1;
+        in_block = match rest.split_once("*/") {
+            // Code may follow the end of the comment
+            Some((_, after)) if !after.trim().is_empty() => return false,
+            Some(_) => false,
+            None => true,
+        };
+    }
+    comments > 0
+}
+
+fn is_doc_comment(line: &str) -> bool {
+    (line.starts_with("///") && !line.starts_with("////"))
+        || line.starts_with("//!")
+        || line.starts_with("/*!")
+        || (line.starts_with("/**") && !line.starts_with("/***") && !line.starts_with("/**/"))
+}
+
+/// The changes made to a file by a unified diff.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct FileDiff {
+    /// The path before the change, `None` for added files
+    pub old_path: Option<String>,
+    /// The path after the change, `None` for deleted files
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Why a part of a diff couldn't be parsed.
+#[derive(Debug, PartialEq, Clone, Copy, thiserror::Error)]
+pub enum DiffParseErrorKind {
+    #[error("malformed hunk header")]
+    MalformedHunkHeader,
+    #[error("hunk outside of a file")]
+    HunkOutsideFile,
+    #[error("the hunk has fewer lines than its header says")]
+    TruncatedHunk,
+    #[error("the hunk has more lines than its header says")]
+    HunkTooLong,
+    #[error("line number overflow")]
+    LineOverflow,
+}
+
+/// A malformed hunk, `line` is the 1-based line of its header.
+#[derive(Debug, PartialEq, Clone, Copy, thiserror::Error)]
+#[error("line {line}: {kind}")]
+pub struct DiffParseError {
+    pub line: usize,
+    pub kind: DiffParseErrorKind,
+}
+
+/// The result of a lenient parse: what could be parsed,
+/// and the hunks that were skipped.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ParsedDiff {
+    pub files: Vec<FileDiff>,
+    pub errors: Vec<DiffParseError>,
+}
+
+impl ParsedDiff {
+    /// Returns the sections of the Rust files added or modified by the diff.
+    ///
+    /// File names are joined to `root`, the directory the diff applies to.
+    #[must_use]
+    pub fn sections(&self, root: impl AsRef<Path>) -> Vec<Section> {
+        file_sections(&self.files, root.as_ref())
+    }
+}
+
+/// A unified diff parser, such as `git diff` or `git format-patch` output.
+///
+/// The parser never panics, malformed hunks are reported and skipped.
+pub struct Parser<'a> {
+    diff: &'a str,
+    filter: ChangeFilter,
+}
+
+type Lines<'a> = std::iter::Peekable<std::iter::Enumerate<TextLines<'a>>>;
+
+impl<'a> Parser<'a> {
+    #[must_use]
+    pub fn new(diff: &'a str) -> Self {
+        Self {
+            diff,
+            filter: ChangeFilter::default(),
+        }
+    }
+
+    /// Leaves out the hunks `filter` ignores.
+    #[must_use]
+    pub fn with_filter(mut self, filter: ChangeFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Parses the diff, skipping the malformed hunks.
+    #[must_use]
+    pub fn parse(&self) -> ParsedDiff {
+        let mut parsed = ParsedDiff::default();
+        let mut lines = text_lines(self.diff).enumerate().peekable();
+        while let Some((index, line)) = lines.next() {
+            if let Some(old_path) = line.strip_prefix("--- ") {
+                // A `---` line alone separates the commit message from the diffstat
+                let Some(new_path) = lines.peek().and_then(|(_, next)| next.strip_prefix("+++ "))
+                else {
+                    continue;
+                };
+                parsed.files.push(FileDiff {
+                    old_path: diff_path(old_path),
+                    new_path: diff_path(new_path),
+                    hunks: Vec::new(),
+                });
+                lines.next();
+            } else if line.starts_with("@@ ") {
+                let hunks = match parsed.files.last_mut() {
+                    None => Err(DiffParseErrorKind::HunkOutsideFile),
+                    Some(file) => hunk_header(line)
+                        .and_then(|header| hunk_body(header, &mut lines, self.filter))
+                        .map(|hunks| file.hunks.extend(hunks)),
+                };
+                if let Err(kind) = hunks {
+                    parsed.errors.push(DiffParseError {
+                        line: index + 1,
+                        kind,
+                    });
+                }
+            }
+        }
+        parsed
+    }
+
+    /// Returns the changes made to each file.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn files(&self) -> Result<Vec<FileDiff>, DiffParseError> {
+        let parsed = self.parse();
+        match parsed.errors.first() {
+            Some(error) => Err(*error),
+            None => Ok(parsed.files),
+        }
+    }
+
+    /// Returns the sections of the Rust files added or modified by the diff.
+    ///
+    /// File names are joined to `root`, the directory the diff applies to.
+    /// Use `parse` to skip the malformed hunks instead of failing.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn sections(&self, root: impl AsRef<Path>) -> Result<Vec<Section>, DiffParseError> {
+        Ok(file_sections(&self.files()?, root.as_ref()))
+    }
+}
+
+/// Parses the lines of a hunk, and splits them into hunks without context lines.
+///
+/// The hunk is consumed up to the line counts of its header,
+/// a line that can't belong to a hunk is left for the caller.
+/// The hunks `filter` ignores are left out.
+fn hunk_body<'a>(
+    header: [u32; 4],
+    lines: &mut Lines<'a>,
+    filter: ChangeFilter,
+) -> Result<Vec<Hunk>, DiffParseErrorKind> {
+    use DiffParseErrorKind::{HunkTooLong, LineOverflow, TruncatedHunk};
+    let [mut old_line, old_count, mut new_line, new_count] = header;
+    let (mut old_remaining, mut new_remaining) = (old_count, new_count);
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk> = None;
+    let (mut removed, mut added): (Vec<&'a str>, Vec<&'a str>) = (Vec::new(), Vec::new());
+    while old_remaining > 0 || new_remaining > 0 {
+        let (origin, text) = match lines.peek() {
+            None => return Err(TruncatedHunk),
+            Some((_, line)) => (line.chars().next(), line.get(1..).unwrap_or_default()),
+        };
+        match origin {
+            // Some tools strip the trailing whitespace of empty context lines
+            Some('-' | '+' | ' ' | '\\') | None => {
+                lines.next();
+            }
+            _ => return Err(TruncatedHunk),
+        }
+        match origin {
+            Some('-') => {
+                old_remaining = old_remaining.checked_sub(1).ok_or(HunkTooLong)?;
+                let hunk = current.get_or_insert_with(|| hunk_at(old_line, new_line));
+                if hunk.old_lines == 0 {
+                    hunk.old_start = old_line;
+                }
+                hunk.old_lines += 1;
+                removed.push(text);
+                old_line = old_line.checked_add(1).ok_or(LineOverflow)?;
+            }
+            Some('+') => {
+                new_remaining = new_remaining.checked_sub(1).ok_or(HunkTooLong)?;
+                let hunk = current.get_or_insert_with(|| hunk_at(old_line, new_line));
+                if hunk.new_lines == 0 {
+                    hunk.new_start = new_line;
+                }
+                hunk.new_lines += 1;
+                added.push(text);
+                new_line = new_line.checked_add(1).ok_or(LineOverflow)?;
+            }
+            Some('\\') => {}
+            _ => {
+                old_remaining = old_remaining.checked_sub(1).ok_or(HunkTooLong)?;
+                new_remaining = new_remaining.checked_sub(1).ok_or(HunkTooLong)?;
+                hunks.extend(current.take().filter(|_| !filter.ignores(&removed, &added)));
+                removed.clear();
+                added.clear();
+                old_line = old_line.checked_add(1).ok_or(LineOverflow)?;
+                new_line = new_line.checked_add(1).ok_or(LineOverflow)?;
+            }
+        }
+    }
+    hunks.extend(current.take().filter(|_| !filter.ignores(&removed, &added)));
+    Ok(hunks)
+}
+
+/// A hunk in progress, for which no line has been removed or added yet.
+fn hunk_at(old_line: u32, new_line: u32) -> Hunk {
+    Hunk {
+        old_start: old_line.saturating_sub(1),
+        old_lines: 0,
+        new_start: new_line.saturating_sub(1),
+        new_lines: 0,
+    }
+}
+
+/// Parses `@@ -old_start[,old_count] +new_start[,new_count] @@`,
+/// and returns the first line of each range along with its count.
+///
+/// Ranges whose last line doesn't fit in a `u32` are rejected.
+fn hunk_header(line: &str) -> Result<[u32; 4], DiffParseErrorKind> {
+    use DiffParseErrorKind::{LineOverflow, MalformedHunkHeader};
+    let mut ranges = line
+        .strip_prefix("@@ -")
+        .and_then(|ranges| ranges.split(" @@").next())
+        .ok_or(MalformedHunkHeader)?
+        .split(" +");
+    let parse_range = |range: Option<&str>| -> Result<[u32; 2], DiffParseErrorKind> {
+        let mut parts = range.ok_or(MalformedHunkHeader)?.splitn(2, ',');
+        let start: u32 = parts
+            .next()
+            .and_then(|start| start.parse().ok())
+            .ok_or(MalformedHunkHeader)?;
+        let count: u32 = match parts.next() {
+            Some(count) => count.parse().map_err(|_| MalformedHunkHeader)?,
+            None => 1,
+        };
+        // An empty range starts at the line before it
+        let first_line = if count == 0 {
+            start.checked_add(1).ok_or(LineOverflow)?
+        } else {
+            start
+        };
+        first_line.checked_add(count).ok_or(LineOverflow)?;
+        Ok([first_line, count])
+    };
+    let [old_start, old_count] = parse_range(ranges.next())?;
+    let [new_start, new_count] = parse_range(ranges.next())?;
+    if ranges.next().is_some() {
+        return Err(MalformedHunkHeader);
+    }
+    Ok([old_start, old_count, new_start, new_count])
+}
+
+/// Strips the `a/` and `b/` prefixes and trailing timestamps from diff paths.
+fn diff_path(path: &str) -> Option<String> {
+    let path = path.split('\t').next().unwrap_or(path).trim_end();
+    if path == "/dev/null" {
+        return None;
+    }
+    let unquoted = unquote(path);
+    let path = unquoted.as_deref().unwrap_or(path);
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Git quotes the paths with unusual characters, and escapes them C style.
+/// Non ASCII characters are escaped as octal bytes.
+fn unquote(path: &str) -> Option<String> {
+    let quoted = path.strip_prefix('"')?.strip_suffix('"')?;
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.bytes();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let escaped = chars.next()?;
+        bytes.push(match escaped {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'v' => 0x0b,
+            b'f' => 0x0c,
+            b'r' => b'\r',
+            b'0'..=b'3' => {
+                let digits = [escaped, chars.next()?, chars.next()?];
+                u8::from_str_radix(std::str::from_utf8(&digits).ok()?, 8).ok()?
+            }
+            other => other,
+        });
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+pub(crate) fn file_sections(files: &[FileDiff], root: &Path) -> Vec<Section> {
+    rust_hunks(files, root)
+        .map(|(file_name, hunk)| Section {
+            file_name,
+            line_start: hunk.new_start,
+            line_end: hunk.new_start + hunk.new_lines,
+        })
+        .collect()
+}
+
+/// The lines the hunks of the Rust files add, as sections with an inclusive end.
+///
+/// Unlike `file_sections`, the line after an addition and the line before a removal
+/// aren't included, and hunks only removing lines have no section.
+pub(crate) fn added_sections(files: &[FileDiff], root: &Path) -> Vec<Section> {
+    rust_hunks(files, root)
+        .filter(|(_, hunk)| hunk.new_lines > 0)
+        .map(|(file_name, hunk)| Section {
+            file_name,
+            line_start: hunk.new_start,
+            line_end: hunk.new_start + hunk.new_lines - 1,
+        })
+        .collect()
+}
+
+/// The files the diff adds, modifies or deletes, whatever their language, joined to `root`.
+pub(crate) fn changed_files(files: &[FileDiff], root: &Path) -> Vec<String> {
+    let mut changed: Vec<String> = files
+        .iter()
+        .filter_map(|file| file.new_path.as_ref().or(file.old_path.as_ref()))
+        .map(|path| root.join(path).to_string_lossy().to_string())
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+/// The hunks of the Rust files added or modified by the diff, with their path joined to `root`.
+fn rust_hunks<'a>(
+    files: &'a [FileDiff],
+    root: &'a Path,
+) -> impl Iterator<Item = (String, &'a Hunk)> + 'a {
+    files
+        .iter()
+        .filter_map(|file| file.new_path.as_ref().map(|path| (path, &file.hunks)))
+        .filter(|(path, _)| path.ends_with(".rs"))
+        .flat_map(move |(path, hunks)| {
+            let file_name = root.join(path).to_string_lossy().to_string();
+            hunks.iter().map(move |hunk| (file_name.clone(), hunk))
+        })
+}
+
+/// A single patch file, used instead of a git diff.
+pub struct Patch {
+    path: PathBuf,
+    filter: ChangeFilter,
+}
+
+impl Patch {
+    #[must_use]
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            filter: ChangeFilter::default(),
+        }
+    }
+
+    /// Leaves out the hunks of the patch `filter` ignores.
+    #[must_use]
+    pub fn with_change_filter(mut self, filter: ChangeFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn files(&self) -> Result<Vec<FileDiff>, Error> {
+        self.parse(self.filter)
+    }
+
+    fn parse(&self, filter: ChangeFilter) -> Result<Vec<FileDiff>, Error> {
+        let patch = decode_output(std::fs::read(&self.path)?, &self.path.display().to_string());
+        Parser::new(&patch)
+            .with_filter(filter)
+            .files()
+            .map_err(|error| Error::DiffParse(self.path.display().to_string(), error))
+    }
+}
+
+impl VCS for Patch {
+    fn root<P>(&self, repo_path: P) -> Result<PathBuf, Error>
+    where
+        P: AsRef<Path>,
+    {
+        patch_root(repo_path)
+    }
+
+    fn sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(file_sections(&self.files()?, repo_path.as_ref()))
+    }
+
+    fn added_sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(added_sections(&self.files()?, repo_path.as_ref()))
+    }
+
+    fn changed_files<P>(&self, repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(changed_files(&self.files()?, repo_path.as_ref()))
+    }
+
+    fn targets<P>(&self, _repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(vec![self.path.display().to_string()])
+    }
+}
+
+/// Patches apply to the repository root, or to the current directory
+/// when there is no repository.
+fn patch_root(repo_path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    match super::git::open(repo_path.as_ref()) {
+        Ok(repo) => Ok(repo
+            .workdir()
+            .or_else(|| repo.path().parent())
+            .map_or_else(|| PathBuf::from("/"), Path::to_path_buf)),
+        Err(_) => Ok(repo_path.as_ref().to_path_buf()),
+    }
+}
+
+/// A series of patches, such as a `git format-patch` output directory,
+/// applied in order on top of each other.
+///
+/// The working tree is expected to contain the whole series,
+/// the sections of each patch are translated so that they match
+/// the lines of the working tree.
+pub struct PatchSeries {
+    patches: Vec<Patch>,
+}
+
+impl PatchSeries {
+    /// Reads every `.patch` file of a directory, ordered by file name.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|p| p.extension().is_some_and(|e| e == "patch"));
+        paths.sort();
+        Ok(Self::new(paths.into_iter().map(Patch::from_path).collect()))
+    }
+
+    #[must_use]
+    pub fn new(patches: Vec<Patch>) -> Self {
+        Self { patches }
+    }
+
+    /// Leaves out the hunks of every patch `filter` ignores.
+    #[must_use]
+    pub fn with_change_filter(self, filter: ChangeFilter) -> Self {
+        Self::new(
+            self.patches
+                .into_iter()
+                .map(|patch| patch.with_change_filter(filter))
+                .collect(),
+        )
+    }
+
+    #[must_use]
+    pub fn patches(&self) -> &[Patch] {
+        &self.patches
+    }
+
+    /// Returns the sections of each patch, in working tree lines.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn patch_sections(&self, root: impl AsRef<Path>) -> Result<Vec<Vec<Section>>, Error> {
+        let (files, unfiltered) = self.files()?;
+        Ok(series_sections(&files, &unfiltered, root.as_ref()))
+    }
+
+    /// The changes of each patch, and all of them, which the lines of the earlier patches
+    /// are translated through, whatever their filter leaves out.
+    #[allow(clippy::type_complexity)]
+    fn files(&self) -> Result<(Vec<Vec<FileDiff>>, Vec<Vec<FileDiff>>), Error> {
+        let unfiltered = self
+            .patches
+            .iter()
+            .map(|patch| patch.parse(ChangeFilter::default()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if self
+            .patches
+            .iter()
+            .all(|patch| patch.filter == ChangeFilter::default())
+        {
+            return Ok((unfiltered.clone(), unfiltered));
+        }
+        let files = self
+            .patches
+            .iter()
+            .map(Patch::files)
+            .collect::<Result<_, _>>()?;
+        Ok((files, unfiltered))
+    }
+
+    /// Splits lints by the patch that introduced them.
+    ///
+    /// The result has the lints of each patch, in the series order.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn split<'a>(
+        &'a self,
+        lints: &[Lint],
+        root: impl AsRef<Path>,
+    ) -> Result<Vec<(&'a Patch, Vec<Lint>)>, Error> {
+        Ok(self
+            .patches
+            .iter()
+            .zip(self.patch_sections(root)?)
+            .map(|(patch, sections)| (patch, crate::scout::lints_from_diff(lints, &sections)))
+            .collect())
+    }
+}
+
+impl VCS for PatchSeries {
+    fn root<P>(&self, repo_path: P) -> Result<PathBuf, Error>
+    where
+        P: AsRef<Path>,
+    {
+        patch_root(repo_path)
+    }
+
+    fn sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(self
+            .patch_sections(repo_path)?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    fn added_sections<P>(&self, repo_path: P) -> Result<Vec<Section>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let (files, unfiltered) = self.files()?;
+        Ok(series_files(&files, &unfiltered)
+            .iter()
+            .flat_map(|files| added_sections(files, repo_path.as_ref()))
+            .collect())
+    }
+
+    fn changed_files<P>(&self, repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let files: Vec<FileDiff> = self.files()?.0.into_iter().flatten().collect();
+        Ok(changed_files(&files, repo_path.as_ref()))
+    }
+
+    fn targets<P>(&self, _repo_path: P) -> Result<Vec<String>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(self
+            .patches
+            .iter()
+            .map(|patch| patch.path.display().to_string())
+            .collect())
+    }
+}
+
+fn series_sections(
+    series: &[Vec<FileDiff>],
+    unfiltered: &[Vec<FileDiff>],
+    root: &Path,
+) -> Vec<Vec<Section>> {
+    series_files(series, unfiltered)
+        .iter()
+        .map(|files| file_sections(files, root))
+        .collect()
+}
+
+/// The changes of each patch, moved to the lines they end up at in the working tree
+/// through every change of the later patches, those of `unfiltered`.
+fn series_files(series: &[Vec<FileDiff>], unfiltered: &[Vec<FileDiff>]) -> Vec<Vec<FileDiff>> {
+    series
+        .iter()
+        .enumerate()
+        .map(|(index, files)| {
+            files
+                .iter()
+                .filter_map(|file| {
+                    unfiltered[index + 1..]
+                        .iter()
+                        .try_fold(file.clone(), |file, later| translate(file, later))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Moves the hunks of `file` to the lines they end up at once `later`
+/// is applied. Lines rewritten by `later` are dropped.
+///
+/// Returns `None` if `later` deletes the file.
+fn translate(mut file: FileDiff, later: &[FileDiff]) -> Option<FileDiff> {
+    let Some(path) = file.new_path.clone() else {
+        return Some(file);
+    };
+    let Some(later_file) = later
+        .iter()
+        .find(|l| l.old_path.as_deref() == Some(path.as_str()))
+    else {
+        return Some(file);
+    };
+    file.new_path = Some(later_file.new_path.clone()?);
+    let mut hunks = Vec::new();
+    for hunk in &file.hunks {
+        if hunk.new_lines == 0 {
+            if let Some(line) = translate_line(hunk.new_start, &later_file.hunks) {
+                hunks.push(Hunk {
+                    new_start: line,
+                    ..*hunk
+                });
+            }
+            continue;
+        }
+        // Translate line by line, and group the lines that stayed contiguous
+        for line in hunk.new_start..hunk.new_start + hunk.new_lines {
+            let Some(line) = translate_line(line, &later_file.hunks) else {
+                continue;
+            };
+            match hunks.last_mut() {
+                Some(last) if last.new_lines > 0 && last.new_start + last.new_lines == line => {
+                    last.new_lines += 1;
+                }
+                _ => hunks.push(Hunk {
+                    new_start: line,
+                    new_lines: 1,
+                    ..*hunk
+                }),
+            }
+        }
+    }
+    file.hunks = hunks;
+    Some(file)
+}
+
+fn translate_line(line: u32, later_hunks: &[Hunk]) -> Option<u32> {
+    let mut offset = 0_i64;
+    for hunk in later_hunks {
+        let removes_line =
+            hunk.old_lines > 0 && hunk.old_start <= line && line < hunk.old_start + hunk.old_lines;
+        if removes_line {
+            return None;
+        }
+        let before_line = if hunk.old_lines == 0 {
+            hunk.old_start < line
+        } else {
+            hunk.old_start + hunk.old_lines <= line
+        };
+        if !before_line {
+            break;
+        }
+        offset += i64::from(hunk.new_lines) - i64::from(hunk.old_lines);
+    }
+    u32::try_from(i64::from(line) + offset).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        changed_files, series_sections, ChangeFilter, DiffParseError, DiffParseErrorKind, FileDiff,
+        Hunk, Parser, PatchSeries,
+    };
+    use crate::vcs::{Section, VCS};
+    use std::path::Path;
+
+    const PATCH: &str = "From 1234 Mon Sep 17 00:00:00 2001
+From: Jane <jane@example.com>
+Subject: [PATCH 1/2] Change foo
+
+---
+ src/foo.rs | 3 ++-
+ 1 file changed, 2 insertions(+), 1 deletion(-)
+
+diff --git a/src/foo.rs b/src/foo.rs
+index 83db48f..bf269f4 100644
+--- a/src/foo.rs
++++ b/src/foo.rs
+@@ -1,5 +1,6 @@
+ fn main() {
+-    let a = 1;
++    let a = 2;
++    let b = 3;
+     println!(\"{}\", a);
+ }
+
+@@ -10,3 +11,2 @@ fn other() {
+ fn other() {
+-    todo!();
+ }
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-hello
++hello world
+--
+2.25.0
+";
+
+    #[test]
+    fn test_parse_files() -> Result<(), DiffParseError> {
+        let files = Parser::new(PATCH).files()?;
+        assert_eq!(
+            vec![
+                FileDiff {
+                    old_path: Some("src/foo.rs".to_string()),
+                    new_path: Some("src/foo.rs".to_string()),
+                    hunks: vec![
+                        Hunk {
+                            old_start: 2,
+                            old_lines: 1,
+                            new_start: 2,
+                            new_lines: 2,
+                        },
+                        Hunk {
+                            old_start: 11,
+                            old_lines: 1,
+                            new_start: 11,
+                            new_lines: 0,
+                        },
+                    ],
+                },
+                FileDiff {
+                    old_path: Some("README.md".to_string()),
+                    new_path: Some("README.md".to_string()),
+                    hunks: vec![Hunk {
+                        old_start: 1,
+                        old_lines: 1,
+                        new_start: 1,
+                        new_lines: 1,
+                    }],
+                },
+            ],
+            files
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_windows_line_endings() -> Result<(), DiffParseError> {
+        let expected = Parser::new(PATCH).files()?;
+        let crlf = format!("\u{feff}{}", PATCH.replace('\n', "\r\n"));
+        assert_eq!(expected, Parser::new(&crlf).files()?);
+        // Diffs of CRLF files converted to CRLF once more
+        let doubled = PATCH.replace('\n', "\r\r\n");
+        assert_eq!(expected, Parser::new(&doubled).files()?);
+        // A byte order mark right before the first file header
+        let diff = "\u{feff}--- a/src/lib.rs\r\n+++ b/src/lib.rs\r\n@@ -1 +1 @@\r\n-a\r\n+b\r\n";
+        assert_eq!(
+            Some("src/lib.rs".to_string()),
+            Parser::new(diff).files()?[0].old_path
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_added_and_deleted_files() -> Result<(), DiffParseError> {
+        let diff = "--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1,2 @@\n+fn a() {}\n+fn b() {}\n--- a/src/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-fn c() {}\n";
+        let files = Parser::new(diff).files()?;
+        assert_eq!(None, files[0].old_path);
+        assert_eq!(
+            vec![Hunk {
+                old_start: 0,
+                old_lines: 0,
+                new_start: 1,
+                new_lines: 2,
+            }],
+            files[0].hunks
+        );
+        assert_eq!(None, files[1].new_path);
+
+        // Deleted files have no sections
+        assert_eq!(
+            vec![Section {
+                file_name: Path::new("/repo")
+                    .join("src/new.rs")
+                    .to_string_lossy()
+                    .to_string(),
+                line_start: 1,
+                line_end: 3,
+            }],
+            Parser::new(diff).sections("/repo")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_quoted_paths() -> Result<(), DiffParseError> {
+        let diff = "--- \"a/caf\\303\\251 \\\"1\\\".rs\"\n+++ \"b/caf\\303\\251 \\\"1\\\".rs\"\n@@ -1 +1 @@\n-a\n+b\n";
+        let files = Parser::new(diff).files()?;
+        assert_eq!(Some("café \"1\".rs".to_string()), files[0].new_path);
+        assert_eq!(files[0].old_path, files[0].new_path);
+        Ok(())
+    }
+
+    fn errors(diff: &str) -> Vec<DiffParseError> {
+        Parser::new(diff).parse().errors
+    }
+
+    fn error(line: usize, kind: DiffParseErrorKind) -> DiffParseError {
+        DiffParseError { line, kind }
+    }
+
+    #[test]
+    fn test_malformed_hunk_headers() {
+        for header in &[
+            "@@ -x +y @@",
+            "@@ -1",
+            "@@ -1,2 @@",
+            "@@ -1,a +1 @@",
+            "@@ -1 +1 +1 @@",
+            "@@ - +1 @@",
+        ] {
+            let diff = format!("--- a/foo.rs\n+++ b/foo.rs\n{}\n-a\n+b\n", header);
+            assert_eq!(
+                vec![error(3, DiffParseErrorKind::MalformedHunkHeader)],
+                errors(&diff),
+                "{}",
+                header
+            );
+        }
+    }
+
+    #[test]
+    fn test_hunk_outside_file() {
+        assert_eq!(
+            vec![error(1, DiffParseErrorKind::HunkOutsideFile)],
+            errors("@@ -1 +1 @@\n-a\n+b\n")
+        );
+    }
+
+    #[test]
+    fn test_truncated_hunks() {
+        assert_eq!(
+            vec![error(3, DiffParseErrorKind::TruncatedHunk)],
+            errors("--- a/foo.rs\n+++ b/foo.rs\n@@ -1,3 +1,3 @@\n-a\n+b\n")
+        );
+        // The next hunk isn't swallowed by the truncated one
+        let diff = "--- a/foo.rs\n+++ b/foo.rs\n@@ -1,3 +1,3 @@\n-a\n@@ -10 +10 @@\n-c\n+d\n";
+        let parsed = Parser::new(diff).parse();
+        assert_eq!(
+            vec![error(3, DiffParseErrorKind::TruncatedHunk)],
+            parsed.errors
+        );
+        assert_eq!(
+            vec![Hunk {
+                old_start: 10,
+                old_lines: 1,
+                new_start: 10,
+                new_lines: 1,
+            }],
+            parsed.files[0].hunks
+        );
+    }
+
+    #[test]
+    fn test_hunk_too_long() {
+        assert_eq!(
+            vec![error(3, DiffParseErrorKind::HunkTooLong)],
+            errors("--- a/foo.rs\n+++ b/foo.rs\n@@ -1,2 +1 @@\n-a\n+b\n+c\n")
+        );
+    }
+
+    #[test]
+    fn test_line_overflow() {
+        let max = u32::MAX;
+        assert_eq!(
+            vec![error(3, DiffParseErrorKind::LineOverflow)],
+            errors(&format!(
+                "--- a/foo.rs\n+++ b/foo.rs\n@@ -{} +1,2 @@\n-a\n+b\n",
+                max
+            ))
+        );
+        assert_eq!(
+            vec![error(3, DiffParseErrorKind::MalformedHunkHeader)],
+            errors("--- a/foo.rs\n+++ b/foo.rs\n@@ -99999999999 +1 @@\n-a\n+b\n")
+        );
+    }
+
+    #[test]
+    fn test_malformed_hunks_are_skipped() {
+        let diff = "--- a/foo.rs\n+++ b/foo.rs\n@@ -x +y @@\n-a\n+b\n@@ -5 +5 @@\n-c\n+d\n";
+        let parser = Parser::new(diff);
+        assert_eq!(
+            Err(error(3, DiffParseErrorKind::MalformedHunkHeader)),
+            parser.sections("/repo")
+        );
+        let sections = parser.parse().sections("/repo");
+        assert_eq!(1, sections.len());
+        assert_eq!(5, sections[0].line_start);
+        assert_eq!(
+            "line 3: malformed hunk header",
+            error(3, DiffParseErrorKind::MalformedHunkHeader).to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_truncated_diffs() {
+        // Every prefix of a valid diff is parsed without panicking
+        for (end, _) in PATCH.char_indices() {
+            let prefix = &PATCH[..end];
+            let _ = Parser::new(prefix).parse();
+        }
+    }
+
+    #[test]
+    fn test_sections() -> Result<(), DiffParseError> {
+        let file_name = Path::new("/repo")
+            .join("src/foo.rs")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(
+            vec![
+                Section {
+                    file_name: file_name.clone(),
+                    line_start: 2,
+                    line_end: 4,
+                },
+                Section {
+                    file_name,
+                    line_start: 11,
+                    line_end: 11,
+                },
+            ],
+            Parser::new(PATCH).sections("/repo")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_files() -> Result<(), DiffParseError> {
+        let root = Path::new("/repo");
+        let mut files = Parser::new(PATCH).files()?;
+        files.push(FileDiff {
+            old_path: Some("clippy.toml".to_string()),
+            new_path: None,
+            hunks: Vec::new(),
+        });
+        let joined = |path: &str| root.join(path).to_string_lossy().to_string();
+        assert_eq!(
+            vec![
+                joined("README.md"),
+                joined("clippy.toml"),
+                joined("src/foo.rs")
+            ],
+            changed_files(&files, root)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_change_filter() -> Result<(), DiffParseError> {
+        let whitespace = ChangeFilter {
+            whitespace: true,
+            ..ChangeFilter::default()
+        };
+        let comments = ChangeFilter {
+            comments: true,
+            ..ChangeFilter::default()
+        };
+        // Reflowed, or reindented
+        assert!(whitespace.ignores(&["let a = foo(b, c);"], &["let a =", "    foo(b, c);"]));
+        assert!(whitespace.ignores(&[], &[""]));
+        assert!(!whitespace.ignores(&["let a = 1;"], &["let a = 2;"]));
+        assert!(!ChangeFilter::default().ignores(&[], &[""]));
+
+        assert!(comments.ignores(&[], &["    // Why it is done", ""]));
+        assert!(comments.ignores(&["let a = 1;"], &["/* a", " * block", " */"]));
+        assert!(!comments.ignores(&[], &["/// Doc comments are linted"]));
+        assert!(!comments.ignores(&[], &["/* a */ let b = 1;"]));
+        assert!(!comments.ignores(&[], &["*b = 1;"]));
+        assert!(!comments.ignores(&[], &["", ""]));
+
+        let diff = "--- a/foo.rs\n+++ b/foo.rs\n@@ -1 +1 @@\n-fn a() {}\n+fn a()  {}\n@@ -5,0 +5 @@\n+// Note\n@@ -9 +9 @@\n-b();\n+c();\n";
+        let hunks = |filter| -> Result<Vec<Hunk>, DiffParseError> {
+            Ok(Parser::new(diff)
+                .with_filter(filter)
+                .files()?
+                .remove(0)
+                .hunks)
+        };
+        assert_eq!(3, hunks(ChangeFilter::default())?.len());
+        assert_eq!(2, hunks(whitespace)?.len());
+        let both = ChangeFilter {
+            whitespace: true,
+            comments: true,
+        };
+        assert_eq!(
+            vec![9],
+            hunks(both)?.iter().map(|h| h.new_start).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_filtered_series_translation() -> Result<(), DiffParseError> {
+        let first = Parser::new("--- a/foo.rs\n+++ b/foo.rs\n@@ -4,0 +5,2 @@\n+a\n+b\n").files()?;
+        // Only adds a blank line at the top, which still moves the lines of the first patch
+        let second = "--- a/foo.rs\n+++ b/foo.rs\n@@ -0,0 +1 @@\n+\n";
+        let filter = ChangeFilter {
+            whitespace: true,
+            ..ChangeFilter::default()
+        };
+        let series = [
+            first.clone(),
+            Parser::new(second).with_filter(filter).files()?,
+        ];
+        let unfiltered = [first, Parser::new(second).files()?];
+        let sections = series_sections(&series, &unfiltered, Path::new(""));
+        assert_eq!(
+            vec![
+                vec![Section {
+                    file_name: "foo.rs".to_string(),
+                    line_start: 6,
+                    line_end: 8,
+                }],
+                Vec::new(),
+            ],
+            sections
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_series_translation() -> Result<(), DiffParseError> {
+        let first = Parser::new("--- a/foo.rs\n+++ b/foo.rs\n@@ -4,0 +5,2 @@\n+a\n+b\n").files()?;
+        // Adds two lines at the top, and rewrites the first line added by the first patch
+        let second = Parser::new(
+            "--- a/foo.rs\n+++ b/foo.rs\n@@ -0,0 +1,2 @@\n+c\n+d\n@@ -5 +7 @@\n-a\n+e\n",
+        )
+        .files()?;
+        let series = [first, second];
+        let sections = series_sections(&series, &series, Path::new(""));
+        assert_eq!(
+            vec![
+                vec![Section {
+                    file_name: "foo.rs".to_string(),
+                    line_start: 8,
+                    line_end: 9,
+                }],
+                vec![
+                    Section {
+                        file_name: "foo.rs".to_string(),
+                        line_start: 1,
+                        line_end: 3,
+                    },
+                    Section {
+                        file_name: "foo.rs".to_string(),
+                        line_start: 7,
+                        line_end: 8,
+                    }
+                ],
+            ],
+            sections
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_series_from_dir() -> Result<(), crate::error::Error> {
+        let dir = tempfile::TempDir::new()?;
+        std::fs::write(
+            dir.path().join("0002-second.patch"),
+            "--- a/foo.rs\n+++ b/foo.rs\n@@ -0,0 +1 @@\n+c\n",
+        )?;
+        std::fs::write(
+            dir.path().join("0001-first.patch"),
+            "--- a/foo.rs\n+++ b/foo.rs\n@@ -1,0 +2 @@\n+a\n",
+        )?;
+        std::fs::write(dir.path().join("cover-letter.txt"), "not a patch")?;
+
+        let series = PatchSeries::from_dir(dir.path())?;
+        let names: Vec<_> = series
+            .patches()
+            .iter()
+            .map(|p| p.path().file_name().unwrap().to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(vec!["0001-first.patch", "0002-second.patch"], names);
+
+        let root = Path::new("/repo");
+        let file_name = root.join("foo.rs").to_string_lossy().to_string();
+        assert_eq!(
+            vec![
+                Section {
+                    file_name: file_name.clone(),
+                    line_start: 3,
+                    line_end: 4,
+                },
+                Section {
+                    file_name,
+                    line_start: 1,
+                    line_end: 2,
+                },
+            ],
+            series.sections(root)?
+        );
+        Ok(())
+    }
+}
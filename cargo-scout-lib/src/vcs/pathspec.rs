@@ -0,0 +1,156 @@
+use crate::error::Error;
+use crate::scout::build_output::glob_match;
+use crate::utils::normalize_path;
+use std::path::{Path, PathBuf};
+
+/// The paths a run is restricted to, like the trailing `-- src/api/**` of git commands.
+///
+/// A pattern matches the file it names, the files of the directory it names,
+/// or the paths it matches as a glob, see `glob_match`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Pathspec {
+    /// The directory the patterns are relative to, usually the current directory
+    base: PathBuf,
+    patterns: Vec<String>,
+}
+
+impl Pathspec {
+    #[must_use]
+    pub fn new(base: impl Into<PathBuf>, patterns: Vec<String>) -> Self {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let pattern = normalize_path(&pattern);
+                let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+                if pattern.is_empty() || pattern == "." {
+                    "**".to_string()
+                } else {
+                    pattern.to_string()
+                }
+            })
+            .collect();
+        Self {
+            base: base.into(),
+            patterns,
+        }
+    }
+
+    /// Whether there are no patterns, every path is then matched.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether `path`, absolute or relative to the base directory, is matched.
+    ///
+    /// The paths outside of the base directory are only matched without patterns.
+    #[must_use]
+    pub fn matches(&self, path: &str) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let path = Path::new(path);
+        let path = if path.is_absolute() {
+            match path.strip_prefix(&self.base) {
+                Ok(relative) => relative,
+                Err(_) => return false,
+            }
+        } else {
+            path
+        };
+        let path = normalize_path(&path.to_string_lossy());
+        self.patterns.iter().any(|pattern| {
+            path == *pattern
+                || path.starts_with(&format!("{}/", pattern))
+                || glob_match(pattern, &path)
+        })
+    }
+
+    /// The patterns, relative to `root` rather than to the base directory.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there are patterns and the base directory is outside of `root`,
+    /// as git does for the paths outside of the repository.
+    pub fn patterns_from(&self, root: &Path) -> Result<Vec<String>, Error> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let prefix = self.base.strip_prefix(root).map_err(|_| {
+            Error::Pathspec(format!(
+                "the paths are relative to {}, outside of the repository {}",
+                self.base.display(),
+                root.display()
+            ))
+        })?;
+        let prefix = normalize_path(&prefix.to_string_lossy());
+        Ok(self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                if prefix.is_empty() {
+                    pattern.clone()
+                } else {
+                    format!("{}/{}", prefix, pattern)
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pathspec;
+    use std::path::Path;
+
+    #[test]
+    fn test_matches() {
+        let pathspec = Pathspec::new(
+            "/repo/crates",
+            vec![
+                "./api/".to_string(),
+                "core/src/**/*.rs".to_string(),
+                "build.rs".to_string(),
+            ],
+        );
+        assert!(pathspec.matches("/repo/crates/api/src/lib.rs"));
+        assert!(pathspec.matches("api/src/lib.rs"));
+        assert!(pathspec.matches("/repo/crates/core/src/a/b.rs"));
+        assert!(pathspec.matches("/repo/crates/build.rs"));
+        assert!(!pathspec.matches("/repo/crates/apis/src/lib.rs"));
+        assert!(!pathspec.matches("/repo/crates/core/README.md"));
+        assert!(!pathspec.matches("/repo/build.rs"));
+        // The base directory is a prefix of the path, but not one of its directories
+        assert!(!pathspec.matches("/repo/crates2/api/src/lib.rs"));
+        assert!(!Pathspec::new("/repo/crates", vec![".".to_string()])
+            .matches("/repo/crates2/src/lib.rs"));
+
+        assert!(Pathspec::default().matches("/anything.rs"));
+        assert!(Pathspec::new("/repo", vec![".".to_string()]).matches("/repo/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_patterns_from() -> Result<(), crate::error::Error> {
+        let pathspec = Pathspec::new("/repo/crates", vec!["api/**".to_string()]);
+        assert_eq!(
+            vec!["crates/api/**"],
+            pathspec.patterns_from(Path::new("/repo"))?
+        );
+        assert_eq!(
+            vec!["api/**"],
+            pathspec.patterns_from(Path::new("/repo/crates"))?
+        );
+        assert!(matches!(
+            pathspec.patterns_from(Path::new("/elsewhere")),
+            Err(crate::error::Error::Pathspec(_))
+        ));
+        assert!(matches!(
+            pathspec.patterns_from(Path::new("/repo/cr")),
+            Err(crate::error::Error::Pathspec(_))
+        ));
+        assert!(Pathspec::new("/elsewhere", Vec::new())
+            .patterns_from(Path::new("/repo"))?
+            .is_empty());
+        Ok(())
+    }
+}
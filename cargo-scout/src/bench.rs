@@ -0,0 +1,162 @@
+use cargo_scout_lib::linter::{Lint, Location};
+use cargo_scout_lib::scout::lints_from_diff;
+use cargo_scout_lib::vcs::Section;
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub struct BenchOptions {
+    #[structopt(long = "files", default_value = "500")]
+    /// The number of files the synthetic diff and lints are spread over
+    pub files: u32,
+    #[structopt(long = "lints", default_value = "20000")]
+    /// The number of synthetic lints
+    pub lints: usize,
+    #[structopt(long = "sections", default_value = "5000")]
+    /// The number of synthetic diff sections
+    pub sections: usize,
+    #[structopt(long = "iterations", default_value = "10")]
+    /// How many times the intersection is run
+    pub iterations: u32,
+    #[structopt(long = "seed", default_value = "42")]
+    /// The seed of the synthetic data generator
+    pub seed: u64,
+}
+
+/// The outcome of an intersection benchmark.
+#[derive(Debug)]
+pub struct BenchResult {
+    pub matched: usize,
+    pub iterations: u32,
+    pub total: Duration,
+}
+
+impl BenchResult {
+    /// The mean time spent in a single intersection.
+    pub fn mean(&self) -> Duration {
+        self.total / self.iterations.max(1)
+    }
+
+    /// How many lints are intersected per second.
+    pub fn lints_per_second(&self, lints: usize) -> f64 {
+        let mean = self.mean().as_secs_f64();
+        if mean == 0.0 {
+            f64::INFINITY
+        } else {
+            // Precision doesn't matter for a throughput estimate
+            #[allow(clippy::cast_precision_loss)]
+            let lints = lints as f64;
+            lints / mean
+        }
+    }
+}
+
+/// A xorshift generator, so runs with the same seed intersect the same data.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // xorshift gets stuck on 0
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: u32) -> u32 {
+        (self.next() % u64::from(max.max(1))) as u32
+    }
+}
+
+fn file_name(index: u32) -> String {
+    format!("/bench/crate-{}/src/module_{}.rs", index % 16, index)
+}
+
+/// Generates lints spread over `files` files.
+pub fn synthetic_lints(opts: &BenchOptions) -> Vec<Lint> {
+    let mut rng = Rng::new(opts.seed);
+    (0..opts.lints)
+        .map(|index| {
+            let start = rng.below(2000) + 1;
+            Lint {
+                message: format!("synthetic lint {}", index),
+                code: Some("clippy::bench".to_string()),
+                location: Location {
+                    path: file_name(rng.below(opts.files)),
+                    lines: [start, start + rng.below(8)],
+                    ..Location::default()
+                },
+                suggestions: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Generates diff sections spread over `files` files.
+pub fn synthetic_sections(opts: &BenchOptions) -> Vec<Section> {
+    let mut rng = Rng::new(opts.seed.wrapping_add(1));
+    (0..opts.sections)
+        .map(|_| {
+            let line_start = rng.below(2000) + 1;
+            Section {
+                file_name: file_name(rng.below(opts.files)),
+                line_start,
+                line_end: line_start + rng.below(20),
+            }
+        })
+        .collect()
+}
+
+/// Intersects the same synthetic data `iterations` times.
+pub fn run(lints: &[Lint], sections: &[Section], iterations: u32) -> BenchResult {
+    let mut matched = 0;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        matched = lints_from_diff(lints, sections).len();
+    }
+    BenchResult {
+        matched,
+        iterations,
+        total: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, synthetic_lints, synthetic_sections, BenchOptions};
+
+    fn options(seed: u64) -> BenchOptions {
+        BenchOptions {
+            files: 10,
+            lints: 200,
+            sections: 100,
+            iterations: 2,
+            seed,
+        }
+    }
+
+    #[test]
+    fn test_synthetic_data_is_reproducible() {
+        assert_eq!(synthetic_lints(&options(7)), synthetic_lints(&options(7)));
+        assert_eq!(
+            synthetic_sections(&options(7)),
+            synthetic_sections(&options(7))
+        );
+        assert_ne!(synthetic_lints(&options(7)), synthetic_lints(&options(8)));
+    }
+
+    #[test]
+    fn test_run() {
+        let opts = options(0);
+        let lints = synthetic_lints(&opts);
+        let result = run(&lints, &synthetic_sections(&opts), opts.iterations);
+        assert_eq!(200, lints.len());
+        assert!(result.matched > 0);
+        assert!(result.matched <= lints.len());
+        assert!(result.lints_per_second(lints.len()) > 0.0);
+    }
+}
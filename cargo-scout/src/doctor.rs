@@ -0,0 +1,211 @@
+use cargo_scout_lib::config::rust::CargoConfig;
+use cargo_scout_lib::linter::clippy::Clippy;
+use cargo_scout_lib::linter::Lint;
+use cargo_scout_lib::scout::Scout;
+use cargo_scout_lib::vcs::git::Git;
+use std::path::Path;
+use std::process::Command;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub struct DoctorOptions {
+    #[structopt(long = "keep")]
+    /// Keep the synthetic repository once checked, to inspect it
+    pub keep: bool,
+}
+
+/// The lint the diff of the synthetic repository introduces, clippy reports it by default.
+pub const EXPECTED_LINT: &str = "clippy::needless_return";
+
+const MANIFEST: &str = "[package]
+name = \"scout-doctor\"
+version = \"0.1.0\"
+edition = \"2018\"
+";
+
+const LIB: &str = "pub fn answer() -> u32 {
+    42
+}
+";
+
+const LIB_WITH_LINT: &str = "pub fn answer() -> u32 {
+    42
+}
+
+pub fn question() -> u32 {
+    return answer();
+}
+";
+
+/// A check of the environment, and what it found or why it failed.
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Result<String, String>,
+}
+
+/// A check run in the directory of the synthetic repository.
+type Step = fn(&Path) -> Result<String, String>;
+
+/// Checks the environment by scouting a synthetic repository created in `dir`:
+/// a crate committed to git, with a diff introducing `EXPECTED_LINT`.
+///
+/// The checks stop at the first failure, as the next ones depend on it.
+pub fn run(dir: &Path) -> Vec<Check> {
+    let steps: [(&str, Step); 6] = [
+        ("permissions", write_crate),
+        ("git", |dir| version(dir, "git", &["--version"])),
+        ("cargo", |dir| version(dir, "cargo", &["--version"])),
+        ("clippy", |dir| {
+            version(dir, "cargo", &["clippy", "--version"])
+        }),
+        ("repository", commit_crate),
+        ("pipeline", scout),
+    ];
+    let mut checks = Vec::new();
+    for (name, step) in &steps {
+        let outcome = step(dir);
+        let failed = outcome.is_err();
+        checks.push(Check { name, outcome });
+        if failed {
+            break;
+        }
+    }
+    checks
+}
+
+fn write_crate(dir: &Path) -> Result<String, String> {
+    let write = || -> std::io::Result<()> {
+        std::fs::create_dir_all(dir.join("src"))?;
+        std::fs::write(dir.join("Cargo.toml"), MANIFEST)?;
+        std::fs::write(dir.join("src/lib.rs"), LIB)
+    };
+    write()
+        .map(|()| format!("wrote a synthetic crate to {}", dir.display()))
+        .map_err(|error| format!("could not write to {}: {}", dir.display(), error))
+}
+
+/// The first line `program` prints with `args`, run from `dir`.
+fn version(dir: &Path, program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|error| format!("could not run {}: {}", program, error))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!(
+            "`{} {}` failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+/// Commits the synthetic crate, then introduces `EXPECTED_LINT` in the working tree.
+fn commit_crate(dir: &Path) -> Result<String, String> {
+    let git = |args: &[&str]| -> Result<(), String> {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args([
+                "-c",
+                "user.name=cargo-scout",
+                "-c",
+                "user.email=doctor@cargo-scout",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .output()
+            .map_err(|error| format!("could not run git: {}", error))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "`git {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+    git(&["init", "--quiet"])?;
+    git(&["add", "Cargo.toml", "src/lib.rs"])?;
+    git(&["commit", "--quiet", "--message", "initial"])?;
+    std::fs::write(dir.join("src/lib.rs"), LIB_WITH_LINT)
+        .map_err(|error| format!("could not change src/lib.rs: {}", error))?;
+    Ok(format!(
+        "committed the crate, and introduced {}",
+        EXPECTED_LINT
+    ))
+}
+
+/// Runs the whole pipeline on the synthetic repository, from its directory like a user would.
+fn scout(dir: &Path) -> Result<String, String> {
+    let previous_dir = std::env::current_dir().map_err(|error| error.to_string())?;
+    std::env::set_current_dir(dir).map_err(|error| error.to_string())?;
+    let lints = CargoConfig::from_manifest_path(dir.join("Cargo.toml"), &[])
+        .and_then(|config| Scout::new(Git::default(), config, Clippy::default()).run())
+        .map(|outcome| outcome.lints);
+    let _ = std::env::set_current_dir(previous_dir);
+    let lints = lints.map_err(|error| format!("scout failed: {}", error))?;
+    caught(&lints)
+}
+
+/// Whether the `lints` scout reported include `EXPECTED_LINT`.
+fn caught(lints: &[Lint]) -> Result<String, String> {
+    match lints
+        .iter()
+        .find(|lint| lint.code.as_deref() == Some(EXPECTED_LINT))
+    {
+        Some(lint) => Ok(format!(
+            "caught {} at src/lib.rs:{}",
+            EXPECTED_LINT, lint.location.lines[0]
+        )),
+        None => Err(format!(
+            "scout reported {} lint(s), but not {}: is clippy configured to allow it?",
+            lints.len(),
+            EXPECTED_LINT
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{caught, commit_crate, write_crate, EXPECTED_LINT, LIB_WITH_LINT};
+    use cargo_scout_lib::linter::{Lint, Location};
+
+    #[test]
+    fn test_caught() {
+        let lint = Lint {
+            message: "unneeded `return` statement".to_string(),
+            code: Some(EXPECTED_LINT.to_string()),
+            location: Location {
+                path: "/tmp/doctor/src/lib.rs".to_string(),
+                lines: [6, 6],
+                ..Location::default()
+            },
+            suggestions: Vec::new(),
+        };
+        assert_eq!(
+            Ok("caught clippy::needless_return at src/lib.rs:6".to_string()),
+            caught(&[lint])
+        );
+        assert!(caught(&[]).is_err());
+    }
+
+    #[test]
+    fn test_synthetic_repository() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-scout-doctor-test-{}", std::process::id()));
+        assert!(write_crate(&dir).is_ok());
+        let committed = commit_crate(&dir);
+        let lib = std::fs::read_to_string(dir.join("src/lib.rs")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        // Without git, there is nothing more to check
+        if committed.is_ok() {
+            assert_eq!(LIB_WITH_LINT, lib);
+        }
+    }
+}
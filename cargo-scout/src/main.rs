@@ -1,44 +1,300 @@
+use bench::BenchOptions;
+use cargo_scout_lib::config::check::{self, Diagnostic, Severity};
+use cargo_scout_lib::config::env;
+use cargo_scout_lib::config::grace::{self, Date};
+use cargo_scout_lib::config::graph::DependencyGraph;
+use cargo_scout_lib::config::layers::{Layered, Locations};
 use cargo_scout_lib::config::rust::CargoConfig;
+use cargo_scout_lib::config::schema;
+use cargo_scout_lib::config::scout::{PathMapping, ScoutConfig, SCOUT_TOML};
+use cargo_scout_lib::config::Config;
+use cargo_scout_lib::exit;
+use cargo_scout_lib::limits::{self, ResourceLimits};
 use cargo_scout_lib::linter::clippy::Clippy;
+use cargo_scout_lib::linter::command::{self, Platform};
+use cargo_scout_lib::linter::container::Container;
+use cargo_scout_lib::linter::crash::{self, Crash};
+use cargo_scout_lib::linter::fingerprint::{self, Fingerprint};
+use cargo_scout_lib::linter::ingest::{Ingested, LintsFormat};
+use cargo_scout_lib::linter::postprocess::{self, PathPrefixMap, PostProcessors, StripAnsi};
+use cargo_scout_lib::linter::runner::{CommandRunner, Wrapper};
 use cargo_scout_lib::linter::rustfmt::RustFmt;
-use cargo_scout_lib::linter::Lint;
-use cargo_scout_lib::scout::Scout;
-use cargo_scout_lib::vcs::git::Git;
+use cargo_scout_lib::linter::{Invocation, Lint, Linter, LinterCommand};
+use cargo_scout_lib::output::{self, Capabilities, ColorChoice};
+use cargo_scout_lib::report::annotate;
+use cargo_scout_lib::report::baseline::Baseline;
+#[cfg(feature = "http")]
+use cargo_scout_lib::report::baseline::Deferred;
+use cargo_scout_lib::report::cache::{self, ResultCache};
+use cargo_scout_lib::report::group::{self, GroupBy};
+#[cfg(feature = "http")]
+use cargo_scout_lib::report::issues::IssueExporter;
+use cargo_scout_lib::report::json::{self, JsonLint, JsonReport, ReportDiff};
+use cargo_scout_lib::report::json_lines::{self, JsonLinesObserver};
+use cargo_scout_lib::report::markdown::MarkdownReporter;
+use cargo_scout_lib::report::patch;
+use cargo_scout_lib::report::plan::{JsonPlan, PlanFormat};
+use cargo_scout_lib::report::problem_matcher::{self, ProblemMatcher};
+use cargo_scout_lib::report::quickfix;
+use cargo_scout_lib::report::rank::Ranker;
+use cargo_scout_lib::report::run_id;
+use cargo_scout_lib::report::schema::SchemaKind;
+#[cfg(feature = "store")]
+use cargo_scout_lib::report::store::{self, Query, Run, Store};
+use cargo_scout_lib::report::summary::DebugSummary;
+use cargo_scout_lib::report::text::{self, TextReporter};
+#[cfg(feature = "http")]
+use cargo_scout_lib::report::webhook::WebhookReporter;
+use cargo_scout_lib::report::OutputFormat;
+use cargo_scout_lib::retry::RetryPolicy;
+use cargo_scout_lib::scout::allow;
+use cargo_scout_lib::scout::budget;
+use cargo_scout_lib::scout::empty_diff::{self, Status};
+use cargo_scout_lib::scout::fix::FixGuard;
+use cargo_scout_lib::scout::gate::DiffGate;
+use cargo_scout_lib::scout::matching::MatchStrategy;
+use cargo_scout_lib::scout::observer::Observer;
+use cargo_scout_lib::scout::order::MemberOrder;
+use cargo_scout_lib::scout::policy::Policy;
+use cargo_scout_lib::scout::schedule::{self, JobLimit};
+use cargo_scout_lib::scout::selection::TargetSelection;
+use cargo_scout_lib::scout::shard::Shard;
+use cargo_scout_lib::scout::suggest::{self, AppliedFixes};
+use cargo_scout_lib::scout::{Outcome, Plan, Scout};
+use cargo_scout_lib::signal;
+use cargo_scout_lib::vcs::git::{self, Git};
+use cargo_scout_lib::vcs::patch::{ChangeFilter, Patch, PatchSeries};
+use cargo_scout_lib::vcs::pathspec::Pathspec;
+use cargo_scout_lib::vcs::{BaseMode, Unversioned, VCS};
 use cargo_scout_lib::Error;
-use cargo_scout_macros::{success, warn};
+use cargo_scout_macros::{error, info, success, warn};
 use colored::Colorize;
+use doctor::DoctorOptions;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use structopt::StructOpt;
 
+mod bench;
+mod doctor;
+
 #[derive(StructOpt)]
 #[structopt(
     name = "cargo-scout",
     author,
-    about = "Leave the codebase better than when you found it."
+    about = "Leave the codebase better than when you found it.",
+    after_help = exit::HELP
 )]
 enum Command {
+    #[structopt(after_help = exit::HELP)]
     Fmt(FmtOptions),
+    #[structopt(after_help = exit::HELP)]
     Lint(LintOptions),
+    #[structopt(name = "check-config", after_help = exit::HELP)]
+    /// Validate the `.scout.toml` file, without running any linter
+    CheckConfig(CheckConfigOptions),
+    #[structopt(name = "bench-intersect", after_help = exit::HELP)]
+    /// Measure the throughput of the diff and lints intersection on synthetic data
+    BenchIntersect(BenchOptions),
+    #[structopt(name = "diff-results", after_help = exit::HELP)]
+    /// Compare two runs saved as json, and report the lints added and removed
+    DiffResults(DiffResultsOptions),
+    #[structopt(name = "merge", after_help = exit::HELP)]
+    /// Combine json reports, like those of the shards of a run, deduplicating their lints
+    Merge(MergeOptions),
+    #[structopt(name = "suggest", after_help = exit::HELP)]
+    /// Apply the machine-applicable fixes of the lints in the diff, and optionally commit them
+    Suggest(SuggestOptions),
+    #[structopt(name = "allow", after_help = exit::HELP)]
+    /// Allow the lints of these fingerprints with an `#[allow]` on the smallest item holding each,
+    /// when suppressing them was agreed on
+    Allow(AllowOptions),
+    #[cfg(feature = "http")]
+    #[structopt(name = "defer", after_help = exit::HELP)]
+    /// File an issue for each lint of these fingerprints, and stop reporting them
+    Defer(DeferOptions),
+    #[structopt(name = "doctor", after_help = exit::HELP)]
+    /// Check that git, cargo and clippy work, by scouting a synthetic repository
+    Doctor(DoctorOptions),
+    #[structopt(name = "schema", after_help = exit::HELP)]
+    /// Print the JSON Schema of the json or json-lines output, to generate clients and check compatibility
+    Schema(SchemaOptions),
+    #[structopt(name = "config", after_help = exit::HELP)]
+    /// Inspect the configuration merged from every `.scout.toml` and the environment
+    Config(ConfigCommand),
+    #[cfg(feature = "store")]
+    #[structopt(name = "query", after_help = exit::HELP)]
+    /// Query the lints of the runs recorded with --store
+    Query(QueryOptions),
+}
+
+impl Command {
+    fn color(&self) -> ColorChoice {
+        match self {
+            Self::Fmt(opts) => opts.scout.color,
+            Self::Lint(opts) => opts.scout.color,
+            Self::Suggest(opts) => opts.lint.scout.color,
+            Self::Allow(opts) => opts.lint.scout.color,
+            #[cfg(feature = "http")]
+            Self::Defer(opts) => opts.lint.scout.color,
+            Self::CheckConfig(_)
+            | Self::Schema(_)
+            | Self::Config(_)
+            | Self::Doctor(_)
+            | Self::BenchIntersect(_)
+            | Self::DiffResults(_)
+            | Self::Merge(_) => ColorChoice::Auto,
+            #[cfg(feature = "store")]
+            Self::Query(_) => ColorChoice::Auto,
+        }
+    }
 }
 
 #[derive(Debug, StructOpt)]
 struct FmtOptions {
-    #[structopt(
-        short = "b",
-        long = "branch",
-        value_name = "branch",
-        default_value = "HEAD"
-    )]
-    /// Set the target branch
-    branch: String,
-    #[structopt(long = "member")]
-    /// Only run for these workspace members, if the crate is a workspace
-    members: Vec<String>,
-    #[structopt(short = "t", long = "cargo-toml", default_value = "./Cargo.toml")]
-    /// Pass the path of the `Cargo.toml` file
-    cargo_toml: String,
-    #[structopt(short = "w", long = "without-error")]
-    /// Set to display the warnings without actually returning an error
-    without_error: bool,
+    #[structopt(flatten)]
+    scout: ScoutOptions,
+}
+
+#[derive(Debug, StructOpt)]
+struct SuggestOptions {
+    #[structopt(long = "commit")]
+    /// Commit the fixed files, with a message listing the lints fixed
+    commit: bool,
+    #[structopt(long = "push", value_name = "branch", requires = "commit")]
+    /// Push the commit to this branch of the remote
+    push: Option<String>,
+    #[structopt(long = "remote", default_value = "origin")]
+    /// The remote the commit is pushed to
+    remote: String,
+    #[structopt(flatten)]
+    lint: LintOptions,
+}
+
+#[derive(Debug, StructOpt)]
+struct SchemaOptions {
+    #[structopt(default_value = "json", value_name = "json|json-lines")]
+    /// The output format
+    format: SchemaKind,
+    #[structopt(long = "out-dir", value_name = "dir")]
+    /// Write the schemas of every format to this directory instead, named like those of the repository
+    out_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct AllowOptions {
+    #[structopt(value_name = "fingerprint", required = true)]
+    /// The fingerprints of the lints, as the json output gives them
+    fingerprints: Vec<Fingerprint>,
+    #[structopt(long = "reviewed-by", value_name = "name")]
+    /// Who agreed on the suppression, for the `// scout: reviewed by` comment, the git `user.name` by default
+    reviewed_by: Option<String>,
+    #[structopt(long = "report", value_name = "path")]
+    /// Take the lints from this json report, instead of linting the diff
+    report: Option<PathBuf>,
+    #[structopt(flatten)]
+    lint: LintOptions,
+}
+
+#[cfg(feature = "http")]
+#[derive(Debug, StructOpt)]
+struct DeferOptions {
+    #[structopt(value_name = "fingerprint", required = true)]
+    /// The fingerprints of the lints, as the json output gives them
+    fingerprints: Vec<Fingerprint>,
+    #[structopt(long = "report", value_name = "path")]
+    /// Take the lints from this json report, instead of linting the diff
+    report: Option<PathBuf>,
+    #[structopt(flatten)]
+    lint: LintOptions,
+}
+
+#[cfg(feature = "store")]
+#[derive(Debug, StructOpt)]
+struct QueryOptions {
+    #[structopt(long = "store", value_name = "path")]
+    /// The database the runs were recorded in
+    store: PathBuf,
+    #[structopt(long = "code", value_name = "pattern")]
+    /// Only the lints whose code matches this glob, like `clippy::unwrap_*`
+    code: Option<String>,
+    #[structopt(long = "author", value_name = "pattern")]
+    /// Only the lints on lines whose `git blame` author matches this glob
+    author: Option<String>,
+    #[structopt(long = "member", value_name = "pattern")]
+    /// Only the lints of the workspace members matching this glob
+    member: Option<String>,
+    #[structopt(long = "since", value_name = "YYYY-MM-DD")]
+    /// Only the runs from this day on
+    since: Option<String>,
+    #[structopt(long = "until", value_name = "YYYY-MM-DD")]
+    /// Only the runs until this day, included
+    until: Option<String>,
+    #[structopt(long = "count-by", value_name = "code|author|member")]
+    /// Count the matching lints by code, author or member, instead of listing them
+    count_by: Option<store::CountBy>,
+}
+
+#[derive(Debug, StructOpt)]
+enum ConfigCommand {
+    #[structopt(name = "show", after_help = exit::HELP)]
+    /// Print the effective configuration, defaults included
+    Show(ConfigShowOptions),
+    #[structopt(name = "migrate", after_help = exit::HELP)]
+    /// Upgrade the `.scout.toml` file of the workspace to the schema version of this cargo-scout
+    Migrate(ConfigMigrateOptions),
+}
+
+#[derive(Debug, StructOpt)]
+struct ConfigMigrateOptions {
+    #[structopt(long = "check")]
+    /// Fail if the file needs to be migrated, without writing it
+    check: bool,
+    #[structopt(short = "t", long = "cargo-toml")]
+    /// Pass the path of the `Cargo.toml` file the configuration is next to,
+    /// defaults to the workspace of the current directory
+    cargo_toml: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct ConfigShowOptions {
+    #[structopt(long = "origin")]
+    /// Print each value on its own line, with the file or variable it comes from
+    origin: bool,
+    #[structopt(short = "t", long = "cargo-toml")]
+    /// Pass the path of the `Cargo.toml` file of the workspace,
+    /// defaults to the workspace of the current directory
+    cargo_toml: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct CheckConfigOptions {
+    #[structopt(short = "t", long = "cargo-toml")]
+    /// Pass the path of the `Cargo.toml` file the configuration is next to,
+    /// defaults to the workspace of the current directory
+    cargo_toml: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct DiffResultsOptions {
+    #[structopt(parse(from_os_str))]
+    /// The report of the previous run
+    old: PathBuf,
+    #[structopt(parse(from_os_str))]
+    /// The report of the new run
+    new: PathBuf,
+}
+
+#[derive(Debug, StructOpt)]
+struct MergeOptions {
+    #[structopt(parse(from_os_str), required = true)]
+    /// The reports to combine, written with `--output-format json`
+    reports: Vec<PathBuf>,
+    #[structopt(long = "output", short = "o", value_name = "path", parse(from_os_str))]
+    /// Write the combined report to a file instead of the standard output
+    output: Option<PathBuf>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -65,123 +321,2398 @@ struct LintOptions {
     #[structopt(long = "features")]
     /// Pass features to clippy
     features: Option<String>,
+    #[structopt(short = "p", long = "preview")]
+    /// Enable nightly features (e.g. get lints even after the build has already been done.)
+    preview: bool,
+    #[structopt(long = "fix", conflicts_with_all = &["patches", "all-workspaces", "dry-run"])]
+    /// Let clippy apply its fixes, then revert those made outside of the diff
+    fix: bool,
+    #[structopt(long = "short-messages")]
+    /// Ask clippy for one line diagnostics, like in hooks,
+    /// instead of the diagnostics rendered for the width of the terminal
+    short_messages: bool,
+    #[structopt(
+        long = "max-lints",
+        value_name = "N",
+        default_value = "10000",
+        env = "SCOUT_MAX_LINTS"
+    )]
+    /// Stop when a member has more than N lints, like pedantic lints of generated code, 0 never stops
+    max_lints: usize,
+    #[structopt(long = "select-targets", conflicts_with_all = &["all-targets", "tests"])]
+    /// Only lint the lib, bin, test, example or bench targets holding the changed files,
+    /// found from `cargo metadata` and the `mod` declarations, instead of every target
+    select_targets: bool,
+    #[structopt(long = "preset", value_name = "name", env = "SCOUT_PRESET")]
+    /// Only enable and report the lints of a preset, `security` or one of the `.scout.toml`
+    preset: Option<String>,
+    #[structopt(long = "lints-from-stdin", conflicts_with = "fix")]
+    /// Read the lints from stdin instead of running clippy, like those a separate build step produced,
+    /// relative paths being relative to the workspace root
+    lints_from_stdin: bool,
+    #[structopt(
+        long = "lints-format",
+        value_name = "clippy-json|sarif",
+        default_value = "clippy-json"
+    )]
+    /// The format of the lints read from stdin
+    lints_format: LintsFormat,
+    #[structopt(
+        long = "sarif",
+        value_name = "file",
+        number_of_values = 1,
+        conflicts_with = "fix"
+    )]
+    /// Scout the findings of a SARIF file instead of running clippy, like those of semgrep or CodeQL,
+    /// can be repeated and combined with --lints-from-stdin
+    sarif: Vec<PathBuf>,
+    #[structopt(flatten)]
+    scout: ScoutOptions,
+}
+
+/// The options shared by every command
+#[derive(Debug, StructOpt)]
+struct ScoutOptions {
     #[structopt(long = "member")]
-    /// Only run for these workspace members, if the crate is a workspace
+    /// Only run for these workspace members, if the crate is a workspace:
+    /// a relative path, a package name, or an unambiguous directory name
     members: Vec<String>,
     #[structopt(
         short = "b",
         long = "branch",
         value_name = "branch",
-        default_value = "HEAD"
+        default_value = "HEAD",
+        number_of_values = 1,
+        env = "SCOUT_BRANCH"
     )]
-    /// Set the target branch
-    branch: String,
-
-    #[structopt(short = "t", long = "cargo-toml", default_value = "./Cargo.toml")]
-    /// Pass the path of the `Cargo.toml` file
-    cargo_toml: String,
+    /// Set the target branch, can be repeated to compare against several branches
+    branch: Vec<String>,
+    #[structopt(
+        long = "base-mode",
+        value_name = "union|intersection",
+        default_value = "union",
+        env = "SCOUT_BASE_MODE"
+    )]
+    /// With several target branches, whether a line must be changed against any or all of them
+    base_mode: BaseMode,
+    #[structopt(long = "ignore-whitespace")]
+    /// Ignore the changes only touching whitespace, like reindented or reflowed code,
+    /// as `git diff -w` does
+    ignore_whitespace: bool,
+    #[structopt(long = "ignore-comment-only-changes")]
+    /// Ignore the changes only adding comments and blank lines, doc comments aren't ignored
+    ignore_comment_only_changes: bool,
+    #[structopt(
+        long = "match-strategy",
+        value_name = "overlap|containment|changed-lines-only",
+        default_value = "overlap",
+        env = "SCOUT_MATCH_STRATEGY"
+    )]
+    /// How strictly a warning must match the diff: overlap it, be within it,
+    /// or start on a line it adds
+    match_strategy: MatchStrategy,
+    #[structopt(long = "skip-tests-code")]
+    /// Skip the warnings of `#[cfg(test)]` modules, `#[test]` functions and `tests` directories,
+    /// as the `[test-code]` configuration does
+    skip_tests_code: bool,
+    #[structopt(short = "t", long = "cargo-toml")]
+    /// Pass the path of the `Cargo.toml` file, defaults to the workspace of the current directory
+    cargo_toml: Option<String>,
     #[structopt(short = "w", long = "without-error")]
-    /// Set to display the warnings without actually returning an error
+    /// Set to display the warnings without actually returning an error, or set SCOUT_WITHOUT_ERROR=true
     without_error: bool,
-    #[structopt(short = "p", long = "preview")]
-    /// Enable nightly features (e.g. get lints even after the build has already been done.)
-    preview: bool,
+    #[structopt(long = "no-diff")]
+    /// Report every warning of the workspace default members, regardless of the diff
+    no_diff: bool,
+    #[structopt(long = "max-diff-lines", value_name = "N")]
+    /// Skip diffs changing more than N lines, like generated mass refactors,
+    /// exiting with code 4
+    max_diff_lines: Option<u64>,
+    #[structopt(long = "max-diff-files", value_name = "N")]
+    /// Skip diffs changing more than N files, exiting with code 4
+    max_diff_files: Option<usize>,
+    #[structopt(long = "time-budget", value_name = "secs", env = "SCOUT_TIME_BUDGET")]
+    /// Lint the most changed members first, skipping those left after `secs` seconds
+    /// and reporting the warnings found so far
+    time_budget: Option<u64>,
+    #[structopt(long = "fail-on-empty-diff")]
+    /// Exit with code 5 when the diff changes nothing, like a branch with no commits of its own
+    fail_on_empty_diff: bool,
+    #[structopt(long = "shard", value_name = "k/n", env = "SCOUT_SHARD")]
+    /// Only lint the k-th of n shards of the relevant members, assigned by a stable hash,
+    /// and combine the json reports of the shards with `cargo scout merge`
+    shard: Option<Shard>,
+    #[structopt(long = "feature-matrix")]
+    /// Lint the relevant members once per feature combination of `feature-matrix.sets`,
+    /// telling which combinations found the warnings only some of them found
+    feature_matrix: bool,
+    #[structopt(
+        long = "order",
+        value_name = "config|diff-size|alpha|deps",
+        env = "SCOUT_ORDER"
+    )]
+    /// The order members are linted in: as configured, the most changed first, by name,
+    /// or the most depended on first. Defaults to diff-size with --time-budget, else config
+    order: Option<MemberOrder>,
+    #[structopt(long = "no-result-cache")]
+    /// Run the linters even when an earlier run scouted the same commits with the same options
+    no_result_cache: bool,
+    #[structopt(long = "git-dir", value_name = "path")]
+    /// Use the git directory at this path, like a bare repository, as `git --git-dir` does
+    git_dir: Option<PathBuf>,
+    #[structopt(long = "work-tree", value_name = "path")]
+    /// Use the working tree at this path, like the export of a bare repository,
+    /// as `git --work-tree` does
+    work_tree: Option<PathBuf>,
+    #[structopt(long = "no-vcs-fallback")]
+    /// Outside of a git repository, fail instead of reporting every warning
+    no_vcs_fallback: bool,
+    #[structopt(long = "workspace", requires = "no-diff")]
+    /// Check every workspace member instead of the default members, with --no-diff
+    workspace: bool,
+    #[structopt(long = "patches", value_name = "dir", conflicts_with = "no-diff")]
+    /// Scout the `.patch` files of a directory (e.g. `git format-patch` output) instead of a diff,
+    /// the series must be applied to the working tree
+    patches: Option<PathBuf>,
+    #[structopt(short = "j", long = "jobs", env = "SCOUT_JOBS")]
+    /// Bound the jobs of every cargo process combined,
+    /// defaults to the `-j` or jobserver of CARGO_MAKEFLAGS and MAKEFLAGS
+    jobs: Option<usize>,
+    #[structopt(long = "all-workspaces", conflicts_with_all = &["cargo-toml", "patches"])]
+    /// Scout every Cargo workspace of the repository, instead of the one of the current directory
+    all_workspaces: bool,
+    #[structopt(long = "member-jobs", default_value = "1")]
+    /// How many members are linted at once
+    member_jobs: usize,
+    #[structopt(long = "jobs-per-target-dir", default_value = "1")]
+    /// How many of the members sharing a target directory are linted at once
+    jobs_per_target_dir: usize,
+    #[structopt(long = "pipeline")]
+    /// Start linting the members as soon as their changed files are diffed, while git diffs the rest
+    pipeline: bool,
+    #[structopt(long = "retries", default_value = "2", env = "SCOUT_RETRIES")]
+    /// How many times cargo is run again when it fails for a transient reason, like lock contention
+    retries: u32,
+    #[structopt(long = "retry-backoff", default_value = "500")]
+    /// The milliseconds waited before the first retry, doubled after each retry
+    retry_backoff: u64,
+    #[structopt(long = "max-memory", value_name = "size", parse(try_from_str = limits::parse_memory))]
+    /// Limit the memory of each process spawned by the linter, like 4G (Unix only)
+    max_memory: Option<u64>,
+    #[structopt(long = "nice", value_name = "niceness")]
+    /// Run the linter with a niceness, from -20 to 19 (Unix only)
+    nice: Option<i32>,
+    #[structopt(
+        long = "in-container",
+        value_name = "image",
+        env = "SCOUT_IN_CONTAINER"
+    )]
+    /// Run cargo in a container of the image, with the repository mounted in it,
+    /// so that CI and local runs use the same toolchain
+    in_container: Option<String>,
+    #[structopt(
+        long = "container-engine",
+        value_name = "engine",
+        requires = "in-container"
+    )]
+    /// The engine running --in-container, docker by default, or e.g. podman
+    container_engine: Option<String>,
+    #[structopt(long = "runner", value_name = "command", env = "SCOUT_RUNNER")]
+    /// Run cargo through a wrapper command, like "ssh farm --", which gets the cargo command
+    /// as its last arguments and its directory as SCOUT_WORKING_DIR
+    runner: Option<String>,
+    #[structopt(long = "emit-problem-matcher")]
+    /// Register a GitHub Actions problem matcher, and print the warnings in a format it matches
+    emit_problem_matcher: bool,
+    #[structopt(
+        long = "color",
+        value_name = "auto|always|never",
+        default_value = "auto",
+        env = "SCOUT_COLOR"
+    )]
+    /// Color the output, `auto` honors NO_COLOR and CLICOLOR_FORCE and checks for a terminal
+    color: ColorChoice,
+    #[structopt(long = "max-message-lines", value_name = "N")]
+    /// Truncate each warning message after N lines
+    max_message_lines: Option<usize>,
+    #[structopt(long = "first-line-only", conflicts_with = "max-message-lines")]
+    /// Only display the first line of each warning message
+    first_line_only: bool,
+    #[structopt(
+        long = "output-format",
+        value_name = "text|json|json-lines|markdown|quickfix",
+        default_value = "text",
+        env = "SCOUT_OUTPUT_FORMAT"
+    )]
+    /// Set the format of the warnings
+    output_format: OutputFormat,
+    #[structopt(
+        long = "output-file",
+        value_name = "path",
+        conflicts_with = "emit-problem-matcher",
+        env = "SCOUT_OUTPUT_FILE"
+    )]
+    /// Write the warnings to a file instead of the standard output
+    output_file: Option<PathBuf>,
+    #[structopt(long = "group-by", value_name = "author|code|file|item")]
+    /// Group the text warnings by the `git blame` author of their line, their lint, their file or their item
+    group_by: Option<GroupBy>,
+    #[structopt(long = "rank")]
+    /// List the most actionable warnings first: errors, then the lints of `ranking.deny`,
+    /// those of new files, of added lines, and those matching the boundaries of the diff
+    rank: bool,
+    #[structopt(long = "write-latest")]
+    /// Also write the warnings as json to `target/scout/latest.json`, for editor plugins to poll
+    write_latest: bool,
+    #[structopt(long = "emit-patches", value_name = "dir")]
+    /// Write a `.patch` file in this directory for each lint clippy can fix, to apply with `git apply`
+    emit_patches: Option<PathBuf>,
+    #[structopt(long = "annotate-sources", value_name = "dir")]
+    /// Copy the changed Rust files to this directory, out of the work tree, with a `// ^ LINT:`
+    /// comment under the lines of their warnings, to review without a pull request integration
+    annotate_sources: Option<PathBuf>,
+    #[cfg(feature = "store")]
+    #[structopt(long = "store", value_name = "path")]
+    /// Record the run and its lints in this SQLite database, to look them up with `cargo scout query`
+    store: Option<PathBuf>,
+    #[structopt(long = "dry-run")]
+    /// Print the diff targets, changed files, members and linter commands, without running them
+    dry_run: bool,
+    #[structopt(
+        long = "emit-plan",
+        value_name = "json",
+        conflicts_with_all = &["dry-run", "all-workspaces"]
+    )]
+    /// Print what --dry-run does and whether the result cache holds the run, for orchestrators
+    /// splitting the work across agents. It is written to --output-file if it is set
+    emit_plan: Option<PlanFormat>,
+    #[structopt(long = "explain-filtering")]
+    /// Explain why each lint was reported or dropped, in the json output
+    explain_filtering: bool,
+    #[structopt(long = "with-dependents")]
+    /// Also lint the members depending on the changed ones, and report their deprecation lints
+    with_dependents: bool,
+    #[structopt(long = "run-id", value_name = "id", env = "SCOUT_RUN_ID")]
+    /// Identify the run in the json, json-lines and markdown outputs,
+    /// defaults to a hash of the compared commits and of the configuration
+    run_id: Option<String>,
+    #[structopt(
+        long = "label",
+        value_name = "key=value",
+        number_of_values = 1,
+        parse(try_from_str = json::parse_label)
+    )]
+    /// Stamp the run and each warning with a label of its configuration in the json and
+    /// json-lines outputs, like `os=linux`, can be repeated. Merged reports keep the labels
+    /// of the configurations finding each warning
+    labels: Vec<(String, String)>,
+    #[structopt(long = "strip-ansi")]
+    /// Strip the ANSI escape sequences of the warnings, like the colors of
+    /// `CARGO_TERM_COLOR=always`, before they are reported
+    strip_ansi: bool,
+    #[structopt(
+        long = "map-path-prefix",
+        value_name = "from=to",
+        number_of_values = 1,
+        parse(try_from_str = postprocess::parse_prefix)
+    )]
+    /// Rewrite the paths of the warnings starting with `from` to start with `to`, like
+    /// `/build/src=/home/me/repo` for a build made in another directory, can be repeated
+    map_path_prefix: Vec<(PathBuf, PathBuf)>,
+    #[structopt(long = "debug-summary")]
+    /// Print the backends, filters, caches and timing the run involved at its end,
+    /// to attach to bug reports. Nothing is sent anywhere
+    debug_summary: bool,
+    /// Set by `--select-targets`, which only clippy supports
+    #[structopt(skip)]
+    select_targets: bool,
+    #[structopt(last = true, value_name = "pathspec")]
+    /// Only scout the changes and warnings of these paths, relative to the current directory,
+    /// like `-- src/api/**`
+    pathspec: Vec<String>,
+}
+
+impl ScoutOptions {
+    fn without_error(&self) -> bool {
+        self.without_error
+            || std::env::var("SCOUT_WITHOUT_ERROR").is_ok_and(|value| env::is_true(&value))
+    }
+
+    /// Forwards `--git-dir` and `--work-tree` to libgit2 and to the git invocations,
+    /// through the environment variables git reads them from.
+    fn export_git_paths(&self) -> Result<(), Error> {
+        for (var, path) in [
+            ("GIT_DIR", &self.git_dir),
+            ("GIT_WORK_TREE", &self.work_tree),
+        ] {
+            if let Some(path) = path {
+                // Git runs from the directories of the members too
+                std::env::set_var(var, std::fs::canonicalize(path)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Where the json lines go: appended to the output file if there is one,
+    /// so that it gets the events of every workspace then the lints, or the standard output.
+    fn json_lines_out(&self) -> Result<Box<dyn Write + Send>, Error> {
+        Ok(match &self.output_file {
+            Some(path) => Box::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?,
+            ),
+            None => Box::new(std::io::stdout()),
+        })
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            retries: self.retries,
+            initial_backoff: Duration::from_millis(self.retry_backoff),
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// The job limit of `--jobs`, or of the `make` running scout.
+    fn job_limit(&self) -> Option<JobLimit> {
+        self.jobs.map(JobLimit::Jobs).or_else(|| {
+            ["CARGO_MAKEFLAGS", "MAKEFLAGS"]
+                .iter()
+                .find_map(|var| schedule::makeflags_limit(&std::env::var(var).ok()?))
+        })
+    }
+
+    /// How many members are linted at once, and the `-j` passed to cargo.
+    fn job_split(&self) -> (usize, Option<usize>) {
+        job_split(self.job_limit(), self.member_jobs)
+    }
+
+    fn limits(&self) -> ResourceLimits {
+        let limits = ResourceLimits {
+            max_memory: self.max_memory,
+            nice: self.nice,
+        };
+        if !limits.is_empty() && !ResourceLimits::is_supported() {
+            warn!("--max-memory and --nice are only supported on Unix, they are ignored");
+        }
+        limits
+    }
+
+    /// The container of `--in-container`, with the repository root mounted in it,
+    /// or the directory of the manifest outside of a repository.
+    fn container(&self) -> Result<Option<Container>, Error> {
+        let Some(image) = &self.in_container else {
+            return Ok(None);
+        };
+        let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+        let host_root = if git::is_repository(&current_dir) {
+            Git::default().root(&current_dir)?
+        } else {
+            let manifest = std::fs::canonicalize(manifest_path(self.cargo_toml.as_deref())?)?;
+            manifest.parent().unwrap_or(&current_dir).to_path_buf()
+        };
+        Ok(Some(Container::new(
+            self.container_engine.as_deref().unwrap_or("docker"),
+            image.as_str(),
+            host_root,
+        )))
+    }
+
+    /// The wrapper of `--runner`, split as the shell of the platform would.
+    fn runner(&self) -> Result<Option<Arc<dyn CommandRunner>>, Error> {
+        let Some(runner) = &self.runner else {
+            return Ok(None);
+        };
+        let argv = command::split(runner, Platform::current())?;
+        Ok(Wrapper::from_argv(argv).map(|wrapper| Arc::new(wrapper) as Arc<dyn CommandRunner>))
+    }
+
+    /// The summary of the options for `--debug-summary`, the run adds what it involved.
+    fn debug_summary(&self) -> DebugSummary {
+        let mut summary = DebugSummary::default();
+        summary
+            .add(
+                "version",
+                format!(
+                    "cargo-scout {}, cargo-scout-lib {}",
+                    env!("CARGO_PKG_VERSION"),
+                    cargo_scout_lib::VERSION
+                ),
+            )
+            .add(
+                "platform",
+                format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            )
+            .add(
+                "arguments",
+                std::env::args().skip(1).collect::<Vec<_>>().join(" "),
+            );
+        match &self.patches {
+            Some(patches) => summary.add("vcs", format!("patches of {}", patches.display())),
+            None => summary.add(
+                "vcs",
+                format!(
+                    "git, against {} ({:?}), git dir {}, work tree {}",
+                    self.branch.join(", "),
+                    self.base_mode,
+                    self.git_dir
+                        .as_ref()
+                        .map_or_else(|| "discovered".into(), |dir| dir.display().to_string()),
+                    self.work_tree
+                        .as_ref()
+                        .map_or_else(|| "discovered".into(), |dir| dir.display().to_string()),
+                ),
+            ),
+        };
+        let runs = match (&self.in_container, &self.runner) {
+            (Some(image), _) => format!(
+                "in a {} container of {}",
+                self.container_engine.as_deref().unwrap_or("docker"),
+                image
+            ),
+            (None, Some(runner)) => format!("through {}", runner),
+            (None, None) => "locally".to_string(),
+        };
+        let (member_jobs, cargo_jobs) = self.job_split();
+        summary
+            .add(
+                "linter",
+                format!(
+                    "{}, {} member(s) at once, cargo -j {}, {} retries{}",
+                    runs,
+                    member_jobs,
+                    cargo_jobs.map_or_else(|| "default".to_string(), |jobs| jobs.to_string()),
+                    self.retries,
+                    if self.pipeline { ", pipelined" } else { "" }
+                ),
+            )
+            .add("filters", self.filters().join(", "))
+            .add("output", format!("{:?}", self.output_format));
+        summary
+    }
+
+    /// The options changing which members are linted and which lints are reported.
+    fn filters(&self) -> Vec<String> {
+        let mut filters = vec![format!("{:?} match", self.match_strategy)];
+        if !self.members.is_empty() {
+            filters.push(format!("members {}", self.members.join(" ")));
+        }
+        let flags = [
+            (self.no_diff, "no diff"),
+            (self.workspace, "whole workspace"),
+            (self.skip_tests_code, "tests code skipped"),
+            (self.with_dependents, "with dependents"),
+            (self.explain_filtering, "explained"),
+            (self.select_targets, "changed targets only"),
+            (self.ignore_whitespace, "whitespace changes ignored"),
+            (
+                self.ignore_comment_only_changes,
+                "comment-only changes ignored",
+            ),
+            (self.feature_matrix, "feature matrix"),
+        ];
+        filters.extend(
+            flags
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, filter)| (*filter).to_string()),
+        );
+        filters.extend(
+            self.max_diff_lines
+                .map(|lines| format!("at most {} diff lines", lines)),
+        );
+        filters.extend(
+            self.max_diff_files
+                .map(|files| format!("at most {} diff files", files)),
+        );
+        filters.extend(
+            self.time_budget
+                .map(|secs| format!("time budget of {}s", secs)),
+        );
+        filters.extend(self.order.map(|order| format!("{:?} order", order)));
+        filters.extend(self.shard.map(|shard| format!("shard {}", shard)));
+        filters
+    }
+
+    /// What rewrites the warnings as the linter found them,
+    /// their paths with the `path_map` of the workspace at `root` too.
+    fn post_processors(&self, path_map: &[PathMapping], root: &Path) -> PostProcessors {
+        let mut post_processors = PostProcessors::default();
+        if self.strip_ansi {
+            post_processors.push(StripAnsi);
+        }
+        let prefixes: Vec<(PathBuf, PathBuf)> = self
+            .map_path_prefix
+            .iter()
+            .cloned()
+            .chain(path_map.iter().map(|mapping| mapping.prefix(root)))
+            .collect();
+        if !prefixes.is_empty() {
+            post_processors.push(PathPrefixMap::new(prefixes));
+        }
+        post_processors
+    }
+
+    fn change_filter(&self) -> ChangeFilter {
+        ChangeFilter {
+            whitespace: self.ignore_whitespace,
+            comments: self.ignore_comment_only_changes,
+        }
+    }
+
+    /// The diff against the target branches, without the changes the options ignore.
+    fn git(&self) -> Git {
+        Git::with_targets(self.branch.clone(), self.base_mode)
+            .with_change_filter(self.change_filter())
+            .with_pathspec(self.pathspec())
+    }
+
+    /// The paths the run is restricted to, relative to the current directory.
+    fn pathspec(&self) -> Pathspec {
+        let current_dir = std::env::current_dir()
+            .and_then(std::fs::canonicalize)
+            .unwrap_or_default();
+        Pathspec::new(current_dir, self.pathspec.clone())
+    }
+
+    /// The patches of `dir`, without the changes the options ignore.
+    fn patch_series(&self, dir: &Path) -> Result<PatchSeries, Error> {
+        Ok(PatchSeries::from_dir(dir)?.with_change_filter(self.change_filter()))
+    }
+
+    /// The text reporter of the lints of `outcome`.
+    fn text_reporter(&self, outcome: &Outcome) -> TextReporter {
+        let mut reporter = TextReporter::default();
+        reporter
+            .set_max_message_lines(self.max_message_lines)
+            .set_first_line_only(self.first_line_only)
+            .set_features(outcome.features.clone());
+        reporter
+    }
 }
 
 // There is no logic to test
 #[cfg(not(tarpaulin_include))]
-fn main() -> Result<(), Error> {
-    match Command::from_args() {
+fn main() {
+    let command = match Command::clap().get_matches_safe() {
+        Ok(matches) => Command::from_clap(&matches),
+        // --help and --version aren't errors
+        Err(error) if !error.use_stderr() => error.exit(),
+        Err(error) => {
+            eprintln!("{}", error.message);
+            std::process::exit(exit::USAGE);
+        }
+    };
+    output::init(command.color());
+    signal::install();
+    let result = match command {
         Command::Fmt(opts) => run_fmt(opts),
         Command::Lint(opts) => run_lint(opts),
+        Command::Suggest(opts) => run_suggest(opts),
+        Command::Allow(opts) => run_allow(opts),
+        #[cfg(feature = "http")]
+        Command::Defer(opts) => run_defer(opts),
+        Command::CheckConfig(opts) => run_check_config(&opts),
+        Command::Schema(opts) => run_schema(&opts),
+        Command::Config(ConfigCommand::Show(opts)) => run_config_show(&opts),
+        Command::Config(ConfigCommand::Migrate(opts)) => run_config_migrate(&opts),
+        Command::DiffResults(opts) => run_diff_results(&opts),
+        Command::Merge(opts) => run_merge(&opts),
+        Command::Doctor(opts) => run_doctor(&opts),
+        #[cfg(feature = "store")]
+        Command::Query(opts) => run_query(opts),
+        Command::BenchIntersect(opts) => {
+            run_bench(&opts);
+            Ok(())
+        }
+    };
+    if let Some(signal) = signal::received() {
+        error!(
+            "Interrupted by signal {}, only the members linted until then were reported",
+            signal
+        );
+        std::process::exit(signal::exit_code(signal));
+    }
+    match &result {
+        Err(Error::DiffTooLarge(reason)) => {
+            warn!("[Scout] - diff too large, skipped: {}", reason);
+        }
+        Err(Error::NoChanges(status)) => warn!("[Scout] - {}, nothing was scouted", status),
+        Err(Error::LinterCrashed(members, message)) => error!(
+            "[Scout] - the linter crashed on {}, please report it:\n{}",
+            members, message
+        ),
+        Err(error) => eprintln!("Error: {:?}", error),
+        Ok(()) => {}
     }
+    std::process::exit(exit::code(&result));
 }
 
+/// The manifest passed with `--cargo-toml`,
+/// or the one cargo would use from the current directory.
 #[cfg(not(tarpaulin_include))]
-fn run_lint(opts: LintOptions) -> Result<(), Error> {
-    let fail_if_errors = opts.without_error;
+fn manifest_path(cargo_toml: Option<&str>) -> Result<PathBuf, Error> {
+    if let Some(cargo_toml) = cargo_toml {
+        return Ok(PathBuf::from(cargo_toml));
+    }
+    let current_dir = std::env::current_dir()?;
+    CargoConfig::discover_manifest(&current_dir).ok_or_else(|| {
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "could not find Cargo.toml in {} or any parent directory",
+                current_dir.display()
+            ),
+        ))
+    })
+}
 
-    let vcs = Git::with_target(opts.branch);
-    let config = CargoConfig::from_manifest_path(opts.cargo_toml, &opts.members)?;
+#[cfg(not(tarpaulin_include))]
+fn run_check_config(opts: &CheckConfigOptions) -> Result<(), Error> {
+    let config = CargoConfig::from_manifest_path(manifest_path(opts.cargo_toml.as_deref())?, &[])?;
+    let path = config.root().join(SCOUT_TOML);
+    if !path.is_file() {
+        info!("No {} found in {}", SCOUT_TOML, config.root().display());
+        return Ok(());
+    }
+    let diagnostics = check::check(&std::fs::read_to_string(&path)?, &config.members());
+    return_diagnostics(&path, &diagnostics)
+}
+
+fn return_diagnostics(path: &Path, diagnostics: &[Diagnostic]) -> Result<(), Error> {
+    for diagnostic in diagnostics {
+        match diagnostic.severity {
+            Severity::Error => error!("{}:{}", path.display(), diagnostic),
+            Severity::Warning => warn!("{}:{}", path.display(), diagnostic),
+        }
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(Error::InvalidConfig(errors));
+    }
+    success!("{} is valid", path.display());
+    Ok(())
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_config_show(opts: &ConfigShowOptions) -> Result<(), Error> {
+    let config = CargoConfig::from_manifest_path(manifest_path(opts.cargo_toml.as_deref())?, &[])?;
+    let layered = Layered::load(&Locations::discover(config.root()), std::env::vars())?;
+    let table = ScoutConfig::from_layers(&layered)?.to_table()?;
+    if opts.origin {
+        for line in layered.annotate(&table) {
+            println!("{}", line);
+        }
+    } else {
+        print!("{}", toml::to_string(&table)?);
+    }
+    Ok(())
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_config_migrate(opts: &ConfigMigrateOptions) -> Result<(), Error> {
+    let config = CargoConfig::from_manifest_path(manifest_path(opts.cargo_toml.as_deref())?, &[])?;
+    let path = config.root().join(SCOUT_TOML);
+    if !path.is_file() {
+        info!("No {} found in {}", SCOUT_TOML, config.root().display());
+        return Ok(());
+    }
+    let Some(migrated) = schema::migrate(&std::fs::read_to_string(&path)?)? else {
+        success!(
+            "{} is at schema version {}",
+            path.display(),
+            schema::SCHEMA_VERSION
+        );
+        return Ok(());
+    };
+    if opts.check {
+        warn!(
+            "{} must be migrated to schema version {}, run `cargo scout config migrate`",
+            path.display(),
+            schema::SCHEMA_VERSION
+        );
+        return Err(Error::NotClean);
+    }
+    std::fs::write(&path, migrated)?;
+    success!(
+        "Migrated {} to schema version {}",
+        path.display(),
+        schema::SCHEMA_VERSION
+    );
+    Ok(())
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_schema(opts: &SchemaOptions) -> Result<(), Error> {
+    let Some(dir) = &opts.out_dir else {
+        print!("{}", opts.format.render()?);
+        return Ok(());
+    };
+    std::fs::create_dir_all(dir)?;
+    for kind in SchemaKind::ALL {
+        let path = dir.join(kind.file_name());
+        std::fs::write(&path, kind.render()?)?;
+        info!("[Scout] - wrote {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_diff_results(opts: &DiffResultsOptions) -> Result<(), Error> {
+    let old = JsonReport::from_path(&opts.old)?;
+    let new = JsonReport::from_path(&opts.new)?;
+    return_report_diff(&old.diff(&new))
+}
+
+fn run_merge(opts: &MergeOptions) -> Result<(), Error> {
+    let reports = opts
+        .reports
+        .iter()
+        .map(|path| {
+            let report = JsonReport::from_path(path)?;
+            if report.version != json::FORMAT_VERSION {
+                return Err(Error::Merge(format!(
+                    "{} has format version {}, cargo-scout {} reads version {}",
+                    path.display(),
+                    report.version,
+                    cargo_scout_lib::VERSION,
+                    json::FORMAT_VERSION
+                )));
+            }
+            Ok(report)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let count = reports.len();
+    let found: usize = reports.iter().map(|report| report.lints.len()).sum();
+    let merged = JsonReport::merge(reports);
+    match &opts.output {
+        Some(path) => {
+            merged.write(path)?;
+            info!(
+                "[Scout] - merged {} reports into {}: {} lint(s), {} duplicate(s) dropped",
+                count,
+                path.display(),
+                merged.lints.len(),
+                found - merged.lints.len()
+            );
+        }
+        None => println!("{}", merged.to_json()?),
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "store", not(tarpaulin_include)))]
+fn run_query(opts: QueryOptions) -> Result<(), Error> {
+    let store = Store::new(&opts.store);
+    let query = Query {
+        code: opts.code,
+        author: opts.author,
+        member: opts.member,
+        since: opts.since,
+        until: opts.until,
+        count_by: opts.count_by,
+    };
+    if query.count_by.is_some() {
+        for count in store.counts(&query)? {
+            println!(
+                "{:>6} {}",
+                count.count,
+                count.key.as_deref().unwrap_or("unknown")
+            );
+        }
+        return Ok(());
+    }
+    let rows = store.lints(&query)?;
+    for row in &rows {
+        println!("{}", row.line());
+    }
+    info!("{} lint(s) match", rows.len());
+    Ok(())
+}
+
+/// Splits the job limit between the members linted at once.
+///
+/// A jobserver already bounds cargo, which is then run without `-j`.
+fn job_split(limit: Option<JobLimit>, member_jobs: usize) -> (usize, Option<usize>) {
+    match limit {
+        Some(JobLimit::Jobs(jobs)) => {
+            let (member_jobs, cargo_jobs) = schedule::split_jobs(jobs, member_jobs);
+            (member_jobs, Some(cargo_jobs))
+        }
+        Some(JobLimit::Jobserver) | None => (member_jobs, None),
+    }
+}
+
+fn report_diff_lines(diff: &ReportDiff) -> Vec<String> {
+    let line = |sign: char, lint: &JsonLint| {
+        format!(
+            "{} {}:{}: {}",
+            sign,
+            lint.path,
+            lint.lines[0],
+            lint.message.lines().next().unwrap_or_default()
+        )
+    };
+    diff.added
+        .iter()
+        .map(|lint| line('+', lint))
+        .chain(diff.removed.iter().map(|lint| line('-', lint)))
+        .collect()
+}
+
+/// Fails if the new run added lints.
+fn return_report_diff(diff: &ReportDiff) -> Result<(), Error> {
+    for line in report_diff_lines(diff) {
+        println!("{}", line);
+    }
+    if diff.added.is_empty() {
+        success!("No lint added, {} lint(s) removed", diff.removed.len());
+        Ok(())
+    } else {
+        warn!(
+            "{} lint(s) added, {} lint(s) removed",
+            diff.added.len(),
+            diff.removed.len()
+        );
+        Err(Error::NotClean)
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_doctor(opts: &DoctorOptions) -> Result<(), Error> {
+    let dir = std::env::temp_dir().join(format!("cargo-scout-doctor-{}", std::process::id()));
+    let checks = doctor::run(&dir);
+    if opts.keep {
+        info!(
+            "[Doctor] - kept the synthetic repository in {}",
+            dir.display()
+        );
+    } else {
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    for check in &checks {
+        match &check.outcome {
+            Ok(found) => success!("[Doctor] - {}: {}", check.name, found),
+            Err(reason) => {
+                error!("[Doctor] - {}: {}", check.name, reason);
+                return Err(Error::Doctor(check.name.to_string()));
+            }
+        }
+    }
+    success!("Everything works, cargo scout is ready");
+    Ok(())
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_bench(opts: &BenchOptions) {
+    let lints = bench::synthetic_lints(opts);
+    let sections = bench::synthetic_sections(opts);
+    info!(
+        "Intersecting {} lints with {} sections over {} files, {} times",
+        lints.len(),
+        sections.len(),
+        opts.files,
+        opts.iterations
+    );
+    let result = bench::run(&lints, &sections, opts.iterations);
+    success!(
+        "{} lints matched, {:?} per intersection, {:.0} lints/s",
+        result.matched,
+        result.mean(),
+        result.lints_per_second(lints.len())
+    );
+}
+
+/// The clippy linter configured by the options.
+#[cfg(not(tarpaulin_include))]
+fn clippy(opts: &LintOptions) -> Result<Clippy, Error> {
+    let preset = match &opts.preset {
+        Some(name) => {
+            let manifest = manifest_path(opts.scout.cargo_toml.as_deref())?;
+            let workspace_root = manifest.parent().unwrap_or_else(|| Path::new("."));
+            Some(
+                ScoutConfig::discover(workspace_root)?
+                    .preset(name)
+                    .ok_or_else(|| Error::UnknownPreset(name.clone()))?,
+            )
+        }
+        None => None,
+    };
     let mut linter = Clippy::default();
     linter
         .set_verbose(opts.verbose)
         .set_no_default_features(opts.no_default_features)
         .set_all_features(opts.all_features)
-        .set_features(opts.features)
+        .set_features(opts.features.clone())
         .set_preview(opts.preview)
         .set_all_targets(opts.all_targets)
-        .set_target(opts.target)
-        .set_test(opts.tests);
-    let scout = Scout::new(vcs, config, linter);
-    let relevant_lints = scout.run()?;
-    return_warnings(&relevant_lints, fail_if_errors)
+        .set_target(opts.target.clone())
+        .set_test(opts.tests)
+        .set_jobs(opts.scout.job_split().1)
+        .set_fix(opts.fix)
+        .set_short_messages(opts.short_messages)
+        .set_max_lints(Some(opts.max_lints).filter(|max_lints| *max_lints > 0))
+        .set_diagnostic_width(if opts.short_messages {
+            None
+        } else {
+            Capabilities::detect(opts.scout.color).width
+        })
+        .set_retry(opts.scout.retry_policy())
+        .set_limits(opts.scout.limits())
+        .set_preset(preset)
+        .set_container(opts.scout.container()?)
+        .set_runner(opts.scout.runner()?);
+    Ok(linter)
+}
+
+/// The linter of the lint options: clippy, or the lints read beforehand.
+enum LintSource {
+    Clippy(Box<Clippy>),
+    Ingested(Ingested),
+}
+
+impl Linter for LintSource {
+    fn lints(&self, working_dir: impl Into<PathBuf>) -> Result<Vec<Lint>, Error> {
+        match self {
+            Self::Clippy(clippy) => clippy.lints(working_dir),
+            Self::Ingested(ingested) => ingested.lints(working_dir),
+        }
+    }
+
+    fn invoke(&self, invocation: &Invocation) -> Result<Vec<Lint>, Error> {
+        match self {
+            Self::Clippy(clippy) => clippy.invoke(invocation),
+            Self::Ingested(ingested) => ingested.invoke(invocation),
+        }
+    }
+
+    fn command(&self, invocation: &Invocation) -> Option<LinterCommand> {
+        match self {
+            Self::Clippy(clippy) => clippy.command(invocation),
+            Self::Ingested(ingested) => ingested.command(invocation),
+        }
+    }
+
+    fn retries(&self) -> u32 {
+        match self {
+            Self::Clippy(clippy) => clippy.retries(),
+            Self::Ingested(ingested) => ingested.retries(),
+        }
+    }
+}
+
+/// Clippy, or with `--lints-from-stdin` and `--sarif` the lints read from stdin and the SARIF files.
+#[cfg(not(tarpaulin_include))]
+fn linter(opts: &mut LintOptions) -> Result<LintSource, Error> {
+    if !opts.lints_from_stdin && opts.sarif.is_empty() {
+        return clippy(opts).map(|clippy| LintSource::Clippy(Box::new(clippy)));
+    }
+    let manifest = manifest_path(opts.scout.cargo_toml.as_deref())?;
+    let workspace_root =
+        std::fs::canonicalize(manifest.parent().unwrap_or_else(|| Path::new(".")))?;
+    let mut ingested = Ingested::default();
+    if opts.lints_from_stdin {
+        ingested.extend(Ingested::from_reader(
+            std::io::stdin(),
+            opts.lints_format,
+            &workspace_root,
+        )?);
+    }
+    for path in &opts.sarif {
+        ingested.extend(Ingested::from_path(
+            path,
+            LintsFormat::Sarif,
+            &workspace_root,
+        )?);
+    }
+    info!("[Scout] - read {} lints", ingested.len());
+    // The cached results are those of earlier inputs
+    opts.scout.no_result_cache = true;
+    Ok(LintSource::Ingested(ingested))
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_lint(mut opts: LintOptions) -> Result<(), Error> {
+    opts.scout.export_git_paths()?;
+    opts.scout.select_targets = opts.select_targets;
+    let linter = linter(&mut opts)?;
+    if !opts.fix {
+        return run(opts.scout, linter);
+    }
+    // The sections must be computed before the fix changes the lines they refer to
+    let vcs = opts.scout.git();
+    let root = vcs.root(std::fs::canonicalize(std::env::current_dir()?)?)?;
+    let sections = vcs.sections(&root)?;
+    let guard = FixGuard::record(&root)?;
+    // Reusing the results of an earlier run wouldn't apply the fixes
+    opts.scout.no_result_cache = true;
+    let result = run(opts.scout, linter);
+    let summary = guard.restrict(&sections)?;
+    info!(
+        "[Scout] - kept {} fixed hunk(s) in the diff, reverted {} outside of it",
+        summary.kept, summary.reverted
+    );
+    result
+}
+
+/// Applies the fixes of the lints in the diff, then commits and pushes them as asked.
+#[cfg(not(tarpaulin_include))]
+fn run_suggest(mut opts: SuggestOptions) -> Result<(), Error> {
+    opts.lint.scout.export_git_paths()?;
+    opts.lint.scout.select_targets = opts.lint.select_targets;
+    let mut linter = linter(&mut opts.lint)?;
+    if let LintSource::Clippy(clippy) = &mut linter {
+        clippy.set_fix(false);
+    }
+    let scout_opts = &opts.lint.scout;
+    let config = CargoConfig::from_manifest_path(
+        manifest_path(scout_opts.cargo_toml.as_deref())?,
+        &scout_opts.members,
+    )?;
+    let workspace_root = config.root().clone();
+    let vcs = scout_opts.git();
+    let root = vcs.root(std::fs::canonicalize(std::env::current_dir()?)?)?;
+    let debug_summary = scout_opts.debug_summary.then(|| scout_opts.debug_summary());
+    let outcome = with_debug_summary(debug_summary, |summary| {
+        let scout_config = ScoutConfig::discover(&workspace_root)?;
+        let outcome = scout(vcs, config, scout_config, linter, scout_opts)?;
+        summary.add_run(&outcome);
+        Ok(outcome)
+    })?;
+    with_crashes(
+        apply_fixes(&outcome.lints, &root, &workspace_root, &opts),
+        &outcome.crashes,
+    )
+}
+
+/// Applies the fixes of `lints`, then commits and pushes them as asked.
+#[cfg(not(tarpaulin_include))]
+fn apply_fixes(
+    lints: &[Lint],
+    root: &Path,
+    workspace_root: &Path,
+    opts: &SuggestOptions,
+) -> Result<(), Error> {
+    let fixes = AppliedFixes::apply(lints, workspace_root)?;
+    if fixes.is_empty() {
+        success!("No machine-applicable fix for the lints of the diff");
+        return Ok(());
+    }
+    success!(
+        "Applied the fixes of {} lint(s) to {} file(s)",
+        fixes.lints.len(),
+        fixes.files.len()
+    );
+    if !opts.commit {
+        return Ok(());
+    }
+    let id = fixes.commit(root)?;
+    info!("[Scout] - committed the fixes as {}", id);
+    if let Some(branch) = &opts.push {
+        suggest::push(root, &opts.remote, branch)?;
+        info!("[Scout] - pushed the fixes to {} {}", opts.remote, branch);
+    }
+    Ok(())
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_allow(mut opts: AllowOptions) -> Result<(), Error> {
+    opts.lint.scout.export_git_paths()?;
+    let scout_opts = &opts.lint.scout;
+    let vcs = scout_opts.git();
+    let root = vcs.root(std::fs::canonicalize(std::env::current_dir()?)?)?;
+    let reviewer = match opts.reviewed_by.clone().or_else(|| allow::reviewer(&root)) {
+        Some(reviewer) => reviewer,
+        None => {
+            return Err(Error::Allow(
+                "no reviewer for the `// scout: reviewed by` comment, set --reviewed-by or the git `user.name`"
+                    .to_string(),
+            ))
+        }
+    };
+    let lints = lints_by_fingerprint(
+        &mut opts.lint,
+        opts.report.as_deref(),
+        &root,
+        &opts.fingerprints,
+        |missing| Error::Allow(format!("no lint has the fingerprint {}", missing)),
+    )?;
+    let lints: Vec<Lint> = lints.into_iter().map(|(lint, _)| lint).collect();
+    for allowed in allow::allow(&lints, &reviewer, &root)? {
+        success!(
+            "Allowed {} on {} in {}",
+            allowed.codes.join(", "),
+            allowed.item,
+            Path::new(&allowed.path)
+                .strip_prefix(&root)
+                .unwrap_or_else(|_| allowed.path.as_ref())
+                .display()
+        );
+    }
+    Ok(())
+}
+
+/// Files an issue for each lint of the fingerprints, and records them in the baseline.
+#[cfg(feature = "http")]
+#[cfg(not(tarpaulin_include))]
+fn run_defer(mut opts: DeferOptions) -> Result<(), Error> {
+    opts.lint.scout.export_git_paths()?;
+    let root = opts
+        .lint
+        .scout
+        .git()
+        .root(std::fs::canonicalize(std::env::current_dir()?)?)?;
+    let workspace_root = CargoConfig::from_manifest_path(
+        manifest_path(opts.lint.scout.cargo_toml.as_deref())?,
+        &opts.lint.scout.members,
+    )?
+    .root()
+    .clone();
+    let config = ScoutConfig::discover(&workspace_root)?.issues;
+    if config.url.is_none() {
+        return Err(Error::Issues(
+            "`issues.url` is needed to file issues".to_string(),
+        ));
+    }
+    let baseline_path = workspace_root.join(&config.baseline);
+    let mut baseline = Baseline::from_path(&baseline_path)?;
+    let lints = lints_by_fingerprint(
+        &mut opts.lint,
+        opts.report.as_deref(),
+        &root,
+        &opts.fingerprints,
+        |missing| Error::Issues(format!("no lint has the fingerprint {}", missing)),
+    )?;
+    let exporter = IssueExporter::new(config);
+    for (lint, fingerprint) in lints {
+        let path = Path::new(&lint.location.path)
+            .strip_prefix(&root)
+            .unwrap_or_else(|_| lint.location.path.as_ref())
+            .display()
+            .to_string();
+        if let Some(issue) = baseline.issue(fingerprint) {
+            info!("[Scout] - {} is already deferred to {}", fingerprint, issue);
+            continue;
+        }
+        let issue = exporter.create(&lint, fingerprint, &root)?;
+        success!(
+            "Deferred {} of {}:{} to {}",
+            lint.code.as_deref().unwrap_or("the lint"),
+            path,
+            lint.location.position(),
+            issue
+        );
+        baseline.defer(Deferred {
+            fingerprint: fingerprint.to_string(),
+            code: lint.code.clone(),
+            path,
+            issue,
+        });
+        // Record each issue as soon as it's filed, so none is filed twice if the next one fails
+        baseline.write(&baseline_path)?;
+    }
+    Ok(())
+}
+
+/// The lints of the `wanted` fingerprints, read from a json report or found by linting the diff,
+/// with their fingerprints relative to `root`.
+///
+/// A fingerprint no lint has is an error, made by `missing`.
+#[cfg(not(tarpaulin_include))]
+fn lints_by_fingerprint(
+    lint_opts: &mut LintOptions,
+    report: Option<&Path>,
+    root: &Path,
+    wanted: &[Fingerprint],
+    missing: impl Fn(Fingerprint) -> Error,
+) -> Result<Vec<(Lint, Fingerprint)>, Error> {
+    let lints = match report {
+        Some(path) => JsonReport::from_path(path)?.to_lints(root),
+        None => {
+            lint_opts.scout.select_targets = lint_opts.select_targets;
+            let linter = linter(lint_opts)?;
+            let scout_opts = &lint_opts.scout;
+            let config = CargoConfig::from_manifest_path(
+                manifest_path(scout_opts.cargo_toml.as_deref())?,
+                &scout_opts.members,
+            )?;
+            let scout_config = ScoutConfig::discover(config.root())?;
+            scout(scout_opts.git(), config, scout_config, linter, scout_opts)?.lints
+        }
+    };
+    let fingerprints = fingerprint::fingerprints(&lints, root);
+    let mut found = Vec::new();
+    for wanted in wanted {
+        let count = found.len();
+        found.extend(
+            lints
+                .iter()
+                .zip(&fingerprints)
+                .filter(|(_, fingerprint)| *fingerprint == wanted)
+                .map(|(lint, fingerprint)| (lint.clone(), *fingerprint)),
+        );
+        if found.len() == count {
+            return Err(missing(*wanted));
+        }
+    }
+    Ok(found)
 }
 
 #[cfg(not(tarpaulin_include))]
 fn run_fmt(opts: FmtOptions) -> Result<(), Error> {
-    let fail_if_errors = opts.without_error;
+    opts.scout.export_git_paths()?;
+    let mut linter = RustFmt::default();
+    linter
+        .set_retry(opts.scout.retry_policy())
+        .set_limits(opts.scout.limits())
+        .set_container(opts.scout.container()?)
+        .set_runner(opts.scout.runner()?);
+    run(opts.scout, linter)
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run(opts: ScoutOptions, linter: impl Linter) -> Result<(), Error> {
+    let debug_summary = opts.debug_summary.then(|| opts.debug_summary());
+    if let (OutputFormat::JsonLines, Some(path)) = (opts.output_format, &opts.output_file) {
+        // The events are appended to it as the run goes
+        std::fs::File::create(path)?;
+    }
+    with_debug_summary(debug_summary, |summary| {
+        run_workspaces(opts, linter, summary)
+    })
+}
+
+/// Runs `run`, which adds what it involved to the summary, then prints the `summary`
+/// to stderr if there is one, so that it stays out of the json outputs.
+#[cfg(not(tarpaulin_include))]
+fn with_debug_summary<T>(
+    summary: Option<DebugSummary>,
+    run: impl FnOnce(&mut DebugSummary) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let started = Instant::now();
+    let printed = summary.is_some();
+    let mut summary = summary.unwrap_or_default();
+    let result = run(&mut summary);
+    if printed {
+        summary.add_total(started.elapsed()).add(
+            "result",
+            result
+                .as_ref()
+                .map_or_else(ToString::to_string, |_| "ok".to_string()),
+        );
+        for line in summary.lines() {
+            eprintln!("{}", line);
+        }
+    }
+    result
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_workspaces(
+    opts: ScoutOptions,
+    linter: impl Linter,
+    summary: &mut DebugSummary,
+) -> Result<(), Error> {
+    if opts.all_workspaces {
+        return run_all_workspaces(opts, &linter, summary);
+    }
+
+    let config =
+        CargoConfig::from_manifest_path(manifest_path(opts.cargo_toml.as_deref())?, &opts.members)?;
+    // Loaded once, its includes may be fetched over the network
+    let scout_config = ScoutConfig::discover(config.root())?;
+    if opts.dry_run || opts.emit_plan.is_some() {
+        return dry_run(config, &scout_config, linter, &opts);
+    }
+    let workspace_root = config.root().clone();
+    let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+    if let Some(patches) = &opts.patches {
+        let series = opts.patch_series(patches)?;
+        let root = series.root(current_dir)?;
+        let opts = with_run_id(opts, &series, &root, &scout_config)?;
+        let mut outcome = scout(&series, config, scout_config.clone(), linter, &opts)?;
+        summary.add_run(&outcome);
+        drop_deferred(
+            &series,
+            &mut outcome.lints,
+            &root,
+            &workspace_root,
+            &scout_config,
+            &opts,
+        )?;
+        rank(&series, &mut outcome.lints, &root, &scout_config, &opts)?;
+        annotate_sources(&series, &outcome.lints, &root, &opts)?;
+        return return_lints(
+            &outcome,
+            &root,
+            &workspace_root,
+            &scout_config,
+            &opts,
+            Some(&series),
+        );
+    }
+    if !opts.no_vcs_fallback && !git::is_repository(&current_dir) {
+        warn!(
+            "[Scout] - {} is not in a git repository, every warning is reported",
+            current_dir.display()
+        );
+        summary.add("vcs", "none, every warning is reported");
+        let mut opts = opts;
+        opts.no_diff = true;
+        let vcs = Unversioned::new(&workspace_root);
+        return run_vcs(
+            vcs,
+            config,
+            &scout_config,
+            linter,
+            opts,
+            current_dir,
+            summary,
+        );
+    }
+    let vcs = opts.git();
+    run_vcs(
+        vcs,
+        config,
+        &scout_config,
+        linter,
+        opts,
+        current_dir,
+        summary,
+    )
+}
+
+/// Scouts the workspace of `config` with the diff of `vcs`, and reports its lints.
+#[cfg(not(tarpaulin_include))]
+fn run_vcs(
+    vcs: impl VCS,
+    config: CargoConfig,
+    scout_config: &ScoutConfig,
+    linter: impl Linter,
+    opts: ScoutOptions,
+    current_dir: PathBuf,
+    summary: &mut DebugSummary,
+) -> Result<(), Error> {
+    let workspace_root = config.root().clone();
+    let root = vcs.root(current_dir)?;
+    let opts = with_run_id(opts, &vcs, &root, scout_config)?;
+    let cache =
+        result_cache(&vcs, &root, &workspace_root, scout_config, &opts, summary)?.map(|context| {
+            let key = context.key(std::env::args().skip(1));
+            (context.cache, key)
+        });
+    let cached = cache.as_ref().and_then(|(cache, key)| cache.get(key));
+    if let Some((cache, key)) = &cache {
+        let outcome = if cached.is_some() { "hit" } else { "miss" };
+        summary.add(
+            "result cache",
+            format!("{} for {} in {}", outcome, key, cache.dir().display()),
+        );
+    }
+    let mut outcome = if let Some(report) = cached {
+        info!("[Scout] - reusing the results of an earlier run of the same commits");
+        let empty_diff = if report.status == Status::NoChanges {
+            let targets = vcs.targets(&root)?;
+            info!(
+                "[Scout] - {}, nothing to scout",
+                empty_diff::describe(&targets)
+            );
+            Some(targets)
+        } else {
+            None
+        };
+        Outcome {
+            lints: report.to_lints(&root),
+            features: report.to_features(&root),
+            empty_diff,
+            ..Outcome::default()
+        }
+    } else {
+        let outcome = scout(&vcs, config, scout_config.clone(), linter, &opts)?;
+        // Partial results aren't reused
+        let complete = signal::received().is_none() && outcome.is_complete();
+        if let Some((cache, key)) = cache.as_ref().filter(|_| complete) {
+            cache.put(key, &json_report(&outcome, &root, &opts))?;
+        }
+        outcome
+    };
+    summary.add_run(&outcome);
+    drop_deferred(
+        &vcs,
+        &mut outcome.lints,
+        &root,
+        &workspace_root,
+        scout_config,
+        &opts,
+    )?;
+    rank(&vcs, &mut outcome.lints, &root, scout_config, &opts)?;
+    annotate_sources(&vcs, &outcome.lints, &root, &opts)?;
+    return_lints(&outcome, &root, &workspace_root, scout_config, &opts, None)
+}
+
+/// Scouts each workspace of the repository in its own directory, with the same diff.
+///
+/// The lints of every workspace are reported together.
+#[cfg(not(tarpaulin_include))]
+fn run_all_workspaces(
+    opts: ScoutOptions,
+    linter: &impl Linter,
+    summary: &mut DebugSummary,
+) -> Result<(), Error> {
+    let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+    let vcs = opts.git();
+    let root = vcs.root(current_dir)?;
+    let root_config = ScoutConfig::discover(&root)?;
+    let opts = &with_run_id(opts, &vcs, &root, &root_config)?;
+    let workspaces = CargoConfig::discover_workspaces(&root, &root_config.workspaces);
+    let mut outcome = Outcome::default();
+    for manifest in workspaces {
+        let config = CargoConfig::from_manifest_path(&manifest, &opts.members)?;
+        info!("[Scout] - workspace {}", config.root().display());
+        let scout_config = ScoutConfig::discover(config.root())?;
+        if opts.dry_run {
+            dry_run(config, &scout_config, linter, opts)?;
+            continue;
+        }
+        outcome.extend(scout(&vcs, config, scout_config, linter, opts)?);
+    }
+    if opts.dry_run {
+        return Ok(());
+    }
+    summary.add_run(&outcome);
+    drop_deferred(&vcs, &mut outcome.lints, &root, &root, &root_config, opts)?;
+    rank(&vcs, &mut outcome.lints, &root, &root_config, opts)?;
+    annotate_sources(&vcs, &outcome.lints, &root, opts)?;
+    return_lints(&outcome, &root, &root, &root_config, opts, None)
+}
+
+/// Sets the run id of `opts` to its default, unless `--run-id` sets it.
+#[cfg(not(tarpaulin_include))]
+fn with_run_id(
+    mut opts: ScoutOptions,
+    vcs: &impl VCS,
+    root: &Path,
+    scout_config: &ScoutConfig,
+) -> Result<ScoutOptions, Error> {
+    if opts.run_id.is_none() {
+        let mut commits = vcs.targets(root)?;
+        commits.extend(git::head_commit(root));
+        opts.run_id = Some(run_id::default_run_id(&commits, scout_config));
+    }
+    Ok(opts)
+}
+
+/// What the result cache keys a run on besides its arguments, if its results can be reused.
+///
+/// The results are reused for the same commits, configuration and options,
+/// as long as the working tree is clean and the lints don't need their suggestions.
+#[cfg(not(tarpaulin_include))]
+fn result_cache(
+    vcs: &impl VCS,
+    root: &Path,
+    workspace_root: &Path,
+    scout_config: &ScoutConfig,
+    opts: &ScoutOptions,
+    summary: &mut DebugSummary,
+) -> Result<Option<CacheContext>, Error> {
+    let disabled = if opts.no_result_cache {
+        Some("disabled")
+    } else if opts.explain_filtering || opts.emit_patches.is_some() {
+        Some("unused, the lints need their explanations or suggestions")
+    } else if !git::is_clean(root) {
+        Some("unused, the working tree has changes")
+    } else {
+        None
+    };
+    if let Some(reason) = disabled {
+        summary.add("result cache", reason);
+        return Ok(None);
+    }
+    let Some(head) = git::head_commit(root) else {
+        summary.add("result cache", "unused, there is no commit");
+        return Ok(None);
+    };
+    let mut commits = vcs.targets(root)?;
+    commits.push(head);
+    let context: Vec<String> = std::env::vars()
+        .filter(|(key, _)| {
+            (key.starts_with("SCOUT_") && key != "SCOUT_OUTPUT_FILE")
+                || key == "RUSTUP_TOOLCHAIN"
+                || key == "CLIPPY_CONF_DIR"
+        })
+        .map(|(key, value)| format!("{}={}", key, value))
+        // The toolchain and lint configuration out of the repository change the lints too
+        .chain(std::iter::once(
+            scout_config.invalidators.fingerprint(
+                workspace_root,
+                std::env::var_os("CLIPPY_CONF_DIR")
+                    .map(PathBuf::from)
+                    .as_deref(),
+            ),
+        ))
+        // The same pathspec restricts another run from another directory
+        .chain(opts.pathspec().patterns_from(root)?)
+        .collect();
+    Ok(Some(CacheContext {
+        cache: ResultCache::new(workspace_root),
+        commits,
+        config: scout_config.clone(),
+        context,
+    }))
+}
+
+/// The result cache, and what it keys the runs on besides their arguments.
+struct CacheContext {
+    cache: ResultCache,
+    commits: Vec<String>,
+    config: ScoutConfig,
+    /// The variables, configuration files out of the repository and pathspec of the run
+    context: Vec<String>,
+}
+
+impl CacheContext {
+    /// The key of the run with the command line `args`, the program name left out.
+    fn key(&self, args: impl Iterator<Item = String>) -> String {
+        let options: Vec<String> = cache_options(args)
+            .into_iter()
+            .chain(self.context.iter().cloned())
+            .collect();
+        cache::key(&self.commits, &self.config, &options)
+    }
+}
+
+/// The arguments of a run that change the lints it reports, keyed by the result cache.
+fn cache_options(args: impl Iterator<Item = String>) -> Vec<String> {
+    let mut options = Vec::new();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-result-cache" | "--debug-summary" => {}
+            // The plan of a run has the key of the run itself, wherever either is written
+            "--emit-plan" | "--output-file" => {
+                args.next();
+            }
+            _ if arg.starts_with("--emit-plan=") || arg.starts_with("--output-file=") => {}
+            _ => options.push(arg),
+        }
+    }
+    options
+}
+
+/// Reports the lints found in a diff, as the options ask.
+///
+/// The text output of a patch `series` lists the lints of each patch.
+#[cfg(not(tarpaulin_include))]
+fn return_lints(
+    outcome: &Outcome,
+    root: &Path,
+    workspace_root: &Path,
+    scout_config: &ScoutConfig,
+    opts: &ScoutOptions,
+    series: Option<&PatchSeries>,
+) -> Result<(), Error> {
+    with_crashes(
+        report_lints(outcome, root, workspace_root, scout_config, opts, series),
+        &outcome.crashes,
+    )
+    .and_then(|()| match &outcome.empty_diff {
+        Some(targets) if opts.fail_on_empty_diff => {
+            Err(Error::NoChanges(empty_diff::describe(targets)))
+        }
+        _ => Ok(()),
+    })
+}
+
+/// The result of a run the linter crashed during: a crash hides the lints of its member,
+/// whether the others had some or not.
+fn with_crashes(result: Result<(), Error>, crashes: &[Crash]) -> Result<(), Error> {
+    match (result, crash::error(crashes)) {
+        (Ok(()) | Err(Error::NotClean), Some(crashed)) => Err(crashed),
+        (result, _) => result,
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn report_lints(
+    outcome: &Outcome,
+    root: &Path,
+    workspace_root: &Path,
+    scout_config: &ScoutConfig,
+    opts: &ScoutOptions,
+    series: Option<&PatchSeries>,
+) -> Result<(), Error> {
+    let relevant_lints = &outcome.lints;
+    if opts.write_latest {
+        json_report(outcome, root, opts).write(json::latest_path(workspace_root))?;
+    }
+    emit_patches(relevant_lints, workspace_root, root, opts)?;
+    #[cfg(feature = "store")]
+    store_run(relevant_lints, root, workspace_root, opts)?;
+    #[cfg(feature = "http")]
+    notify_webhook(outcome, root, scout_config, opts)?;
+    let without_error = without_error(relevant_lints, root, scout_config, opts)?;
+    if opts.emit_problem_matcher {
+        return return_matched_warnings(relevant_lints, root, without_error);
+    }
+    if opts.output_format != OutputFormat::Text
+        || opts.output_file.is_some()
+        || opts.group_by.is_some()
+    {
+        return report(without_error, outcome, root, scout_config, opts);
+    }
+    if let Some(series) = series {
+        return return_series_warnings(
+            &series.split(relevant_lints, root)?,
+            &opts.text_reporter(outcome),
+            without_error,
+        );
+    }
+    return_warnings(relevant_lints, &opts.text_reporter(outcome), without_error)
+}
+
+/// Whether the run succeeds whatever the warnings: with `--without-error`,
+/// or when the `[targets]` and `[[grace]]` of the `.scout.toml` make every warning informational.
+fn without_error(
+    lints: &[Lint],
+    root: &Path,
+    config: &ScoutConfig,
+    opts: &ScoutOptions,
+) -> Result<bool, Error> {
+    if opts.without_error() || lints.is_empty() {
+        return Ok(opts.without_error());
+    }
+    let today = Date::today();
+    let mut graced = BTreeMap::new();
+    let mut fails = false;
+    for lint in lints.iter().filter(|lint| config.targets.fails(lint, root)) {
+        match (grace::ends(&config.grace, lint, today), &lint.code) {
+            (Some(ends), Some(code)) => {
+                graced.insert(code.clone(), ends);
+            }
+            _ => fails = true,
+        }
+    }
+    for (code, ends) in graced {
+        info!(
+            "[Scout] - {} is warn-only until {}, it fails the runs from then on",
+            code, ends
+        );
+    }
+    Ok(!fails)
+}
+
+/// Posts the lints to the webhook of the `.scout.toml`, if there are enough of them.
+///
+/// A failure to post is reported, but doesn't fail the run.
+#[cfg(all(feature = "http", not(tarpaulin_include)))]
+fn notify_webhook(
+    outcome: &Outcome,
+    root: &Path,
+    scout_config: &ScoutConfig,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    let reporter = WebhookReporter::new(scout_config.webhook.clone());
+    match reporter.notify(&outcome.lints, root, &json_report(outcome, root, opts)) {
+        Ok(true) => info!("[Scout] - posted the results to the webhook"),
+        Ok(false) => {}
+        Err(error) => warn!("Failed to post the results to the webhook: {}", error),
+    }
+    Ok(())
+}
+
+/// Records the run in the database of `--store`, if the options ask for it.
+#[cfg(all(feature = "store", not(tarpaulin_include)))]
+fn store_run(
+    relevant_lints: &[Lint],
+    root: &Path,
+    workspace_root: &Path,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    if let Some(path) = &opts.store {
+        let members = CargoConfig::from_manifest_path(workspace_root.join("Cargo.toml"), &[])?;
+        let run = Run::new(
+            relevant_lints,
+            root,
+            workspace_root,
+            &members.members(),
+            &opts.branch,
+        );
+        Store::new(path).record(&run)?;
+        info!(
+            "[Scout] - recorded {} lint(s) in {}",
+            run.lints.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Drops the lints the baseline defers to issues, see `cargo scout defer`.
+///
+/// The baseline is ignored when the diff changes an invalidator, like `clippy.toml`:
+/// the lints were deferred with another toolchain or lint configuration.
+#[cfg(not(tarpaulin_include))]
+fn drop_deferred(
+    vcs: &impl VCS,
+    relevant_lints: &mut Vec<Lint>,
+    root: &Path,
+    workspace_root: &Path,
+    config: &ScoutConfig,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    let baseline = &config.issues.baseline;
+    if !opts.no_diff {
+        if let Some(trigger) = config
+            .invalidators
+            .trigger(&vcs.changed_files(root)?, workspace_root)
+        {
+            info!(
+                "[Scout] - {} changed, the lints deferred in {} are reported",
+                trigger,
+                baseline.display()
+            );
+            return Ok(());
+        }
+    }
+    let dropped =
+        Baseline::from_path(workspace_root.join(baseline))?.drop_deferred(relevant_lints, root);
+    if dropped > 0 {
+        info!(
+            "[Scout] - {} lint(s) deferred to issues in {} weren't reported",
+            dropped,
+            baseline.display()
+        );
+    }
+    Ok(())
+}
+
+/// Sorts the lints by likely usefulness with the `[ranking]` weights, if the options ask for it.
+#[cfg(not(tarpaulin_include))]
+fn rank(
+    vcs: &impl VCS,
+    relevant_lints: &mut [Lint],
+    root: &Path,
+    config: &ScoutConfig,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    if opts.rank {
+        let added_sections = vcs.added_sections(root)?;
+        Ranker::new(&config.ranking, &added_sections).rank(relevant_lints);
+    }
+    Ok(())
+}
 
-    let vcs = Git::with_target(opts.branch);
-    let config = CargoConfig::from_manifest_path(opts.cargo_toml, &opts.members)?;
-    let linter = RustFmt::default();
+/// Copies the files `vcs` changes with their lints annotated, if the options ask for it.
+#[cfg(not(tarpaulin_include))]
+fn annotate_sources(
+    vcs: &impl VCS,
+    relevant_lints: &[Lint],
+    root: &Path,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    if let Some(dir) = &opts.annotate_sources {
+        let files = vcs.changed_files(root)?;
+        let written = annotate::write_annotated(relevant_lints, &files, root, dir)?;
+        info!(
+            "[Scout] - wrote {} annotated file(s) to {}",
+            written,
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Writes the fixes of the lints as patch files, if the options ask for them.
+#[cfg(not(tarpaulin_include))]
+fn emit_patches(
+    relevant_lints: &[Lint],
+    workspace_root: &Path,
+    root: &Path,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    if let Some(dir) = &opts.emit_patches {
+        let written = patch::write_patches(relevant_lints, workspace_root, root, dir)?;
+        info!("[Scout] - wrote {} patch(es) to {}", written, dir.display());
+    }
+    Ok(())
+}
 
-    let scout = Scout::new(vcs, config, linter);
-    let relevant_lints = scout.run()?;
-    return_warnings(&relevant_lints, fail_if_errors)
+#[cfg(not(tarpaulin_include))]
+fn new_scout<V: VCS, L: Linter>(
+    vcs: V,
+    config: CargoConfig,
+    mut scout_config: ScoutConfig,
+    linter: L,
+    opts: &ScoutOptions,
+) -> Result<Scout<V, CargoConfig, L>, Error> {
+    scout_config.test_code.skip |= opts.skip_tests_code;
+    let feature_matrix = if opts.feature_matrix {
+        if scout_config.feature_matrix.sets.is_empty() {
+            return Err(Error::FeatureMatrix(
+                "`--feature-matrix` needs the combinations of `feature-matrix.sets`".to_string(),
+            ));
+        }
+        Some(scout_config.feature_matrix.clone())
+    } else {
+        None
+    };
+    let dependency_graph = if opts.with_dependents {
+        Some(DependencyGraph::load(
+            config.root().join("Cargo.toml"),
+            config.root(),
+            &config.members(),
+            &opts.retry_policy(),
+        )?)
+    } else {
+        None
+    };
+    let order_graph = if opts.order == Some(MemberOrder::Deps) && dependency_graph.is_none() {
+        Some(DependencyGraph::load(
+            config.root().join("Cargo.toml"),
+            config.root(),
+            &config.members(),
+            &opts.retry_policy(),
+        )?)
+    } else {
+        None
+    };
+    let target_selection = if opts.select_targets && !opts.no_diff {
+        Some(TargetSelection::load(
+            config.root().join("Cargo.toml"),
+            config.root(),
+            &config.members(),
+            &opts.retry_policy(),
+        )?)
+    } else {
+        None
+    };
+    // The events are streamed as the run goes
+    let observer: Option<Box<dyn Observer>> = if opts.output_format == OutputFormat::JsonLines {
+        Some(Box::new(
+            JsonLinesObserver::new(opts.json_lines_out()?)
+                .with_run_id(opts.run_id.clone())
+                .with_labels(opts.labels.iter().cloned().collect()),
+        ))
+    } else {
+        None
+    };
+    let post_processors = opts.post_processors(&scout_config.path_map, config.root());
+    let policy = Policy::load(&scout_config, config.root())?;
+    let mut scout = Scout::new(vcs, config, linter);
+    scout
+        .set_member_overrides(scout_config.members)
+        .set_no_diff(opts.no_diff)
+        .set_workspace(opts.workspace)
+        .set_jobs(opts.job_split().0)
+        .set_jobs_per_target_dir(opts.jobs_per_target_dir)
+        .set_pipelined(opts.pipeline)
+        .set_generated(scout_config.generated)
+        .set_build_outputs(scout_config.build_outputs)
+        .set_padding(scout_config.padding)
+        .set_dirty(scout_config.dirty)
+        .set_relevance(scout_config.relevance)
+        .set_invalidators(scout_config.invalidators)
+        .set_dependency_graph(dependency_graph)
+        .set_dependents(scout_config.dependents)
+        .set_test_code(scout_config.test_code)
+        .set_policy(policy)
+        .set_observer(observer)
+        .set_post_processors(post_processors)
+        .set_pathspec(opts.pathspec())
+        .set_match_strategy(opts.match_strategy)
+        .set_diff_gate(DiffGate {
+            max_lines: opts.max_diff_lines,
+            max_files: opts.max_diff_files,
+        })
+        .set_budget(opts.time_budget.map(Duration::from_secs))
+        .set_member_order(opts.order)
+        .set_order_graph(order_graph)
+        .set_target_selection(target_selection)
+        .set_shard(opts.shard)
+        .set_feature_matrix(feature_matrix);
+    Ok(scout)
 }
 
-fn return_warnings(lints: &[Lint], without_error: bool) -> Result<(), Error> {
+/// Runs scout, the explanations are only computed with `--explain-filtering`.
+#[cfg(not(tarpaulin_include))]
+fn scout(
+    vcs: impl VCS,
+    config: CargoConfig,
+    scout_config: ScoutConfig,
+    linter: impl Linter,
+    opts: &ScoutOptions,
+) -> Result<Outcome, Error> {
+    let scout = new_scout(vcs, config, scout_config, linter, opts)?;
+    let outcome = if opts.explain_filtering {
+        scout.explain()?
+    } else {
+        scout.run()?
+    };
+    if outcome.retries > 0 {
+        info!("Retried {} flaky cargo invocation(s)", outcome.retries);
+    }
+    if !outcome.skipped.is_empty() {
+        warn!(
+            "[Scout] - {}: {}",
+            budget::skipped_marker(outcome.skipped.len()),
+            outcome.skipped.join(", ")
+        );
+    }
+    for crash in &outcome.crashes {
+        warn!(
+            "[Scout] - the linter crashed on {}, its lints are missing",
+            crash.member
+        );
+    }
+    Ok(outcome)
+}
+
+#[cfg(not(tarpaulin_include))]
+fn dry_run(
+    config: CargoConfig,
+    scout_config: &ScoutConfig,
+    linter: impl Linter,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    match &opts.patches {
+        Some(patches) => plan_run(
+            opts.patch_series(patches)?,
+            config,
+            scout_config,
+            linter,
+            opts,
+        ),
+        None => plan_run(opts.git(), config, scout_config, linter, opts),
+    }
+}
+
+/// Prints the plan of the run with the diff of `vcs`, as lines or as `--emit-plan` asks.
+#[cfg(not(tarpaulin_include))]
+fn plan_run(
+    vcs: impl VCS,
+    config: CargoConfig,
+    scout_config: &ScoutConfig,
+    linter: impl Linter,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    let workspace_root = config.root().clone();
+    let plan = new_scout(&vcs, config, scout_config.clone(), linter, opts)?.plan()?;
+    let Some(PlanFormat::Json) = opts.emit_plan else {
+        for line in plan_lines(&plan) {
+            info!(line);
+        }
+        return Ok(());
+    };
+    let root = vcs.root(std::fs::canonicalize(std::env::current_dir()?)?)?;
+    let mut json = JsonPlan::new(&plan, &root);
+    // The plan isn't summarized
+    let summary = &mut DebugSummary::default();
+    if let Some(context) = result_cache(&vcs, &root, &workspace_root, scout_config, opts, summary)?
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let key = context.key(args.iter().cloned());
+        let hit = context.cache.get(&key).is_some();
+        // Each agent runs with the same arguments, and the member it lints
+        json = json
+            .with_result_cache(key, hit)
+            .with_member_caches(|member| {
+                let key = context.key(
+                    args.iter()
+                        .cloned()
+                        .chain(["--member".to_string(), member.to_string()]),
+                );
+                let hit = context.cache.get(&key).is_some();
+                (key, hit)
+            });
+    }
+    let rendered = json.to_json()?;
+    match &opts.output_file {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
+
+/// Describes a plan, one line at a time.
+fn plan_lines(plan: &Plan) -> Vec<String> {
+    let mut lines = Vec::new();
+    match &plan.files {
+        Some(files) => {
+            lines.push("Diff targets:".to_string());
+            lines.extend(plan.targets.iter().map(|t| format!("  {}", t)));
+            lines.push(format!("Changed files ({}):", files.len()));
+            lines.extend(files.iter().map(|f| format!("  {}", f)));
+        }
+        None => lines.push("No diff, every warning is reported".to_string()),
+    }
+    lines.push(format!("Members ({}):", plan.members.len()));
+    for (member, command) in plan.members.iter().zip(&plan.commands) {
+        match command {
+            Some(command) => lines.push(format!("  {}: {}", member, command)),
+            None => lines.push(format!("  {}", member)),
+        }
+    }
+    lines
+}
+
+fn return_series_warnings(
+    patches: &[(&Patch, Vec<Lint>)],
+    reporter: &TextReporter,
+    without_error: bool,
+) -> Result<(), Error> {
+    for (patch, lints) in patches {
+        info!("[{}]", patch.path().display());
+        if lints.is_empty() {
+            success!("No issues in this patch");
+        } else {
+            display_warnings(lints, reporter);
+        }
+    }
+    let lints: Vec<Lint> = patches
+        .iter()
+        .flat_map(|(_, lints)| lints.iter().cloned())
+        .collect();
+    if lints.is_empty() {
+        success!("No issues in your patches, you're good to go!");
+    }
+    return_status(&lints, without_error)
+}
+
+fn return_warnings(
+    lints: &[Lint],
+    reporter: &TextReporter,
+    without_error: bool,
+) -> Result<(), Error> {
     if lints.is_empty() {
         success!("No issues in your diff, you're good to go!");
-        Ok(())
     } else {
-        display_warnings(&lints);
-        if without_error {
-            Ok(())
-        } else {
-            Err(Error::NotClean)
+        display_warnings(lints, reporter);
+    }
+    return_status(lints, without_error)
+}
+
+/// Writes the warnings in the output format, to the output file if there is one.
+fn json_report(outcome: &Outcome, root: &Path, opts: &ScoutOptions) -> JsonReport {
+    JsonReport::with_features(&outcome.lints, &outcome.features, root)
+        .with_explanations(&outcome.explanations, root)
+        .with_status(outcome.status())
+        .with_retries(outcome.retries)
+        .with_skipped(outcome.skipped.clone())
+        .with_crashed(outcome.crashes.clone())
+        .with_run_id(opts.run_id.clone())
+        .with_labels(opts.labels.iter().cloned().collect())
+}
+
+fn report(
+    without_error: bool,
+    outcome: &Outcome,
+    root: &Path,
+    scout_config: &ScoutConfig,
+    opts: &ScoutOptions,
+) -> Result<(), Error> {
+    let lints = &outcome.lints;
+    let rendered = match opts.output_format {
+        OutputFormat::Text => match opts.group_by {
+            Some(by) => opts
+                .text_reporter(outcome)
+                .render_groups(&group::group(lints, by, root)),
+            None => opts.text_reporter(outcome).render(lints),
+        },
+        OutputFormat::Json => json_report(outcome, root, opts).to_json()?,
+        // The observer already streamed the rest of the events
+        OutputFormat::JsonLines => {
+            write!(
+                opts.json_lines_out()?,
+                "{}",
+                json_lines::render_lints(
+                    lints,
+                    &outcome.features,
+                    root,
+                    opts.labels.iter().cloned().collect()
+                )
+            )?;
+            return return_status(lints, without_error);
         }
+        OutputFormat::Markdown => MarkdownReporter::new(scout_config.markdown.clone())
+            .with_run_id(opts.run_id.clone())
+            .with_skipped(outcome.skipped.len())
+            .render(lints, root),
+        // Relative to the directory the editor runs scout from
+        OutputFormat::Quickfix => quickfix::render(
+            lints,
+            &std::env::current_dir().unwrap_or_else(|_| root.into()),
+        ),
+    };
+    match &opts.output_file {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => println!("{}", rendered.trim_end()),
     }
+    return_status(lints, without_error)
 }
 
-fn display_warnings(warnings: &[Lint]) {
-    for w in warnings {
-        for l in w.message.split('\n') {
-            warn!(l);
+/// Prints the warnings for the problem matcher, while it is registered.
+fn return_matched_warnings(lints: &[Lint], root: &Path, without_error: bool) -> Result<(), Error> {
+    {
+        let _matcher = ProblemMatcher::register(std::env::temp_dir())?;
+        for lint in lints {
+            println!("{}", problem_matcher::format_lint(lint, root));
         }
     }
+    return_status(lints, without_error)
+}
 
-    if warnings.len() == 1 {
-        warn!("Cargo scout found a warning");
+fn return_status(lints: &[Lint], without_error: bool) -> Result<(), Error> {
+    if lints.is_empty() || without_error {
+        Ok(())
     } else {
-        warn!("Cargo scout found {} warnings", warnings.len());
+        Err(Error::NotClean)
     }
 }
 
+fn display_warnings(warnings: &[Lint], reporter: &TextReporter) {
+    for w in warnings {
+        for l in reporter.lint_lines(w) {
+            warn!(l);
+        }
+    }
+
+    warn!(text::summary(warnings.len()));
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{return_warnings, Lint};
+    use super::{
+        cache_options, job_split, plan_lines, report_diff_lines, return_diagnostics,
+        return_matched_warnings, return_report_diff, return_series_warnings, return_warnings,
+        run_merge, with_crashes, Crash, Diagnostic, Error, JobLimit, JsonLint, Lint, MergeOptions,
+        Patch, Plan, ReportDiff, ScoutOptions, Severity, TextReporter,
+    };
+    use cargo_scout_lib::linter::LinterCommand;
     use cargo_scout_lib::linter::Location;
+    use structopt::StructOpt;
     #[test]
     fn test_return_status_with_lints() {
         let lints = vec![Lint {
             message: String::new(),
+            code: None,
             location: Location {
                 path: String::new(),
                 lines: [0, 0],
+                ..Location::default()
             },
+            suggestions: Vec::new(),
         }];
 
-        assert!(return_warnings(&lints, true).is_ok());
-        assert!(return_warnings(&lints, false).is_err());
+        assert!(return_warnings(&lints, &TextReporter::default(), true).is_ok());
+        assert!(return_warnings(&lints, &TextReporter::default(), false).is_err());
+    }
+
+    #[test]
+    fn test_with_crashes() {
+        let crashes = [Crash {
+            member: "member1".to_string(),
+            message: "error: internal compiler error".to_string(),
+        }];
+        assert!(with_crashes(Ok(()), &[]).is_ok());
+        assert!(matches!(
+            with_crashes(Err(Error::NotClean), &crashes),
+            Err(Error::LinterCrashed(members, _)) if members == "member1"
+        ));
+        assert!(matches!(
+            with_crashes(Ok(()), &crashes),
+            Err(Error::LinterCrashed(..))
+        ));
+        // Errors which aren't about the lints are kept
+        assert!(matches!(
+            with_crashes(Err(Error::NoChanges(String::new())), &crashes),
+            Err(Error::NoChanges(_))
+        ));
+    }
+
+    #[test]
+    fn test_return_series_status() {
+        let first = Patch::from_path("0001-first.patch");
+        let second = Patch::from_path("0002-second.patch");
+        let lints = vec![Lint::default()];
+
+        let clean = vec![(&first, Vec::new()), (&second, Vec::new())];
+        assert!(return_series_warnings(&clean, &TextReporter::default(), false).is_ok());
+
+        let dirty = vec![(&first, Vec::new()), (&second, lints)];
+        assert!(return_series_warnings(&dirty, &TextReporter::default(), true).is_ok());
+        assert!(return_series_warnings(&dirty, &TextReporter::default(), false).is_err());
     }
 
     #[test]
     fn test_return_status_without_existing_lints() {
         let lints: Vec<Lint> = Vec::new();
 
-        assert!(return_warnings(&lints, true).is_ok());
-        assert!(return_warnings(&lints, false).is_ok());
+        assert!(return_warnings(&lints, &TextReporter::default(), true).is_ok());
+        assert!(return_warnings(&lints, &TextReporter::default(), false).is_ok());
+    }
+
+    #[test]
+    fn test_return_matched_status() {
+        let lints = vec![Lint::default()];
+        let root = std::path::Path::new("/");
+
+        assert!(return_matched_warnings(&lints, root, true).is_ok());
+        assert!(return_matched_warnings(&lints, root, false).is_err());
+        assert!(return_matched_warnings(&[], root, false).is_ok());
+    }
+
+    #[test]
+    fn test_return_diagnostics() {
+        let path = std::path::Path::new(".scout.toml");
+        let mut diagnostic = Diagnostic {
+            severity: Severity::Warning,
+            line: 1,
+            column: 1,
+            message: String::new(),
+        };
+        assert!(return_diagnostics(path, &[]).is_ok());
+        assert!(return_diagnostics(path, &[diagnostic.clone()]).is_ok());
+        diagnostic.severity = Severity::Error;
+        assert!(return_diagnostics(path, &[diagnostic]).is_err());
+    }
+
+    #[test]
+    fn test_plan_lines() {
+        let plan = Plan {
+            targets: vec!["HEAD (1234)".to_string()],
+            files: Some(vec!["/repo/src/lib.rs".to_string()]),
+            members: vec!["foo".to_string(), "bar".to_string()],
+            commands: vec![
+                Some(LinterCommand {
+                    working_dir: "/repo/foo".into(),
+                    envs: Vec::new(),
+                    program: "cargo".to_string(),
+                    args: vec!["clippy".to_string()],
+                }),
+                None,
+            ],
+        };
+        assert_eq!(
+            vec![
+                "Diff targets:",
+                "  HEAD (1234)",
+                "Changed files (1):",
+                "  /repo/src/lib.rs",
+                "Members (2):",
+                "  foo: (in /repo/foo) cargo clippy",
+                "  bar",
+            ],
+            plan_lines(&plan)
+        );
+    }
+
+    #[test]
+    fn test_return_report_diff() {
+        let lint = |message: &str| JsonLint {
+            path: "src/lib.rs".to_string(),
+            lines: [3, 4],
+            columns: [0, 0],
+            bytes: None,
+            snippet: Vec::new(),
+            code: None,
+            message: message.to_string(),
+            fingerprint: "0123456789abcdef".to_string(),
+            item: None,
+            features: None,
+            labels: std::collections::BTreeMap::new(),
+        };
+        let diff = ReportDiff {
+            added: vec![lint("unused variable\n  --> src/lib.rs:3:4")],
+            removed: vec![lint("needless borrow")],
+        };
+        assert_eq!(
+            vec![
+                "+ src/lib.rs:3: unused variable",
+                "- src/lib.rs:3: needless borrow"
+            ],
+            report_diff_lines(&diff)
+        );
+        assert!(return_report_diff(&diff).is_err());
+        assert!(return_report_diff(&ReportDiff {
+            added: Vec::new(),
+            removed: diff.removed,
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_job_split() {
+        assert_eq!((2, Some(4)), job_split(Some(JobLimit::Jobs(8)), 2));
+        assert_eq!((2, Some(1)), job_split(Some(JobLimit::Jobs(2)), 4));
+        assert_eq!((4, None), job_split(Some(JobLimit::Jobserver), 4));
+        assert_eq!((1, None), job_split(None, 1));
+    }
+
+    #[test]
+    fn test_merge_newer_report() {
+        let path = std::env::temp_dir().join(format!(
+            "cargo-scout-merge-test-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, "{\"version\": 99, \"lints\": []}").unwrap();
+        let merged = run_merge(&MergeOptions {
+            reports: vec![path.clone()],
+            output: None,
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(merged, Err(Error::Merge(message)) if message.contains("version 99")));
+    }
+
+    #[test]
+    fn test_cache_options() {
+        let args = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            args(&["lint", "-b", "main"]),
+            cache_options(
+                args(&[
+                    "lint",
+                    "--emit-plan",
+                    "json",
+                    "-b",
+                    "main",
+                    "--debug-summary",
+                    "--emit-plan=json",
+                    "--output-file",
+                    "plan.json"
+                ])
+                .into_iter()
+            )
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let opts = ScoutOptions::from_iter(&["scout"]);
+        assert_eq!(vec!["Overlap match"], opts.filters());
+        let opts = ScoutOptions::from_iter(&[
+            "scout",
+            "--no-diff",
+            "--member",
+            "core",
+            "--max-diff-lines",
+            "500",
+            "--order",
+            "alpha",
+        ]);
+        assert_eq!(
+            vec![
+                "Overlap match",
+                "members core",
+                "no diff",
+                "at most 500 diff lines",
+                "Alpha order"
+            ],
+            opts.filters()
+        );
     }
 }
@@ -0,0 +1,34 @@
+#![no_main]
+use cargo_scout_lib::linter::{Lint, Location};
+use cargo_scout_lib::scout::lints_from_diff;
+use cargo_scout_lib::vcs::patch::Parser;
+use libfuzzer_sys::fuzz_target;
+
+// Intersects the sections of an arbitrary diff with lints spanning
+// arbitrary lines of the same files
+fuzz_target!(|data: &[u8]| {
+    let diff = String::from_utf8_lossy(data);
+    let sections = Parser::new(&diff).parse().sections("/repo");
+    let lints: Vec<Lint> = sections
+        .iter()
+        .zip(data.chunks(8))
+        .map(|(section, chunk)| {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            let start = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let end = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            Lint {
+                message: String::new(),
+                code: None,
+                location: Location {
+                    path: section.file_name.clone(),
+                    lines: [start.min(end), start.max(end)],
+                    ..Location::default()
+                },
+                suggestions: Vec::new(),
+            }
+        })
+        .collect();
+    let in_diff = lints_from_diff(&lints, &sections);
+    assert!(in_diff.len() <= lints.len());
+});
@@ -0,0 +1,15 @@
+#![no_main]
+use cargo_scout_lib::vcs::patch::Parser;
+use libfuzzer_sys::fuzz_target;
+
+// The diff parser must never panic, whatever the diff looks like
+fuzz_target!(|data: &[u8]| {
+    let diff = String::from_utf8_lossy(data);
+    let parsed = Parser::new(&diff).parse();
+    let _ = parsed.sections("/repo");
+    // The strict parse fails exactly when the lenient one skipped something
+    assert_eq!(
+        parsed.errors.is_empty(),
+        Parser::new(&diff).files().is_ok()
+    );
+});